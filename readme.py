@@ -62,18 +62,50 @@ awsx is a command-line utility is meant as an AWS CLI extension, providing featu
 """
 
 SUBCOMMANDS = [
+    "blue-green",
+    "clone-stack",
+    "compare-stacks",
+    "copy-db-snapshot",
+    "create-cloudfront-invalidation",
+    "create-db-snapshot",
     "create-stack",
+    "delete-change-set",
+    "deployment-changelog",
+    "deployment-report",
+    "deregister-targets",
+    "describe-change-set",
+    "execute-change-set",
     "find-amis-inuse",
     "find-auto-scaling-group",
     "find-cloudfront-distribution",
     "find-db-cluster-snapshot",
     "find-db-snapshot",
     "find-target-group",
+    "get-target-health",
+    "iam-policy",
     "identify-new-parameters",
+    "lint-template",
+    "move-resource",
     "override-parameters",
+    "package",
+    "promote-stack",
+    "prune-templates",
+    "register-targets",
+    "resume-auto-scaling-processes",
+    "self-update",
+    "set-auto-scaling-group-capacity",
+    "share-db-snapshot",
+    "shift-route53-weights",
+    "show-stack-parameters",
+    "start-instance-refresh",
+    "suspend-auto-scaling-processes",
+    "template-graph",
     "update-deployed-template",
     "verify-changes-compatible",
     "verify-parameter-file",
+    "wait-for-cloudfront-distribution",
+    "wait-for-db-snapshot",
+    "who-changed-stack",
 ]
 
 
@@ -0,0 +1,362 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Opt-in audit log of all changes awsx performs on stacks and other resources.
+//!
+//! Every mutating action, i.e. creating, executing or deleting a change set, and changing any other
+//! resource, e.g. sharing a DB snapshot or registering targets, is recorded as a single line of
+//! JSON appended to the audit log. The entry, including the deployment metadata, is redacted the
+//! same way as all other output. The same event can be published to an SNS topic, for downstream
+//! automation to subscribe to.
+
+use awsx::{
+    deployment::DeploymentMetadata,
+    endpoint,
+    error::Error,
+    http,
+    parameter::Parameters,
+    s3::ChecksumAlgorithm,
+    sns::{self, SnsClient},
+    template::Template,
+};
+use chrono::{SecondsFormat, Utc};
+use rusoto_core::Region;
+use rusoto_sts::{GetCallerIdentityRequest, Sts, StsClient};
+use serde::Serialize;
+use serde_json::json;
+use std::{
+    convert::TryFrom,
+    fs::OpenOptions,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use crate::{redact, AwsxProvider, Opt as GlobalOpt};
+
+/// The caller ARN recorded if the identity of the caller could not be retrieved.
+const UNKNOWN_CALLER_ARN: &str = "unknown";
+
+static AUDIT_LOG: Mutex<Option<AuditLog>> = Mutex::new(None);
+
+struct AuditLog {
+    /// The file the events are appended to, if any.
+    path: Option<PathBuf>,
+    /// The SNS topic the events are published to, if any.
+    sns_topic_arn: Option<String>,
+    command: String,
+    provider: AwsxProvider,
+    region: Region,
+    /// The ARN of the caller, which is only retrieved once the first entry is recorded.
+    caller_arn: Option<String>,
+    /// The hash algorithm template checksums are generated with.
+    checksum_algorithm: ChecksumAlgorithm,
+    /// The parameter the deployment metadata is stored in.
+    deployment_metadata_parameter: String,
+}
+
+/// A mutating action to record in the audit log.
+#[derive(Debug, Serialize)]
+pub(crate) struct AuditEntry<'a> {
+    action: &'static str,
+    stack_name: Option<&'a str>,
+    /// Identifier of the resource other than a stack the action changes, if known.
+    resource: Option<String>,
+    change_set_name: Option<&'a str>,
+    template_checksum: Option<String>,
+    parameters: Option<serde_json::Value>,
+    deployment_metadata: Option<DeploymentMetadata>,
+    /// The result of the action, if it succeeded.
+    details: Option<serde_json::Value>,
+}
+
+impl<'a> AuditEntry<'a> {
+    pub(crate) fn new(action: &'static str, stack_name: &'a str) -> Self {
+        AuditEntry {
+            action,
+            stack_name: Some(stack_name),
+            resource: None,
+            change_set_name: None,
+            template_checksum: None,
+            parameters: None,
+            deployment_metadata: None,
+            details: None,
+        }
+    }
+
+    /// Create the entry of an action changing a resource other than a stack.
+    ///
+    /// The identifier of the resource can be unknown, e.g. if it was selected by its tags, but could
+    /// not be found.
+    pub(crate) fn for_resource(action: &'static str, resource: Option<String>) -> Self {
+        AuditEntry {
+            resource,
+            stack_name: None,
+            ..AuditEntry::new(action, "")
+        }
+    }
+
+    /// Record the result of the action, if it succeeded.
+    pub(crate) fn with_details<T: Serialize>(mut self, details: Option<&T>) -> Self {
+        self.details = details
+            .and_then(|details| serde_json::to_value(details).ok())
+            .map(redact::redact_json);
+        self
+    }
+
+    pub(crate) fn with_change_set_name(mut self, change_set_name: &'a str) -> Self {
+        self.change_set_name = Some(change_set_name);
+        self
+    }
+
+    pub(crate) fn with_template(mut self, template: &Template) -> Self {
+        let checksum_algorithm = AUDIT_LOG
+            .lock()
+            .expect("audit log lock poisoned")
+            .as_ref()
+            .map(|audit_log| audit_log.checksum_algorithm)
+            .unwrap_or_default();
+        self.template_checksum = template.checksum_hex(checksum_algorithm).ok();
+        self
+    }
+
+    pub(crate) fn with_parameters(mut self, parameters: &Parameters) -> Self {
+        self.parameters = serde_json::to_value(parameters)
+            .ok()
+            .map(redact::redact_json);
+        self.deployment_metadata = AUDIT_LOG
+            .lock()
+            .expect("audit log lock poisoned")
+            .as_ref()
+            .and_then(|audit_log| parameters.get(&audit_log.deployment_metadata_parameter))
+            .cloned()
+            .and_then(|parameter| DeploymentMetadata::try_from(parameter).ok());
+        self
+    }
+}
+
+/// Enable the audit log if a path or an SNS topic was configured, recording the subcommand that is
+/// run.
+pub(crate) fn configure(global_opt: &GlobalOpt, command: &str, provider: &AwsxProvider) {
+    if global_opt.audit_log_path.is_some() || global_opt.notify_sns_topic_arn.is_some() {
+        *AUDIT_LOG.lock().expect("audit log lock poisoned") = Some(AuditLog {
+            path: global_opt.audit_log_path.as_ref().map(PathBuf::from),
+            sns_topic_arn: global_opt.notify_sns_topic_arn.clone(),
+            command: command.to_owned(),
+            provider: provider.clone(),
+            region: global_opt.region(),
+            caller_arn: None,
+            checksum_algorithm: global_opt.checksum_algorithm,
+            deployment_metadata_parameter: global_opt.deployment_metadata_parameter.clone(),
+        });
+    }
+}
+
+/// Publish the event to the SNS topic, in the region of the topic.
+async fn publish(
+    topic_arn: &str,
+    provider: AwsxProvider,
+    region: Region,
+    event: &serde_json::Value,
+    entry: &AuditEntry<'_>,
+    succeeded: bool,
+) -> Result<(), Error> {
+    let sns = SnsClient::new_with(
+        http::client()?,
+        provider,
+        endpoint::region_for("sns", sns::topic_region(topic_arn).unwrap_or(region)),
+    );
+    let mut attributes = vec![("action", entry.action)];
+    // Message attributes can not be empty, so unknown resources are left out.
+    if let Some(stack_name) = entry.stack_name {
+        attributes.push(("stack_name", stack_name));
+    }
+    if let Some(resource) = entry.resource.as_deref() {
+        attributes.push(("resource", resource));
+    }
+    attributes.push(("succeeded", if succeeded { "true" } else { "false" }));
+    sns.publish(topic_arn, &event.to_string(), &attributes)
+        .await?;
+    Ok(())
+}
+
+/// Create the redacted event recording the action and its outcome.
+fn event<T>(
+    command: &str,
+    caller_arn: &str,
+    entry: &AuditEntry<'_>,
+    result: &Result<T, Error>,
+) -> serde_json::Value {
+    redact::redact_json(json!({
+        "timestamp": Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+        "awsx_version": env!("CARGO_PKG_VERSION"),
+        "command": command,
+        "caller_arn": caller_arn,
+        "action": entry.action,
+        "stack_name": entry.stack_name,
+        "resource": entry.resource,
+        "change_set_name": entry.change_set_name,
+        "template_checksum": entry.template_checksum,
+        "parameters": entry.parameters,
+        "deployment_metadata": entry.deployment_metadata,
+        "details": entry.details,
+        "succeeded": result.is_ok(),
+        "error": result
+            .as_ref()
+            .err()
+            .map(|error| error.to_string()),
+    }))
+}
+
+/// Append the event as a single line to the audit log, creating the file if it does not exist.
+fn append(path: &Path, event: &serde_json::Value) -> Result<(), Error> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", event)?;
+    Ok(())
+}
+
+/// Record the action and its outcome in the audit log and publish it to the SNS topic, if either is
+/// enabled.
+///
+/// If the entry cannot be written to the audit log, an error is returned, since an action missing
+/// from the audit log would defeat its purpose. Failing to publish the event only prints a
+/// warning, since the action has already been performed.
+pub(crate) async fn record<T>(
+    entry: AuditEntry<'_>,
+    result: &Result<T, Error>,
+) -> Result<(), Error> {
+    let (caller_arn, provider, region) = {
+        let audit_log = AUDIT_LOG.lock().expect("audit log lock poisoned");
+        match &*audit_log {
+            Some(audit_log) => (
+                audit_log.caller_arn.clone(),
+                audit_log.provider.clone(),
+                audit_log.region.clone(),
+            ),
+            None => return Ok(()),
+        }
+    };
+    let caller_arn = match caller_arn {
+        Some(caller_arn) => caller_arn,
+        None => {
+            let sts = StsClient::new_with(
+                http::client()?,
+                provider.clone(),
+                endpoint::region_for("sts", region.clone()),
+            );
+            // The action has already been performed, so it must not fail because of the caller.
+            match sts.get_caller_identity(GetCallerIdentityRequest {}).await {
+                Ok(identity) => {
+                    let caller_arn = identity.arn.unwrap_or_default();
+                    if let Some(audit_log) =
+                        &mut *AUDIT_LOG.lock().expect("audit log lock poisoned")
+                    {
+                        audit_log.caller_arn = Some(caller_arn.clone());
+                    }
+                    caller_arn
+                }
+                Err(e) => {
+                    eprintln!(
+                        "WARNING: failed to retrieve the identity of the caller for the audit log: {}",
+                        e,
+                    );
+                    UNKNOWN_CALLER_ARN.to_owned()
+                }
+            }
+        }
+    };
+
+    let (path, sns_topic_arn, line) = {
+        let audit_log = AUDIT_LOG.lock().expect("audit log lock poisoned");
+        let audit_log = match &*audit_log {
+            Some(audit_log) => audit_log,
+            None => return Ok(()),
+        };
+        let line = event(&audit_log.command, &caller_arn, &entry, result);
+        (
+            audit_log.path.clone(),
+            audit_log.sns_topic_arn.clone(),
+            line,
+        )
+    };
+
+    if let Some(path) = path {
+        append(&path, &line)?;
+    }
+    if let Some(topic_arn) = sns_topic_arn {
+        if let Err(e) = publish(&topic_arn, provider, region, &line, &entry, result.is_ok()).await {
+            eprintln!(
+                "WARNING: failed to publish the event to the SNS topic '{}': {}",
+                topic_arn, e,
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn events_are_appended_as_lines_of_json() {
+        let path = std::env::temp_dir().join(format!("awsx-audit-{}.log", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let entry = AuditEntry::new("execute_change_set", "app").with_change_set_name("awsx-1");
+        let succeeded = event(
+            "update-stack",
+            "arn:aws:iam::123456789012:user/deployer",
+            &entry,
+            &Ok::<_, Error>(()),
+        );
+        let failed = event(
+            "delete-stack",
+            UNKNOWN_CALLER_ARN,
+            &AuditEntry::for_resource("share_db_snapshot", None),
+            &Err::<(), _>(Error::HookFailed("exit status 1".to_owned())),
+        );
+        append(&path, &succeeded).unwrap();
+        append(&path, &failed).unwrap();
+
+        let contents = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let lines = contents
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(vec![succeeded.clone(), failed.clone()], lines);
+
+        assert_eq!("update-stack", succeeded["command"]);
+        assert_eq!(
+            "arn:aws:iam::123456789012:user/deployer",
+            succeeded["caller_arn"]
+        );
+        assert_eq!("execute_change_set", succeeded["action"]);
+        assert_eq!("app", succeeded["stack_name"]);
+        assert_eq!("awsx-1", succeeded["change_set_name"]);
+        assert_eq!(true, succeeded["succeeded"]);
+        assert!(succeeded["error"].is_null());
+        assert_eq!(env!("CARGO_PKG_VERSION"), succeeded["awsx_version"]);
+
+        assert_eq!(UNKNOWN_CALLER_ARN, failed["caller_arn"]);
+        assert!(failed["stack_name"].is_null());
+        assert!(failed["resource"].is_null());
+        assert_eq!(false, failed["succeeded"]);
+        assert_eq!("hook failed: exit status 1", failed["error"]);
+    }
+}
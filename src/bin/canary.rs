@@ -0,0 +1,471 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+//! Monitoring of CloudWatch alarms after a change set has been executed.
+//!
+//! During the bake period following a successful execution, the alarms are polled and every state
+//! transition is reported. If any alarm fires, the canary fails, which fails the command too, so
+//! that pipelines can gate on the outcome. Optionally, the stack is rolled back to the template and
+//! parameters recorded in its deployment metadata before the execution.
+
+use awsx::{
+    changeset::{is_no_changes_reason, ChangeSet},
+    cloudwatch::CloudWatchClient,
+    commands::{
+        cloudformation::change_set::RollbackReport,
+        cloudwatch::{bake_alarms, verify_alarms, CanaryReport},
+    },
+    deployment::{DeploymentMetadata, DeploymentMetadataApplied},
+    endpoint,
+    error::Error,
+    http,
+    parameter::{Parameter, Parameters},
+    stack::Stack,
+};
+use itertools::Itertools;
+use rusoto_cloudformation::{CloudFormation, CreateChangeSetInput};
+use std::{convert::TryFrom, time::Duration};
+use structopt::StructOpt;
+
+use crate::{
+    audit::{self, AuditEntry},
+    command::execute_change_set::verify_protected_resources,
+    util::deployment_metadata,
+    AwsxProvider, Opt as GlobalOpt,
+};
+
+/// Options to monitor CloudWatch alarms after executing a change set.
+#[derive(Debug, StructOpt)]
+pub(crate) struct CanaryOpt {
+    #[structopt(
+        long = "canary",
+        requires = "canary-alarm-names",
+        help = "Monitor CloudWatch alarms after the execution",
+        long_help = "Once the execution has completed successfully, monitor the CloudWatch alarms \
+                     given by --canary-alarm-names for --canary-bake-time seconds, reporting \
+                     their state transitions. If any alarm is in or goes into the `ALARM` state, \
+                     the command fails. Implies --wait."
+    )]
+    pub(crate) canary: bool,
+    #[structopt(
+        long = "canary-alarm-names",
+        help = "Names of the CloudWatch alarms to monitor with --canary",
+        long_help = "Names of the CloudWatch alarms to monitor with --canary, separated by \
+                     spaces. Both metric and composite alarms are supported. All alarms have to \
+                     exist, which is verified before the change set is executed."
+    )]
+    pub(crate) canary_alarm_names: Vec<String>,
+    #[structopt(
+        long = "canary-bake-time",
+        default_value = "300",
+        help = "Number of seconds to monitor the alarms for with --canary"
+    )]
+    pub(crate) canary_bake_time: u64,
+    #[structopt(
+        long = "canary-rollback",
+        requires = "canary",
+        help = "Roll the stack back if the canary fails",
+        long_help = "Roll the stack back if the canary fails, by creating and executing a change \
+                     set that re-applies the template and parameters recorded in the deployment \
+                     metadata of the stack before the execution. Only deployments whose template \
+                     was uploaded to S3 can be rolled back to, and only if the change set keeps \
+                     the values of all `NoEcho` parameters, which can not be restored otherwise. \
+                     Stacks that are created by the change set are not rolled back."
+    )]
+    pub(crate) canary_rollback: bool,
+}
+
+/// The template and parameters recorded for the deployment before the execution, to roll back to.
+#[derive(Debug, PartialEq)]
+pub(crate) struct RollbackSnapshot {
+    template_url: String,
+    /// The recorded parameters, including the deployment metadata itself. `NoEcho` parameters keep
+    /// their previous value, which the change set has to keep as well.
+    parameters: Parameters,
+}
+
+impl RollbackSnapshot {
+    /// Determine what to roll back to from the deployment metadata deployed before the execution,
+    /// and the record of what the change set applies.
+    ///
+    /// Returns the reason if the stack can not be rolled back.
+    fn from_metadata(
+        metadata_parameter: Option<Parameter>,
+        applying: Option<&DeploymentMetadataApplied>,
+    ) -> Result<RollbackSnapshot, String> {
+        let metadata_parameter = metadata_parameter
+            .ok_or_else(|| "it does not have a deployment-metadata parameter".to_owned())?;
+        let applied = DeploymentMetadata::try_from(metadata_parameter.clone())
+            .ok()
+            .and_then(|metadata| metadata.applied)
+            .ok_or_else(|| {
+                "its deployment metadata does not record the template and parameters of the \
+                 deployment"
+                    .to_owned()
+            })?;
+        let template_url = applied.template_url.ok_or_else(|| {
+            "the template of the deployment was not uploaded to S3, specify --s3-bucket-name when \
+             deploying"
+                .to_owned()
+        })?;
+
+        let no_echo = applied
+            .parameters
+            .iter()
+            .filter(|(_, value)| value.is_none())
+            .map(|(key, _)| key.as_str())
+            .collect::<Vec<_>>();
+        if !no_echo.is_empty() {
+            let changed = match applying {
+                Some(applying) => no_echo
+                    .iter()
+                    .filter(|key| {
+                        applying
+                            .changed_no_echo_parameters
+                            .iter()
+                            .any(|k| k == *key)
+                    })
+                    .collect::<Vec<_>>(),
+                None => no_echo.iter().collect(),
+            };
+            if !changed.is_empty() {
+                return Err(format!(
+                    "the change set may change the NoEcho or sensitive parameters {}, whose values \
+                     can not be restored",
+                    changed.iter().join(", ")
+                ));
+            }
+        }
+
+        let mut parameters = applied
+            .parameters
+            .into_iter()
+            .map(|(key, value)| match value {
+                Some(value) => Parameter::WithValue { key, value },
+                None => Parameter::PreviousValue { key },
+            })
+            .collect::<Vec<_>>();
+        parameters.push(metadata_parameter);
+        Ok(RollbackSnapshot {
+            template_url,
+            parameters: parameters.into(),
+        })
+    }
+
+    /// Create the input for the change set re-applying the snapshot.
+    fn change_set_input(
+        &self,
+        stack_name: &str,
+        change_set_name: &str,
+        description: String,
+    ) -> CreateChangeSetInput {
+        CreateChangeSetInput {
+            stack_name: stack_name.to_owned(),
+            change_set_name: change_set_name.to_owned(),
+            change_set_type: Some("UPDATE".to_owned()),
+            description: Some(description),
+            template_url: Some(self.template_url.clone()),
+            parameters: Some((&self.parameters).into()),
+            capabilities: Some(vec![
+                "CAPABILITY_IAM".to_owned(),
+                "CAPABILITY_NAMED_IAM".to_owned(),
+                "CAPABILITY_AUTO_EXPAND".to_owned(),
+            ]),
+            ..Default::default()
+        }
+    }
+}
+
+/// Monitors the alarms given by the canary options.
+pub(crate) struct Canary<'a> {
+    opt: &'a CanaryOpt,
+    cloudwatch: CloudWatchClient,
+}
+
+impl<'a> Canary<'a> {
+    /// Create the canary if it was requested, `None` otherwise.
+    pub(crate) fn new(
+        opt: &'a CanaryOpt,
+        global_opt: &GlobalOpt,
+        provider: &AwsxProvider,
+    ) -> Result<Option<Canary<'a>>, Error> {
+        if !opt.canary {
+            return Ok(None);
+        }
+        Ok(Some(Canary {
+            opt,
+            cloudwatch: CloudWatchClient::new_with(
+                http::client()?,
+                provider.clone(),
+                endpoint::region_for("monitoring", global_opt.region()),
+            ),
+        }))
+    }
+
+    /// Verify that all alarms exist, warning about alarms that are firing already.
+    ///
+    /// This is meant to be called before executing the change set, so that a misspelled alarm
+    /// name does not go unnoticed until the bake period.
+    pub(crate) async fn verify_alarms(&self) -> Result<(), Error> {
+        for alarm_state in verify_alarms(&self.cloudwatch, &self.opt.canary_alarm_names).await? {
+            eprintln!(
+                "WARNING: alarm {} is already in state ALARM before the execution, the canary \
+                 will fail unless it recovers",
+                alarm_state.alarm_name
+            );
+        }
+        Ok(())
+    }
+
+    /// Poll the alarms for the bake period, stopping early as soon as any alarm fires.
+    pub(crate) async fn bake(&self) -> Result<CanaryReport, Error> {
+        eprintln!(
+            "Monitoring alarms for {} seconds: {}",
+            self.opt.canary_bake_time,
+            self.opt.canary_alarm_names.iter().join(", ")
+        );
+        bake_alarms(
+            &self.cloudwatch,
+            &self.opt.canary_alarm_names,
+            Duration::from_secs(self.opt.canary_bake_time),
+            |transition| {
+                eprintln!(
+                    "Alarm {} changed from {} to {}{}",
+                    transition.alarm_name,
+                    transition.from,
+                    transition.to,
+                    transition
+                        .reason
+                        .as_deref()
+                        .map(|reason| format!(": {}", reason))
+                        .unwrap_or_default()
+                )
+            },
+        )
+        .await
+    }
+
+    /// Determine the deployment recorded on the stack before executing the change set, if a
+    /// rollback was requested.
+    ///
+    /// Returns `None` if no rollback was requested, or if the stack can not be rolled back, e.g.
+    /// because it does not exist yet.
+    pub(crate) async fn snapshot(
+        &self,
+        cfn: &dyn CloudFormation,
+        global_opt: &GlobalOpt,
+        change_set: &ChangeSet,
+    ) -> Result<Option<RollbackSnapshot>, Error> {
+        if !self.opt.canary_rollback {
+            return Ok(None);
+        }
+        let stack_name = change_set.stack_name();
+        let stack = Stack::new(stack_name);
+        match stack.status(cfn).await? {
+            Some(status) if status != "REVIEW_IN_PROGRESS" => {}
+            _ => {
+                eprintln!(
+                    "WARNING: stack {} does not exist yet, it will not be rolled back if the \
+                     canary fails",
+                    stack_name
+                );
+                return Ok(None);
+            }
+        }
+
+        let metadata_parameter = stack
+            .get_parameter(cfn, &global_opt.deployment_metadata_parameter)
+            .await?;
+        let applying = deployment_metadata(global_opt, &change_set.describe(cfn).await?.parameters)
+            .and_then(|metadata| metadata.applied);
+        match RollbackSnapshot::from_metadata(metadata_parameter, applying.as_ref()) {
+            Ok(snapshot) => Ok(Some(snapshot)),
+            Err(reason) => {
+                eprintln!(
+                    "WARNING: stack {} will not be rolled back if the canary fails, since {}",
+                    stack_name, reason
+                );
+                Ok(None)
+            }
+        }
+    }
+
+    /// Roll the stack back to the snapshot, waiting until the rollback has completed.
+    ///
+    /// A rollback removing or replacing protected resources is refused and its change set deleted.
+    pub(crate) async fn roll_back(
+        &self,
+        cfn: &dyn CloudFormation,
+        global_opt: &GlobalOpt,
+        change_set: &ChangeSet,
+        snapshot: &RollbackSnapshot,
+    ) -> Result<RollbackReport, Error> {
+        let rollback = ChangeSet::new(
+            change_set.stack_name(),
+            format!("{}-rollback", change_set.name()),
+        );
+        eprintln!(
+            "Rolling back stack {} through change set {}",
+            rollback.stack_name(),
+            rollback.name()
+        );
+
+        let result = cfn
+            .create_change_set(snapshot.change_set_input(
+                rollback.stack_name(),
+                rollback.name(),
+                format!(
+                    "Rollback of change set {} after the canary failed",
+                    change_set.name()
+                ),
+            ))
+            .await
+            .map_err(Error::from);
+        audit::record(
+            AuditEntry::new("CreateChangeSet", rollback.stack_name())
+                .with_change_set_name(rollback.name())
+                .with_parameters(&snapshot.parameters),
+            &result,
+        )
+        .await?;
+        result?;
+
+        let description = match rollback.wait_until_created(cfn).await {
+            Ok(description) => description,
+            Err(Error::ChangeSetCreationFailed(reason)) if is_no_changes_reason(&reason) => {
+                rollback.delete(cfn).await?;
+                return Ok(RollbackReport {
+                    change_set_name: rollback.name().to_owned(),
+                    stack_status: None,
+                });
+            }
+            Err(error) => return Err(error),
+        };
+        if let Err(error) = verify_protected_resources(global_opt, &description) {
+            rollback.delete(cfn).await?;
+            return Err(error);
+        }
+
+        let result = rollback.execute(cfn).await;
+        audit::record(
+            AuditEntry::new("ExecuteChangeSet", rollback.stack_name())
+                .with_change_set_name(rollback.name()),
+            &result,
+        )
+        .await?;
+        result?;
+        let stack_status = rollback.wait_until_executed(cfn).await?;
+        Ok(RollbackReport {
+            change_set_name: rollback.name().to_owned(),
+            stack_status: Some(stack_status),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata_parameter(applied: DeploymentMetadataApplied) -> Parameter {
+        Parameter::WithValue {
+            key: "DeploymentMetadata".to_owned(),
+            value: DeploymentMetadata {
+                applied: Some(applied),
+                ..Default::default()
+            }
+            .to_string(),
+        }
+    }
+
+    fn applied(template_url: Option<&str>) -> DeploymentMetadataApplied {
+        DeploymentMetadataApplied {
+            template_url: template_url.map(ToOwned::to_owned),
+            parameters: vec![
+                ("Name".to_owned(), Some("app".to_owned())),
+                ("Secret".to_owned(), None),
+            ]
+            .into_iter()
+            .collect(),
+            changed_no_echo_parameters: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn rollback_reapplies_recorded_deployment() {
+        let metadata = metadata_parameter(applied(Some("https://bucket/old.template")));
+        let snapshot = RollbackSnapshot::from_metadata(
+            Some(metadata.clone()),
+            Some(&DeploymentMetadataApplied::default()),
+        )
+        .unwrap();
+
+        let input = snapshot.change_set_input("my-stack", "cs-rollback", "Rollback".to_owned());
+        assert_eq!("my-stack", input.stack_name);
+        assert_eq!("cs-rollback", input.change_set_name);
+        assert_eq!(Some("UPDATE"), input.change_set_type.as_deref());
+        assert_eq!(
+            Some("https://bucket/old.template"),
+            input.template_url.as_deref()
+        );
+        assert_eq!(None, input.template_body);
+        let parameters = input.parameters.unwrap();
+        assert_eq!(3, parameters.len());
+        assert_eq!(Some("Name"), parameters[0].parameter_key.as_deref());
+        assert_eq!(Some("app"), parameters[0].parameter_value.as_deref());
+        assert_eq!(Some("Secret"), parameters[1].parameter_key.as_deref());
+        assert_eq!(Some(true), parameters[1].use_previous_value);
+        assert_eq!(
+            Some("DeploymentMetadata"),
+            parameters[2].parameter_key.as_deref()
+        );
+        assert_eq!(
+            metadata,
+            Parameter::WithValue {
+                key: "DeploymentMetadata".to_owned(),
+                value: parameters[2].parameter_value.clone().unwrap(),
+            }
+        );
+    }
+
+    #[test]
+    fn rollback_requires_restorable_deployment() {
+        let changing_secret = DeploymentMetadataApplied {
+            changed_no_echo_parameters: vec!["Secret".to_owned()],
+            ..Default::default()
+        };
+        let metadata = metadata_parameter(applied(Some("https://bucket/old.template")));
+        assert!(RollbackSnapshot::from_metadata(None, None).is_err());
+        assert!(RollbackSnapshot::from_metadata(
+            Some(metadata_parameter(applied(None))),
+            Some(&DeploymentMetadataApplied::default())
+        )
+        .is_err());
+        assert!(
+            RollbackSnapshot::from_metadata(Some(metadata.clone()), Some(&changing_secret))
+                .unwrap_err()
+                .contains("Secret")
+        );
+        // Without a record of what the change set applies, NoEcho parameters may have changed.
+        assert!(RollbackSnapshot::from_metadata(Some(metadata), None).is_err());
+        assert!(RollbackSnapshot::from_metadata(
+            Some(Parameter::WithValue {
+                key: "DeploymentMetadata".to_owned(),
+                value: DeploymentMetadata::default().to_string(),
+            }),
+            None
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,551 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+use awsx::{
+    changeset::{is_no_changes_reason, ChangeSet},
+    commands::{
+        blue_green::{
+            blue_decommission, prepare_green_provision, resume_green_provision, shift_traffic,
+            wait_healthy, BlueDecommission, DecommissionedBlueStack, DeploymentOutcome,
+            DeploymentStatus, GreenProvision, HealthyGreenStack, Phase, PhaseDetails, PhaseOutcome,
+            Plan, ProvisionedGreenStack, ShiftedTraffic, State, PHASES,
+        },
+        cloudformation::change_set::{create_change_set, ChangeSetOptions},
+        CommandOutput,
+    },
+    endpoint,
+    error::Error,
+    http,
+    parameter::Parameters,
+    route53::Route53Client,
+    s3::S3Uploader,
+    stack::Stack,
+};
+use rusoto_cloudformation::CloudFormationClient;
+use rusoto_elbv2::ElbClient;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use structopt::StructOpt;
+use uuid::Uuid;
+
+use crate::{
+    audit::{self, AuditEntry},
+    command::execute_change_set::verify_changes,
+    guard, redact,
+    util::{
+        change_set_description, load_parameter_file, load_template, metadata_options, s3_uploader,
+        template_git_path, PlaceholderContext,
+    },
+    AwsxOutput, AwsxProvider, ExitCode, Opt as GlobalOpt,
+};
+
+#[derive(Debug, StructOpt)]
+pub(crate) struct Opt {
+    #[structopt(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, StructOpt)]
+enum Command {
+    #[structopt(
+        name = "run",
+        about = "Run all phases of the plan that have not completed yet",
+        long_about = "Run all phases of the plan that have not completed yet, in order: provision, \
+                      wait-healthy, shift-traffic and decommission. If a previous run was \
+                      interrupted, the deployment is resumed where it stopped."
+    )]
+    Run(PlanOpt),
+    #[structopt(
+        name = "status",
+        about = "Show which phases of the plan have completed"
+    )]
+    Status(PlanOpt),
+    #[structopt(
+        name = "provision",
+        about = "Create or update the green stack from the template of the plan"
+    )]
+    Provision(PlanOpt),
+    #[structopt(
+        name = "wait-healthy",
+        about = "Wait until the targets of the green stack are healthy"
+    )]
+    WaitHealthy(PlanOpt),
+    #[structopt(
+        name = "shift-traffic",
+        about = "Shift the traffic from the blue to the green stack"
+    )]
+    ShiftTraffic(PlanOpt),
+    #[structopt(name = "decommission", about = "Delete the blue stack")]
+    Decommission(PlanOpt),
+}
+
+#[derive(Debug, StructOpt)]
+struct PlanOpt {
+    #[structopt(
+        long = "plan-path",
+        help = "Path to the YAML plan of the deployment",
+        long_help = "Path to the YAML plan of the deployment, describing the blue and green \
+                     stacks, how to check the health of the green stack and how to shift the \
+                     traffic. Relative paths in the plan are resolved relative to the plan."
+    )]
+    plan_path: String,
+    #[structopt(
+        long = "state-path",
+        help = "Path to the file recording the progress of the deployment",
+        long_help = "Path to the file recording the progress of the deployment, which allows \
+                     resuming an interrupted deployment. Defaults to the plan path with the \
+                     extension `.state.json`. Delete the file to start a new deployment."
+    )]
+    state_path: Option<String>,
+    #[structopt(
+        long = "allow-replacement",
+        help = "Provision the green stack even if it replaces resources",
+        long_help = "Execute the change set provisioning the green stack even if it replaces \
+                     resources, either certainly or conditionally. Without this option, executing \
+                     such a change set has to be confirmed interactively, and is refused if stdin \
+                     is not a TTY. Change sets removing or replacing the protected resources \
+                     configured for the green stack as `protected-resources` in the configuration \
+                     file are refused regardless."
+    )]
+    allow_replacement: bool,
+}
+
+impl PlanOpt {
+    fn state_path(&self) -> PathBuf {
+        match &self.state_path {
+            Some(state_path) => PathBuf::from(state_path),
+            None => Path::new(&self.plan_path).with_extension("state.json"),
+        }
+    }
+}
+
+fn load_plan(plan_path: &str) -> Result<Plan, Error> {
+    Plan::from_yaml(&fs::read(plan_path)?, plan_path)
+}
+
+/// Load the progress of the deployment, or start a new deployment if nothing was recorded yet.
+fn load_state(path: &Path) -> Result<State, Error> {
+    if path.exists() {
+        Ok(serde_json::from_slice(&fs::read(path)?)?)
+    } else {
+        Ok(State::default())
+    }
+}
+
+fn save_state(state: &State, path: &Path) -> Result<(), Error> {
+    fs::write(path, serde_json::to_vec_pretty(state)?)?;
+    Ok(())
+}
+
+/// Everything a phase needs to run.
+struct Deployment<'a> {
+    plan: Plan,
+    plan_dir: PathBuf,
+    state: State,
+    state_path: PathBuf,
+    allow_replacement: bool,
+    global_opt: &'a GlobalOpt,
+    provider: AwsxProvider,
+    cfn: CloudFormationClient,
+    elb: ElbClient,
+}
+
+impl<'a> Deployment<'a> {
+    fn new(
+        plan_opt: &PlanOpt,
+        global_opt: &'a GlobalOpt,
+        provider: AwsxProvider,
+    ) -> Result<Deployment<'a>, Error> {
+        let plan = load_plan(&plan_opt.plan_path)?;
+        let state_path = plan_opt.state_path();
+        let state = load_state(&state_path)?;
+        let region = global_opt.region();
+        Ok(Deployment {
+            plan,
+            plan_dir: Path::new(&plan_opt.plan_path)
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default(),
+            state,
+            state_path,
+            allow_replacement: plan_opt.allow_replacement,
+            global_opt,
+            cfn: CloudFormationClient::new_with(
+                http::client()?,
+                provider.clone(),
+                endpoint::region_for("cloudformation", region.clone()),
+            ),
+            elb: ElbClient::new_with(
+                http::client()?,
+                provider.clone(),
+                endpoint::region_for("elasticloadbalancing", region),
+            ),
+            provider,
+        })
+    }
+
+    /// Run the phase, unless it has completed already, returning a summary of what it did.
+    async fn run_phase(&mut self, phase: Phase) -> Result<(String, PhaseOutcome), Error> {
+        if !self.state.needs_run(phase)? {
+            return Ok((
+                format!("Phase {} has already completed", phase.name()),
+                PhaseOutcome::skipped(phase),
+            ));
+        }
+
+        eprintln!("Running phase {}", phase.name());
+        let (message, details) = match phase {
+            Phase::Provision => self.provision().await?,
+            Phase::WaitHealthy => self.wait_healthy().await?,
+            Phase::ShiftTraffic => self.shift_traffic().await?,
+            Phase::Decommission => self.decommission().await?,
+        };
+        self.state.completed_phases.push(phase);
+        save_state(&self.state, &self.state_path)?;
+        Ok((message, PhaseOutcome::completed(phase, details)))
+    }
+
+    /// Create a change set for the green stack from the template and execute it.
+    ///
+    /// If a change set was created by a previous, interrupted run, that change set is picked up
+    /// again instead of creating a new one. The change set is verified like when executing it with
+    /// `execute-change-set`, i.e. it is not executed if it touches protected resources, or replaces
+    /// resources without `--allow-replacement` or an interactive confirmation.
+    async fn provision(&mut self) -> Result<(String, PhaseDetails), Error> {
+        let stack_name = self.plan.green.stack_name.clone();
+        let change_set =
+            match resume_green_provision(&self.cfn, &self.plan.green, &self.state).await? {
+                Some(change_set) => change_set,
+                None => self.create_change_set().await?,
+            };
+
+        let description = match change_set.wait_until_created(&self.cfn).await {
+            Ok(description) => description,
+            Err(Error::ChangeSetCreationFailed(reason)) if is_no_changes_reason(&reason) => {
+                change_set.delete(&self.cfn).await?;
+                return Ok((
+                    format!("Green stack {} is already up to date", stack_name),
+                    PhaseDetails::Provision(ProvisionedGreenStack {
+                        stack_name,
+                        change_set_name: None,
+                        changes: false,
+                    }),
+                ));
+            }
+            Err(error) => return Err(error),
+        };
+
+        match description.execution_status.as_deref() {
+            Some("EXECUTE_COMPLETE") => {}
+            Some("EXECUTE_IN_PROGRESS") => {
+                change_set.wait_until_executed(&self.cfn).await?;
+            }
+            _ => {
+                verify_changes(
+                    &self.cfn,
+                    self.global_opt,
+                    &change_set,
+                    self.allow_replacement,
+                )
+                .await?;
+                let result = change_set.execute(&self.cfn).await;
+                audit::record(
+                    AuditEntry::new("ExecuteChangeSet", change_set.stack_name())
+                        .with_change_set_name(change_set.name()),
+                    &result,
+                )
+                .await?;
+                result?;
+                change_set.wait_until_executed(&self.cfn).await?;
+            }
+        }
+
+        Ok((
+            format!(
+                "Green stack {} provisioned through change set {}",
+                stack_name,
+                change_set.name()
+            ),
+            PhaseDetails::Provision(ProvisionedGreenStack {
+                stack_name,
+                change_set_name: Some(change_set.name().to_owned()),
+                changes: true,
+            }),
+        ))
+    }
+
+    async fn create_change_set(&mut self) -> Result<ChangeSet, Error> {
+        let green = &self.plan.green;
+        let template_path = self
+            .plan_dir
+            .join(&green.template_path)
+            .to_string_lossy()
+            .into_owned();
+        let template =
+            load_template(&template_path)?.with_minification(self.global_opt.minify_templates);
+
+        let parameter_file: Option<Parameters> = match &green.parameter_path {
+            Some(parameter_path) => {
+                let placeholder_context = PlaceholderContext {
+                    stack_name: Some(&green.stack_name),
+                    global_opt: self.global_opt,
+                    provider: &self.provider,
+                };
+                let parameter_path = self.plan_dir.join(parameter_path);
+                Some(
+                    load_parameter_file(&parameter_path.to_string_lossy(), &placeholder_context)
+                        .await?
+                        .values()
+                        .filter(|parameter| !parameter.is_previous_value())
+                        .collect::<Vec<_>>()
+                        .into(),
+                )
+            }
+            None => None,
+        };
+        let s3 = s3_uploader(
+            self.global_opt,
+            self.provider.clone(),
+            Some(template_git_path(&template_path)),
+        );
+        let s3_upload: Option<(&S3Uploader, &str)> = self
+            .global_opt
+            .s3_bucket_name
+            .as_ref()
+            .map(|bucket_name| (&s3, bucket_name.as_ref()));
+
+        let change_set_name = format!("awsx-blue-green-{}", Uuid::new_v4());
+        let mut prepared = prepare_green_provision(
+            &self.cfn,
+            green,
+            GreenProvision {
+                change_set_name: &change_set_name,
+                template,
+                parameter_file,
+            },
+            &metadata_options(self.global_opt, Some(template_git_path(&template_path))),
+            s3_upload,
+        )
+        .await?;
+        redact::register_parameters(&prepared.parameters);
+        prepared.validate(false)?;
+        for warning in &prepared.warnings {
+            eprintln!("WARNING: {}", redact::redact_str(warning));
+        }
+        let template = prepared
+            .template
+            .as_ref()
+            .expect("the green stack is provisioned from a template");
+        guard::validate_template(self.global_opt, template, &prepared.parameters)?;
+
+        let description = change_set_description(self.global_opt, &prepared.parameters);
+        let result = create_change_set(
+            &self.cfn,
+            &prepared,
+            &ChangeSetOptions {
+                role_arn: green.role_arn.as_deref(),
+                resource_types: Some(green.resource_types.as_slice())
+                    .filter(|types| !types.is_empty()),
+                description: description.as_deref(),
+                s3_upload,
+            },
+        )
+        .await;
+        audit::record(
+            AuditEntry::new("CreateChangeSet", &green.stack_name)
+                .with_change_set_name(&change_set_name)
+                .with_template(template)
+                .with_parameters(&prepared.parameters),
+            &result,
+        )
+        .await?;
+        result?;
+
+        let change_set = ChangeSet::new(&green.stack_name, &change_set_name);
+        self.state.change_set_name = Some(change_set_name);
+        save_state(&self.state, &self.state_path)?;
+        Ok(change_set)
+    }
+
+    /// Wait until enough targets of the target group of the green stack are healthy.
+    async fn wait_healthy(&self) -> Result<(String, PhaseDetails), Error> {
+        let health = wait_healthy(&self.cfn, &self.elb, &self.plan, |progress| {
+            eprintln!(
+                "Waiting for targets to become healthy: {} of {} healthy",
+                progress.healthy_count,
+                progress.targets.len()
+            )
+        })
+        .await?;
+
+        Ok((
+            format!(
+                "{} targets of the green stack {} are healthy",
+                health.healthy_count, self.plan.green.stack_name
+            ),
+            PhaseDetails::WaitHealthy(HealthyGreenStack {
+                target_group_arn: health.target_group_arn,
+                healthy_count: health.healthy_count,
+            }),
+        ))
+    }
+
+    /// Shift the traffic to the green stack, in as many steps as the plan requests.
+    ///
+    /// Every completed step is recorded, so that an interrupted shift continues with the next step.
+    async fn shift_traffic(&mut self) -> Result<(String, PhaseDetails), Error> {
+        let route53 = Route53Client::new_with(http::client()?, self.provider.clone());
+        let state = &mut self.state;
+        let state_path = &self.state_path;
+        shift_traffic(
+            &self.cfn,
+            &self.elb,
+            &route53,
+            &self.plan,
+            state.traffic_step,
+            |traffic_step| {
+                eprintln!(
+                    "Step {} of {}: blue={}, green={}",
+                    traffic_step.step,
+                    traffic_step.steps,
+                    traffic_step.blue_weight,
+                    traffic_step.green_weight
+                );
+                state.traffic_step = traffic_step.step;
+                save_state(state, state_path)
+            },
+        )
+        .await?;
+
+        Ok((
+            format!(
+                "Traffic shifted from the blue stack {} to the green stack {}",
+                self.plan.blue.stack_name, self.plan.green.stack_name
+            ),
+            PhaseDetails::ShiftTraffic(ShiftedTraffic {
+                steps: self.plan.traffic.steps.get(),
+            }),
+        ))
+    }
+
+    /// Delete the blue stack, unless the plan asks to keep it.
+    async fn decommission(&self) -> Result<(String, PhaseDetails), Error> {
+        let blue = &self.plan.blue;
+        let stack = Stack::new(&blue.stack_name);
+        match blue_decommission(&self.cfn, &self.plan).await? {
+            BlueDecommission::Keep => {
+                return Ok((
+                    format!("Blue stack {} was kept", blue.stack_name),
+                    PhaseDetails::Decommission(DecommissionedBlueStack {
+                        stack_name: blue.stack_name.clone(),
+                        deleted: false,
+                    }),
+                ));
+            }
+            BlueDecommission::Gone => {}
+            BlueDecommission::WaitUntilDeleted => stack.wait_until_deleted(&self.cfn).await?,
+            BlueDecommission::Delete => {
+                let result = stack
+                    .delete(&self.cfn, self.plan.decommission.role_arn.as_deref())
+                    .await;
+                audit::record(AuditEntry::new("DeleteStack", &blue.stack_name), &result).await?;
+                result?;
+                stack.wait_until_deleted(&self.cfn).await?;
+            }
+        }
+
+        Ok((
+            format!("Blue stack {} deleted", blue.stack_name),
+            PhaseDetails::Decommission(DecommissionedBlueStack {
+                stack_name: blue.stack_name.clone(),
+                deleted: true,
+            }),
+        ))
+    }
+}
+
+fn status_output(plan_opt: &PlanOpt) -> Result<AwsxOutput<CommandOutput<DeploymentStatus>>, Error> {
+    // Loading the plan validates it, even though only the state is reported.
+    let plan = load_plan(&plan_opt.plan_path)?;
+    let state = load_state(&plan_opt.state_path())?;
+    let mut lines = PHASES
+        .iter()
+        .map(|phase| {
+            format!(
+                "- {}: {}",
+                phase.name(),
+                if state.is_completed(*phase) {
+                    "completed"
+                } else {
+                    "pending"
+                }
+            )
+        })
+        .collect::<Vec<_>>();
+    if !state.is_completed(Phase::ShiftTraffic) && state.traffic_step > 0 {
+        lines.push(format!(
+            "Traffic shifted {} of {} steps",
+            state.traffic_step, plan.traffic.steps
+        ));
+    }
+
+    Ok(AwsxOutput {
+        human_readable: lines.join("\n"),
+        structured: CommandOutput::success(
+            "Deployment status loaded successfully",
+            DeploymentStatus::new(&plan, &state),
+        ),
+        exit_code: ExitCode::Success,
+    })
+}
+
+pub(crate) async fn blue_green(
+    opt: &Opt,
+    global_opt: &GlobalOpt,
+    provider: AwsxProvider,
+) -> Result<AwsxOutput, Error> {
+    let (plan_opt, phases) = match &opt.command {
+        Command::Status(plan_opt) => return status_output(plan_opt).map(AwsxOutput::into_json),
+        Command::Run(plan_opt) => (plan_opt, PHASES),
+        Command::Provision(plan_opt) => (plan_opt, &PHASES[0..1]),
+        Command::WaitHealthy(plan_opt) => (plan_opt, &PHASES[1..2]),
+        Command::ShiftTraffic(plan_opt) => (plan_opt, &PHASES[2..3]),
+        Command::Decommission(plan_opt) => (plan_opt, &PHASES[3..4]),
+    };
+
+    let mut deployment = Deployment::new(plan_opt, global_opt, provider)?;
+    let mut messages = Vec::new();
+    let mut outcomes = Vec::new();
+    for phase in phases {
+        let (message, outcome) = deployment.run_phase(*phase).await?;
+        messages.push(message);
+        outcomes.push(outcome);
+    }
+
+    Ok(AwsxOutput {
+        human_readable: messages.join("\n"),
+        structured: CommandOutput::success(
+            "Deployment phases completed successfully",
+            DeploymentOutcome {
+                phases: outcomes,
+                completed_phases: deployment.state.completed_phases,
+            },
+        ),
+        exit_code: ExitCode::Success,
+    }
+    .into_json())
+}
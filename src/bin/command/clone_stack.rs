@@ -0,0 +1,146 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use awsx::{
+    commands::{
+        cloudformation::{
+            change_set::ChangeSetOutcome,
+            deploy::{prepare_stack_clone, ClonedFrom, StackClone},
+        },
+        CommandOutput,
+    },
+    endpoint,
+    error::Error,
+    http,
+    parameter::Parameter,
+    s3::S3Uploader,
+};
+use rusoto_cloudformation::CloudFormationClient;
+use structopt::StructOpt;
+
+use crate::{
+    command::execute_change_set::{
+        create_prepared_change_set, handle_created_change_set, ChangeSetOpt,
+    },
+    redact,
+    util::{metadata_options, s3_uploader},
+    AwsxOutput, AwsxProvider, ExitCode, Opt as GlobalOpt,
+};
+
+#[derive(Debug, StructOpt)]
+pub(crate) struct Opt {
+    #[structopt(long = "source-stack-name", help = "Name of the stack to clone")]
+    source_stack_name: String,
+    #[structopt(
+        long = "stack-name",
+        required_unless = "stack-name-suffix",
+        conflicts_with = "stack-name-suffix",
+        help = "Name of the stack to create"
+    )]
+    stack_name: Option<String>,
+    #[structopt(
+        long = "stack-name-suffix",
+        help = "Suffix appended to the name of the source stack",
+        long_help = "Suffix appended to the name of the source stack to form the name of the stack \
+                     to create, e.g. `-review-123`. You cannot specify this if --stack-name has \
+                     been specified."
+    )]
+    stack_name_suffix: Option<String>,
+    #[structopt(long = "change-set-name", help = "Name for the new change set")]
+    change_set_name: String,
+    #[structopt(
+        long = "role-arn",
+        help = "IAM Role that AWS CloudFormation assumes when executing the change set"
+    )]
+    role_arn: Option<String>,
+    #[structopt(
+        short = "p",
+        long = "parameters",
+        help = "Parameters to change",
+        long_help = "Parameters to change compared to the source stack. Specify as multiple \
+                     `Key=Value` pairs. Parameters declared as `NoEcho` have to be specified, \
+                     since their values can not be retrieved from the source stack."
+    )]
+    parameters: Vec<Parameter>,
+    #[structopt(flatten)]
+    change_set_opt: ChangeSetOpt,
+}
+
+pub(crate) async fn clone_stack(
+    opt: &Opt,
+    global_opt: &GlobalOpt,
+    provider: AwsxProvider,
+) -> Result<AwsxOutput<CommandOutput<ChangeSetOutcome<ClonedFrom>>>, Error> {
+    let stack_name = match (&opt.stack_name, &opt.stack_name_suffix) {
+        (Some(stack_name), _) => stack_name.to_owned(),
+        (None, Some(suffix)) => format!("{}{}", opt.source_stack_name, suffix),
+        // clap should catch this situation before this code-path is ever reached.
+        (None, None) => unreachable!(),
+    };
+
+    // Create AWS clients
+    let cfn = CloudFormationClient::new_with(
+        http::client()?,
+        provider.clone(),
+        endpoint::region_for("cloudformation", global_opt.region()),
+    );
+    let s3 = s3_uploader(global_opt, provider.clone(), None);
+    let s3_upload: Option<(&S3Uploader, &str)> = global_opt
+        .s3_bucket_name
+        .as_ref()
+        .map(|bucket_name| (&s3, bucket_name.as_ref()));
+
+    let mut prepared = prepare_stack_clone(
+        &cfn,
+        StackClone {
+            source_stack_name: &opt.source_stack_name,
+            stack_name: &stack_name,
+            change_set_name: &opt.change_set_name,
+            parameters: (&opt.parameters).into(),
+            minify_template: global_opt.minify_templates,
+        },
+        &metadata_options(global_opt, None),
+        s3_upload,
+    )
+    .await?;
+    if let Some(template) = &prepared.template {
+        redact::register_template(template);
+    }
+    redact::register_parameters(&prepared.parameters);
+    prepared.validate(false)?;
+    let created = create_prepared_change_set(
+        &opt.change_set_opt,
+        &cfn,
+        global_opt,
+        &prepared,
+        opt.role_arn.as_deref(),
+        s3_upload,
+    )
+    .await?;
+
+    let output = AwsxOutput {
+        human_readable: format!(
+            "Change set {} creation for new stack {} (cloned from {}) started successfully",
+            opt.change_set_name, stack_name, opt.source_stack_name,
+        ),
+        structured: CommandOutput::success(
+            "Change set creation started successfully",
+            ChangeSetOutcome::created(created, prepared.details),
+        ),
+        exit_code: ExitCode::Success,
+    };
+    handle_created_change_set(&opt.change_set_opt, &cfn, global_opt, &provider, output).await
+}
@@ -0,0 +1,137 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use awsx::{
+    commands::{
+        cloudformation::stack::{self, StackComparison},
+        CommandOutput,
+    },
+    endpoint,
+    error::Error,
+    http,
+};
+use rusoto_cloudformation::CloudFormationClient;
+use rusoto_core::Region;
+use structopt::StructOpt;
+
+use crate::{util::provider_for, AwsxOutput, AwsxProvider, ExitCode, Opt as GlobalOpt};
+
+#[derive(Debug, StructOpt)]
+pub(crate) struct Opt {
+    #[structopt(long = "stack-name", help = "Name of the first stack")]
+    stack_name: String,
+    #[structopt(
+        long = "other-stack-name",
+        help = "Name of the stack to compare against"
+    )]
+    other_stack_name: String,
+    #[structopt(
+        long = "other-region",
+        help = "Region of the stack to compare against",
+        long_help = "Region of the stack to compare against. If unspecified, the stack is expected \
+                     in the same region as the first stack."
+    )]
+    other_region: Option<Region>,
+    #[structopt(
+        long = "other-assume-role-arn",
+        help = "Role to assume to access the stack to compare against",
+        long_help = "Role to assume to access the stack to compare against, e.g. if it resides in \
+                     another account. If unspecified, the stack is accessed with the same \
+                     credentials as the first stack."
+    )]
+    other_assume_role_arn: Option<String>,
+}
+
+pub(crate) async fn compare_stacks(
+    opt: &Opt,
+    global_opt: &GlobalOpt,
+    provider: AwsxProvider,
+) -> Result<AwsxOutput<CommandOutput<StackComparison>>, Error> {
+    // Create AWS clients, where the other stack might reside in another account or region.
+    let (other_provider, other_region) = provider_for(
+        global_opt,
+        opt.other_region.clone(),
+        opt.other_assume_role_arn.clone(),
+    )?;
+    let cfn = CloudFormationClient::new_with(
+        http::client()?,
+        provider,
+        endpoint::region_for("cloudformation", global_opt.region()),
+    );
+    let other_cfn = CloudFormationClient::new_with(
+        http::client()?,
+        other_provider,
+        endpoint::region_for("cloudformation", other_region),
+    );
+
+    let comparison =
+        stack::compare_stacks(&cfn, &opt.stack_name, &other_cfn, &opt.other_stack_name).await?;
+    let equal = comparison.is_equal();
+
+    let colorize = atty::is(atty::Stream::Stdout);
+    let section = |title: &str, is_equal: bool, rendered: String| {
+        if is_equal {
+            format!("{}: equal", title)
+        } else {
+            format!("{}:\n{}", title, rendered)
+        }
+    };
+    let human_readable = [
+        format!(
+            "Comparing stack {} (-) with stack {} (+).",
+            opt.stack_name, opt.other_stack_name
+        ),
+        section(
+            "Template",
+            comparison.template.equal,
+            comparison.template.diff.clone(),
+        ),
+        section(
+            "Parameters",
+            comparison.parameters.is_none(),
+            comparison
+                .parameters
+                .as_ref()
+                .map(|parameters| parameters.render(colorize))
+                .unwrap_or_default(),
+        ),
+        section(
+            "Tags",
+            comparison.tags.is_empty(),
+            comparison.tags.render(colorize),
+        ),
+        section(
+            "Outputs",
+            comparison.outputs.is_empty(),
+            comparison.outputs.render(colorize),
+        ),
+    ]
+    .join("\n");
+
+    Ok(AwsxOutput {
+        human_readable,
+        structured: if equal {
+            CommandOutput::success("The stacks are equal", comparison)
+        } else {
+            CommandOutput::failure("The stacks differ", comparison)
+        },
+        exit_code: if equal {
+            ExitCode::Success
+        } else {
+            ExitCode::Mismatch
+        },
+    })
+}
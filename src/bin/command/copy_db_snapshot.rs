@@ -0,0 +1,196 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use awsx::{
+    commands::{
+        rds::{copy_db_snapshot as copy, wait_for_db_snapshot, DbSnapshotStatus, RegionalRds},
+        CommandOutput,
+    },
+    endpoint,
+    error::Error,
+    http,
+    rds::{CopyOptions, DbSnapshot},
+    tags::TagSpecification,
+};
+use rusoto_core::Region;
+use rusoto_rds::RdsClient;
+use std::time::Duration;
+use structopt::StructOpt;
+
+use crate::{
+    audit::{self, AuditEntry},
+    AwsxOutput, AwsxProvider, ExitCode, Opt as GlobalOpt,
+};
+
+#[derive(Debug, StructOpt)]
+pub(crate) struct Opt {
+    #[structopt(
+        long = "db-snapshot-identifier",
+        required_unless = "db-cluster-snapshot-identifier",
+        conflicts_with = "db-cluster-snapshot-identifier",
+        help = "Identifier or ARN of the DB snapshot to copy"
+    )]
+    db_snapshot_identifier: Option<String>,
+    #[structopt(
+        long = "db-cluster-snapshot-identifier",
+        required_unless = "db-snapshot-identifier",
+        conflicts_with = "db-snapshot-identifier",
+        help = "Identifier or ARN of the DB cluster snapshot to copy"
+    )]
+    db_cluster_snapshot_identifier: Option<String>,
+    #[structopt(
+        long = "target-snapshot-identifier",
+        help = "Identifier for the copy of the snapshot"
+    )]
+    target_snapshot_identifier: String,
+    #[structopt(
+        long = "source-region",
+        help = "Region of the snapshot to copy",
+        long_help = "Region of the snapshot to copy. The copy is always created in the region \
+                     given by --aws-region. If unspecified, the snapshot is expected in that \
+                     region as well."
+    )]
+    source_region: Option<Region>,
+    #[structopt(
+        long = "kms-key-id",
+        help = "KMS key to encrypt the copy with",
+        long_help = "Identifier or ARN of the KMS key to encrypt the copy with. If unspecified, \
+                     the copy is encrypted with the same key as the source snapshot, if it is \
+                     encrypted at all. Required when copying an encrypted snapshot to another \
+                     region, as KMS keys are regional."
+    )]
+    kms_key_id: Option<String>,
+    #[structopt(
+        long = "copy-tags",
+        help = "Copy all tags of the source snapshot to the copy"
+    )]
+    copy_tags: bool,
+    #[structopt(
+        long = "tags",
+        help = "Tags to assign to the copy",
+        long_help = "Tags to assign to the copy. Specify multiple `Key=Value` pairs, separated by \
+                     spaces. Combined with --copy-tags, these tags are assigned in addition to the \
+                     tags of the source snapshot."
+    )]
+    tags: Vec<TagSpecification>,
+    #[structopt(
+        long = "wait",
+        help = "Wait until the copy is available",
+        long_help = "Wait until the copy of the snapshot has completed. If the copy ends up in any \
+                     other status than `available`, or the copy does not complete within \
+                     --wait-timeout, the command fails."
+    )]
+    wait: bool,
+    #[structopt(
+        long = "wait-timeout",
+        default_value = "3600",
+        help = "Maximum number of seconds to wait for the copy to become available"
+    )]
+    wait_timeout: u64,
+}
+
+impl Opt {
+    fn db_snapshot(&self) -> DbSnapshot {
+        match (
+            &self.db_snapshot_identifier,
+            &self.db_cluster_snapshot_identifier,
+        ) {
+            (Some(identifier), _) => DbSnapshot::Instance(identifier.clone()),
+            (None, Some(identifier)) => DbSnapshot::Cluster(identifier.clone()),
+            (None, None) => unreachable!("structopt requires one of the identifiers"),
+        }
+    }
+}
+
+pub(crate) async fn copy_db_snapshot(
+    opt: &Opt,
+    global_opt: &GlobalOpt,
+    provider: AwsxProvider,
+) -> Result<AwsxOutput<CommandOutput<DbSnapshotStatus>>, Error> {
+    let destination_region = global_opt.region();
+    let source_region = opt
+        .source_region
+        .clone()
+        .unwrap_or_else(|| destination_region.clone());
+    let source_rds = RdsClient::new_with(
+        http::client()?,
+        provider.clone(),
+        endpoint::region_for("rds", source_region.clone()),
+    );
+    let destination_rds = RdsClient::new_with(
+        http::client()?,
+        provider.clone(),
+        endpoint::region_for("rds", destination_region.clone()),
+    );
+
+    let options = CopyOptions {
+        kms_key_id: opt.kms_key_id.clone(),
+        copy_tags: opt.copy_tags,
+        tags: opt.tags.clone(),
+        pre_signed_url: None,
+    };
+    let db_snapshot = opt.db_snapshot();
+    let result = copy(
+        &RegionalRds {
+            rds: &source_rds,
+            region: source_region,
+        },
+        &RegionalRds {
+            rds: &destination_rds,
+            region: destination_region,
+        },
+        &db_snapshot,
+        &opt.target_snapshot_identifier,
+        &options,
+        &provider,
+    )
+    .await;
+    let action = match db_snapshot {
+        DbSnapshot::Instance(_) => "CopyDBSnapshot",
+        DbSnapshot::Cluster(_) => "CopyDBClusterSnapshot",
+    };
+    audit::record(
+        AuditEntry::for_resource(action, Some(opt.target_snapshot_identifier.clone()))
+            .with_details(result.as_ref().ok().map(|(_, status)| status)),
+        &result,
+    )
+    .await?;
+    let (target, mut status) = result?;
+    if opt.wait {
+        status = wait_for_db_snapshot(
+            &destination_rds,
+            &target,
+            Duration::from_secs(opt.wait_timeout),
+        )
+        .await?;
+    }
+
+    Ok(AwsxOutput {
+        human_readable: status
+            .db_snapshot_arn
+            .clone()
+            .unwrap_or_else(|| target.identifier().to_owned()),
+        structured: CommandOutput::success(
+            if opt.wait {
+                "Snapshot copied successfully"
+            } else {
+                "Snapshot copy started successfully"
+            },
+            status,
+        ),
+        exit_code: ExitCode::Success,
+    })
+}
@@ -0,0 +1,124 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use awsx::{
+    commands::{
+        cloudfront::{
+            create_cloudfront_invalidation as create_invalidation, wait_for_invalidation,
+            InvalidationStatus,
+        },
+        CommandOutput,
+    },
+    error::Error,
+};
+use std::time::Duration;
+use structopt::StructOpt;
+
+use crate::{
+    audit::{self, AuditEntry},
+    command::find_cloudfront_distribution::{cloudfront_client, DistributionSelectorOpt},
+    AwsxOutput, AwsxProvider, ExitCode, Opt as GlobalOpt,
+};
+
+#[derive(Debug, StructOpt)]
+pub(crate) struct Opt {
+    #[structopt(flatten)]
+    distribution_selector_opt: DistributionSelectorOpt,
+    #[structopt(
+        long = "paths",
+        required = true,
+        help = "Paths to invalidate, e.g. `/index.html /assets/*`",
+        long_help = "Paths to invalidate, separated by spaces, e.g. `/index.html /assets/*`. Each \
+                     path has to start with a `/`, and may end with a `*` to invalidate all paths \
+                     with the given prefix."
+    )]
+    paths: Vec<String>,
+    #[structopt(
+        long = "wait",
+        help = "Wait until the invalidation has completed",
+        long_help = "Wait until the invalidation has completed. If it does not complete within \
+                     --wait-timeout, the command fails."
+    )]
+    wait: bool,
+    #[structopt(
+        long = "wait-timeout",
+        default_value = "1800",
+        help = "Maximum number of seconds to wait for the invalidation to complete"
+    )]
+    wait_timeout: u64,
+}
+
+pub(crate) async fn create_cloudfront_invalidation(
+    opt: &Opt,
+    _global_opt: &GlobalOpt,
+    provider: AwsxProvider,
+) -> Result<AwsxOutput<CommandOutput<InvalidationStatus>>, Error> {
+    let cloudfront = cloudfront_client(provider)?;
+
+    let result = create_invalidation(
+        &cloudfront,
+        &opt.distribution_selector_opt.selector(),
+        &opt.paths,
+    )
+    .await;
+    let distribution_id = result
+        .as_ref()
+        .ok()
+        .map(|invalidation| invalidation.cloudfront_distribution_id.clone());
+    audit::record(
+        AuditEntry::for_resource("CreateInvalidation", distribution_id)
+            .with_details(result.as_ref().ok()),
+        &result,
+    )
+    .await?;
+    let invalidation = result?;
+
+    let status = if opt.wait {
+        tokio::time::timeout(
+            Duration::from_secs(opt.wait_timeout),
+            wait_for_invalidation(
+                &cloudfront,
+                &invalidation.cloudfront_distribution_id,
+                &invalidation.invalidation_id,
+            ),
+        )
+        .await
+        .unwrap_or_else(|_| {
+            Err(Error::WaitTimeout(format!(
+                "invalidation {} of distribution {} to complete",
+                invalidation.invalidation_id, invalidation.cloudfront_distribution_id
+            )))
+        })?
+    } else {
+        invalidation.status.clone()
+    };
+
+    Ok(AwsxOutput {
+        human_readable: invalidation.invalidation_id.clone(),
+        structured: CommandOutput::success(
+            if opt.wait {
+                "Invalidation completed successfully"
+            } else {
+                "Invalidation created successfully"
+            },
+            InvalidationStatus {
+                status,
+                ..invalidation
+            },
+        ),
+        exit_code: ExitCode::Success,
+    })
+}
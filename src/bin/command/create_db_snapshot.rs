@@ -0,0 +1,134 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use awsx::{
+    commands::{
+        rds::{create_db_snapshot as create, wait_for_db_snapshot, DbSnapshotStatus},
+        CommandOutput,
+    },
+    endpoint,
+    error::Error,
+    http,
+    rds::DbSnapshot,
+    tags::TagSpecification,
+};
+use rusoto_rds::RdsClient;
+use std::time::Duration;
+use structopt::StructOpt;
+
+use crate::{
+    audit::{self, AuditEntry},
+    AwsxOutput, AwsxProvider, ExitCode, Opt as GlobalOpt,
+};
+
+#[derive(Debug, StructOpt)]
+pub(crate) struct Opt {
+    #[structopt(
+        long = "db-instance-identifier",
+        required_unless = "db-cluster-identifier",
+        conflicts_with = "db-cluster-identifier",
+        help = "Identifier of the DB instance to create a DB snapshot of"
+    )]
+    db_instance_identifier: Option<String>,
+    #[structopt(
+        long = "db-cluster-identifier",
+        required_unless = "db-instance-identifier",
+        conflicts_with = "db-instance-identifier",
+        help = "Identifier of the DB cluster to create a DB cluster snapshot of"
+    )]
+    db_cluster_identifier: Option<String>,
+    #[structopt(long = "snapshot-identifier", help = "Identifier for the new snapshot")]
+    snapshot_identifier: String,
+    #[structopt(
+        long = "tags",
+        help = "Tags to assign to the snapshot",
+        long_help = "Tags to assign to the snapshot. Specify multiple `Key=Value` pairs, separated \
+                     by spaces."
+    )]
+    tags: Vec<TagSpecification>,
+    #[structopt(
+        long = "wait",
+        help = "Wait until the snapshot is available",
+        long_help = "Wait until the creation of the snapshot has completed. If the snapshot ends \
+                     up in any other status than `available`, or the creation does not complete \
+                     within --wait-timeout, the command fails."
+    )]
+    wait: bool,
+    #[structopt(
+        long = "wait-timeout",
+        default_value = "3600",
+        help = "Maximum number of seconds to wait for the snapshot to become available"
+    )]
+    wait_timeout: u64,
+}
+
+pub(crate) async fn create_db_snapshot(
+    opt: &Opt,
+    global_opt: &GlobalOpt,
+    provider: AwsxProvider,
+) -> Result<AwsxOutput<CommandOutput<DbSnapshotStatus>>, Error> {
+    let rds = RdsClient::new_with(
+        http::client()?,
+        provider,
+        endpoint::region_for("rds", global_opt.region()),
+    );
+
+    let (db_snapshot, source_identifier) =
+        match (&opt.db_instance_identifier, &opt.db_cluster_identifier) {
+            (Some(identifier), _) => (
+                DbSnapshot::Instance(opt.snapshot_identifier.clone()),
+                identifier,
+            ),
+            (None, Some(identifier)) => (
+                DbSnapshot::Cluster(opt.snapshot_identifier.clone()),
+                identifier,
+            ),
+            (None, None) => unreachable!("structopt requires one of the identifiers"),
+        };
+
+    let result = create(&rds, &db_snapshot, source_identifier, &opt.tags).await;
+    let action = match db_snapshot {
+        DbSnapshot::Instance(_) => "CreateDBSnapshot",
+        DbSnapshot::Cluster(_) => "CreateDBClusterSnapshot",
+    };
+    audit::record(
+        AuditEntry::for_resource(action, Some(opt.snapshot_identifier.clone()))
+            .with_details(result.as_ref().ok()),
+        &result,
+    )
+    .await?;
+    let mut status = result?;
+    if opt.wait {
+        status =
+            wait_for_db_snapshot(&rds, &db_snapshot, Duration::from_secs(opt.wait_timeout)).await?;
+    }
+
+    Ok(AwsxOutput {
+        human_readable: status
+            .db_snapshot_arn
+            .clone()
+            .unwrap_or_else(|| opt.snapshot_identifier.clone()),
+        structured: CommandOutput::success(
+            if opt.wait {
+                "Snapshot created successfully"
+            } else {
+                "Snapshot creation started successfully"
+            },
+            status,
+        ),
+        exit_code: ExitCode::Success,
+    })
+}
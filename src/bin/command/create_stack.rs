@@ -15,19 +15,34 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use awsx::{
+    commands::{
+        cloudformation::{
+            change_set::ChangeSetOutcome,
+            deploy::{prepare_stack_creation, StackCreation},
+        },
+        CommandOutput,
+    },
+    endpoint,
     error::Error,
+    http,
     parameter::{Parameter, Parameters},
     s3::S3Uploader,
-    template::Template,
 };
-use itertools::Itertools;
 use rusoto_cloudformation::CloudFormationClient;
-use rusoto_core::HttpClient;
-use serde_json::json;
-use std::{fs::File, io::BufReader};
 use structopt::StructOpt;
 
-use crate::{util::generate_deployment_metadata, AwsxOutput, AwsxProvider, Opt as GlobalOpt};
+use crate::{
+    command::execute_change_set::{
+        create_prepared_change_set, handle_created_change_set, ChangeSetOpt,
+    },
+    redact,
+    util::{
+        apply_excludes_includes, load_parameter_defaults, load_parameter_file, load_template,
+        metadata_options, package_template, s3_uploader, template_git_path, ConflictStrategy,
+        PlaceholderContext, STDIN_TEMPLATE_PATH,
+    },
+    AwsxOutput, AwsxProvider, ExitCode, Opt as GlobalOpt,
+};
 
 #[derive(Debug, StructOpt)]
 pub(crate) struct Opt {
@@ -40,7 +55,11 @@ pub(crate) struct Opt {
         help = "IAM Role that AWS CloudFormation assumes when executing the change set"
     )]
     role_arn: Option<String>,
-    #[structopt(long = "template-path", help = "Path to the new template")]
+    #[structopt(
+        long = "template-path",
+        help = "Path to the new template",
+        long_help = "Path to the new template. Specify `-` to read the template from stdin."
+    )]
     template_path: String,
     #[structopt(
         short = "p",
@@ -55,14 +74,60 @@ pub(crate) struct Opt {
     parameters: Vec<Parameter>,
     #[structopt(
         long = "parameter-path",
-        help = "Path to a JSON parameter file",
-        long_help = "Path to a JSON parameter file. This file should be structured the same as the \
-                     AWS CLI expects. The file shuold contain all parameters required by the \
-                     template (parameters with defaults can be skipped).\n(If you specify this \
-                     parameter and --parameter-overrides, parameters specified through \
-                     --parameters will override parameters provided via the parameter file.)"
+        help = "Path to a JSON or YAML parameter file",
+        long_help = "Path to a JSON or YAML parameter file. This file should be structured the \
+                     same as the AWS CLI expects. Values can contain placeholders, i.e. \
+                     `{{stack_name}}`, `{{region}}`, `{{account_id}}` or \
+                     `{{parameters.OtherKey}}`, while other placeholders like dynamic references \
+                     are kept; `{{{{` is a literal `{{`. The file shuold contain all parameters \
+                     required by the template (parameters with defaults can be skipped).\n(If you \
+                     specify this parameter and --parameter-overrides, parameters specified \
+                     through --parameters will override parameters provided via the parameter \
+                     file.)"
     )]
     parameter_path: Option<String>,
+    #[structopt(
+        long = "parameter-conflicts",
+        default_value = "keep-right",
+        help = "How conflicting parameter values are resolved",
+        long_help = "How parameters specified with different values by multiple sources are \
+                     resolved, where the sources are ordered --parameter-path, then --parameters: \
+                     `keep-right` takes the value of the later source, `keep-left` keeps the value \
+                     of the earlier source, `error` fails the command listing all conflicting \
+                     parameters, and `interactive` prompts which of the values to keep."
+    )]
+    parameter_conflicts: ConflictStrategy,
+    #[structopt(
+        long = "parameter-defaults-path",
+        help = "Path to a JSON or YAML parameter file with defaults",
+        long_help = "Path to a JSON or YAML parameter file, from which values will be taken if not \
+                     specified in the regular parameter file or through --parameters. This file \
+                     should be structured the same as the AWS CLI expects. Its values take \
+                     precedence over the defaults defined in the template. If the provided path \
+                     does not exist, no error is thrown, instead it will be simply ignored."
+    )]
+    parameter_defaults_path: Option<String>,
+    #[structopt(
+        long = "exclude",
+        requires = "parameter-path",
+        help = "Exclude parameters",
+        long_help = "Exclude parameters of the parameter file based on the patterns provided. All \
+                     patterns will be compiled into a regex-set, which will be used to match each \
+                     parameter key. If a parameter key matches any of the exclude-patterns, the \
+                     parameter will not be applied."
+    )]
+    excludes: Vec<String>,
+    #[structopt(
+        long = "include",
+        requires = "parameter-path",
+        help = "Include parameters",
+        long_help = "Include parameters of the parameter file based on the patterns provided. All \
+                     patterns will be compiled into a regex-set, which will be used to match each \
+                     parameter key. Every parameter key that doesn't match any of the \
+                     include-patterns will not be applied.\n(Excludes are applied before \
+                     includes, and you cannot include a parameter that was previously excluded.)"
+    )]
+    includes: Vec<String>,
     #[structopt(
         long = "force-create",
         help = "Force change set creation",
@@ -72,108 +137,119 @@ pub(crate) struct Opt {
                      be invalid."
     )]
     force_create: bool,
+    #[structopt(
+        long = "package",
+        help = "Package local artifacts referenced by the template",
+        long_help = "Package local artifacts referenced by the template before creating the change \
+                     set, uploading them to S3 the same way the `package` subcommand does. \
+                     Requires --s3-bucket-name."
+    )]
+    package: bool,
+    #[structopt(flatten)]
+    change_set_opt: ChangeSetOpt,
 }
 
 pub(crate) async fn create_stack(
     opt: &Opt,
     global_opt: &GlobalOpt,
     provider: AwsxProvider,
-) -> Result<AwsxOutput, Error> {
+) -> Result<AwsxOutput<CommandOutput<ChangeSetOutcome>>, Error> {
+    opt.parameter_conflicts
+        .ensure_usable(opt.template_path == STDIN_TEMPLATE_PATH)?;
+
     // Load the template
-    let template = Template::new(&opt.template_path)?;
+    let template =
+        load_template(&opt.template_path)?.with_minification(global_opt.minify_templates);
 
     // Create AWS clients
     let cfn = CloudFormationClient::new_with(
-        HttpClient::new()?,
+        http::client()?,
+        provider.clone(),
+        endpoint::region_for("cloudformation", global_opt.region()),
+    );
+    let s3 = s3_uploader(
+        global_opt,
         provider.clone(),
-        global_opt.aws_region.clone().unwrap_or_default(),
+        Some(template_git_path(&opt.template_path)),
     );
-    let s3 = S3Uploader::new(global_opt.aws_region.clone().unwrap_or_default(), provider);
     let s3_upload: Option<(&S3Uploader, &str)> = global_opt
         .s3_bucket_name
         .as_ref()
         .map(|bucket_name| (&s3, bucket_name.as_ref()));
 
-    // Retrieve the parameters defined on the template.
-    let mut template_parameters = template.get_parameters().to_owned();
+    // Package local artifacts referenced by the template, if requested.
+    let template = if opt.package {
+        package_template(
+            &template,
+            &opt.template_path,
+            &s3,
+            global_opt.s3_bucket_name.as_deref(),
+        )
+        .await?
+    } else {
+        template
+    };
 
     // Get the user provided parameters.
+    let placeholder_context = PlaceholderContext {
+        stack_name: Some(&opt.stack_name),
+        global_opt,
+        provider: &provider,
+    };
+    let mut provided_parameters = Parameters::new(Vec::new());
     if let Some(parameter_path) = &opt.parameter_path {
-        let file = File::open(parameter_path)?;
-        let reader = BufReader::new(file);
         let parameters: Parameters = {
-            let parameters: Parameters = serde_json::from_reader(reader).unwrap();
+            let parameters = load_parameter_file(parameter_path, &placeholder_context).await?;
             parameters
                 .values()
                 .filter(|parameter| !parameter.is_previous_value())
                 .collect::<Vec<_>>()
                 .into()
         };
-        template_parameters.update(parameters);
+        provided_parameters = apply_excludes_includes(parameters, &opt.excludes, &opt.includes)?;
     }
-    template_parameters.update(&opt.parameters);
+    opt.parameter_conflicts.merge(
+        &mut provided_parameters,
+        opt.parameters.clone().into(),
+        ("the parameter file", "--parameters"),
+    )?;
+    let parameter_defaults =
+        load_parameter_defaults(&opt.parameter_defaults_path, &placeholder_context).await?;
 
-    // Unless otherwise requested, we will set the deployment-metadata parameter
-    if !global_opt.dont_update_deployment_metadata {
-        let metadata = generate_deployment_metadata(None, Some(&opt.template_path))?;
-        template_parameters.insert(
-            global_opt.deployment_metadata_parameter.clone(),
-            Parameter::WithValue {
-                key: global_opt.deployment_metadata_parameter.clone(),
-                value: metadata.to_string(),
-            },
-        );
-    }
+    let mut prepared = prepare_stack_creation(
+        StackCreation {
+            stack_name: &opt.stack_name,
+            change_set_name: &opt.change_set_name,
+            template,
+            parameters: provided_parameters,
+            parameter_defaults,
+            force_create: opt.force_create,
+        },
+        &metadata_options(global_opt, Some(template_git_path(&opt.template_path))),
+        s3_upload,
+    )?;
+    redact::register_parameters(&prepared.parameters);
+    prepared.validate(opt.force_create)?;
+    let created = create_prepared_change_set(
+        &opt.change_set_opt,
+        &cfn,
+        global_opt,
+        &prepared,
+        opt.role_arn.as_deref(),
+        s3_upload,
+    )
+    .await?;
 
-    // We need to ensure that the user has provided all parameters required by the template.
-    let missing_parameters = template_parameters
-        .iter()
-        .filter_map(|(name, parameter)| {
-            if parameter.is_previous_value() {
-                Some(name)
-            } else {
-                None
-            }
-        })
-        .collect::<Vec<_>>();
-    if !missing_parameters.is_empty() {
-        if opt.force_create {
-            eprintln!(
-                "WARNING: some required parameters ({}) have not been supplied. The change set \
-                 will be created since it was explicitly requested!",
-                missing_parameters.iter().join(", ")
-            );
-        } else {
-            return Err(Error::InvalidParameters(format!(
-                "not all required parameters ({}) were provided",
-                missing_parameters.iter().join(", ")
-            )));
-        }
-    }
-
-    // Create the change set for the new template, including the new parameters.
-    template
-        .create_change_set(
-            &cfn,
-            &opt.change_set_name,
-            &opt.stack_name,
-            &template_parameters,
-            opt.role_arn.as_deref(),
-            s3_upload,
-            true,
-        )
-        .await?;
-
-    Ok(AwsxOutput {
+    let output = AwsxOutput {
         human_readable: format!(
             "Change set {} creation for new stack {} started successfully",
             opt.change_set_name, opt.stack_name,
         ),
-        structured: json!({
-            "success": true,
-            "stack_name": opt.stack_name,
-            "change_set_name": opt.change_set_name,
-        }),
-        successful: true,
-    })
+        structured: CommandOutput::success(
+            "Change set creation started successfully",
+            ChangeSetOutcome::created(created, ()),
+        ),
+        exit_code: ExitCode::Success,
+    };
+    handle_created_change_set(&opt.change_set_opt, &cfn, global_opt, &provider, output).await
 }
@@ -0,0 +1,70 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use awsx::{
+    commands::{
+        cloudformation::change_set::{self, DeletedChangeSet},
+        CommandOutput,
+    },
+    endpoint,
+    error::Error,
+    http,
+};
+use rusoto_cloudformation::CloudFormationClient;
+use structopt::StructOpt;
+
+use crate::{
+    audit::{self, AuditEntry},
+    AwsxOutput, AwsxProvider, ExitCode, Opt as GlobalOpt,
+};
+
+#[derive(Debug, StructOpt)]
+pub(crate) struct Opt {
+    #[structopt(
+        long = "stack-name",
+        help = "Name of the stack the change set belongs to"
+    )]
+    stack_name: String,
+    #[structopt(long = "change-set-name", help = "Name of the change set to delete")]
+    change_set_name: String,
+}
+
+pub(crate) async fn delete_change_set(
+    opt: &Opt,
+    global_opt: &GlobalOpt,
+    provider: AwsxProvider,
+) -> Result<AwsxOutput<CommandOutput<DeletedChangeSet>>, Error> {
+    let cfn = CloudFormationClient::new_with(
+        http::client()?,
+        provider,
+        endpoint::region_for("cloudformation", global_opt.region()),
+    );
+
+    let result = change_set::delete_change_set(&cfn, &opt.stack_name, &opt.change_set_name).await;
+    audit::record(
+        AuditEntry::new("DeleteChangeSet", &opt.stack_name)
+            .with_change_set_name(&opt.change_set_name),
+        &result,
+    )
+    .await?;
+    let deleted = result?;
+
+    Ok(AwsxOutput {
+        human_readable: format!("Change set {} deleted", opt.change_set_name),
+        structured: CommandOutput::success("Change set deleted", deleted),
+        exit_code: ExitCode::Success,
+    })
+}
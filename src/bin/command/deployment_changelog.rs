@@ -0,0 +1,117 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+use awsx::{
+    commands::{
+        cloudformation::deployment::{self, DeploymentChangelog},
+        CommandOutput,
+    },
+    endpoint,
+    error::Error,
+    http,
+};
+use prettytable::row;
+use rusoto_cloudformation::CloudFormationClient;
+use structopt::StructOpt;
+
+use crate::{AwsxOutput, AwsxProvider, ExitCode, Opt as GlobalOpt};
+
+#[derive(Debug, StructOpt)]
+pub(crate) struct Opt {
+    #[structopt(long = "stack-name", help = "Name of the deployed stack")]
+    stack_name: String,
+    #[structopt(
+        long = "git-path",
+        help = "Path to git-repository of the template",
+        long_help = "Path to git-repository of the template. The default is to use the current \
+                     working directory."
+    )]
+    git_path: Option<String>,
+}
+
+pub(crate) async fn deployment_changelog(
+    opt: &Opt,
+    global_opt: &GlobalOpt,
+    provider: AwsxProvider,
+) -> Result<AwsxOutput<CommandOutput<Option<DeploymentChangelog>>>, Error> {
+    let cfn = CloudFormationClient::new_with(
+        http::client()?,
+        provider,
+        endpoint::region_for("cloudformation", global_opt.region()),
+    );
+
+    let git_path = opt.git_path.as_deref().unwrap_or(".");
+    let changelog = deployment::deployment_changelog(
+        &cfn,
+        &opt.stack_name,
+        &global_opt.deployment_metadata_parameter,
+        git_path,
+    )
+    .await?;
+    let changelog =
+        match changelog {
+            Some(changelog) => changelog,
+            None => return Ok(AwsxOutput {
+                human_readable: "Stack currently deployed does not have a deployed commit in its \
+                                 deployment metadata"
+                    .to_string(),
+                structured: CommandOutput::failure(
+                    "Stack currently deployed does not have a deployed commit in its deployment \
+                     metadata",
+                    None,
+                ),
+                exit_code: ExitCode::Failure,
+            }),
+        };
+
+    let human_readable = if changelog.changelog.is_empty() {
+        format!(
+            "No commits between the deployed commit {} and HEAD.",
+            changelog.deployed_commit
+        )
+    } else {
+        let mut table = prettytable::Table::new();
+        table.set_format(*prettytable::format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+        table.set_titles(row!["Commit", "PR", "Author", "Subject"]);
+        for entry in &changelog.changelog {
+            table.add_row(row![
+                &entry.commit[..entry.commit.len().min(12)],
+                entry
+                    .pull_request
+                    .map(|number| format!("#{}", number))
+                    .unwrap_or_default(),
+                entry.author,
+                entry.subject,
+            ]);
+        }
+        format!(
+            "Commits between the deployed commit {} and HEAD:\n{}",
+            changelog.deployed_commit,
+            table.to_string().trim_end()
+        )
+    };
+
+    Ok(AwsxOutput {
+        human_readable,
+        structured: CommandOutput::success(
+            format!(
+                "Found {} commits since the deployed commit",
+                changelog.changelog.len()
+            ),
+            Some(changelog),
+        ),
+        exit_code: ExitCode::Success,
+    })
+}
@@ -0,0 +1,220 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+use awsx::{
+    changeset::ResourceChange,
+    commands::{
+        cloudformation::deployment::{self, DeploymentReport},
+        CommandOutput,
+    },
+    deployment::{ChangelogEntry, DeploymentMetadata},
+    endpoint,
+    error::Error,
+    http,
+    parameter::DifferenceFormat,
+};
+use itertools::Itertools;
+use rusoto_cloudformation::CloudFormationClient;
+use structopt::StructOpt;
+
+use crate::{AwsxOutput, AwsxProvider, ExitCode, Opt as GlobalOpt};
+
+#[derive(Debug, StructOpt)]
+pub(crate) struct Opt {
+    #[structopt(
+        long = "stack-name",
+        help = "Name of the stack the change set belongs to"
+    )]
+    stack_name: String,
+    #[structopt(long = "change-set-name", help = "Name of the change set to report on")]
+    change_set_name: String,
+    #[structopt(
+        long = "git-path",
+        help = "Path to git-repository to build the changelog from",
+        long_help = "Path to git-repository to build the changelog from. The default is to use \
+                     the current working directory."
+    )]
+    git_path: Option<String>,
+}
+
+/// Escape a value for use within a Markdown table cell.
+fn cell(value: &str) -> String {
+    value.replace('|', "\\|").replace('\n', " ")
+}
+
+fn format_metadata_rows(
+    previous: Option<&DeploymentMetadata>,
+    current: Option<&DeploymentMetadata>,
+) -> String {
+    let row = |label: &str, value: &dyn Fn(&DeploymentMetadata) -> String| {
+        format!(
+            "| {} | {} | {} |",
+            label,
+            previous
+                .map(|metadata| cell(&value(metadata)))
+                .unwrap_or_default(),
+            current
+                .map(|metadata| cell(&value(metadata)))
+                .unwrap_or_default(),
+        )
+    };
+
+    [
+        "| | Currently deployed | This deployment |".to_owned(),
+        "|---|---|---|".to_owned(),
+        row("Deployed by", &|metadata| metadata.user.clone()),
+        row("When", &|metadata| metadata.when.clone()),
+        row("Git ref", &|metadata| metadata.git.r#ref.clone()),
+        row("Commit", &|metadata| {
+            format!(
+                "{}{}",
+                metadata.git.commit,
+                if metadata.git.dirty { " (dirty)" } else { "" }
+            )
+        }),
+    ]
+    .join("\n")
+}
+
+fn format_resource_changes(changes: &[ResourceChange]) -> String {
+    if changes.is_empty() {
+        return "No resource changes.".to_owned();
+    }
+
+    let mut lines = vec![
+        "| Action | Logical ID | Resource type | Replacement |".to_owned(),
+        "|---|---|---|---|".to_owned(),
+    ];
+    lines.extend(changes.iter().map(|change| {
+        format!(
+            "| {} | {} | {} | {} |",
+            change.action,
+            cell(&change.logical_id),
+            change.resource_type,
+            change.replacement.as_deref().unwrap_or(""),
+        )
+    }));
+    lines.join("\n")
+}
+
+fn format_changelog(changelog: &[ChangelogEntry]) -> String {
+    if changelog.is_empty() {
+        return "No commits since the currently deployed commit.".to_owned();
+    }
+
+    changelog
+        .iter()
+        .map(|entry| {
+            format!(
+                "- `{}` {} ({})",
+                &entry.commit[..entry.commit.len().min(12)],
+                entry.subject,
+                entry.author
+            )
+        })
+        .join("\n")
+}
+
+pub(crate) async fn deployment_report(
+    opt: &Opt,
+    global_opt: &GlobalOpt,
+    provider: AwsxProvider,
+) -> Result<AwsxOutput<CommandOutput<DeploymentReport>>, Error> {
+    let cfn = CloudFormationClient::new_with(
+        http::client()?,
+        provider,
+        endpoint::region_for("cloudformation", global_opt.region()),
+    );
+
+    let git_path = opt.git_path.as_deref().unwrap_or(".");
+    let report = deployment::deployment_report(
+        &cfn,
+        &opt.stack_name,
+        &opt.change_set_name,
+        &global_opt.deployment_metadata_parameter,
+        git_path,
+    )
+    .await?;
+    // The changelog is a convenience, a report without it is still worth having.
+    if let Some(changelog_error) = &report.changelog_error {
+        eprintln!(
+            "WARNING: Failed to build the git changelog: {}",
+            changelog_error
+        );
+    }
+
+    // Values masked by CloudFormation on either side are masked on both sides by the rendering.
+    let parameter_changes = match &report.parameter_changes {
+        Some(difference) => format!(
+            "```diff\n{}\n```",
+            difference
+                .as_difference()
+                .render(DifferenceFormat::Text, |_| false, false)
+        ),
+        None => "No parameter changes.".to_owned(),
+    };
+
+    let mut sections = vec![
+        format!("# Deployment report for stack `{}`", opt.stack_name),
+        format!(
+            "Change set `{}` ({}){}",
+            report.change_set_name,
+            report.status,
+            report
+                .description
+                .as_deref()
+                .map(|description| format!(": {}", description))
+                .unwrap_or_default()
+        ),
+        format!(
+            "## Deployment\n\n{}",
+            format_metadata_rows(
+                report.deployment.currently_deployed.as_ref(),
+                report.deployment.this_deployment.as_ref()
+            )
+        ),
+        format!(
+            "## Resource changes\n\n{}",
+            format_resource_changes(&report.resource_changes.changes)
+        ),
+    ];
+    if !report.resource_changes.replacements.is_empty() {
+        sections.push(format!(
+            "**Resources that will be replaced:** {}",
+            report
+                .resource_changes
+                .replacements
+                .iter()
+                .map(|logical_id| format!("`{}`", logical_id))
+                .join(", ")
+        ));
+    }
+    sections.push(format!("## Parameter changes\n\n{}", parameter_changes));
+    if let Some(changelog) = &report.changelog {
+        sections.push(format!("## Changelog\n\n{}", format_changelog(changelog)));
+    }
+
+    Ok(AwsxOutput {
+        human_readable: sections.join("\n\n"),
+        structured: CommandOutput::success(
+            format!(
+                "Change set has {} resource changes",
+                report.resource_changes.changes.len()
+            ),
+            report,
+        ),
+        exit_code: ExitCode::Success,
+    })
+}
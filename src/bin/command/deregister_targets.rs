@@ -0,0 +1,143 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use awsx::{
+    commands::{
+        elb::{
+            deregister_targets as deregister, parse_target, wait_for_target_state,
+            TargetGroupTargets,
+        },
+        CommandOutput,
+    },
+    endpoint,
+    error::Error,
+    http,
+};
+use itertools::Itertools;
+use rusoto_elbv2::{ElbClient, TargetDescription};
+use std::time::Duration;
+use structopt::StructOpt;
+
+use crate::{
+    audit::{self, AuditEntry},
+    command::find_target_group::TargetGroupSelectorOpt,
+    AwsxOutput, AwsxProvider, ExitCode, Opt as GlobalOpt,
+};
+
+#[derive(Debug, StructOpt)]
+pub(crate) struct Opt {
+    #[structopt(flatten)]
+    target_group_selector_opt: TargetGroupSelectorOpt,
+    #[structopt(
+        long = "targets",
+        required = true,
+        parse(try_from_str = parse_target),
+        help = "Targets to deregister, e.g. `i-0123456789abcdef0 10.0.0.1:8080`",
+        long_help = "Targets to deregister, separated by spaces. Each target is specified the same \
+                     way as for `register-targets`, i.e. including the port if it was registered \
+                     with one."
+    )]
+    targets: Vec<TargetDescription>,
+    #[structopt(
+        long = "wait",
+        help = "Wait until the targets are drained",
+        long_help = "Wait until the connections to the targets are drained and the targets are no \
+                     longer registered. If this does not happen within --wait-timeout, the command \
+                     fails."
+    )]
+    wait: bool,
+    #[structopt(
+        long = "wait-timeout",
+        default_value = "600",
+        help = "Maximum number of seconds to wait for the targets to be drained"
+    )]
+    wait_timeout: u64,
+}
+
+pub(crate) async fn deregister_targets(
+    opt: &Opt,
+    global_opt: &GlobalOpt,
+    provider: AwsxProvider,
+) -> Result<AwsxOutput<CommandOutput<TargetGroupTargets>>, Error> {
+    let elb = ElbClient::new_with(
+        http::client()?,
+        provider,
+        endpoint::region_for("elasticloadbalancing", global_opt.region()),
+    );
+
+    let result = deregister(
+        &elb,
+        &opt.target_group_selector_opt.selector(),
+        &opt.targets,
+    )
+    .await;
+    let target_group_arn = result
+        .as_ref()
+        .ok()
+        .map(|deregistered| deregistered.target_group_arn.clone());
+    audit::record(
+        AuditEntry::for_resource("DeregisterTargets", target_group_arn)
+            .with_details(result.as_ref().ok()),
+        &result,
+    )
+    .await?;
+    let deregistered = result?;
+    if opt.wait {
+        // Once drained, targets are reported as `unused` until they are forgotten.
+        tokio::time::timeout(
+            Duration::from_secs(opt.wait_timeout),
+            wait_for_target_state(
+                &elb,
+                &deregistered.target_group_arn,
+                &opt.targets,
+                "unused",
+                |target_health| {
+                    eprintln!(
+                        "Waiting for targets to become unused: {}",
+                        target_health
+                            .iter()
+                            .map(|target| format!("{} {}", target.target(), target.state))
+                            .join(", ")
+                    )
+                },
+            ),
+        )
+        .await
+        .unwrap_or_else(|_| {
+            Err(Error::WaitTimeout(format!(
+                "targets of {} to become unused",
+                deregistered.target_group_arn
+            )))
+        })?;
+    }
+
+    Ok(AwsxOutput {
+        human_readable: format!(
+            "Deregistered targets from {}: {}",
+            deregistered.target_group_arn,
+            deregistered.targets.join(", ")
+        ),
+        structured: CommandOutput::success(
+            if opt.wait {
+                "Targets deregistered and drained"
+            } else {
+                "Targets deregistered successfully"
+            },
+            deregistered,
+        ),
+        exit_code: ExitCode::Success,
+    })
+}
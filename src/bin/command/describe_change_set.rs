@@ -0,0 +1,139 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use awsx::{
+    changeset::ChangeSetDescription,
+    commands::{
+        cloudformation::change_set::{self, DescribedChangeSet},
+        CommandOutput,
+    },
+    endpoint,
+    error::Error,
+    http,
+};
+use itertools::Itertools;
+use prettytable::row;
+use rusoto_cloudformation::CloudFormationClient;
+use structopt::StructOpt;
+
+use crate::{AwsxOutput, AwsxProvider, ExitCode, Opt as GlobalOpt};
+
+#[derive(Debug, StructOpt)]
+pub(crate) struct Opt {
+    #[structopt(
+        long = "stack-name",
+        help = "Name of the stack the change set belongs to"
+    )]
+    stack_name: String,
+    #[structopt(long = "change-set-name", help = "Name of the change set to describe")]
+    change_set_name: String,
+    #[structopt(
+        long = "wait",
+        help = "Wait until the change set has been created",
+        long_help = "Wait until the creation of the change set has completed before describing \
+                     it. If the creation fails, the command fails too."
+    )]
+    wait: bool,
+}
+
+/// Render the resource changes of a change set as a human readable table.
+pub(crate) fn format_change_set_description(
+    description: &ChangeSetDescription,
+) -> Result<String, Error> {
+    let mut table = prettytable::Table::new();
+    table.set_format(*prettytable::format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+    table.set_titles(row![
+        "Action",
+        "Logical ID",
+        "Physical ID",
+        "Resource type",
+        "Replacement",
+    ]);
+    for change in &description.changes {
+        table.add_row(row![
+            change.action,
+            change.logical_id,
+            change.physical_id.as_deref().unwrap_or(""),
+            change.resource_type,
+            change.replacement.as_deref().unwrap_or(""),
+        ]);
+    }
+
+    let mut human_readable = Vec::new();
+    table.print(&mut human_readable)?;
+    let human_readable =
+        String::from_utf8(human_readable).expect("prettytable did not return UTF-8");
+
+    let replacements = description.replacements();
+    let replacements = if replacements.is_empty() {
+        String::new()
+    } else {
+        format!(
+            "\nResources that will be replaced:\n{}",
+            replacements
+                .iter()
+                .map(|change| format!(
+                    "- {} ({}){}",
+                    change.logical_id,
+                    change.resource_type,
+                    if change.replacement.as_deref() == Some("Conditional") {
+                        ", conditionally"
+                    } else {
+                        ""
+                    }
+                ))
+                .join("\n")
+        )
+    };
+
+    Ok(format!(
+        "Change set {} on stack {} ({}):\n{}{}",
+        description.change_set_name,
+        description.stack_name,
+        description.status,
+        human_readable,
+        replacements,
+    ))
+}
+
+pub(crate) async fn describe_change_set(
+    opt: &Opt,
+    global_opt: &GlobalOpt,
+    provider: AwsxProvider,
+) -> Result<AwsxOutput<CommandOutput<DescribedChangeSet>>, Error> {
+    let cfn = CloudFormationClient::new_with(
+        http::client()?,
+        provider,
+        endpoint::region_for("cloudformation", global_opt.region()),
+    );
+
+    let described =
+        change_set::describe_change_set(&cfn, &opt.stack_name, &opt.change_set_name, opt.wait)
+            .await?;
+
+    Ok(AwsxOutput {
+        human_readable: format_change_set_description(&described.change_set)?,
+        structured: CommandOutput::success(
+            format!(
+                "Change set has {} changes, {} of which replace resources",
+                described.change_set.changes.len(),
+                described.replacements.len()
+            ),
+            described,
+        ),
+        exit_code: ExitCode::Success,
+    })
+}
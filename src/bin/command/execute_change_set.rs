@@ -0,0 +1,726 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use awsx::{
+    changeset::{ChangeSet, ChangeSetDescription},
+    commands::{
+        cloudformation::change_set::{
+            create_change_set, start_change_set_execution, wait_for_change_set_execution,
+            ChangeSetExecution, ChangeSetOptions, ChangeSetOutcome, CreatedChangeSet,
+            PreparedChangeSet,
+        },
+        CommandOutput,
+    },
+    endpoint,
+    error::Error,
+    http,
+    parameter::Parameters,
+    s3::S3Uploader,
+    stack::Stack,
+};
+use itertools::Itertools;
+use rusoto_cloudformation::{CloudFormation, CloudFormationClient, StackEvent};
+use serde::Serialize;
+use std::io::{BufRead, Write};
+use structopt::StructOpt;
+
+use crate::{
+    audit::{self, AuditEntry},
+    canary::{Canary, CanaryOpt},
+    command::describe_change_set::format_change_set_description,
+    guard,
+    hook::{run_hook, run_stage_hook, HookContext, HookEvent, HookPayload, HookStage},
+    notification::{self, notify},
+    redact,
+    util::{change_set_description, deployment_metadata},
+    AwsxOutput, AwsxProvider, ExitCode, Opt as GlobalOpt,
+};
+
+#[derive(Debug, StructOpt)]
+pub(crate) struct Opt {
+    #[structopt(
+        long = "stack-name",
+        help = "Name of the stack the change set belongs to"
+    )]
+    stack_name: String,
+    #[structopt(long = "change-set-name", help = "Name of the change set to execute")]
+    change_set_name: String,
+    #[structopt(
+        long = "wait",
+        help = "Wait until the execution has completed",
+        long_help = "Wait until the stack operation started by executing the change set has \
+                     completed, printing the stack events to stderr as they happen. If the \
+                     operation fails or is rolled back, the command fails too."
+    )]
+    wait: bool,
+    #[structopt(
+        long = "allow-replacement",
+        help = "Execute the change set even if it replaces resources",
+        long_help = "Execute the change set even if it replaces resources, either certainly or \
+                     conditionally. Without this option, executing such a change set has to be \
+                     confirmed interactively, and is refused if stdin is not a TTY. Change sets \
+                     removing or replacing the protected resources configured for the stack as \
+                     `protected-resources` in the configuration file are refused regardless."
+    )]
+    allow_replacement: bool,
+    #[structopt(flatten)]
+    canary_opt: CanaryOpt,
+}
+
+/// Options shared by all commands that create a change set.
+#[derive(Debug, StructOpt)]
+pub(crate) struct ChangeSetOpt {
+    #[structopt(
+        long = "wait",
+        help = "Wait until the change set has been created",
+        long_help = "Wait until the creation of the change set has completed. If --execute is \
+                     specified as well, wait until the execution has completed instead, printing \
+                     the stack events to stderr as they happen. If the creation or execution \
+                     fails, the command fails too."
+    )]
+    pub(crate) wait: bool,
+    #[structopt(
+        long = "execute",
+        help = "Execute the change set once it has been created",
+        long_help = "Execute the change set once its creation has completed. Unless --wait is \
+                     specified as well, the command will not wait for the execution to complete."
+    )]
+    pub(crate) execute: bool,
+    #[structopt(
+        long = "interactive",
+        conflicts_with = "execute",
+        help = "Review the change set and decide whether to execute it",
+        long_help = "Wait until the change set has been created, display the resource changes it \
+                     contains, and prompt whether to execute, keep or delete it. If --wait is \
+                     specified as well, wait until the execution has completed when executing \
+                     the change set. If stdin is not a TTY, the change set is kept."
+    )]
+    pub(crate) interactive: bool,
+    #[structopt(
+        long = "resource-types",
+        help = "Resource types the change set is allowed to contain",
+        long_help = "Resource types the change set is allowed to contain, e.g. `AWS::EC2::*` or \
+                     `AWS::S3::Bucket`. CloudFormation refuses to create the change set if the \
+                     template contains resources of any other type, which allows guaranteeing \
+                     that e.g. no IAM resources can be modified. All resource types are allowed \
+                     if not specified."
+    )]
+    pub(crate) resource_types: Vec<String>,
+    #[structopt(
+        long = "change-set-description",
+        help = "Description of the change set",
+        long_help = "Description of the change set, shown alongside it e.g. in the console. \
+                     Defaults to a summary of the deployment metadata, i.e. who deployed which \
+                     commit, unless the deployment metadata is not updated."
+    )]
+    pub(crate) change_set_description: Option<String>,
+    #[structopt(
+        long = "allow-replacement",
+        help = "Execute the change set even if it replaces resources",
+        long_help = "Execute the change set with --execute even if it replaces resources, either \
+                     certainly or conditionally. Without this option, executing such a change set \
+                     has to be confirmed interactively, and is refused if stdin is not a TTY. \
+                     (Executing the change set after reviewing it with --interactive counts as \
+                     confirmation.) Change sets removing or replacing protected resources are \
+                     refused regardless, see --strict."
+    )]
+    pub(crate) allow_replacement: bool,
+    #[structopt(
+        long = "strict",
+        help = "Delete change sets touching protected resources right after creating them",
+        long_help = "Wait until the change set has been created, and delete it right away if it \
+                     removes or replaces any of the protected resources configured for the stack \
+                     as `protected-resources` in the configuration file, failing the command. \
+                     Without this option, such change sets are created, but awsx refuses to \
+                     execute them."
+    )]
+    pub(crate) strict: bool,
+    #[structopt(flatten)]
+    pub(crate) canary_opt: CanaryOpt,
+}
+
+impl ChangeSetOpt {
+    /// The resource types the change set is restricted to, if any were specified.
+    pub(crate) fn resource_types(&self) -> Option<&[String]> {
+        if self.resource_types.is_empty() {
+            None
+        } else {
+            Some(&self.resource_types)
+        }
+    }
+
+    /// The description of the change set applying the parameters, falling back to the summary of
+    /// the deployment metadata they contain.
+    pub(crate) fn description(
+        &self,
+        global_opt: &GlobalOpt,
+        parameters: &Parameters,
+    ) -> Option<String> {
+        self.change_set_description
+            .clone()
+            .or_else(|| change_set_description(global_opt, parameters))
+    }
+}
+
+/// The decision made when reviewing a change set interactively.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Decision {
+    Execute,
+    Keep,
+    Delete,
+}
+
+/// Prompt on stderr until one of the decisions was entered on stdin.
+///
+/// If stdin is closed before a decision was made, the change set is kept.
+fn prompt_decision() -> Result<Decision, Error> {
+    let stdin = std::io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        eprint!("Execute, keep or delete the change set? [e/k/d]: ");
+        std::io::stderr().flush()?;
+        let line = match lines.next() {
+            Some(line) => line?,
+            None => return Ok(Decision::Keep),
+        };
+        match line.trim().to_lowercase().as_str() {
+            "e" | "execute" => return Ok(Decision::Execute),
+            "k" | "keep" => return Ok(Decision::Keep),
+            "d" | "delete" => return Ok(Decision::Delete),
+            _ => continue,
+        }
+    }
+}
+
+/// Prompt on stderr whether to execute a change set replacing resources.
+///
+/// If stdin is closed before an answer was given, the change set is not executed.
+fn prompt_replacement_confirmation() -> Result<bool, Error> {
+    let stdin = std::io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        eprint!("Execute the change set anyway? [y/n]: ");
+        std::io::stderr().flush()?;
+        let line = match lines.next() {
+            Some(line) => line?,
+            None => return Ok(false),
+        };
+        match line.trim().to_lowercase().as_str() {
+            "y" | "yes" => return Ok(true),
+            "n" | "no" => return Ok(false),
+            _ => continue,
+        }
+    }
+}
+
+/// Refuse to create or execute a change set removing or replacing protected resources.
+///
+/// Every command executing a change set has to call this, either directly or through
+/// [`verify_changes`], regardless of any option forcing the execution.
+pub(crate) fn verify_protected_resources(
+    global_opt: &GlobalOpt,
+    description: &ChangeSetDescription,
+) -> Result<(), Error> {
+    let protected = global_opt.protected_resources(&description.stack_name);
+    let protected_changes = description.protected_changes(&protected);
+    if protected_changes.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::ProtectedResourceChange(
+            protected_changes
+                .iter()
+                .map(|change| format!("{} ({})", change.logical_id, change.resource_type))
+                .join(", "),
+        ))
+    }
+}
+
+/// Refuse to execute a change set removing or replacing protected resources, as well as a change
+/// set replacing resources, unless the replacement was allowed explicitly or is confirmed
+/// interactively.
+pub(crate) async fn verify_changes(
+    cfn: &dyn CloudFormation,
+    global_opt: &GlobalOpt,
+    change_set: &ChangeSet,
+    allow_replacement: bool,
+) -> Result<(), Error> {
+    let protected_resources_configured = !global_opt
+        .protected_resources(change_set.stack_name())
+        .is_empty();
+    if allow_replacement && !protected_resources_configured {
+        return Ok(());
+    }
+    let description = change_set.describe(cfn).await?;
+    verify_protected_resources(global_opt, &description)?;
+    if allow_replacement {
+        return Ok(());
+    }
+
+    let replacements = description
+        .replacements()
+        .iter()
+        .map(|change| {
+            if change.replacement.as_deref() == Some("Conditional") {
+                format!("{} (conditionally)", change.logical_id)
+            } else {
+                change.logical_id.clone()
+            }
+        })
+        .collect::<Vec<_>>();
+    if replacements.is_empty() {
+        return Ok(());
+    }
+
+    if atty::is(atty::Stream::Stdin) {
+        eprintln!(
+            "The change set {} replaces the resources {}.",
+            change_set.name(),
+            replacements.join(", ")
+        );
+        if prompt_replacement_confirmation()? {
+            return Ok(());
+        }
+    }
+    Err(Error::ResourceReplacementRefused(format!(
+        "{} (specify --allow-replacement to execute it anyway)",
+        replacements.join(", ")
+    )))
+}
+
+/// Format a stack event as a single line, as it is printed while following the execution.
+fn format_stack_event(event: &StackEvent) -> String {
+    redact::redact_str(&format!(
+        "{} {} {} ({}){}",
+        event.timestamp,
+        event.resource_status.as_deref().unwrap_or_default(),
+        event.logical_resource_id.as_deref().unwrap_or_default(),
+        event.resource_type.as_deref().unwrap_or_default(),
+        event
+            .resource_status_reason
+            .as_deref()
+            .map(|reason| format!(": {}", reason))
+            .unwrap_or_default(),
+    ))
+}
+
+/// Execute the change set, optionally waiting for the execution to complete.
+///
+/// While waiting, the stack events are printed to stderr as they happen, followed by a summary of
+/// the resources that failed. The hook is invoked and notifications are sent when the execution
+/// starts, and once it has finished if waiting. With a canary, the execution is always waited for,
+/// and the alarms are monitored for the bake period afterwards. Change sets removing or replacing
+/// protected resources are never executed, other change sets replacing resources only if
+/// `allow_replacement` is set or the replacement is confirmed interactively.
+async fn execute<T: Serialize>(
+    cfn: &dyn CloudFormation,
+    global_opt: &GlobalOpt,
+    mut outcome: ChangeSetOutcome<T>,
+    wait: bool,
+    allow_replacement: bool,
+    canary: Option<&Canary<'_>>,
+) -> Result<AwsxOutput<CommandOutput<ChangeSetOutcome<T>>>, Error> {
+    let change_set = ChangeSet::new(&outcome.stack_name, &outcome.change_set_name);
+    verify_changes(cfn, global_opt, &change_set, allow_replacement).await?;
+    let wait = wait || canary.is_some();
+    let rollback_snapshot = match canary {
+        Some(canary) => {
+            canary.verify_alarms().await?;
+            canary.snapshot(cfn, global_opt, &change_set).await?
+        }
+        None => None,
+    };
+
+    // Once executed, the change set can no longer be described, which is why the deployment
+    // metadata for notifications is retrieved upfront.
+    let deployment_metadata = if !notification::is_enabled(global_opt) {
+        None
+    } else {
+        change_set
+            .describe(cfn)
+            .await
+            .ok()
+            .and_then(|description| deployment_metadata(global_opt, &description.parameters))
+    };
+
+    let result =
+        start_change_set_execution(cfn, change_set.stack_name(), change_set.name(), wait).await;
+    audit::record(
+        AuditEntry::new("ExecuteChangeSet", change_set.stack_name())
+            .with_change_set_name(change_set.name()),
+        &result,
+    )
+    .await?;
+    let execution = result?;
+    let payload = HookPayload::new(
+        HookEvent::ExecutionStarted,
+        change_set.stack_name(),
+        change_set.name(),
+    )
+    .with_deployment_metadata(deployment_metadata.clone());
+    run_hook(global_opt, &payload).await;
+    notify(global_opt, &payload).await;
+
+    if !wait {
+        let output = AwsxOutput {
+            human_readable: format!(
+                "Change set {} execution started successfully",
+                change_set.name()
+            ),
+            structured: CommandOutput::success(
+                "Change set execution started successfully",
+                outcome,
+            ),
+            exit_code: ExitCode::Success,
+        };
+        run_stage_hook(
+            global_opt,
+            HookStage::PostExecute,
+            &HookContext::new(change_set.stack_name(), change_set.name())
+                .with_output(&serde_json::to_value(&output.structured)?),
+        )?;
+        return Ok(output);
+    }
+
+    let mut failed_events = Vec::new();
+    let result = wait_for_change_set_execution(cfn, &execution, |event| {
+        eprintln!("{}", format_stack_event(event));
+        if event
+            .resource_status
+            .as_deref()
+            .map(|status| status.ends_with("_FAILED"))
+            .unwrap_or(false)
+        {
+            failed_events.push(event.clone());
+        }
+    })
+    .await;
+    if !failed_events.is_empty() {
+        eprintln!("Failed resources:");
+        for event in &failed_events {
+            eprintln!("- {}", format_stack_event(event));
+        }
+    }
+    let stack_status = match &result {
+        Ok(executed) => executed.stack_status.clone(),
+        Err(Error::StackOperationFailed(stack_status)) => stack_status.clone(),
+        Err(_) => String::new(),
+    };
+    let payload = HookPayload::new(
+        HookEvent::DeploymentFinished,
+        change_set.stack_name(),
+        change_set.name(),
+    )
+    .with_stack_status(&stack_status)
+    .with_deployment_metadata(deployment_metadata);
+    run_hook(global_opt, &payload).await;
+    notify(global_opt, &payload).await;
+    if let Err(Error::StackOperationFailed(_)) = &result {
+        run_stage_hook(
+            global_opt,
+            HookStage::PostExecute,
+            &HookContext::new(change_set.stack_name(), change_set.name())
+                .with_stack_status(&stack_status),
+        )?;
+    }
+    let stack_status = result?.stack_status;
+
+    let output = match canary {
+        Some(canary) => {
+            let report = canary.bake().await?;
+            if report.is_success() {
+                let human_readable = format!(
+                    "Change set {} executed successfully, stack {} is {}, no alarm fired during \
+                     the bake period",
+                    change_set.name(),
+                    change_set.stack_name(),
+                    stack_status,
+                );
+                outcome.execution = Some(ChangeSetExecution {
+                    stack_status: Some(stack_status.clone()),
+                    canary: Some(report),
+                    rollback: None,
+                });
+                AwsxOutput {
+                    human_readable,
+                    structured: CommandOutput::success(
+                        "Change set executed successfully, no alarm fired",
+                        outcome,
+                    ),
+                    exit_code: ExitCode::Success,
+                }
+            } else {
+                let rollback = match &rollback_snapshot {
+                    Some(rollback_snapshot) => Some(
+                        canary
+                            .roll_back(cfn, global_opt, &change_set, rollback_snapshot)
+                            .await?,
+                    ),
+                    None => None,
+                };
+                let human_readable = format!(
+                    "Change set {} executed successfully, but the canary failed: alarms {} fired{}",
+                    change_set.name(),
+                    report.fired.join(", "),
+                    match &rollback {
+                        Some(rollback) => format!(
+                            ", stack {} was rolled back through change set {}",
+                            change_set.stack_name(),
+                            rollback.change_set_name
+                        ),
+                        None => String::new(),
+                    },
+                );
+                outcome.execution = Some(ChangeSetExecution {
+                    stack_status: Some(stack_status.clone()),
+                    canary: Some(report),
+                    rollback,
+                });
+                AwsxOutput {
+                    human_readable,
+                    structured: CommandOutput::failure(
+                        "Change set executed successfully, but alarms fired",
+                        outcome,
+                    ),
+                    exit_code: ExitCode::Failure,
+                }
+            }
+        }
+        None => {
+            let human_readable = format!(
+                "Change set {} executed successfully, stack {} is {}",
+                change_set.name(),
+                change_set.stack_name(),
+                stack_status,
+            );
+            outcome.execution = Some(ChangeSetExecution {
+                stack_status: Some(stack_status.clone()),
+                ..Default::default()
+            });
+            AwsxOutput {
+                human_readable,
+                structured: CommandOutput::success("Change set executed successfully", outcome),
+                exit_code: ExitCode::Success,
+            }
+        }
+    };
+    run_stage_hook(
+        global_opt,
+        HookStage::PostExecute,
+        &HookContext::new(change_set.stack_name(), change_set.name())
+            .with_stack_status(&stack_status)
+            .with_output(&serde_json::to_value(&output.structured)?),
+    )?;
+    Ok(output)
+}
+
+/// Create the prepared change set, once the template passed the guard rules and the hooks of the
+/// [`HookStage::PreChangeSet`] stage, invoking the hook and sending notifications afterwards.
+///
+/// The warnings raised while preparing the change set are printed first. Its parameters have to be
+/// registered for redaction and validated beforehand.
+pub(crate) async fn create_prepared_change_set<T>(
+    opt: &ChangeSetOpt,
+    cfn: &dyn CloudFormation,
+    global_opt: &GlobalOpt,
+    prepared: &PreparedChangeSet<T>,
+    role_arn: Option<&str>,
+    s3_upload: Option<(&S3Uploader, &str)>,
+) -> Result<CreatedChangeSet, Error> {
+    for warning in &prepared.warnings {
+        eprintln!("WARNING: {}", redact::redact_str(warning));
+    }
+
+    // If the deployed template is kept, it is only retrieved if there are guard rules to validate.
+    let deployed_template = match &prepared.template {
+        None if !global_opt.guard_rules.is_empty() => {
+            Some(Stack::new(&prepared.stack_name).get_template(cfn).await?)
+        }
+        _ => None,
+    };
+    let template = prepared.template.as_ref().or(deployed_template.as_ref());
+    if let Some(template) = template {
+        guard::validate_template(global_opt, template, &prepared.parameters)?;
+    }
+    run_stage_hook(
+        global_opt,
+        HookStage::PreChangeSet,
+        &HookContext::new(&prepared.stack_name, &prepared.change_set_name),
+    )?;
+
+    let description = opt.description(global_opt, &prepared.parameters);
+    let result = create_change_set(
+        cfn,
+        prepared,
+        &ChangeSetOptions {
+            role_arn,
+            resource_types: opt.resource_types(),
+            description: description.as_deref(),
+            s3_upload,
+        },
+    )
+    .await;
+    // The checksum of a kept template is only recorded if it was retrieved anyway.
+    let mut entry = AuditEntry::new("CreateChangeSet", &prepared.stack_name)
+        .with_change_set_name(&prepared.change_set_name)
+        .with_parameters(&prepared.parameters);
+    if let Some(template) = template {
+        entry = entry.with_template(template);
+    }
+    audit::record(entry, &result).await?;
+    let created = result?;
+
+    let payload = HookPayload::new(
+        HookEvent::ChangeSetCreated,
+        &created.stack_name,
+        &created.change_set_name,
+    )
+    .with_deployment_metadata(deployment_metadata(global_opt, &prepared.parameters));
+    run_hook(global_opt, &payload).await;
+    notify(global_opt, &payload).await;
+
+    Ok(created)
+}
+
+/// Handle a freshly created change set according to the shared change set options.
+///
+/// If neither waiting nor execution was requested, the output provided by the creating command is
+/// returned as-is.
+pub(crate) async fn handle_created_change_set<T: Serialize>(
+    opt: &ChangeSetOpt,
+    cfn: &dyn CloudFormation,
+    global_opt: &GlobalOpt,
+    provider: &AwsxProvider,
+    output: AwsxOutput<CommandOutput<ChangeSetOutcome<T>>>,
+) -> Result<AwsxOutput<CommandOutput<ChangeSetOutcome<T>>>, Error> {
+    let change_set = ChangeSet::new(
+        &output.structured.result.stack_name,
+        &output.structured.result.change_set_name,
+    );
+    run_stage_hook(
+        global_opt,
+        HookStage::PostChangeSetCreate,
+        &HookContext::new(change_set.stack_name(), change_set.name())
+            .with_output(&serde_json::to_value(&output.structured)?),
+    )?;
+
+    if opt.strict {
+        let description = change_set.wait_until_created(cfn).await?;
+        if let Err(error) = verify_protected_resources(global_opt, &description) {
+            let result = change_set.delete(cfn).await;
+            audit::record(
+                AuditEntry::new("DeleteChangeSet", change_set.stack_name())
+                    .with_change_set_name(change_set.name()),
+                &result,
+            )
+            .await?;
+            result?;
+            return Err(error);
+        }
+    }
+
+    if opt.canary_opt.canary && !opt.execute && !opt.interactive {
+        eprintln!(
+            "WARNING: --canary has no effect, since the change set will not be executed. Specify \
+             --execute or --interactive to execute it."
+        );
+    }
+    if !opt.wait && !opt.execute && !opt.interactive {
+        return Ok(output);
+    }
+    let canary = Canary::new(&opt.canary_opt, global_opt, provider)?;
+    let mut outcome = output.structured.result;
+
+    let description = change_set.wait_until_created(cfn).await?;
+    if opt.interactive {
+        let decision = if atty::is(atty::Stream::Stdin) {
+            eprintln!("{}", format_change_set_description(&description)?);
+            prompt_decision()?
+        } else {
+            eprintln!(
+                "WARNING: an interactive review of the change set was requested, but stdin is not \
+                 a TTY. The change set will be kept without executing it."
+            );
+            Decision::Keep
+        };
+        match decision {
+            Decision::Execute => {
+                // The replaced resources were shown as part of the review, so the decision to
+                // execute confirms their replacement.
+                return execute(cfn, global_opt, outcome, opt.wait, true, canary.as_ref()).await;
+            }
+            Decision::Delete => {
+                let result = change_set.delete(cfn).await;
+                audit::record(
+                    AuditEntry::new("DeleteChangeSet", change_set.stack_name())
+                        .with_change_set_name(change_set.name()),
+                    &result,
+                )
+                .await?;
+                result?;
+                outcome.change_set = Some(description);
+                return Ok(AwsxOutput {
+                    human_readable: format!("Change set {} deleted", change_set.name()),
+                    structured: CommandOutput::success("Change set deleted", outcome),
+                    exit_code: ExitCode::Success,
+                });
+            }
+            Decision::Keep => {}
+        }
+    }
+
+    if opt.execute {
+        execute(
+            cfn,
+            global_opt,
+            outcome,
+            opt.wait,
+            opt.allow_replacement,
+            canary.as_ref(),
+        )
+        .await
+    } else {
+        let human_readable = format_change_set_description(&description)?;
+        outcome.change_set = Some(description);
+        Ok(AwsxOutput {
+            human_readable,
+            structured: CommandOutput::success("Change set created successfully", outcome),
+            exit_code: ExitCode::Success,
+        })
+    }
+}
+
+pub(crate) async fn execute_change_set(
+    opt: &Opt,
+    global_opt: &GlobalOpt,
+    provider: AwsxProvider,
+) -> Result<AwsxOutput<CommandOutput<ChangeSetOutcome>>, Error> {
+    let cfn = CloudFormationClient::new_with(
+        http::client()?,
+        provider.clone(),
+        endpoint::region_for("cloudformation", global_opt.region()),
+    );
+    let canary = Canary::new(&opt.canary_opt, global_opt, &provider)?;
+
+    execute(
+        &cfn,
+        global_opt,
+        ChangeSetOutcome::existing(&opt.stack_name, &opt.change_set_name),
+        opt.wait,
+        opt.allow_replacement,
+        canary.as_ref(),
+    )
+    .await
+}
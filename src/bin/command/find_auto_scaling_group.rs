@@ -14,121 +14,160 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 
-use awsx::error::Error;
-use rusoto_autoscaling::{AutoScalingGroupNamesType, Autoscaling, AutoscalingClient};
-use rusoto_core::HttpClient;
-use serde_json::json;
-use std::str::FromStr;
+use awsx::{
+    commands::{
+        auto_scaling::{
+            stream_auto_scaling_groups, AutoScalingGroupDetails, AutoScalingGroupSelector,
+            FoundAutoScalingGroups,
+        },
+        CommandOutput,
+    },
+    endpoint,
+    error::Error,
+    http,
+    tags::{Tag, TagFilter},
+};
+use futures::{future, TryStreamExt};
+use itertools::Itertools;
+use rusoto_autoscaling::{AutoScalingGroup, AutoscalingClient};
 use structopt::StructOpt;
 
-use crate::{AwsxOutput, AwsxProvider, Opt as GlobalOpt};
-
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub(crate) struct Tag {
-    key: String,
-    value: String,
-}
-
-impl FromStr for Tag {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut split = s.splitn(2, '=');
-
-        Ok(Tag {
-            key: split
-                .next()
-                .ok_or_else(|| "Tag needs to be provided in the form `Key=Value`".to_owned())?
-                .to_owned(),
-            value: split
-                .next()
-                .ok_or_else(|| "Tag needs to be provided in the form `Key=Value`".to_owned())?
-                .to_owned(),
-        })
-    }
-}
+use crate::{stream, AwsxOutput, AwsxProvider, ExitCode, Opt as GlobalOpt};
 
 #[derive(Debug, StructOpt)]
 pub(crate) struct Opt {
     #[structopt(
         long = "tags",
-        required = true,
+        required_unless = "not-tags",
         help = "Filter for auto-scaling groups by their tags",
         long_help = "Filter for auto-scaling groups by their tags. Specify multiple `Key=Value` \
                      pairs, separated by spaces, where each key-value-pair corresponds to a tag \
-                     assigned to the auto-scaling groups."
+                     assigned to the auto-scaling groups. A value containing `*` matches any \
+                     characters in its place, e.g. `Version=1.4.*`, and `Key~=Regex` matches the \
+                     value against a regex. A key without a value, e.g. `Key`, matches any value \
+                     of the tag."
     )]
     tags: Vec<Tag>,
+    #[structopt(
+        long = "not-tags",
+        help = "Exclude auto-scaling groups by their tags",
+        long_help = "Exclude auto-scaling groups that have any of the given tags assigned. The \
+                     tags are specified the same way as for --tags, e.g. `Key` excludes all \
+                     auto-scaling groups that have the tag assigned, regardless of its value."
+    )]
+    not_tags: Vec<Tag>,
+    #[structopt(
+        long = "all",
+        help = "Return all matching auto-scaling groups instead of only the first one"
+    )]
+    all: bool,
+}
+
+/// Options to select a single auto-scaling group by its name or by its tags.
+#[derive(Debug, StructOpt)]
+pub(crate) struct AutoScalingGroupSelectorOpt {
+    #[structopt(
+        long = "auto-scaling-group-name",
+        required_unless_one = &["tags", "not-tags"],
+        conflicts_with_all = &["tags", "not-tags"],
+        help = "Name of the auto-scaling group"
+    )]
+    auto_scaling_group_name: Option<String>,
+    #[structopt(
+        long = "tags",
+        help = "Select the auto-scaling group by its tags",
+        long_help = "Select the auto-scaling group by its tags instead of its name, which have to \
+                     match exactly one auto-scaling group. The tags are specified the same way as \
+                     for `find-auto-scaling-group`."
+    )]
+    tags: Vec<Tag>,
+    #[structopt(
+        long = "not-tags",
+        help = "Exclude auto-scaling groups by their tags when selecting by tags"
+    )]
+    not_tags: Vec<Tag>,
+}
+
+impl AutoScalingGroupSelectorOpt {
+    /// The selector of the auto-scaling group the command operates on.
+    pub(crate) fn selector(&self) -> AutoScalingGroupSelector {
+        match &self.auto_scaling_group_name {
+            Some(name) => AutoScalingGroupSelector::Name(name.clone()),
+            None => AutoScalingGroupSelector::Tags(
+                TagFilter::new(self.tags.clone()).excluding(self.not_tags.clone()),
+            ),
+        }
+    }
 }
 
 pub(crate) async fn find_auto_scaling_group(
     opt: &Opt,
     global_opt: &GlobalOpt,
     provider: AwsxProvider,
-) -> Result<AwsxOutput, Error> {
+) -> Result<AwsxOutput<CommandOutput<FoundAutoScalingGroups>>, Error> {
+    let tag_filter = TagFilter::new(opt.tags.clone()).excluding(opt.not_tags.clone());
     let autoscaling = AutoscalingClient::new_with(
-        HttpClient::new()?,
+        http::client()?,
         provider,
-        global_opt.aws_region.clone().unwrap_or_default(),
+        endpoint::region_for("autoscaling", global_opt.region()),
     );
 
-    let mut auto_scaling_groups = Vec::new();
-    let mut continuation_token: Option<String> = None;
-    while {
-        let mut output = autoscaling
-            .describe_auto_scaling_groups(AutoScalingGroupNamesType {
-                next_token: continuation_token.clone(),
-                ..Default::default()
-            })
-            .await?;
-        continuation_token = output.next_token;
-        auto_scaling_groups.append(&mut output.auto_scaling_groups);
-
-        continuation_token.is_some()
-    } {}
-
-    let auto_scaling_group =
-        auto_scaling_groups
-            .into_iter()
-            .find(|auto_scaling_group| match &auto_scaling_group.tags {
-                Some(resource_tags) => opt.tags.iter().all(|needle| {
-                    resource_tags.iter().any(|haystack| {
-                        haystack
-                            .key
-                            .as_ref()
-                            .map(|key| key == &needle.key)
-                            .unwrap_or(false)
-                            && haystack
-                                .value
-                                .as_ref()
-                                .map(|value| value == &needle.value)
-                                .unwrap_or(false)
-                    })
-                }),
-                None => false,
-            });
+    // With --all, the matching groups are streamed as soon as their page was retrieved.
+    let streaming = opt.all && stream::start();
+    let auto_scaling_groups: Vec<AutoScalingGroup> = stream_auto_scaling_groups(&autoscaling, None)
+        .try_filter(|auto_scaling_group| {
+            future::ready(
+                tag_filter.matches(auto_scaling_group.tags.as_deref().unwrap_or_default()),
+            )
+        })
+        .inspect_ok(|auto_scaling_group| {
+            if streaming {
+                stream::emit(&AutoScalingGroupDetails::from(auto_scaling_group));
+            }
+        })
+        .try_collect()
+        .await?;
+    let mut auto_scaling_groups = auto_scaling_groups.into_iter().peekable();
 
-    match auto_scaling_group {
-        Some(auto_scaling_group) => Ok(AwsxOutput {
+    Ok(match auto_scaling_groups.peek() {
+        Some(_) if opt.all => {
+            let auto_scaling_groups: Vec<AutoScalingGroup> = auto_scaling_groups.collect();
+            AwsxOutput {
+                human_readable: auto_scaling_groups
+                    .iter()
+                    .map(|auto_scaling_group| &auto_scaling_group.auto_scaling_group_name)
+                    .join("\n"),
+                structured: CommandOutput::success(
+                    "Found auto-scaling groups matching given filters",
+                    FoundAutoScalingGroups::All {
+                        auto_scaling_groups: auto_scaling_groups
+                            .iter()
+                            .map(AutoScalingGroupDetails::from)
+                            .collect(),
+                    },
+                ),
+                exit_code: ExitCode::Success,
+            }
+        }
+        Some(auto_scaling_group) => AwsxOutput {
             human_readable: auto_scaling_group.auto_scaling_group_name.clone(),
-            structured: json!({
-                "success": true,
-                "message": "Found auto-scaling group matching given filters",
-                "auto_scaling_group_arn":  auto_scaling_group
-                    .auto_scaling_group_arn
-                    .map(serde_json::Value::String)
-                    .unwrap_or_else(|| serde_json::Value::Null),
-                "auto_scaling_group_name": &auto_scaling_group.auto_scaling_group_name,
-            }),
-            successful: true,
-        }),
-        None => Ok(AwsxOutput {
+            structured: CommandOutput::success(
+                "Found auto-scaling group matching given filters",
+                FoundAutoScalingGroups::First {
+                    auto_scaling_group_arn: auto_scaling_group.auto_scaling_group_arn.clone(),
+                    auto_scaling_group_name: auto_scaling_group.auto_scaling_group_name.clone(),
+                    auto_scaling_group: Box::new(AutoScalingGroupDetails::from(auto_scaling_group)),
+                },
+            ),
+            exit_code: ExitCode::Success,
+        },
+        None => AwsxOutput {
             human_readable: "Unable to find auto-scaling group matching given filters".to_owned(),
-            structured: json!({
-                "success": false,
-                "message": "Unable to find auto-scaling group matching given filters",
-            }),
-            successful: false,
-        }),
-    }
+            structured: CommandOutput::failure(
+                "Unable to find auto-scaling group matching given filters",
+                FoundAutoScalingGroups::None,
+            ),
+            exit_code: ExitCode::Failure,
+        },
+    })
 }
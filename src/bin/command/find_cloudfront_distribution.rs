@@ -14,139 +14,121 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 
-use awsx::error::Error;
-use futures::stream::{FuturesOrdered, TryStreamExt};
-use rusoto_cloudfront::{
-    CloudFront, CloudFrontClient, ListDistributionsRequest, ListTagsForResourceRequest,
+use awsx::{
+    commands::{
+        cloudfront::{
+            find_cloudfront_distribution as find_distribution, DistributionSelector,
+            FoundDistribution,
+        },
+        CommandOutput,
+    },
+    error::Error,
+    http,
+    tags::{Tag, TagFilter},
 };
-use rusoto_core::{HttpClient, Region};
-use serde_json::json;
-use std::str::FromStr;
+use rusoto_cloudfront::CloudFrontClient;
+use rusoto_core::Region;
 use structopt::StructOpt;
 
-use crate::{AwsxOutput, AwsxProvider, Opt as GlobalOpt};
-
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub(crate) struct Tag {
-    key: String,
-    value: String,
-}
-
-impl FromStr for Tag {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut split = s.splitn(2, '=');
-
-        Ok(Tag {
-            key: split
-                .next()
-                .ok_or_else(|| "Tag needs to be provided in the form `Key=Value`".to_owned())?
-                .to_owned(),
-            value: split
-                .next()
-                .ok_or_else(|| "Tag needs to be provided in the form `Key=Value`".to_owned())?
-                .to_owned(),
-        })
-    }
-}
+use crate::{AwsxOutput, AwsxProvider, ExitCode, Opt as GlobalOpt};
 
 #[derive(Debug, StructOpt)]
 pub(crate) struct Opt {
     #[structopt(
         long = "tags",
-        required = true,
+        required_unless = "not-tags",
         help = "Filter for CloudFront distributions by their tags",
         long_help = "Filter for CloudFront distributions by their tags. Specify multiple \
                      `Key=Value` pairs, separated by spaces, where each key-value-pair corresponds \
-                     to a tag assigned to the CloudFront distributions."
+                     to a tag assigned to the CloudFront distributions. A value containing `*` \
+                     matches any characters in its place, e.g. `Version=1.4.*`, and `Key~=Regex` \
+                     matches the value against a regex. A key without a value, e.g. `Key`, matches \
+                     any value of the tag."
     )]
     tags: Vec<Tag>,
+    #[structopt(
+        long = "not-tags",
+        help = "Exclude CloudFront distributions by their tags",
+        long_help = "Exclude CloudFront distributions that have any of the given tags assigned. \
+                     The tags are specified the same way as for --tags, e.g. `Key` excludes all \
+                     CloudFront distributions that have the tag assigned, regardless of its value."
+    )]
+    not_tags: Vec<Tag>,
 }
 
-pub(crate) async fn find_cloudfront_distribution(
-    opt: &Opt,
-    _global_opt: &GlobalOpt,
-    provider: AwsxProvider,
-) -> Result<AwsxOutput, Error> {
-    let cloudfront = CloudFrontClient::new_with(
-        HttpClient::new()?,
+/// Options to select a single CloudFront distribution by its ID or by its tags.
+#[derive(Debug, StructOpt)]
+pub(crate) struct DistributionSelectorOpt {
+    #[structopt(
+        long = "distribution-id",
+        required_unless_one = &["tags", "not-tags"],
+        conflicts_with_all = &["tags", "not-tags"],
+        help = "ID of the CloudFront distribution"
+    )]
+    distribution_id: Option<String>,
+    #[structopt(
+        long = "tags",
+        help = "Select the CloudFront distribution by its tags",
+        long_help = "Select the CloudFront distribution by its tags instead of its ID, which have \
+                     to match exactly one distribution. The tags are specified the same way as \
+                     for `find-cloudfront-distribution`."
+    )]
+    tags: Vec<Tag>,
+    #[structopt(
+        long = "not-tags",
+        help = "Exclude CloudFront distributions by their tags when selecting by tags"
+    )]
+    not_tags: Vec<Tag>,
+}
+
+impl DistributionSelectorOpt {
+    /// The selector of the CloudFront distribution the command operates on.
+    pub(crate) fn selector(&self) -> DistributionSelector {
+        match &self.distribution_id {
+            Some(distribution_id) => DistributionSelector::Id(distribution_id.clone()),
+            None => DistributionSelector::Tags(
+                TagFilter::new(self.tags.clone()).excluding(self.not_tags.clone()),
+            ),
+        }
+    }
+}
+
+/// Create a CloudFront client.
+pub(crate) fn cloudfront_client(provider: AwsxProvider) -> Result<CloudFrontClient, Error> {
+    Ok(CloudFrontClient::new_with(
+        http::client()?,
         provider,
         // The region for CloudFront is hardcoded! Given that CloudFront is a global service, its
         // API is only valid within us-east-1 -- every other region returns an error.
         Region::UsEast1,
-    );
-
-    let mut cloudfront_distributions = Vec::new();
-    let mut continuation_token: Option<String> = None;
-    while {
-        let output = cloudfront
-            .list_distributions(ListDistributionsRequest {
-                marker: continuation_token.clone(),
-                ..Default::default()
-            })
-            .await?;
-        if let Some(distribution_list) = output.distribution_list {
-            continuation_token = distribution_list.next_marker;
-            if let Some(mut items) = distribution_list.items {
-                cloudfront_distributions.append(&mut items);
-            }
-        } else {
-            continuation_token = None;
-        }
-
-        continuation_token.is_some()
-    } {}
+    ))
+}
 
-    let cloudfront_distribution = cloudfront_distributions
-        .into_iter()
-        .map(|distribution| async {
-            cloudfront
-                .list_tags_for_resource(ListTagsForResourceRequest {
-                    resource: distribution.arn.clone(),
-                })
-                .await
-                .map(|tags| (distribution, tags.tags.items))
-        })
-        .collect::<FuturesOrdered<_>>()
-        .try_collect::<Vec<_>>()
-        .await?
-        .into_iter()
-        .filter_map(|(distribution, tags)| tags.map(|tags| (distribution, tags)))
-        .filter(|(_, resource_tags)| {
-            opt.tags.iter().all(|needle| {
-                resource_tags.iter().any(|haystack| {
-                    haystack.key == needle.key
-                        && haystack
-                            .value
-                            .as_ref()
-                            .map(|value| value == &needle.value)
-                            .unwrap_or(false)
-                })
-            })
-        })
-        .map(|(distribution, _)| distribution)
-        .next();
+pub(crate) async fn find_cloudfront_distribution(
+    opt: &Opt,
+    _global_opt: &GlobalOpt,
+    provider: AwsxProvider,
+) -> Result<AwsxOutput<CommandOutput<Option<FoundDistribution>>>, Error> {
+    let tag_filter = TagFilter::new(opt.tags.clone()).excluding(opt.not_tags.clone());
+    let cloudfront = cloudfront_client(provider)?;
 
-    match cloudfront_distribution {
-        Some(cloudfront_distribution) => Ok(AwsxOutput {
-            human_readable: cloudfront_distribution.id.clone(),
-            structured: json!({
-                "success": true,
-                "message": "Found CloudFront distribution matching given filters",
-                "cloudfront_distribution_arn": &cloudfront_distribution.arn,
-                "cloudfront_distribution_id": &cloudfront_distribution.id,
-            }),
-            successful: true,
+    match find_distribution(&cloudfront, &tag_filter).await? {
+        Some(distribution) => Ok(AwsxOutput {
+            human_readable: distribution.cloudfront_distribution_id.clone(),
+            structured: CommandOutput::success(
+                "Found CloudFront distribution matching given filters",
+                Some(distribution),
+            ),
+            exit_code: ExitCode::Success,
         }),
         None => Ok(AwsxOutput {
             human_readable: "Unable to find CloudFront distribution matching given filters"
                 .to_owned(),
-            structured: json!({
-                "success": false,
-                "message": "Unable to find CloudFront distribution matching given filters",
-            }),
-            successful: false,
+            structured: CommandOutput::failure(
+                "Unable to find CloudFront distribution matching given filters",
+                None,
+            ),
+            exit_code: ExitCode::Failure,
         }),
     }
 }
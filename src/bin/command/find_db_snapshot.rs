@@ -14,83 +14,24 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 
-use std::str::FromStr;
+use std::num::NonZeroUsize;
 
-use futures::stream::{self, StreamExt};
-use rusoto_core::HttpClient;
-use rusoto_rds::{
-    DBSnapshot, DescribeDBSnapshotsMessage, ListTagsForResourceMessage, Rds, RdsClient,
-};
-use serde_json::json;
+use chrono::{DateTime, Utc};
+use rusoto_rds::RdsClient;
 use structopt::StructOpt;
 
-use awsx::error::Error;
-
-use crate::{AwsxOutput, AwsxProvider, Opt as GlobalOpt};
-
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub(crate) struct Tag {
-    key: String,
-    value: String,
-}
-
-impl FromStr for Tag {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut split = s.splitn(2, '=');
-
-        Ok(Tag {
-            key: split
-                .next()
-                .ok_or_else(|| "Tag needs to be provided in the form `Key=Value`".to_owned())?
-                .to_owned(),
-            value: split
-                .next()
-                .ok_or_else(|| "Tag needs to be provided in the form `Key=Value`".to_owned())?
-                .to_owned(),
-        })
-    }
-}
-
-mod serde_remote {
-    use serde::Serialize;
-
-    #[derive(Serialize)]
-    #[serde(remote = "rusoto_rds::DBSnapshot")]
-    struct DBSnapshotProxy {
-        pub allocated_storage: Option<i64>,
-        pub availability_zone: Option<String>,
-        pub db_instance_identifier: Option<String>,
-        pub db_snapshot_arn: Option<String>,
-        pub db_snapshot_identifier: Option<String>,
-        pub dbi_resource_id: Option<String>,
-        pub encrypted: Option<bool>,
-        pub engine: Option<String>,
-        pub engine_version: Option<String>,
-        pub iam_database_authentication_enabled: Option<bool>,
-        pub instance_create_time: Option<String>,
-        pub iops: Option<i64>,
-        pub kms_key_id: Option<String>,
-        pub license_model: Option<String>,
-        pub master_username: Option<String>,
-        pub option_group_name: Option<String>,
-        pub percent_progress: Option<i64>,
-        pub port: Option<i64>,
-        pub snapshot_create_time: Option<String>,
-        pub snapshot_type: Option<String>,
-        pub source_db_snapshot_identifier: Option<String>,
-        pub source_region: Option<String>,
-        pub status: Option<String>,
-        pub storage_type: Option<String>,
-        pub tde_credential_arn: Option<String>,
-        pub timezone: Option<String>,
-        pub vpc_id: Option<String>,
-    }
+use awsx::{
+    commands::{
+        rds::{find_db_snapshot as find, FoundDbSnapshot, SnapshotFilter, SnapshotQuery},
+        CommandOutput,
+    },
+    endpoint,
+    error::Error,
+    http,
+    tags::{Tag, TagFilter},
+};
 
-    #[derive(Serialize)]
-    pub struct DBSnapshot(#[serde(with = "DBSnapshotProxy")] pub rusoto_rds::DBSnapshot);
-}
+use crate::{AwsxOutput, AwsxProvider, ExitCode, Opt as GlobalOpt};
 
 #[derive(Debug, StructOpt)]
 pub(crate) struct Opt {
@@ -106,108 +47,123 @@ pub(crate) struct Opt {
         help = "Filter for target groups by their tags",
         long_help = "Filter for DB snapshots by their tags. Specify multiple `Key=Value` pairs, \
                      separated by spaces, where each key-value-pair corresponds to a tag assigned \
-                     to the DB snapshot."
+                     to the DB snapshot. A value containing `*` matches any characters in its \
+                     place, e.g. `Version=1.4.*`, and `Key~=Regex` matches the value against a \
+                     regex. A key without a value, e.g. `Key`, matches any value of the tag."
     )]
     tags: Vec<Tag>,
+    #[structopt(
+        long = "not-tags",
+        help = "Exclude DB snapshots by their tags",
+        long_help = "Exclude DB snapshots that have any of the given tags assigned. The tags are \
+                     specified the same way as for --tags, e.g. `Key` excludes all DB snapshots \
+                     that have the tag assigned, regardless of its value."
+    )]
+    not_tags: Vec<Tag>,
+    #[structopt(
+        long = "concurrency",
+        default_value = "10",
+        help = "Maximum number of concurrent tag lookups",
+        long_help = "Maximum number of DB snapshots to retrieve the tags of concurrently. The \
+                     command fails if the tags of any snapshot cannot be retrieved; lower this \
+                     value if the requests are throttled."
+    )]
+    concurrency: NonZeroUsize,
+    #[structopt(flatten)]
+    snapshot_filter_opt: SnapshotFilterOpt,
+}
+
+/// Filters shared by the commands finding DB snapshots and DB cluster snapshots.
+#[derive(Debug, StructOpt)]
+pub(crate) struct SnapshotFilterOpt {
+    #[structopt(
+        long = "engine",
+        help = "Filter snapshots by the engine of their database, e.g. `postgres`"
+    )]
+    engine: Option<String>,
+    #[structopt(
+        long = "status",
+        help = "Filter snapshots by their status, e.g. `available`"
+    )]
+    status: Option<String>,
+    #[structopt(
+        long = "min-create-time",
+        help = "Only consider snapshots created at or after the given time",
+        long_help = "Only consider snapshots created at or after the given time, specified in RFC \
+                     3339 format, e.g. `2025-01-01T00:00:00Z`."
+    )]
+    min_create_time: Option<DateTime<Utc>>,
+    #[structopt(
+        long = "max-create-time",
+        help = "Only consider snapshots created at or before the given time",
+        long_help = "Only consider snapshots created at or before the given time, specified in \
+                     RFC 3339 format, e.g. `2025-01-01T00:00:00Z`."
+    )]
+    max_create_time: Option<DateTime<Utc>>,
+    #[structopt(
+        long = "include-shared",
+        help = "Include snapshots shared with this account by other accounts",
+        long_help = "Include snapshots shared with this account by other accounts. The tags of \
+                     these snapshots can't be retrieved, so they are never found when filtering \
+                     by tags."
+    )]
+    include_shared: bool,
+    #[structopt(
+        long = "include-public",
+        help = "Include public snapshots",
+        long_help = "Include public snapshots. The tags of these snapshots can't be retrieved, so \
+                     they are never found when filtering by tags."
+    )]
+    include_public: bool,
+}
+
+impl SnapshotFilterOpt {
+    /// The filters on the properties of the snapshots.
+    pub(crate) fn filter(&self) -> SnapshotFilter {
+        SnapshotFilter {
+            engine: self.engine.clone(),
+            status: self.status.clone(),
+            min_create_time: self.min_create_time,
+            max_create_time: self.max_create_time,
+            include_shared: self.include_shared,
+            include_public: self.include_public,
+        }
+    }
 }
 
 pub(crate) async fn find_db_snapshot(
     opt: &Opt,
     global_opt: &GlobalOpt,
     provider: AwsxProvider,
-) -> Result<AwsxOutput, Error> {
+) -> Result<AwsxOutput<CommandOutput<Option<FoundDbSnapshot>>>, Error> {
     let rds = RdsClient::new_with(
-        HttpClient::new()?,
+        http::client()?,
         provider,
-        global_opt.aws_region.clone().unwrap_or_default(),
+        endpoint::region_for("rds", global_opt.region()),
     );
 
-    let mut db_snapshots = Vec::new();
-    let mut continuation_token: Option<String> = None;
-    while {
-        let mut output = rds
-            .describe_db_snapshots(DescribeDBSnapshotsMessage {
-                db_instance_identifier: opt.db_instance_identifier.clone(),
-                snapshot_type: opt.snapshot_type.clone(),
-                marker: continuation_token.clone(),
-                ..Default::default()
-            })
-            .await?;
-        continuation_token = output.marker;
-        if let Some(new_db_snapshots) = output.db_snapshots.as_mut() {
-            db_snapshots.append(new_db_snapshots)
-        }
-
-        continuation_token.is_some()
-    } {}
-
-    let enriched_db_snapshots: Vec<(DBSnapshot, Vec<rusoto_rds::Tag>)> =
-        stream::iter(db_snapshots.into_iter())
-            .filter_map(|db_snapshot| async {
-                if let Some(db_snapshot_arn) = &db_snapshot.db_snapshot_arn {
-                    let tags = rds
-                        .list_tags_for_resource(ListTagsForResourceMessage {
-                            resource_name: db_snapshot_arn.clone(),
-                            ..Default::default()
-                        })
-                        .await;
-                    if let Ok(tags) = tags {
-                        tags.tag_list.map(|tag_list| (db_snapshot, tag_list))
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            })
-            .collect::<Vec<_>>()
-            .await;
-
-    let matching_db_snapshot = enriched_db_snapshots
-        .into_iter()
-        .filter(|(_, tag_list)| {
-            opt.tags.iter().all(|needle| {
-                tag_list.iter().any(|haystack| {
-                    haystack
-                        .key
-                        .as_ref()
-                        .map(|key| key == &needle.key)
-                        .unwrap_or(false)
-                        && haystack
-                            .value
-                            .as_ref()
-                            .map(|value| value == &needle.value)
-                            .unwrap_or(false)
-                })
-            })
-        })
-        .map(|(db_snapshot, _)| db_snapshot)
-        .max_by_key(|db_snapshot| db_snapshot.snapshot_create_time.clone());
-
-    match matching_db_snapshot {
-        Some(db_snapshot) if db_snapshot.db_snapshot_arn.is_some() => {
-            let db_snapshot_arn = db_snapshot
-                .db_snapshot_arn
-                .as_ref()
-                .expect("DB snapshot ARN should exist");
-            Ok(AwsxOutput {
-                human_readable: db_snapshot_arn.to_owned(),
-                structured: json!({
-                    "success": true,
-                    "message": "Found DB-snapshot matching given filters",
-                    "db_snapshot_arn": db_snapshot_arn,
-                    "db_snapshot": serde_remote::DBSnapshot(db_snapshot),
-                }),
-                successful: true,
-            })
-        }
-        _ => Ok(AwsxOutput {
+    let query = SnapshotQuery {
+        source_identifier: opt.db_instance_identifier.clone(),
+        snapshot_type: opt.snapshot_type.clone(),
+        filter: opt.snapshot_filter_opt.filter(),
+        tag_filter: TagFilter::new(opt.tags.clone()).excluding(opt.not_tags.clone()),
+    };
+    match find(&rds, &query, opt.concurrency).await? {
+        Some(db_snapshot) => Ok(AwsxOutput {
+            human_readable: db_snapshot.db_snapshot_arn.clone(),
+            structured: CommandOutput::success(
+                "Found DB-snapshot matching given filters",
+                Some(db_snapshot),
+            ),
+            exit_code: ExitCode::Success,
+        }),
+        None => Ok(AwsxOutput {
             human_readable: "Unable to find DB-snapshot matching given filters".to_owned(),
-            structured: json!({
-                "success": false,
-                "message": "Unable to find DB-snapshot matching given filters",
-            }),
-            successful: false,
+            structured: CommandOutput::failure(
+                "Unable to find DB-snapshot matching given filters",
+                None,
+            ),
+            exit_code: ExitCode::Failure,
         }),
     }
 }
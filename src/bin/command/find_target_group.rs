@@ -14,40 +14,20 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 
-use awsx::error::Error;
-use futures::stream::{FuturesOrdered, TryStreamExt};
-use rusoto_core::HttpClient;
-use rusoto_elbv2::{DescribeTagsInput, DescribeTargetGroupsInput, Elb, ElbClient, TagDescription};
-use serde_json::json;
-use std::str::FromStr;
+use awsx::{
+    commands::{
+        elb::{find_target_group as find, FoundTargetGroup, TargetGroupSelector},
+        CommandOutput,
+    },
+    endpoint,
+    error::Error,
+    http,
+    tags::{Tag, TagFilter},
+};
+use rusoto_elbv2::ElbClient;
 use structopt::StructOpt;
 
-use crate::{AwsxOutput, AwsxProvider, Opt as GlobalOpt};
-
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub(crate) struct Tag {
-    key: String,
-    value: String,
-}
-
-impl FromStr for Tag {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut split = s.splitn(2, '=');
-
-        Ok(Tag {
-            key: split
-                .next()
-                .ok_or_else(|| "Tag needs to be provided in the form `Key=Value`".to_owned())?
-                .to_owned(),
-            value: split
-                .next()
-                .ok_or_else(|| "Tag needs to be provided in the form `Key=Value`".to_owned())?
-                .to_owned(),
-        })
-    }
-}
+use crate::{AwsxOutput, AwsxProvider, ExitCode, Opt as GlobalOpt};
 
 #[derive(Debug, StructOpt)]
 pub(crate) struct Opt {
@@ -61,97 +41,86 @@ pub(crate) struct Opt {
         help = "Filter for target groups by their tags",
         long_help = "Filter for target groups by their tags. Specify multiple `Key=Value` pairs, \
                      separated by spaces, where each key-value-pair corresponds to a tag assigned \
-                     to the target groups."
+                     to the target groups. A value containing `*` matches any characters in its \
+                     place, e.g. `Version=1.4.*`, and `Key~=Regex` matches the value against a \
+                     regex. A key without a value, e.g. `Key`, matches any value of the tag."
+    )]
+    tags: Vec<Tag>,
+    #[structopt(
+        long = "not-tags",
+        help = "Exclude target groups by their tags",
+        long_help = "Exclude target groups that have any of the given tags assigned. The tags are \
+                     specified the same way as for --tags, e.g. `Key` excludes all target groups \
+                     that have the tag assigned, regardless of its value."
+    )]
+    not_tags: Vec<Tag>,
+}
+
+/// Options to select a single target group by its ARN or by its tags.
+#[derive(Debug, StructOpt)]
+pub(crate) struct TargetGroupSelectorOpt {
+    #[structopt(
+        long = "target-group-arn",
+        required_unless_one = &["tags", "not-tags"],
+        conflicts_with_all = &["tags", "not-tags"],
+        help = "ARN of the target group"
+    )]
+    target_group_arn: Option<String>,
+    #[structopt(
+        long = "tags",
+        help = "Select the target group by its tags",
+        long_help = "Select the target group by its tags instead of its ARN, which have to match \
+                     exactly one target group. The tags are specified the same way as for \
+                     `find-target-group`."
     )]
     tags: Vec<Tag>,
+    #[structopt(
+        long = "not-tags",
+        help = "Exclude target groups by their tags when selecting by tags"
+    )]
+    not_tags: Vec<Tag>,
+}
+
+impl TargetGroupSelectorOpt {
+    /// The target group selected by the options.
+    pub(crate) fn selector(&self) -> TargetGroupSelector {
+        match &self.target_group_arn {
+            Some(target_group_arn) => TargetGroupSelector::Arn(target_group_arn.clone()),
+            None => TargetGroupSelector::Tags(
+                TagFilter::new(self.tags.clone()).excluding(self.not_tags.clone()),
+            ),
+        }
+    }
 }
 
 pub(crate) async fn find_target_group(
     opt: &Opt,
     global_opt: &GlobalOpt,
     provider: AwsxProvider,
-) -> Result<AwsxOutput, Error> {
+) -> Result<AwsxOutput<CommandOutput<Option<FoundTargetGroup>>>, Error> {
+    let tag_filter = TagFilter::new(opt.tags.clone()).excluding(opt.not_tags.clone());
     let elb = ElbClient::new_with(
-        HttpClient::new()?,
+        http::client()?,
         provider,
-        global_opt.aws_region.clone().unwrap_or_default(),
+        endpoint::region_for("elasticloadbalancing", global_opt.region()),
     );
 
-    let mut target_groups = Vec::new();
-    let mut continuation_token: Option<String> = None;
-    while {
-        let mut output = elb
-            .describe_target_groups(DescribeTargetGroupsInput {
-                load_balancer_arn: opt.load_balancer_arn.clone(),
-                marker: continuation_token.clone(),
-                ..Default::default()
-            })
-            .await?;
-        continuation_token = output.next_marker;
-        if let Some(new_target_groups) = output.target_groups.as_mut() {
-            target_groups.append(new_target_groups)
-        }
-
-        continuation_token.is_some()
-    } {}
-
-    let tag_descriptions: Vec<TagDescription> = target_groups
-        .into_iter()
-        .filter_map(|target_group| target_group.target_group_arn)
-        .collect::<Vec<_>>()
-        .chunks(20)
-        .map(|arns| arns.to_vec())
-        .map(|arns| async {
-            elb.describe_tags(DescribeTagsInput {
-                resource_arns: arns,
-            })
-            .await
-        })
-        .collect::<FuturesOrdered<_>>()
-        .try_collect::<Vec<_>>()
-        .await?
-        .into_iter()
-        .fold(Vec::new(), |mut acc, mut tag_descriptions| {
-            if let Some(tag_descriptions) = tag_descriptions.tag_descriptions.as_mut() {
-                acc.append(tag_descriptions)
-            }
-            acc
-        });
-    let target_group_arn = tag_descriptions
-        .into_iter()
-        .filter(|tag_description| match &tag_description.tags {
-            Some(resource_tags) => opt.tags.iter().all(|needle| {
-                resource_tags.iter().any(|haystack| {
-                    haystack.key == needle.key
-                        && haystack
-                            .value
-                            .as_ref()
-                            .map(|value| value == &needle.value)
-                            .unwrap_or(false)
-                })
-            }),
-            None => false,
-        })
-        .filter_map(|tag_description| tag_description.resource_arn)
-        .next();
-
-    match target_group_arn {
-        Some(arn) => Ok(AwsxOutput {
-            human_readable: arn.clone(),
-            structured: json!({
-                "success": true,
-                "message": "Found target group matching given filters",
-                "target_group_arn": &arn,
-            }),
-            successful: true,
+    match find(&elb, opt.load_balancer_arn.as_deref(), &tag_filter).await? {
+        Some(target_group) => Ok(AwsxOutput {
+            human_readable: target_group.target_group_arn.clone(),
+            structured: CommandOutput::success(
+                "Found target group matching given filters",
+                Some(target_group),
+            ),
+            exit_code: ExitCode::Success,
         }),
         None => Ok(AwsxOutput {
             human_readable: "Unable to find target group matching given filters".to_owned(),
-            structured: json!({
-                "success": false,
-                "message": "Unable to find target group matching given filters",
-            }),
-            successful: false,
+            structured: CommandOutput::failure(
+                "Unable to find target group matching given filters",
+                None,
+            ),
+            exit_code: ExitCode::Failure,
         }),
     }
 }
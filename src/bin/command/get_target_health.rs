@@ -0,0 +1,132 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use awsx::{
+    commands::{
+        elb::{get_target_health as get, wait_for_healthy_targets, TargetGroupHealth},
+        CommandOutput,
+    },
+    endpoint,
+    error::Error,
+    http,
+};
+use itertools::Itertools;
+use rusoto_elbv2::ElbClient;
+use std::time::Duration;
+use structopt::StructOpt;
+
+use crate::{
+    command::find_target_group::TargetGroupSelectorOpt, stream, AwsxOutput, AwsxProvider, ExitCode,
+    Opt as GlobalOpt,
+};
+
+#[derive(Debug, StructOpt)]
+pub(crate) struct Opt {
+    #[structopt(flatten)]
+    target_group_selector_opt: TargetGroupSelectorOpt,
+    #[structopt(
+        long = "min-healthy",
+        help = "Minimum number of healthy targets",
+        long_help = "Minimum number of healthy targets. If fewer targets are healthy, the command \
+                     fails. If unspecified, all registered targets, but at least one, have to be \
+                     healthy."
+    )]
+    min_healthy: Option<usize>,
+    #[structopt(
+        long = "wait-healthy",
+        help = "Wait until enough targets are healthy",
+        long_help = "Wait until enough targets are healthy, see --min-healthy. If this does not \
+                     happen within --wait-timeout, the command fails."
+    )]
+    wait_healthy: bool,
+    #[structopt(
+        long = "wait-timeout",
+        default_value = "600",
+        help = "Maximum number of seconds to wait for the targets to become healthy"
+    )]
+    wait_timeout: u64,
+}
+
+pub(crate) async fn get_target_health(
+    opt: &Opt,
+    global_opt: &GlobalOpt,
+    provider: AwsxProvider,
+) -> Result<AwsxOutput<CommandOutput<TargetGroupHealth>>, Error> {
+    let elb = ElbClient::new_with(
+        http::client()?,
+        provider,
+        endpoint::region_for("elasticloadbalancing", global_opt.region()),
+    );
+
+    let target_group_arn = opt
+        .target_group_selector_opt
+        .selector()
+        .find_arn(&elb)
+        .await?;
+    let target_group_health = if opt.wait_healthy {
+        tokio::time::timeout(
+            Duration::from_secs(opt.wait_timeout),
+            wait_for_healthy_targets(&elb, &target_group_arn, opt.min_healthy, |progress| {
+                eprintln!(
+                    "Waiting for targets to become healthy: {}",
+                    progress
+                        .targets
+                        .iter()
+                        .map(|target| format!("{} {}", target.target(), target.state))
+                        .join(", ")
+                )
+            }),
+        )
+        .await
+        .unwrap_or_else(|_| {
+            Err(Error::WaitTimeout(format!(
+                "targets of {} to become healthy",
+                target_group_arn
+            )))
+        })?
+    } else {
+        get(&elb, &target_group_arn).await?
+    };
+
+    let healthy_enough = target_group_health.is_healthy_enough(opt.min_healthy);
+    // The target health is not paginated, so all targets are streamed at once.
+    if stream::start() {
+        target_group_health.targets.iter().for_each(stream::emit);
+    }
+
+    Ok(AwsxOutput {
+        human_readable: target_group_health
+            .targets
+            .iter()
+            .map(|target| match &target.description {
+                Some(description) => {
+                    format!("- {}: {} ({})", target.target(), target.state, description)
+                }
+                None => format!("- {}: {}", target.target(), target.state),
+            })
+            .join("\n"),
+        structured: if healthy_enough {
+            CommandOutput::success("Enough targets are healthy", target_group_health)
+        } else {
+            CommandOutput::failure("Not enough targets are healthy", target_group_health)
+        },
+        exit_code: if healthy_enough {
+            ExitCode::Success
+        } else {
+            ExitCode::Failure
+        },
+    })
+}
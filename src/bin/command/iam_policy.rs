@@ -0,0 +1,181 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use awsx::{
+    commands::{
+        iam::{iam_policy as policy, PolicyScope},
+        CommandOutput,
+    },
+    endpoint::Partition,
+    error::Error,
+};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use structopt::{clap::ErrorKind, StructOpt};
+
+use crate::{AwsxOutput, AwsxProvider, ExitCode, Opt as GlobalOpt};
+
+/// Heading of the list of permissions in the help of every subcommand.
+const PERMISSIONS_HEADING: &str = "IAM permissions required:";
+
+#[derive(Debug, StructOpt)]
+pub(crate) struct Opt {
+    #[structopt(help = "Subcommand to generate the IAM policy for")]
+    subcommand: String,
+    #[structopt(
+        long = "stack-arn",
+        number_of_values = 1,
+        help = "ARN of a stack to scope CloudFormation permissions to",
+        long_help = "ARN of a stack to scope CloudFormation permissions to, which can contain \
+                     wildcards, e.g. `arn:aws:cloudformation:eu-central-1:123456789012:stack/app-*`. \
+                     Can be specified multiple times. If unspecified, the permissions apply to all \
+                     stacks."
+    )]
+    stack_arns: Vec<String>,
+    #[structopt(
+        long = "bucket-name",
+        number_of_values = 1,
+        help = "Name of an S3 bucket to scope S3 permissions to",
+        long_help = "Name of an S3 bucket to scope S3 permissions to. Can be specified multiple \
+                     times. If unspecified, the permissions apply to all buckets. The partition of \
+                     the bucket ARNs is derived from the region, e.g. `aws-cn` for `cn-north-1`."
+    )]
+    bucket_names: Vec<String>,
+    #[structopt(
+        long = "only-required",
+        help = "Omit permissions only required by optional flags",
+        long_help = "Omit permissions that are only required when using optional flags of the \
+                     subcommand, e.g. `--execute`."
+    )]
+    only_required: bool,
+}
+
+/// Retrieve the long help of the subcommand, as it is displayed with `--help`.
+fn subcommand_help(subcommand: &str) -> Option<String> {
+    match GlobalOpt::clap().get_matches_from_safe(vec!["awsx", subcommand, "--help"]) {
+        Err(error) if error.kind == ErrorKind::HelpDisplayed => Some(error.message),
+        _ => None,
+    }
+}
+
+/// Parse the permissions listed in the help of a subcommand.
+///
+/// Every permission is listed on its own line, optionally followed by a note in parentheses.
+/// Notes starting with "with" mark permissions only required when using an optional flag.
+fn parse_permissions(help: &str, only_required: bool) -> Option<BTreeSet<String>> {
+    let mut lines = help
+        .lines()
+        .skip_while(|line| line.trim() != PERMISSIONS_HEADING);
+    lines.next()?;
+    Some(
+        lines
+            .map_while(|line| line.trim().strip_prefix("- "))
+            .filter(|permission| !only_required || !permission.contains("(with "))
+            .filter_map(|permission| permission.split_whitespace().next())
+            .map(ToOwned::to_owned)
+            .collect(),
+    )
+}
+
+/// The subcommand whose IAM permissions are not documented.
+#[derive(Debug, Serialize)]
+struct UndocumentedSubcommand<'a> {
+    subcommand: &'a str,
+}
+
+pub(crate) async fn iam_policy(
+    opt: &Opt,
+    global_opt: &GlobalOpt,
+    _provider: AwsxProvider,
+) -> Result<AwsxOutput, Error> {
+    let permissions = match subcommand_help(&opt.subcommand)
+        .and_then(|help| parse_permissions(&help, opt.only_required))
+    {
+        Some(permissions) => permissions,
+        None => {
+            return Ok(AwsxOutput {
+                human_readable: format!(
+                    "No IAM permissions are documented for subcommand {}",
+                    opt.subcommand
+                ),
+                structured: CommandOutput::failure(
+                    "No IAM permissions are documented for the subcommand",
+                    UndocumentedSubcommand {
+                        subcommand: &opt.subcommand,
+                    },
+                ),
+                exit_code: ExitCode::Failure,
+            }
+            .into_json())
+        }
+    };
+
+    // The policy document itself is the structured output, so that it can be passed to IAM as-is.
+    let policy = policy(
+        &permissions,
+        &PolicyScope {
+            stack_arns: opt.stack_arns.clone(),
+            bucket_names: opt.bucket_names.clone(),
+            partition: Partition::of(&global_opt.region()),
+        },
+    );
+    Ok(AwsxOutput {
+        human_readable: serde_json::to_string_pretty(&policy)?,
+        structured: policy,
+        exit_code: ExitCode::Success,
+    }
+    .into_json())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_permissions_from_help() {
+        let help = "USAGE:\n    awsx create-stack [FLAGS]\n\nIAM permissions required:\n\
+                    - cloudformation:CreateChangeSet\n\
+                    - cloudformation:GetTemplate (both stacks)\n\
+                    - cloudformation:ExecuteChangeSet (with --execute)\n";
+
+        assert_eq!(
+            Some(
+                vec![
+                    "cloudformation:CreateChangeSet",
+                    "cloudformation:ExecuteChangeSet",
+                    "cloudformation:GetTemplate",
+                ]
+                .into_iter()
+                .map(ToOwned::to_owned)
+                .collect()
+            ),
+            parse_permissions(help, false)
+        );
+        assert_eq!(
+            Some(
+                vec![
+                    "cloudformation:CreateChangeSet",
+                    "cloudformation:GetTemplate"
+                ]
+                .into_iter()
+                .map(ToOwned::to_owned)
+                .collect()
+            ),
+            parse_permissions(help, true)
+        );
+        assert_eq!(None, parse_permissions("USAGE:\n    awsx", false));
+    }
+}
@@ -14,20 +14,30 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 
-use awsx::{error::Error, stack::Stack, template::Template};
+use awsx::{
+    commands::{
+        cloudformation::parameters::{self, NewParameters},
+        CommandOutput,
+    },
+    endpoint,
+    error::Error,
+    http,
+};
 use itertools::Itertools;
 use rusoto_cloudformation::CloudFormationClient;
-use rusoto_core::HttpClient;
-use serde_json::json;
 use structopt::StructOpt;
 
-use crate::{AwsxOutput, AwsxProvider, Opt as GlobalOpt};
+use crate::{util::load_template, AwsxOutput, AwsxProvider, ExitCode, Opt as GlobalOpt};
 
 #[derive(Debug, StructOpt)]
 pub(crate) struct Opt {
     #[structopt(long = "stack-name", help = "Name of the stack to update")]
     stack_name: String,
-    #[structopt(long = "template-path", help = "Path to the new template")]
+    #[structopt(
+        long = "template-path",
+        help = "Path to the new template",
+        long_help = "Path to the new template. Specify `-` to read the template from stdin."
+    )]
     template_path: String,
 }
 
@@ -35,60 +45,81 @@ pub(crate) async fn identify_new_parameters(
     opt: &Opt,
     global_opt: &GlobalOpt,
     provider: AwsxProvider,
-) -> Result<AwsxOutput, Error> {
+) -> Result<AwsxOutput<CommandOutput<NewParameters>>, Error> {
     // Load the template
-    let template = Template::new(&opt.template_path)?;
+    let template = load_template(&opt.template_path)?;
 
     // Create AWS clients
     let cfn = CloudFormationClient::new_with(
-        HttpClient::new()?,
+        http::client()?,
         provider.clone(),
-        global_opt.aws_region.clone().unwrap_or_default(),
+        endpoint::region_for("cloudformation", global_opt.region()),
     );
 
-    let stack = Stack::new(&opt.stack_name);
-
-    // Retrieve the parameters defined on the template, as well as the current parameters defined on
-    // the stack.
-    let template_parameters = template.get_parameters_as_previous_value();
-    let stack_parameters = stack.get_parameters_as_previous_value(&cfn).await?;
-
-    // Identify newly added parameters, which are parameters defined on the template, but not on the
-    // stack. (Parameters that are defined on the stack but not on the template, so the other way
-    // around, are simply ignored, since they do not need to be set and will simply be removed
-    // once the change-set is deployed.)
-    let new_parameters = template_parameters.clone() - &stack_parameters;
+    let new_parameters =
+        parameters::identify_new_parameters(&cfn, &opt.stack_name, &template).await?;
+    let removed_human_readable = if new_parameters.removed_parameters.is_empty() {
+        String::new()
+    } else {
+        format!(
+            "\nParameters set on the stack, removed from the template:\n{}",
+            new_parameters
+                .removed_parameters
+                .iter()
+                .map(|key| format!("- {}", key))
+                .join("\n")
+        )
+    };
 
-    if new_parameters.is_empty() {
+    if new_parameters.parameters.is_empty() {
         Ok(AwsxOutput {
-            human_readable: "No new parameters available".to_owned(),
-            structured: json!({
-                "success": false,
-                "message": "No new parameters available",
-                "parameters": [],
-            }),
-            successful: false,
+            human_readable: format!("No new parameters available{}", removed_human_readable),
+            structured: CommandOutput::failure("No new parameters available", new_parameters),
+            exit_code: ExitCode::NoChanges,
         })
     } else {
         let human_readable = {
             let parameters = new_parameters
-                .keys()
-                .map(|key| format!("- {}", key))
+                .parameter_details
+                .iter()
+                .map(|parameter| match &parameter.declaration {
+                    Some(declaration) => {
+                        let mut attributes = vec![declaration.parameter_type.clone()];
+                        if let Some(default) = &declaration.default {
+                            attributes.push(format!("default: {}", default));
+                        }
+                        if declaration.no_echo {
+                            attributes.push("NoEcho".to_owned());
+                        }
+                        let description = declaration
+                            .description
+                            .as_ref()
+                            .map(|description| format!(": {}", description))
+                            .unwrap_or_default();
+                        format!(
+                            "- {} ({}){}",
+                            parameter.key,
+                            attributes.join(", "),
+                            description
+                        )
+                    }
+                    None => format!("- {}", parameter.key),
+                })
                 .join("\n");
             format!(
-                "New parameters defined in the template, not set on the stack:\n{}",
-                parameters
+                "New parameters defined in the template, not set on the stack:\n{}{}",
+                parameters, removed_human_readable
             )
         };
-        let structured = json!({
-            "success": true,
-            "parameters": new_parameters.keys().collect::<Vec<_>>(),
-        });
+        let structured = CommandOutput::success(
+            format!("Found {} new parameters", new_parameters.parameters.len()),
+            new_parameters,
+        );
 
         Ok(AwsxOutput {
             human_readable,
             structured,
-            successful: true,
+            exit_code: ExitCode::Success,
         })
     }
 }
@@ -0,0 +1,92 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+use awsx::{
+    commands::{cloudformation::template, CommandOutput},
+    error::Error,
+    template::TemplateLintReport,
+};
+use itertools::Itertools;
+use structopt::StructOpt;
+
+use crate::{util::load_template, AwsxOutput, AwsxProvider, ExitCode, Opt as GlobalOpt};
+
+#[derive(Debug, StructOpt)]
+pub(crate) struct Opt {
+    #[structopt(
+        long = "template-path",
+        help = "Path to the template to lint",
+        long_help = "Path to the template to lint. Specify `-` to read the template from stdin."
+    )]
+    template_path: String,
+}
+
+pub(crate) async fn lint_template(
+    opt: &Opt,
+    _global_opt: &GlobalOpt,
+    _provider: AwsxProvider,
+) -> Result<AwsxOutput<CommandOutput<TemplateLintReport>>, Error> {
+    let report = template::lint_template(&load_template(&opt.template_path)?)?;
+
+    let message = if report.is_clean() {
+        "The template does not contain any unused parameters or undeclared references".to_owned()
+    } else {
+        format!(
+            "The template contains {} unused parameters and {} undeclared references",
+            report.unused_parameters.len(),
+            report.undeclared_references.len()
+        )
+    };
+    let human_readable = if report.is_clean() {
+        message.clone()
+    } else {
+        let mut sections = Vec::new();
+        if !report.unused_parameters.is_empty() {
+            sections.push(format!(
+                "Parameters declared, but never referenced:\n{}",
+                report
+                    .unused_parameters
+                    .iter()
+                    .map(|key| format!("- {}", key))
+                    .join("\n")
+            ));
+        }
+        if !report.undeclared_references.is_empty() {
+            sections.push(format!(
+                "References to undeclared parameters, resources or conditions:\n{}",
+                report
+                    .undeclared_references
+                    .iter()
+                    .map(|logical_id| format!("- {}", logical_id))
+                    .join("\n")
+            ));
+        }
+        sections.join("\n")
+    };
+
+    Ok(if report.is_clean() {
+        AwsxOutput {
+            human_readable,
+            structured: CommandOutput::success(message, report),
+            exit_code: ExitCode::Success,
+        }
+    } else {
+        AwsxOutput {
+            human_readable,
+            structured: CommandOutput::failure(message, report),
+            exit_code: ExitCode::Failure,
+        }
+    })
+}
@@ -14,15 +14,49 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 
+pub(crate) mod blue_green;
+pub(crate) mod clone_stack;
+pub(crate) mod compare_stacks;
+pub(crate) mod copy_db_snapshot;
+pub(crate) mod create_cloudfront_invalidation;
+pub(crate) mod create_db_snapshot;
 pub(crate) mod create_stack;
+pub(crate) mod delete_change_set;
+pub(crate) mod deployment_changelog;
+pub(crate) mod deployment_report;
+pub(crate) mod deregister_targets;
+pub(crate) mod describe_change_set;
+pub(crate) mod execute_change_set;
 pub(crate) mod find_amis_inuse;
 pub(crate) mod find_auto_scaling_group;
 pub(crate) mod find_cloudfront_distribution;
 pub(crate) mod find_db_cluster_snapshot;
 pub(crate) mod find_db_snapshot;
 pub(crate) mod find_target_group;
+pub(crate) mod get_target_health;
+pub(crate) mod iam_policy;
 pub(crate) mod identify_new_parameters;
+pub(crate) mod lint_template;
+pub(crate) mod move_resource;
 pub(crate) mod override_parameters;
+pub(crate) mod package;
+pub(crate) mod promote_stack;
+pub(crate) mod prune_templates;
+pub(crate) mod register_targets;
+pub(crate) mod resume_auto_scaling_processes;
+pub(crate) mod self_update;
+pub(crate) mod set_auto_scaling_group_capacity;
+pub(crate) mod share_db_snapshot;
+pub(crate) mod shift_route53_weights;
+pub(crate) mod show_stack_parameters;
+pub(crate) mod start_instance_refresh;
+pub(crate) mod suspend_auto_scaling_processes;
+pub(crate) mod template_graph;
+#[cfg(feature = "tui")]
+pub(crate) mod ui;
 pub(crate) mod update_deployed_template;
 pub(crate) mod verify_changes_compatible;
 pub(crate) mod verify_parameter_file;
+pub(crate) mod wait_for_cloudfront_distribution;
+pub(crate) mod wait_for_db_snapshot;
+pub(crate) mod who_changed_stack;
@@ -0,0 +1,262 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use awsx::{
+    commands::{
+        cloudformation::move_resource::{
+            create_move_change_set, plan_resource_move, verify_move_change_set, IdentifierProperty,
+            MoveStep, ResourceMove, ResourceMoveOutcome, ResourceMovePlan,
+        },
+        CommandOutput,
+    },
+    endpoint,
+    error::Error,
+    http,
+    s3::S3Uploader,
+};
+use itertools::Itertools;
+use rusoto_cloudformation::{CloudFormation, CloudFormationClient};
+use structopt::StructOpt;
+
+use crate::{
+    audit::{self, AuditEntry},
+    command::execute_change_set::verify_protected_resources,
+    guard,
+    util::{load_template, s3_uploader, template_git_path},
+    AwsxOutput, AwsxProvider, ExitCode, Opt as GlobalOpt,
+};
+
+#[derive(Debug, StructOpt)]
+pub(crate) struct Opt {
+    #[structopt(
+        long = "source-stack-name",
+        help = "Name of the stack currently managing the resource"
+    )]
+    source_stack_name: String,
+    #[structopt(
+        long = "destination-stack-name",
+        help = "Name of the stack to move the resource to"
+    )]
+    destination_stack_name: String,
+    #[structopt(
+        long = "logical-resource-id",
+        help = "Logical ID of the resource in the source stack"
+    )]
+    logical_resource_id: String,
+    #[structopt(
+        long = "destination-logical-resource-id",
+        help = "Logical ID of the resource in the destination stack",
+        long_help = "Logical ID of the resource in the destination stack. Defaults to the logical \
+                     ID in the source stack."
+    )]
+    destination_logical_resource_id: Option<String>,
+    #[structopt(
+        long = "destination-template-path",
+        help = "Path to the template of the destination stack declaring the resource",
+        long_help = "Path to the template of the destination stack, which has to declare the \
+                     resource including a `DeletionPolicy`, and must not contain any other \
+                     changes compared to the deployed template. Specify `-` to read the template \
+                     from stdin. If not specified, the declaration of the resource is copied from \
+                     the source stack into the deployed template of the destination stack, which \
+                     is only possible if the declaration does not reference any parameters, \
+                     resources or conditions."
+    )]
+    destination_template_path: Option<String>,
+    #[structopt(
+        long = "resource-identifier",
+        help = "Identifier properties of the resource as `Property=Value` pairs",
+        long_help = "Identifier properties of the resource as `Property=Value` pairs, e.g. \
+                     `BucketName=my-bucket`, used to import the resource into the destination \
+                     stack. Only required for resource types identified by more than one \
+                     property, otherwise the physical ID of the resource is used."
+    )]
+    resource_identifier: Vec<IdentifierProperty>,
+    #[structopt(
+        long = "source-role-arn",
+        help = "IAM role to use when updating the source stack"
+    )]
+    source_role_arn: Option<String>,
+    #[structopt(
+        long = "destination-role-arn",
+        help = "IAM role to use when updating the destination stack"
+    )]
+    destination_role_arn: Option<String>,
+    #[structopt(
+        long = "dry-run",
+        help = "Only perform the safety checks, without updating either stack"
+    )]
+    dry_run: bool,
+}
+
+pub(crate) async fn move_resource(
+    opt: &Opt,
+    global_opt: &GlobalOpt,
+    provider: AwsxProvider,
+) -> Result<AwsxOutput<CommandOutput<ResourceMoveOutcome>>, Error> {
+    let cfn = CloudFormationClient::new_with(
+        http::client()?,
+        provider.clone(),
+        endpoint::region_for("cloudformation", global_opt.region()),
+    );
+    let s3 = s3_uploader(
+        global_opt,
+        provider,
+        opt.destination_template_path
+            .as_deref()
+            .map(template_git_path),
+    );
+    let s3_upload: Option<(&S3Uploader, &str)> = global_opt
+        .s3_bucket_name
+        .as_ref()
+        .map(|bucket_name| (&s3, bucket_name.as_ref()));
+
+    let destination_template = opt
+        .destination_template_path
+        .as_deref()
+        .map(load_template)
+        .transpose()?;
+    let plan = plan_resource_move(
+        &cfn,
+        ResourceMove {
+            source_stack_name: &opt.source_stack_name,
+            destination_stack_name: &opt.destination_stack_name,
+            logical_resource_id: &opt.logical_resource_id,
+            destination_logical_resource_id: opt.destination_logical_resource_id.as_deref(),
+            destination_template,
+            resource_identifier: &opt.resource_identifier,
+        },
+        s3_upload,
+    )
+    .await?;
+    let resource = &plan.resource;
+    guard::validate_template(global_opt, &plan.retained_template, &plan.source_parameters)?;
+    guard::validate_template(global_opt, &plan.removed_template, &plan.source_parameters)?;
+    guard::validate_template(
+        global_opt,
+        &plan.destination_template,
+        &plan.destination_parameters,
+    )?;
+
+    if opt.dry_run {
+        return Ok(AwsxOutput {
+            human_readable: format!(
+                "All safety checks passed, the move would:\n{}",
+                resource
+                    .plan
+                    .iter()
+                    .map(|step| format!("- {}", step))
+                    .join("\n")
+            ),
+            structured: CommandOutput::success(
+                "All safety checks passed",
+                ResourceMoveOutcome {
+                    resource: resource.clone(),
+                    dry_run: true,
+                    change_sets: Vec::new(),
+                },
+            ),
+            exit_code: ExitCode::Success,
+        });
+    }
+
+    let mut change_sets = Vec::new();
+    for step in plan.steps() {
+        let role_arn = match step {
+            MoveStep::Retain | MoveStep::Remove => opt.source_role_arn.as_deref(),
+            MoveStep::Import => opt.destination_role_arn.as_deref(),
+        };
+        let result = run_step(&cfn, global_opt, &plan, step, role_arn, s3_upload).await;
+        // Once the resource was removed, it is no longer managed by any stack, so failing to
+        // import it has to be reported prominently.
+        if step == MoveStep::Import && result.is_err() {
+            eprintln!(
+                "WARNING: {} was removed from stack {} and retained, but could not be imported \
+                 into stack {}, it is not managed by any stack until it is imported manually",
+                resource.physical_resource_id,
+                resource.source_stack_name,
+                resource.destination_stack_name
+            );
+        }
+        if let Some(change_set) = result? {
+            change_sets.push(change_set);
+        }
+    }
+
+    Ok(AwsxOutput {
+        human_readable: format!(
+            "Moved {} from stack {} to stack {} as {}",
+            resource.logical_resource_id,
+            resource.source_stack_name,
+            resource.destination_stack_name,
+            resource.destination_logical_resource_id
+        ),
+        structured: CommandOutput::success(
+            "Resource moved",
+            ResourceMoveOutcome {
+                resource: resource.clone(),
+                dry_run: false,
+                change_sets,
+            },
+        ),
+        exit_code: ExitCode::Success,
+    })
+}
+
+/// Carry out the step of the move through a change set, waiting for its execution to complete.
+///
+/// Returns the name of the executed change set, or `None` if it did not contain any changes. A
+/// change set removing a protected resource is deleted, and fails the move.
+async fn run_step(
+    cfn: &dyn CloudFormation,
+    global_opt: &GlobalOpt,
+    plan: &ResourceMovePlan,
+    step: MoveStep,
+    role_arn: Option<&str>,
+    s3_upload: Option<(&S3Uploader, &str)>,
+) -> Result<Option<String>, Error> {
+    eprintln!("{}", plan.describe(step));
+    let change_set = plan.change_set(step);
+    let result = create_move_change_set(cfn, plan, step, &change_set, role_arn, s3_upload).await;
+    audit::record(
+        AuditEntry::new("CreateChangeSet", change_set.stack_name())
+            .with_change_set_name(change_set.name())
+            .with_template(plan.template(step))
+            .with_parameters(plan.parameters(step)),
+        &result,
+    )
+    .await?;
+    result?;
+
+    let description = match verify_move_change_set(cfn, plan, step, &change_set).await? {
+        Some(description) => description,
+        None => return Ok(None),
+    };
+    if let Err(error) = verify_protected_resources(global_opt, &description) {
+        change_set.delete(cfn).await?;
+        return Err(error);
+    }
+
+    let result = change_set.execute(cfn).await;
+    audit::record(
+        AuditEntry::new("ExecuteChangeSet", change_set.stack_name())
+            .with_change_set_name(change_set.name()),
+        &result,
+    )
+    .await?;
+    result?;
+    change_set.wait_until_executed(cfn).await?;
+    Ok(Some(change_set.name().to_owned()))
+}
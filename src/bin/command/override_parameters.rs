@@ -15,20 +15,31 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use awsx::{
+    commands::{
+        cloudformation::{
+            change_set::ChangeSetOutcome,
+            deploy::{copy_stack_parameters, prepare_parameter_overrides},
+        },
+        CommandOutput,
+    },
+    endpoint,
     error::Error,
+    http,
     parameter::{Parameter, Parameters},
-    stack::Stack,
 };
 use rusoto_cloudformation::CloudFormationClient;
-use rusoto_core::HttpClient;
-use serde_json::json;
-use std::fs::File;
-use std::io::BufReader;
 use structopt::StructOpt;
 
 use crate::{
-    util::{apply_excludes_includes, generate_deployment_metadata},
-    AwsxOutput, AwsxProvider, Opt as GlobalOpt,
+    command::execute_change_set::{
+        create_prepared_change_set, handle_created_change_set, ChangeSetOpt,
+    },
+    redact,
+    util::{
+        apply_excludes_includes, load_parameter_file, metadata_options, ConflictStrategy,
+        PlaceholderContext,
+    },
+    AwsxOutput, AwsxProvider, ExitCode, Opt as GlobalOpt,
 };
 
 #[derive(Debug, StructOpt)]
@@ -55,10 +66,13 @@ pub(crate) struct Opt {
     parameter_overrides: Vec<Parameter>,
     #[structopt(
         long = "parameter-path",
-        help = "Path to a JSON parameter file",
-        long_help = "Path to a JSON parameter file. This file should be structured the same as the \
-                     AWS CLI expects. The file can only contain parameters newly added to the \
-                     template, unless the existing parameters are defined as \
+        help = "Path to a JSON or YAML parameter file",
+        long_help = "Path to a JSON or YAML parameter file. This file should be structured the \
+                     same as the AWS CLI expects. Values can contain placeholders, i.e. \
+                     `{{stack_name}}`, `{{region}}`, `{{account_id}}` or \
+                     `{{parameters.OtherKey}}`, while other placeholders like dynamic references \
+                     are kept; `{{{{` is a literal `{{`. The file can only contain parameters \
+                     newly added to the template, unless the existing parameters are defined as \
                      `UsePreviousValue=true`.\n(If you specify this parameter and \
                      --parameter-overrides, parameters specified through --parameters will \
                      override parameters provided via the parameter file.)"
@@ -89,100 +103,137 @@ pub(crate) struct Opt {
                      will not be affected by this.)"
     )]
     includes: Vec<String>,
+    #[structopt(
+        long = "from-stack",
+        requires = "from-stack-parameters",
+        help = "Stack to copy parameter values from",
+        long_help = "Stack to copy the current values of the parameters specified through \
+                     --from-stack-parameter from, e.g. to promote an AMI ID from a staging stack.\n\
+                     (Values copied from the stack override parameters provided via the parameter \
+                     file, and are overridden by parameters specified through \
+                     --parameter-overrides.)"
+    )]
+    from_stack: Option<String>,
+    #[structopt(
+        long = "from-stack-parameter",
+        requires = "from-stack",
+        number_of_values = 1,
+        help = "Parameter to copy from the stack specified through --from-stack",
+        long_help = "Key of a parameter whose current value should be copied from the stack \
+                     specified through --from-stack. Can be specified multiple times."
+    )]
+    from_stack_parameters: Vec<String>,
+    #[structopt(
+        long = "parameter-conflicts",
+        default_value = "keep-right",
+        help = "How conflicting parameter values are resolved",
+        long_help = "How parameters specified with different values by multiple sources are \
+                     resolved, where the sources are ordered --parameter-path, then --from-stack, \
+                     then --parameter-overrides: `keep-right` takes the value of the later source, \
+                     `keep-left` keeps the value of the earlier source, `error` fails the command \
+                     listing all conflicting parameters, and `interactive` prompts which of the \
+                     values to keep."
+    )]
+    parameter_conflicts: ConflictStrategy,
+    #[structopt(flatten)]
+    change_set_opt: ChangeSetOpt,
 }
 
 pub(crate) async fn override_parameters(
     opt: &Opt,
     global_opt: &GlobalOpt,
     provider: AwsxProvider,
-) -> Result<AwsxOutput, Error> {
+) -> Result<AwsxOutput<CommandOutput<Option<ChangeSetOutcome>>>, Error> {
+    opt.parameter_conflicts.ensure_usable(false)?;
+
     let cfn = CloudFormationClient::new_with(
-        HttpClient::new()?,
-        provider,
-        global_opt.aws_region.clone().unwrap_or_default(),
+        http::client()?,
+        provider.clone(),
+        endpoint::region_for("cloudformation", global_opt.region()),
     );
 
-    // Retrieve the parameters currently set on the stack. This will return a list of parameters
-    // where the previous value will be used in a change set.
-    let stack = Stack::new(&opt.stack_name);
-    let mut stack_parameters = stack.get_parameters_as_previous_value(&cfn).await?;
-
-    // We now update the retrieved parameters, overriding them as specified on the command-line.
+    // Get the parameters to override, as specified on the command-line.
+    let placeholder_context = PlaceholderContext {
+        stack_name: Some(&opt.stack_name),
+        global_opt,
+        provider: &provider,
+    };
+    let mut provided_parameters = Parameters::new(Vec::new());
     if let Some(parameter_path) = &opt.parameter_path {
-        let file = File::open(parameter_path)?;
-        let reader = BufReader::new(file);
         let parameters: Parameters = {
-            let parameters: Parameters = serde_json::from_reader(reader).unwrap();
+            let parameters = load_parameter_file(parameter_path, &placeholder_context).await?;
             parameters
                 .values()
                 .filter(|parameter| !parameter.is_previous_value())
                 .collect::<Vec<_>>()
                 .into()
         };
-        stack_parameters.update(apply_excludes_includes(
+        provided_parameters = apply_excludes_includes(parameters, &opt.excludes, &opt.includes)?;
+    }
+    if let Some(from_stack) = &opt.from_stack {
+        let parameters =
+            copy_stack_parameters(&cfn, from_stack, &opt.from_stack_parameters).await?;
+        opt.parameter_conflicts.merge(
+            &mut provided_parameters,
             parameters,
-            &opt.excludes,
-            &opt.includes,
-        )?);
+            ("the parameter file", "--from-stack"),
+        )?;
     }
-    stack_parameters.update(&opt.parameter_overrides);
+    opt.parameter_conflicts.merge(
+        &mut provided_parameters,
+        opt.parameter_overrides.clone().into(),
+        (
+            "the parameter file or --from-stack",
+            "--parameter-overrides",
+        ),
+    )?;
 
-    if stack_parameters.is_empty() {
-        Ok(AwsxOutput {
-            human_readable: "No parameters specified (or all filtered), no change set created"
-                .to_owned(),
-            structured: json!({
-                "success": false,
-                "message": "No parameters specified (or all filtered), no change set created",
-            }),
-            successful: false,
-        })
-    } else {
-        // Unless otherwise requested, we will update the deployment-metadata parameter
-        if !global_opt.dont_update_deployment_metadata {
-            if let Some(previous_metadata_parameter) = stack
-                .get_parameter(&cfn, &global_opt.deployment_metadata_parameter)
-                .await?
-            {
-                let metadata =
-                    generate_deployment_metadata(Some(previous_metadata_parameter), None)?;
-                stack_parameters.insert(
-                    global_opt.deployment_metadata_parameter.clone(),
-                    Parameter::WithValue {
-                        key: global_opt.deployment_metadata_parameter.clone(),
-                        value: metadata.to_string(),
-                    },
-                );
-            } else {
-                eprintln!(
-                    "WARNING: an update to the deployment-metadata parameter '{}' was requested, \
-                     but the stack does not have this parameter. The change-set will be created, \
-                     although without any metadata.",
-                    &global_opt.deployment_metadata_parameter,
-                );
-            }
+    let prepared = prepare_parameter_overrides(
+        &cfn,
+        &opt.stack_name,
+        &opt.change_set_name,
+        provided_parameters,
+        &metadata_options(global_opt, None),
+    )
+    .await?;
+    let prepared = match prepared {
+        Some(prepared) => prepared,
+        None => {
+            return Ok(AwsxOutput {
+                human_readable: "No parameters specified (or all filtered), no change set created"
+                    .to_owned(),
+                structured: CommandOutput::failure(
+                    "No parameters specified (or all filtered), no change set created",
+                    None,
+                ),
+                exit_code: ExitCode::NoChanges,
+            });
         }
+    };
 
-        stack
-            .create_change_set(
-                &cfn,
-                &opt.change_set_name,
-                opt.role_arn.as_deref(),
-                &stack_parameters,
-            )
-            .await?;
+    redact::register_parameters(&prepared.parameters);
+    let created = create_prepared_change_set(
+        &opt.change_set_opt,
+        &cfn,
+        global_opt,
+        &prepared,
+        opt.role_arn.as_deref(),
+        None,
+    )
+    .await?;
 
-        Ok(AwsxOutput {
-            human_readable: format!(
-                "Change set {} creation started successfully",
-                opt.change_set_name
-            ),
-            structured: json!({
-                "success": true,
-                "message": "Change set creation started successfully",
-                "change_set_name": opt.change_set_name,
-            }),
-            successful: true,
-        })
-    }
+    let output = AwsxOutput {
+        human_readable: format!(
+            "Change set {} creation started successfully",
+            opt.change_set_name
+        ),
+        structured: CommandOutput::success(
+            "Change set creation started successfully",
+            ChangeSetOutcome::created(created, ()),
+        ),
+        exit_code: ExitCode::Success,
+    };
+    handle_created_change_set(&opt.change_set_opt, &cfn, global_opt, &provider, output)
+        .await
+        .map(|output| output.map_result(Some))
 }
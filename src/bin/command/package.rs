@@ -0,0 +1,95 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use awsx::{
+    commands::{
+        cloudformation::template::{self, PackagedTemplate},
+        CommandOutput,
+    },
+    error::Error,
+};
+use std::fs;
+use structopt::StructOpt;
+
+use crate::{
+    util::{load_template, s3_uploader, template_base_dir, template_git_path},
+    AwsxOutput, AwsxProvider, ExitCode, Opt as GlobalOpt,
+};
+
+#[derive(Debug, StructOpt)]
+pub(crate) struct Opt {
+    #[structopt(
+        long = "template-path",
+        help = "Path to the template to package",
+        long_help = "Path to the template to package. Specify `-` to read the template from stdin, \
+                     in which case local artifacts are resolved relative to the current working \
+                     directory."
+    )]
+    template_path: String,
+    #[structopt(
+        long = "output-template-path",
+        help = "Path to write the packaged template to",
+        long_help = "Path to write the packaged template to. If unspecified, the packaged template \
+                     is printed instead."
+    )]
+    output_template_path: Option<String>,
+}
+
+pub(crate) async fn package(
+    opt: &Opt,
+    global_opt: &GlobalOpt,
+    provider: AwsxProvider,
+) -> Result<AwsxOutput<CommandOutput<PackagedTemplate>>, Error> {
+    let template = load_template(&opt.template_path)?;
+    let s3 = s3_uploader(
+        global_opt,
+        provider,
+        Some(template_git_path(&opt.template_path)),
+    );
+
+    let s3_bucket_name = global_opt
+        .s3_bucket_name
+        .as_deref()
+        .ok_or_else(|| Error::S3BucketRequired("package the template".to_owned()))?;
+
+    let mut packaged = template::package_template(
+        &s3,
+        &template,
+        &template_base_dir(&opt.template_path),
+        s3_bucket_name,
+    )
+    .await?;
+
+    if let Some(output_template_path) = &opt.output_template_path {
+        fs::write(
+            output_template_path,
+            packaged.template.take().unwrap_or_default(),
+        )?;
+        packaged.output_template_path = Some(output_template_path.clone());
+        let message = format!("Packaged template written to {}", output_template_path);
+        Ok(AwsxOutput {
+            human_readable: message.clone(),
+            structured: CommandOutput::success(message, packaged),
+            exit_code: ExitCode::Success,
+        })
+    } else {
+        Ok(AwsxOutput {
+            human_readable: packaged.template.clone().unwrap_or_default(),
+            structured: CommandOutput::success("Template packaged", packaged),
+            exit_code: ExitCode::Success,
+        })
+    }
+}
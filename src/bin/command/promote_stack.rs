@@ -0,0 +1,164 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use awsx::{
+    commands::{
+        cloudformation::{
+            change_set::ChangeSetOutcome,
+            deploy::{prepare_stack_promotion, PromotedFrom, StackPromotion},
+        },
+        CommandOutput,
+    },
+    endpoint,
+    error::Error,
+    http,
+    parameter::Parameter,
+    s3::S3Uploader,
+};
+use rusoto_cloudformation::CloudFormationClient;
+use rusoto_core::Region;
+use structopt::StructOpt;
+
+use crate::{
+    command::execute_change_set::{
+        create_prepared_change_set, handle_created_change_set, ChangeSetOpt,
+    },
+    redact,
+    util::{metadata_options, provider_for, s3_uploader},
+    AwsxOutput, AwsxProvider, ExitCode, Opt as GlobalOpt,
+};
+
+#[derive(Debug, StructOpt)]
+pub(crate) struct Opt {
+    #[structopt(long = "source-stack-name", help = "Name of the stack to promote from")]
+    source_stack_name: String,
+    #[structopt(
+        long = "source-region",
+        help = "Region of the source stack",
+        long_help = "Region of the source stack. If unspecified, the source stack is expected in \
+                     the same region as the destination stack."
+    )]
+    source_region: Option<Region>,
+    #[structopt(
+        long = "source-assume-role-arn",
+        help = "Role to assume to access the source stack",
+        long_help = "Role to assume to access the source stack, e.g. if it resides in another \
+                     account. If unspecified, the source stack is accessed with the same \
+                     credentials as the destination stack."
+    )]
+    source_assume_role_arn: Option<String>,
+    #[structopt(long = "stack-name", help = "Name of the stack to promote to")]
+    stack_name: String,
+    #[structopt(long = "change-set-name", help = "Name for the new change set")]
+    change_set_name: String,
+    #[structopt(
+        long = "role-arn",
+        help = "IAM Role that AWS CloudFormation assumes when executing the change set"
+    )]
+    role_arn: Option<String>,
+    #[structopt(
+        long = "promote-parameter",
+        number_of_values = 1,
+        help = "Parameter whose value is promoted from the source stack",
+        long_help = "Key of a parameter whose value is promoted from the source stack, e.g. an AMI \
+                     ID. Can be specified multiple times. All other parameters already defined on \
+                     the destination stack keep their values, whereas parameters newly added to \
+                     the template take the value of the source stack."
+    )]
+    promote_parameters: Vec<String>,
+    #[structopt(
+        short = "p",
+        long = "parameters",
+        help = "Parameters to change",
+        long_help = "Parameters to change on the destination stack. Specify as multiple \
+                     `Key=Value` pairs. These override values promoted from the source stack."
+    )]
+    parameters: Vec<Parameter>,
+    #[structopt(flatten)]
+    change_set_opt: ChangeSetOpt,
+}
+
+pub(crate) async fn promote_stack(
+    opt: &Opt,
+    global_opt: &GlobalOpt,
+    provider: AwsxProvider,
+) -> Result<AwsxOutput<CommandOutput<ChangeSetOutcome<PromotedFrom>>>, Error> {
+    // Create AWS clients, where the source stack might reside in another account or region.
+    let (source_provider, source_region) = provider_for(
+        global_opt,
+        opt.source_region.clone(),
+        opt.source_assume_role_arn.clone(),
+    )?;
+    let source_cfn = CloudFormationClient::new_with(
+        http::client()?,
+        source_provider,
+        endpoint::region_for("cloudformation", source_region.clone()),
+    );
+    let cfn = CloudFormationClient::new_with(
+        http::client()?,
+        provider.clone(),
+        endpoint::region_for("cloudformation", global_opt.region()),
+    );
+    let s3 = s3_uploader(global_opt, provider.clone(), None);
+    let s3_upload: Option<(&S3Uploader, &str)> = global_opt
+        .s3_bucket_name
+        .as_ref()
+        .map(|bucket_name| (&s3, bucket_name.as_ref()));
+
+    let mut prepared = prepare_stack_promotion(
+        &source_cfn,
+        &cfn,
+        StackPromotion {
+            source_stack_name: &opt.source_stack_name,
+            source_region: source_region.name(),
+            stack_name: &opt.stack_name,
+            change_set_name: &opt.change_set_name,
+            promote_parameters: &opt.promote_parameters,
+            parameters: (&opt.parameters).into(),
+            minify_template: global_opt.minify_templates,
+        },
+        &metadata_options(global_opt, None),
+        s3_upload,
+    )
+    .await?;
+    if let Some(template) = &prepared.template {
+        redact::register_template(template);
+    }
+    redact::register_parameters(&prepared.parameters);
+    prepared.validate(false)?;
+    let created = create_prepared_change_set(
+        &opt.change_set_opt,
+        &cfn,
+        global_opt,
+        &prepared,
+        opt.role_arn.as_deref(),
+        s3_upload,
+    )
+    .await?;
+
+    let output = AwsxOutput {
+        human_readable: format!(
+            "Change set {} creation for promoting {} to {} started successfully",
+            opt.change_set_name, opt.source_stack_name, opt.stack_name,
+        ),
+        structured: CommandOutput::success(
+            "Change set creation started successfully",
+            ChangeSetOutcome::created(created, prepared.details),
+        ),
+        exit_code: ExitCode::Success,
+    };
+    handle_created_change_set(&opt.change_set_opt, &cfn, global_opt, &provider, output).await
+}
@@ -0,0 +1,193 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use awsx::{
+    commands::{
+        cloudformation::template::{
+            self, PrunedTemplates, RegionalCloudFormation, TemplatePruning,
+        },
+        CommandOutput,
+    },
+    endpoint,
+    error::Error,
+    http,
+};
+use prettytable::row;
+use rusoto_cloudformation::CloudFormationClient;
+use rusoto_core::Region;
+use rusoto_s3::S3Client;
+use structopt::StructOpt;
+
+use crate::{
+    audit::{self, AuditEntry},
+    stream, AwsxOutput, AwsxProvider, ExitCode, Opt as GlobalOpt,
+};
+
+#[derive(Debug, StructOpt)]
+pub(crate) struct Opt {
+    #[structopt(
+        long = "retention-days",
+        default_value = "30",
+        help = "Minimum age in days of the templates to delete",
+        long_help = "Minimum age in days of the templates to delete. Younger templates are kept \
+                     even if no stack references them, e.g. since they belong to a deployment \
+                     that is still in progress."
+    )]
+    retention_days: i64,
+    #[structopt(
+        long = "regions",
+        use_delimiter = true,
+        help = "Regions whose stacks can reference the templates, e.g. `eu-central-1,eu-west-1`",
+        long_help = "Comma-separated regions whose stacks and change sets can reference the \
+                     templates in the bucket, e.g. `eu-central-1,eu-west-1`. A template is only \
+                     considered unreferenced if no stack in any of these regions references it. \
+                     The referenced templates are determined from the template URL recorded in \
+                     the deployment metadata of the stacks and change sets, falling back to the \
+                     checksum of the template if no URL is recorded. Defaults to the region given \
+                     by --aws-region."
+    )]
+    regions: Vec<Region>,
+    #[structopt(
+        long = "delete",
+        help = "Delete the unreferenced templates instead of only listing them"
+    )]
+    delete: bool,
+}
+
+pub(crate) async fn prune_templates(
+    opt: &Opt,
+    global_opt: &GlobalOpt,
+    provider: AwsxProvider,
+) -> Result<AwsxOutput<CommandOutput<PrunedTemplates>>, Error> {
+    let bucket_name = global_opt
+        .s3_bucket_name
+        .clone()
+        .ok_or_else(|| Error::S3BucketRequired("prune templates".to_owned()))?;
+    let regions = if opt.regions.is_empty() {
+        vec![global_opt.region()]
+    } else {
+        opt.regions.clone()
+    };
+    let s3 = S3Client::new_with(
+        http::client()?,
+        provider.clone(),
+        endpoint::region_for("s3", global_opt.region()),
+    );
+    let cfns = regions
+        .iter()
+        .map(|region| {
+            Ok(CloudFormationClient::new_with(
+                http::client()?,
+                provider.clone(),
+                endpoint::region_for("cloudformation", region.clone()),
+            ))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    let cloudformations = cfns
+        .iter()
+        .zip(&regions)
+        .map(|(cfn, region)| RegionalCloudFormation {
+            cfn,
+            region: region.clone(),
+        })
+        .collect::<Vec<_>>();
+
+    // Unreferenced templates are streamed as soon as the page they appear on was retrieved.
+    let streaming = stream::start();
+    let result = template::prune_templates(
+        &s3,
+        &cloudformations,
+        &TemplatePruning {
+            bucket_name,
+            deployment_metadata_parameter: global_opt.deployment_metadata_parameter.clone(),
+            retention_days: opt.retention_days,
+            delete: opt.delete,
+        },
+        |template| {
+            if streaming {
+                stream::emit(template);
+            }
+        },
+    )
+    .await;
+    if opt.delete {
+        audit::record(
+            AuditEntry::for_resource("DeleteObjects", global_opt.s3_bucket_name.clone())
+                .with_details(result.as_ref().ok()),
+            &result,
+        )
+        .await?;
+    }
+    let pruned = result?;
+    for failed in &pruned.failed {
+        eprintln!(
+            "WARNING: failed to delete {}: {}",
+            failed.key, failed.message
+        );
+    }
+
+    let summary = if pruned.unreferenced_templates.is_empty() {
+        format!(
+            "No unreferenced templates older than {} days in bucket {}.",
+            opt.retention_days, pruned.bucket_name
+        )
+    } else if !opt.delete {
+        format!(
+            "Would delete {} unreferenced templates, use --delete to delete them.",
+            pruned.unreferenced_templates.len()
+        )
+    } else if pruned.failed.is_empty() {
+        format!(
+            "Deleted {} unreferenced templates.",
+            pruned.unreferenced_templates.len()
+        )
+    } else {
+        format!(
+            "Deleted {} of {} unreferenced templates.",
+            pruned.unreferenced_templates.len() - pruned.failed.len(),
+            pruned.unreferenced_templates.len()
+        )
+    };
+    let human_readable = if pruned.unreferenced_templates.is_empty() {
+        summary.clone()
+    } else {
+        let mut table = prettytable::Table::new();
+        table.set_format(*prettytable::format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+        table.set_titles(row!["Key", "Last modified", "Size"]);
+        for template in &pruned.unreferenced_templates {
+            table.add_row(row![
+                template.key,
+                template.last_modified.format("%Y-%m-%d"),
+                r->template.size,
+            ]);
+        }
+        format!("{}\n{}", table.to_string().trim_end(), summary)
+    };
+
+    Ok(if pruned.failed.is_empty() {
+        AwsxOutput {
+            human_readable,
+            structured: CommandOutput::success(summary, pruned),
+            exit_code: ExitCode::Success,
+        }
+    } else {
+        AwsxOutput {
+            human_readable,
+            structured: CommandOutput::failure(summary, pruned),
+            exit_code: ExitCode::Failure,
+        }
+    })
+}
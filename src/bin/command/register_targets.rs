@@ -0,0 +1,140 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use awsx::{
+    commands::{
+        elb::{
+            parse_target, register_targets as register, wait_for_target_state, TargetGroupTargets,
+        },
+        CommandOutput,
+    },
+    endpoint,
+    error::Error,
+    http,
+};
+use itertools::Itertools;
+use rusoto_elbv2::{ElbClient, TargetDescription};
+use std::time::Duration;
+use structopt::StructOpt;
+
+use crate::{
+    audit::{self, AuditEntry},
+    command::find_target_group::TargetGroupSelectorOpt,
+    AwsxOutput, AwsxProvider, ExitCode, Opt as GlobalOpt,
+};
+
+#[derive(Debug, StructOpt)]
+pub(crate) struct Opt {
+    #[structopt(flatten)]
+    target_group_selector_opt: TargetGroupSelectorOpt,
+    #[structopt(
+        long = "targets",
+        required = true,
+        parse(try_from_str = parse_target),
+        help = "Targets to register, e.g. `i-0123456789abcdef0 10.0.0.1:8080`",
+        long_help = "Targets to register, separated by spaces. Each target is an instance ID, IP \
+                     address or Lambda function ARN, optionally followed by `:port` to override \
+                     the port of the target group, e.g. `i-0123456789abcdef0 10.0.0.1:8080`."
+    )]
+    targets: Vec<TargetDescription>,
+    #[structopt(
+        long = "wait",
+        help = "Wait until the registered targets are healthy",
+        long_help = "Wait until all registered targets pass the health checks of the target group. \
+                     If this does not happen within --wait-timeout, the command fails."
+    )]
+    wait: bool,
+    #[structopt(
+        long = "wait-timeout",
+        default_value = "600",
+        help = "Maximum number of seconds to wait for the targets"
+    )]
+    wait_timeout: u64,
+}
+
+pub(crate) async fn register_targets(
+    opt: &Opt,
+    global_opt: &GlobalOpt,
+    provider: AwsxProvider,
+) -> Result<AwsxOutput<CommandOutput<TargetGroupTargets>>, Error> {
+    let elb = ElbClient::new_with(
+        http::client()?,
+        provider,
+        endpoint::region_for("elasticloadbalancing", global_opt.region()),
+    );
+
+    let result = register(
+        &elb,
+        &opt.target_group_selector_opt.selector(),
+        &opt.targets,
+    )
+    .await;
+    let target_group_arn = result
+        .as_ref()
+        .ok()
+        .map(|registered| registered.target_group_arn.clone());
+    audit::record(
+        AuditEntry::for_resource("RegisterTargets", target_group_arn)
+            .with_details(result.as_ref().ok()),
+        &result,
+    )
+    .await?;
+    let registered = result?;
+    if opt.wait {
+        tokio::time::timeout(
+            Duration::from_secs(opt.wait_timeout),
+            wait_for_target_state(
+                &elb,
+                &registered.target_group_arn,
+                &opt.targets,
+                "healthy",
+                |target_health| {
+                    eprintln!(
+                        "Waiting for targets to become healthy: {}",
+                        target_health
+                            .iter()
+                            .map(|target| format!("{} {}", target.target(), target.state))
+                            .join(", ")
+                    )
+                },
+            ),
+        )
+        .await
+        .unwrap_or_else(|_| {
+            Err(Error::WaitTimeout(format!(
+                "targets of {} to become healthy",
+                registered.target_group_arn
+            )))
+        })?;
+    }
+
+    Ok(AwsxOutput {
+        human_readable: format!(
+            "Registered targets with {}: {}",
+            registered.target_group_arn,
+            registered.targets.join(", ")
+        ),
+        structured: CommandOutput::success(
+            if opt.wait {
+                "Targets registered and healthy"
+            } else {
+                "Targets registered successfully"
+            },
+            registered,
+        ),
+        exit_code: ExitCode::Success,
+    })
+}
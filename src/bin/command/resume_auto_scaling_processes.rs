@@ -0,0 +1,97 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use awsx::{
+    commands::{
+        auto_scaling::{
+            parse_scaling_process, resume_auto_scaling_processes as resume_processes,
+            SuspendedProcesses,
+        },
+        CommandOutput,
+    },
+    endpoint,
+    error::Error,
+    http,
+};
+use rusoto_autoscaling::AutoscalingClient;
+use structopt::StructOpt;
+
+use crate::{
+    audit::{self, AuditEntry},
+    command::find_auto_scaling_group::AutoScalingGroupSelectorOpt,
+    AwsxOutput, AwsxProvider, ExitCode, Opt as GlobalOpt,
+};
+
+#[derive(Debug, StructOpt)]
+pub(crate) struct Opt {
+    #[structopt(flatten)]
+    auto_scaling_group_selector_opt: AutoScalingGroupSelectorOpt,
+    #[structopt(
+        long = "scaling-processes",
+        parse(try_from_str = parse_scaling_process),
+        help = "Processes to resume, e.g. `Launch Terminate AZRebalance`",
+        long_help = "Processes to resume, separated by spaces, e.g. `Launch Terminate \
+                     AZRebalance`. If unspecified, all suspended processes are resumed."
+    )]
+    scaling_processes: Vec<String>,
+}
+
+pub(crate) async fn resume_auto_scaling_processes(
+    opt: &Opt,
+    global_opt: &GlobalOpt,
+    provider: AwsxProvider,
+) -> Result<AwsxOutput<CommandOutput<SuspendedProcesses>>, Error> {
+    let autoscaling = AutoscalingClient::new_with(
+        http::client()?,
+        provider,
+        endpoint::region_for("autoscaling", global_opt.region()),
+    );
+
+    let result = resume_processes(
+        &autoscaling,
+        &opt.auto_scaling_group_selector_opt.selector(),
+        &opt.scaling_processes,
+    )
+    .await;
+    let auto_scaling_group_name = result
+        .as_ref()
+        .ok()
+        .map(|processes| processes.auto_scaling_group_name.clone());
+    audit::record(
+        AuditEntry::for_resource("ResumeProcesses", auto_scaling_group_name)
+            .with_details(result.as_ref().ok()),
+        &result,
+    )
+    .await?;
+    let processes = result?;
+
+    Ok(AwsxOutput {
+        human_readable: if processes.suspended_processes.is_empty() {
+            format!(
+                "All processes of {} are active",
+                processes.auto_scaling_group_name
+            )
+        } else {
+            format!(
+                "Processes of {} still suspended: {}",
+                processes.auto_scaling_group_name,
+                processes.suspended_processes.join(", ")
+            )
+        },
+        structured: CommandOutput::success("Processes resumed successfully", processes),
+        exit_code: ExitCode::Success,
+    })
+}
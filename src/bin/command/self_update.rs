@@ -0,0 +1,405 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use awsx::{commands::CommandOutput, error::Error, http, s3::ChecksumAlgorithm};
+use hyper::{header, Body, Client, Request, Uri};
+use minisign_verify::{PublicKey, Signature};
+use serde::Serialize;
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    process::Command,
+};
+use structopt::StructOpt;
+use uuid::Uuid;
+
+use crate::{AwsxOutput, AwsxProvider, ExitCode, Opt as GlobalOpt};
+
+/// The maximum number of redirects followed when downloading a release asset.
+const MAX_REDIRECTS: usize = 10;
+
+/// The minisign public key the checksum files of releases are signed with, embedded when building
+/// a release.
+const RELEASE_PUBLIC_KEY: Option<&str> = option_env!("AWSX_RELEASE_PUBLIC_KEY");
+
+/// The release assets built for every release, see `.github/workflows/release.yml`.
+const RELEASE_ASSETS: &[&str] = &[
+    "awsx-x86_64-linux",
+    "awsx-aarch64-linux",
+    "awsx-x86_64-macos",
+    "awsx-aarch64-macos",
+];
+
+#[derive(Debug, StructOpt)]
+pub(crate) struct Opt {
+    #[structopt(
+        help = "Version to install, e.g. `1.4.0`",
+        long_help = "Version to install, e.g. `1.4.0`. Defaults to the latest release, which can \
+                     also be an older version than the one currently installed."
+    )]
+    version: Option<String>,
+    #[structopt(
+        long = "release-url",
+        default_value = "https://github.com/takkt-ag/awsx/releases",
+        help = "URL of the GitHub releases to download awsx from",
+        long_help = "URL of the GitHub releases to download awsx from, e.g. of a mirror. The \
+                     binary is expected at `<release-url>/download/v<version>/<asset>`, or at \
+                     `<release-url>/latest/download/<asset>` for the latest release, with its \
+                     SHA-256 checksum next to it as `<asset>.sha256`, and the minisign signature \
+                     of the checksum file as `<asset>.sha256.minisig`. The signature is verified \
+                     against the public key embedded in awsx, regardless of where it is \
+                     downloaded from."
+    )]
+    release_url: String,
+}
+
+/// The name of the release asset containing the binary for the current platform, e.g.
+/// `awsx-x86_64-linux`.
+fn asset_name() -> String {
+    format!(
+        "awsx-{}-{}{}",
+        std::env::consts::ARCH,
+        std::env::consts::OS,
+        std::env::consts::EXE_SUFFIX
+    )
+}
+
+/// The release a checksum file was signed for, as recorded in the trusted comment of its
+/// signature, e.g. `awsx v1.4.0 awsx-x86_64-linux`.
+#[derive(Debug, PartialEq, Eq)]
+struct SignedRelease {
+    version: String,
+    asset: String,
+}
+
+/// Verify the minisign signature of the checksum file against the public key, and return the
+/// release it was signed for.
+fn verify_signature(
+    public_key: &str,
+    checksum_file: &[u8],
+    signature: &str,
+) -> Result<SignedRelease, Error> {
+    let failed = |reason: String| {
+        Error::SelfUpdateFailed(format!(
+            "the signature of the checksum file is invalid: {}",
+            reason
+        ))
+    };
+    let public_key = PublicKey::from_base64(public_key).map_err(|e| failed(e.to_string()))?;
+    let signature = Signature::decode(signature).map_err(|e| failed(e.to_string()))?;
+    public_key
+        .verify(checksum_file, &signature, false)
+        .map_err(|e| failed(e.to_string()))?;
+    match signature
+        .trusted_comment()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        ["awsx", version, asset] => Ok(SignedRelease {
+            version: version.trim_start_matches('v').to_owned(),
+            asset: (*asset).to_owned(),
+        }),
+        _ => Err(failed(format!(
+            "unexpected trusted comment `{}`",
+            signature.trusted_comment()
+        ))),
+    }
+}
+
+/// Parse the SHA-256 checksum from the contents of a checksum file, as written by e.g.
+/// `sha256sum`, i.e. the hex digest optionally followed by the file name.
+fn parse_checksum(contents: &str) -> Option<String> {
+    contents
+        .split_whitespace()
+        .next()
+        .filter(|checksum| checksum.len() == 64 && checksum.chars().all(|c| c.is_ascii_hexdigit()))
+        .map(str::to_ascii_lowercase)
+}
+
+/// Download the contents at the URL, following redirects, e.g. to the storage GitHub serves
+/// release assets from.
+async fn download(url: &str) -> Result<Vec<u8>, Error> {
+    let failed =
+        |reason: String| Error::SelfUpdateFailed(format!("downloading {}: {}", url, reason));
+    let client = Client::builder().build::<_, Body>(http::https_connector()?);
+    let mut uri: Uri = url
+        .parse()
+        .map_err(|e: hyper::http::uri::InvalidUri| failed(e.to_string()))?;
+    for _ in 0..=MAX_REDIRECTS {
+        let request = Request::get(uri.clone())
+            .header(
+                header::USER_AGENT,
+                concat!("awsx/", env!("CARGO_PKG_VERSION")),
+            )
+            .body(Body::empty())
+            .map_err(|e| failed(e.to_string()))?;
+        let response = tokio::time::timeout(http::read_timeout(), async {
+            let response = client.request(request).await?;
+            let status = response.status();
+            let location = response.headers().get(header::LOCATION).cloned();
+            let body = hyper::body::to_bytes(response.into_body()).await?;
+            Ok::<_, hyper::Error>((status, location, body))
+        })
+        .await
+        .map_err(|_| failed("timed out".to_owned()))?;
+        let (status, location, body) = response.map_err(|e| failed(e.to_string()))?;
+
+        if status.is_success() {
+            return Ok(body.to_vec());
+        } else if status.is_redirection() {
+            let location = location
+                .and_then(|location| location.to_str().ok().map(ToOwned::to_owned))
+                .ok_or_else(|| failed(format!("{} without a location", status)))?;
+            let mut parts = location
+                .parse::<Uri>()
+                .map_err(|e| failed(e.to_string()))?
+                .into_parts();
+            // Relative redirects keep the scheme and host of the previous request.
+            if parts.authority.is_none() {
+                parts.scheme = uri.scheme().cloned();
+                parts.authority = uri.authority().cloned();
+            }
+            uri = Uri::from_parts(parts).map_err(|e| failed(e.to_string()))?;
+        } else {
+            return Err(failed(status.to_string()));
+        }
+    }
+    Err(failed(format!("more than {} redirects", MAX_REDIRECTS)))
+}
+
+/// Write the binary next to the executable, and return the version it reports.
+///
+/// Running the binary verifies that it works on this platform before it replaces the executable.
+/// The file is removed again if it does not.
+fn stage_binary(staged: &Path, binary: &[u8]) -> Result<String, Error> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o755);
+    let result = options
+        .open(staged)
+        .and_then(|mut file| file.write_all(binary))
+        .map_err(Error::from)
+        .and_then(|_| {
+            // Failing to execute the binary at all, e.g. since it was built for another
+            // architecture, is reported the same way as a binary that does not report a version.
+            let version = Command::new(staged)
+                .arg("--version")
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+                .unwrap_or_default();
+            match version.split_whitespace().collect::<Vec<_>>().as_slice() {
+                ["awsx", version] => Ok((*version).to_owned()),
+                _ => Err(Error::SelfUpdateFailed(
+                    "the downloaded binary does not run on this platform".to_owned(),
+                )),
+            }
+        });
+    if result.is_err() {
+        let _ = fs::remove_file(staged);
+    }
+    result
+}
+
+/// Replace the executable with the staged binary.
+///
+/// Windows does not allow replacing a running executable, but renaming it, which is why it is
+/// moved aside to `<executable>.old` first.
+fn replace_executable(executable: &Path, staged: &Path) -> Result<(), Error> {
+    #[cfg(windows)]
+    {
+        let old = executable.with_extension("old");
+        let _ = fs::remove_file(&old);
+        fs::rename(executable, &old)?;
+    }
+    fs::rename(staged, executable).map_err(|error| {
+        let _ = fs::remove_file(staged);
+        error.into()
+    })
+}
+
+/// The executable after updating it.
+#[derive(Debug, Serialize)]
+pub(crate) struct UpdatedExecutable {
+    /// Path of the replaced binary.
+    path: PathBuf,
+    /// Version of awsx before the update.
+    previous_version: &'static str,
+    /// Version of awsx after the update.
+    version: String,
+    /// SHA-256 checksum of the new binary.
+    checksum: String,
+}
+
+pub(crate) async fn self_update(
+    opt: &Opt,
+    _global_opt: &GlobalOpt,
+    _provider: AwsxProvider,
+) -> Result<AwsxOutput<CommandOutput<UpdatedExecutable>>, Error> {
+    let asset = asset_name();
+    if !RELEASE_ASSETS.contains(&asset.as_str()) {
+        return Err(Error::SelfUpdateFailed(format!(
+            "no release asset is published for this platform ({}), only {}",
+            asset,
+            RELEASE_ASSETS.join(", ")
+        )));
+    }
+    let public_key = RELEASE_PUBLIC_KEY.ok_or_else(|| {
+        Error::SelfUpdateFailed(
+            "this build of awsx has no release public key embedded to verify updates with"
+                .to_owned(),
+        )
+    })?;
+    let requested_version = opt
+        .version
+        .as_deref()
+        .map(|version| version.trim_start_matches('v'));
+    let release_url = opt.release_url.trim_end_matches('/');
+    let asset_url = match requested_version {
+        Some(version) => format!("{}/download/v{}/{}", release_url, version, asset),
+        None => format!("{}/latest/download/{}", release_url, asset),
+    };
+
+    eprintln!("Downloading {}", asset_url);
+    let checksum_file = download(&format!("{}.sha256", asset_url)).await?;
+    let signature = download(&format!("{}.sha256.minisig", asset_url)).await?;
+    let signed = verify_signature(
+        public_key,
+        &checksum_file,
+        &String::from_utf8_lossy(&signature),
+    )?;
+    if signed.asset != asset {
+        return Err(Error::SelfUpdateFailed(format!(
+            "the checksum file was signed for {}, not for {}",
+            signed.asset, asset
+        )));
+    }
+    if let Some(requested_version) = requested_version {
+        if signed.version != requested_version {
+            return Err(Error::SelfUpdateFailed(format!(
+                "the checksum file was signed for version {}, not for the requested version {}",
+                signed.version, requested_version
+            )));
+        }
+    }
+    let checksum = parse_checksum(&String::from_utf8_lossy(&checksum_file)).ok_or_else(|| {
+        Error::SelfUpdateFailed(format!("{}.sha256 contains no SHA-256 checksum", asset_url))
+    })?;
+    let binary = download(&asset_url).await?;
+    let actual_checksum = ChecksumAlgorithm::Sha256.hex_digest(&binary);
+    if actual_checksum != checksum {
+        return Err(Error::SelfUpdateFailed(format!(
+            "the checksum {} of the downloaded binary does not match the published checksum {}",
+            actual_checksum, checksum
+        )));
+    }
+
+    // The executable can be a symlink, e.g. if installed through a package manager, in which case
+    // the binary it points to is replaced.
+    let executable = fs::canonicalize(std::env::current_exe()?)?;
+    let staged = executable
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(PathBuf::new)
+        .join(format!(".awsx-update-{}", Uuid::new_v4()));
+    let version = stage_binary(&staged, &binary)?;
+    if version != signed.version {
+        let _ = fs::remove_file(&staged);
+        return Err(Error::SelfUpdateFailed(format!(
+            "the downloaded binary reports version {}, but was signed as version {}",
+            version, signed.version
+        )));
+    }
+    replace_executable(&executable, &staged)?;
+
+    Ok(AwsxOutput {
+        human_readable: format!(
+            "Updated awsx at {} from {} to {}",
+            executable.display(),
+            env!("CARGO_PKG_VERSION"),
+            version
+        ),
+        structured: CommandOutput::success(
+            "awsx updated successfully",
+            UpdatedExecutable {
+                path: executable,
+                previous_version: env!("CARGO_PKG_VERSION"),
+                version,
+                checksum,
+            },
+        ),
+        exit_code: ExitCode::Success,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_checksum_files() {
+        let checksum = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+        assert_eq!(Some(checksum.to_owned()), parse_checksum(checksum));
+        assert_eq!(
+            Some(checksum.to_owned()),
+            parse_checksum(&format!("{}  awsx-x86_64-linux\n", checksum.to_uppercase()))
+        );
+        assert_eq!(
+            None,
+            parse_checksum("5d41402abc4b2a76b9719d911017c592  awsx")
+        );
+        assert_eq!(None, parse_checksum(""));
+    }
+
+    // Generated with a throwaway key, the signature covers `CHECKSUM_FILE` and the trusted comment
+    // `awsx v1.4.0 awsx-x86_64-linux`.
+    const TEST_PUBLIC_KEY: &str = "RWQBAgMEBQYHCAOhB7/zzhC+HXDdGOdLwJln5NYwm6UNXx3chmQSVTG4";
+    const CHECKSUM_FILE: &[u8] =
+        b"2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824  awsx-x86_64-linux\n";
+    const SIGNATURE: &str = "untrusted comment: signature from minisign secret key
+RUQBAgMEBQYHCOrA3Wwxfuq3WE7prtBkvThUoTMuK/rfSZhgWoVLFJ8XYdA2kp1sE7FjbnmukdMx7dpglbln+35s/wytcb5iLQI=
+trusted comment: awsx v1.4.0 awsx-x86_64-linux
+pPhNzpMHqlKD5fAafV+jOIGGGfVn/f33vyRDHjYxh9uwWFV2aFAM833/YhBwiQg91gERnh272yJjiVtetzsYDw==
+";
+
+    #[test]
+    fn verify_signed_checksum_file() {
+        assert_eq!(
+            SignedRelease {
+                version: "1.4.0".to_owned(),
+                asset: "awsx-x86_64-linux".to_owned(),
+            },
+            verify_signature(TEST_PUBLIC_KEY, CHECKSUM_FILE, SIGNATURE).unwrap()
+        );
+    }
+
+    #[test]
+    fn reject_tampered_checksum_file() {
+        let tampered = String::from_utf8_lossy(CHECKSUM_FILE).replace("2cf2", "0000");
+        assert!(verify_signature(TEST_PUBLIC_KEY, tampered.as_bytes(), SIGNATURE).is_err());
+
+        let tampered_comment = SIGNATURE.replace("v1.4.0", "v9.9.9");
+        assert!(verify_signature(TEST_PUBLIC_KEY, CHECKSUM_FILE, &tampered_comment).is_err());
+
+        let other_key = "RWQBAgMEBQYHCAOhB7/zzhC+HXDdGOdLwJln5NYwm6UNXx3chmQSVTG5";
+        assert!(verify_signature(other_key, CHECKSUM_FILE, SIGNATURE).is_err());
+        assert!(verify_signature(TEST_PUBLIC_KEY, CHECKSUM_FILE, "").is_err());
+    }
+}
@@ -0,0 +1,150 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use awsx::{
+    commands::{
+        auto_scaling::{
+            set_auto_scaling_group_capacity as set_capacity, wait_for_capacity, Capacity,
+            CapacityUpdate,
+        },
+        CommandOutput,
+    },
+    endpoint,
+    error::Error,
+    http,
+};
+use rusoto_autoscaling::AutoscalingClient;
+use std::time::Duration;
+use structopt::StructOpt;
+
+use crate::{
+    audit::{self, AuditEntry},
+    command::find_auto_scaling_group::AutoScalingGroupSelectorOpt,
+    AwsxOutput, AwsxProvider, ExitCode, Opt as GlobalOpt,
+};
+
+#[derive(Debug, StructOpt)]
+pub(crate) struct Opt {
+    #[structopt(flatten)]
+    auto_scaling_group_selector_opt: AutoScalingGroupSelectorOpt,
+    #[structopt(
+        long = "desired-capacity",
+        required_unless_one = &["min-size", "max-size"],
+        help = "Number of instances the auto-scaling group should run"
+    )]
+    desired_capacity: Option<i64>,
+    #[structopt(
+        long = "min-size",
+        help = "Minimum number of instances of the auto-scaling group"
+    )]
+    min_size: Option<i64>,
+    #[structopt(
+        long = "max-size",
+        help = "Maximum number of instances of the auto-scaling group"
+    )]
+    max_size: Option<i64>,
+    #[structopt(
+        long = "wait",
+        help = "Wait until the auto-scaling group runs the desired number of instances",
+        long_help = "Wait until the number of healthy instances in service matches the desired \
+                     capacity of the auto-scaling group, and no other instances are left. If this \
+                     does not happen within --wait-timeout, the command fails."
+    )]
+    wait: bool,
+    #[structopt(
+        long = "wait-timeout",
+        default_value = "1800",
+        help = "Maximum number of seconds to wait for the instances to match the desired capacity"
+    )]
+    wait_timeout: u64,
+}
+
+pub(crate) async fn set_auto_scaling_group_capacity(
+    opt: &Opt,
+    global_opt: &GlobalOpt,
+    provider: AwsxProvider,
+) -> Result<AwsxOutput<CommandOutput<Capacity>>, Error> {
+    let autoscaling = AutoscalingClient::new_with(
+        http::client()?,
+        provider,
+        endpoint::region_for("autoscaling", global_opt.region()),
+    );
+
+    let result = set_capacity(
+        &autoscaling,
+        &opt.auto_scaling_group_selector_opt.selector(),
+        CapacityUpdate {
+            desired_capacity: opt.desired_capacity,
+            min_size: opt.min_size,
+            max_size: opt.max_size,
+        },
+    )
+    .await;
+    let auto_scaling_group_name = result
+        .as_ref()
+        .ok()
+        .map(|capacity| capacity.auto_scaling_group_name.clone());
+    audit::record(
+        AuditEntry::for_resource("UpdateAutoScalingGroup", auto_scaling_group_name)
+            .with_details(result.as_ref().ok()),
+        &result,
+    )
+    .await?;
+    let capacity = result?;
+
+    if opt.wait {
+        tokio::time::timeout(
+            Duration::from_secs(opt.wait_timeout),
+            wait_for_capacity(
+                &autoscaling,
+                &capacity.auto_scaling_group_name,
+                capacity.desired_capacity,
+                |counts| {
+                    eprintln!(
+                        "{} of {} instances in service, {} instances in total",
+                        counts.in_service, capacity.desired_capacity, counts.total
+                    )
+                },
+            ),
+        )
+        .await
+        .unwrap_or_else(|_| {
+            Err(Error::WaitTimeout(format!(
+                "{} to run {} instances",
+                capacity.auto_scaling_group_name, capacity.desired_capacity
+            )))
+        })?;
+    }
+
+    Ok(AwsxOutput {
+        human_readable: format!(
+            "Set capacity of {} to {} (min: {}, max: {})",
+            capacity.auto_scaling_group_name,
+            capacity.desired_capacity,
+            capacity.min_size,
+            capacity.max_size
+        ),
+        structured: CommandOutput::success(
+            if opt.wait {
+                "Auto-scaling group reached the desired capacity"
+            } else {
+                "Capacity of the auto-scaling group updated successfully"
+            },
+            capacity,
+        ),
+        exit_code: ExitCode::Success,
+    })
+}
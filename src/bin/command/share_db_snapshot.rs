@@ -0,0 +1,147 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use awsx::{
+    commands::{
+        rds::{share_db_snapshot as share, SharedDbSnapshot},
+        CommandOutput,
+    },
+    endpoint,
+    error::Error,
+    http,
+    rds::DbSnapshot,
+};
+use rusoto_rds::RdsClient;
+use structopt::StructOpt;
+
+use crate::{
+    audit::{self, AuditEntry},
+    AwsxOutput, AwsxProvider, ExitCode, Opt as GlobalOpt,
+};
+
+#[derive(Debug, StructOpt)]
+pub(crate) struct Opt {
+    #[structopt(
+        long = "db-snapshot-identifier",
+        required_unless = "db-cluster-snapshot-identifier",
+        conflicts_with = "db-cluster-snapshot-identifier",
+        help = "Identifier or ARN of the DB snapshot to share"
+    )]
+    db_snapshot_identifier: Option<String>,
+    #[structopt(
+        long = "db-cluster-snapshot-identifier",
+        required_unless = "db-snapshot-identifier",
+        conflicts_with = "db-snapshot-identifier",
+        help = "Identifier or ARN of the DB cluster snapshot to share"
+    )]
+    db_cluster_snapshot_identifier: Option<String>,
+    #[structopt(
+        long = "account-ids",
+        required = true,
+        parse(try_from_str = parse_account_id),
+        help = "IDs of the AWS accounts to share the snapshot with",
+        long_help = "IDs of the AWS accounts to share the snapshot with, separated by spaces. The \
+                     accounts are able to copy and restore the snapshot afterwards."
+    )]
+    account_ids: Vec<String>,
+    #[structopt(
+        long = "revoke",
+        help = "Revoke the access of the accounts instead of sharing the snapshot with them"
+    )]
+    revoke: bool,
+}
+
+impl Opt {
+    fn db_snapshot(&self) -> DbSnapshot {
+        match (
+            &self.db_snapshot_identifier,
+            &self.db_cluster_snapshot_identifier,
+        ) {
+            (Some(identifier), _) => DbSnapshot::Instance(identifier.clone()),
+            (None, Some(identifier)) => DbSnapshot::Cluster(identifier.clone()),
+            (None, None) => unreachable!("structopt requires one of the identifiers"),
+        }
+    }
+}
+
+fn parse_account_id(account_id: &str) -> Result<String, String> {
+    if account_id.len() == 12 && account_id.chars().all(|c| c.is_ascii_digit()) {
+        Ok(account_id.to_owned())
+    } else {
+        Err(format!(
+            "`{}` is not a valid AWS account ID, expected 12 digits",
+            account_id
+        ))
+    }
+}
+
+pub(crate) async fn share_db_snapshot(
+    opt: &Opt,
+    global_opt: &GlobalOpt,
+    provider: AwsxProvider,
+) -> Result<AwsxOutput<CommandOutput<SharedDbSnapshot>>, Error> {
+    let rds = RdsClient::new_with(
+        http::client()?,
+        provider,
+        endpoint::region_for("rds", global_opt.region()),
+    );
+
+    let db_snapshot = opt.db_snapshot();
+    let result = if opt.revoke {
+        share(&rds, &db_snapshot, &[], &opt.account_ids).await
+    } else {
+        let description = db_snapshot.describe(&rds).await?;
+        if description.encrypted {
+            eprintln!(
+                "WARNING: the snapshot is encrypted with the KMS key {}. The target accounts \
+                 additionally need access to this key to copy or restore the snapshot.",
+                description.kms_key_id.as_deref().unwrap_or("<unknown>")
+            );
+        }
+        share(&rds, &db_snapshot, &opt.account_ids, &[]).await
+    };
+    let action = match db_snapshot {
+        DbSnapshot::Instance(_) => "ModifyDBSnapshotAttribute",
+        DbSnapshot::Cluster(_) => "ModifyDBClusterSnapshotAttribute",
+    };
+    audit::record(
+        AuditEntry::for_resource(action, Some(db_snapshot.identifier().to_owned()))
+            .with_details(result.as_ref().ok()),
+        &result,
+    )
+    .await?;
+    let shared = result?;
+
+    let message = if opt.revoke {
+        "Revoked access to DB snapshot"
+    } else {
+        "Shared DB snapshot"
+    };
+    Ok(AwsxOutput {
+        human_readable: format!(
+            "{} {}, it is now shared with: {}",
+            message,
+            db_snapshot.identifier(),
+            if shared.shared_with_account_ids.is_empty() {
+                "no accounts".to_owned()
+            } else {
+                shared.shared_with_account_ids.join(", ")
+            }
+        ),
+        structured: CommandOutput::success(message, shared),
+        exit_code: ExitCode::Success,
+    })
+}
@@ -0,0 +1,148 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use awsx::{
+    commands::{
+        route53::{
+            shift_route53_weights as shift, HostedZoneSelector, ShiftedWeights, Weight, WeightShift,
+        },
+        CommandOutput,
+    },
+    error::Error,
+    http,
+    route53::Route53Client,
+};
+use itertools::Itertools;
+use std::{num::NonZeroU32, time::Duration};
+use structopt::StructOpt;
+
+use crate::{
+    audit::{self, AuditEntry},
+    AwsxOutput, AwsxProvider, ExitCode, Opt as GlobalOpt,
+};
+
+#[derive(Debug, StructOpt)]
+pub(crate) struct Opt {
+    #[structopt(
+        long = "hosted-zone-id",
+        required_unless = "hosted-zone-name",
+        conflicts_with = "hosted-zone-name",
+        help = "ID of the hosted zone of the record sets"
+    )]
+    hosted_zone_id: Option<String>,
+    #[structopt(
+        long = "hosted-zone-name",
+        required_unless = "hosted-zone-id",
+        conflicts_with = "hosted-zone-id",
+        help = "Name of the hosted zone of the record sets, e.g. `example.com`"
+    )]
+    hosted_zone_name: Option<String>,
+    #[structopt(
+        long = "record-name",
+        help = "Name of the weighted record sets, e.g. `app.example.com`"
+    )]
+    record_name: String,
+    #[structopt(
+        long = "record-type",
+        default_value = "A",
+        help = "Type of the weighted record sets"
+    )]
+    record_type: String,
+    #[structopt(
+        long = "weights",
+        required = true,
+        help = "Target weights of the record sets, e.g. `blue=0 green=100`",
+        long_help = "Target weights of the record sets, specified as `SetIdentifier=Weight` pairs \
+                     separated by spaces, e.g. `blue=0 green=100`. The weights have to be between \
+                     0 and 255. Record sets that are not given keep their weight."
+    )]
+    weights: Vec<Weight>,
+    #[structopt(
+        long = "steps",
+        default_value = "1",
+        help = "Number of steps to shift the weights in",
+        long_help = "Number of steps to shift the weights in. With more than one step, the weights \
+                     are changed gradually from their current to their target values, waiting \
+                     --step-interval seconds between the steps."
+    )]
+    steps: NonZeroU32,
+    #[structopt(
+        long = "step-interval",
+        default_value = "60",
+        help = "Number of seconds to wait between the steps"
+    )]
+    step_interval: u64,
+    #[structopt(
+        long = "change-timeout",
+        default_value = "300",
+        help = "Maximum number of seconds to wait for each change to be propagated"
+    )]
+    change_timeout: u64,
+}
+
+fn format_weights(shifted: &ShiftedWeights) -> String {
+    shifted
+        .weights
+        .iter()
+        .map(|(set_identifier, weight)| format!("{}={}", set_identifier, weight))
+        .join(", ")
+}
+
+pub(crate) async fn shift_route53_weights(
+    opt: &Opt,
+    _global_opt: &GlobalOpt,
+    provider: AwsxProvider,
+) -> Result<AwsxOutput<CommandOutput<ShiftedWeights>>, Error> {
+    let route53 = Route53Client::new_with(http::client()?, provider);
+
+    let hosted_zone = match (&opt.hosted_zone_id, &opt.hosted_zone_name) {
+        (Some(hosted_zone_id), _) => HostedZoneSelector::Id(hosted_zone_id.clone()),
+        (None, Some(hosted_zone_name)) => HostedZoneSelector::Name(hosted_zone_name.clone()),
+        (None, None) => unreachable!("structopt requires one of the hosted zone options"),
+    };
+    let steps = opt.steps;
+    let result = shift(
+        &route53,
+        &WeightShift {
+            hosted_zone,
+            record_name: opt.record_name.clone(),
+            record_type: opt.record_type.clone(),
+            weights: opt.weights.clone(),
+            steps,
+            step_interval: Duration::from_secs(opt.step_interval),
+            change_timeout: Duration::from_secs(opt.change_timeout),
+        },
+        |step, shifted| eprintln!("Step {} of {}: {}", step, steps, format_weights(shifted)),
+    )
+    .await;
+    audit::record(
+        AuditEntry::for_resource("ChangeResourceRecordSets", Some(opt.record_name.clone()))
+            .with_details(result.as_ref().ok()),
+        &result,
+    )
+    .await?;
+    let shifted = result?;
+
+    Ok(AwsxOutput {
+        human_readable: format!(
+            "Shifted weights of {}: {}",
+            opt.record_name,
+            format_weights(&shifted)
+        ),
+        structured: CommandOutput::success("Weights shifted successfully", shifted),
+        exit_code: ExitCode::Success,
+    })
+}
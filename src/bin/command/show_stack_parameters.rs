@@ -0,0 +1,143 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use awsx::{
+    commands::{
+        cloudformation::parameters::{self, StackParameters},
+        CommandOutput,
+    },
+    endpoint,
+    error::Error,
+    http,
+    parameter::{Parameter, Parameters, NO_ECHO_PARAMETER_VALUE},
+};
+use prettytable::row;
+use rusoto_cloudformation::CloudFormationClient;
+use structopt::StructOpt;
+
+use crate::{redact, stream, AwsxOutput, AwsxProvider, ExitCode, Opt as GlobalOpt};
+
+#[derive(Debug, StructOpt)]
+pub(crate) struct Opt {
+    #[structopt(
+        long = "stack-name",
+        help = "Name of the stack to show the parameters of"
+    )]
+    stack_name: String,
+    #[structopt(
+        long = "output-parameter-path",
+        help = "Write the parameters to a parameter file",
+        long_help = "Write the parameters to a parameter file, e.g. to seed the parameter file of \
+                     another stack. The file is written as YAML if its extension is `.yaml` or \
+                     `.yml`, and as JSON otherwise. NoEcho parameters are written to use their \
+                     previous value, since their values can not be retrieved. The values of \
+                     sensitive parameters are written unredacted."
+    )]
+    output_parameter_path: Option<String>,
+    #[structopt(
+        long = "sort-parameters",
+        help = "Order the parameters alphabetically by their key",
+        long_help = "Order the parameters alphabetically by their key, instead of the order in \
+                     which CloudFormation returns them. This guarantees a stable output, e.g. to \
+                     avoid noisy diffs of parameter files kept in git."
+    )]
+    sort_parameters: bool,
+}
+
+pub(crate) async fn show_stack_parameters(
+    opt: &Opt,
+    global_opt: &GlobalOpt,
+    provider: AwsxProvider,
+) -> Result<AwsxOutput<CommandOutput<StackParameters>>, Error> {
+    let cfn = CloudFormationClient::new_with(
+        http::client()?,
+        provider,
+        endpoint::region_for("cloudformation", global_opt.region()),
+    );
+
+    let mut stack_parameters =
+        parameters::show_stack_parameters(&cfn, &opt.stack_name, opt.sort_parameters).await?;
+
+    // The parameter file is meant to be deployed, so it contains the values of sensitive parameters.
+    if let Some(output_parameter_path) = &opt.output_parameter_path {
+        stack_parameters
+            .to_parameter_file()
+            .to_file(output_parameter_path)?;
+    }
+
+    // Mask the values of sensitive parameters, including the values they resolve to, wherever they
+    // appear, and drop them from the structured output, which would otherwise show short values.
+    redact::register_parameters(&stack_parameters.to_parameter_file());
+    redact::register_parameters(&Parameters::from(
+        stack_parameters
+            .parameters
+            .iter()
+            .filter_map(|parameter| {
+                let resolved_value = parameter.resolved_value.as_deref()?;
+                Some(Parameter::with_value(
+                    parameter.key.as_str(),
+                    resolved_value,
+                ))
+            })
+            .collect::<Vec<_>>(),
+    ));
+    for parameter in &mut stack_parameters.parameters {
+        if redact::is_sensitive(&parameter.key) {
+            if parameter.value.is_some() {
+                parameter.value = Some(NO_ECHO_PARAMETER_VALUE.to_owned());
+            }
+            if parameter.resolved_value.is_some() {
+                parameter.resolved_value = Some(NO_ECHO_PARAMETER_VALUE.to_owned());
+            }
+        }
+    }
+
+    let mut table = prettytable::Table::new();
+    table.set_format(*prettytable::format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+    table.set_titles(row!["Key", "Value", "Resolved value"]);
+    for parameter in &stack_parameters.parameters {
+        table.add_row(row![
+            parameter.key,
+            if parameter.no_echo {
+                "(NoEcho)"
+            } else {
+                parameter.value.as_deref().unwrap_or("")
+            },
+            parameter.resolved_value.as_deref().unwrap_or(""),
+        ]);
+    }
+    // The parameters of a stack are not paginated, so all of them are streamed at once.
+    if stream::start() {
+        stack_parameters.parameters.iter().for_each(stream::emit);
+    }
+
+    let mut human_readable = Vec::new();
+    table.print(&mut human_readable)?;
+    let human_readable =
+        String::from_utf8(human_readable).expect("prettytable did not return UTF-8");
+
+    Ok(AwsxOutput {
+        human_readable: format!(
+            "Parameters of stack {}:\n{}",
+            opt.stack_name, human_readable
+        ),
+        structured: CommandOutput::success(
+            format!("Found {} parameters", stack_parameters.parameters.len()),
+            stack_parameters,
+        ),
+        exit_code: ExitCode::Success,
+    })
+}
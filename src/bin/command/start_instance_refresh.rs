@@ -0,0 +1,163 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use awsx::{
+    commands::{
+        auto_scaling::{
+            start_instance_refresh as start_refresh, wait_for_instance_refresh,
+            InstanceRefreshOptions, InstanceRefreshStatus, StartedInstanceRefresh,
+        },
+        CommandOutput,
+    },
+    endpoint,
+    error::Error,
+    http,
+};
+use rusoto_autoscaling::AutoscalingClient;
+use std::time::Duration;
+use structopt::StructOpt;
+
+use crate::{
+    audit::{self, AuditEntry},
+    command::find_auto_scaling_group::AutoScalingGroupSelectorOpt,
+    AwsxOutput, AwsxProvider, ExitCode, Opt as GlobalOpt,
+};
+
+#[derive(Debug, StructOpt)]
+pub(crate) struct Opt {
+    #[structopt(flatten)]
+    auto_scaling_group_selector_opt: AutoScalingGroupSelectorOpt,
+    #[structopt(
+        long = "instance-warmup",
+        help = "Number of seconds until a new instance is considered to be warmed up",
+        long_help = "Number of seconds until a new instance is considered to be warmed up, after \
+                     it reached the `InService` state. If unspecified, the health check grace \
+                     period of the auto-scaling group is used."
+    )]
+    instance_warmup: Option<i64>,
+    #[structopt(
+        long = "min-healthy-percentage",
+        parse(try_from_str = parse_percentage),
+        help = "Percentage of the desired capacity that has to stay healthy during the refresh",
+        long_help = "Percentage of the desired capacity that has to stay in service and healthy \
+                     during the refresh, which determines the size of the batches instances are \
+                     replaced in. If unspecified, AWS defaults to 90 percent."
+    )]
+    min_healthy_percentage: Option<i64>,
+    #[structopt(
+        long = "wait",
+        help = "Wait until the instance refresh has completed",
+        long_help = "Wait until the instance refresh has completed, reporting the progress of each \
+                     batch. If the instance refresh fails or is cancelled, or does not complete \
+                     within --wait-timeout, the command fails."
+    )]
+    wait: bool,
+    #[structopt(
+        long = "wait-timeout",
+        default_value = "3600",
+        help = "Maximum number of seconds to wait for the instance refresh to complete"
+    )]
+    wait_timeout: u64,
+}
+
+fn parse_percentage(percentage: &str) -> Result<i64, String> {
+    match percentage.parse::<i64>() {
+        Ok(percentage) if (0..=100).contains(&percentage) => Ok(percentage),
+        _ => Err(format!(
+            "`{}` is not a valid percentage, expected a number between 0 and 100",
+            percentage
+        )),
+    }
+}
+
+pub(crate) async fn start_instance_refresh(
+    opt: &Opt,
+    global_opt: &GlobalOpt,
+    provider: AwsxProvider,
+) -> Result<AwsxOutput<CommandOutput<InstanceRefreshStatus>>, Error> {
+    let autoscaling = AutoscalingClient::new_with(
+        http::client()?,
+        provider,
+        endpoint::region_for("autoscaling", global_opt.region()),
+    );
+
+    let result = start_refresh(
+        &autoscaling,
+        &opt.auto_scaling_group_selector_opt.selector(),
+        InstanceRefreshOptions {
+            instance_warmup: opt.instance_warmup,
+            min_healthy_percentage: opt.min_healthy_percentage,
+        },
+    )
+    .await;
+    let auto_scaling_group_name = result
+        .as_ref()
+        .ok()
+        .map(|started| started.auto_scaling_group_name.clone());
+    audit::record(
+        AuditEntry::for_resource("StartInstanceRefresh", auto_scaling_group_name)
+            .with_details(result.as_ref().ok()),
+        &result,
+    )
+    .await?;
+    let StartedInstanceRefresh {
+        auto_scaling_group_name,
+        instance_refresh_id,
+    } = result?;
+
+    let status = if opt.wait {
+        tokio::time::timeout(
+            Duration::from_secs(opt.wait_timeout),
+            wait_for_instance_refresh(
+                &autoscaling,
+                &auto_scaling_group_name,
+                &instance_refresh_id,
+                |progress| {
+                    eprintln!(
+                        "{}: {}% complete, {} instances left to update",
+                        progress.status, progress.percentage_complete, progress.instances_to_update
+                    )
+                },
+            ),
+        )
+        .await
+        .unwrap_or_else(|_| {
+            Err(Error::WaitTimeout(format!(
+                "instance refresh {} of {} to complete",
+                instance_refresh_id, auto_scaling_group_name
+            )))
+        })?
+    } else {
+        "Pending".to_owned()
+    };
+
+    Ok(AwsxOutput {
+        human_readable: instance_refresh_id.clone(),
+        structured: CommandOutput::success(
+            if opt.wait {
+                "Instance refresh completed successfully"
+            } else {
+                "Instance refresh started successfully"
+            },
+            InstanceRefreshStatus {
+                auto_scaling_group_name,
+                instance_refresh_id,
+                status,
+            },
+        ),
+        exit_code: ExitCode::Success,
+    })
+}
@@ -0,0 +1,67 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+use awsx::{
+    commands::{
+        cloudformation::template::{self, TemplateGraph},
+        CommandOutput,
+    },
+    error::Error,
+};
+use structopt::StructOpt;
+
+use crate::{util::load_template, AwsxOutput, AwsxProvider, ExitCode, Opt as GlobalOpt};
+
+#[derive(Debug, StructOpt)]
+pub(crate) struct Opt {
+    #[structopt(
+        long = "template-path",
+        help = "Path to the template",
+        long_help = "Path to the template. Specify `-` to read the template from stdin."
+    )]
+    template_path: String,
+    #[structopt(
+        long = "logical-resource-id",
+        help = "Only include the resource and all resources depending on it",
+        long_help = "Only include the given resource and all resources that directly or \
+                     transitively depend on it, i.e. the resources that may be affected by a \
+                     change of it."
+    )]
+    logical_resource_id: Option<String>,
+}
+
+pub(crate) async fn template_graph(
+    opt: &Opt,
+    _global_opt: &GlobalOpt,
+    _provider: AwsxProvider,
+) -> Result<AwsxOutput<CommandOutput<TemplateGraph>>, Error> {
+    let graph = template::template_graph(
+        &load_template(&opt.template_path)?,
+        opt.logical_resource_id.as_deref(),
+    )?;
+
+    Ok(AwsxOutput {
+        human_readable: graph.dot.clone(),
+        structured: CommandOutput::success(
+            format!(
+                "The graph contains {} resources with {} dependencies",
+                graph.resources.len(),
+                graph.dependencies.len()
+            ),
+            graph,
+        ),
+        exit_code: ExitCode::Success,
+    })
+}
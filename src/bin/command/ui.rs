@@ -0,0 +1,323 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use awsx::{
+    commands::CommandOutput,
+    endpoint,
+    error::Error,
+    http,
+    stack::{Stack, StackEventsSince},
+};
+use futures::{StreamExt, TryStreamExt};
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::{Color, Style, Stylize},
+    text::Line,
+    widgets::{Block, List, ListItem, ListState, Row, Table},
+    DefaultTerminal, Frame,
+};
+use rusoto_cloudformation::{
+    ChangeSetSummary, CloudFormation, CloudFormationClient, StackEvent, StackSummary,
+};
+use std::time::{Duration, Instant};
+use structopt::StructOpt;
+
+use crate::{redact, AwsxOutput, AwsxProvider, ExitCode, Opt as GlobalOpt};
+
+/// Number of stack events kept for the selected stack.
+const MAX_EVENTS: usize = 100;
+
+/// Interval in which key presses are polled, which also bounds how long a refresh can be delayed.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, StructOpt)]
+pub(crate) struct Opt {
+    #[structopt(
+        long = "refresh-interval",
+        default_value = "5",
+        help = "Seconds between refreshes of the displayed information"
+    )]
+    refresh_interval: u64,
+}
+
+#[derive(Default)]
+struct App {
+    stacks: Vec<StackSummary>,
+    stacks_state: ListState,
+    /// Name of the stack the change sets and events below belong to.
+    details_stack_name: Option<String>,
+    change_sets: Vec<ChangeSetSummary>,
+    /// Events of the selected stack, newest first.
+    events: Vec<StackEvent>,
+    error: Option<String>,
+}
+
+impl App {
+    fn selected_stack_name(&self) -> Option<String> {
+        self.stacks_state
+            .selected()
+            .and_then(|index| self.stacks.get(index))
+            .map(|stack| stack.stack_name.clone())
+    }
+
+    fn select_next(&mut self) {
+        self.stacks_state.select_next();
+    }
+
+    fn select_previous(&mut self) {
+        self.stacks_state.select_previous();
+    }
+
+    async fn refresh_stacks(&mut self, cfn: &dyn CloudFormation) -> Result<(), Error> {
+        // Retain the selection by name, since stacks might have been created or deleted.
+        let selected_stack_name = self.selected_stack_name();
+        self.stacks = Stack::list(cfn).await?;
+        self.stacks
+            .sort_by(|left, right| left.stack_name.cmp(&right.stack_name));
+        let index = selected_stack_name
+            .and_then(|name| {
+                self.stacks
+                    .iter()
+                    .position(|stack| stack.stack_name == name)
+            })
+            .or(if self.stacks.is_empty() {
+                None
+            } else {
+                Some(0)
+            });
+        self.stacks_state.select(index);
+        Ok(())
+    }
+
+    async fn refresh_details(&mut self, cfn: &dyn CloudFormation) -> Result<(), Error> {
+        let stack_name = match self.selected_stack_name() {
+            Some(stack_name) => stack_name,
+            None => {
+                self.details_stack_name = None;
+                self.change_sets.clear();
+                self.events.clear();
+                return Ok(());
+            }
+        };
+        let stack = Stack::new(&stack_name);
+
+        // Only retrieve the events that are new since the last refresh of the same stack.
+        let since = if self.details_stack_name.as_deref() == Some(&stack_name) {
+            self.events
+                .first()
+                .map(|event| StackEventsSince::EventId(event.event_id.clone()))
+        } else {
+            self.events.clear();
+            None
+        };
+        let mut events: Vec<StackEvent> = stack
+            .events(cfn, since)
+            .take(MAX_EVENTS)
+            .try_collect()
+            .await?;
+        events.append(&mut self.events);
+        events.truncate(MAX_EVENTS);
+        self.events = events;
+        self.change_sets = stack.list_change_sets(cfn).await?;
+        self.details_stack_name = Some(stack_name);
+        Ok(())
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main_area, status_area] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let [stacks_area, details_area] =
+            Layout::horizontal([Constraint::Percentage(30), Constraint::Percentage(70)])
+                .areas(main_area);
+        let [change_sets_area, events_area] =
+            Layout::vertical([Constraint::Length(8), Constraint::Min(0)]).areas(details_area);
+
+        let stacks = List::new(self.stacks.iter().map(|stack| {
+            ListItem::new(Line::from(vec![
+                stack.stack_name.clone().into(),
+                " ".into(),
+                stack
+                    .stack_status
+                    .clone()
+                    .fg(status_color(&stack.stack_status)),
+            ]))
+        }))
+        .block(Block::bordered().title("Stacks"))
+        .highlight_style(Style::new().reversed())
+        .highlight_symbol("> ");
+        frame.render_stateful_widget(stacks, stacks_area, &mut self.stacks_state);
+
+        let title = |title: &str| match &self.details_stack_name {
+            Some(stack_name) => format!("{} of {}", title, stack_name),
+            None => title.to_owned(),
+        };
+
+        let change_sets = Table::new(
+            self.change_sets.iter().map(|change_set| {
+                let status = change_set.status.clone().unwrap_or_default();
+                Row::new(vec![
+                    change_set
+                        .change_set_name
+                        .clone()
+                        .unwrap_or_default()
+                        .into(),
+                    status.clone().fg(status_color(&status)),
+                    change_set
+                        .execution_status
+                        .clone()
+                        .unwrap_or_default()
+                        .into(),
+                    change_set.creation_time.clone().unwrap_or_default().into(),
+                ])
+            }),
+            [
+                Constraint::Percentage(40),
+                Constraint::Percentage(20),
+                Constraint::Percentage(15),
+                Constraint::Percentage(25),
+            ],
+        )
+        .header(Row::new(vec!["Name", "Status", "Execution", "Created"]).bold())
+        .block(Block::bordered().title(title("Change sets")));
+        frame.render_widget(change_sets, change_sets_area);
+
+        let events = Table::new(
+            self.events.iter().map(|event| {
+                let status = event.resource_status.clone().unwrap_or_default();
+                Row::new(vec![
+                    event.timestamp.clone().into(),
+                    event.logical_resource_id.clone().unwrap_or_default().into(),
+                    event.resource_type.clone().unwrap_or_default().into(),
+                    status.clone().fg(status_color(&status)),
+                    redact::redact_str(&event.resource_status_reason.clone().unwrap_or_default())
+                        .into(),
+                ])
+            }),
+            [
+                Constraint::Length(24),
+                Constraint::Percentage(20),
+                Constraint::Percentage(20),
+                Constraint::Percentage(15),
+                Constraint::Min(0),
+            ],
+        )
+        .header(Row::new(vec!["Timestamp", "Logical ID", "Type", "Status", "Reason"]).bold())
+        .block(Block::bordered().title(title("Events")));
+        frame.render_widget(events, events_area);
+
+        let status = match &self.error {
+            Some(error) => Line::from(redact::redact_str(error)).fg(Color::Red),
+            None => Line::from("↑/k ↓/j select stack, r refresh, q quit").dim(),
+        };
+        frame.render_widget(status, status_area);
+    }
+}
+
+/// Color a stack, resource or change set status by its outcome.
+fn status_color(status: &str) -> Color {
+    if status.contains("FAILED") || status.contains("ROLLBACK") {
+        Color::Red
+    } else if status.ends_with("IN_PROGRESS") || status.ends_with("PENDING") {
+        Color::Yellow
+    } else if status.ends_with("COMPLETE") {
+        Color::Green
+    } else {
+        Color::Reset
+    }
+}
+
+async fn run(
+    terminal: &mut DefaultTerminal,
+    cfn: &dyn CloudFormation,
+    refresh_interval: Duration,
+) -> Result<(), Error> {
+    let mut app = App::default();
+    let mut last_refresh: Option<Instant> = None;
+    let mut selection_changed = false;
+    loop {
+        // Errors while refreshing are shown in the status line, instead of ending the UI.
+        if last_refresh
+            .map(|last_refresh| last_refresh.elapsed() >= refresh_interval)
+            .unwrap_or(true)
+        {
+            let result = match app.refresh_stacks(cfn).await {
+                Ok(()) => app.refresh_details(cfn).await,
+                error => error,
+            };
+            app.error = result.err().map(|error| error.to_string());
+            last_refresh = Some(Instant::now());
+        } else if selection_changed {
+            app.error = app
+                .refresh_details(cfn)
+                .await
+                .err()
+                .map(|error| error.to_string());
+        }
+        selection_changed = false;
+
+        terminal.draw(|frame| app.draw(frame))?;
+
+        if event::poll(POLL_INTERVAL)? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+                match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                    KeyCode::Char('r') => last_refresh = None,
+                    KeyCode::Down | KeyCode::Char('j') => {
+                        app.select_next();
+                        selection_changed = true;
+                    }
+                    KeyCode::Up | KeyCode::Char('k') => {
+                        app.select_previous();
+                        selection_changed = true;
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+pub(crate) async fn ui(
+    opt: &Opt,
+    global_opt: &GlobalOpt,
+    provider: AwsxProvider,
+) -> Result<AwsxOutput<CommandOutput<()>>, Error> {
+    let cfn = CloudFormationClient::new_with(
+        http::client()?,
+        provider,
+        endpoint::region_for("cloudformation", global_opt.region()),
+    );
+
+    let mut terminal = ratatui::init();
+    let result = run(
+        &mut terminal,
+        &cfn,
+        Duration::from_secs(opt.refresh_interval),
+    )
+    .await;
+    ratatui::restore();
+    result?;
+
+    Ok(AwsxOutput {
+        human_readable: String::new(),
+        structured: CommandOutput::success("UI closed", ()),
+        exit_code: ExitCode::Success,
+    })
+}
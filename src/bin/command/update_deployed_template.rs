@@ -15,25 +15,33 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use awsx::{
+    commands::{
+        cloudformation::{
+            change_set::ChangeSetOutcome,
+            deploy::{prepare_template_update, OverriddenParameters, TemplateUpdate},
+        },
+        CommandOutput,
+    },
+    endpoint,
     error::Error,
+    http,
     parameter::{Parameter, Parameters},
     s3::S3Uploader,
-    stack::Stack,
-    template::Template,
 };
 use itertools::Itertools;
 use rusoto_cloudformation::CloudFormationClient;
-use rusoto_core::HttpClient;
-use serde_json::json;
-use std::{convert::TryFrom, fs::File, io::BufReader};
 use structopt::StructOpt;
 
 use crate::{
+    command::execute_change_set::{
+        create_prepared_change_set, handle_created_change_set, ChangeSetOpt,
+    },
+    redact,
     util::{
-        apply_defaults, apply_excludes_includes, generate_deployment_metadata,
-        verify_changes_compatible, DeploymentMetadata,
+        apply_excludes_includes, load_parameter_defaults, load_parameter_file, load_template,
+        metadata_options, package_template, s3_uploader, template_git_path, PlaceholderContext,
     },
-    AwsxOutput, AwsxProvider, Opt as GlobalOpt,
+    AwsxOutput, AwsxProvider, ExitCode, Opt as GlobalOpt,
 };
 
 #[derive(Debug, StructOpt)]
@@ -47,7 +55,11 @@ pub(crate) struct Opt {
         help = "IAM Role that AWS CloudFormation assumes when executing the change set"
     )]
     role_arn: Option<String>,
-    #[structopt(long = "template-path", help = "Path to the new template")]
+    #[structopt(
+        long = "template-path",
+        help = "Path to the new template",
+        long_help = "Path to the new template. Specify `-` to read the template from stdin."
+    )]
     template_path: String,
     #[structopt(
         short = "p",
@@ -56,25 +68,29 @@ pub(crate) struct Opt {
         help = "New parameters required by template",
         long_help = "New parameters required by template. Specify as multiple `Key=Value` pairs, \
                      where each key has to correspond to a parameter newly added to the template, \
-                     i.e. the parameter can not be already defined on the stack.\n(If you specify \
+                     i.e. the parameter can not be already defined on the stack, unless \
+                     --allow-parameter-overrides is specified.\n(If you specify \
                      this parameter, you cannot specify --parameter-path, --exclude or --include.)"
     )]
     parameters: Vec<Parameter>,
     #[structopt(
         long = "parameter-path",
-        help = "Path to a JSON parameter file",
+        help = "Path to a JSON or YAML parameter file",
         conflicts_with = "parameters",
-        long_help = "Path to a JSON parameter file. This file should be structured the same as the \
-                     AWS CLI expects. The file can only contain parameters newly added to the \
-                     template, unless the existing parameters are defined as \
+        long_help = "Path to a JSON or YAML parameter file. This file should be structured the \
+                     same as the AWS CLI expects. Values can contain placeholders, i.e. \
+                     `{{stack_name}}`, `{{region}}`, `{{account_id}}` or \
+                     `{{parameters.OtherKey}}`, while other placeholders like dynamic references \
+                     are kept; `{{{{` is a literal `{{`. The file can only contain parameters \
+                     newly added to the template, unless the existing parameters are defined as \
                      `UsePreviousValue=true`.\n(If you specify this parameter, you cannot specify \
                      --parameters.)"
     )]
     parameter_path: Option<String>,
     #[structopt(
         long = "parameter-defaults-path",
-        help = "Path to a JSON parameter file with defaults",
-        long_help = "Path to a JSON parameter file, from which values will be taken if not \
+        help = "Path to a JSON or YAML parameter file with defaults",
+        long_help = "Path to a JSON or YAML parameter file, from which values will be taken if not \
                      specified in the regular parameter file. This file should be structured the \
                      same as the AWS CLI expects. If the provided path does not exist, no error is \
                      thrown, instead it will be simply ignored."
@@ -114,6 +130,18 @@ pub(crate) struct Opt {
                      only those are used that are actually new."
     )]
     only_new_parameters: bool,
+    #[structopt(
+        long = "allow-parameter-overrides",
+        conflicts_with = "only-new-parameters",
+        help = "Allow changing parameters already defined on the stack",
+        long_help = "Allow the given parameters to change the values of parameters that are \
+                     already defined on the stack, in addition to providing the newly added \
+                     parameters. In contrast to --force-create, all newly added parameters still \
+                     have to be provided, parameters the template does not define are still \
+                     rejected, and the template still has to be a direct descendant of the \
+                     deployed template."
+    )]
+    allow_parameter_overrides: bool,
     #[structopt(
         long = "force-create",
         help = "Force change set creation",
@@ -125,52 +153,65 @@ pub(crate) struct Opt {
                      that are not part of the template you are trying to deploy."
     )]
     force_create: bool,
+    #[structopt(
+        long = "package",
+        help = "Package local artifacts referenced by the template",
+        long_help = "Package local artifacts referenced by the template before creating the change \
+                     set, uploading them to S3 the same way the `package` subcommand does. \
+                     Requires --s3-bucket-name."
+    )]
+    package: bool,
+    #[structopt(flatten)]
+    change_set_opt: ChangeSetOpt,
 }
 
 pub(crate) async fn update_stack(
     opt: &Opt,
     global_opt: &GlobalOpt,
     provider: AwsxProvider,
-) -> Result<AwsxOutput, Error> {
+) -> Result<AwsxOutput<CommandOutput<Option<ChangeSetOutcome<OverriddenParameters>>>>, Error> {
     // Load the template
-    let template = Template::new(&opt.template_path)?;
+    let template =
+        load_template(&opt.template_path)?.with_minification(global_opt.minify_templates);
 
     // Create AWS clients
     let cfn = CloudFormationClient::new_with(
-        HttpClient::new()?,
+        http::client()?,
+        provider.clone(),
+        endpoint::region_for("cloudformation", global_opt.region()),
+    );
+    let s3 = s3_uploader(
+        global_opt,
         provider.clone(),
-        global_opt.aws_region.clone().unwrap_or_default(),
+        Some(template_git_path(&opt.template_path)),
     );
-    let s3 = S3Uploader::new(global_opt.aws_region.clone().unwrap_or_default(), provider);
     let s3_upload: Option<(&S3Uploader, &str)> = global_opt
         .s3_bucket_name
         .as_ref()
         .map(|bucket_name| (&s3, bucket_name.as_ref()));
 
-    let stack = Stack::new(&opt.stack_name);
-
-    // Retrieve the parameters defined on the template, as well as the current parameters defined on
-    // the stack.
-    let mut template_parameters = template.get_parameters_as_previous_value();
-    let stack_parameters = stack.get_parameters_as_previous_value(&cfn).await?;
-
-    // Identify newly added parameters, which are parameters defined on the template, but not on the
-    // stack. (Parameters that are defined on the stack but not on the template, so the other way
-    // around, are simply ignored, since they do not need to be set and will simply be removed
-    // once the change-set is deployed.)
-    let new_parameters = template_parameters.clone() - &stack_parameters;
-    // Retrieve the template default values for the newly defined parameters.
-    let template_defaults = template.get_parameters().to_owned() - &stack_parameters;
-
-    // We track all provided parameters in this variable.
-    let mut provided_parameters = new_parameters.clone();
+    // Package local artifacts referenced by the template, if requested.
+    let template = if opt.package {
+        package_template(
+            &template,
+            &opt.template_path,
+            &s3,
+            global_opt.s3_bucket_name.as_deref(),
+        )
+        .await?
+    } else {
+        template
+    };
 
-    // Get and merge in the user provided parameters.
-    provided_parameters.merge(if let Some(parameter_path) = &opt.parameter_path {
-        let file = File::open(parameter_path)?;
-        let reader = BufReader::new(file);
+    // Get the user provided parameters.
+    let placeholder_context = PlaceholderContext {
+        stack_name: Some(&opt.stack_name),
+        global_opt,
+        provider: &provider,
+    };
+    let provided_parameters = if let Some(parameter_path) = &opt.parameter_path {
         let parameters: Parameters = {
-            let parameters: Parameters = serde_json::from_reader(reader).unwrap();
+            let parameters = load_parameter_file(parameter_path, &placeholder_context).await?;
             parameters
                 .values()
                 .filter(|parameter| !parameter.is_previous_value())
@@ -180,127 +221,73 @@ pub(crate) async fn update_stack(
         apply_excludes_includes(parameters, &opt.excludes, &opt.includes)?
     } else {
         (&opt.parameters).into()
-    });
-
-    // Apply defaults if provided.
-    provided_parameters = apply_defaults(provided_parameters, &opt.parameter_defaults_path)?;
-    // Apply defaults from template parameters. This ensures that any defaults specified in the
-    // template itself will be honored and passed onto CloudFormation.
-    provided_parameters.apply_defaults(template_defaults);
-
-    // If requested, remove any parameters that are not new (i.e. don't accidentally overwrite a
-    // parameter).
-    if opt.only_new_parameters {
-        provided_parameters = provided_parameters
-            .values()
-            .filter(|parameter| new_parameters.contains_key(parameter.key()))
-            .collect::<Vec<_>>()
-            .into();
-    }
-
-    // We need to ensure that the user has provided exactly the parameters that have been added.
-    if !new_parameters
-        .keys()
-        .sorted()
-        .eq(provided_parameters.keys().sorted())
-    {
-        if opt.force_create {
-            eprintln!(
-                "WARNING: all newly required parameters ({}) might not have been supplied, or some \
-                 old or non-existent parameters were specified. The change set will be created \
-                 since it was explicitly requested!",
-                new_parameters.keys().join(", ")
-            );
-        } else {
-            return Err(Error::InvalidParameters(format!(
-                "all newly required parameters have to be provided ({}), and no old or \
-                 non-existent parameters can be specified",
-                new_parameters.keys().join(", ")
-            )));
-        }
-    }
-
-    // Update the template parameters with the provided parameters.
-    template_parameters.update(provided_parameters);
-
-    // Unless otherwise requested, we will update the deployment-metadata parameter
-    if !global_opt.dont_update_deployment_metadata {
-        if template_parameters.contains_key(&global_opt.deployment_metadata_parameter) {
-            let previous_metadata_parameter = stack
-                .get_parameter(&cfn, &global_opt.deployment_metadata_parameter)
-                .await?;
-            let previous_metadata =
-                previous_metadata_parameter
-                    .clone()
-                    .and_then(|previous_metadata_parameter| {
-                        DeploymentMetadata::try_from(previous_metadata_parameter).ok()
-                    });
-            let metadata = generate_deployment_metadata(
-                previous_metadata_parameter,
-                Some(&opt.template_path),
-            )?;
-
-            if let Some(previous_metadata) = previous_metadata {
-                // Verify that the changes are compatible
-                let changes_compatible =
-                    verify_changes_compatible(&previous_metadata, &metadata, &opt.template_path)?;
-                if !changes_compatible {
-                    if opt.force_create {
-                        eprintln!(
-                            "WARNING: the changes you are trying to deploy are not a direct \
-                             descendant of the currently deployed changes. The created change-set \
-                             might overwrite and thus destroy the previously deployed changes."
-                        );
-                    } else {
-                        return Err(Error::InvalidTemplate(
-                            "the template provided is not a direct descendant of the currently \
-                             deployed template, creating a changeset might overwrite previously \
-                             deployed changes"
-                                .to_string(),
-                        ));
-                    }
-                }
-            }
+    };
+    let parameter_defaults =
+        load_parameter_defaults(&opt.parameter_defaults_path, &placeholder_context).await?;
 
-            template_parameters.insert(
-                global_opt.deployment_metadata_parameter.clone(),
-                Parameter::WithValue {
-                    key: global_opt.deployment_metadata_parameter.clone(),
-                    value: metadata.to_string(),
-                },
-            );
-        } else {
-            eprintln!(
-                "WARNING: an update to the deployment-metadata parameter '{}' was requested, but \
-                 the template that should be deployed does not have this parameter. The change-set \
-                 will be created, although without any metadata.",
-                &global_opt.deployment_metadata_parameter,
+    let prepared = prepare_template_update(
+        &cfn,
+        TemplateUpdate {
+            stack_name: &opt.stack_name,
+            change_set_name: &opt.change_set_name,
+            template,
+            parameters: provided_parameters,
+            parameter_defaults,
+            only_new_parameters: opt.only_new_parameters,
+            allow_parameter_overrides: opt.allow_parameter_overrides,
+            force_create: opt.force_create,
+        },
+        &metadata_options(global_opt, Some(template_git_path(&opt.template_path))),
+        s3_upload,
+    )
+    .await?;
+    let mut prepared = match prepared {
+        Some(prepared) => prepared,
+        None => {
+            let message = format!(
+                "The template and parameters are identical to what is deployed on stack {}, no \
+                 change set created",
+                opt.stack_name
             );
+            return Ok(AwsxOutput {
+                human_readable: message.clone(),
+                structured: CommandOutput::success(message, None),
+                exit_code: ExitCode::NoChanges,
+            });
         }
+    };
+    let overridden_parameters = &prepared.details.overridden_parameters;
+    if !overridden_parameters.is_empty() {
+        eprintln!(
+            "Overriding parameters already defined on the stack: {}",
+            overridden_parameters.iter().join(", ")
+        );
     }
 
-    // Create the change set for the new template, including the new parameters.
-    template
-        .create_change_set(
-            &cfn,
-            &opt.change_set_name,
-            &opt.stack_name,
-            &template_parameters,
-            opt.role_arn.as_deref(),
-            s3_upload,
-            false,
-        )
-        .await?;
+    redact::register_parameters(&prepared.parameters);
+    prepared.validate(opt.force_create)?;
+    let created = create_prepared_change_set(
+        &opt.change_set_opt,
+        &cfn,
+        global_opt,
+        &prepared,
+        opt.role_arn.as_deref(),
+        s3_upload,
+    )
+    .await?;
 
-    Ok(AwsxOutput {
+    let output = AwsxOutput {
         human_readable: format!(
             "Change set {} creation started successfully",
             opt.change_set_name
         ),
-        structured: json!({
-            "success": true,
-            "change_set_name": opt.change_set_name,
-        }),
-        successful: true,
-    })
+        structured: CommandOutput::success(
+            "Change set creation started successfully",
+            ChangeSetOutcome::created(created, prepared.details),
+        ),
+        exit_code: ExitCode::Success,
+    };
+    handle_created_change_set(&opt.change_set_opt, &cfn, global_opt, &provider, output)
+        .await
+        .map(|output| output.map_result(Some))
 }
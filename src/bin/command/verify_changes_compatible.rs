@@ -14,17 +14,19 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 
-use awsx::{error::Error, stack::Stack};
+use awsx::{
+    commands::{
+        cloudformation::deployment::{self, ChangesCompatibility},
+        CommandOutput,
+    },
+    endpoint,
+    error::Error,
+    http,
+};
 use rusoto_cloudformation::CloudFormationClient;
-use rusoto_core::HttpClient;
-use serde_json::json;
-use std::convert::TryFrom;
 use structopt::StructOpt;
 
-use crate::{
-    util::{self, generate_deployment_metadata, DeploymentMetadata},
-    AwsxOutput, AwsxProvider, Opt as GlobalOpt,
-};
+use crate::{AwsxOutput, AwsxProvider, ExitCode, Opt as GlobalOpt};
 
 #[derive(Debug, StructOpt)]
 pub(crate) struct Opt {
@@ -43,64 +45,48 @@ pub(crate) async fn verify_changes_compatible(
     opt: &Opt,
     global_opt: &GlobalOpt,
     provider: AwsxProvider,
-) -> Result<AwsxOutput, Error> {
+) -> Result<AwsxOutput<CommandOutput<Option<ChangesCompatibility>>>, Error> {
     // Create CloudFormation client
     let cfn = CloudFormationClient::new_with(
-        HttpClient::new()?,
+        http::client()?,
         provider.clone(),
-        global_opt.aws_region.clone().unwrap_or_default(),
+        endpoint::region_for("cloudformation", global_opt.region()),
     );
 
-    // Retrieve previous deployment metadata
-    let stack = Stack::new(&opt.stack_name);
-    let previous_metadata_parameter = stack
-        .get_parameter(&cfn, &global_opt.deployment_metadata_parameter)
-        .await?;
-    let previous_metadata = previous_metadata_parameter.and_then(|previous_metadata_parameter| {
-        DeploymentMetadata::try_from(previous_metadata_parameter).ok()
+    let git_path = opt.git_path.clone().unwrap_or_else(|| {
+        std::env::current_dir()
+            .expect("Failed to get current directory")
+            .into_os_string()
+            .into_string()
+            .expect("Failed to get current directory as string")
     });
+    let compatibility = deployment::verify_changes_compatible(
+        &cfn,
+        &opt.stack_name,
+        &global_opt.deployment_metadata_parameter,
+        &git_path,
+    )
+    .await?;
 
-    match previous_metadata {
+    match compatibility {
         None => Ok(AwsxOutput {
             human_readable: "Stack currently deployed does not have deployment metadata"
                 .to_string(),
-            structured: json!({
-                "success": false,
-                "message": "Stack currently deployed does not have deployment metadata",
-            }),
-            successful: false,
+            structured: CommandOutput::failure(
+                "Stack currently deployed does not have deployment metadata",
+                None,
+            ),
+            exit_code: ExitCode::Failure,
+        }),
+        Some(compatibility) if compatibility.compatible => Ok(AwsxOutput {
+            human_readable: "Changes are compatible".to_string(),
+            structured: CommandOutput::success("Changes are compatible", Some(compatibility)),
+            exit_code: ExitCode::Success,
+        }),
+        Some(compatibility) => Ok(AwsxOutput {
+            human_readable: "Changes are NOT compatible".to_string(),
+            structured: CommandOutput::failure("Changes are NOT compatible", Some(compatibility)),
+            exit_code: ExitCode::Mismatch,
         }),
-        Some(previous_metadata) => {
-            let git_path = opt.git_path.clone().unwrap_or_else(|| {
-                std::env::current_dir()
-                    .expect("Failed to get current directory")
-                    .into_os_string()
-                    .into_string()
-                    .expect("Failed to get current directory as string")
-            });
-            let current_metadata = generate_deployment_metadata(None, Some(&git_path))?;
-            let changes_compatible =
-                util::verify_changes_compatible(&previous_metadata, &current_metadata, &git_path)?;
-
-            if changes_compatible {
-                Ok(AwsxOutput {
-                    human_readable: "Changes are compatible".to_string(),
-                    structured: json!({
-                        "success": true,
-                        "message": "Changes are compatible",
-                    }),
-                    successful: true,
-                })
-            } else {
-                Ok(AwsxOutput {
-                    human_readable: "Changes are NOT compatible".to_string(),
-                    structured: json!({
-                        "success": false,
-                        "message": "Changes are NOT compatible",
-                    }),
-                    successful: false,
-                })
-            }
-        }
     }
 }
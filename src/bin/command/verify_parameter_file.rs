@@ -15,23 +15,24 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use awsx::{
+    commands::{
+        cloudformation::parameters::{
+            self, DifferenceCategory, ParameterFileVerification, VerifiedAgainst,
+        },
+        CommandOutput,
+    },
+    endpoint,
     error::Error,
-    parameter::{Parameter, Parameters},
-    stack::Stack,
-    template::Template,
+    http,
+    parameter::DifferenceFormat,
 };
-use prettytable::row;
 use rusoto_cloudformation::CloudFormationClient;
-use rusoto_core::HttpClient;
-use serde::Serialize;
-use serde_json::json;
-use std::fs::File;
-use std::io::BufReader;
 use structopt::StructOpt;
 
-use crate::{util::apply_defaults, AwsxOutput, AwsxProvider, Opt as GlobalOpt};
-
-const NO_ECHO_PARAMETER_VALUE: &str = "****";
+use crate::{
+    util::{apply_defaults, load_parameter_file, load_template, PlaceholderContext},
+    AwsxOutput, AwsxProvider, ExitCode, Opt as GlobalOpt,
+};
 
 #[derive(Debug, StructOpt)]
 pub(crate) struct Opt {
@@ -53,40 +54,79 @@ pub(crate) struct Opt {
                      specify this if --stack-name has been specified."
     )]
     template_path: Option<String>,
+    #[structopt(
+        long = "change-set-name",
+        requires = "stack-name",
+        help = "Name of a change set of the stack",
+        long_help = "Name of a change set of the stack to compare the parameter-file against, \
+                     instead of the parameters currently deployed on the stack. This allows \
+                     verifying that a pending change set applies exactly the parameters that were \
+                     approved."
+    )]
+    change_set_name: Option<String>,
     #[structopt(
         long = "parameter-path",
         required = true,
-        help = "Path to a JSON parameter file",
-        long_help = "Path to a JSON parameter file. This file should be structured the same as the \
-                     AWS CLI expects."
+        help = "Path to a JSON or YAML parameter file",
+        long_help = "Path to a JSON or YAML parameter file. This file should be structured the \
+                     same as the AWS CLI expects. Values can contain placeholders, i.e. \
+                     `{{stack_name}}`, `{{region}}`, `{{account_id}}` or \
+                     `{{parameters.OtherKey}}`, while other placeholders like dynamic references \
+                     are kept; `{{{{` is a literal `{{`."
     )]
     parameter_path: String,
     #[structopt(
         long = "parameter-defaults-path",
-        help = "Path to a JSON parameter file with defaults",
-        long_help = "Path to a JSON parameter file, from which values will be taken if not \
+        help = "Path to a JSON or YAML parameter file with defaults",
+        long_help = "Path to a JSON or YAML parameter file, from which values will be taken if not \
                      specified in the regular parameter file. This file should be structured the \
                      same as the AWS CLI expects. If the provided path does not exist, no error is \
                      thrown, instead it will be simply ignored."
     )]
     parameter_defaults_path: Option<String>,
+    #[structopt(
+        long = "compare-resolved-values",
+        requires = "stack-name",
+        help = "Compare against the resolved values of the stack or change set",
+        long_help = "Compare the parameter file against the values CloudFormation resolved the \
+                     parameters of the stack or change set to, rather than the values they were \
+                     specified with. This applies to parameters of the AWS Systems Manager \
+                     parameter types, e.g. `AWS::SSM::Parameter::Value<String>`, where the \
+                     specified value is the name of the SSM parameter, and allows verifying a \
+                     parameter file holding the actual values."
+    )]
+    compare_resolved_values: bool,
+    #[structopt(
+        long = "fail-on",
+        possible_values(&["missing", "unequal", "extra"]),
+        help = "Categories of differences that cause the verification to fail",
+        long_help = "Categories of differences that cause the verification to fail, separated by \
+                     spaces: `missing` (parameters only on the stack, change set or template), \
+                     `unequal` (parameters with different values) and `extra` (parameters only in \
+                     the parameter file). Differences of other categories are reported as \
+                     warnings only. Defaults to all categories.\n(The exit code identifies the \
+                     most severe failing category: 2 for unequal, 4 for missing and 5 for extra \
+                     parameters.)"
+    )]
+    fail_on: Vec<DifferenceCategory>,
+    #[structopt(
+        long = "diff-format",
+        default_value = "text",
+        possible_values(&["text", "table"]),
+        help = "Format of the rendered differences",
+        long_help = "Format in which the differences are rendered in the human-readable output: \
+                     `text` shows one line per differing parameter, `table` shows a table with \
+                     the values on the stack, change set or template and in the parameter file."
+    )]
+    diff_format: DifferenceFormat,
 }
 
-#[derive(Debug, Serialize)]
-struct UnequalParameterDifference<'a> {
-    stack: &'a Parameter,
-    template: &'a Parameter,
-}
-
-impl<'a> UnequalParameterDifference<'a> {
-    fn from(unequal_parameters: Vec<(&'a Parameter, &'a Parameter)>) -> Vec<Self> {
-        // By convention, the "left" parameter in our case is the stack, whereas the "right"
-        // parameter is the used parameter-file.
-        unequal_parameters
-            .into_iter()
-            .filter(|(stack, ..)| !matches!(stack, Parameter::WithValue { value, .. } if value == NO_ECHO_PARAMETER_VALUE))
-            .map(|(stack, template)| UnequalParameterDifference { stack, template })
-            .collect()
+/// The exit code identifying the most severe failing category of differences.
+fn exit_code(category: DifferenceCategory) -> ExitCode {
+    match category {
+        DifferenceCategory::Missing => ExitCode::MissingParameters,
+        DifferenceCategory::Unequal => ExitCode::Mismatch,
+        DifferenceCategory::Extra => ExitCode::ExtraParameters,
     }
 }
 
@@ -94,87 +134,87 @@ pub(crate) async fn verify_parameter_file(
     opt: &Opt,
     global_opt: &GlobalOpt,
     provider: AwsxProvider,
-) -> Result<AwsxOutput, Error> {
+) -> Result<AwsxOutput<CommandOutput<ParameterFileVerification>>, Error> {
     // Load parameter file
-    let file = File::open(&opt.parameter_path)?;
-    let reader = BufReader::new(file);
-    let mut file_parameters: Parameters = serde_json::from_reader(reader).unwrap();
+    let placeholder_context = PlaceholderContext {
+        stack_name: opt.stack_name.as_deref(),
+        global_opt,
+        provider: &provider,
+    };
+    let mut file_parameters =
+        load_parameter_file(&opt.parameter_path, &placeholder_context).await?;
 
     // Apply defaults if provided
-    file_parameters = apply_defaults(file_parameters, &opt.parameter_defaults_path)?;
+    file_parameters = apply_defaults(
+        file_parameters,
+        &opt.parameter_defaults_path,
+        &placeholder_context,
+    )
+    .await?;
 
-    let defined_parameters = if let Some(stack_name) = &opt.stack_name {
-        // Create AWS clients
-        let cfn = CloudFormationClient::new_with(
-            HttpClient::new()?,
-            provider.clone(),
-            global_opt.aws_region.clone().unwrap_or_default(),
-        );
-        // Retrieve stack parameters
-        let stack = Stack::new(stack_name);
-        stack.get_parameters(&cfn).await?
+    let against = if let Some(stack_name) = &opt.stack_name {
+        match &opt.change_set_name {
+            Some(change_set_name) => VerifiedAgainst::ChangeSet {
+                stack_name: stack_name.clone(),
+                change_set_name: change_set_name.clone(),
+                compare_resolved_values: opt.compare_resolved_values,
+            },
+            None => VerifiedAgainst::Stack {
+                stack_name: stack_name.clone(),
+                compare_resolved_values: opt.compare_resolved_values,
+            },
+        }
     } else if let Some(template_path) = &opt.template_path {
-        // Load the template
-        let template = Template::new(template_path)?;
-        // Retrieve the parameters defined on the template.
-        template.get_parameters().to_owned()
+        VerifiedAgainst::Template(load_template(template_path)?)
     } else {
         // clap should catch this situation before this code-path is ever reached.
         unreachable!();
     };
 
-    // Compare
-    let differences = defined_parameters.loose_difference(&file_parameters);
-    if let Some(differences) = differences {
-        let mut table = prettytable::Table::new();
-        table.set_format(*prettytable::format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
-        table.set_titles(row![
-            "Only on stack or template",
-            "Unequal between both",
-            "Only in parameter file",
-        ]);
-        for parameter in &differences.left {
-            table.add_row(row![parameter.key(), "", ""]);
-        }
-        for parameter in &differences.unequal {
-            table.add_row(row!["", parameter.0.key(), ""]);
-        }
-        for parameter in &differences.right {
-            table.add_row(row!["", "", parameter.key()]);
-        }
-
-        let mut human_readable = Vec::new();
-        table.print(&mut human_readable)?;
-        let human_readable =
-            String::from_utf8(human_readable).expect("prettytable did not return UTF-8");
+    // Create AWS clients
+    let cfn = CloudFormationClient::new_with(
+        http::client()?,
+        provider.clone(),
+        endpoint::region_for("cloudformation", global_opt.region()),
+    );
 
+    // Compare
+    let verification =
+        parameters::verify_parameter_file(&cfn, &against, &file_parameters, &opt.fail_on).await?;
+    let compared_against = against.description();
+    if let Some(rendered) =
+        verification.render_difference(opt.diff_format, atty::is(atty::Stream::Stdout))
+    {
+        let message = format!(
+            "The parameters in the given file DO NOT MATCH the {}",
+            compared_against
+        );
+        let exit_code = verification
+            .failed_category
+            .map(exit_code)
+            .unwrap_or(ExitCode::Success);
         Ok(AwsxOutput {
-            human_readable,
-            structured: json!({
-                "success": false,
-                "parameters": {
-                    "only_on_stack_or_template": differences.left,
-                    "equal_between_both": differences.equal,
-                    "unequal_between_both": UnequalParameterDifference::from(differences.unequal),
-                    "only_in_parameter_file": differences.right,
-                },
-            }),
-            successful: false,
+            human_readable: format!(
+                "{} (-: only on stack, change set or template, +: only in parameter file, ~: \
+                 unequal):\n{}",
+                message, rendered,
+            ),
+            structured: if verification.failed_category.is_none() {
+                CommandOutput::success(message, verification)
+            } else {
+                CommandOutput::failure(message, verification)
+            },
+            exit_code,
         })
     } else {
+        let message = format!(
+            "The parameters in the given file MATCH the {}",
+            compared_against
+        );
         Ok(AwsxOutput {
-            human_readable: "The parameters in the given file MATCH the CloudFormation stack."
-                .to_string(),
-            structured: json!({
-                "success": true,
-                "parameters": {
-                    "only_on_stack_or_template": [],
-                    "equal_between_both": defined_parameters,
-                    "unequal_between_both": [],
-                    "only_in_parameter_file": [],
-                },
-            }),
-            successful: true,
+            human_readable: format!("{}.", message),
+            structured: CommandOutput::success(message, verification),
+            exit_code: ExitCode::Success,
         })
     }
 }
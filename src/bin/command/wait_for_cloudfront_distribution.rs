@@ -0,0 +1,87 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use awsx::{
+    commands::{
+        cloudfront::{
+            wait_for_cloudfront_distribution as wait_until_deployed, DeployedDistribution,
+        },
+        CommandOutput,
+    },
+    error::Error,
+};
+use std::time::{Duration, Instant};
+use structopt::StructOpt;
+
+use crate::{
+    command::find_cloudfront_distribution::{cloudfront_client, DistributionSelectorOpt},
+    AwsxOutput, AwsxProvider, ExitCode, Opt as GlobalOpt,
+};
+
+#[derive(Debug, StructOpt)]
+pub(crate) struct Opt {
+    #[structopt(flatten)]
+    distribution_selector_opt: DistributionSelectorOpt,
+    #[structopt(
+        long = "timeout",
+        default_value = "1800",
+        help = "Maximum number of seconds to wait for the distribution to be deployed"
+    )]
+    timeout: u64,
+}
+
+pub(crate) async fn wait_for_cloudfront_distribution(
+    opt: &Opt,
+    _global_opt: &GlobalOpt,
+    provider: AwsxProvider,
+) -> Result<AwsxOutput<CommandOutput<DeployedDistribution>>, Error> {
+    let cloudfront = cloudfront_client(provider)?;
+
+    let distribution_id = opt
+        .distribution_selector_opt
+        .selector()
+        .find_id(&cloudfront)
+        .await?;
+    let started = Instant::now();
+    let distribution = tokio::time::timeout(
+        Duration::from_secs(opt.timeout),
+        wait_until_deployed(&cloudfront, &distribution_id, |progress| {
+            eprintln!(
+                "{}: {} ({} invalidations in progress), waiting for {}s",
+                distribution_id,
+                progress.status,
+                progress.in_progress_invalidation_batches,
+                started.elapsed().as_secs()
+            )
+        }),
+    )
+    .await
+    .unwrap_or_else(|_| {
+        Err(Error::WaitTimeout(format!(
+            "CloudFront distribution {} to be deployed",
+            distribution_id
+        )))
+    })?;
+
+    Ok(AwsxOutput {
+        human_readable: format!(
+            "CloudFront distribution {} is deployed",
+            distribution.cloudfront_distribution_id
+        ),
+        structured: CommandOutput::success("CloudFront distribution is deployed", distribution),
+        exit_code: ExitCode::Success,
+    })
+}
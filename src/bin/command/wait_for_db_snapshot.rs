@@ -0,0 +1,89 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use awsx::{
+    commands::{
+        rds::{wait_for_db_snapshot as wait, DbSnapshotStatus},
+        CommandOutput,
+    },
+    endpoint,
+    error::Error,
+    http,
+    rds::DbSnapshot,
+};
+use rusoto_rds::RdsClient;
+use std::time::Duration;
+use structopt::StructOpt;
+
+use crate::{AwsxOutput, AwsxProvider, ExitCode, Opt as GlobalOpt};
+
+#[derive(Debug, StructOpt)]
+pub(crate) struct Opt {
+    #[structopt(
+        long = "db-snapshot-identifier",
+        required_unless = "db-cluster-snapshot-identifier",
+        conflicts_with = "db-cluster-snapshot-identifier",
+        help = "Identifier or ARN of the DB snapshot to wait for"
+    )]
+    db_snapshot_identifier: Option<String>,
+    #[structopt(
+        long = "db-cluster-snapshot-identifier",
+        required_unless = "db-snapshot-identifier",
+        conflicts_with = "db-snapshot-identifier",
+        help = "Identifier or ARN of the DB cluster snapshot to wait for"
+    )]
+    db_cluster_snapshot_identifier: Option<String>,
+    #[structopt(
+        long = "timeout",
+        default_value = "3600",
+        help = "Maximum number of seconds to wait for the snapshot to become available"
+    )]
+    timeout: u64,
+}
+
+impl Opt {
+    fn db_snapshot(&self) -> DbSnapshot {
+        match (
+            &self.db_snapshot_identifier,
+            &self.db_cluster_snapshot_identifier,
+        ) {
+            (Some(identifier), _) => DbSnapshot::Instance(identifier.clone()),
+            (None, Some(identifier)) => DbSnapshot::Cluster(identifier.clone()),
+            (None, None) => unreachable!("structopt requires one of the identifiers"),
+        }
+    }
+}
+
+pub(crate) async fn wait_for_db_snapshot(
+    opt: &Opt,
+    global_opt: &GlobalOpt,
+    provider: AwsxProvider,
+) -> Result<AwsxOutput<CommandOutput<DbSnapshotStatus>>, Error> {
+    let rds = RdsClient::new_with(
+        http::client()?,
+        provider,
+        endpoint::region_for("rds", global_opt.region()),
+    );
+
+    let db_snapshot = opt.db_snapshot();
+    let status = wait(&rds, &db_snapshot, Duration::from_secs(opt.timeout)).await?;
+
+    Ok(AwsxOutput {
+        human_readable: format!("DB snapshot {} is available", db_snapshot.identifier()),
+        structured: CommandOutput::success("DB snapshot is available", status),
+        exit_code: ExitCode::Success,
+    })
+}
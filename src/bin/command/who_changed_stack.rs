@@ -0,0 +1,104 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+use awsx::{
+    cloudtrail::CloudTrailClient,
+    commands::{
+        cloudtrail::{who_changed_stack as who_changed, StackChanges},
+        CommandOutput,
+    },
+    endpoint,
+    error::Error,
+    http,
+};
+use chrono::SecondsFormat;
+use prettytable::row;
+use structopt::StructOpt;
+
+use crate::{AwsxOutput, AwsxProvider, ExitCode, Opt as GlobalOpt};
+
+#[derive(Debug, StructOpt)]
+pub(crate) struct Opt {
+    #[structopt(long = "stack-name", help = "Name of the stack to list the changes of")]
+    stack_name: String,
+    #[structopt(
+        long = "days",
+        default_value = "7",
+        help = "Number of days to look back",
+        long_help = "Number of days to look back. CloudTrail retains the management events of the \
+                     last 90 days."
+    )]
+    days: i64,
+}
+
+pub(crate) async fn who_changed_stack(
+    opt: &Opt,
+    global_opt: &GlobalOpt,
+    provider: AwsxProvider,
+) -> Result<AwsxOutput<CommandOutput<StackChanges>>, Error> {
+    let cloudtrail = CloudTrailClient::new_with(
+        http::client()?,
+        provider,
+        endpoint::region_for("cloudtrail", global_opt.region()),
+    );
+
+    let changes = who_changed(&cloudtrail, &opt.stack_name, opt.days).await?;
+
+    let human_readable = if changes.events.is_empty() {
+        format!(
+            "No changes to stack {} within the last {} days.",
+            opt.stack_name, opt.days
+        )
+    } else {
+        let mut table = prettytable::Table::new();
+        table.set_format(*prettytable::format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+        table.set_titles(row!["Time", "Action", "Caller", "Source IP", "Result"]);
+        for event in &changes.events {
+            table.add_row(row![
+                event
+                    .event_time
+                    .map(|time| time.to_rfc3339_opts(SecondsFormat::Secs, true))
+                    .unwrap_or_default(),
+                event.event_name,
+                event
+                    .caller_arn
+                    .as_deref()
+                    .or(event.username.as_deref())
+                    .unwrap_or("unknown"),
+                event.source_ip_address.as_deref().unwrap_or_default(),
+                event
+                    .error_code
+                    .as_deref()
+                    .map(|error_code| format!("failed ({})", error_code))
+                    .unwrap_or_else(|| "succeeded".to_owned()),
+            ]);
+        }
+        format!(
+            "Changes to stack {} within the last {} days:\n{}",
+            opt.stack_name,
+            opt.days,
+            table.to_string().trim_end()
+        )
+    };
+
+    Ok(AwsxOutput {
+        human_readable,
+        structured: CommandOutput::success(
+            format!("Found {} changes to the stack", changes.events.len()),
+            changes,
+        ),
+        exit_code: ExitCode::Success,
+    })
+}
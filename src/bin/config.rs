@@ -0,0 +1,72 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! The optional configuration file of awsx.
+//!
+//! The configuration file provides defaults for global options, which allows teams to share their
+//! settings through the repository of their stacks. Options provided on the command-line always
+//! take precedence over the configuration file.
+
+use awsx::error::Error;
+use serde::Deserialize;
+use std::{collections::HashMap, path::Path};
+
+use crate::notification::NotificationConfig;
+
+/// The configuration file used if no path was provided explicitly, if it exists.
+pub(crate) const DEFAULT_CONFIG_PATH: &str = ".awsx.yml";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub(crate) struct Config {
+    pub(crate) hooks: HooksConfig,
+    pub(crate) guard_rules: Vec<String>,
+    pub(crate) audit_log_path: Option<String>,
+    pub(crate) s3_bucket_name: Option<String>,
+    pub(crate) ca_bundle: Option<String>,
+    pub(crate) connect_timeout: Option<u64>,
+    pub(crate) read_timeout: Option<u64>,
+    pub(crate) connect_retries: Option<u32>,
+    /// Logical IDs or resource types of the resources that must not be removed or replaced, keyed
+    /// by the stack name, where `*` applies to all stacks.
+    pub(crate) protected_resources: HashMap<String, Vec<String>>,
+    /// Webhooks to notify when change sets are created or executed.
+    pub(crate) notifications: Vec<NotificationConfig>,
+}
+
+/// Shell commands to run at the stages of a deployment.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub(crate) struct HooksConfig {
+    pub(crate) pre_change_set: Option<String>,
+    pub(crate) post_change_set_create: Option<String>,
+    pub(crate) post_execute: Option<String>,
+}
+
+impl Config {
+    /// Load the configuration file from the given path, or the default path if it exists.
+    pub(crate) fn load(path: Option<&str>) -> Result<Config, Error> {
+        let path = match path {
+            Some(path) => Path::new(path),
+            None if Path::new(DEFAULT_CONFIG_PATH).exists() => Path::new(DEFAULT_CONFIG_PATH),
+            None => return Ok(Config::default()),
+        };
+
+        let contents = std::fs::read(path)?;
+        serde_yaml::from_slice(&contents)
+            .map_err(|error| Error::InvalidConfiguration(format!("{}: {}", path.display(), error)))
+    }
+}
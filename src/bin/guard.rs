@@ -0,0 +1,186 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Policy-as-code validation of templates through cfn-guard.
+
+use awsx::{
+    error::Error,
+    parameter::{Parameter, Parameters},
+    template::Template,
+};
+use serde_json::{json, Map, Value};
+use std::{
+    fs::OpenOptions,
+    io::Write,
+    path::PathBuf,
+    process::{Command, Output},
+};
+use uuid::Uuid;
+
+use crate::{redact, Opt as GlobalOpt};
+
+/// Exit code of `cfn-guard validate` if at least one rule failed.
+const GUARD_VALIDATION_FAILED: i32 = 19;
+
+/// A file in the temporary directory that is removed once dropped.
+struct TemporaryFile(PathBuf);
+
+impl TemporaryFile {
+    /// Create the file with the given contents.
+    ///
+    /// The contents can include secret parameter values, which is why the file is only readable by
+    /// the current user. Creating the file fails if the path exists already, rather than following
+    /// a symlink planted there.
+    fn create(contents: &[u8]) -> Result<TemporaryFile, Error> {
+        let path = std::env::temp_dir().join(format!("awsx-guard-{}", Uuid::new_v4()));
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(&path)?;
+        // From here on, the file is removed again if writing fails.
+        let temporary_file = TemporaryFile(path);
+        file.write_all(contents)?;
+        Ok(temporary_file)
+    }
+}
+
+impl Drop for TemporaryFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Validate the template and the parameters it will be deployed with against the configured
+/// cfn-guard rules, if any.
+///
+/// Parameters with a value are provided to the rules as `ResolvedParameters`, mapping each key to
+/// its value. Parameters using their previous value are not included, since their value is not
+/// known to awsx.
+pub(crate) fn validate_template(
+    global_opt: &GlobalOpt,
+    template: &Template,
+    parameters: &Parameters,
+) -> Result<(), Error> {
+    if global_opt.guard_rules.is_empty() {
+        return Ok(());
+    }
+
+    let resolved_parameters: Map<String, Value> = parameters
+        .values()
+        .filter_map(|parameter| match parameter {
+            Parameter::WithValue { key, value } => Some((key.clone(), Value::from(value.clone()))),
+            Parameter::PreviousValue { .. } => None,
+        })
+        .collect();
+    let template_file = TemporaryFile::create(template.contents())?;
+    let parameters_file = TemporaryFile::create(
+        json!({ "ResolvedParameters": resolved_parameters })
+            .to_string()
+            .as_bytes(),
+    )?;
+
+    let mut command = Command::new(&global_opt.guard_cmd);
+    command
+        .arg("validate")
+        .arg("--data")
+        .arg(&template_file.0)
+        .arg("--input-parameters")
+        .arg(&parameters_file.0)
+        .args(["--show-summary", "fail"])
+        .args(["--output-format", "single-line-summary"]);
+    for rules in &global_opt.guard_rules {
+        command.arg("--rules").arg(rules);
+    }
+
+    let output = command.output().map_err(|error| {
+        Error::PolicyValidationFailed(format!(
+            "failed to run '{}': {}",
+            global_opt.guard_cmd, error
+        ))
+    })?;
+    validation_result(&global_opt.guard_cmd, &output)
+}
+
+/// Map the output of `cfn-guard validate` to the outcome of the validation.
+///
+/// The rules that failed are reported on stdout, while any other failure is reported on stderr.
+/// Both can contain parameter values, so they are redacted.
+fn validation_result(guard_cmd: &str, output: &Output) -> Result<(), Error> {
+    match output.status.code() {
+        Some(0) => Ok(()),
+        Some(GUARD_VALIDATION_FAILED) => Err(Error::PolicyValidationFailed(redact::redact_str(
+            String::from_utf8_lossy(&output.stdout).trim(),
+        ))),
+        _ => Err(Error::PolicyValidationFailed(format!(
+            "'{}' exited unsuccessfully ({}): {}",
+            guard_cmd,
+            output.status,
+            redact::redact_str(String::from_utf8_lossy(&output.stderr).trim()),
+        ))),
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::ExitStatus;
+
+    fn output(code: i32, stdout: &str, stderr: &str) -> Output {
+        Output {
+            status: ExitStatus::from_raw(code << 8),
+            stdout: stdout.as_bytes().to_vec(),
+            stderr: stderr.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn successful_validation_passes() {
+        assert!(validation_result("cfn-guard", &output(0, "", "")).is_ok());
+    }
+
+    #[test]
+    fn failed_rules_are_reported_from_stdout() {
+        let error = validation_result(
+            "cfn-guard",
+            &output(
+                GUARD_VALIDATION_FAILED,
+                "Bucket FAIL encryption\n",
+                "ignored",
+            ),
+        )
+        .unwrap_err();
+        assert_eq!(
+            "policy validation failed:\nBucket FAIL encryption",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn other_failures_are_reported_from_stderr() {
+        let error = validation_result(
+            "cfn-guard",
+            &output(5, "ignored", "Parser Error when parsing rules\n"),
+        )
+        .unwrap_err();
+        assert_eq!(
+            "policy validation failed:\n'cfn-guard' exited unsuccessfully (exit status: 5): \
+             Parser Error when parsing rules",
+            error.to_string()
+        );
+    }
+}
@@ -0,0 +1,261 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use awsx::{deployment::DeploymentMetadata, error::Error};
+use serde::{Deserialize, Serialize};
+use std::process::{Command, Stdio};
+use tokio::io::AsyncWriteExt;
+
+use crate::{redact, Opt as GlobalOpt};
+
+/// Lifecycle points at which the hook command is invoked and notifications are sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum HookEvent {
+    ChangeSetCreated,
+    ExecutionStarted,
+    DeploymentFinished,
+}
+
+impl HookEvent {
+    /// The name of the event, as it appears in the JSON payload.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            HookEvent::ChangeSetCreated => "change_set_created",
+            HookEvent::ExecutionStarted => "execution_started",
+            HookEvent::DeploymentFinished => "deployment_finished",
+        }
+    }
+}
+
+/// The JSON payload passed to the hook command via stdin.
+#[derive(Debug, Serialize)]
+pub(crate) struct HookPayload<'a> {
+    pub(crate) event: HookEvent,
+    pub(crate) stack_name: &'a str,
+    pub(crate) change_set_name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) stack_status: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) deployment_metadata: Option<DeploymentMetadata>,
+    pub(crate) awsx_version: &'static str,
+}
+
+impl<'a> HookPayload<'a> {
+    pub(crate) fn new(event: HookEvent, stack_name: &'a str, change_set_name: &'a str) -> Self {
+        HookPayload {
+            event,
+            stack_name,
+            change_set_name,
+            stack_status: None,
+            deployment_metadata: None,
+            awsx_version: env!("CARGO_PKG_VERSION"),
+        }
+    }
+
+    pub(crate) fn with_stack_status(mut self, stack_status: &'a str) -> Self {
+        self.stack_status = Some(stack_status);
+        self
+    }
+
+    pub(crate) fn with_deployment_metadata(
+        mut self,
+        deployment_metadata: Option<DeploymentMetadata>,
+    ) -> Self {
+        self.deployment_metadata = deployment_metadata;
+        self
+    }
+}
+
+/// Invoke the hook command, if one was configured, passing the payload as JSON via stdin, with the
+/// values of sensitive parameters masked.
+///
+/// A failing hook will not fail the command itself, since the hook is only meant to trigger side
+/// effects. Instead a warning is printed.
+pub(crate) async fn run_hook(global_opt: &GlobalOpt, payload: &HookPayload<'_>) {
+    let hook_cmd = match &global_opt.hook_cmd {
+        Some(hook_cmd) => hook_cmd,
+        None => return,
+    };
+
+    let result = async {
+        let payload = redact::redact_json(serde_json::to_value(payload)?)
+            .to_string()
+            .into_bytes();
+        // Anything the hook prints must not end up in the output of awsx.
+        let mut child = tokio::process::Command::new(hook_cmd)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::from(std::io::stderr()))
+            .spawn()?;
+        // Wait for the hook even if writing the payload failed, to not leave a zombie behind.
+        let written = match child.stdin.take() {
+            // Dropping stdin closes it, signalling the end of the payload to the hook.
+            Some(mut stdin) => stdin.write_all(&payload).await,
+            None => Ok(()),
+        };
+        let status = child.wait().await?;
+        written.map(|()| status)
+    }
+    .await;
+
+    match result {
+        Ok(status) if status.success() => {}
+        Ok(status) => eprintln!(
+            "WARNING: the hook command '{}' exited unsuccessfully ({}) for event '{}'.",
+            hook_cmd,
+            status,
+            payload.event.name(),
+        ),
+        Err(e) => eprintln!(
+            "WARNING: failed to run the hook command '{}' for event '{}': {}",
+            hook_cmd,
+            payload.event.name(),
+            e,
+        ),
+    }
+}
+
+/// The stages of a deployment at which the configured shell commands are run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HookStage {
+    PreChangeSet,
+    PostChangeSetCreate,
+    PostExecute,
+}
+
+impl HookStage {
+    fn name(self) -> &'static str {
+        match self {
+            HookStage::PreChangeSet => "pre-change-set",
+            HookStage::PostChangeSetCreate => "post-change-set-create",
+            HookStage::PostExecute => "post-execute",
+        }
+    }
+
+    fn command(self, global_opt: &GlobalOpt) -> Option<&str> {
+        match self {
+            HookStage::PreChangeSet => global_opt.pre_change_set_hook.as_deref(),
+            HookStage::PostChangeSetCreate => global_opt.post_change_set_create_hook.as_deref(),
+            HookStage::PostExecute => global_opt.post_execute_hook.as_deref(),
+        }
+    }
+}
+
+/// The context exported to the shell command of a stage through environment variables.
+#[derive(Debug)]
+pub(crate) struct HookContext<'a> {
+    pub(crate) stack_name: &'a str,
+    pub(crate) change_set_name: &'a str,
+    pub(crate) stack_status: Option<&'a str>,
+    pub(crate) output: Option<&'a serde_json::Value>,
+}
+
+impl<'a> HookContext<'a> {
+    pub(crate) fn new(stack_name: &'a str, change_set_name: &'a str) -> Self {
+        HookContext {
+            stack_name,
+            change_set_name,
+            stack_status: None,
+            output: None,
+        }
+    }
+
+    pub(crate) fn with_stack_status(mut self, stack_status: &'a str) -> Self {
+        self.stack_status = Some(stack_status);
+        self
+    }
+
+    pub(crate) fn with_output(mut self, output: &'a serde_json::Value) -> Self {
+        self.output = Some(output);
+        self
+    }
+}
+
+/// Run the shell command configured for the stage, if any.
+///
+/// In contrast to [`run_hook`], a failing command fails awsx, which allows stage hooks to act as
+/// gates, e.g. through smoke tests. Like for [`run_hook`], the stdout of the command is redirected
+/// to stderr.
+pub(crate) fn run_stage_hook(
+    global_opt: &GlobalOpt,
+    stage: HookStage,
+    context: &HookContext,
+) -> Result<(), Error> {
+    let command = match stage.command(global_opt) {
+        Some(command) => command,
+        None => return Ok(()),
+    };
+
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    // The output of the command must not end up in the output of awsx.
+    shell
+        .arg(command)
+        .stdout(Stdio::from(std::io::stderr()))
+        .env("AWSX_HOOK_STAGE", stage.name())
+        .env("AWSX_STACK_NAME", context.stack_name)
+        .env("AWSX_CHANGE_SET_NAME", context.change_set_name)
+        .env("AWSX_VERSION", env!("CARGO_PKG_VERSION"));
+    if let Some(stack_status) = context.stack_status {
+        shell.env("AWSX_STACK_STATUS", stack_status);
+    }
+    if let Some(output) = context.output {
+        shell.env(
+            "AWSX_OUTPUT",
+            redact::redact_json(output.clone()).to_string(),
+        );
+    }
+
+    let status = shell.status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(Error::HookFailed(format!(
+            "the {} hook '{}' exited unsuccessfully ({})",
+            stage.name(),
+            command,
+            status
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn event_name_matches_payload() {
+        for (event, name) in [
+            (HookEvent::ChangeSetCreated, "change_set_created"),
+            (HookEvent::ExecutionStarted, "execution_started"),
+            (HookEvent::DeploymentFinished, "deployment_finished"),
+        ] {
+            assert_eq!(name, event.name());
+            assert_eq!(
+                name,
+                serde_json::to_value(HookPayload::new(event, "stack", "change-set")).unwrap()
+                    ["event"]
+            );
+        }
+    }
+}
@@ -14,31 +14,80 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 
-use awsx::{error::Error, provider::AwsxProvider};
+use awsx::{
+    changeset::is_no_changes_reason,
+    commands::CommandOutput,
+    endpoint::{self, EndpointOptions},
+    error::Error,
+    http::{self, HttpClientOptions},
+    provider::AwsxProvider,
+    region::{self, RegionSource},
+    s3::{ChecksumAlgorithm, UrlStyle},
+    tags::TagSpecification,
+};
 use rusoto_core::Region;
+use semver::{Version, VersionReq};
 use serde::{Serialize, Serializer};
-use std::str::FromStr;
+use serde_json::json;
+use std::{collections::HashMap, str::FromStr, time::Duration};
 use structopt::StructOpt;
 
+mod audit;
+mod canary;
 mod command;
+mod config;
+mod guard;
+mod hook;
+mod notification;
+mod plugin;
+mod redact;
+mod stream;
 mod util;
 
+#[cfg(feature = "tui")]
+use command::ui;
 use command::{
-    create_stack, find_amis_inuse, find_auto_scaling_group, find_cloudfront_distribution,
-    find_db_cluster_snapshot, find_db_snapshot, find_target_group, identify_new_parameters,
-    override_parameters, update_deployed_template, verify_changes_compatible,
-    verify_parameter_file,
+    blue_green, clone_stack, compare_stacks, copy_db_snapshot, create_cloudfront_invalidation,
+    create_db_snapshot, create_stack, delete_change_set, deployment_changelog, deployment_report,
+    deregister_targets, describe_change_set, execute_change_set, find_amis_inuse,
+    find_auto_scaling_group, find_cloudfront_distribution, find_db_cluster_snapshot,
+    find_db_snapshot, find_target_group, get_target_health, iam_policy, identify_new_parameters,
+    lint_template, move_resource, override_parameters, package, promote_stack, prune_templates,
+    register_targets, resume_auto_scaling_processes, self_update, set_auto_scaling_group_capacity,
+    share_db_snapshot, shift_route53_weights, show_stack_parameters, start_instance_refresh,
+    suspend_auto_scaling_processes, template_graph, update_deployed_template,
+    verify_changes_compatible, verify_parameter_file, wait_for_cloudfront_distribution,
+    wait_for_db_snapshot, who_changed_stack,
 };
+use config::Config;
+use notification::NotificationConfig;
 
 #[derive(Debug, StructOpt)]
-#[structopt(author)]
+#[structopt(
+    author,
+    after_help = "Exit codes:\n\
+                  - 0: the command was successful\n\
+                  - 1: the command failed, e.g. due to an AWS or runtime error\n\
+                  - 2: a verification failed, e.g. the parameter file does not match the stack\n\
+                  - 3: there was nothing to do, e.g. a change set would not contain any changes\n\
+                  - 4: a verification found parameters missing from the parameter file\n\
+                  - 5: a verification found parameters only defined in the parameter file\n\
+                  \n\
+                  Plugins:\n\
+                  Any other subcommand is dispatched to an `awsx-<subcommand>` executable on the \
+                  PATH, which receives the region and resolved credentials via the standard AWS \
+                  environment variables."
+)]
 pub(crate) struct Opt {
     #[structopt(
         long = "aws-region",
         help = "Region the AWS API calls should be performed in",
         long_help = "Region the AWS API calls should be performed in. If left unspecified, the \
-                     region will be determined automatically, falling back to us-east-1 should it \
-                     fail."
+                     region is taken from the environment variables AWS_REGION and \
+                     AWS_DEFAULT_REGION, the profile in the AWS config file, or the instance \
+                     metadata service of EC2, in this order. Should all of them fail, us-east-1 \
+                     is used with a warning. Subcommands that don't perform AWS API calls, like \
+                     lint-template, skip the instance metadata service and the warning."
     )]
     pub aws_region: Option<Region>,
     #[structopt(
@@ -63,6 +112,61 @@ pub(crate) struct Opt {
         requires = "aws-access-key-id"
     )]
     pub aws_secret_access_key: Option<String>,
+    #[structopt(
+        long = "ca-bundle",
+        env = "AWS_CA_BUNDLE",
+        help = "PEM file of additional certificates to trust",
+        long_help = "PEM file of certificates to trust in addition to those of the operating \
+                     system, e.g. the certificate of the CA a proxy intercepting TLS connections \
+                     signs its certificates with. Applies to all connections, i.e. to AWS and to \
+                     webhooks. (Can be configured as `ca-bundle` in the configuration file.)"
+    )]
+    pub ca_bundle: Option<String>,
+    #[structopt(
+        long = "connect-timeout",
+        value_name = "seconds",
+        help = "Seconds to wait for a connection to be established [default: 10]",
+        long_help = "Seconds to wait for a connection to be established, before the attempt is \
+                     retried or awsx fails with a timeout error. Defaults to 10 seconds. (Can be \
+                     configured as `connect-timeout` in the configuration file.)"
+    )]
+    pub connect_timeout: Option<u64>,
+    #[structopt(
+        long = "read-timeout",
+        value_name = "seconds",
+        help = "Seconds to wait for the response to a request [default: 120]",
+        long_help = "Seconds to wait for the response to a request once it was sent, and for \
+                     every further part of its body, before awsx fails with a timeout error. \
+                     Defaults to 120 seconds. (Can be configured as `read-timeout` in the \
+                     configuration file.)"
+    )]
+    pub read_timeout: Option<u64>,
+    #[structopt(
+        long = "connect-retries",
+        help = "How often to retry establishing a connection [default: 2]",
+        long_help = "How often to retry establishing a connection that failed or timed out, before \
+                     awsx fails. Defaults to 2 retries. (Can be configured as `connect-retries` in \
+                     the configuration file.)"
+    )]
+    pub connect_retries: Option<u32>,
+    #[structopt(
+        long = "use-fips-endpoints",
+        help = "Use the FIPS endpoints of all AWS services",
+        long_help = "Use the FIPS endpoints of all AWS services, e.g. \
+                     `cloudformation-fips.us-gov-west-1.amazonaws.com`, which only use FIPS \
+                     140-2 validated cryptography. Global services like CloudFront and Route53 \
+                     keep using their default endpoints."
+    )]
+    pub use_fips_endpoints: bool,
+    #[structopt(
+        long = "use-dualstack-endpoints",
+        help = "Use the dual-stack endpoints of all AWS services",
+        long_help = "Use the dual-stack endpoints of all AWS services, e.g. \
+                     `cloudformation.eu-central-1.api.aws`, which can be reached through IPv4 and \
+                     IPv6. Can be combined with `--use-fips-endpoints`. Global services like \
+                     CloudFront and Route53 keep using their default endpoints."
+    )]
+    pub use_dualstack_endpoints: bool,
     #[structopt(
         long = "assume-role-arn",
         help = "Optional role to assume before executing AWS API calls",
@@ -71,14 +175,53 @@ pub(crate) struct Opt {
                      in a single account. If unspecified, no role will be assumed."
     )]
     pub assume_role_arn: Option<String>,
+    #[structopt(
+        long = "assume-role-source-identity",
+        help = "Source identity to assume roles with",
+        long_help = "Source identity to assume roles with, which CloudTrail records for all \
+                     actions performed with the role, and which service control policies can \
+                     require. The default is the git user.email, which is also recorded in the \
+                     deployment metadata. The trust policy of the role has to allow \
+                     `sts:SetSourceIdentity`."
+    )]
+    pub assume_role_source_identity: Option<String>,
+    #[structopt(
+        long = "dont-set-source-identity",
+        conflicts_with = "assume-role-source-identity",
+        help = "Do not assume roles with a source identity",
+        long_help = "Do not assume roles with a source identity, e.g. if the trust policy of the \
+                     role does not allow `sts:SetSourceIdentity`."
+    )]
+    pub dont_set_source_identity: bool,
+    #[structopt(
+        long = "assume-role-tag",
+        number_of_values = 1,
+        help = "Session tag to assume roles with, as `Key=Value`",
+        long_help = "Session tag to assume roles with, specified as `Key=Value`, e.g. \
+                     `team=platform`. IAM policies can use the tags of the session to scope the \
+                     resources the role may access. Can be specified multiple times. The trust \
+                     policy of the role has to allow `sts:TagSession`."
+    )]
+    pub assume_role_tags: Vec<TagSpecification>,
     #[structopt(
         long = "output-format",
         help = "Specify the format of the application output",
         long_help = "Specify the format of the application output. The default, if left \
                      unspecified, depends on whether stdout is a TTY. If it is, the output will be \
                      human readable. If it isn't, the contents will be output in structured form, \
-                     specifically JSON.",
-        possible_values(&["human", "human-readable", "structured", "json", "yml", "yaml"])
+                     specifically JSON.\n(With `ndjson`, commands producing lists write every item \
+                     as a single line of JSON as soon as it is available. All other commands write \
+                     their output as a single line of JSON.)",
+        possible_values(&[
+            "human",
+            "human-readable",
+            "structured",
+            "json",
+            "ndjson",
+            "json-lines",
+            "yml",
+            "yaml"
+        ])
     )]
     pub output_format: Option<OutputFormat>,
     #[structopt(
@@ -88,9 +231,54 @@ pub(crate) struct Opt {
                      stack template will upload the template to S3 if this parameter is specified. \
                      If the parameter is unspecified, the awsx will try to provide the template \
                      within the API call to AWS, although the template size here is limited to \
-                     51,200 bytes (enforced by the AWS API)."
+                     51,200 bytes (enforced by the AWS API), failing early for larger templates. \
+                     (Can be configured as `s3-bucket-name` in the configuration file.)"
     )]
     pub s3_bucket_name: Option<String>,
+    #[structopt(
+        long = "s3-path-style-urls",
+        help = "Refer to uploaded templates with path-style S3 URLs",
+        long_help = "Refer to uploaded templates and artifacts with path-style S3 URLs, i.e. \
+                     `https://s3.<region>.amazonaws.com/<bucket>/<key>`, instead of the \
+                     virtual-hosted-style URLs `https://<bucket>.s3.<region>.amazonaws.com/<key>`. \
+                     Path-style URLs are deprecated by AWS, but they are still used for buckets \
+                     whose names contain dots."
+    )]
+    pub s3_path_style_urls: bool,
+    #[structopt(
+        long = "s3-object-tag",
+        number_of_values = 1,
+        help = "Tag to attach to uploaded templates and artifacts, e.g. `Key=Value`",
+        long_help = "Tag to attach to all templates and artifacts uploaded to S3, of the form \
+                     `Key=Value`, e.g. to select them in lifecycle rules of the bucket. Can be \
+                     specified multiple times. Independent of the tags, uploaded objects carry \
+                     the stack, the git commit and the user as their metadata. Objects that exist \
+                     already are not uploaded again, so they keep the tags and metadata of their \
+                     first upload."
+    )]
+    pub s3_object_tags: Vec<TagSpecification>,
+    #[structopt(
+        long = "checksum-algorithm",
+        default_value = "md5",
+        possible_values(&["md5", "sha256"]),
+        help = "Hash algorithm used to name templates and artifacts uploaded to S3",
+        long_help = "Hash algorithm used to name templates and artifacts uploaded to S3, which \
+                     deduplicates uploads of identical contents. The default, `md5`, matches the \
+                     AWS CLI, so that uploads are deduplicated across both tools. Use `sha256` if \
+                     MD5 must not be used. The same algorithm is used for the template checksums \
+                     in the audit log."
+    )]
+    pub checksum_algorithm: ChecksumAlgorithm,
+    #[structopt(
+        long = "minify-templates",
+        help = "Minify templates exceeding the size limit for inline submission",
+        long_help = "Minify templates that are submitted within the API call to AWS, i.e. without \
+                     --s3-bucket-name, should they exceed the limit of 51,200 bytes. Comments and \
+                     insignificant whitespace are removed, and YAML templates are normalized. \
+                     Checksums, e.g. in the audit log, are still computed on the original \
+                     template."
+    )]
+    pub minify_templates: bool,
     #[structopt(
         long = "dont-update-deployment-metadata",
         help = "Do not update the stack parameter containing deployment metadata",
@@ -109,12 +297,328 @@ pub(crate) struct Opt {
                      updates with the `--dont-update-deployment-metadata` option."
     )]
     pub deployment_metadata_parameter: String,
+    #[structopt(
+        long = "hook-cmd",
+        help = "Program to invoke at key lifecycle points",
+        long_help = "Program to invoke at key lifecycle points, for example once a change set has \
+                     been created. The program receives a JSON payload describing the event via \
+                     stdin, and its stdout is redirected to stderr to keep the output of awsx \
+                     intact. A failing hook will not fail the command, instead a warning is \
+                     printed."
+    )]
+    pub hook_cmd: Option<String>,
+    #[structopt(
+        long = "config-path",
+        help = "Path to the awsx configuration file",
+        long_help = "Path to the awsx configuration file, which provides defaults for global \
+                     options. Options provided on the command-line take precedence. If \
+                     unspecified, `.awsx.yml` in the current directory is used if it exists."
+    )]
+    pub config_path: Option<String>,
+    #[structopt(
+        long = "pre-change-set-hook",
+        help = "Shell command to run before a change set is created",
+        long_help = "Shell command to run before a change set is created. The context is provided \
+                     through environment variables, i.e. `AWSX_HOOK_STAGE`, `AWSX_STACK_NAME` and \
+                     `AWSX_CHANGE_SET_NAME`. If the command fails, the change set will not be \
+                     created. (Can be configured as `hooks.pre-change-set` in the configuration \
+                     file.)"
+    )]
+    pub pre_change_set_hook: Option<String>,
+    #[structopt(
+        long = "post-change-set-create-hook",
+        help = "Shell command to run after a change set was created",
+        long_help = "Shell command to run after the creation of a change set was started. The \
+                     context is provided through environment variables, i.e. `AWSX_HOOK_STAGE`, \
+                     `AWSX_STACK_NAME`, `AWSX_CHANGE_SET_NAME` and `AWSX_OUTPUT`, which contains \
+                     the structured output of the command so far. If the command fails, awsx fails \
+                     too. (Can be configured as `hooks.post-change-set-create` in the \
+                     configuration file.)"
+    )]
+    pub post_change_set_create_hook: Option<String>,
+    #[structopt(
+        long = "post-execute-hook",
+        help = "Shell command to run after a change set was executed",
+        long_help = "Shell command to run after a change set was executed, i.e. once the execution \
+                     has completed if waiting for it, for example to run smoke tests. The context \
+                     is provided through environment variables, i.e. `AWSX_HOOK_STAGE`, \
+                     `AWSX_STACK_NAME`, `AWSX_CHANGE_SET_NAME`, `AWSX_STACK_STATUS` and \
+                     `AWSX_OUTPUT`. If the command fails, awsx fails too. (Can be configured as \
+                     `hooks.post-execute` in the configuration file.)"
+    )]
+    pub post_execute_hook: Option<String>,
+    #[structopt(
+        long = "guard-rules",
+        number_of_values = 1,
+        help = "cfn-guard rule file to validate templates against",
+        long_help = "cfn-guard rule file to validate the template and parameters against before a \
+                     change set is created. If any rule fails, the change set will not be \
+                     created. The resolved parameter values are available to the rules as \
+                     `ResolvedParameters`. Can be specified multiple times. (Can be configured as \
+                     `guard-rules` in the configuration file.)"
+    )]
+    pub guard_rules: Vec<String>,
+    #[structopt(
+        long = "guard-cmd",
+        default_value = "cfn-guard",
+        help = "The cfn-guard executable used to validate templates"
+    )]
+    pub guard_cmd: String,
+    #[structopt(
+        long = "audit-log-path",
+        help = "Path to a file recording all changes awsx performs",
+        long_help = "Path to a file recording all changes awsx performs, i.e. creating, executing \
+                     and deleting change sets, and changing other resources like DB snapshots, \
+                     target groups or auto-scaling groups. Each change is appended as a line of \
+                     JSON, containing the command, the stack and change set or the resource, the \
+                     template checksum, the redacted parameters, the caller identity and the time. \
+                     If an entry cannot be written, the command fails. (Can be configured as `audit-log-path` in the \
+                     configuration file.)"
+    )]
+    pub audit_log_path: Option<String>,
+    #[structopt(
+        long = "notify-sns-topic-arn",
+        help = "SNS topic to publish an event to for all changes awsx performs",
+        long_help = "SNS topic to publish an event to for all changes awsx performs, i.e. \
+                     creating, executing and deleting change sets, and changing other resources. \
+                     The event is the same JSON object that is recorded in the audit log, \
+                     including the deployment metadata and whether the change succeeded. The \
+                     action, the stack name or resource and the outcome are attached as the \
+                     message attributes `action`, `stack_name` or `resource` and `succeeded`, \
+                     which subscriptions can filter by. Failing to publish an event \
+                     only prints a warning."
+    )]
+    pub notify_sns_topic_arn: Option<String>,
+    #[structopt(
+        long = "event-bus-name",
+        help = "EventBridge event bus to put deployment events on",
+        long_help = "Name or ARN of the EventBridge event bus to put an event on when a change set \
+                     is created, its execution starts and the deployment has finished. The events \
+                     have the source `awsx` and the detail type `awsx.deployment`, their detail \
+                     contains the stack, the change set, the stack status and the full deployment \
+                     metadata. Failing to put an event only prints a warning."
+    )]
+    pub event_bus_name: Option<String>,
+    #[structopt(
+        long = "require-version",
+        help = "Fail unless the awsx version satisfies the given requirement",
+        long_help = "Fail unless the version of awsx satisfies the given requirement, for example \
+                     `>=1.4`. This allows pipelines to ensure that all build agents run a \
+                     known-good version of awsx."
+    )]
+    pub require_version: Option<VersionReq>,
+    #[structopt(
+        long = "redact-parameter-pattern",
+        default_value = "(?i)password|secret|token|credential",
+        number_of_values = 1,
+        help = "Regex matching keys of parameters whose values are redacted",
+        long_help = "Regex matching keys of parameters whose values are redacted in all output, \
+                     including warnings and errors. Can be specified multiple times, which \
+                     replaces the default pattern. Parameters declared as `NoEcho` on a template \
+                     loaded by awsx are always redacted."
+    )]
+    pub redact_parameter_patterns: Vec<String>,
+    #[structopt(
+        long = "dont-redact-parameters",
+        help = "Do not redact sensitive parameter values in the output"
+    )]
+    pub dont_redact_parameters: bool,
+    /// Protected resources per stack, which can only be configured in the configuration file.
+    #[structopt(skip)]
+    pub protected_resources: HashMap<String, Vec<String>>,
+    /// Webhooks to notify about deployments, which can only be configured in the configuration
+    /// file.
+    #[structopt(skip)]
+    pub notifications: Vec<NotificationConfig>,
     #[structopt(subcommand)]
     command: Command,
 }
 
+impl Opt {
+    /// Return the region the AWS API calls are performed in, as resolved on startup.
+    ///
+    /// For subcommands that don't perform AWS API calls, the region is not resolved on startup, in
+    /// which case it is taken from the environment or the AWS config file, falling back to
+    /// us-east-1 without a warning.
+    pub(crate) fn region(&self) -> Region {
+        self.aws_region.clone().unwrap_or_default()
+    }
+
+    /// Return the source identity to assume roles with, which defaults to the git user.email.
+    pub(crate) fn source_identity(&self) -> Option<String> {
+        if self.dont_set_source_identity {
+            return None;
+        }
+        self.assume_role_source_identity
+            .clone()
+            .or_else(util::git_user_email)
+    }
+
+    /// Return the style of the S3 URLs uploaded templates and artifacts are referred to with.
+    pub(crate) fn s3_url_style(&self) -> UrlStyle {
+        if self.s3_path_style_urls {
+            UrlStyle::Path
+        } else {
+            UrlStyle::VirtualHosted
+        }
+    }
+
+    /// Return the logical IDs or resource types protected on the stack.
+    pub(crate) fn protected_resources(&self, stack_name: &str) -> Vec<String> {
+        ["*", stack_name]
+            .iter()
+            .filter_map(|key| self.protected_resources.get(*key))
+            .flatten()
+            .cloned()
+            .collect()
+    }
+
+    /// Use the values of the configuration file for all options not provided on the command-line.
+    fn apply_config(&mut self, config: Config) {
+        self.pre_change_set_hook = self
+            .pre_change_set_hook
+            .take()
+            .or(config.hooks.pre_change_set);
+        self.post_change_set_create_hook = self
+            .post_change_set_create_hook
+            .take()
+            .or(config.hooks.post_change_set_create);
+        self.post_execute_hook = self.post_execute_hook.take().or(config.hooks.post_execute);
+        if self.guard_rules.is_empty() {
+            self.guard_rules = config.guard_rules;
+        }
+        self.audit_log_path = self.audit_log_path.take().or(config.audit_log_path);
+        self.s3_bucket_name = self.s3_bucket_name.take().or(config.s3_bucket_name);
+        self.ca_bundle = self.ca_bundle.take().or(config.ca_bundle);
+        self.connect_timeout = self.connect_timeout.or(config.connect_timeout);
+        self.read_timeout = self.read_timeout.or(config.read_timeout);
+        self.connect_retries = self.connect_retries.or(config.connect_retries);
+        self.protected_resources = config.protected_resources;
+        self.notifications = config.notifications;
+    }
+}
+
 #[derive(Debug, StructOpt)]
 enum Command {
+    #[structopt(
+        name = "blue-green",
+        author,
+        about = "Run a blue/green deployment described by a plan file",
+        long_about = "Run a blue/green deployment described by a YAML plan file: provision the \
+                      green stack from a template, wait until its targets are healthy, shift the \
+                      traffic from the blue to the green stack through the weights of an ALB \
+                      listener (rule) or of Route53 record sets, and delete the blue stack. The \
+                      progress is recorded in a state file, so that an interrupted deployment can \
+                      be resumed by running the same command again.",
+        after_help = "IAM permissions required:\n\
+                      - cloudformation:DescribeStacks\n\
+                      - cloudformation:CreateChangeSet (with run or provision)\n\
+                      - cloudformation:DescribeChangeSet (with run or provision)\n\
+                      - cloudformation:ExecuteChangeSet (with run or provision)\n\
+                      - cloudformation:DeleteChangeSet (with run or provision)\n\
+                      - s3:PutObject (with run or provision and --s3-bucket-name)\n\
+                      - s3:PutObjectTagging (with --s3-object-tag)\n\
+                      - s3:GetObject (with templates uploaded before)\n\
+                      - elasticloadbalancing:DescribeTargetHealth (with run or wait-healthy)\n\
+                      - elasticloadbalancing:DescribeListeners (with run or shift-traffic)\n\
+                      - elasticloadbalancing:ModifyListener (with run or shift-traffic)\n\
+                      - elasticloadbalancing:DescribeRules (with run or shift-traffic)\n\
+                      - elasticloadbalancing:ModifyRule (with run or shift-traffic)\n\
+                      - route53:ListHostedZonesByName (with run or shift-traffic)\n\
+                      - route53:ListResourceRecordSets (with run or shift-traffic)\n\
+                      - route53:ChangeResourceRecordSets (with run or shift-traffic)\n\
+                      - route53:GetChange (with run or shift-traffic)\n\
+                      - cloudformation:DeleteStack (with run or decommission)\n\
+                      - sts:GetCallerIdentity (with --audit-log-path or --notify-sns-topic-arn)\n\
+                      - sns:Publish (with --notify-sns-topic-arn)"
+    )]
+    BlueGreen(blue_green::Opt),
+    #[structopt(
+        name = "clone-stack",
+        author,
+        about = "Create a new stack from the template and parameters of an existing stack",
+        long_about = "Create a new stack from the deployed template and parameters of an existing \
+                      stack, e.g. to stand up a review environment. Parameters can be changed \
+                      compared to the source stack. NOTE: this will only create a change set that \
+                      will not be automatically executed, unless requested.",
+        after_help = "IAM permissions required:\n\
+                      - cloudformation:DescribeStacks\n\
+                      - cloudformation:GetTemplate\n\
+                      - cloudformation:CreateChangeSet\n\
+                      - cloudformation:DescribeChangeSet (with --wait, --execute or --interactive)\n\
+                      - cloudformation:ExecuteChangeSet (with --execute)\n\
+                      - cloudformation:DescribeStackEvents (with --execute and --wait)\n\
+                      - cloudformation:DeleteChangeSet (with --interactive)\n\
+                      - s3:PutObject\n\
+                      - s3:PutObjectTagging (with --s3-object-tag)\n\
+                      - s3:GetObject (with templates uploaded before)\n\
+                      - cloudwatch:DescribeAlarms (with --canary)\n\
+                      - sts:GetCallerIdentity (with --audit-log-path or --notify-sns-topic-arn)\n\
+                      - sns:Publish (with --notify-sns-topic-arn)\n\
+                      - events:PutEvents (with --event-bus-name)"
+    )]
+    CloneStack(clone_stack::Opt),
+    #[structopt(
+        name = "compare-stacks",
+        author,
+        about = "Compare two deployed stacks",
+        long_about = "Compare the templates, parameters, tags and outputs of two deployed stacks, \
+                      which can reside in different accounts or regions. The command fails if the \
+                      stacks differ, which allows asserting the parity of environments. This \
+                      subcommand performs only read-only actions.",
+        after_help = "IAM permissions required:\n\
+                      - cloudformation:DescribeStacks (both stacks)\n\
+                      - cloudformation:GetTemplate (both stacks)\n\
+                      - sts:AssumeRole (with --other-assume-role-arn)"
+    )]
+    CompareStacks(compare_stacks::Opt),
+    #[structopt(
+        name = "copy-db-snapshot",
+        author,
+        about = "Copy a DB snapshot or DB cluster snapshot",
+        long_about = "Copy a DB snapshot or DB cluster snapshot, optionally from another region \
+                      given by --source-region or re-encrypted with another KMS key, and print the \
+                      ARN of the copy. Optionally waits until the copy is available.",
+        after_help = "IAM permissions required:\n\
+                      - rds:DescribeDBSnapshots (with --db-snapshot-identifier)\n\
+                      - rds:CopyDBSnapshot (with --db-snapshot-identifier)\n\
+                      - rds:DescribeDBClusterSnapshots (with --db-cluster-snapshot-identifier)\n\
+                      - rds:CopyDBClusterSnapshot (with --db-cluster-snapshot-identifier)\n\
+                      - rds:AddTagsToResource (with --tags)\n\
+                      - kms:CreateGrant (with --kms-key-id)\n\
+                      - kms:DescribeKey (with --kms-key-id)"
+    )]
+    CopyDBSnapshot(copy_db_snapshot::Opt),
+    #[structopt(
+        name = "create-cloudfront-invalidation",
+        author,
+        about = "Invalidate paths of a CloudFront distribution",
+        long_about = "Create an invalidation of the given paths on a CloudFront distribution, \
+                      selected by its ID or its tags, e.g. after deploying changed static assets, \
+                      and print the ID of the invalidation. Optionally waits until the \
+                      invalidation has completed.",
+        after_help = "IAM permissions required:\n\
+                      - cloudfront:CreateInvalidation\n\
+                      - cloudfront:GetInvalidation (with --wait)\n\
+                      - cloudfront:ListDistributions (with --tags)\n\
+                      - cloudfront:ListTagsForResource (with --tags)"
+    )]
+    CreateCloudFrontInvalidation(create_cloudfront_invalidation::Opt),
+    #[structopt(
+        name = "create-db-snapshot",
+        author,
+        about = "Create a snapshot of a DB instance or DB cluster",
+        long_about = "Create a snapshot of a DB instance or DB cluster, e.g. as safety step before \
+                      a deployment, and print the ARN of the new snapshot. Optionally waits until \
+                      the snapshot is available.",
+        after_help = "IAM permissions required:\n\
+                      - rds:CreateDBSnapshot (with --db-instance-identifier)\n\
+                      - rds:CreateDBClusterSnapshot (with --db-cluster-identifier)\n\
+                      - rds:AddTagsToResource (with --tags)\n\
+                      - rds:DescribeDBSnapshots (with --db-instance-identifier and --wait)\n\
+                      - rds:DescribeDBClusterSnapshots (with --db-cluster-identifier and --wait)"
+    )]
+    CreateDBSnapshot(create_db_snapshot::Opt),
     #[structopt(
         name = "create-stack",
         author,
@@ -122,9 +626,96 @@ enum Command {
         after_help = "IAM permissions required:\n\
                       - cloudformation:DescribeStacks\n\
                       - cloudformation:CreateChangeSet\n\
-                      - s3:PutObject"
+                      - cloudformation:DescribeChangeSet (with --wait, --execute or --interactive)\n\
+                      - cloudformation:ExecuteChangeSet (with --execute)\n\
+                      - cloudformation:DescribeStackEvents (with --execute and --wait)\n\
+                      - cloudformation:DeleteChangeSet (with --interactive)\n\
+                      - s3:PutObject\n\
+                      - s3:PutObjectTagging (with --s3-object-tag)\n\
+                      - s3:GetObject (with templates uploaded before)\n\
+                      - cloudwatch:DescribeAlarms (with --canary)\n\
+                      - cloudformation:GetTemplate (with --canary-rollback)\n\
+                      - sts:GetCallerIdentity (with --audit-log-path or --notify-sns-topic-arn)\n\
+                      - sns:Publish (with --notify-sns-topic-arn)\n\
+                      - events:PutEvents (with --event-bus-name)"
     )]
     CreateStack(create_stack::Opt),
+    #[structopt(
+        name = "delete-change-set",
+        author,
+        about = "Delete a change set",
+        after_help = "IAM permissions required:\n\
+                      - cloudformation:DeleteChangeSet\n\
+                      - sts:GetCallerIdentity (with --audit-log-path or --notify-sns-topic-arn)\n\
+                      - sns:Publish (with --notify-sns-topic-arn)"
+    )]
+    DeleteChangeSet(delete_change_set::Opt),
+    #[structopt(
+        name = "deployment-changelog",
+        author,
+        about = "Show the git changelog between the deployed commit and HEAD",
+        long_about = "Show the commits between the commit recorded in the deployment metadata of \
+                      the stack and the current HEAD of the template repository, including the \
+                      pull requests they were merged with.",
+        after_help = "IAM permissions required:\n\
+                      - cloudformation:DescribeStacks"
+    )]
+    DeploymentChangelog(deployment_changelog::Opt),
+    #[structopt(
+        name = "deployment-report",
+        author,
+        about = "Generate a deployment report for a change set",
+        long_about = "Generate a Markdown report for a change set, ready to paste into a change \
+                      ticket or pull request: the resource and parameter changes, who deploys \
+                      which commit, and the git changelog since the currently deployed commit.",
+        after_help = "IAM permissions required:\n\
+                      - cloudformation:DescribeChangeSet\n\
+                      - cloudformation:DescribeStacks"
+    )]
+    DeploymentReport(deployment_report::Opt),
+    #[structopt(
+        name = "deregister-targets",
+        author,
+        about = "Deregister targets from a target group",
+        long_about = "Deregister instances, IP addresses or Lambda functions from a target group, \
+                      selected by its ARN or its tags, e.g. during a blue/green cutover. \
+                      Optionally waits until the connections to the targets are drained.",
+        after_help = "IAM permissions required:\n\
+                      - elasticloadbalancing:DeregisterTargets\n\
+                      - elasticloadbalancing:DescribeTargetHealth (with --wait)\n\
+                      - elasticloadbalancing:DescribeTargetGroups (with --tags)\n\
+                      - elasticloadbalancing:DescribeTags (with --tags)"
+    )]
+    DeregisterTargets(deregister_targets::Opt),
+    #[structopt(
+        name = "describe-change-set",
+        author,
+        about = "Describe a change set and the resource changes it contains",
+        after_help = "IAM permissions required:\n\
+                      - cloudformation:DescribeChangeSet"
+    )]
+    DescribeChangeSet(describe_change_set::Opt),
+    #[structopt(
+        name = "execute-change-set",
+        author,
+        about = "Execute a change set, optionally waiting for completion",
+        after_help = "IAM permissions required:\n\
+                      - cloudformation:ExecuteChangeSet\n\
+                      - cloudformation:DescribeChangeSet (without --allow-replacement)\n\
+                      - cloudformation:DescribeChangeSet (with notifications or --event-bus-name)\n\
+                      - cloudformation:DescribeChangeSet (with --wait or --canary)\n\
+                      - cloudformation:DescribeStacks (with --wait or --canary)\n\
+                      - cloudformation:DescribeStackEvents (with --wait or --canary)\n\
+                      - cloudwatch:DescribeAlarms (with --canary)\n\
+                      - cloudformation:GetTemplate (with --canary-rollback)\n\
+                      - cloudformation:CreateChangeSet (with --canary-rollback)\n\
+                      - cloudformation:DescribeChangeSet (with --canary-rollback)\n\
+                      - cloudformation:DeleteChangeSet (with --canary-rollback)\n\
+                      - sts:GetCallerIdentity (with --audit-log-path or --notify-sns-topic-arn)\n\
+                      - sns:Publish (with --notify-sns-topic-arn)\n\
+                      - events:PutEvents (with --event-bus-name)"
+    )]
+    ExecuteChangeSet(execute_change_set::Opt),
     #[structopt(
         name = "find-amis-inuse",
         author,
@@ -143,6 +734,10 @@ enum Command {
         name = "find-auto-scaling-group",
         author,
         about = "Find an auto scaling group based on its tags",
+        long_about = "Find an auto scaling group based on its tags, and print its name. The \
+                      structured output additionally describes the capacity, the instances with \
+                      their health, and the launch template of the group. With --all, every \
+                      matching group is returned instead of only the first one.",
         after_help = "IAM permissions required:\n\
                       - autoscaling:DescribeAutoScalingGroups"
     )]
@@ -183,17 +778,86 @@ enum Command {
                       - elasticloadbalancing:DescribeTags"
     )]
     FindTargetGroup(find_target_group::Opt),
+    #[structopt(
+        name = "get-target-health",
+        author,
+        about = "Report the health of the targets of a target group",
+        long_about = "Report the health of the targets registered with a target group, selected by \
+                      its ARN or its tags, including the reasons for unhealthy targets. The \
+                      command fails if fewer targets than --min-healthy are healthy, and can wait \
+                      until enough targets are healthy with --wait-healthy, e.g. before shifting \
+                      traffic.",
+        after_help = "IAM permissions required:\n\
+                      - elasticloadbalancing:DescribeTargetHealth\n\
+                      - elasticloadbalancing:DescribeTargetGroups (with --tags)\n\
+                      - elasticloadbalancing:DescribeTags (with --tags)"
+    )]
+    GetTargetHealth(get_target_health::Opt),
+    #[structopt(
+        name = "iam-policy",
+        author,
+        about = "Generate an IAM policy for a subcommand",
+        long_about = "Generate an IAM policy document granting the permissions required by the given \
+                      subcommand, for example to provision the role of a CI pipeline. The \
+                      permissions are those listed in the help of the subcommand. This subcommand \
+                      does not perform any AWS actions.",
+        after_help = "IAM permissions required: none"
+    )]
+    IamPolicy(iam_policy::Opt),
     #[structopt(
         name = "identify-new-parameters",
         author,
         about = "Show new template parameters not present on the stack",
         long_about = "Show all new parameters defined on the template, but not present on the \
-                      stack. This subcommand does not create a change set, and performs only \
-                      read-only actions.",
+                      stack, together with their type, default value, description and whether \
+                      they are NoEcho. Parameters present on the stack, but removed from the \
+                      template, are reported separately. This subcommand does not create a change set, and \
+                      performs only read-only actions.",
         after_help = "IAM permissions required:\n\
                       - cloudformation:DescribeStacks"
     )]
     IdentifyNewParameters(identify_new_parameters::Opt),
+    #[structopt(
+        name = "lint-template",
+        author,
+        about = "Lint a template for unused and undeclared parameters",
+        long_about = "Lint a template for parameters that are declared, but never referenced \
+                      within the resources, outputs or conditions, as well as for references to \
+                      parameters, resources or conditions that are not declared. The exit code \
+                      is 1 if any finding is reported. This subcommand does not perform any AWS \
+                      actions.",
+        after_help = "IAM permissions required: none"
+    )]
+    LintTemplate(lint_template::Opt),
+    #[structopt(
+        name = "move-resource",
+        author,
+        about = "Move a resource from one stack to another",
+        long_about = "Move a resource from one stack to another without replacing it: set its \
+                      DeletionPolicy to Retain in the source stack, remove it from the source \
+                      stack and import it into the destination stack. Before updating either \
+                      stack, the command verifies that the resource is not referenced within the \
+                      source stack and that it can be imported into the destination stack, and \
+                      every change set is verified to only contain the expected change before it \
+                      is executed. Removing a protected resource, as configured with \
+                      `protected-resources` in the configuration file, is refused. Use --dry-run \
+                      to only perform these checks.",
+        after_help = "IAM permissions required:\n\
+                      - cloudformation:DescribeStacks\n\
+                      - cloudformation:ListStackResources\n\
+                      - cloudformation:GetTemplate\n\
+                      - cloudformation:GetTemplateSummary\n\
+                      - cloudformation:CreateChangeSet (without --dry-run)\n\
+                      - cloudformation:DescribeChangeSet (without --dry-run)\n\
+                      - cloudformation:ExecuteChangeSet (without --dry-run)\n\
+                      - cloudformation:DeleteChangeSet (without --dry-run)\n\
+                      - s3:PutObject (with --s3-bucket-name)\n\
+                      - s3:PutObjectTagging (with --s3-object-tag)\n\
+                      - s3:GetObject (with templates uploaded before)\n\
+                      - sts:GetCallerIdentity (with --audit-log-path or --notify-sns-topic-arn)\n\
+                      - sns:Publish (with --notify-sns-topic-arn)"
+    )]
+    MoveResource(move_resource::Opt),
     #[structopt(
         name = "override-parameters",
         author,
@@ -204,21 +868,253 @@ enum Command {
                       that will not be automatically executed.",
         after_help = "IAM permissions required:\n\
                       - cloudformation:DescribeStacks\n\
-                      - cloudformation:CreateChangeSet"
+                      - cloudformation:CreateChangeSet\n\
+                      - cloudformation:DescribeChangeSet (with --wait, --execute or --interactive)\n\
+                      - cloudformation:ExecuteChangeSet (with --execute)\n\
+                      - cloudformation:DescribeStackEvents (with --execute and --wait)\n\
+                      - cloudformation:DeleteChangeSet (with --interactive)\n\
+                      - cloudformation:GetTemplate (with --guard-rules)\n\
+                      - cloudwatch:DescribeAlarms (with --canary)\n\
+                      - cloudformation:GetTemplate (with --canary-rollback)\n\
+                      - sts:GetCallerIdentity (with --audit-log-path or --notify-sns-topic-arn)\n\
+                      - sns:Publish (with --notify-sns-topic-arn)\n\
+                      - events:PutEvents (with --event-bus-name)"
     )]
     OverrideParameters(override_parameters::Opt),
+    #[structopt(
+        name = "package",
+        author,
+        about = "Upload local artifacts referenced by a template to S3",
+        long_about = "Upload local artifacts referenced by a template to S3, and emit a template \
+                      referencing the uploaded artifacts instead, similar to `aws cloudformation \
+                      package`. This covers nested stacks, which are packaged recursively, Lambda \
+                      functions and layers, as well as the code and definitions of serverless \
+                      resources. Requires --s3-bucket-name.",
+        after_help = "IAM permissions required:\n\
+                      - s3:PutObject\n\
+                      - s3:PutObjectTagging (with --s3-object-tag)\n\
+                      - s3:GetObject (with templates uploaded before)"
+    )]
+    Package(package::Opt),
+    #[structopt(
+        name = "promote-stack",
+        author,
+        about = "Promote the template and chosen parameters of a stack to another stack",
+        long_about = "Promote the deployed template and chosen parameters of a source stack to a \
+                      destination stack, e.g. from staging to production. The source stack can \
+                      reside in another account or region. The deployment metadata of the source \
+                      stack is carried forward, recording the promotion. NOTE: this will only \
+                      create a change set that will not be automatically executed, unless \
+                      requested.",
+        after_help = "IAM permissions required:\n\
+                      - cloudformation:DescribeStacks (source and destination)\n\
+                      - cloudformation:GetTemplate (source)\n\
+                      - cloudformation:CreateChangeSet\n\
+                      - cloudformation:DescribeChangeSet (with --wait, --execute or --interactive)\n\
+                      - cloudformation:ExecuteChangeSet (with --execute)\n\
+                      - cloudformation:DescribeStackEvents (with --execute and --wait)\n\
+                      - cloudformation:DeleteChangeSet (with --interactive)\n\
+                      - s3:PutObject\n\
+                      - s3:PutObjectTagging (with --s3-object-tag)\n\
+                      - s3:GetObject (with templates uploaded before)\n\
+                      - sts:AssumeRole (with --source-assume-role-arn)\n\
+                      - cloudwatch:DescribeAlarms (with --canary)\n\
+                      - cloudformation:GetTemplate (with --canary-rollback)\n\
+                      - sts:GetCallerIdentity (with --audit-log-path or --notify-sns-topic-arn)\n\
+                      - sns:Publish (with --notify-sns-topic-arn)\n\
+                      - events:PutEvents (with --event-bus-name)"
+    )]
+    PromoteStack(promote_stack::Opt),
+    #[structopt(
+        name = "prune-templates",
+        author,
+        about = "Delete uploaded templates no stack references anymore",
+        long_about = "Delete the templates uploaded to the S3 bucket given by --s3-bucket-name \
+                      that neither a stack nor a pending change set in the regions given by \
+                      --regions references anymore, and that are older than the retention period. \
+                      The references are determined by the checksums of the deployed templates, \
+                      which name the uploaded objects. Stacks in other accounts sharing the bucket \
+                      are not taken into account. Only lists the templates, unless --delete is \
+                      given.",
+        after_help = "IAM permissions required:\n\
+                      - cloudformation:ListStacks\n\
+                      - cloudformation:GetTemplate\n\
+                      - cloudformation:ListChangeSets\n\
+                      - s3:ListBucket\n\
+                      - s3:DeleteObject (with --delete)"
+    )]
+    PruneTemplates(prune_templates::Opt),
+    #[structopt(
+        name = "register-targets",
+        author,
+        about = "Register targets with a target group",
+        long_about = "Register instances, IP addresses or Lambda functions with a target group, \
+                      selected by its ARN or its tags, e.g. during a blue/green cutover. \
+                      Optionally waits until the targets are healthy.",
+        after_help = "IAM permissions required:\n\
+                      - elasticloadbalancing:RegisterTargets\n\
+                      - elasticloadbalancing:DescribeTargetHealth (with --wait)\n\
+                      - elasticloadbalancing:DescribeTargetGroups (with --tags)\n\
+                      - elasticloadbalancing:DescribeTags (with --tags)"
+    )]
+    RegisterTargets(register_targets::Opt),
+    #[structopt(
+        name = "resume-auto-scaling-processes",
+        author,
+        about = "Resume suspended processes of an auto-scaling group",
+        long_about = "Resume suspended processes of an auto-scaling group, selected by its name or \
+                      its tags, e.g. after maintenance, and print the processes that are still \
+                      suspended.",
+        after_help = "IAM permissions required:\n\
+                      - autoscaling:DescribeAutoScalingGroups\n\
+                      - autoscaling:ResumeProcesses"
+    )]
+    ResumeAutoScalingProcesses(resume_auto_scaling_processes::Opt),
+    #[structopt(
+        name = "self-update",
+        author,
+        about = "Replace the awsx binary with a released version",
+        long_about = "Download the awsx binary of the latest or a given release for the current \
+                      platform, verify it against the SHA-256 checksum published alongside it, and \
+                      replace the running binary with it. The binary is only replaced if it runs on \
+                      this platform. Use --require-version to verify the installed version in \
+                      pipelines.",
+        after_help = "IAM permissions required: none"
+    )]
+    SelfUpdate(self_update::Opt),
+    #[structopt(
+        name = "set-auto-scaling-group-capacity",
+        author,
+        about = "Set the capacity of an auto-scaling group",
+        long_about = "Set the desired capacity, minimum and maximum size of an auto-scaling group, \
+                      selected by its name or its tags, e.g. to scale up before a heavy \
+                      deployment. Values that are not given are kept. Optionally waits until the \
+                      group runs the desired number of healthy instances.",
+        after_help = "IAM permissions required:\n\
+                      - autoscaling:DescribeAutoScalingGroups\n\
+                      - autoscaling:UpdateAutoScalingGroup"
+    )]
+    SetAutoScalingGroupCapacity(set_auto_scaling_group_capacity::Opt),
+    #[structopt(
+        name = "share-db-snapshot",
+        author,
+        about = "Share a DB snapshot or DB cluster snapshot with other accounts",
+        long_about = "Share a manual DB snapshot or DB cluster snapshot with other AWS accounts, \
+                      allowing them to copy and restore it, or revoke their access with --revoke. \
+                      Encrypted snapshots can only be shared if they are encrypted with a customer \
+                      managed KMS key, to which the target accounts need access as well.",
+        after_help = "IAM permissions required:\n\
+                      - rds:DescribeDBSnapshots (with --db-snapshot-identifier)\n\
+                      - rds:ModifyDBSnapshotAttribute (with --db-snapshot-identifier)\n\
+                      - rds:DescribeDBClusterSnapshots (with --db-cluster-snapshot-identifier)\n\
+                      - rds:ModifyDBClusterSnapshotAttribute (with --db-cluster-snapshot-identifier)"
+    )]
+    ShareDBSnapshot(share_db_snapshot::Opt),
+    #[structopt(
+        name = "shift-route53-weights",
+        author,
+        about = "Shift traffic between weighted Route 53 record sets",
+        long_about = "Shift traffic between weighted Route 53 record sets, e.g. for a blue/green \
+                      DNS cutover, by changing their weights to the given target weights. The \
+                      weights can be changed gradually in multiple steps, waiting in between. \
+                      Every step waits until the change was propagated to all DNS servers.",
+        after_help = "IAM permissions required:\n\
+                      - route53:ListResourceRecordSets\n\
+                      - route53:ChangeResourceRecordSets\n\
+                      - route53:GetChange\n\
+                      - route53:ListHostedZonesByName (with --hosted-zone-name)"
+    )]
+    ShiftRoute53Weights(shift_route53_weights::Opt),
+    #[structopt(
+        name = "show-stack-parameters",
+        author,
+        about = "Show the current parameters of a stack",
+        long_about = "Show the current parameters of a stack, including the values resolved by \
+                      CloudFormation, e.g. for parameters referencing SSM parameters. The values \
+                      of NoEcho parameters can not be retrieved and are marked as such. This \
+                      subcommand performs only read-only actions.",
+        after_help = "IAM permissions required:\n\
+                      - cloudformation:DescribeStacks"
+    )]
+    ShowStackParameters(show_stack_parameters::Opt),
+    #[structopt(
+        name = "start-instance-refresh",
+        author,
+        about = "Start an instance refresh of an auto-scaling group",
+        long_about = "Start an instance refresh of an auto-scaling group, selected by its name or \
+                      its tags, replacing its instances in batches, e.g. after updating its launch \
+                      template. Optionally waits until the instance refresh has completed, \
+                      reporting the progress of each batch.",
+        after_help = "IAM permissions required:\n\
+                      - autoscaling:DescribeAutoScalingGroups\n\
+                      - autoscaling:StartInstanceRefresh\n\
+                      - autoscaling:DescribeInstanceRefreshes (with --wait)"
+    )]
+    StartInstanceRefresh(start_instance_refresh::Opt),
+    #[structopt(
+        name = "suspend-auto-scaling-processes",
+        author,
+        about = "Suspend processes of an auto-scaling group",
+        long_about = "Suspend processes of an auto-scaling group, selected by its name or its \
+                      tags, e.g. `Launch`, `Terminate` and `AZRebalance` during maintenance, and \
+                      print all processes that are suspended afterwards.",
+        after_help = "IAM permissions required:\n\
+                      - autoscaling:DescribeAutoScalingGroups\n\
+                      - autoscaling:SuspendProcesses"
+    )]
+    SuspendAutoScalingProcesses(suspend_auto_scaling_processes::Opt),
+    #[cfg(feature = "tui")]
+    #[structopt(
+        name = "ui",
+        author,
+        about = "Show a terminal dashboard of stacks",
+        long_about = "Show a terminal dashboard listing all stacks with their status, as well as the \
+                      change sets and a live feed of the events of the selected stack. This \
+                      subcommand performs only read-only actions.",
+        after_help = "IAM permissions required:\n\
+                      - cloudformation:DescribeStackEvents\n\
+                      - cloudformation:ListChangeSets\n\
+                      - cloudformation:ListStacks"
+    )]
+    Ui(ui::Opt),
+    #[structopt(
+        name = "template-graph",
+        author,
+        about = "Show the dependencies between the resources of a template",
+        long_about = "Show the dependencies between the resources of a template, established \
+                      through Ref, Fn::GetAtt, Fn::Sub and DependsOn, e.g. to assess which \
+                      resources may be affected by a change. The human-readable output is a \
+                      graph in the DOT language of Graphviz, with edges pointing from a resource \
+                      to the resources it depends on, the structured output lists the resources \
+                      and their dependencies. This subcommand does not perform any AWS actions.",
+        after_help = "IAM permissions required: none"
+    )]
+    TemplateGraph(template_graph::Opt),
     #[structopt(
         name = "update-deployed-template",
         author,
         about = "Update an existing stack with a new template",
         long_about = "Update an existing stack with a new template, without updating any \
                       parameters already defined on the stack. You can and have to supply \
-                      parameters that are newly added. NOTE: this will only create a change set \
+                      parameters that are newly added. If neither the template nor the \
+                      parameters differ from what is deployed, no change set is created and the \
+                      command exits with exit code 3. NOTE: this will only create a change set \
                       that will not be automatically executed.",
         after_help = "IAM permissions required:\n\
                       - cloudformation:DescribeStacks\n\
+                      - cloudformation:GetTemplate\n\
                       - cloudformation:CreateChangeSet\n\
-                      - s3:PutObject"
+                      - cloudformation:DescribeChangeSet (with --wait, --execute or --interactive)\n\
+                      - cloudformation:ExecuteChangeSet (with --execute)\n\
+                      - cloudformation:DescribeStackEvents (with --execute and --wait)\n\
+                      - cloudformation:DeleteChangeSet (with --interactive)\n\
+                      - s3:PutObject\n\
+                      - s3:PutObjectTagging (with --s3-object-tag)\n\
+                      - s3:GetObject (with templates uploaded before)\n\
+                      - cloudwatch:DescribeAlarms (with --canary)\n\
+                      - sts:GetCallerIdentity (with --audit-log-path or --notify-sns-topic-arn)\n\
+                      - sns:Publish (with --notify-sns-topic-arn)\n\
+                      - events:PutEvents (with --event-bus-name)"
     )]
     UpdateDeployedTemplate(update_deployed_template::Opt),
     #[structopt(
@@ -237,21 +1133,96 @@ enum Command {
         long_about = "Verify that the parameters defined in your parameters file match a currently \
                       deployed stack. If your parameter-file has parameters defined as \
                       `UsePreviousValue`, they will be considered equal to whatever is defined on \
-                      the stack. This subcommand does not create a change set, and performs only \
-                      read-only actions.",
+                      the stack. With --change-set-name, the parameters of a pending change set \
+                      are verified instead. This subcommand does not create a change set, and \
+                      performs only read-only actions.",
         after_help = "IAM permissions required:\n\
-                      - cloudformation:DescribeStacks"
+                      - cloudformation:DescribeStacks\n\
+                      - cloudformation:DescribeChangeSet (with --change-set-name)\n\
+                      - cloudformation:GetTemplate (with --stack-name)"
     )]
     VerifyParameterFile(verify_parameter_file::Opt),
+    #[structopt(
+        name = "wait-for-cloudfront-distribution",
+        author,
+        about = "Wait until the changes to a CloudFront distribution are deployed",
+        long_about = "Wait until the changes to a CloudFront distribution, selected by its ID or \
+                      its tags, are deployed to all edge locations, e.g. after executing a change \
+                      set touching the distribution. The command fails if the distribution is not \
+                      deployed within the timeout.",
+        after_help = "IAM permissions required:\n\
+                      - cloudfront:GetDistribution\n\
+                      - cloudfront:ListDistributions (with --tags)\n\
+                      - cloudfront:ListTagsForResource (with --tags)"
+    )]
+    WaitForCloudFrontDistribution(wait_for_cloudfront_distribution::Opt),
+    #[structopt(
+        name = "wait-for-db-snapshot",
+        author,
+        about = "Wait until a DB snapshot or DB cluster snapshot is available",
+        long_about = "Wait until a DB snapshot or DB cluster snapshot is available, e.g. after it \
+                      was created or copied. The command fails if the snapshot ends up in any \
+                      other status, or if it does not become available within the timeout.",
+        after_help = "IAM permissions required:\n\
+                      - rds:DescribeDBSnapshots (with --db-snapshot-identifier)\n\
+                      - rds:DescribeDBClusterSnapshots (with --db-cluster-snapshot-identifier)"
+    )]
+    WaitForDBSnapshot(wait_for_db_snapshot::Opt),
+    #[structopt(
+        name = "who-changed-stack",
+        author,
+        about = "List who changed a stack recently",
+        long_about = "List the recent changes to a stack recorded by CloudTrail, i.e. stack \
+                      updates, change set executions and stack policy changes, with the identity \
+                      that performed them and when. This helps to track down the origin of drift \
+                      or of surprising parameter changes.",
+        after_help = "IAM permissions required:\n\
+                      - cloudtrail:LookupEvents"
+    )]
+    WhoChangedStack(who_changed_stack::Opt),
+    /// Any other subcommand is dispatched to an `awsx-<subcommand>` executable on the PATH.
+    #[structopt(external_subcommand)]
+    External(Vec<String>),
+}
+
+impl Command {
+    /// Check whether the subcommand performs AWS API calls, which requires resolving the region on
+    /// startup.
+    ///
+    /// Resolving the region can involve the instance metadata service of EC2, which delays every
+    /// invocation outside of EC2 by its timeout. Plugins are passed credentials, but resolve the
+    /// region themselves unless it was provided.
+    fn calls_aws(&self) -> bool {
+        !matches!(
+            self,
+            Command::IamPolicy(_)
+                | Command::LintTemplate(_)
+                | Command::SelfUpdate(_)
+                | Command::TemplateGraph(_)
+                | Command::External(_)
+        )
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum OutputFormat {
     HumanReadable,
     Json,
+    Ndjson,
     Yaml,
 }
 
+impl OutputFormat {
+    fn name(&self) -> &'static str {
+        match self {
+            OutputFormat::HumanReadable => "human-readable",
+            OutputFormat::Json => "json",
+            OutputFormat::Ndjson => "ndjson",
+            OutputFormat::Yaml => "yaml",
+        }
+    }
+}
+
 impl Default for OutputFormat {
     fn default() -> Self {
         if atty::is(atty::Stream::Stdout) {
@@ -269,6 +1240,7 @@ impl FromStr for OutputFormat {
         match s {
             "human" | "human-readable" => Ok(OutputFormat::HumanReadable),
             "json" | "structured" => Ok(OutputFormat::Json),
+            "ndjson" | "json-lines" => Ok(OutputFormat::Ndjson),
             "yml" | "yaml" => Ok(OutputFormat::Yaml),
             _ => Err(Error::UnknownOutputFormat(s.to_owned())),
         }
@@ -277,84 +1249,438 @@ impl FromStr for OutputFormat {
 
 #[tokio::main]
 async fn main() {
-    let opt = Opt::from_args();
+    let matches = Opt::clap().get_matches();
+    let mut opt = Opt::from_clap(&matches);
+    match Config::load(opt.config_path.as_deref()) {
+        Ok(config) => opt.apply_config(config),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+    if let Some(require_version) = &opt.require_version {
+        let version =
+            Version::parse(env!("CARGO_PKG_VERSION")).expect("awsx version is not valid semver");
+        if !require_version.matches(&version) {
+            eprintln!(
+                "awsx {} does not satisfy the required version {}",
+                version, require_version
+            );
+            std::process::exit(1);
+        }
+    }
+
+    if let Err(e) = redact::configure(&opt) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+    let output_format = opt.output_format.clone().unwrap_or_default();
+    if output_format == OutputFormat::Ndjson {
+        stream::enable();
+    }
+
+    let http_client_options = HttpClientOptions::default()
+        .with_ca_bundle(opt.ca_bundle.as_ref())
+        .with_connect_timeout(opt.connect_timeout.map(Duration::from_secs))
+        .with_read_timeout(opt.read_timeout.map(Duration::from_secs))
+        .with_connect_retries(opt.connect_retries);
+    if let Err(e) = http::configure(http_client_options) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+    let endpoint_options = EndpointOptions::default()
+        .with_fips(opt.use_fips_endpoints)
+        .with_dualstack(opt.use_dualstack_endpoints);
+    if let Err(e) = endpoint::configure(endpoint_options) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+
+    if opt.command.calls_aws() {
+        match region::resolve(opt.aws_region.take()).await {
+            Ok((region, RegionSource::Fallback)) => {
+                eprintln!(
+                    "WARNING: unable to determine the region, falling back to {}. Provide it with \
+                     --aws-region or AWS_REGION.",
+                    region.name()
+                );
+                opt.aws_region = Some(region);
+            }
+            Ok((region, _)) => opt.aws_region = Some(region),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
     let provider = AwsxProvider::new(
         opt.assume_role_arn.clone(),
-        opt.aws_region.clone().unwrap_or_default(),
+        opt.region(),
         opt.aws_access_key_id.clone(),
         opt.aws_secret_access_key.clone(),
     )
-    .expect("failed to create awsx credential provider");
+    .expect("failed to create awsx credential provider")
+    .with_source_identity(opt.source_identity())
+    .with_session_tags(opt.assume_role_tags.clone());
+    audit::configure(
+        &opt,
+        matches.subcommand_name().unwrap_or_default(),
+        &provider,
+    );
+    notification::configure(&opt, &provider);
 
     use Command::*;
     let output: Result<AwsxOutput, Error> = match opt.command {
-        CreateStack(ref command_opt) => {
-            create_stack::create_stack(command_opt, &opt, provider).await
+        BlueGreen(ref command_opt) => blue_green::blue_green(command_opt, &opt, provider).await,
+        CloneStack(ref command_opt) => clone_stack::clone_stack(command_opt, &opt, provider)
+            .await
+            .map(AwsxOutput::into_json),
+        CompareStacks(ref command_opt) => {
+            compare_stacks::compare_stacks(command_opt, &opt, provider)
+                .await
+                .map(AwsxOutput::into_json)
+        }
+        CopyDBSnapshot(ref command_opt) => {
+            copy_db_snapshot::copy_db_snapshot(command_opt, &opt, provider)
+                .await
+                .map(AwsxOutput::into_json)
+        }
+        CreateCloudFrontInvalidation(ref command_opt) => {
+            create_cloudfront_invalidation::create_cloudfront_invalidation(
+                command_opt,
+                &opt,
+                provider,
+            )
+            .await
+            .map(AwsxOutput::into_json)
+        }
+        CreateDBSnapshot(ref command_opt) => {
+            create_db_snapshot::create_db_snapshot(command_opt, &opt, provider)
+                .await
+                .map(AwsxOutput::into_json)
+        }
+        CreateStack(ref command_opt) => create_stack::create_stack(command_opt, &opt, provider)
+            .await
+            .map(AwsxOutput::into_json),
+        DeleteChangeSet(ref command_opt) => {
+            delete_change_set::delete_change_set(command_opt, &opt, provider)
+                .await
+                .map(AwsxOutput::into_json)
+        }
+        DeploymentChangelog(ref command_opt) => {
+            deployment_changelog::deployment_changelog(command_opt, &opt, provider)
+                .await
+                .map(AwsxOutput::into_json)
+        }
+        DeploymentReport(ref command_opt) => {
+            deployment_report::deployment_report(command_opt, &opt, provider)
+                .await
+                .map(AwsxOutput::into_json)
+        }
+        DeregisterTargets(ref command_opt) => {
+            deregister_targets::deregister_targets(command_opt, &opt, provider)
+                .await
+                .map(AwsxOutput::into_json)
+        }
+        DescribeChangeSet(ref command_opt) => {
+            describe_change_set::describe_change_set(command_opt, &opt, provider)
+                .await
+                .map(AwsxOutput::into_json)
+        }
+        ExecuteChangeSet(ref command_opt) => {
+            execute_change_set::execute_change_set(command_opt, &opt, provider)
+                .await
+                .map(AwsxOutput::into_json)
         }
         FindAmisInuse(ref command_opt) => {
-            find_amis_inuse::find_amis_inuse(command_opt, &opt, provider).await
+            find_amis_inuse::find_amis_inuse(command_opt, &opt, provider)
+                .await
+                .map(AwsxOutput::into_json)
         }
         FindAutoScalingGroup(ref command_opt) => {
-            find_auto_scaling_group::find_auto_scaling_group(command_opt, &opt, provider).await
+            find_auto_scaling_group::find_auto_scaling_group(command_opt, &opt, provider)
+                .await
+                .map(AwsxOutput::into_json)
         }
         FindCloudfrontDistribution(ref command_opt) => {
             find_cloudfront_distribution::find_cloudfront_distribution(command_opt, &opt, provider)
                 .await
+                .map(AwsxOutput::into_json)
         }
         FindDBClusterSnapshot(ref command_opt) => {
-            find_db_cluster_snapshot::find_db_cluster_snapshot(command_opt, &opt, provider).await
+            find_db_cluster_snapshot::find_db_cluster_snapshot(command_opt, &opt, provider)
+                .await
+                .map(AwsxOutput::into_json)
         }
         FindDBSnapshot(ref command_opt) => {
-            find_db_snapshot::find_db_snapshot(command_opt, &opt, provider).await
+            find_db_snapshot::find_db_snapshot(command_opt, &opt, provider)
+                .await
+                .map(AwsxOutput::into_json)
         }
         FindTargetGroup(ref command_opt) => {
-            find_target_group::find_target_group(command_opt, &opt, provider).await
+            find_target_group::find_target_group(command_opt, &opt, provider)
+                .await
+                .map(AwsxOutput::into_json)
         }
+        GetTargetHealth(ref command_opt) => {
+            get_target_health::get_target_health(command_opt, &opt, provider)
+                .await
+                .map(AwsxOutput::into_json)
+        }
+        IamPolicy(ref command_opt) => iam_policy::iam_policy(command_opt, &opt, provider).await,
         IdentifyNewParameters(ref command_opt) => {
-            identify_new_parameters::identify_new_parameters(command_opt, &opt, provider).await
+            identify_new_parameters::identify_new_parameters(command_opt, &opt, provider)
+                .await
+                .map(AwsxOutput::into_json)
         }
+        LintTemplate(ref command_opt) => lint_template::lint_template(command_opt, &opt, provider)
+            .await
+            .map(AwsxOutput::into_json),
+        MoveResource(ref command_opt) => move_resource::move_resource(command_opt, &opt, provider)
+            .await
+            .map(AwsxOutput::into_json),
         OverrideParameters(ref command_opt) => {
-            override_parameters::override_parameters(command_opt, &opt, provider).await
+            override_parameters::override_parameters(command_opt, &opt, provider)
+                .await
+                .map(AwsxOutput::into_json)
+        }
+        Package(ref command_opt) => package::package(command_opt, &opt, provider)
+            .await
+            .map(AwsxOutput::into_json),
+        PromoteStack(ref command_opt) => promote_stack::promote_stack(command_opt, &opt, provider)
+            .await
+            .map(AwsxOutput::into_json),
+        PruneTemplates(ref command_opt) => {
+            prune_templates::prune_templates(command_opt, &opt, provider)
+                .await
+                .map(AwsxOutput::into_json)
+        }
+        RegisterTargets(ref command_opt) => {
+            register_targets::register_targets(command_opt, &opt, provider)
+                .await
+                .map(AwsxOutput::into_json)
+        }
+        ResumeAutoScalingProcesses(ref command_opt) => {
+            resume_auto_scaling_processes::resume_auto_scaling_processes(
+                command_opt,
+                &opt,
+                provider,
+            )
+            .await
+            .map(AwsxOutput::into_json)
+        }
+        SelfUpdate(ref command_opt) => self_update::self_update(command_opt, &opt, provider)
+            .await
+            .map(AwsxOutput::into_json),
+        SetAutoScalingGroupCapacity(ref command_opt) => {
+            set_auto_scaling_group_capacity::set_auto_scaling_group_capacity(
+                command_opt,
+                &opt,
+                provider,
+            )
+            .await
+            .map(AwsxOutput::into_json)
+        }
+        ShareDBSnapshot(ref command_opt) => {
+            share_db_snapshot::share_db_snapshot(command_opt, &opt, provider)
+                .await
+                .map(AwsxOutput::into_json)
+        }
+        ShiftRoute53Weights(ref command_opt) => {
+            shift_route53_weights::shift_route53_weights(command_opt, &opt, provider)
+                .await
+                .map(AwsxOutput::into_json)
+        }
+        ShowStackParameters(ref command_opt) => {
+            show_stack_parameters::show_stack_parameters(command_opt, &opt, provider)
+                .await
+                .map(AwsxOutput::into_json)
+        }
+        StartInstanceRefresh(ref command_opt) => {
+            start_instance_refresh::start_instance_refresh(command_opt, &opt, provider)
+                .await
+                .map(AwsxOutput::into_json)
+        }
+        SuspendAutoScalingProcesses(ref command_opt) => {
+            suspend_auto_scaling_processes::suspend_auto_scaling_processes(
+                command_opt,
+                &opt,
+                provider,
+            )
+            .await
+            .map(AwsxOutput::into_json)
+        }
+        #[cfg(feature = "tui")]
+        Ui(ref command_opt) => ui::ui(command_opt, &opt, provider)
+            .await
+            .map(AwsxOutput::into_json),
+        TemplateGraph(ref command_opt) => {
+            template_graph::template_graph(command_opt, &opt, provider)
+                .await
+                .map(AwsxOutput::into_json)
         }
         UpdateDeployedTemplate(ref command_opt) => {
-            update_deployed_template::update_stack(command_opt, &opt, provider).await
+            update_deployed_template::update_stack(command_opt, &opt, provider)
+                .await
+                .map(AwsxOutput::into_json)
         }
         VerifyChangesCompatible(ref command_opt) => {
-            verify_changes_compatible::verify_changes_compatible(command_opt, &opt, provider).await
+            verify_changes_compatible::verify_changes_compatible(command_opt, &opt, provider)
+                .await
+                .map(AwsxOutput::into_json)
         }
         VerifyParameterFile(ref command_opt) => {
-            verify_parameter_file::verify_parameter_file(command_opt, &opt, provider).await
+            verify_parameter_file::verify_parameter_file(command_opt, &opt, provider)
+                .await
+                .map(AwsxOutput::into_json)
+        }
+        WaitForCloudFrontDistribution(ref command_opt) => {
+            wait_for_cloudfront_distribution::wait_for_cloudfront_distribution(
+                command_opt,
+                &opt,
+                provider,
+            )
+            .await
+            .map(AwsxOutput::into_json)
+        }
+        WaitForDBSnapshot(ref command_opt) => {
+            wait_for_db_snapshot::wait_for_db_snapshot(command_opt, &opt, provider)
+                .await
+                .map(AwsxOutput::into_json)
         }
+        WhoChangedStack(ref command_opt) => {
+            who_changed_stack::who_changed_stack(command_opt, &opt, provider)
+                .await
+                .map(AwsxOutput::into_json)
+        }
+        // Plugins write their output themselves, awsx only passes on their exit code.
+        External(ref args) => match plugin::run_plugin(args, &opt, provider).await {
+            Ok(exit_code) => std::process::exit(exit_code),
+            Err(e) => Err(e),
+        },
     };
-    match output {
+    match output.map(redact::redact_output) {
         Ok(output) => {
-            let output_string = match opt.output_format.unwrap_or_default() {
+            // Commands that streamed their items have already written all of their output.
+            if output.exit_code == ExitCode::Success && stream::has_started() {
+                return;
+            }
+            let output_string = match output_format {
                 OutputFormat::HumanReadable => output.human_readable,
-                OutputFormat::Json => serde_json::to_string(&output.structured).unwrap(),
+                OutputFormat::Json | OutputFormat::Ndjson => {
+                    serde_json::to_string(&output.structured).unwrap()
+                }
                 OutputFormat::Yaml => serde_yaml::to_string(&output.structured).unwrap(),
             };
-            if output.successful {
+            if output.exit_code == ExitCode::Success {
                 println!("{}", output_string);
             } else {
                 eprintln!("{}", output_string);
-                std::process::exit(1);
+                std::process::exit(output.exit_code as i32);
             }
         }
         Err(e) => {
-            eprintln!("{}", e);
-            std::process::exit(1);
+            let message = redact::redact_str(&e.to_string());
+            let structured = || {
+                redact::redact_json(json!({
+                    "success": false,
+                    "error": {
+                        "kind": e.kind(),
+                        "message": message,
+                        "parameter_keys": e.parameter_keys(),
+                        "aws_error_code": e.aws_error_code(),
+                        "aws_http_status": e.aws_error_details().map(|details| details.http_status),
+                        "aws_request_id": e
+                            .aws_error_details()
+                            .and_then(|details| details.request_id.as_deref()),
+                    },
+                }))
+            };
+            let error_string = match output_format {
+                OutputFormat::HumanReadable => message.clone(),
+                OutputFormat::Json | OutputFormat::Ndjson => {
+                    serde_json::to_string(&structured()).unwrap()
+                }
+                OutputFormat::Yaml => serde_yaml::to_string(&structured()).unwrap(),
+            };
+            eprintln!("{}", error_string);
+            std::process::exit(ExitCode::from(&e) as i32);
         }
     };
 }
 
+/// The output of a command, whose structured output is of type `T`.
+///
+/// Every command has a dedicated output type, which is converted to a JSON value through
+/// [`AwsxOutput::into_json`] when dispatching the command.
 #[derive(Debug)]
-pub(crate) struct AwsxOutput {
+pub(crate) struct AwsxOutput<T = serde_json::Value> {
     human_readable: String,
-    structured: serde_json::Value,
-    successful: bool,
+    structured: T,
+    exit_code: ExitCode,
+}
+
+impl<T: Serialize> AwsxOutput<T> {
+    /// Convert the structured output to JSON, so that the outputs of all commands can be handled
+    /// the same way.
+    pub(crate) fn into_json(self) -> AwsxOutput {
+        AwsxOutput {
+            human_readable: self.human_readable,
+            structured: serde_json::to_value(self.structured)
+                .expect("structured output should be representable as JSON"),
+            exit_code: self.exit_code,
+        }
+    }
+}
+
+impl<T> AwsxOutput<CommandOutput<T>> {
+    /// Map the result of the command, keeping the rest of the output.
+    pub(crate) fn map_result<U>(self, f: impl FnOnce(T) -> U) -> AwsxOutput<CommandOutput<U>> {
+        AwsxOutput {
+            human_readable: self.human_readable,
+            structured: CommandOutput {
+                success: self.structured.success,
+                message: self.structured.message,
+                result: f(self.structured.result),
+            },
+            exit_code: self.exit_code,
+        }
+    }
+}
+
+/// The exit code of awsx, which allows scripts to branch on the outcome of a command without
+/// parsing its output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ExitCode {
+    /// The command was successful.
+    Success = 0,
+    /// The command failed, e.g. due to an error returned by AWS.
+    Failure = 1,
+    /// A verification failed, e.g. the parameter file does not match the stack.
+    Mismatch = 2,
+    /// There was nothing to do, e.g. a change set would not contain any changes.
+    NoChanges = 3,
+    /// A verification found parameters missing from the parameter file.
+    MissingParameters = 4,
+    /// A verification found parameters only defined in the parameter file.
+    ExtraParameters = 5,
+}
+
+impl From<&Error> for ExitCode {
+    fn from(error: &Error) -> Self {
+        match error {
+            Error::ChangeSetCreationFailed(status_reason)
+                if is_no_changes_reason(status_reason) =>
+            {
+                ExitCode::NoChanges
+            }
+            _ => ExitCode::Failure,
+        }
+    }
 }
 
-impl Serialize for AwsxOutput {
+impl<T: Serialize> Serialize for AwsxOutput<T> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
@@ -0,0 +1,297 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+//! Opt-in notifications about deployments through webhooks, e.g. Slack incoming webhooks, and
+//! EventBridge.
+//!
+//! A notification is posted as JSON for the same lifecycle events the hook command is invoked for.
+//! The payload contains a `text` field with a human readable message, which is what chat services
+//! like Slack display, alongside the fields of the hook payload and a link to the stack. The same
+//! payload is the detail of the `awsx.deployment` event put on the event bus.
+
+use awsx::{
+    endpoint,
+    eventbridge::{Event, EventBridgeClient},
+    http,
+};
+use hyper::{Body, Client, Method, Request};
+use rusoto_core::Region;
+use serde::{Deserialize, Serialize};
+use std::{sync::Mutex, time::Duration};
+
+use crate::{
+    hook::{HookEvent, HookPayload},
+    redact, AwsxProvider, Opt as GlobalOpt,
+};
+
+/// Source of the events awsx puts on the event bus.
+const EVENT_SOURCE: &str = "awsx";
+
+/// Detail type of the events awsx puts on the event bus.
+const EVENT_DETAIL_TYPE: &str = "awsx.deployment";
+
+static EVENT_BUS: Mutex<Option<EventBus>> = Mutex::new(None);
+
+#[derive(Clone)]
+struct EventBus {
+    name: String,
+    provider: AwsxProvider,
+    region: Region,
+}
+
+/// How long to wait for a webhook to respond before giving up on the notification.
+const NOTIFICATION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A webhook to notify about deployments, as configured in the configuration file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub(crate) struct NotificationConfig {
+    /// URL to post notifications to, which can contain the placeholders `{{stack_name}}`,
+    /// `{{change_set_name}}`, `{{event}}`, `{{region}}` and `{{env.NAME}}` for the value of the
+    /// environment variable `NAME`, e.g. to keep the secret part of the URL out of the repository.
+    pub(crate) url: String,
+    /// Events to notify about, all events if empty.
+    #[serde(default)]
+    pub(crate) events: Vec<HookEvent>,
+}
+
+#[derive(Debug, Serialize)]
+struct Notification<'a> {
+    text: String,
+    link: String,
+    #[serde(flatten)]
+    payload: &'a HookPayload<'a>,
+}
+
+/// Expand the placeholders of the URL template of a webhook.
+fn expand_url(template: &str, payload: &HookPayload, region: &str) -> Result<String, String> {
+    let mut result = String::with_capacity(template.len());
+    let mut remaining = template;
+    while let Some(start) = remaining.find("{{") {
+        let end = remaining[start..]
+            .find("}}")
+            .map(|end| start + end)
+            .ok_or_else(|| "unterminated placeholder".to_owned())?;
+        result.push_str(&remaining[..start]);
+
+        let name = remaining[start + 2..end].trim();
+        let replacement = match name {
+            "stack_name" => payload.stack_name.to_owned(),
+            "change_set_name" => payload.change_set_name.to_owned(),
+            "event" => payload.event.name().to_owned(),
+            "region" => region.to_owned(),
+            _ => match name.strip_prefix("env.") {
+                Some(variable) => std::env::var(variable)
+                    .map_err(|_| format!("environment variable {} is not set", variable))?,
+                None => return Err(format!("unknown placeholder {}", name)),
+            },
+        };
+        result.push_str(&replacement);
+        remaining = &remaining[end + 2..];
+    }
+    result.push_str(remaining);
+    Ok(result)
+}
+
+/// The human readable message of a notification.
+fn message(payload: &HookPayload, region: &str) -> String {
+    let mut message = match (payload.event, payload.stack_status) {
+        (HookEvent::ChangeSetCreated, _) => format!(
+            "Change set {} was created for stack {} in {}",
+            payload.change_set_name, payload.stack_name, region
+        ),
+        (HookEvent::ExecutionStarted, _) => format!(
+            "Execution of change set {} on stack {} in {} started",
+            payload.change_set_name, payload.stack_name, region
+        ),
+        (HookEvent::DeploymentFinished, Some(stack_status))
+            if stack_status.ends_with("_COMPLETE") && !stack_status.contains("ROLLBACK") =>
+        {
+            format!(
+                "Deployment of change set {} on stack {} in {} succeeded ({})",
+                payload.change_set_name, payload.stack_name, region, stack_status
+            )
+        }
+        (HookEvent::DeploymentFinished, stack_status) => format!(
+            "Deployment of change set {} on stack {} in {} FAILED ({})",
+            payload.change_set_name,
+            payload.stack_name,
+            region,
+            stack_status.unwrap_or("unknown status")
+        ),
+    };
+    if let Some(deployment_metadata) = &payload.deployment_metadata {
+        message.push_str(&format!("\n{}", deployment_metadata.summary()));
+    }
+    message
+}
+
+/// Link to the stack in the CloudFormation console.
+fn console_link(stack_name: &str, region: &str) -> String {
+    format!(
+        "https://{region}.console.aws.amazon.com/cloudformation/home?region={region}\
+         #/stacks/stackinfo?stackId={}",
+        stack_name,
+        region = region
+    )
+}
+
+async fn post(url: &str, body: Vec<u8>) -> Result<(), String> {
+    let connector = http::https_connector().map_err(|e| e.to_string())?;
+    let client = Client::builder().build::<_, Body>(connector);
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri(url)
+        .header("content-type", "application/json")
+        .header("user-agent", concat!("awsx/", env!("CARGO_PKG_VERSION")))
+        .body(Body::from(body))
+        .map_err(|e| e.to_string())?;
+
+    let response = tokio::time::timeout(NOTIFICATION_TIMEOUT, client.request(request))
+        .await
+        .map_err(|_| "timed out".to_owned())?
+        .map_err(|e| e.to_string())?;
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body())
+            .await
+            .unwrap_or_default();
+        Err(format!("{} {}", status, String::from_utf8_lossy(&body)))
+    }
+}
+
+/// Enable putting events on the event bus, if one was configured.
+pub(crate) fn configure(global_opt: &GlobalOpt, provider: &AwsxProvider) {
+    if let Some(event_bus_name) = &global_opt.event_bus_name {
+        *EVENT_BUS.lock().expect("event bus lock poisoned") = Some(EventBus {
+            name: event_bus_name.to_owned(),
+            provider: provider.clone(),
+            region: global_opt.region(),
+        });
+    }
+}
+
+/// Check whether notifications are sent at all, i.e. whether any webhook or an event bus was
+/// configured.
+pub(crate) fn is_enabled(global_opt: &GlobalOpt) -> bool {
+    !global_opt.notifications.is_empty() || global_opt.event_bus_name.is_some()
+}
+
+async fn put_event(event_bus: EventBus, detail: String) -> Result<(), awsx::error::Error> {
+    let eventbridge = EventBridgeClient::new_with(
+        http::client()?,
+        event_bus.provider,
+        endpoint::region_for("events", event_bus.region),
+    );
+    eventbridge
+        .put_events(&[Event {
+            source: EVENT_SOURCE.to_owned(),
+            detail_type: EVENT_DETAIL_TYPE.to_owned(),
+            detail,
+            event_bus_name: Some(event_bus.name),
+        }])
+        .await?;
+    Ok(())
+}
+
+/// Post the notification to all webhooks configured for the event, and put it on the event bus.
+///
+/// The values of sensitive parameters are masked in the notification, like in all other output.
+///
+/// Like a failing hook command, a failing webhook or event bus does not fail the command itself,
+/// instead a warning is printed. The URL is not part of the warning, since it usually contains a
+/// secret.
+pub(crate) async fn notify(global_opt: &GlobalOpt, payload: &HookPayload<'_>) {
+    // Remember the position of every webhook in the configuration to identify it in warnings.
+    let notifications = global_opt
+        .notifications
+        .iter()
+        .enumerate()
+        .filter(|(_, notification)| {
+            notification.events.is_empty() || notification.events.contains(&payload.event)
+        })
+        .collect::<Vec<_>>();
+    let event_bus = EVENT_BUS.lock().expect("event bus lock poisoned").clone();
+    if notifications.is_empty() && event_bus.is_none() {
+        return;
+    }
+
+    let region = global_opt.region();
+    let notification = Notification {
+        text: message(payload, region.name()),
+        link: console_link(payload.stack_name, region.name()),
+        payload,
+    };
+    let body = match serde_json::to_value(&notification) {
+        Ok(body) => redact::redact_json(body).to_string().into_bytes(),
+        Err(e) => {
+            eprintln!("WARNING: failed to serialize the notification: {}", e);
+            return;
+        }
+    };
+
+    for (index, notification) in notifications {
+        let result = match expand_url(&notification.url, payload, region.name()) {
+            Ok(url) => post(&url, body.clone()).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            eprintln!(
+                "WARNING: failed to send notification #{} for event '{:?}': {}",
+                index + 1,
+                payload.event,
+                e,
+            );
+        }
+    }
+    if let Some(event_bus) = event_bus {
+        let name = event_bus.name.clone();
+        let detail = String::from_utf8(body).expect("serde_json did not return UTF-8");
+        if let Err(e) = put_event(event_bus, detail).await {
+            eprintln!(
+                "WARNING: failed to put the event for '{:?}' on the event bus '{}': {}",
+                payload.event, name, e,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expand_url_replaces_placeholders() {
+        std::env::set_var("AWSX_TEST_WEBHOOK_TOKEN", "secret");
+        let payload = HookPayload::new(HookEvent::ChangeSetCreated, "shop", "deploy-1");
+
+        assert_eq!(
+            expand_url(
+                "https://hooks.example.com/{{ env.AWSX_TEST_WEBHOOK_TOKEN }}?stack={{stack_name}}&event={{event}}",
+                &payload,
+                "eu-central-1",
+            ),
+            Ok("https://hooks.example.com/secret?stack=shop&event=change_set_created".to_owned()),
+        );
+        assert!(expand_url(
+            "https://hooks.example.com/{{unknown}}",
+            &payload,
+            "eu-central-1"
+        )
+        .is_err());
+    }
+}
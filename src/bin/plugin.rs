@@ -0,0 +1,96 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Dispatching of unknown subcommands to external plugins.
+//!
+//! Similar to git, `awsx foo` runs the executable `awsx-foo` found on the `PATH`, passing all
+//! remaining arguments. The global options are passed via environment variables, including the
+//! resolved temporary credentials, so that plugins don't have to handle role assumption
+//! themselves.
+
+use awsx::{error::Error, provider::AwsxProvider};
+use rusoto_credential::ProvideAwsCredentials;
+use std::{path::PathBuf, process::Command};
+
+use crate::Opt as GlobalOpt;
+
+/// Prefix of the executables that provide plugin subcommands.
+const PLUGIN_PREFIX: &str = "awsx-";
+
+/// Run the plugin providing the subcommand, returning its exit code.
+pub(crate) async fn run_plugin(
+    args: &[String],
+    global_opt: &GlobalOpt,
+    provider: AwsxProvider,
+) -> Result<i32, Error> {
+    let (subcommand, args) = args
+        .split_first()
+        .ok_or_else(|| Error::PluginNotFound(String::new()))?;
+    let executable = format!("{}{}", PLUGIN_PREFIX, subcommand);
+    // Look up the plugin before resolving credentials, since an unknown subcommand is more likely
+    // a typo than a request for a plugin.
+    let executable_path = find_executable(&executable).ok_or(Error::PluginNotFound(executable))?;
+
+    let credentials = provider
+        .credentials()
+        .await
+        .map_err(|error| Error::AwsError(error.into()))?;
+
+    let mut command = Command::new(executable_path);
+    // Unless the region was provided explicitly, the plugin resolves it from the environment it
+    // inherits, like awsx would.
+    if let Some(region) = &global_opt.aws_region {
+        command
+            .env("AWS_REGION", region.name())
+            .env("AWS_DEFAULT_REGION", region.name());
+    }
+    command
+        .args(args)
+        .env("AWS_ACCESS_KEY_ID", credentials.aws_access_key_id())
+        .env("AWS_SECRET_ACCESS_KEY", credentials.aws_secret_access_key())
+        .env("AWSX_VERSION", env!("CARGO_PKG_VERSION"));
+    // Credentials of an assumed role are only valid together with their session token, while a
+    // session token from the environment must not be combined with other credentials.
+    match credentials.token() {
+        Some(token) => command.env("AWS_SESSION_TOKEN", token),
+        None => command.env_remove("AWS_SESSION_TOKEN"),
+    };
+    if let Some(assume_role_arn) = &global_opt.assume_role_arn {
+        command.env("AWSX_ASSUME_ROLE_ARN", assume_role_arn);
+    }
+    if let Some(output_format) = &global_opt.output_format {
+        command.env("AWSX_OUTPUT_FORMAT", output_format.name());
+    }
+    if let Some(s3_bucket_name) = &global_opt.s3_bucket_name {
+        command.env("AWSX_S3_BUCKET_NAME", s3_bucket_name);
+    }
+
+    let status = command.status()?;
+    Ok(status.code().unwrap_or(1))
+}
+
+/// Find the executable in the directories of the `PATH`.
+fn find_executable(executable: &str) -> Option<PathBuf> {
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths)
+        .flat_map(|directory| {
+            let path = directory.join(executable);
+            // On Windows, executables are usually only found with their extension.
+            let path_with_extension = path.with_extension(std::env::consts::EXE_EXTENSION);
+            vec![path, path_with_extension]
+        })
+        .find(|path| path.is_file())
+}
@@ -0,0 +1,66 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Streaming of items for commands producing lists, when NDJSON output was requested.
+//!
+//! Commands enumerating many items emit every item through [`emit`] as soon as it is available.
+//! If the output format is NDJSON, each item is written to stdout as a single line of JSON right
+//! away, allowing downstream tools to process the items while awsx is still running. For all other
+//! output formats, emitting an item does nothing, and the items are only part of the final output.
+
+use serde::Serialize;
+use std::{
+    io::Write,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crate::redact;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static STARTED: AtomicBool = AtomicBool::new(false);
+
+/// Enable streaming of items, which should happen if the output format is NDJSON.
+pub(crate) fn enable() {
+    ENABLED.store(true, Ordering::SeqCst);
+}
+
+/// Mark the start of streaming items, returning whether the items are actually streamed.
+///
+/// Commands supporting streaming have to call this before emitting items. Once the items were
+/// streamed, the final output of the command is only written if the command was unsuccessful.
+pub(crate) fn start() -> bool {
+    let enabled = ENABLED.load(Ordering::SeqCst);
+    STARTED.store(enabled, Ordering::SeqCst);
+    enabled
+}
+
+/// Return whether the current command has streamed its items.
+pub(crate) fn has_started() -> bool {
+    STARTED.load(Ordering::SeqCst)
+}
+
+/// Write a single item as a line of JSON to stdout, if items are streamed.
+pub(crate) fn emit<T: Serialize>(item: &T) {
+    if !has_started() {
+        return;
+    }
+
+    let item = redact::redact_json(serde_json::to_value(item).expect("item is not serializable"));
+    let stdout = std::io::stdout();
+    let mut stdout = stdout.lock();
+    // There is nothing sensible to do if stdout is gone, e.g. because the consumer exited early.
+    let _ = writeln!(stdout, "{}", item).and_then(|_| stdout.flush());
+}
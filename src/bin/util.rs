@@ -15,45 +15,110 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use awsx::{
+    commands::cloudformation::deploy::MetadataOptions,
+    deployment::{generate_deployment_metadata, DeploymentMetadata},
+    endpoint,
     error::Error,
-    parameter::{Parameter, Parameters},
+    http, package,
+    parameter::{MergeStrategy, Parameter, Parameters},
+    s3::S3Uploader,
+    template::Template,
 };
-use chrono::{Local, SecondsFormat};
-use failure::format_err;
-use git2::{Config, Oid, Repository};
+use git2::{Config, Repository};
 use regex::RegexSet;
-use serde::{Deserialize, Serialize};
-use std::{convert::TryFrom, fmt, fs::File, io::BufReader, path::Path};
+use rusoto_core::Region;
+use rusoto_sts::{GetCallerIdentityRequest, Sts, StsClient};
+use std::{
+    collections::HashMap,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::{redact, AwsxProvider, Opt as GlobalOpt};
 
-#[derive(Debug, Clone, Default, Deserialize, Serialize)]
-pub(crate) struct DeploymentMetadata {
-    pub(crate) user: String,
-    pub(crate) when: String,
-    pub(crate) git: DeploymentMetadataGit,
+/// The template path that signals that the template should be read from stdin.
+pub(crate) const STDIN_TEMPLATE_PATH: &str = "-";
+
+/// Load the template from the given path, or from stdin if the path is `-`.
+///
+/// Parameters the template declares as `NoEcho` are registered for redaction.
+pub(crate) fn load_template(template_path: &str) -> Result<Template, Error> {
+    let template = if template_path == STDIN_TEMPLATE_PATH {
+        let mut contents = Vec::new();
+        io::stdin().read_to_end(&mut contents)?;
+        Template::from_bytes(contents)?
+    } else {
+        Template::new(template_path)?
+    };
+    redact::register_template(&template);
+    Ok(template)
 }
 
-impl fmt::Display for DeploymentMetadata {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", serde_json::to_string(self).unwrap_or_default())
+/// Return the path from which the git repository of the template should be discovered.
+///
+/// If the template is read from stdin, the repository is discovered from the current working
+/// directory instead.
+pub(crate) fn template_git_path(template_path: &str) -> &str {
+    if template_path == STDIN_TEMPLATE_PATH {
+        "."
+    } else {
+        template_path
     }
 }
 
-#[derive(Debug, Clone, Default, Deserialize, Serialize)]
-pub(crate) struct DeploymentMetadataGit {
-    pub(crate) commit: String,
-    pub(crate) r#ref: String,
-    pub(crate) dirty: bool,
+/// Return the directory relative to which local artifacts referenced by the template are resolved.
+///
+/// If the template is read from stdin, the current working directory is used.
+pub(crate) fn template_base_dir(template_path: &str) -> PathBuf {
+    if template_path == STDIN_TEMPLATE_PATH {
+        PathBuf::from(".")
+    } else {
+        Path::new(template_path)
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| PathBuf::from("."))
+    }
 }
 
-impl TryFrom<Parameter> for DeploymentMetadata {
-    type Error = Error;
+/// Package the template, uploading all local artifacts it references to the given S3 bucket.
+pub(crate) async fn package_template(
+    template: &Template,
+    template_path: &str,
+    s3: &S3Uploader,
+    s3_bucket_name: Option<&str>,
+) -> Result<Template, Error> {
+    let s3_bucket_name =
+        s3_bucket_name.ok_or_else(|| Error::S3BucketRequired("package the template".to_owned()))?;
+    package::package_template(
+        template,
+        &template_base_dir(template_path),
+        s3,
+        s3_bucket_name,
+    )
+    .await
+}
 
-    fn try_from(parameter: Parameter) -> Result<Self, Self::Error> {
-        match parameter {
-            Parameter::WithValue { value, .. } => serde_json::from_str(&value).map_err(Into::into),
-            Parameter::PreviousValue { key } => Err(Error::InvalidParameters(key)),
-        }
-    }
+/// Create a credential provider for accessing resources in another region or account.
+///
+/// Unspecified values fall back to the global options, so that the provider accesses the same
+/// account and region as the provider created from the global options.
+pub(crate) fn provider_for(
+    global_opt: &GlobalOpt,
+    region: Option<Region>,
+    assume_role_arn: Option<String>,
+) -> Result<(AwsxProvider, Region), Error> {
+    let region = region.unwrap_or_else(|| global_opt.region());
+    let provider = AwsxProvider::new(
+        assume_role_arn.or_else(|| global_opt.assume_role_arn.clone()),
+        region.clone(),
+        global_opt.aws_access_key_id.clone(),
+        global_opt.aws_secret_access_key.clone(),
+    )
+    .map_err(|error| Error::AwsError(error.into()))?
+    .with_source_identity(global_opt.source_identity())
+    .with_session_tags(global_opt.assume_role_tags.clone());
+    Ok((provider, region))
 }
 
 pub(crate) fn apply_excludes_includes(
@@ -81,111 +146,234 @@ pub(crate) fn apply_excludes_includes(
     Ok(parameters)
 }
 
-pub(crate) fn apply_defaults(
+/// How conflicting values of multiple parameter sources, e.g. a parameter file and `--parameters`,
+/// are resolved.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum ConflictStrategy {
+    /// Resolve conflicts without interaction.
+    Merge(MergeStrategy<'static>),
+    /// Prompt on stderr which of the values to keep.
+    Interactive,
+}
+
+impl FromStr for ConflictStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "keep-left" => Ok(ConflictStrategy::Merge(MergeStrategy::KeepLeft)),
+            "keep-right" => Ok(ConflictStrategy::Merge(MergeStrategy::KeepRight)),
+            "error" => Ok(ConflictStrategy::Merge(MergeStrategy::Error)),
+            "interactive" => Ok(ConflictStrategy::Interactive),
+            _ => Err(format!(
+                "`{}` is not a valid strategy, expected keep-left, keep-right, error or interactive",
+                s
+            )),
+        }
+    }
+}
+
+impl ConflictStrategy {
+    /// Fail early if the strategy prompts, but stdin is no terminal or is already consumed as an
+    /// input, e.g. for `--template-path -`.
+    pub(crate) fn ensure_usable(self, stdin_in_use: bool) -> Result<(), Error> {
+        if let ConflictStrategy::Interactive = self {
+            if stdin_in_use {
+                return Err(Error::InvalidParameters(
+                    "interactive conflict resolution is unavailable while stdin is used as input"
+                        .to_owned(),
+                ));
+            }
+            if !atty::is(atty::Stream::Stdin) {
+                return Err(Error::InvalidParameters(
+                    "interactive conflict resolution requires stdin to be a terminal".to_owned(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Add or update the parameters of the other source to the parameters, resolving conflicting
+    /// values with the strategy. The sources are named when prompting interactively.
+    pub(crate) fn merge(
+        self,
+        parameters: &mut Parameters,
+        other: Parameters,
+        (left_source, right_source): (&str, &str),
+    ) -> Result<(), Error> {
+        match self {
+            ConflictStrategy::Merge(strategy) => parameters.merge_with(other, strategy),
+            ConflictStrategy::Interactive => {
+                let prompt = |left: &Parameter, right: &Parameter| -> Result<Parameter, Error> {
+                    let value = |parameter: &Parameter| match parameter {
+                        Parameter::WithValue { value, .. } => redact::redact_str(value),
+                        _ => String::new(),
+                    };
+                    loop {
+                        eprint!(
+                            "Parameter {} is `{}` in {}, but `{}` in {}. Keep which? [l/r]: ",
+                            left.key(),
+                            value(left),
+                            left_source,
+                            value(right),
+                            right_source
+                        );
+                        io::stderr().flush()?;
+                        let mut line = String::new();
+                        if io::stdin().read_line(&mut line)? == 0 {
+                            return Err(Error::InvalidParameters(format!(
+                                "no value was chosen for the conflicting parameter {}",
+                                left.key()
+                            )));
+                        }
+                        match line.trim().to_lowercase().as_str() {
+                            "l" | "left" => return Ok(left.clone()),
+                            "r" | "right" => return Ok(right.clone()),
+                            _ => continue,
+                        }
+                    }
+                };
+                parameters.merge_with(other, MergeStrategy::Custom(&prompt))
+            }
+        }
+    }
+}
+
+pub(crate) async fn apply_defaults(
     mut parameters: Parameters,
     parameter_defaults_path: &Option<String>,
+    context: &PlaceholderContext<'_>,
+) -> Result<Parameters, Error> {
+    if let Some(default_parameters) =
+        load_parameter_defaults(parameter_defaults_path, context).await?
+    {
+        parameters.apply_defaults(default_parameters);
+    }
+
+    Ok(parameters)
+}
+
+/// Load the parameter file with defaults, if one was given and it exists.
+pub(crate) async fn load_parameter_defaults(
+    parameter_defaults_path: &Option<String>,
+    context: &PlaceholderContext<'_>,
+) -> Result<Option<Parameters>, Error> {
+    match parameter_defaults_path {
+        Some(parameter_defaults_path) if Path::new(parameter_defaults_path).exists() => Ok(Some(
+            load_parameter_file(parameter_defaults_path, context).await?,
+        )),
+        _ => Ok(None),
+    }
+}
+
+/// Context in which placeholders in parameter files are expanded.
+pub(crate) struct PlaceholderContext<'a> {
+    pub(crate) stack_name: Option<&'a str>,
+    pub(crate) global_opt: &'a GlobalOpt,
+    pub(crate) provider: &'a AwsxProvider,
+}
+
+/// Load a parameter file in JSON or YAML format, expanding placeholders in its values.
+///
+/// Besides references to other parameters of the file (`{{parameters.Key}}`), the variables
+/// `{{stack_name}}` (if the command targets a stack), `{{region}}` and `{{account_id}}` are
+/// available. The account ID is only retrieved if the file references it. Other placeholders, e.g.
+/// dynamic references, are passed on to CloudFormation as they are.
+pub(crate) async fn load_parameter_file(
+    parameter_path: &str,
+    context: &PlaceholderContext<'_>,
 ) -> Result<Parameters, Error> {
-    if let Some(parameter_defaults_path) = parameter_defaults_path {
-        if Path::new(parameter_defaults_path).exists() {
-            let defaults_file = File::open(parameter_defaults_path)?;
-            let reader = BufReader::new(defaults_file);
-            let default_parameters: Parameters = serde_json::from_reader(reader).unwrap();
-            parameters.apply_defaults(default_parameters);
+    let mut parameters = Parameters::from_file(parameter_path)?;
+
+    let region = context.global_opt.region();
+    let mut variables = HashMap::new();
+    variables.insert("region".to_owned(), region.name().to_owned());
+    if let Some(stack_name) = context.stack_name {
+        variables.insert("stack_name".to_owned(), stack_name.to_owned());
+    }
+    if parameters.references_variable("account_id") {
+        let sts = StsClient::new_with(
+            http::client()?,
+            context.provider.clone(),
+            endpoint::region_for("sts", region),
+        );
+        if let Some(account_id) = sts
+            .get_caller_identity(GetCallerIdentityRequest {})
+            .await?
+            .account
+        {
+            variables.insert("account_id".to_owned(), account_id);
         }
-    };
+    }
 
+    parameters.expand_placeholders(&variables)?;
     Ok(parameters)
 }
 
-pub(crate) fn generate_deployment_metadata(
-    previous_metadata_parameter: Option<Parameter>,
+/// Return the git user.email of the repository in the current directory, falling back to the
+/// global git configuration.
+pub(crate) fn git_user_email() -> Option<String> {
+    Repository::discover(".")
+        .and_then(|repo| repo.config())
+        .or_else(|_| Config::open_default())
+        .and_then(|config| config.get_string("user.email"))
+        .ok()
+}
+
+/// Create the uploader for templates and artifacts, configured through the global options.
+///
+/// Uploaded objects record who uploaded them and, if the given path is within a git repository,
+/// from which commit, as their user metadata.
+pub(crate) fn s3_uploader(
+    global_opt: &GlobalOpt,
+    provider: AwsxProvider,
     git_discover_path: Option<&str>,
-) -> Result<DeploymentMetadata, Error> {
-    let mut metadata = previous_metadata_parameter
-        .and_then(|parameter| DeploymentMetadata::try_from(parameter).ok())
-        .unwrap_or_default();
-
-    metadata.user = Config::open_default()?
-        .get_string("user.email")
-        .unwrap_or_else(|_| "unknown".to_owned());
-    metadata.when = Local::now().to_rfc3339_opts(SecondsFormat::Secs, true);
-
-    if let Some(git_discover_path) = git_discover_path {
-        let repo = Repository::discover(git_discover_path)?;
-        let head = repo.head()?;
-        let r#ref = head
-            .shorthand()
-            .ok_or_else(|| Error::GitError(format_err!("Failed to retrieve ref for git HEAD")))?
-            .to_owned();
-        let commit = format!(
-            "{}",
-            head.target().ok_or_else(|| Error::GitError(format_err!(
-                "Failed to retrieve commit for git HEAD"
-            )))?
-        );
-        let statuses = repo.statuses(Some(git2::StatusOptions::new().include_untracked(false)))?;
-        let dirty = !statuses.is_empty();
-
-        metadata.user = repo
-            .config()?
-            .get_string("user.email")
-            .unwrap_or_else(|_| "unknown".to_owned());
-        metadata.git = DeploymentMetadataGit {
-            commit,
-            r#ref,
-            dirty,
+) -> S3Uploader {
+    let mut object_metadata = HashMap::new();
+    // The provenance is informational only, which is why failing to determine it is not an error.
+    if let Ok(metadata) = generate_deployment_metadata(None, git_discover_path) {
+        object_metadata.insert("uploaded-by".to_owned(), metadata.user);
+        if !metadata.git.commit.is_empty() {
+            object_metadata.insert("git-commit".to_owned(), metadata.git.commit);
+            object_metadata.insert("git-ref".to_owned(), metadata.git.r#ref);
         }
     }
 
-    Ok(metadata)
-}
-
-pub(crate) fn verify_changes_compatible(
-    previous_metadata: &DeploymentMetadata,
-    current_metadata: &DeploymentMetadata,
-    git_discover_path: &str,
-) -> Result<bool, Error> {
-    // Find the common ancestor
-    let previous_commit_is_common_ancestor =
-        if previous_metadata.git.commit == current_metadata.git.commit {
-            true
-        } else {
-            // Retrieve previous and current commit
-            let repo = Repository::discover(git_discover_path)?;
-            let previous_commit = Oid::from_str(&previous_metadata.git.commit)?;
-            let current_commit = repo.head()?.target().ok_or_else(|| {
-                Error::GitError(format_err!("Failed to retrieve commit for git HEAD"))
-            })?;
-
-            match repo.merge_base(previous_commit, current_commit) {
-                Ok(common_ancestor) => previous_commit == common_ancestor,
-                Err(ref e)
-                    if e.code() == git2::ErrorCode::GenericError
-                        && e.class() == git2::ErrorClass::Odb =>
-                {
-                    // If either of the commits we are comparing is unknown to the repository, the
-                    // error returned will be of code `GenericError` and class `Odb` (bad object).
-                    // Rather than showing that error, which can commonly occur if either the
-                    // deployed changes are based on a commit another developer only has locally, or
-                    // if the user has rebased their own changes since the last time they deployed,
-                    // we simply return `false` here indicating that the changes are not compatible.
-                    false
-                }
-                Err(e) => return Err(e.into()),
-            }
-        };
-
-    // In general it is true that if the previous changes were dirty, we cannot guarantee any
-    // compatibility. We make one exception: if the user stays unchanged, and the previous commit is
-    // the common ancestor, we assume that the change is just the person developing and testing.
-    if previous_metadata.git.dirty {
-        return Ok(
-            previous_metadata.user == current_metadata.user && previous_commit_is_common_ancestor
-        );
+    S3Uploader::new(global_opt.region(), provider)
+        .with_checksum_algorithm(global_opt.checksum_algorithm)
+        .with_url_style(global_opt.s3_url_style())
+        .with_object_tags(global_opt.s3_object_tags.clone())
+        .with_object_metadata(object_metadata)
+}
+
+/// Return the deployment metadata contained in the parameters, if any.
+pub(crate) fn deployment_metadata(
+    global_opt: &GlobalOpt,
+    parameters: &Parameters,
+) -> Option<DeploymentMetadata> {
+    DeploymentMetadata::from_parameters(parameters, &global_opt.deployment_metadata_parameter)
+}
+
+/// The options for recording the deployment metadata, as configured through the global options.
+///
+/// The commit deployed is only recorded if the path within a git repository is given.
+pub(crate) fn metadata_options<'a>(
+    global_opt: &'a GlobalOpt,
+    git_path: Option<&'a str>,
+) -> MetadataOptions<'a> {
+    MetadataOptions {
+        parameter_key: &global_opt.deployment_metadata_parameter,
+        record: !global_opt.dont_update_deployment_metadata,
+        git_path,
+        is_sensitive: &redact::matches_pattern,
     }
+}
 
-    // If the previous changes were not dirty, we can now verify if the current commit is a direct
-    // descendant from the previous commit. If it isn't, the two commits are out of two separate
-    // trees and we thus cannot assume them to be compatible.
-    Ok(previous_commit_is_common_ancestor)
+/// Return the default description of a change set applying the parameters, which is the summary of
+/// the deployment metadata they contain, if any.
+pub(crate) fn change_set_description(
+    global_opt: &GlobalOpt,
+    parameters: &Parameters,
+) -> Option<String> {
+    deployment_metadata(global_opt, parameters).map(|metadata| metadata.summary())
 }
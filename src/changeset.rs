@@ -0,0 +1,485 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module enables handling of CloudFormation change sets.
+
+use futures::TryStreamExt;
+use indexmap::IndexMap;
+use rusoto_cloudformation::{
+    CloudFormation, DeleteChangeSetInput, DescribeChangeSetError, DescribeChangeSetInput,
+    ExecuteChangeSetInput, GetTemplateInput, StackEvent,
+};
+use rusoto_core::RusotoError;
+use serde::Serialize;
+use std::time::Duration;
+
+use crate::{
+    error::Error,
+    parameter::{Parameter, Parameters},
+    stack::{Stack, StackEventsSince},
+    template::Template,
+};
+
+/// Interval in which the status of a change set or stack is polled while waiting.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Represents a CloudFormation change set of a specific stack.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangeSet {
+    stack_name: String,
+    name: String,
+}
+
+/// The description of a change set, including all resource changes it contains.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChangeSetDescription {
+    /// Name of the stack the change set belongs to.
+    pub stack_name: String,
+    /// Name of the change set.
+    pub change_set_name: String,
+    /// ARN of the change set.
+    pub change_set_id: Option<String>,
+    /// Description of the change set, if one was provided on creation.
+    pub description: Option<String>,
+    /// Creation status of the change set, e.g. `CREATE_COMPLETE`.
+    pub status: String,
+    /// Reason for the current status, e.g. why the creation failed.
+    pub status_reason: Option<String>,
+    /// Execution status of the change set, e.g. `AVAILABLE`.
+    pub execution_status: Option<String>,
+    /// Parameters the change set will apply to the stack.
+    pub parameters: Parameters,
+    /// Values CloudFormation resolved the parameters of the change set to, keyed by the parameter
+    /// key, e.g. for parameters of the type `AWS::SSM::Parameter::Value<String>`.
+    pub resolved_values: IndexMap<String, String>,
+    /// Resource changes the change set will perform.
+    pub changes: Vec<ResourceChange>,
+}
+
+/// A single resource change contained in a change set.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ResourceChange {
+    /// The action performed on the resource, e.g. `Add`, `Modify` or `Remove`.
+    pub action: String,
+    /// Logical ID of the resource, as defined in the template.
+    pub logical_id: String,
+    /// Physical ID of the resource, if it already exists.
+    pub physical_id: Option<String>,
+    /// Type of the resource, e.g. `AWS::EC2::Instance`.
+    pub resource_type: String,
+    /// Whether the resource will be replaced: `True`, `False` or `Conditional`.
+    pub replacement: Option<String>,
+    /// Which parts of the resource are affected, e.g. `Properties` or `Tags`.
+    pub scope: Vec<String>,
+}
+
+impl ChangeSetDescription {
+    /// Return the resource changes that remove or replace resources matched by any of the
+    /// protected patterns, which are either logical IDs or resource types.
+    pub fn protected_changes(&self, protected: &[String]) -> Vec<&ResourceChange> {
+        self.changes
+            .iter()
+            .filter(|change| {
+                change.is_destructive() && protected.iter().any(|pattern| change.matches(pattern))
+            })
+            .collect()
+    }
+
+    /// Return the resource changes that replace the resource, either certainly or depending on
+    /// the values of other resources (`Conditional`).
+    pub fn replacements(&self) -> Vec<&ResourceChange> {
+        self.changes
+            .iter()
+            .filter(|change| change.is_replacement())
+            .collect()
+    }
+}
+
+impl ResourceChange {
+    /// Check if the change replaces the resource, either certainly or depending on the values of
+    /// other resources (`Conditional`).
+    pub fn is_replacement(&self) -> bool {
+        matches!(
+            self.replacement.as_deref(),
+            Some("True") | Some("Conditional")
+        )
+    }
+
+    /// Check if the change removes or replaces the resource.
+    pub fn is_destructive(&self) -> bool {
+        self.action == "Remove" || self.is_replacement()
+    }
+
+    /// Check if the resource is matched by the pattern, which is either a logical ID or a resource
+    /// type, e.g. `AWS::RDS::DBInstance`.
+    pub fn matches(&self, pattern: &str) -> bool {
+        self.logical_id == pattern || self.resource_type == pattern
+    }
+}
+
+/// Return whether the status reason of a failed change set states that it did not contain any
+/// changes, in contrast to a change set that failed because of an actual error.
+pub fn is_no_changes_reason(status_reason: &str) -> bool {
+    status_reason.contains("didn't contain changes")
+        || status_reason.contains("No updates are to be performed")
+}
+
+impl ChangeSet {
+    /// Create a new representation of a change set of the given stack.
+    pub fn new<S: AsRef<str>, N: AsRef<str>>(stack_name: S, name: N) -> ChangeSet {
+        ChangeSet {
+            stack_name: stack_name.as_ref().to_owned(),
+            name: name.as_ref().to_owned(),
+        }
+    }
+
+    /// Return the name of the stack the change set belongs to.
+    pub fn stack_name(&self) -> &str {
+        &self.stack_name
+    }
+
+    /// Return the name of the change set.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Describe the change set, retrieving all resource changes.
+    pub async fn describe(&self, cfn: &dyn CloudFormation) -> Result<ChangeSetDescription, Error> {
+        let mut description: Option<ChangeSetDescription> = None;
+        let mut continuation_token: Option<String> = None;
+        while {
+            let output = cfn
+                .describe_change_set(DescribeChangeSetInput {
+                    change_set_name: self.name.clone(),
+                    stack_name: Some(self.stack_name.clone()),
+                    next_token: continuation_token.clone(),
+                })
+                .await?;
+            continuation_token = output.next_token.clone();
+
+            let changes = output
+                .changes
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|change| change.resource_change)
+                .map(|resource_change| ResourceChange {
+                    action: resource_change.action.unwrap_or_default(),
+                    logical_id: resource_change.logical_resource_id.unwrap_or_default(),
+                    physical_id: resource_change.physical_resource_id,
+                    resource_type: resource_change.resource_type.unwrap_or_default(),
+                    replacement: resource_change.replacement,
+                    scope: resource_change.scope.unwrap_or_default(),
+                });
+            match description.as_mut() {
+                Some(description) => description.changes.extend(changes),
+                None => {
+                    description = Some(ChangeSetDescription {
+                        stack_name: self.stack_name.clone(),
+                        change_set_name: self.name.clone(),
+                        change_set_id: output.change_set_id,
+                        description: output.description,
+                        status: output.status.unwrap_or_default(),
+                        status_reason: output.status_reason,
+                        execution_status: output.execution_status,
+                        parameters: output
+                            .parameters
+                            .as_deref()
+                            .unwrap_or_default()
+                            .iter()
+                            .filter_map(Parameter::from)
+                            .collect::<Vec<_>>()
+                            .into(),
+                        resolved_values: output
+                            .parameters
+                            .unwrap_or_default()
+                            .into_iter()
+                            .filter_map(|parameter| {
+                                Some((parameter.parameter_key?, parameter.resolved_value?))
+                            })
+                            .collect(),
+                        changes: changes.collect(),
+                    })
+                }
+            }
+
+            continuation_token.is_some()
+        } {}
+
+        // The loop runs at least once, so the description is always available at this point.
+        Ok(description.expect("change set description should have been retrieved"))
+    }
+
+    /// Get the template the change set was created with.
+    ///
+    /// This retrieves the original template, i.e. before any transforms have been processed.
+    pub async fn get_template(&self, cfn: &dyn CloudFormation) -> Result<Template, Error> {
+        let output = cfn
+            .get_template(GetTemplateInput {
+                stack_name: Some(self.stack_name.clone()),
+                change_set_name: Some(self.name.clone()),
+                template_stage: Some("Original".to_owned()),
+            })
+            .await?;
+        output
+            .template_body
+            .ok_or_else(|| Error::InvalidStack(self.stack_name.clone()))
+            .and_then(Template::from_bytes)
+    }
+
+    /// Wait until the creation of the change set has completed, returning its description.
+    ///
+    /// Returns an [`Error::ChangeSetCreationFailed`] if the creation failed, which notably also is
+    /// the case if the change set does not contain any changes.
+    ///
+    /// [`Error::ChangeSetCreationFailed`]: ../error/enum.Error.html#variant.ChangeSetCreationFailed
+    pub async fn wait_until_created(
+        &self,
+        cfn: &dyn CloudFormation,
+    ) -> Result<ChangeSetDescription, Error> {
+        loop {
+            let description = self.describe(cfn).await?;
+            match description.status.as_str() {
+                "CREATE_PENDING" | "CREATE_IN_PROGRESS" => tokio::time::sleep(POLL_INTERVAL).await,
+                "CREATE_COMPLETE" => return Ok(description),
+                _ => {
+                    return Err(Error::ChangeSetCreationFailed(
+                        description.status_reason.unwrap_or(description.status),
+                    ))
+                }
+            }
+        }
+    }
+
+    /// Execute the change set.
+    ///
+    /// This method will not wait for the execution to complete, use [`wait_until_executed`] for
+    /// that.
+    ///
+    /// [`wait_until_executed`]: #method.wait_until_executed
+    pub async fn execute(&self, cfn: &dyn CloudFormation) -> Result<(), Error> {
+        cfn.execute_change_set(ExecuteChangeSetInput {
+            change_set_name: self.name.clone(),
+            stack_name: Some(self.stack_name.clone()),
+            ..Default::default()
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// Wait until the stack operation started by executing the change set has completed,
+    /// returning the final stack status.
+    ///
+    /// Until the change set reports that its execution has started, the stack may still show the
+    /// final status of its previous operation, which is not mistaken for the result.
+    ///
+    /// Returns an [`Error::StackOperationFailed`] if the stack operation failed or was rolled back.
+    ///
+    /// [`Error::StackOperationFailed`]: ../error/enum.Error.html#variant.StackOperationFailed
+    pub async fn wait_until_executed(&self, cfn: &dyn CloudFormation) -> Result<String, Error> {
+        let stack = Stack::new(&self.stack_name);
+        let mut started = false;
+        loop {
+            match self.poll_execution(cfn, &stack, &mut started).await? {
+                ExecutionProgress::InProgress => tokio::time::sleep(POLL_INTERVAL).await,
+                ExecutionProgress::Complete(status) => return Ok(status),
+                ExecutionProgress::Failed(status) => {
+                    return Err(Error::StackOperationFailed(status))
+                }
+            }
+        }
+    }
+
+    /// Wait until the stack operation started by executing the change set has completed, passing
+    /// every stack event that happens in the meantime to `on_event`, oldest first.
+    ///
+    /// Only events after `since` are passed, which usually identifies the newest event before the
+    /// change set was executed. Apart from that, this behaves like [`wait_until_executed`].
+    ///
+    /// [`wait_until_executed`]: #method.wait_until_executed
+    pub async fn wait_until_executed_following_events<F: FnMut(&StackEvent)>(
+        &self,
+        cfn: &dyn CloudFormation,
+        mut since: Option<StackEventsSince>,
+        mut on_event: F,
+    ) -> Result<String, Error> {
+        let stack = Stack::new(&self.stack_name);
+        let mut started = false;
+        loop {
+            // The status is retrieved before the events, so that all events leading up to the
+            // final status are passed on.
+            let progress = self.poll_execution(cfn, &stack, &mut started).await?;
+
+            let mut events: Vec<StackEvent> =
+                stack.events(cfn, since.clone()).try_collect().await?;
+            events.reverse();
+            if let Some(event) = events.last() {
+                since = Some(StackEventsSince::EventId(event.event_id.clone()));
+            }
+            events.iter().for_each(&mut on_event);
+
+            match progress {
+                ExecutionProgress::InProgress => tokio::time::sleep(POLL_INTERVAL).await,
+                ExecutionProgress::Complete(status) => return Ok(status),
+                ExecutionProgress::Failed(status) => {
+                    return Err(Error::StackOperationFailed(status))
+                }
+            }
+        }
+    }
+
+    /// Determine the progress of the execution from the stack status and, until the execution is
+    /// known to have started, the execution status of the change set.
+    async fn poll_execution(
+        &self,
+        cfn: &dyn CloudFormation,
+        stack: &Stack,
+        started: &mut bool,
+    ) -> Result<ExecutionProgress, Error> {
+        let execution_status = if *started {
+            None
+        } else {
+            let execution_status = self.execution_status(cfn).await?;
+            *started = execution_status.as_deref() != Some("AVAILABLE");
+            execution_status
+        };
+        let status = stack
+            .status(cfn)
+            .await?
+            .ok_or_else(|| Error::InvalidStack(self.stack_name.clone()))?;
+        Ok(execution_progress(execution_status.as_deref(), status))
+    }
+
+    /// Retrieve only the execution status of the change set, or `None` if the change set no
+    /// longer exists.
+    async fn execution_status(&self, cfn: &dyn CloudFormation) -> Result<Option<String>, Error> {
+        match cfn
+            .describe_change_set(DescribeChangeSetInput {
+                change_set_name: self.name.clone(),
+                stack_name: Some(self.stack_name.clone()),
+                next_token: None,
+            })
+            .await
+        {
+            Ok(output) => Ok(output.execution_status),
+            Err(RusotoError::Service(DescribeChangeSetError::ChangeSetNotFound(_))) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Delete the change set.
+    pub async fn delete(&self, cfn: &dyn CloudFormation) -> Result<(), Error> {
+        cfn.delete_change_set(DeleteChangeSetInput {
+            change_set_name: self.name.clone(),
+            stack_name: Some(self.stack_name.clone()),
+        })
+        .await?;
+        Ok(())
+    }
+}
+
+/// The progress of the execution of a change set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ExecutionProgress {
+    /// The stack operation has not started or not completed yet.
+    InProgress,
+    /// The stack operation completed successfully with the given stack status.
+    Complete(String),
+    /// The stack operation failed or was rolled back with the given status.
+    Failed(String),
+}
+
+/// Determine the progress of the execution of a change set from its execution status, if still
+/// relevant, and the stack status.
+///
+/// As long as the change set is `AVAILABLE`, the execution has not started, and the stack status
+/// still belongs to the previous stack operation.
+fn execution_progress(execution_status: Option<&str>, stack_status: String) -> ExecutionProgress {
+    let is_successful = stack_status.ends_with("_COMPLETE") && !stack_status.contains("ROLLBACK");
+    match execution_status {
+        Some("AVAILABLE") => ExecutionProgress::InProgress,
+        _ if stack_status.ends_with("_IN_PROGRESS") => ExecutionProgress::InProgress,
+        Some("EXECUTE_FAILED") if is_successful => {
+            ExecutionProgress::Failed("EXECUTE_FAILED".to_owned())
+        }
+        _ if is_successful => ExecutionProgress::Complete(stack_status),
+        _ => ExecutionProgress::Failed(stack_status),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn execution_progress_ignores_status_of_previous_operation() {
+        // Right after executing the change set, the stack still shows the status of the previous
+        // update.
+        assert_eq!(
+            ExecutionProgress::InProgress,
+            execution_progress(Some("AVAILABLE"), "UPDATE_COMPLETE".to_owned())
+        );
+        assert_eq!(
+            ExecutionProgress::InProgress,
+            execution_progress(Some("EXECUTE_IN_PROGRESS"), "UPDATE_IN_PROGRESS".to_owned())
+        );
+        assert_eq!(
+            ExecutionProgress::Complete("UPDATE_COMPLETE".to_owned()),
+            execution_progress(None, "UPDATE_COMPLETE".to_owned())
+        );
+    }
+
+    #[test]
+    fn execution_progress_while_following_events() {
+        // The first poll after executing the change set still returns the old status, the events
+        // of the update only follow with the next polls.
+        let polls = [
+            (Some("AVAILABLE"), "UPDATE_COMPLETE"),
+            (Some("EXECUTE_IN_PROGRESS"), "UPDATE_IN_PROGRESS"),
+            (None, "UPDATE_COMPLETE_CLEANUP_IN_PROGRESS"),
+            (None, "UPDATE_COMPLETE"),
+        ];
+        let progress: Vec<_> = polls
+            .iter()
+            .map(|(execution_status, status)| {
+                execution_progress(*execution_status, (*status).to_owned())
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                ExecutionProgress::InProgress,
+                ExecutionProgress::InProgress,
+                ExecutionProgress::InProgress,
+                ExecutionProgress::Complete("UPDATE_COMPLETE".to_owned()),
+            ],
+            progress
+        );
+    }
+
+    #[test]
+    fn execution_progress_failures() {
+        assert_eq!(
+            ExecutionProgress::Failed("UPDATE_ROLLBACK_COMPLETE".to_owned()),
+            execution_progress(
+                Some("EXECUTE_FAILED"),
+                "UPDATE_ROLLBACK_COMPLETE".to_owned()
+            )
+        );
+        assert_eq!(
+            ExecutionProgress::Failed("EXECUTE_FAILED".to_owned()),
+            execution_progress(Some("EXECUTE_FAILED"), "UPDATE_COMPLETE".to_owned())
+        );
+    }
+}
@@ -0,0 +1,235 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+//! This module enables looking up the management events CloudTrail recorded.
+//!
+//! There is no Rusoto crate for CloudTrail available to awsx, which is why this module provides a
+//! minimal client for looking up events, dispatching signed requests through Rusoto.
+
+use chrono::{DateTime, TimeZone, Utc};
+use rusoto_core::{
+    credential::ProvideAwsCredentials, signature::SignedRequest, Client, DispatchSignedRequest,
+    Region, RusotoError,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::convert::Infallible;
+
+use crate::error::Error;
+
+/// Maximum number of events CloudTrail returns in a single response.
+const MAX_RESULTS: usize = 50;
+
+/// A management event recorded by CloudTrail.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TrailEvent {
+    /// ID of the event.
+    pub event_id: String,
+    /// Name of the API action, e.g. `UpdateStack`.
+    pub event_name: String,
+    /// When the action was performed.
+    pub event_time: Option<DateTime<Utc>>,
+    /// Name of the user or role session that performed the action.
+    pub username: Option<String>,
+    /// ARN of the identity that performed the action.
+    pub caller_arn: Option<String>,
+    /// IP address the action was performed from.
+    pub source_ip_address: Option<String>,
+    /// The user agent the action was performed with, e.g. the AWS CLI or awsx.
+    pub user_agent: Option<String>,
+    /// Names or ARNs of the resources the action was performed on.
+    pub resources: Vec<String>,
+    /// The parameters of the request.
+    pub request_parameters: serde_json::Value,
+    /// Error code, if the action failed.
+    pub error_code: Option<String>,
+    /// Error message, if the action failed.
+    pub error_message: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct LookupEventsResponse {
+    #[serde(default)]
+    events: Vec<LookupEventsEvent>,
+    next_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct LookupEventsEvent {
+    event_id: String,
+    event_name: String,
+    event_time: Option<f64>,
+    username: Option<String>,
+    #[serde(default)]
+    resources: Vec<LookupEventsResource>,
+    cloud_trail_event: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct LookupEventsResource {
+    resource_name: Option<String>,
+}
+
+impl From<LookupEventsEvent> for TrailEvent {
+    fn from(event: LookupEventsEvent) -> Self {
+        // The details of the event are only available as the serialized CloudTrail record.
+        let record: serde_json::Value = event
+            .cloud_trail_event
+            .as_deref()
+            .and_then(|record| serde_json::from_str(record).ok())
+            .unwrap_or_default();
+        let text = |pointer: &str| {
+            record
+                .pointer(pointer)
+                .and_then(|value| value.as_str())
+                .map(ToOwned::to_owned)
+        };
+
+        TrailEvent {
+            event_time: event
+                .event_time
+                .and_then(|seconds| Utc.timestamp_opt(seconds as i64, 0).single()),
+            caller_arn: text("/userIdentity/arn"),
+            source_ip_address: text("/sourceIPAddress"),
+            user_agent: text("/userAgent"),
+            resources: event
+                .resources
+                .into_iter()
+                .filter_map(|resource| resource.resource_name)
+                .collect(),
+            request_parameters: record.get("requestParameters").cloned().unwrap_or_default(),
+            error_code: text("/errorCode"),
+            error_message: text("/errorMessage"),
+            event_id: event.event_id,
+            event_name: event.event_name,
+            username: event.username,
+        }
+    }
+}
+
+/// A minimal client for the CloudTrail API.
+pub struct CloudTrailClient {
+    client: Client,
+    region: Region,
+}
+
+impl CloudTrailClient {
+    /// Create a client dispatching its requests with the given dispatcher and credentials.
+    pub fn new_with<P, D>(request_dispatcher: D, credentials_provider: P, region: Region) -> Self
+    where
+        P: ProvideAwsCredentials + Send + Sync + 'static,
+        D: DispatchSignedRequest + Send + Sync + 'static,
+    {
+        CloudTrailClient {
+            client: Client::new_with(credentials_provider, request_dispatcher),
+            region,
+        }
+    }
+
+    async fn request(&self, body: serde_json::Value) -> Result<LookupEventsResponse, Error> {
+        let mut request = SignedRequest::new("POST", "cloudtrail", &self.region, "/");
+        request.add_header(
+            "x-amz-target",
+            "com.amazonaws.cloudtrail.v20131101.CloudTrail_20131101.LookupEvents",
+        );
+        request.set_content_type("application/x-amz-json-1.1".to_owned());
+        request.set_payload(Some(serde_json::to_vec(&body)?));
+
+        let mut response = self
+            .client
+            .sign_and_dispatch(request)
+            .await
+            .map_err(RusotoError::<Infallible>::from)?;
+        let response = response
+            .buffer()
+            .await
+            .map_err(RusotoError::<Infallible>::from)?;
+        if !response.status.is_success() {
+            return Err(RusotoError::<Infallible>::Unknown(response).into());
+        }
+        Ok(serde_json::from_slice(&response.body)?)
+    }
+
+    /// Look up the events of the API action with the given name within the time range, newest
+    /// first.
+    pub async fn lookup_events_by_name(
+        &self,
+        event_name: &str,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> Result<Vec<TrailEvent>, Error> {
+        let mut events = Vec::new();
+        let mut next_token: Option<String> = None;
+        loop {
+            let mut body = json!({
+                "LookupAttributes": [{
+                    "AttributeKey": "EventName",
+                    "AttributeValue": event_name,
+                }],
+                "StartTime": start_time.timestamp(),
+                "EndTime": end_time.timestamp(),
+                "MaxResults": MAX_RESULTS,
+            });
+            if let Some(token) = next_token.take() {
+                body["NextToken"] = json!(token);
+            }
+
+            let response = self.request(body).await?;
+            events.extend(response.events.into_iter().map(TrailEvent::from));
+            next_token = response.next_token.filter(|token| !token.is_empty());
+            if next_token.is_none() {
+                break;
+            }
+        }
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trail_event_from_cloudtrail_record() {
+        let event: LookupEventsEvent = serde_json::from_str(
+            r#"{
+                "EventId": "0f6a",
+                "EventName": "UpdateStack",
+                "EventTime": 1735732800.0,
+                "Username": "deployer",
+                "Resources": [{"ResourceType": "AWS::CloudFormation::Stack", "ResourceName": "shop"}],
+                "CloudTrailEvent": "{\"userIdentity\":{\"arn\":\"arn:aws:sts::123456789012:assumed-role/deploy/deployer\"},\"sourceIPAddress\":\"203.0.113.1\",\"requestParameters\":{\"stackName\":\"shop\"},\"errorCode\":\"ValidationException\"}"
+            }"#,
+        )
+        .unwrap();
+
+        let event = TrailEvent::from(event);
+        assert_eq!(
+            event.event_time,
+            Some("2025-01-01T12:00:00Z".parse().unwrap())
+        );
+        assert_eq!(
+            event.caller_arn.as_deref(),
+            Some("arn:aws:sts::123456789012:assumed-role/deploy/deployer")
+        );
+        assert_eq!(event.resources, vec!["shop".to_owned()]);
+        assert_eq!(event.request_parameters["stackName"], "shop");
+        assert_eq!(event.error_code.as_deref(), Some("ValidationException"));
+        assert_eq!(event.error_message, None);
+    }
+}
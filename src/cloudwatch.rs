@@ -0,0 +1,197 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+//! This module enables monitoring CloudWatch alarms.
+//!
+//! There is no Rusoto crate for CloudWatch available to awsx, which is why this module provides a
+//! minimal client for the few actions awsx needs, dispatching signed requests through Rusoto.
+
+use rusoto_core::{
+    credential::ProvideAwsCredentials, param::Params, signature::SignedRequest, Client,
+    DispatchSignedRequest, Region, RusotoError,
+};
+use serde::Serialize;
+use std::convert::Infallible;
+
+use crate::{error::Error, xml::XmlElement};
+
+/// Version of the CloudWatch API this client implements.
+const API_VERSION: &str = "2010-08-01";
+
+/// Maximum number of alarm names CloudWatch accepts in a single request.
+const MAX_ALARM_NAMES: usize = 100;
+
+/// The current state of a CloudWatch alarm.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AlarmState {
+    /// Name of the alarm.
+    pub alarm_name: String,
+    /// State of the alarm: `OK`, `ALARM` or `INSUFFICIENT_DATA`.
+    pub state: String,
+    /// Explanation of the current state.
+    pub reason: Option<String>,
+    /// When the state was last updated.
+    pub updated_at: Option<String>,
+}
+
+impl AlarmState {
+    /// Check whether the alarm is firing.
+    pub fn is_alarm(&self) -> bool {
+        self.state == "ALARM"
+    }
+}
+
+/// Extract the states of both the metric and the composite alarms of a `DescribeAlarms` response.
+fn alarm_states(response: &XmlElement) -> Vec<AlarmState> {
+    let result = response.child("DescribeAlarmsResult");
+    ["MetricAlarms", "CompositeAlarms"]
+        .iter()
+        .filter_map(|alarms| result.and_then(|result| result.child(alarms)))
+        .flat_map(|alarms| alarms.children.iter())
+        .map(|alarm| AlarmState {
+            alarm_name: alarm.child_text("AlarmName").unwrap_or_default().to_owned(),
+            state: alarm
+                .child_text("StateValue")
+                .unwrap_or_default()
+                .to_owned(),
+            reason: alarm.child_text("StateReason").map(ToOwned::to_owned),
+            updated_at: alarm
+                .child_text("StateUpdatedTimestamp")
+                .map(ToOwned::to_owned),
+        })
+        .collect()
+}
+
+/// A minimal client for the CloudWatch API.
+pub struct CloudWatchClient {
+    client: Client,
+    region: Region,
+}
+
+impl CloudWatchClient {
+    /// Create a client dispatching its requests with the given dispatcher and credentials.
+    pub fn new_with<P, D>(request_dispatcher: D, credentials_provider: P, region: Region) -> Self
+    where
+        P: ProvideAwsCredentials + Send + Sync + 'static,
+        D: DispatchSignedRequest + Send + Sync + 'static,
+    {
+        CloudWatchClient {
+            client: Client::new_with(credentials_provider, request_dispatcher),
+            region,
+        }
+    }
+
+    async fn request(&self, action: &str, mut params: Params) -> Result<XmlElement, Error> {
+        params.insert("Action".to_owned(), Some(action.to_owned()));
+        params.insert("Version".to_owned(), Some(API_VERSION.to_owned()));
+        let mut request = SignedRequest::new("GET", "monitoring", &self.region, "/");
+        request.set_params(params);
+
+        let mut response = self
+            .client
+            .sign_and_dispatch(request)
+            .await
+            .map_err(RusotoError::<Infallible>::from)?;
+        let response = response
+            .buffer()
+            .await
+            .map_err(RusotoError::<Infallible>::from)?;
+        if !response.status.is_success() {
+            return Err(RusotoError::<Infallible>::Unknown(response).into());
+        }
+        XmlElement::parse(&response.body)
+    }
+
+    /// Describe the current state of the metric and composite alarms with the given names.
+    ///
+    /// Alarms that do not exist are missing from the result, rather than causing an error.
+    pub async fn describe_alarms(&self, alarm_names: &[String]) -> Result<Vec<AlarmState>, Error> {
+        let mut states = Vec::new();
+        for alarm_names in alarm_names.chunks(MAX_ALARM_NAMES) {
+            let mut next_token = None;
+            loop {
+                let mut params = Params::new();
+                for (index, alarm_name) in alarm_names.iter().enumerate() {
+                    params.insert(
+                        format!("AlarmNames.member.{}", index + 1),
+                        Some(alarm_name.clone()),
+                    );
+                }
+                params.insert(
+                    "AlarmTypes.member.1".to_owned(),
+                    Some("MetricAlarm".to_owned()),
+                );
+                params.insert(
+                    "AlarmTypes.member.2".to_owned(),
+                    Some("CompositeAlarm".to_owned()),
+                );
+                if let Some(token) = next_token.take() {
+                    params.insert("NextToken".to_owned(), Some(token));
+                }
+
+                let response = self.request("DescribeAlarms", params).await?;
+                states.extend(alarm_states(&response));
+                next_token = response
+                    .child("DescribeAlarmsResult")
+                    .and_then(|result| result.child_text("NextToken"))
+                    .filter(|token| !token.is_empty())
+                    .map(ToOwned::to_owned);
+                if next_token.is_none() {
+                    break;
+                }
+            }
+        }
+        Ok(states)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn statesof_metric_and_composite_alarms() {
+        let response = XmlElement::parse(
+            br#"<DescribeAlarmsResponse xmlns="http://monitoring.amazonaws.com/doc/2010-08-01/">
+                <DescribeAlarmsResult>
+                    <MetricAlarms>
+                        <member>
+                            <AlarmName>app-5xx</AlarmName>
+                            <StateValue>ALARM</StateValue>
+                            <StateReason>Threshold Crossed</StateReason>
+                            <StateUpdatedTimestamp>2025-01-01T12:00:00Z</StateUpdatedTimestamp>
+                        </member>
+                    </MetricAlarms>
+                    <CompositeAlarms>
+                        <member>
+                            <AlarmName>app-health</AlarmName>
+                            <StateValue>OK</StateValue>
+                        </member>
+                    </CompositeAlarms>
+                </DescribeAlarmsResult>
+            </DescribeAlarmsResponse>"#,
+        )
+        .unwrap();
+
+        let alarm_states = alarm_states(&response);
+        assert_eq!(alarm_states.len(), 2);
+        assert_eq!(alarm_states[0].alarm_name, "app-5xx");
+        assert!(alarm_states[0].is_alarm());
+        assert_eq!(alarm_states[0].reason.as_deref(), Some("Threshold Crossed"));
+        assert_eq!(alarm_states[1].alarm_name, "app-health");
+        assert!(!alarm_states[1].is_alarm());
+        assert_eq!(alarm_states[1].updated_at, None);
+    }
+}
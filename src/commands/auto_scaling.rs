@@ -0,0 +1,666 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Commands operating on auto-scaling groups.
+
+use futures::{Stream, TryStreamExt};
+use itertools::Itertools;
+use rusoto_autoscaling::{
+    AutoScalingGroup, AutoScalingGroupNamesType, Autoscaling, DescribeInstanceRefreshesType,
+    LaunchTemplateSpecification, RefreshPreferences, ScalingProcessQuery, StartInstanceRefreshType,
+    UpdateAutoScalingGroupType,
+};
+use serde::Serialize;
+use std::time::Duration;
+
+use crate::{
+    error::Error,
+    pagination::{paginate, Page},
+    tags::TagFilter,
+};
+
+/// Interval in which auto-scaling groups and instance refreshes are polled while waiting.
+const POLL_INTERVAL: Duration = Duration::from_secs(15);
+
+/// All processes of an auto-scaling group that can be suspended.
+pub const SCALING_PROCESSES: &[&str] = &[
+    "Launch",
+    "Terminate",
+    "AddToLoadBalancer",
+    "AlarmNotification",
+    "AZRebalance",
+    "HealthCheck",
+    "InstanceRefresh",
+    "ReplaceUnhealthy",
+    "ScheduledActions",
+];
+
+/// Parse the name of a scaling process case-insensitively, returning its canonical spelling.
+pub fn parse_scaling_process(scaling_process: &str) -> Result<String, String> {
+    SCALING_PROCESSES
+        .iter()
+        .find(|known| known.eq_ignore_ascii_case(scaling_process))
+        .map(|known| (*known).to_owned())
+        .ok_or_else(|| {
+            format!(
+                "`{}` is not a scaling process, expected one of: {}",
+                scaling_process,
+                SCALING_PROCESSES.join(", ")
+            )
+        })
+}
+
+/// Selects a single auto-scaling group.
+#[derive(Debug, Clone)]
+pub enum AutoScalingGroupSelector {
+    /// Select the auto-scaling group by its name.
+    Name(String),
+    /// Select the only auto-scaling group matching the tag filter.
+    Tags(TagFilter),
+}
+
+impl AutoScalingGroupSelector {
+    /// Find the selected auto-scaling group.
+    ///
+    /// Fails if no auto-scaling group matches, or if the tags match more than one group.
+    pub async fn find(&self, autoscaling: &dyn Autoscaling) -> Result<AutoScalingGroup, Error> {
+        let auto_scaling_groups = match self {
+            AutoScalingGroupSelector::Name(name) => {
+                describe_auto_scaling_groups(autoscaling, Some(vec![name.clone()])).await?
+            }
+            AutoScalingGroupSelector::Tags(tag_filter) => {
+                find_auto_scaling_groups(autoscaling, tag_filter).await?
+            }
+        };
+
+        match auto_scaling_groups.len() {
+            0 => Err(Error::ResourceNotFound(
+                "an auto-scaling group matching given filters".to_owned(),
+            )),
+            1 => Ok(auto_scaling_groups
+                .into_iter()
+                .next()
+                .expect("auto-scaling group should exist")),
+            _ => Err(Error::AmbiguousResource(format!(
+                "the tags match multiple auto-scaling groups: {}",
+                auto_scaling_groups
+                    .iter()
+                    .map(|auto_scaling_group| &auto_scaling_group.auto_scaling_group_name)
+                    .join(", ")
+            ))),
+        }
+    }
+}
+
+/// Describe the auto-scaling groups with the given names, or all auto-scaling groups.
+pub async fn describe_auto_scaling_groups(
+    autoscaling: &dyn Autoscaling,
+    auto_scaling_group_names: Option<Vec<String>>,
+) -> Result<Vec<AutoScalingGroup>, Error> {
+    stream_auto_scaling_groups(autoscaling, auto_scaling_group_names)
+        .try_collect()
+        .await
+}
+
+/// Stream the auto-scaling groups with the given names, or all auto-scaling groups.
+///
+/// The pages are retrieved lazily as the stream is consumed, which allows to process the groups of
+/// each page as soon as it was retrieved.
+pub fn stream_auto_scaling_groups<'a>(
+    autoscaling: &'a dyn Autoscaling,
+    auto_scaling_group_names: Option<Vec<String>>,
+) -> impl Stream<Item = Result<AutoScalingGroup, Error>> + 'a {
+    paginate(move |next_token| {
+        let auto_scaling_group_names = auto_scaling_group_names.clone();
+        async move {
+            let output = autoscaling
+                .describe_auto_scaling_groups(AutoScalingGroupNamesType {
+                    auto_scaling_group_names,
+                    next_token,
+                    ..Default::default()
+                })
+                .await?;
+            Ok::<_, Error>(Page::new(
+                Some(output.auto_scaling_groups),
+                output.next_token,
+            ))
+        }
+    })
+}
+
+/// Find all auto-scaling groups matching the tag filter.
+pub async fn find_auto_scaling_groups(
+    autoscaling: &dyn Autoscaling,
+    tag_filter: &TagFilter,
+) -> Result<Vec<AutoScalingGroup>, Error> {
+    Ok(describe_auto_scaling_groups(autoscaling, None)
+        .await?
+        .into_iter()
+        .filter(|auto_scaling_group| {
+            tag_filter.matches(auto_scaling_group.tags.as_deref().unwrap_or_default())
+        })
+        .collect())
+}
+
+/// The capacity, instances and launch template of an auto-scaling group.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AutoScalingGroupDetails {
+    /// ARN of the auto-scaling group.
+    pub auto_scaling_group_arn: Option<String>,
+    /// Name of the auto-scaling group.
+    pub auto_scaling_group_name: String,
+    /// Status of the auto-scaling group, only set while it is being deleted.
+    pub status: Option<String>,
+    /// Number of instances the auto-scaling group should run.
+    pub desired_capacity: i64,
+    /// Minimum number of instances.
+    pub min_size: i64,
+    /// Maximum number of instances.
+    pub max_size: i64,
+    /// Number of instances, regardless of their state.
+    pub instance_count: usize,
+    /// Number of instances that are healthy.
+    pub healthy_instance_count: usize,
+    /// Number of instances that are in service.
+    pub in_service_instance_count: usize,
+    /// The instances of the auto-scaling group.
+    pub instances: Vec<InstanceDetails>,
+    /// Name of the launch configuration, if the group uses one.
+    pub launch_configuration_name: Option<String>,
+    /// The launch template, either of the group itself or of its mixed instances policy.
+    pub launch_template: Option<LaunchTemplateDetails>,
+    /// ARNs of the target groups the instances are registered with.
+    pub target_group_arns: Option<Vec<String>>,
+}
+
+/// An instance of an auto-scaling group.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InstanceDetails {
+    /// ID of the instance.
+    pub instance_id: String,
+    /// Availability zone the instance runs in.
+    pub availability_zone: String,
+    /// Health status, `Healthy` or `Unhealthy`.
+    pub health_status: String,
+    /// Lifecycle state, e.g. `InService`.
+    pub lifecycle_state: String,
+    /// Version of the launch template the instance was launched with.
+    pub launch_template_version: Option<String>,
+}
+
+/// The launch template of an auto-scaling group.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LaunchTemplateDetails {
+    /// ID of the launch template.
+    pub launch_template_id: Option<String>,
+    /// Name of the launch template.
+    pub launch_template_name: Option<String>,
+    /// Version of the launch template, e.g. `$Latest`.
+    pub version: Option<String>,
+}
+
+impl From<&AutoScalingGroup> for AutoScalingGroupDetails {
+    fn from(auto_scaling_group: &AutoScalingGroup) -> Self {
+        let instances = auto_scaling_group.instances.as_deref().unwrap_or_default();
+        let launch_template = auto_scaling_group.launch_template.as_ref().or_else(|| {
+            auto_scaling_group
+                .mixed_instances_policy
+                .as_ref()
+                .and_then(|policy| policy.launch_template.as_ref())
+                .and_then(|launch_template| launch_template.launch_template_specification.as_ref())
+        });
+
+        AutoScalingGroupDetails {
+            auto_scaling_group_arn: auto_scaling_group.auto_scaling_group_arn.clone(),
+            auto_scaling_group_name: auto_scaling_group.auto_scaling_group_name.clone(),
+            status: auto_scaling_group.status.clone(),
+            desired_capacity: auto_scaling_group.desired_capacity,
+            min_size: auto_scaling_group.min_size,
+            max_size: auto_scaling_group.max_size,
+            instance_count: instances.len(),
+            healthy_instance_count: instances
+                .iter()
+                .filter(|instance| instance.health_status == "Healthy")
+                .count(),
+            in_service_instance_count: instances
+                .iter()
+                .filter(|instance| instance.lifecycle_state == "InService")
+                .count(),
+            instances: instances
+                .iter()
+                .map(|instance| InstanceDetails {
+                    instance_id: instance.instance_id.clone(),
+                    availability_zone: instance.availability_zone.clone(),
+                    health_status: instance.health_status.clone(),
+                    lifecycle_state: instance.lifecycle_state.clone(),
+                    launch_template_version: instance
+                        .launch_template
+                        .as_ref()
+                        .and_then(|launch_template| launch_template.version.clone()),
+                })
+                .collect(),
+            launch_configuration_name: auto_scaling_group.launch_configuration_name.clone(),
+            launch_template: launch_template.map(
+                |LaunchTemplateSpecification {
+                     launch_template_id,
+                     launch_template_name,
+                     version,
+                 }| LaunchTemplateDetails {
+                    launch_template_id: launch_template_id.clone(),
+                    launch_template_name: launch_template_name.clone(),
+                    version: version.clone(),
+                },
+            ),
+            target_group_arns: auto_scaling_group.target_group_ar_ns.clone(),
+        }
+    }
+}
+
+/// The auto-scaling groups found by `find-auto-scaling-group`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum FoundAutoScalingGroups {
+    /// The first auto-scaling group matching the filters.
+    First {
+        /// ARN of the auto-scaling group.
+        auto_scaling_group_arn: Option<String>,
+        /// Name of the auto-scaling group.
+        auto_scaling_group_name: String,
+        /// Details of the auto-scaling group.
+        auto_scaling_group: Box<AutoScalingGroupDetails>,
+    },
+    /// All auto-scaling groups matching the filters.
+    All {
+        /// Details of the auto-scaling groups.
+        auto_scaling_groups: Vec<AutoScalingGroupDetails>,
+    },
+    /// No auto-scaling group matches the filters.
+    None,
+}
+
+/// The capacity to set for an auto-scaling group.
+///
+/// Sizes that are not set keep their current value. If no desired capacity is set, the current
+/// one is clamped to the new minimum and maximum size.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CapacityUpdate {
+    /// Number of instances the auto-scaling group should run.
+    pub desired_capacity: Option<i64>,
+    /// Minimum number of instances.
+    pub min_size: Option<i64>,
+    /// Maximum number of instances.
+    pub max_size: Option<i64>,
+}
+
+/// The capacity of an auto-scaling group.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Capacity {
+    /// Name of the auto-scaling group.
+    pub auto_scaling_group_name: String,
+    /// Number of instances the auto-scaling group should run.
+    pub desired_capacity: i64,
+    /// Minimum number of instances.
+    pub min_size: i64,
+    /// Maximum number of instances.
+    pub max_size: i64,
+}
+
+/// Update the capacity of the selected auto-scaling group, returning the new capacity.
+///
+/// This does not wait for the instances to match the capacity, use [`wait_for_capacity`] for
+/// that.
+///
+/// [`wait_for_capacity`]: fn.wait_for_capacity.html
+pub async fn set_auto_scaling_group_capacity(
+    autoscaling: &dyn Autoscaling,
+    selector: &AutoScalingGroupSelector,
+    update: CapacityUpdate,
+) -> Result<Capacity, Error> {
+    let auto_scaling_group = selector.find(autoscaling).await?;
+    let min_size = update.min_size.unwrap_or(auto_scaling_group.min_size);
+    let max_size = update.max_size.unwrap_or(auto_scaling_group.max_size);
+    let desired_capacity = update.desired_capacity.unwrap_or_else(|| {
+        auto_scaling_group
+            .desired_capacity
+            .clamp(min_size, max_size)
+    });
+    if min_size > max_size || !(min_size..=max_size).contains(&desired_capacity) {
+        return Err(Error::InvalidParameters(format!(
+            "the desired capacity {} has to be between the minimum size {} and the maximum size {}",
+            desired_capacity, min_size, max_size
+        )));
+    }
+
+    autoscaling
+        .update_auto_scaling_group(UpdateAutoScalingGroupType {
+            auto_scaling_group_name: auto_scaling_group.auto_scaling_group_name.clone(),
+            desired_capacity: Some(desired_capacity),
+            min_size: Some(min_size),
+            max_size: Some(max_size),
+            ..Default::default()
+        })
+        .await?;
+
+    Ok(Capacity {
+        auto_scaling_group_name: auto_scaling_group.auto_scaling_group_name,
+        desired_capacity,
+        min_size,
+        max_size,
+    })
+}
+
+/// The number of instances of an auto-scaling group while waiting for its capacity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct InstanceCounts {
+    /// Number of healthy instances in service.
+    pub in_service: usize,
+    /// Number of instances, regardless of their state.
+    pub total: usize,
+}
+
+impl From<&AutoScalingGroup> for InstanceCounts {
+    fn from(auto_scaling_group: &AutoScalingGroup) -> Self {
+        let instances = auto_scaling_group.instances.as_deref().unwrap_or_default();
+        InstanceCounts {
+            in_service: instances
+                .iter()
+                .filter(|instance| {
+                    instance.lifecycle_state == "InService" && instance.health_status == "Healthy"
+                })
+                .count(),
+            total: instances.len(),
+        }
+    }
+}
+
+/// Wait until the group runs exactly the desired number of healthy instances in service.
+///
+/// The instance counts are passed to `on_progress` whenever they change.
+pub async fn wait_for_capacity<F: FnMut(InstanceCounts)>(
+    autoscaling: &dyn Autoscaling,
+    auto_scaling_group_name: &str,
+    desired_capacity: i64,
+    mut on_progress: F,
+) -> Result<(), Error> {
+    let desired_counts = InstanceCounts {
+        in_service: desired_capacity as usize,
+        total: desired_capacity as usize,
+    };
+    let mut last_counts = None;
+    loop {
+        let auto_scaling_group = describe_auto_scaling_groups(
+            autoscaling,
+            Some(vec![auto_scaling_group_name.to_owned()]),
+        )
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| {
+            Error::ResourceNotFound(format!("auto-scaling group {}", auto_scaling_group_name))
+        })?;
+
+        let counts = InstanceCounts::from(&auto_scaling_group);
+        if last_counts != Some(counts) {
+            on_progress(counts);
+            last_counts = Some(counts);
+        }
+
+        if counts == desired_counts {
+            return Ok(());
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// The suspended processes of an auto-scaling group.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SuspendedProcesses {
+    /// Name of the auto-scaling group.
+    pub auto_scaling_group_name: String,
+    /// Names of the suspended processes, sorted alphabetically.
+    pub suspended_processes: Vec<String>,
+}
+
+/// Return the names of the currently suspended processes of the auto-scaling group.
+pub async fn suspended_processes(
+    autoscaling: &dyn Autoscaling,
+    auto_scaling_group_name: &str,
+) -> Result<SuspendedProcesses, Error> {
+    Ok(SuspendedProcesses {
+        auto_scaling_group_name: auto_scaling_group_name.to_owned(),
+        suspended_processes: describe_auto_scaling_groups(
+            autoscaling,
+            Some(vec![auto_scaling_group_name.to_owned()]),
+        )
+        .await?
+        .into_iter()
+        .flat_map(|auto_scaling_group| auto_scaling_group.suspended_processes.unwrap_or_default())
+        .filter_map(|suspended_process| suspended_process.process_name)
+        .sorted()
+        .collect(),
+    })
+}
+
+/// Suspend the given processes of the selected auto-scaling group, or all processes if none are
+/// given, returning the processes suspended afterwards.
+pub async fn suspend_auto_scaling_processes(
+    autoscaling: &dyn Autoscaling,
+    selector: &AutoScalingGroupSelector,
+    scaling_processes: &[String],
+) -> Result<SuspendedProcesses, Error> {
+    let auto_scaling_group_name = selector.find(autoscaling).await?.auto_scaling_group_name;
+    autoscaling
+        .suspend_processes(scaling_process_query(
+            &auto_scaling_group_name,
+            scaling_processes,
+        ))
+        .await?;
+    suspended_processes(autoscaling, &auto_scaling_group_name).await
+}
+
+/// Resume the given processes of the selected auto-scaling group, or all suspended processes if
+/// none are given, returning the processes still suspended afterwards.
+pub async fn resume_auto_scaling_processes(
+    autoscaling: &dyn Autoscaling,
+    selector: &AutoScalingGroupSelector,
+    scaling_processes: &[String],
+) -> Result<SuspendedProcesses, Error> {
+    let auto_scaling_group_name = selector.find(autoscaling).await?.auto_scaling_group_name;
+    autoscaling
+        .resume_processes(scaling_process_query(
+            &auto_scaling_group_name,
+            scaling_processes,
+        ))
+        .await?;
+    suspended_processes(autoscaling, &auto_scaling_group_name).await
+}
+
+fn scaling_process_query(
+    auto_scaling_group_name: &str,
+    scaling_processes: &[String],
+) -> ScalingProcessQuery {
+    ScalingProcessQuery {
+        auto_scaling_group_name: auto_scaling_group_name.to_owned(),
+        scaling_processes: Some(scaling_processes.to_vec())
+            .filter(|scaling_processes| !scaling_processes.is_empty()),
+    }
+}
+
+/// Preferences of an instance refresh.
+///
+/// Preferences that are not set use the AWS defaults.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InstanceRefreshOptions {
+    /// Number of seconds until a new instance is considered to be warmed up.
+    pub instance_warmup: Option<i64>,
+    /// Percentage of the desired capacity that has to stay healthy during the refresh.
+    pub min_healthy_percentage: Option<i64>,
+}
+
+/// A started instance refresh.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StartedInstanceRefresh {
+    /// Name of the auto-scaling group.
+    pub auto_scaling_group_name: String,
+    /// ID of the instance refresh.
+    pub instance_refresh_id: String,
+}
+
+/// Start a rolling instance refresh of the selected auto-scaling group.
+///
+/// This does not wait for the refresh to complete, use [`wait_for_instance_refresh`] for that.
+///
+/// [`wait_for_instance_refresh`]: fn.wait_for_instance_refresh.html
+pub async fn start_instance_refresh(
+    autoscaling: &dyn Autoscaling,
+    selector: &AutoScalingGroupSelector,
+    options: InstanceRefreshOptions,
+) -> Result<StartedInstanceRefresh, Error> {
+    let auto_scaling_group_name = selector.find(autoscaling).await?.auto_scaling_group_name;
+    let instance_refresh_id = autoscaling
+        .start_instance_refresh(StartInstanceRefreshType {
+            auto_scaling_group_name: auto_scaling_group_name.clone(),
+            preferences: Some(RefreshPreferences {
+                instance_warmup: options.instance_warmup,
+                min_healthy_percentage: options.min_healthy_percentage,
+                ..Default::default()
+            }),
+            strategy: Some("Rolling".to_owned()),
+        })
+        .await?
+        .instance_refresh_id
+        .unwrap_or_default();
+
+    Ok(StartedInstanceRefresh {
+        auto_scaling_group_name,
+        instance_refresh_id,
+    })
+}
+
+/// The status of an instance refresh.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InstanceRefreshStatus {
+    /// Name of the auto-scaling group.
+    pub auto_scaling_group_name: String,
+    /// ID of the instance refresh.
+    pub instance_refresh_id: String,
+    /// Status of the instance refresh, e.g. `Successful`.
+    pub status: String,
+}
+
+/// The progress of an instance refresh.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InstanceRefreshProgress {
+    /// Status of the instance refresh, e.g. `InProgress`.
+    pub status: String,
+    /// Percentage of instances that have been replaced.
+    pub percentage_complete: i64,
+    /// Number of instances left to replace.
+    pub instances_to_update: i64,
+}
+
+/// Wait until the instance refresh has completed, returning its final status.
+///
+/// The progress is passed to `on_progress` whenever it changes, i.e. once per batch of instances.
+/// Returns an `Error::InstanceRefreshFailed` if the refresh failed or was cancelled.
+pub async fn wait_for_instance_refresh<F: FnMut(&InstanceRefreshProgress)>(
+    autoscaling: &dyn Autoscaling,
+    auto_scaling_group_name: &str,
+    instance_refresh_id: &str,
+    mut on_progress: F,
+) -> Result<String, Error> {
+    let mut last_progress = None;
+    loop {
+        let instance_refresh = autoscaling
+            .describe_instance_refreshes(DescribeInstanceRefreshesType {
+                auto_scaling_group_name: auto_scaling_group_name.to_owned(),
+                instance_refresh_ids: Some(vec![instance_refresh_id.to_owned()]),
+                ..Default::default()
+            })
+            .await?
+            .instance_refreshes
+            .and_then(|instance_refreshes| instance_refreshes.into_iter().next())
+            .ok_or_else(|| {
+                Error::ResourceNotFound(format!("instance refresh {}", instance_refresh_id))
+            })?;
+
+        let progress = InstanceRefreshProgress {
+            status: instance_refresh.status.unwrap_or_default(),
+            percentage_complete: instance_refresh.percentage_complete.unwrap_or_default(),
+            instances_to_update: instance_refresh.instances_to_update.unwrap_or_default(),
+        };
+        if last_progress.as_ref() != Some(&progress) {
+            on_progress(&progress);
+        }
+
+        match progress.status.as_str() {
+            "Pending" | "InProgress" | "Cancelling" | "RollbackInProgress" => {
+                tokio::time::sleep(POLL_INTERVAL).await
+            }
+            "Successful" => return Ok(progress.status),
+            _ => {
+                return Err(Error::InstanceRefreshFailed(
+                    auto_scaling_group_name.to_owned(),
+                    match instance_refresh.status_reason {
+                        Some(reason) => format!("{}: {}", progress.status, reason),
+                        None => progress.status,
+                    },
+                ))
+            }
+        }
+        last_progress = Some(progress);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusoto_autoscaling::Instance;
+
+    #[test]
+    fn scaling_process_is_parsed_case_insensitively() {
+        assert_eq!(
+            Ok("AZRebalance".to_owned()),
+            parse_scaling_process("azrebalance")
+        );
+        assert!(parse_scaling_process("Rebalance").is_err());
+    }
+
+    #[test]
+    fn instance_counts_only_include_healthy_instances_in_service() {
+        let instance = |lifecycle_state: &str, health_status: &str| Instance {
+            lifecycle_state: lifecycle_state.to_owned(),
+            health_status: health_status.to_owned(),
+            ..Default::default()
+        };
+        let auto_scaling_group = AutoScalingGroup {
+            instances: Some(vec![
+                instance("InService", "Healthy"),
+                instance("InService", "Unhealthy"),
+                instance("Pending", "Healthy"),
+            ]),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            InstanceCounts {
+                in_service: 1,
+                total: 3,
+            },
+            InstanceCounts::from(&auto_scaling_group)
+        );
+    }
+}
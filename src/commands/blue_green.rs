@@ -0,0 +1,1150 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Commands running the phases of a blue/green deployment described by a declarative [`Plan`].
+//!
+//! A deployment provisions the green stack, waits until its targets are healthy, shifts the
+//! traffic from the blue to the green stack and finally decommissions the blue stack. The
+//! progress is tracked in a [`State`], which the caller persists to resume an interrupted
+//! deployment.
+
+use indexmap::IndexMap;
+use itertools::Itertools;
+use rusoto_cloudformation::CloudFormation;
+use rusoto_elbv2::{
+    Action, DescribeListenersInput, DescribeRulesInput, Elb, ForwardActionConfig,
+    ModifyListenerInput, ModifyRuleInput, TargetGroupTuple,
+};
+use serde::{Deserialize, Serialize};
+use std::{num::NonZeroU32, time::Duration};
+
+use super::{
+    cloudformation::{change_set::PreparedChangeSet, deploy::MetadataOptions},
+    elb::{wait_for_healthy_targets, TargetGroupHealth},
+    route53::step_weight,
+};
+use crate::{
+    changeset::ChangeSet,
+    deployment::generate_deployment_metadata,
+    error::Error,
+    parameter::{Parameter, Parameters},
+    route53::Route53Client,
+    s3::S3Uploader,
+    stack::Stack,
+    template::Template,
+};
+
+/// The declarative plan of a blue/green deployment.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Plan {
+    /// The stack currently receiving the traffic.
+    pub blue: BlueStackPlan,
+    /// The stack the traffic is shifted to.
+    pub green: GreenStackPlan,
+    /// When the green stack is considered healthy.
+    #[serde(default)]
+    pub health: HealthPlan,
+    /// How the traffic is shifted.
+    pub traffic: TrafficPlan,
+    /// What happens to the blue stack once the traffic has been shifted.
+    #[serde(default)]
+    pub decommission: DecommissionPlan,
+}
+
+/// The stack currently receiving the traffic.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct BlueStackPlan {
+    /// Name of the stack.
+    pub stack_name: String,
+    /// ARN of the target group of the stack, otherwise it is taken from the stack output.
+    pub target_group_arn: Option<String>,
+    /// Name of the stack output holding the ARN of the target group.
+    #[serde(default = "default_target_group_output")]
+    pub target_group_output: String,
+}
+
+/// The stack the traffic is shifted to, provisioned from the template.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct GreenStackPlan {
+    /// Name of the stack.
+    pub stack_name: String,
+    /// Path to the template of the stack, relative to the plan.
+    pub template_path: String,
+    /// Path to a parameter file for the template, relative to the plan.
+    pub parameter_path: Option<String>,
+    /// Parameters for the template, which take precedence over the parameter file.
+    #[serde(default)]
+    pub parameters: IndexMap<String, String>,
+    /// IAM role CloudFormation assumes when provisioning the stack.
+    pub role_arn: Option<String>,
+    /// Resource types the stack is allowed to contain, or all resource types if empty.
+    #[serde(default)]
+    pub resource_types: Vec<String>,
+    /// ARN of the target group of the stack, otherwise it is taken from the stack output.
+    pub target_group_arn: Option<String>,
+    /// Name of the stack output holding the ARN of the target group.
+    #[serde(default = "default_target_group_output")]
+    pub target_group_output: String,
+}
+
+/// When the targets of the green stack are considered healthy.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct HealthPlan {
+    /// Minimum number of healthy targets, or all targets if not given.
+    pub min_healthy: Option<usize>,
+    /// Maximum time to wait for the targets to become healthy, in seconds.
+    #[serde(default = "default_health_timeout")]
+    pub timeout: u64,
+}
+
+impl Default for HealthPlan {
+    fn default() -> Self {
+        HealthPlan {
+            min_healthy: None,
+            timeout: default_health_timeout(),
+        }
+    }
+}
+
+/// How the traffic is shifted: through the weights of an ALB listener (rule) forwarding to the
+/// target groups of both stacks, or through the weights of Route53 record sets.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct TrafficPlan {
+    /// Number of steps to shift the traffic in.
+    #[serde(default = "default_steps")]
+    pub steps: NonZeroU32,
+    /// Time to wait between the steps, in seconds.
+    #[serde(default = "default_step_interval")]
+    pub step_interval: u64,
+    /// ARN of the listener whose default action forwards to the target groups.
+    pub listener_arn: Option<String>,
+    /// ARN of the listener rule whose action forwards to the target groups.
+    pub listener_rule_arn: Option<String>,
+    /// The weighted record sets of the stacks.
+    pub route53: Option<Route53TrafficPlan>,
+}
+
+/// The weighted Route53 record sets of the blue and green stack.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Route53TrafficPlan {
+    /// ID of the hosted zone of the record sets.
+    pub hosted_zone_id: Option<String>,
+    /// Name of the hosted zone of the record sets, if its ID is not given.
+    pub hosted_zone_name: Option<String>,
+    /// Name of the record sets, e.g. `app.example.com`.
+    pub record_name: String,
+    /// Type of the record sets.
+    #[serde(default = "default_record_type")]
+    pub record_type: String,
+    /// Set identifier of the record set of the blue stack.
+    pub blue_set_identifier: String,
+    /// Set identifier of the record set of the green stack.
+    pub green_set_identifier: String,
+    /// Maximum time to wait for each change to be propagated, in seconds.
+    #[serde(default = "default_change_timeout")]
+    pub change_timeout: u64,
+}
+
+/// What happens to the blue stack once the traffic has been shifted.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct DecommissionPlan {
+    /// Whether to delete the blue stack, otherwise it is kept.
+    #[serde(default = "default_delete_blue_stack")]
+    pub delete_blue_stack: bool,
+    /// IAM role CloudFormation assumes when deleting the blue stack.
+    pub role_arn: Option<String>,
+}
+
+impl Default for DecommissionPlan {
+    fn default() -> Self {
+        DecommissionPlan {
+            delete_blue_stack: default_delete_blue_stack(),
+            role_arn: None,
+        }
+    }
+}
+
+fn default_target_group_output() -> String {
+    "TargetGroupArn".to_owned()
+}
+
+fn default_health_timeout() -> u64 {
+    600
+}
+
+fn default_steps() -> NonZeroU32 {
+    NonZeroU32::new(1).expect("1 is not zero")
+}
+
+fn default_step_interval() -> u64 {
+    60
+}
+
+fn default_record_type() -> String {
+    "A".to_owned()
+}
+
+fn default_change_timeout() -> u64 {
+    300
+}
+
+fn default_delete_blue_stack() -> bool {
+    true
+}
+
+impl Plan {
+    /// Parse and validate the YAML plan, naming its `origin`, e.g. its path, in errors.
+    pub fn from_yaml(contents: &[u8], origin: &str) -> Result<Plan, Error> {
+        let plan: Plan = serde_yaml::from_slice(contents)
+            .map_err(|error| Error::InvalidConfiguration(format!("{}: {}", origin, error)))?;
+        plan.validate()
+            .map_err(|error| Error::InvalidConfiguration(format!("{}: {}", origin, error)))?;
+        Ok(plan)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let traffic_targets = [
+            self.traffic.listener_arn.is_some(),
+            self.traffic.listener_rule_arn.is_some(),
+            self.traffic.route53.is_some(),
+        ];
+        if traffic_targets.iter().filter(|target| **target).count() != 1 {
+            return Err(
+                "traffic requires exactly one of listener-arn, listener-rule-arn or \
+                        route53"
+                    .to_owned(),
+            );
+        }
+        if let Some(route53) = &self.traffic.route53 {
+            if route53.hosted_zone_id.is_some() == route53.hosted_zone_name.is_some() {
+                return Err(
+                    "traffic.route53 requires exactly one of hosted-zone-id or hosted-zone-name"
+                        .to_owned(),
+                );
+            }
+        }
+        if self.blue.stack_name == self.green.stack_name {
+            return Err("the blue and green stack have to be different stacks".to_owned());
+        }
+        Ok(())
+    }
+}
+
+/// The phases of a blue/green deployment, in the order they are run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Phase {
+    /// Create or update the green stack from the template.
+    Provision,
+    /// Wait until the targets of the green stack are healthy.
+    WaitHealthy,
+    /// Shift the traffic from the blue to the green stack.
+    ShiftTraffic,
+    /// Delete the blue stack.
+    Decommission,
+}
+
+/// All phases, in the order they are run.
+pub const PHASES: &[Phase] = &[
+    Phase::Provision,
+    Phase::WaitHealthy,
+    Phase::ShiftTraffic,
+    Phase::Decommission,
+];
+
+impl Phase {
+    /// The name of the phase, as it is serialized.
+    pub fn name(self) -> &'static str {
+        match self {
+            Phase::Provision => "provision",
+            Phase::WaitHealthy => "wait-healthy",
+            Phase::ShiftTraffic => "shift-traffic",
+            Phase::Decommission => "decommission",
+        }
+    }
+}
+
+/// The progress of a deployment, to be persisted after every step so that it can be resumed.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct State {
+    /// The phases that have completed.
+    pub completed_phases: Vec<Phase>,
+    /// The change set created to provision the green stack, if it was created.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub change_set_name: Option<String>,
+    /// The last step of shifting the traffic that has completed.
+    #[serde(default)]
+    pub traffic_step: u32,
+}
+
+impl State {
+    /// Whether the phase has completed.
+    pub fn is_completed(&self, phase: Phase) -> bool {
+        self.completed_phases.contains(&phase)
+    }
+
+    /// Whether the phase still has to run, failing if an earlier phase has not completed yet.
+    pub fn needs_run(&self, phase: Phase) -> Result<bool, Error> {
+        if self.is_completed(phase) {
+            return Ok(false);
+        }
+        match PHASES
+            .iter()
+            .take_while(|other| **other != phase)
+            .find(|other| !self.is_completed(**other))
+        {
+            Some(required) => Err(Error::DeploymentPhaseOutOfOrder(
+                phase.name().to_owned(),
+                required.name().to_owned(),
+            )),
+            None => Ok(true),
+        }
+    }
+}
+
+/// Where a deployment stands, as recorded in its state.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DeploymentStatus {
+    /// The phases that have completed.
+    pub completed_phases: Vec<Phase>,
+    /// The phases that still have to run, in order.
+    pub pending_phases: Vec<Phase>,
+    /// The change set created to provision the green stack, if it was created.
+    pub change_set_name: Option<String>,
+    /// The last step of shifting the traffic that has completed.
+    pub traffic_step: u32,
+    /// Number of steps the traffic is shifted in.
+    pub traffic_steps: u32,
+}
+
+impl DeploymentStatus {
+    /// Determine the status of the deployment of the plan from its state.
+    pub fn new(plan: &Plan, state: &State) -> DeploymentStatus {
+        DeploymentStatus {
+            completed_phases: state.completed_phases.clone(),
+            pending_phases: PHASES
+                .iter()
+                .copied()
+                .filter(|phase| !state.is_completed(*phase))
+                .collect(),
+            change_set_name: state.change_set_name.clone(),
+            traffic_step: state.traffic_step,
+            traffic_steps: plan.traffic.steps.get(),
+        }
+    }
+}
+
+/// The phases run by a deployment.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DeploymentOutcome {
+    /// The outcome of every phase that was requested, in order.
+    pub phases: Vec<PhaseOutcome>,
+    /// All phases that have completed, including those of previous runs.
+    pub completed_phases: Vec<Phase>,
+}
+
+/// The outcome of a single phase.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PhaseOutcome {
+    /// The phase.
+    pub phase: Phase,
+    /// Whether the phase was skipped, since it had completed in a previous run.
+    pub skipped: bool,
+    /// What the phase did, if it was run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<PhaseDetails>,
+}
+
+impl PhaseOutcome {
+    /// The outcome of a phase that had completed in a previous run.
+    pub fn skipped(phase: Phase) -> PhaseOutcome {
+        PhaseOutcome {
+            phase,
+            skipped: true,
+            details: None,
+        }
+    }
+
+    /// The outcome of a phase that was run.
+    pub fn completed(phase: Phase, details: PhaseDetails) -> PhaseOutcome {
+        PhaseOutcome {
+            phase,
+            skipped: false,
+            details: Some(details),
+        }
+    }
+}
+
+/// What a phase did, specific to the phase.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum PhaseDetails {
+    /// See [`Phase::Provision`].
+    Provision(ProvisionedGreenStack),
+    /// See [`Phase::WaitHealthy`].
+    WaitHealthy(HealthyGreenStack),
+    /// See [`Phase::ShiftTraffic`].
+    ShiftTraffic(ShiftedTraffic),
+    /// See [`Phase::Decommission`].
+    Decommission(DecommissionedBlueStack),
+}
+
+/// The green stack after provisioning it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProvisionedGreenStack {
+    /// Name of the green stack.
+    pub stack_name: String,
+    /// The change set that provisioned the green stack, unless it was up to date already.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change_set_name: Option<String>,
+    /// Whether the green stack was changed.
+    pub changes: bool,
+}
+
+/// The healthy targets of the green stack.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HealthyGreenStack {
+    /// ARN of the target group of the green stack.
+    pub target_group_arn: String,
+    /// Number of healthy targets.
+    pub healthy_count: usize,
+}
+
+/// The traffic after shifting it to the green stack.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ShiftedTraffic {
+    /// Number of steps the traffic was shifted in.
+    pub steps: u32,
+}
+
+/// The blue stack after decommissioning it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DecommissionedBlueStack {
+    /// Name of the blue stack.
+    pub stack_name: String,
+    /// Whether the blue stack was deleted, otherwise it was kept.
+    pub deleted: bool,
+}
+
+/// The input of [`prepare_green_provision`].
+pub struct GreenProvision<'a> {
+    /// Name of the change set provisioning the green stack.
+    pub change_set_name: &'a str,
+    /// The template of the green stack.
+    pub template: Template,
+    /// The parameters of the parameter file of the green stack, if the plan has one.
+    pub parameter_file: Option<Parameters>,
+}
+
+/// Prepare the change set provisioning the green stack from the template.
+///
+/// The parameters of the plan take precedence over the parameter file, which takes precedence over
+/// the defaults declared by the template. All parameters required by the template have to be
+/// provided. The change set creates the green stack, unless it exists already.
+pub async fn prepare_green_provision(
+    cfn: &dyn CloudFormation,
+    plan: &GreenStackPlan,
+    input: GreenProvision<'_>,
+    metadata: &MetadataOptions<'_>,
+    s3_upload: Option<(&S3Uploader, &str)>,
+) -> Result<PreparedChangeSet, Error> {
+    let template = input.template;
+    let mut warnings = Vec::new();
+
+    let mut parameters = template.get_parameters().to_owned();
+    if let Some(parameter_file) = input.parameter_file {
+        parameters.update(parameter_file);
+    }
+    parameters.update(
+        plan.parameters
+            .iter()
+            .map(|(key, value)| Parameter::WithValue {
+                key: key.clone(),
+                value: value.clone(),
+            })
+            .collect::<Vec<_>>(),
+    );
+    if metadata.record {
+        let deployment_metadata = generate_deployment_metadata(None, metadata.git_path)?;
+        metadata.record_applied(
+            deployment_metadata,
+            Some(&template),
+            s3_upload,
+            &mut parameters,
+            None,
+            &mut warnings,
+        )?;
+    }
+
+    let missing_parameters = parameters
+        .iter()
+        .filter(|(_, parameter)| parameter.is_previous_value())
+        .map(|(name, _)| name)
+        .collect::<Vec<_>>();
+    if !missing_parameters.is_empty() {
+        return Err(Error::InvalidParameters(format!(
+            "not all required parameters ({}) were provided",
+            missing_parameters.iter().join(", ")
+        )));
+    }
+
+    // A stack created through a change set that was never executed is still in review, and needs
+    // another change set of the type `CREATE`.
+    let create_stack = Stack::new(&plan.stack_name)
+        .status(cfn)
+        .await?
+        .is_none_or(|status| status == "REVIEW_IN_PROGRESS");
+
+    Ok(PreparedChangeSet {
+        stack_name: plan.stack_name.clone(),
+        change_set_name: input.change_set_name.to_owned(),
+        template: Some(template),
+        parameters,
+        create_stack,
+        warnings,
+        details: (),
+    })
+}
+
+/// Return the change set provisioning the green stack that was created by a previous, interrupted
+/// run, unless there is none or it does not exist anymore.
+pub async fn resume_green_provision(
+    cfn: &dyn CloudFormation,
+    plan: &GreenStackPlan,
+    state: &State,
+) -> Result<Option<ChangeSet>, Error> {
+    let change_set = match &state.change_set_name {
+        Some(change_set_name) => ChangeSet::new(&plan.stack_name, change_set_name),
+        None => return Ok(None),
+    };
+    match change_set.describe(cfn).await {
+        Ok(_) => Ok(Some(change_set)),
+        Err(error) if error.is_not_found() => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+/// Wait until enough targets of the target group of the green stack are healthy, as the plan
+/// requests, returning their health.
+///
+/// Waiting fails once the timeout of the plan has passed. The health of the targets is passed to
+/// `on_progress` whenever they are not healthy enough yet.
+pub async fn wait_healthy<F: FnMut(&TargetGroupHealth)>(
+    cfn: &dyn CloudFormation,
+    elb: &dyn Elb,
+    plan: &Plan,
+    on_progress: F,
+) -> Result<TargetGroupHealth, Error> {
+    let target_group_arn = target_group_arn(
+        cfn,
+        &plan.green.stack_name,
+        plan.green.target_group_arn.as_deref(),
+        &plan.green.target_group_output,
+    )
+    .await?;
+    tokio::time::timeout(
+        Duration::from_secs(plan.health.timeout),
+        wait_for_healthy_targets(elb, &target_group_arn, plan.health.min_healthy, on_progress),
+    )
+    .await
+    .unwrap_or_else(|_| {
+        Err(Error::WaitTimeout(format!(
+            "targets of {} to become healthy",
+            target_group_arn
+        )))
+    })
+}
+
+/// The weights of the blue and green stack after a step of shifting the traffic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct TrafficStep {
+    /// Number of the step, starting at 1.
+    pub step: u32,
+    /// Number of steps the traffic is shifted in.
+    pub steps: u32,
+    /// Weight of the blue stack, between 0 and 100.
+    pub blue_weight: i64,
+    /// Weight of the green stack, between 0 and 100.
+    pub green_weight: i64,
+}
+
+/// The weights of the blue and green stack for every step after the completed one, shifting all
+/// traffic to green in even steps.
+pub fn traffic_weights(completed_step: u32, steps: u32) -> Vec<TrafficStep> {
+    (completed_step + 1..=steps)
+        .map(|step| {
+            let green_weight = step_weight(0, 100, step, steps);
+            TrafficStep {
+                step,
+                steps,
+                blue_weight: 100 - green_weight,
+                green_weight,
+            }
+        })
+        .collect()
+}
+
+/// Shift the traffic to the green stack in the steps of the plan following the completed step,
+/// returning the steps taken.
+///
+/// Every step is passed to `on_step` once it took effect, so that it can be recorded in the state
+/// before waiting for the next step. An error returned by `on_step` aborts the shift.
+pub async fn shift_traffic<F: FnMut(&TrafficStep) -> Result<(), Error>>(
+    cfn: &dyn CloudFormation,
+    elb: &dyn Elb,
+    route53: &Route53Client,
+    plan: &Plan,
+    completed_step: u32,
+    mut on_step: F,
+) -> Result<Vec<TrafficStep>, Error> {
+    let hosted_zone_id = match &plan.traffic.route53 {
+        Some(Route53TrafficPlan {
+            hosted_zone_id: Some(hosted_zone_id),
+            ..
+        }) => Some(hosted_zone_id.clone()),
+        Some(Route53TrafficPlan {
+            hosted_zone_name: Some(hosted_zone_name),
+            ..
+        }) => Some(route53.find_hosted_zone_id(hosted_zone_name).await?),
+        _ => None,
+    };
+    let target_group_arns = if plan.traffic.route53.is_none() {
+        Some((
+            target_group_arn(
+                cfn,
+                &plan.blue.stack_name,
+                plan.blue.target_group_arn.as_deref(),
+                &plan.blue.target_group_output,
+            )
+            .await?,
+            target_group_arn(
+                cfn,
+                &plan.green.stack_name,
+                plan.green.target_group_arn.as_deref(),
+                &plan.green.target_group_output,
+            )
+            .await?,
+        ))
+    } else {
+        None
+    };
+
+    let steps = traffic_weights(completed_step, plan.traffic.steps.get());
+    for traffic_step in &steps {
+        match (&plan.traffic.route53, &hosted_zone_id, &target_group_arns) {
+            (Some(route53_plan), Some(hosted_zone_id), _) => {
+                shift_route53_weights(route53, route53_plan, hosted_zone_id, traffic_step).await?
+            }
+            (_, _, Some((blue_target_group_arn, green_target_group_arn))) => {
+                let target_groups = vec![
+                    TargetGroupTuple {
+                        target_group_arn: Some(blue_target_group_arn.clone()),
+                        weight: Some(traffic_step.blue_weight),
+                    },
+                    TargetGroupTuple {
+                        target_group_arn: Some(green_target_group_arn.clone()),
+                        weight: Some(traffic_step.green_weight),
+                    },
+                ];
+                shift_listener_weights(elb, &plan.traffic, target_groups).await?
+            }
+            _ => unreachable!("the plan has been validated to shift traffic somehow"),
+        }
+        on_step(traffic_step)?;
+
+        if traffic_step.step < traffic_step.steps {
+            tokio::time::sleep(Duration::from_secs(plan.traffic.step_interval)).await;
+        }
+    }
+
+    Ok(steps)
+}
+
+/// Forward the traffic of the listener or listener rule to the weighted target groups.
+async fn shift_listener_weights(
+    elb: &dyn Elb,
+    traffic: &TrafficPlan,
+    target_groups: Vec<TargetGroupTuple>,
+) -> Result<(), Error> {
+    if let Some(listener_rule_arn) = &traffic.listener_rule_arn {
+        let actions = elb
+            .describe_rules(DescribeRulesInput {
+                rule_arns: Some(vec![listener_rule_arn.clone()]),
+                ..Default::default()
+            })
+            .await?
+            .rules
+            .and_then(|rules| rules.into_iter().next())
+            .and_then(|rule| rule.actions)
+            .unwrap_or_default();
+        let actions = with_forward_weights(actions, target_groups).ok_or_else(|| {
+            Error::ResourceNotFound(format!("forward action of rule {}", listener_rule_arn))
+        })?;
+        elb.modify_rule(ModifyRuleInput {
+            rule_arn: listener_rule_arn.clone(),
+            actions: Some(actions),
+            conditions: None,
+        })
+        .await?;
+    } else if let Some(listener_arn) = &traffic.listener_arn {
+        let actions = elb
+            .describe_listeners(DescribeListenersInput {
+                listener_arns: Some(vec![listener_arn.clone()]),
+                ..Default::default()
+            })
+            .await?
+            .listeners
+            .and_then(|listeners| listeners.into_iter().next())
+            .and_then(|listener| listener.default_actions)
+            .unwrap_or_default();
+        let actions = with_forward_weights(actions, target_groups).ok_or_else(|| {
+            Error::ResourceNotFound(format!("forward action of listener {}", listener_arn))
+        })?;
+        elb.modify_listener(ModifyListenerInput {
+            listener_arn: listener_arn.clone(),
+            default_actions: Some(actions),
+            ..Default::default()
+        })
+        .await?;
+    }
+    Ok(())
+}
+
+/// Set the weights of the blue and green record sets.
+async fn shift_route53_weights(
+    route53: &Route53Client,
+    plan: &Route53TrafficPlan,
+    hosted_zone_id: &str,
+    traffic_step: &TrafficStep,
+) -> Result<(), Error> {
+    let resource_record_sets = route53
+        .list_resource_record_sets(hosted_zone_id, &plan.record_name, &plan.record_type)
+        .await?;
+    let shifted = [
+        (&plan.blue_set_identifier, traffic_step.blue_weight),
+        (&plan.green_set_identifier, traffic_step.green_weight),
+    ]
+    .iter()
+    .map(|(set_identifier, weight)| {
+        resource_record_sets
+            .iter()
+            .find(|resource_record_set| {
+                resource_record_set.set_identifier() == Some(set_identifier.as_str())
+                    && resource_record_set.weight().is_some()
+            })
+            .map(|resource_record_set| resource_record_set.with_weight(*weight))
+            .ok_or_else(|| {
+                Error::ResourceNotFound(format!(
+                    "weighted {} record set {} with set identifier {}",
+                    plan.record_type, plan.record_name, set_identifier
+                ))
+            })
+    })
+    .collect::<Result<Vec<_>, Error>>()?;
+
+    let change_id = route53
+        .upsert_resource_record_sets(
+            hosted_zone_id,
+            &format!(
+                "awsx: blue/green deployment, step {} of {}",
+                traffic_step.step, traffic_step.steps
+            ),
+            &shifted,
+        )
+        .await?;
+    route53
+        .wait_for_change(&change_id, Duration::from_secs(plan.change_timeout))
+        .await
+}
+
+/// Replace the forward action among the actions with one forwarding to the weighted target groups,
+/// retaining its stickiness configuration.
+///
+/// Returns `None` if there is no forward action.
+fn with_forward_weights(
+    actions: Vec<Action>,
+    target_groups: Vec<TargetGroupTuple>,
+) -> Option<Vec<Action>> {
+    let mut found = false;
+    let actions = actions
+        .into_iter()
+        .map(|action| {
+            if action.type_ == "forward" {
+                found = true;
+                Action {
+                    target_group_arn: None,
+                    forward_config: Some(ForwardActionConfig {
+                        target_group_stickiness_config: action.forward_config.and_then(
+                            |forward_config| forward_config.target_group_stickiness_config,
+                        ),
+                        target_groups: Some(target_groups.clone()),
+                    }),
+                    ..action
+                }
+            } else {
+                action
+            }
+        })
+        .collect();
+    if found {
+        Some(actions)
+    } else {
+        None
+    }
+}
+
+/// What decommissioning does with the blue stack.
+#[derive(Debug, PartialEq, Eq)]
+pub enum BlueDecommission {
+    /// The plan asks to keep the blue stack.
+    Keep,
+    /// The blue stack does not exist (anymore).
+    Gone,
+    /// A previous run has started deleting the blue stack already.
+    WaitUntilDeleted,
+    /// The blue stack has to be deleted.
+    Delete,
+}
+
+impl BlueDecommission {
+    /// Decide what to do with the blue stack in the given status, `None` if it does not exist.
+    pub fn decide(delete_blue_stack: bool, status: Option<&str>) -> BlueDecommission {
+        match (delete_blue_stack, status) {
+            (false, _) => BlueDecommission::Keep,
+            (true, None) => BlueDecommission::Gone,
+            (true, Some("DELETE_IN_PROGRESS")) => BlueDecommission::WaitUntilDeleted,
+            (true, Some(_)) => BlueDecommission::Delete,
+        }
+    }
+}
+
+/// Decide what decommissioning does with the blue stack of the plan.
+///
+/// The blue stack is not deleted here, since its deletion is up to the caller.
+pub async fn blue_decommission(
+    cfn: &dyn CloudFormation,
+    plan: &Plan,
+) -> Result<BlueDecommission, Error> {
+    let status = if plan.decommission.delete_blue_stack {
+        Stack::new(&plan.blue.stack_name).status(cfn).await?
+    } else {
+        None
+    };
+    Ok(BlueDecommission::decide(
+        plan.decommission.delete_blue_stack,
+        status.as_deref(),
+    ))
+}
+
+/// Return the given target group ARN, or otherwise the ARN from the output of the stack.
+async fn target_group_arn(
+    cfn: &dyn CloudFormation,
+    stack_name: &str,
+    target_group_arn: Option<&str>,
+    target_group_output: &str,
+) -> Result<String, Error> {
+    if let Some(target_group_arn) = target_group_arn {
+        return Ok(target_group_arn.to_owned());
+    }
+    Stack::new(stack_name)
+        .get_outputs(cfn)
+        .await?
+        .swap_remove(target_group_output)
+        .and_then(|output| output.value)
+        .ok_or_else(|| {
+            Error::ResourceNotFound(format!(
+                "output {} of stack {}",
+                target_group_output, stack_name
+            ))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{self, MockDispatcher};
+    use serde_json::json;
+
+    #[test]
+    fn with_forward_weights_replaces_forward_action() {
+        let actions = vec![
+            Action {
+                type_: "authenticate-oidc".to_owned(),
+                order: Some(1),
+                ..Default::default()
+            },
+            Action {
+                type_: "forward".to_owned(),
+                order: Some(2),
+                target_group_arn: Some("blue".to_owned()),
+                ..Default::default()
+            },
+        ];
+        let target_groups = vec![
+            TargetGroupTuple {
+                target_group_arn: Some("blue".to_owned()),
+                weight: Some(25),
+            },
+            TargetGroupTuple {
+                target_group_arn: Some("green".to_owned()),
+                weight: Some(75),
+            },
+        ];
+
+        let actions = with_forward_weights(actions, target_groups.clone()).unwrap();
+        assert_eq!(actions[0].type_, "authenticate-oidc");
+        assert_eq!(actions[1].order, Some(2));
+        assert_eq!(actions[1].target_group_arn, None);
+        assert_eq!(
+            actions[1]
+                .forward_config
+                .as_ref()
+                .and_then(|forward_config| forward_config.target_groups.clone()),
+            Some(target_groups)
+        );
+
+        assert!(with_forward_weights(Vec::new(), Vec::new()).is_none());
+    }
+
+    #[test]
+    fn plan_requires_exactly_one_traffic_target() {
+        let error = Plan::from_yaml(
+            b"blue:\n  stack-name: app-blue\n\
+              green:\n  stack-name: app-green\n  template-path: template.yml\n\
+              traffic:\n  listener-arn: arn:listener\n  listener-rule-arn: arn:rule\n",
+            "plan.yml",
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            Error::InvalidConfiguration(message) if message.starts_with("plan.yml: traffic")
+        ));
+
+        let plan = Plan::from_yaml(
+            b"blue:\n  stack-name: app-blue\n\
+              green:\n  stack-name: app-green\n  template-path: template.yml\n\
+              traffic:\n  listener-arn: arn:listener\n  steps: 4\n",
+            "plan.yml",
+        )
+        .unwrap();
+        assert_eq!(plan.traffic.steps.get(), 4);
+        assert!(plan.decommission.delete_blue_stack);
+    }
+
+    fn weights(steps: Vec<TrafficStep>) -> Vec<(u32, i64, i64)> {
+        steps
+            .into_iter()
+            .map(|step| (step.step, step.blue_weight, step.green_weight))
+            .collect()
+    }
+
+    #[test]
+    fn traffic_weights_shift_evenly_to_green() {
+        assert_eq!(
+            vec![(1, 75, 25), (2, 50, 50), (3, 25, 75), (4, 0, 100)],
+            weights(traffic_weights(0, 4))
+        );
+        assert_eq!(vec![(1, 0, 100)], weights(traffic_weights(0, 1)));
+        assert_eq!(
+            vec![(1, 67, 33), (2, 34, 66), (3, 0, 100)],
+            weights(traffic_weights(0, 3))
+        );
+    }
+
+    #[test]
+    fn traffic_weights_resume_after_completed_step() {
+        assert_eq!(
+            vec![(3, 25, 75), (4, 0, 100)],
+            weights(traffic_weights(2, 4))
+        );
+        assert!(traffic_weights(4, 4).is_empty());
+    }
+
+    #[test]
+    fn phases_run_in_order() {
+        let mut state = State::default();
+        assert!(state.needs_run(Phase::Provision).unwrap());
+        assert!(matches!(
+            state.needs_run(Phase::ShiftTraffic),
+            Err(Error::DeploymentPhaseOutOfOrder(phase, required))
+                if phase == "shift-traffic" && required == "provision"
+        ));
+
+        state.completed_phases.push(Phase::Provision);
+        assert!(!state.needs_run(Phase::Provision).unwrap());
+        assert!(state.needs_run(Phase::WaitHealthy).unwrap());
+        assert!(matches!(
+            state.needs_run(Phase::Decommission),
+            Err(Error::DeploymentPhaseOutOfOrder(_, required)) if required == "wait-healthy"
+        ));
+
+        state.completed_phases.push(Phase::WaitHealthy);
+        state.completed_phases.push(Phase::ShiftTraffic);
+        assert!(state.needs_run(Phase::Decommission).unwrap());
+    }
+
+    #[test]
+    fn state_survives_resume() {
+        let state: State = serde_json::from_str(
+            r#"{"completed_phases": ["provision", "wait-healthy"], "traffic_step": 2}"#,
+        )
+        .unwrap();
+        assert!(state.is_completed(Phase::WaitHealthy));
+        assert!(state.needs_run(Phase::ShiftTraffic).unwrap());
+        assert_eq!(None, state.change_set_name);
+        assert_eq!(2, state.traffic_step);
+
+        let serialized = serde_json::to_value(&state).unwrap();
+        assert_eq!(
+            json!({"completed_phases": ["provision", "wait-healthy"], "traffic_step": 2}),
+            serialized
+        );
+    }
+
+    #[test]
+    fn phase_outcomes_omit_details_of_skipped_phases() {
+        assert_eq!(
+            json!({"phase": "provision", "skipped": true}),
+            serde_json::to_value(PhaseOutcome::skipped(Phase::Provision)).unwrap()
+        );
+        assert_eq!(
+            json!({
+                "phase": "decommission",
+                "skipped": false,
+                "details": {"stack_name": "app-blue", "deleted": true},
+            }),
+            serde_json::to_value(PhaseOutcome::completed(
+                Phase::Decommission,
+                PhaseDetails::Decommission(DecommissionedBlueStack {
+                    stack_name: "app-blue".to_owned(),
+                    deleted: true,
+                })
+            ))
+            .unwrap()
+        );
+    }
+
+    fn green_stack_plan() -> Plan {
+        Plan::from_yaml(
+            b"blue:\n  stack-name: app-blue\n\
+              green:\n  stack-name: app-green\n  template-path: template.yml\n\
+              traffic:\n  listener-arn: arn:listener\n",
+            "plan.yml",
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn resume_green_provision_recreates_missing_change_set() {
+        let plan = green_stack_plan();
+        let state = State {
+            completed_phases: Vec::new(),
+            change_set_name: Some("awsx-blue-green".to_owned()),
+            traffic_step: 0,
+        };
+
+        let dispatcher = MockDispatcher::with_status(vec![(
+            400,
+            "<ErrorResponse><Error><Type>Sender</Type><Code>ChangeSetNotFound</Code>\
+             <Message>ChangeSet [awsx-blue-green] does not exist</Message></Error>\
+             <RequestId>request-id</RequestId></ErrorResponse>",
+        )]);
+        let cfn = mock::cloudformation(&dispatcher);
+        let change_set = resume_green_provision(&cfn, &plan.green, &state)
+            .await
+            .unwrap();
+        assert!(change_set.is_none());
+        assert_eq!(vec!["DescribeChangeSet"], dispatcher.actions());
+
+        let dispatcher = MockDispatcher::new(vec![
+            "<DescribeChangeSetResponse><DescribeChangeSetResult>\
+             <Status>CREATE_COMPLETE</Status><Changes></Changes>\
+             </DescribeChangeSetResult>\
+             <ResponseMetadata><RequestId>request-id</RequestId></ResponseMetadata>\
+             </DescribeChangeSetResponse>",
+        ]);
+        let cfn = mock::cloudformation(&dispatcher);
+        let change_set = resume_green_provision(&cfn, &plan.green, &state)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!("app-green", change_set.stack_name());
+        assert_eq!("awsx-blue-green", change_set.name());
+    }
+
+    #[tokio::test]
+    async fn resume_green_provision_fails_on_other_errors() {
+        let plan = green_stack_plan();
+        let state = State {
+            completed_phases: Vec::new(),
+            change_set_name: Some("awsx-blue-green".to_owned()),
+            traffic_step: 0,
+        };
+
+        let dispatcher = MockDispatcher::with_status(vec![(
+            403,
+            "<ErrorResponse><Error><Type>Sender</Type><Code>AccessDenied</Code>\
+             <Message>denied</Message></Error></ErrorResponse>",
+        )]);
+        let cfn = mock::cloudformation(&dispatcher);
+        let error = resume_green_provision(&cfn, &plan.green, &state)
+            .await
+            .unwrap_err();
+        assert_eq!(Some("AccessDenied"), error.aws_error_code());
+
+        let dispatcher = MockDispatcher::new(Vec::<String>::new());
+        let cfn = mock::cloudformation(&dispatcher);
+        let change_set = resume_green_provision(&cfn, &plan.green, &State::default())
+            .await
+            .unwrap();
+        assert!(change_set.is_none());
+        assert!(dispatcher.actions().is_empty());
+    }
+
+    #[test]
+    fn decommission_decisions() {
+        assert_eq!(
+            BlueDecommission::Keep,
+            BlueDecommission::decide(false, Some("UPDATE_COMPLETE"))
+        );
+        assert_eq!(
+            BlueDecommission::Keep,
+            BlueDecommission::decide(false, None)
+        );
+        assert_eq!(BlueDecommission::Gone, BlueDecommission::decide(true, None));
+        assert_eq!(
+            BlueDecommission::WaitUntilDeleted,
+            BlueDecommission::decide(true, Some("DELETE_IN_PROGRESS"))
+        );
+        for status in &[
+            "UPDATE_COMPLETE",
+            "CREATE_COMPLETE",
+            "UPDATE_ROLLBACK_COMPLETE",
+        ] {
+            assert_eq!(
+                BlueDecommission::Delete,
+                BlueDecommission::decide(true, Some(status))
+            );
+        }
+    }
+}
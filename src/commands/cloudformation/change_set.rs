@@ -0,0 +1,432 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Commands operating on the change sets of stacks.
+//!
+//! The commands deploying a stack, e.g. [`prepare_stack_creation`], prepare a change set which is
+//! then created through [`create_change_set`], so that the caller can inspect the template and
+//! parameters in between, e.g. to validate the template against policies.
+//!
+//! [`prepare_stack_creation`]: ../deploy/fn.prepare_stack_creation.html
+
+use futures::TryStreamExt;
+use rusoto_cloudformation::{CloudFormation, StackEvent};
+use serde::Serialize;
+
+use crate::{
+    changeset::{ChangeSet, ChangeSetDescription},
+    commands::cloudwatch::CanaryReport,
+    error::Error,
+    parameter::Parameters,
+    s3::S3Uploader,
+    stack::{Stack, StackEventsSince},
+    template::Template,
+};
+
+/// A described change set.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DescribedChangeSet {
+    /// Logical IDs of the resources the change set will replace, including those replaced
+    /// conditionally.
+    pub replacements: Vec<String>,
+    /// The description of the change set.
+    pub change_set: ChangeSetDescription,
+}
+
+/// Describe the change set of the stack, optionally waiting until its creation has completed.
+///
+/// If `wait` is set and the creation of the change set fails, the command fails too.
+pub async fn describe_change_set(
+    cfn: &dyn CloudFormation,
+    stack_name: &str,
+    change_set_name: &str,
+    wait: bool,
+) -> Result<DescribedChangeSet, Error> {
+    let change_set = ChangeSet::new(stack_name, change_set_name);
+    let description = if wait {
+        change_set.wait_until_created(cfn).await?
+    } else {
+        change_set.describe(cfn).await?
+    };
+
+    Ok(DescribedChangeSet {
+        replacements: description
+            .replacements()
+            .iter()
+            .map(|change| change.logical_id.clone())
+            .collect(),
+        change_set: description,
+    })
+}
+
+/// A deleted change set.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DeletedChangeSet {
+    /// Name of the change set.
+    pub change_set_name: String,
+}
+
+/// Delete the change set of the stack.
+pub async fn delete_change_set(
+    cfn: &dyn CloudFormation,
+    stack_name: &str,
+    change_set_name: &str,
+) -> Result<DeletedChangeSet, Error> {
+    ChangeSet::new(stack_name, change_set_name)
+        .delete(cfn)
+        .await?;
+
+    Ok(DeletedChangeSet {
+        change_set_name: change_set_name.to_owned(),
+    })
+}
+
+/// Options for creating a change set, independent of the command preparing it.
+#[derive(Clone, Copy, Default)]
+pub struct ChangeSetOptions<'a> {
+    /// IAM role CloudFormation assumes when executing the change set.
+    pub role_arn: Option<&'a str>,
+    /// Resource types the change set is allowed to contain, e.g. `AWS::EC2::*`, or `None` to allow
+    /// all resource types.
+    pub resource_types: Option<&'a [String]>,
+    /// Description of the change set, shown alongside it e.g. in the console.
+    pub description: Option<&'a str>,
+    /// The uploader and bucket to upload the template to, otherwise it is submitted inline.
+    pub s3_upload: Option<(&'a S3Uploader, &'a str)>,
+}
+
+/// A change set prepared by one of the commands deploying a stack, to be created through
+/// [`create_change_set`].
+pub struct PreparedChangeSet<T = ()> {
+    /// Name of the stack.
+    pub stack_name: String,
+    /// Name of the change set.
+    pub change_set_name: String,
+    /// The template to apply, or `None` to keep the deployed template.
+    pub template: Option<Template>,
+    /// The parameters to apply, including the deployment-metadata parameter if it is recorded.
+    pub parameters: Parameters,
+    /// Whether the change set creates the stack.
+    pub create_stack: bool,
+    /// Problems that did not prevent preparing the change set, e.g. because its creation was
+    /// forced.
+    pub warnings: Vec<String>,
+    /// Details about the preparation specific to the command.
+    pub details: T,
+}
+
+impl<T> PreparedChangeSet<T> {
+    /// Validate the parameters against the types and constraints declared by the template.
+    ///
+    /// Values of the parameters can be part of the error, which is why this is not done while
+    /// preparing the change set, so that sensitive values can be registered for redaction first.
+    /// If `force` is set, invalid parameters only add a warning.
+    pub fn validate(&mut self, force: bool) -> Result<(), Error> {
+        let template = match &self.template {
+            Some(template) => template,
+            None => return Ok(()),
+        };
+        let report = self.parameters.validate_against(template);
+        if report.is_valid() {
+            Ok(())
+        } else if force {
+            self.warnings.push(format!(
+                "the parameters are not valid for the template ({}). The change set will be \
+                 created since it was explicitly requested!",
+                report
+            ));
+            Ok(())
+        } else {
+            Err(Error::ParameterValidationFailed(report))
+        }
+    }
+}
+
+/// A created change set.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CreatedChangeSet {
+    /// Name of the stack.
+    pub stack_name: String,
+    /// Name of the change set.
+    pub change_set_name: String,
+    /// ARN of the change set.
+    pub change_set_id: Option<String>,
+}
+
+/// Create the prepared change set.
+///
+/// This does not wait for the creation to complete, see [`describe_change_set`].
+pub async fn create_change_set<T>(
+    cfn: &dyn CloudFormation,
+    prepared: &PreparedChangeSet<T>,
+    options: &ChangeSetOptions<'_>,
+) -> Result<CreatedChangeSet, Error> {
+    let output = match &prepared.template {
+        Some(template) => {
+            template
+                .create_change_set(
+                    cfn,
+                    &prepared.change_set_name,
+                    &prepared.stack_name,
+                    &prepared.parameters,
+                    options.role_arn,
+                    options.resource_types,
+                    options.description,
+                    options.s3_upload,
+                    prepared.create_stack,
+                )
+                .await?
+        }
+        None => {
+            Stack::new(&prepared.stack_name)
+                .create_change_set(
+                    cfn,
+                    &prepared.change_set_name,
+                    options.role_arn,
+                    options.resource_types,
+                    options.description,
+                    &prepared.parameters,
+                )
+                .await?
+        }
+    };
+
+    Ok(CreatedChangeSet {
+        stack_name: prepared.stack_name.clone(),
+        change_set_name: prepared.change_set_name.clone(),
+        change_set_id: output.id,
+    })
+}
+
+/// A change set whose execution was started through [`start_change_set_execution`].
+pub struct StartedExecution {
+    change_set: ChangeSet,
+    /// The newest stack event before the execution, if the events are followed.
+    since: Option<StackEventsSince>,
+}
+
+/// Start executing the change set of the stack, without waiting for the execution to complete.
+///
+/// If `follow_events` is set, the newest stack event before the execution is remembered, so that
+/// [`wait_for_change_set_execution`] only reports the events of this execution.
+pub async fn start_change_set_execution(
+    cfn: &dyn CloudFormation,
+    stack_name: &str,
+    change_set_name: &str,
+    follow_events: bool,
+) -> Result<StartedExecution, Error> {
+    let change_set = ChangeSet::new(stack_name, change_set_name);
+    let since = if follow_events {
+        Box::pin(Stack::new(stack_name).events(cfn, None))
+            .try_next()
+            .await?
+            .map(|event| StackEventsSince::EventId(event.event_id))
+    } else {
+        None
+    };
+    change_set.execute(cfn).await?;
+
+    Ok(StartedExecution { change_set, since })
+}
+
+/// An executed change set.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExecutedChangeSet {
+    /// Name of the stack.
+    pub stack_name: String,
+    /// Name of the change set.
+    pub change_set_name: String,
+    /// Status of the stack once the execution has completed.
+    pub stack_status: String,
+}
+
+/// Wait until the execution of the change set has completed, passing every stack event of the
+/// execution to `on_event` as it happens, oldest first.
+///
+/// If the stack operation fails or is rolled back, the command fails with
+/// [`Error::StackOperationFailed`].
+///
+/// [`Error::StackOperationFailed`]: ../../../error/enum.Error.html#variant.StackOperationFailed
+pub async fn wait_for_change_set_execution<F: FnMut(&StackEvent)>(
+    cfn: &dyn CloudFormation,
+    execution: &StartedExecution,
+    on_event: F,
+) -> Result<ExecutedChangeSet, Error> {
+    let change_set = &execution.change_set;
+    let stack_status = change_set
+        .wait_until_executed_following_events(cfn, execution.since.clone(), on_event)
+        .await?;
+
+    Ok(ExecutedChangeSet {
+        stack_name: change_set.stack_name().to_owned(),
+        change_set_name: change_set.name().to_owned(),
+        stack_status,
+    })
+}
+
+/// The outcome of a command deploying a stack through a change set.
+///
+/// Depending on how the change set is handled once it is created, the outcome contains its
+/// description or the outcome of its execution.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangeSetOutcome<T = ()> {
+    /// Name of the stack.
+    pub stack_name: String,
+    /// Name of the change set.
+    pub change_set_name: String,
+    /// ARN of the change set, if it was created by the command.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change_set_id: Option<String>,
+    /// Details specific to the command, whose fields are serialized next to the others.
+    #[serde(flatten)]
+    pub details: T,
+    /// The description of the change set, if its creation was waited for.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change_set: Option<ChangeSetDescription>,
+    /// The outcome of the execution, if the change set was executed.
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub execution: Option<ChangeSetExecution>,
+}
+
+impl<T> ChangeSetOutcome<T> {
+    /// Create the outcome for the created change set.
+    pub fn created(created: CreatedChangeSet, details: T) -> ChangeSetOutcome<T> {
+        ChangeSetOutcome {
+            stack_name: created.stack_name,
+            change_set_name: created.change_set_name,
+            change_set_id: created.change_set_id,
+            details,
+            change_set: None,
+            execution: None,
+        }
+    }
+}
+
+impl ChangeSetOutcome {
+    /// Create the outcome for an existing change set of the stack.
+    pub fn existing(stack_name: &str, change_set_name: &str) -> ChangeSetOutcome {
+        ChangeSetOutcome {
+            stack_name: stack_name.to_owned(),
+            change_set_name: change_set_name.to_owned(),
+            change_set_id: None,
+            details: (),
+            change_set: None,
+            execution: None,
+        }
+    }
+}
+
+/// The outcome of executing a change set.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ChangeSetExecution {
+    /// Status of the stack once the execution has completed, if it was waited for.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stack_status: Option<String>,
+    /// The report of the canary monitoring the alarms after the execution, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub canary: Option<CanaryReport>,
+    /// The rollback after the canary failed, if the stack was rolled back.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rollback: Option<RollbackReport>,
+}
+
+/// The outcome of rolling a stack back after a failed canary.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RollbackReport {
+    /// Name of the change set rolling the stack back.
+    pub change_set_name: String,
+    /// Status of the stack after the rollback, or `None` if there was nothing to roll back.
+    pub stack_status: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{self, MockDispatcher};
+
+    fn resource_change(logical_id: &str, replacement: &str) -> String {
+        format!(
+            "<member><Type>Resource</Type><ResourceChange><Action>Modify</Action>\
+             <LogicalResourceId>{}</LogicalResourceId>\
+             <ResourceType>AWS::EC2::Instance</ResourceType>\
+             <Replacement>{}</Replacement></ResourceChange></member>",
+            logical_id, replacement
+        )
+    }
+
+    #[tokio::test]
+    async fn described_change_set_lists_replacements() {
+        let dispatcher = MockDispatcher::new(vec![format!(
+            "<DescribeChangeSetResponse><DescribeChangeSetResult>\
+             <Status>CREATE_COMPLETE</Status><Changes>{}{}{}</Changes>\
+             </DescribeChangeSetResult>\
+             <ResponseMetadata><RequestId>request-id</RequestId></ResponseMetadata>\
+             </DescribeChangeSetResponse>",
+            resource_change("Unchanged", "False"),
+            resource_change("Replaced", "True"),
+            resource_change("Maybe", "Conditional"),
+        )]);
+        let cfn = mock::cloudformation(&dispatcher);
+
+        let described = describe_change_set(&cfn, "app", "release", false)
+            .await
+            .unwrap();
+        assert_eq!(vec!["Replaced", "Maybe"], described.replacements);
+        assert_eq!(3, described.change_set.changes.len());
+        assert_eq!(vec!["DescribeChangeSet"], dispatcher.actions());
+    }
+
+    #[test]
+    fn change_set_outcome_flattens_details_and_execution() {
+        #[derive(Serialize)]
+        struct Details {
+            source_stack_name: &'static str,
+        }
+
+        let created = ChangeSetOutcome::created(
+            CreatedChangeSet {
+                stack_name: "app".to_owned(),
+                change_set_name: "release".to_owned(),
+                change_set_id: None,
+            },
+            Details {
+                source_stack_name: "template",
+            },
+        );
+        assert_eq!(
+            serde_json::json!({
+                "stack_name": "app",
+                "change_set_name": "release",
+                "source_stack_name": "template",
+            }),
+            serde_json::to_value(&created).unwrap()
+        );
+
+        let mut executed = ChangeSetOutcome::existing("app", "release");
+        executed.execution = Some(ChangeSetExecution {
+            stack_status: Some("UPDATE_COMPLETE".to_owned()),
+            ..Default::default()
+        });
+        assert_eq!(
+            serde_json::json!({
+                "stack_name": "app",
+                "change_set_name": "release",
+                "stack_status": "UPDATE_COMPLETE",
+            }),
+            serde_json::to_value(&executed).unwrap()
+        );
+    }
+}
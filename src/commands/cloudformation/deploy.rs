@@ -0,0 +1,890 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Commands preparing the change sets that deploy a template or parameters to a stack.
+//!
+//! Every command returns a [`PreparedChangeSet`], which is created through [`create_change_set`].
+//! Unless requested otherwise, the deployment metadata is recorded in the parameters of the change
+//! set, see [`awsx::deployment`].
+//!
+//! [`PreparedChangeSet`]: ../change_set/struct.PreparedChangeSet.html
+//! [`create_change_set`]: ../change_set/fn.create_change_set.html
+//! [`awsx::deployment`]: ../../../deployment/index.html
+
+use itertools::Itertools;
+use rusoto_cloudformation::CloudFormation;
+use serde::Serialize;
+use std::convert::TryFrom;
+
+use super::change_set::PreparedChangeSet;
+use crate::{
+    deployment::{
+        generate_deployment_metadata, verify_changes_compatible, DeploymentMetadata,
+        DeploymentMetadataPromotion,
+    },
+    error::Error,
+    parameter::{Parameter, Parameters, NO_ECHO_PARAMETER_VALUE},
+    s3::S3Uploader,
+    stack::Stack,
+    template::Template,
+};
+
+/// How the deployment metadata is recorded in the parameters of a change set.
+#[derive(Clone, Copy)]
+pub struct MetadataOptions<'a> {
+    /// Key of the deployment-metadata parameter.
+    pub parameter_key: &'a str,
+    /// Whether to record the deployment metadata, otherwise the parameter is left as it is.
+    pub record: bool,
+    /// Path within the git repository the deployment is made from, to record the deployed commit.
+    pub git_path: Option<&'a str>,
+    /// Whether the value of the parameter with the key is sensitive, so that it is withheld from
+    /// the deployment metadata like the value of a `NoEcho` parameter.
+    pub is_sensitive: &'a dyn Fn(&str) -> bool,
+}
+
+impl MetadataOptions<'_> {
+    /// Record what the deployment applies in the metadata, and set the deployment-metadata
+    /// parameter to it.
+    ///
+    /// The `template` is `None` if the deployed template is kept, and the `deployed_parameters` are
+    /// `None` if the stack does not exist yet.
+    pub(crate) fn record_applied(
+        &self,
+        mut metadata: DeploymentMetadata,
+        template: Option<&Template>,
+        s3_upload: Option<(&S3Uploader, &str)>,
+        parameters: &mut Parameters,
+        deployed_parameters: Option<&Parameters>,
+        warnings: &mut Vec<String>,
+    ) -> Result<(), Error> {
+        let template = match template {
+            Some(template) => Some((template, uploaded_template_url(template, s3_upload)?)),
+            None => None,
+        };
+        metadata.record_applied(
+            template,
+            parameters,
+            deployed_parameters,
+            self.parameter_key,
+            self.is_sensitive,
+        );
+        if metadata.applied.is_some() && !metadata.fits_parameter_value() {
+            warnings.push(format!(
+                "the template and parameters of the deployment are too large to be recorded in \
+                 the deployment-metadata parameter '{}', the deployment can not be rolled back to",
+                self.parameter_key
+            ));
+        }
+        parameters.insert(
+            self.parameter_key.to_owned(),
+            metadata.into_parameter(self.parameter_key),
+        );
+        Ok(())
+    }
+}
+
+/// Return the URL the template is uploaded to when creating a change set, or `None` if it is
+/// submitted inline.
+fn uploaded_template_url(
+    template: &Template,
+    s3_upload: Option<(&S3Uploader, &str)>,
+) -> Result<Option<String>, Error> {
+    s3_upload
+        .map(|(s3, bucket_name)| {
+            Ok(s3.object_url(bucket_name, &template.s3_key(s3.checksum_algorithm())?))
+        })
+        .transpose()
+}
+
+/// Return the keys of all parameters whose values are masked, since they are declared as `NoEcho`.
+///
+/// The values of these parameters can not be retrieved from a stack, so they can not be copied.
+fn masked_parameters(parameters: &Parameters) -> Vec<&str> {
+    parameters
+        .values()
+        .filter(|parameter| {
+            matches!(parameter, Parameter::WithValue { value, .. } if value == NO_ECHO_PARAMETER_VALUE)
+        })
+        .map(Parameter::key)
+        .collect()
+}
+
+/// Retrieve the deployed template of the stack, applying the minification setting.
+async fn deployed_template(
+    cfn: &dyn CloudFormation,
+    stack: &Stack,
+    minify_template: bool,
+) -> Result<Template, Error> {
+    Ok(stack
+        .get_template(cfn)
+        .await?
+        .with_minification(minify_template))
+}
+
+/// The input of [`prepare_stack_creation`].
+pub struct StackCreation<'a> {
+    /// Name of the stack to create.
+    pub stack_name: &'a str,
+    /// Name of the change set creating the stack.
+    pub change_set_name: &'a str,
+    /// The template of the stack.
+    pub template: Template,
+    /// The parameters provided for the template, e.g. through a parameter file.
+    pub parameters: Parameters,
+    /// Values for parameters that were not provided, which take precedence over the defaults
+    /// declared by the template.
+    pub parameter_defaults: Option<Parameters>,
+    /// Prepare the change set even if not all required parameters were provided, or if they are
+    /// invalid, adding a warning instead of failing.
+    pub force_create: bool,
+}
+
+/// Prepare the change set creating a new stack from the template.
+///
+/// All parameters required by the template have to be provided, either directly, through the
+/// parameter defaults or through the defaults declared by the template.
+pub fn prepare_stack_creation(
+    input: StackCreation<'_>,
+    metadata: &MetadataOptions<'_>,
+    s3_upload: Option<(&S3Uploader, &str)>,
+) -> Result<PreparedChangeSet, Error> {
+    let template = input.template;
+    let mut warnings = Vec::new();
+
+    // Apply the provided parameters, then the parameter defaults, and afterwards the defaults
+    // defined in the template itself.
+    let mut parameters = template.get_parameters_as_previous_value();
+    parameters.update(input.parameters);
+    if let Some(parameter_defaults) = input.parameter_defaults {
+        parameters.apply_defaults(parameter_defaults);
+    }
+    parameters.apply_defaults(template.get_parameters().to_owned());
+
+    if metadata.record {
+        let deployment_metadata = generate_deployment_metadata(None, metadata.git_path)?;
+        metadata.record_applied(
+            deployment_metadata,
+            Some(&template),
+            s3_upload,
+            &mut parameters,
+            None,
+            &mut warnings,
+        )?;
+    }
+
+    // We need to ensure that the user has provided all parameters required by the template.
+    let missing_parameters = parameters
+        .iter()
+        .filter(|(_, parameter)| parameter.is_previous_value())
+        .map(|(key, _)| key)
+        .collect::<Vec<_>>();
+    if !missing_parameters.is_empty() {
+        if input.force_create {
+            warnings.push(format!(
+                "some required parameters ({}) have not been supplied. The change set will be \
+                 created since it was explicitly requested!",
+                missing_parameters.iter().join(", ")
+            ));
+        } else {
+            return Err(Error::InvalidParameters(format!(
+                "not all required parameters ({}) were provided",
+                missing_parameters.iter().join(", ")
+            )));
+        }
+    }
+
+    Ok(PreparedChangeSet {
+        stack_name: input.stack_name.to_owned(),
+        change_set_name: input.change_set_name.to_owned(),
+        template: Some(template),
+        parameters,
+        create_stack: true,
+        warnings,
+        details: (),
+    })
+}
+
+/// The input of [`prepare_template_update`].
+pub struct TemplateUpdate<'a> {
+    /// Name of the stack to update.
+    pub stack_name: &'a str,
+    /// Name of the change set updating the stack.
+    pub change_set_name: &'a str,
+    /// The new template of the stack.
+    pub template: Template,
+    /// The parameters provided for the template, which should only be the parameters newly added
+    /// to the template.
+    pub parameters: Parameters,
+    /// Values for newly added parameters that were not provided, which take precedence over the
+    /// defaults declared by the template.
+    pub parameter_defaults: Option<Parameters>,
+    /// Only apply the provided parameters that were newly added to the template, ignoring all
+    /// others.
+    pub only_new_parameters: bool,
+    /// Allow the provided parameters to change the values of parameters already defined on the
+    /// stack.
+    pub allow_parameter_overrides: bool,
+    /// Prepare the change set even if the provided parameters do not match the newly added
+    /// parameters, or if the template is not a descendant of the deployed template, adding a
+    /// warning instead of failing.
+    pub force_create: bool,
+}
+
+/// Parameters already defined on the stack whose values a change set changes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct OverriddenParameters {
+    /// Keys of the parameters.
+    pub overridden_parameters: Vec<String>,
+}
+
+/// Prepare the change set updating the stack to the template, keeping the values of all
+/// parameters already defined on the stack.
+///
+/// Exactly the parameters newly added to the template have to be provided, unless
+/// `allow_parameter_overrides` is set, and if the stack records the deployed commit, the template
+/// has to be from a descendant of that commit.
+///
+/// Returns `None` if neither the template nor the parameters differ from what is deployed, in
+/// which case the change set would not contain any changes. The deployment-metadata parameter is
+/// ignored for this comparison, since it changes with every deployment.
+pub async fn prepare_template_update(
+    cfn: &dyn CloudFormation,
+    input: TemplateUpdate<'_>,
+    metadata: &MetadataOptions<'_>,
+    s3_upload: Option<(&S3Uploader, &str)>,
+) -> Result<Option<PreparedChangeSet<OverriddenParameters>>, Error> {
+    let template = input.template;
+    let stack = Stack::new(input.stack_name);
+    let mut warnings = Vec::new();
+
+    // Retrieve the parameters defined on the template, as well as the current parameters defined on
+    // the stack.
+    let mut template_parameters = template.get_parameters_as_previous_value();
+    let stack_parameters = stack.get_parameters_as_previous_value(cfn).await?;
+
+    // Identify newly added parameters, which are parameters defined on the template, but not on the
+    // stack. (Parameters that are defined on the stack but not on the template, so the other way
+    // around, are simply ignored, since they do not need to be set and will simply be removed
+    // once the change-set is deployed.)
+    let new_parameters = template_parameters.clone() - &stack_parameters;
+    // Retrieve the template default values for the newly defined parameters.
+    let template_defaults = template.get_parameters().to_owned() - &stack_parameters;
+
+    // We track all provided parameters in this variable.
+    let mut provided_parameters = new_parameters.clone();
+    provided_parameters.merge(input.parameters);
+    if let Some(parameter_defaults) = input.parameter_defaults {
+        provided_parameters.apply_defaults(parameter_defaults);
+    }
+    // Apply defaults from template parameters. This ensures that any defaults specified in the
+    // template itself will be honored and passed onto CloudFormation.
+    provided_parameters.apply_defaults(template_defaults);
+
+    // If requested, remove any parameters that are not new (i.e. don't accidentally overwrite a
+    // parameter).
+    if input.only_new_parameters {
+        provided_parameters = provided_parameters
+            .values()
+            .filter(|parameter| new_parameters.contains_key(parameter.key()))
+            .collect::<Vec<_>>()
+            .into();
+    }
+
+    // Parameters that change the value of a parameter already defined on the stack, which is only
+    // allowed if explicitly requested.
+    let overridden_parameters = if input.allow_parameter_overrides {
+        provided_parameters
+            .keys()
+            .filter(|key| {
+                stack_parameters.contains_key(*key) && template_parameters.contains_key(*key)
+            })
+            .cloned()
+            .collect::<Vec<_>>()
+    } else {
+        Vec::new()
+    };
+
+    // We need to ensure that the user has provided exactly the parameters that have been added,
+    // apart from the parameters that are explicitly allowed to be overridden.
+    if !new_parameters.keys().sorted().eq(provided_parameters
+        .keys()
+        .filter(|key| !overridden_parameters.contains(*key))
+        .sorted())
+    {
+        if input.force_create {
+            warnings.push(format!(
+                "all newly required parameters ({}) might not have been supplied, or some old or \
+                 non-existent parameters were specified. The change set will be created since it \
+                 was explicitly requested!",
+                new_parameters.keys().join(", ")
+            ));
+        } else {
+            return Err(Error::InvalidParameters(
+                if input.allow_parameter_overrides {
+                    format!(
+                    "all newly required parameters have to be provided ({}), and no non-existent \
+                     parameters can be specified",
+                    new_parameters.keys().join(", ")
+                )
+                } else {
+                    format!(
+                        "all newly required parameters have to be provided ({}), and no old or \
+                     non-existent parameters can be specified (use --allow-parameter-overrides to \
+                     change old parameters)",
+                        new_parameters.keys().join(", ")
+                    )
+                },
+            ));
+        }
+    }
+
+    // Update the template parameters with the provided parameters.
+    template_parameters.update(provided_parameters);
+
+    // If neither the template nor the parameters differ from what is deployed, the change set would
+    // fail for not containing any changes, so we skip creating it.
+    let without_metadata = |parameters: &Parameters| -> Parameters {
+        parameters
+            .values()
+            .filter(|parameter| parameter.key() != metadata.parameter_key)
+            .collect::<Vec<_>>()
+            .into()
+    };
+    let deployed_parameters = stack.get_parameters(cfn).await?;
+    if stack.get_template(cfn).await?.checksum_md5hex()? == template.checksum_md5hex()?
+        && without_metadata(&deployed_parameters)
+            .loosely_equal(&without_metadata(&template_parameters))
+    {
+        return Ok(None);
+    }
+
+    if metadata.record {
+        if template_parameters.contains_key(metadata.parameter_key) {
+            let previous_metadata_parameter =
+                deployed_parameters.get(metadata.parameter_key).cloned();
+            let previous_metadata = previous_metadata_parameter
+                .clone()
+                .and_then(|parameter| DeploymentMetadata::try_from(parameter).ok());
+            let deployment_metadata =
+                generate_deployment_metadata(previous_metadata_parameter, metadata.git_path)?;
+
+            // Verify that the changes are compatible
+            if let (Some(previous_metadata), Some(git_path)) =
+                (previous_metadata, metadata.git_path)
+            {
+                if !verify_changes_compatible(&previous_metadata, &deployment_metadata, git_path)? {
+                    if input.force_create {
+                        warnings.push(
+                            "the changes you are trying to deploy are not a direct descendant of \
+                             the currently deployed changes. The created change-set might \
+                             overwrite and thus destroy the previously deployed changes."
+                                .to_owned(),
+                        );
+                    } else {
+                        return Err(Error::InvalidTemplate(
+                            "the template provided is not a direct descendant of the currently \
+                             deployed template, creating a changeset might overwrite previously \
+                             deployed changes"
+                                .to_owned(),
+                        ));
+                    }
+                }
+            }
+
+            metadata.record_applied(
+                deployment_metadata,
+                Some(&template),
+                s3_upload,
+                &mut template_parameters,
+                Some(&deployed_parameters),
+                &mut warnings,
+            )?;
+        } else {
+            warnings.push(format!(
+                "an update to the deployment-metadata parameter '{}' was requested, but the \
+                 template that should be deployed does not have this parameter. The change-set \
+                 will be created, although without any metadata.",
+                metadata.parameter_key,
+            ));
+        }
+    }
+
+    Ok(Some(PreparedChangeSet {
+        stack_name: input.stack_name.to_owned(),
+        change_set_name: input.change_set_name.to_owned(),
+        template: Some(template),
+        parameters: template_parameters,
+        create_stack: false,
+        warnings,
+        details: OverriddenParameters {
+            overridden_parameters,
+        },
+    }))
+}
+
+/// Retrieve the current values of the parameters with the keys from the stack, e.g. to copy them
+/// to another stack.
+///
+/// Fails if a parameter does not exist on the stack, or if its value is masked since it is
+/// declared as `NoEcho`.
+pub async fn copy_stack_parameters(
+    cfn: &dyn CloudFormation,
+    stack_name: &str,
+    keys: &[String],
+) -> Result<Parameters, Error> {
+    let stack_parameters = Stack::new(stack_name).get_parameters(cfn).await?;
+    Ok(keys
+        .iter()
+        .map(|key| match stack_parameters.get(key) {
+            Some(Parameter::WithValue { value, .. }) if value == NO_ECHO_PARAMETER_VALUE => {
+                Err(Error::InvalidParameters(format!(
+                    "the value of parameter {} on stack {} is masked (NoEcho) and can not be \
+                     copied",
+                    key, stack_name
+                )))
+            }
+            Some(parameter) => Ok(parameter.clone()),
+            None => Err(Error::InvalidParameters(format!(
+                "parameter {} does not exist on stack {}",
+                key, stack_name
+            ))),
+        })
+        .collect::<Result<Vec<_>, _>>()?
+        .into())
+}
+
+/// Prepare the change set overriding the parameters of the stack, keeping the deployed template
+/// and the values of all other parameters.
+///
+/// Returns `None` if there are no parameters to apply.
+pub async fn prepare_parameter_overrides(
+    cfn: &dyn CloudFormation,
+    stack_name: &str,
+    change_set_name: &str,
+    parameters: Parameters,
+    metadata: &MetadataOptions<'_>,
+) -> Result<Option<PreparedChangeSet>, Error> {
+    let stack = Stack::new(stack_name);
+    let mut warnings = Vec::new();
+
+    // Retrieve the parameters currently set on the stack, which keep their previous value unless
+    // they are overridden.
+    let mut stack_parameters = stack.get_parameters_as_previous_value(cfn).await?;
+    stack_parameters.update(parameters);
+    if stack_parameters.is_empty() {
+        return Ok(None);
+    }
+
+    if metadata.record {
+        let deployed_parameters = stack.get_parameters(cfn).await?;
+        if let Some(previous_metadata_parameter) =
+            deployed_parameters.get(metadata.parameter_key).cloned()
+        {
+            // The deployed template is kept, so only the parameters are recorded anew.
+            let deployment_metadata =
+                generate_deployment_metadata(Some(previous_metadata_parameter), None)?;
+            metadata.record_applied(
+                deployment_metadata,
+                None,
+                None,
+                &mut stack_parameters,
+                Some(&deployed_parameters),
+                &mut warnings,
+            )?;
+        } else {
+            warnings.push(format!(
+                "an update to the deployment-metadata parameter '{}' was requested, but the stack \
+                 does not have this parameter. The change-set will be created, although without \
+                 any metadata.",
+                metadata.parameter_key,
+            ));
+        }
+    }
+
+    Ok(Some(PreparedChangeSet {
+        stack_name: stack_name.to_owned(),
+        change_set_name: change_set_name.to_owned(),
+        template: None,
+        parameters: stack_parameters,
+        create_stack: false,
+        warnings,
+        details: (),
+    }))
+}
+
+/// The input of [`prepare_stack_clone`].
+pub struct StackClone<'a> {
+    /// Name of the stack to clone.
+    pub source_stack_name: &'a str,
+    /// Name of the stack to create.
+    pub stack_name: &'a str,
+    /// Name of the change set creating the stack.
+    pub change_set_name: &'a str,
+    /// Parameters to change compared to the source stack.
+    pub parameters: Parameters,
+    /// Whether to minify the template, see [`Template::with_minification`].
+    ///
+    /// [`Template::with_minification`]: ../../../template/struct.Template.html#method.with_minification
+    pub minify_template: bool,
+}
+
+/// The stack a stack was cloned from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ClonedFrom {
+    /// Name of the stack that was cloned.
+    pub source_stack_name: String,
+}
+
+/// Prepare the change set creating a new stack from the deployed template and parameters of the
+/// source stack.
+///
+/// The values of `NoEcho` parameters can not be retrieved from the source stack, so they have to be
+/// provided.
+pub async fn prepare_stack_clone(
+    cfn: &dyn CloudFormation,
+    input: StackClone<'_>,
+    metadata: &MetadataOptions<'_>,
+    s3_upload: Option<(&S3Uploader, &str)>,
+) -> Result<PreparedChangeSet<ClonedFrom>, Error> {
+    let mut warnings = Vec::new();
+
+    // Retrieve the deployed template and parameters of the source stack.
+    let source_stack = Stack::new(input.source_stack_name);
+    let template = deployed_template(cfn, &source_stack, input.minify_template).await?;
+    let source_parameters = source_stack.get_parameters(cfn).await?;
+
+    // Start from the template defaults, take over the values of the source stack and apply the
+    // requested changes.
+    let mut parameters = template.get_parameters().to_owned();
+    parameters.update(&source_parameters);
+    parameters.update(input.parameters);
+
+    let masked_parameters = masked_parameters(&parameters);
+    if !masked_parameters.is_empty() {
+        return Err(Error::InvalidParameters(format!(
+            "the values of the parameters {} can not be retrieved from the source stack and have \
+             to be provided",
+            masked_parameters.iter().join(", ")
+        )));
+    }
+
+    // Carry forward the deployment metadata of the source stack.
+    if metadata.record && parameters.contains_key(metadata.parameter_key) {
+        let deployment_metadata = generate_deployment_metadata(
+            source_parameters.get(metadata.parameter_key).cloned(),
+            None,
+        )?;
+        metadata.record_applied(
+            deployment_metadata,
+            Some(&template),
+            s3_upload,
+            &mut parameters,
+            None,
+            &mut warnings,
+        )?;
+    }
+
+    // Since the stack does not exist yet, every parameter needs a value.
+    let parameters: Parameters = parameters
+        .values()
+        .filter(|parameter| !parameter.is_previous_value())
+        .collect::<Vec<_>>()
+        .into();
+
+    Ok(PreparedChangeSet {
+        stack_name: input.stack_name.to_owned(),
+        change_set_name: input.change_set_name.to_owned(),
+        template: Some(template),
+        parameters,
+        create_stack: true,
+        warnings,
+        details: ClonedFrom {
+            source_stack_name: input.source_stack_name.to_owned(),
+        },
+    })
+}
+
+/// The input of [`prepare_stack_promotion`].
+pub struct StackPromotion<'a> {
+    /// Name of the stack to promote from.
+    pub source_stack_name: &'a str,
+    /// Name of the region of the source stack, which is recorded in the deployment metadata.
+    pub source_region: &'a str,
+    /// Name of the stack to promote to.
+    pub stack_name: &'a str,
+    /// Name of the change set updating the stack.
+    pub change_set_name: &'a str,
+    /// Keys of the parameters whose values are promoted from the source stack.
+    pub promote_parameters: &'a [String],
+    /// Parameters to change, overriding the values promoted from the source stack.
+    pub parameters: Parameters,
+    /// Whether to minify the template, see [`Template::with_minification`].
+    ///
+    /// [`Template::with_minification`]: ../../../template/struct.Template.html#method.with_minification
+    pub minify_template: bool,
+}
+
+/// The stack a stack was promoted from, and the promoted parameters.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PromotedFrom {
+    /// Name of the stack that was promoted from.
+    pub source_stack_name: String,
+    /// The parameters whose values were promoted, as they are applied.
+    pub promoted_parameters: Parameters,
+}
+
+/// Prepare the change set updating the stack to the deployed template of the source stack, which
+/// might reside in another account or region.
+///
+/// Parameters already defined on the stack keep their values, unless they are promoted, whereas
+/// parameters newly added to the template take the value of the source stack, falling back to the
+/// default of the template.
+pub async fn prepare_stack_promotion(
+    source_cfn: &dyn CloudFormation,
+    cfn: &dyn CloudFormation,
+    input: StackPromotion<'_>,
+    metadata: &MetadataOptions<'_>,
+    s3_upload: Option<(&S3Uploader, &str)>,
+) -> Result<PreparedChangeSet<PromotedFrom>, Error> {
+    let mut warnings = Vec::new();
+
+    // Retrieve the deployed template and parameters of the source stack.
+    let source_stack = Stack::new(input.source_stack_name);
+    let template = deployed_template(source_cfn, &source_stack, input.minify_template).await?;
+    let source_parameters = source_stack.get_parameters(source_cfn).await?;
+
+    let stack_parameters = Stack::new(input.stack_name)
+        .get_parameters_as_previous_value(cfn)
+        .await?;
+    let new_parameters = (template.get_parameters().to_owned() - &stack_parameters)
+        .updated(source_parameters.clone() - &stack_parameters);
+    let mut parameters = template.get_parameters_as_previous_value();
+    parameters.update(&new_parameters);
+
+    // Promote the chosen parameters, and apply the requested changes.
+    let promoted_parameters = input
+        .promote_parameters
+        .iter()
+        .map(|key| {
+            source_parameters.get(key).cloned().ok_or_else(|| {
+                Error::InvalidParameters(format!(
+                    "parameter {} does not exist on stack {}",
+                    key, input.source_stack_name
+                ))
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    parameters.update(promoted_parameters);
+    parameters.update(input.parameters);
+
+    let masked_parameters = masked_parameters(&parameters);
+    if !masked_parameters.is_empty() {
+        return Err(Error::InvalidParameters(format!(
+            "the values of the parameters {} can not be retrieved from the source stack and have \
+             to be provided",
+            masked_parameters.iter().join(", ")
+        )));
+    }
+    let missing_parameters = new_parameters
+        .keys()
+        .filter(|key| {
+            parameters
+                .get(key.as_str())
+                .map(Parameter::is_previous_value)
+                .unwrap_or(true)
+        })
+        .collect::<Vec<_>>();
+    if !missing_parameters.is_empty() {
+        return Err(Error::InvalidParameters(format!(
+            "the newly required parameters {} have to be provided",
+            missing_parameters.iter().join(", ")
+        )));
+    }
+
+    // Carry forward the deployment metadata of the source stack, recording where it was promoted
+    // from.
+    if metadata.record && parameters.contains_key(metadata.parameter_key) {
+        let source_metadata_parameter = source_parameters.get(metadata.parameter_key).cloned();
+        let source_when = source_metadata_parameter
+            .clone()
+            .and_then(|parameter| DeploymentMetadata::try_from(parameter).ok())
+            .map(|metadata| metadata.when)
+            .unwrap_or_default();
+        let mut deployment_metadata =
+            generate_deployment_metadata(source_metadata_parameter, None)?;
+        deployment_metadata.promoted_from = Some(DeploymentMetadataPromotion {
+            stack_name: input.source_stack_name.to_owned(),
+            region: input.source_region.to_owned(),
+            when: source_when,
+        });
+        metadata.record_applied(
+            deployment_metadata,
+            Some(&template),
+            s3_upload,
+            &mut parameters,
+            None,
+            &mut warnings,
+        )?;
+    }
+
+    let promoted_parameters = input
+        .promote_parameters
+        .iter()
+        .filter_map(|key| parameters.get(key))
+        .collect::<Vec<_>>()
+        .into();
+    Ok(PreparedChangeSet {
+        stack_name: input.stack_name.to_owned(),
+        change_set_name: input.change_set_name.to_owned(),
+        template: Some(template),
+        parameters,
+        create_stack: false,
+        warnings,
+        details: PromotedFrom {
+            source_stack_name: input.source_stack_name.to_owned(),
+            promoted_parameters,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{self, MockDispatcher};
+
+    fn parameter(key: &str, value: &str) -> String {
+        format!(
+            "<member><ParameterKey>{}</ParameterKey><ParameterValue>{}</ParameterValue></member>",
+            key, value
+        )
+    }
+
+    fn template() -> Template {
+        Template::from_bytes(
+            "Parameters:
+  Name:
+    Type: String
+  Size:
+    Type: Number
+    Default: 1
+  Secret:
+    Type: String
+    NoEcho: true
+Resources: {}
+",
+        )
+        .unwrap()
+    }
+
+    fn without_metadata() -> MetadataOptions<'static> {
+        MetadataOptions {
+            parameter_key: "DeploymentMetadata",
+            record: false,
+            git_path: None,
+            is_sensitive: &|_| false,
+        }
+    }
+
+    fn stack_creation(
+        parameter_defaults: Option<Parameters>,
+        force_create: bool,
+    ) -> StackCreation<'static> {
+        StackCreation {
+            stack_name: "app",
+            change_set_name: "release",
+            template: template(),
+            parameters: Parameters::new(vec![Parameter::with_value("Name", "app")]),
+            parameter_defaults,
+            force_create,
+        }
+    }
+
+    #[test]
+    fn stack_creation_applies_defaults() {
+        let prepared = prepare_stack_creation(
+            stack_creation(
+                Some(Parameters::new(vec![
+                    Parameter::with_value("Name", "default"),
+                    Parameter::with_value("Secret", "s3cr3t"),
+                ])),
+                false,
+            ),
+            &without_metadata(),
+            None,
+        )
+        .unwrap();
+
+        assert!(prepared.create_stack);
+        assert!(prepared.warnings.is_empty());
+        assert_eq!(
+            Some(&Parameter::with_value("Name", "app")),
+            prepared.parameters.get("Name")
+        );
+        assert_eq!(
+            Some(&Parameter::with_value("Size", "1")),
+            prepared.parameters.get("Size")
+        );
+        assert_eq!(
+            Some(&Parameter::with_value("Secret", "s3cr3t")),
+            prepared.parameters.get("Secret")
+        );
+    }
+
+    #[test]
+    fn stack_creation_requires_all_parameters_unless_forced() {
+        match prepare_stack_creation(stack_creation(None, false), &without_metadata(), None) {
+            Err(Error::InvalidParameters(message)) => assert!(message.contains("Secret")),
+            _ => panic!("expected missing parameters to fail the preparation"),
+        }
+
+        let prepared =
+            prepare_stack_creation(stack_creation(None, true), &without_metadata(), None).unwrap();
+        assert_eq!(1, prepared.warnings.len());
+        assert!(prepared.warnings[0].contains("Secret"));
+    }
+
+    #[tokio::test]
+    async fn copying_stack_parameters_refuses_masked_values() {
+        let response = format!(
+            "<DescribeStacksResponse><DescribeStacksResult><Stacks><member>\
+             <StackName>source</StackName>\
+             <CreationTime>2025-01-01T12:00:00Z</CreationTime>\
+             <StackStatus>UPDATE_COMPLETE</StackStatus>\
+             <Parameters>{}{}</Parameters>\
+             </member></Stacks></DescribeStacksResult>\
+             <ResponseMetadata><RequestId>request-id</RequestId></ResponseMetadata>\
+             </DescribeStacksResponse>",
+            parameter("Name", "app"),
+            parameter("Secret", NO_ECHO_PARAMETER_VALUE),
+        );
+        let dispatcher = MockDispatcher::new(vec![response.clone(), response]);
+        let cfn = mock::cloudformation(&dispatcher);
+
+        let copied = copy_stack_parameters(&cfn, "source", &["Name".to_owned()])
+            .await
+            .unwrap();
+        assert_eq!(
+            Parameters::new(vec![Parameter::with_value("Name", "app")]),
+            copied
+        );
+
+        match copy_stack_parameters(&cfn, "source", &["Secret".to_owned()]).await {
+            Err(Error::InvalidParameters(message)) => assert!(message.contains("NoEcho")),
+            _ => panic!("expected the masked parameter to be refused"),
+        }
+    }
+}
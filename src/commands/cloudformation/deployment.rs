@@ -0,0 +1,307 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Commands comparing deployments to stacks, based on the deployment metadata recorded in them.
+//!
+//! The deployment metadata is read from the parameter with the given key, see
+//! [`awsx::deployment`].
+//!
+//! [`awsx::deployment`]: ../../../deployment/index.html
+
+use itertools::Itertools;
+use rusoto_cloudformation::CloudFormation;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::{
+    changeset::{ChangeSet, ResourceChange},
+    deployment::{self, generate_deployment_metadata, ChangelogEntry, DeploymentMetadata},
+    error::Error,
+    parameter::OwnedParametersDifference,
+    stack::Stack,
+};
+
+/// Retrieve the deployment metadata of the stack, if it has any.
+async fn deployed_metadata(
+    cfn: &dyn CloudFormation,
+    stack_name: &str,
+    metadata_parameter: &str,
+) -> Result<Option<DeploymentMetadata>, Error> {
+    Ok(DeploymentMetadata::from_parameters(
+        &Stack::new(stack_name).get_parameters(cfn).await?,
+        metadata_parameter,
+    ))
+}
+
+/// Whether the changes in a git repository are compatible with those deployed to a stack.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChangesCompatibility {
+    /// The commit deployed to the stack.
+    pub deployed_commit: String,
+    /// The commit checked out in the repository.
+    pub current_commit: String,
+    /// Whether the changes are compatible, see [`deployment::verify_changes_compatible`].
+    ///
+    /// [`deployment::verify_changes_compatible`]: ../../../deployment/fn.verify_changes_compatible.html
+    pub compatible: bool,
+}
+
+/// Verify that the changes in the git repository containing `git_path` are compatible with those
+/// deployed to the stack.
+///
+/// Returns `None` if the stack has no deployment metadata.
+pub async fn verify_changes_compatible(
+    cfn: &dyn CloudFormation,
+    stack_name: &str,
+    metadata_parameter: &str,
+    git_path: &str,
+) -> Result<Option<ChangesCompatibility>, Error> {
+    let previous_metadata = match deployed_metadata(cfn, stack_name, metadata_parameter).await? {
+        Some(previous_metadata) => previous_metadata,
+        None => return Ok(None),
+    };
+    let current_metadata = generate_deployment_metadata(None, Some(git_path))?;
+    let compatible =
+        deployment::verify_changes_compatible(&previous_metadata, &current_metadata, git_path)?;
+
+    Ok(Some(ChangesCompatibility {
+        deployed_commit: previous_metadata.git.commit,
+        current_commit: current_metadata.git.commit,
+        compatible,
+    }))
+}
+
+/// The commits made since the commit deployed to a stack.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeploymentChangelog {
+    /// The commit deployed to the stack.
+    pub deployed_commit: String,
+    /// The commits reachable from HEAD but not from the deployed commit, newest first.
+    pub changelog: Vec<ChangelogEntry>,
+}
+
+/// List the commits in the git repository containing `git_path` that were made since the commit
+/// deployed to the stack.
+///
+/// Returns `None` if the deployment metadata of the stack does not record a deployed commit.
+pub async fn deployment_changelog(
+    cfn: &dyn CloudFormation,
+    stack_name: &str,
+    metadata_parameter: &str,
+    git_path: &str,
+) -> Result<Option<DeploymentChangelog>, Error> {
+    let deployed_metadata = deployed_metadata(cfn, stack_name, metadata_parameter)
+        .await?
+        .filter(|metadata| !metadata.git.commit.is_empty());
+    let deployed_commit = match deployed_metadata {
+        Some(deployed_metadata) => deployed_metadata.git.commit,
+        None => return Ok(None),
+    };
+
+    Ok(Some(DeploymentChangelog {
+        changelog: deployment::git_changelog(git_path, &deployed_commit, None)?,
+        deployed_commit,
+    }))
+}
+
+/// The deployment metadata of the deployed stack and of a change set.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeploymentComparison {
+    /// The deployment metadata of the stack, if it has any.
+    pub currently_deployed: Option<DeploymentMetadata>,
+    /// The deployment metadata the change set applies, if it has any.
+    pub this_deployment: Option<DeploymentMetadata>,
+}
+
+/// The resource changes of a change set.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ResourceChanges {
+    /// Number of changes per action, e.g. `Add` or `Modify`.
+    pub counts: BTreeMap<String, usize>,
+    /// Logical IDs of the resources the change set will replace, including those replaced
+    /// conditionally.
+    pub replacements: Vec<String>,
+    /// All resource changes of the change set.
+    pub changes: Vec<ResourceChange>,
+}
+
+/// A report of what a change set will deploy to its stack.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeploymentReport {
+    /// Name of the stack.
+    pub stack_name: String,
+    /// Name of the change set.
+    pub change_set_name: String,
+    /// Creation status of the change set.
+    pub status: String,
+    /// Description of the change set, if one was provided on creation.
+    pub description: Option<String>,
+    /// Who deployed what to the stack, and who deploys what with the change set.
+    pub deployment: DeploymentComparison,
+    /// The resource changes of the change set.
+    pub resource_changes: ResourceChanges,
+    /// The parameter changes of the change set, if there are any, except for the deployment
+    /// metadata, which always changes between deployments.
+    pub parameter_changes: Option<OwnedParametersDifference>,
+    /// The commits between the deployed commit and the commit of the change set, if both are
+    /// known.
+    pub changelog: Option<Vec<ChangelogEntry>>,
+    /// Why the changelog could not be built, if both commits are known but building it failed.
+    #[serde(skip)]
+    pub changelog_error: Option<String>,
+}
+
+/// Report what the change set will deploy to its stack: who deploys what, the resource and
+/// parameter changes, and the commits between the deployed commit and the commit of the change
+/// set, taken from the git repository containing `git_path`.
+///
+/// The changelog is a convenience, which is why failing to build it does not fail the report, see
+/// [`changelog_error`].
+///
+/// [`changelog_error`]: struct.DeploymentReport.html#structfield.changelog_error
+pub async fn deployment_report(
+    cfn: &dyn CloudFormation,
+    stack_name: &str,
+    change_set_name: &str,
+    metadata_parameter: &str,
+    git_path: &str,
+) -> Result<DeploymentReport, Error> {
+    let description = ChangeSet::new(stack_name, change_set_name)
+        .describe(cfn)
+        .await?;
+    let mut deployed_parameters = Stack::new(stack_name).get_parameters(cfn).await?;
+    let mut new_parameters = description.parameters.clone();
+
+    // The deployment metadata is reported on its own, it always changes between deployments.
+    let previous_metadata =
+        DeploymentMetadata::from_parameters(&deployed_parameters, metadata_parameter);
+    let current_metadata = DeploymentMetadata::from_parameters(&new_parameters, metadata_parameter);
+    deployed_parameters.shift_remove(metadata_parameter);
+    new_parameters.shift_remove(metadata_parameter);
+    let parameter_changes = deployed_parameters
+        .loose_difference(&new_parameters)
+        .map(|difference| difference.into_owned());
+
+    let (changelog, changelog_error) = match (&previous_metadata, &current_metadata) {
+        (Some(previous), Some(current))
+            if !previous.git.commit.is_empty() && !current.git.commit.is_empty() =>
+        {
+            match deployment::git_changelog(
+                git_path,
+                &previous.git.commit,
+                Some(&current.git.commit),
+            ) {
+                Ok(changelog) => (Some(changelog), None),
+                Err(e) => (None, Some(e.to_string())),
+            }
+        }
+        _ => (None, None),
+    };
+
+    Ok(DeploymentReport {
+        stack_name: stack_name.to_owned(),
+        change_set_name: description.change_set_name.clone(),
+        status: description.status.clone(),
+        description: description.description.clone(),
+        deployment: DeploymentComparison {
+            currently_deployed: previous_metadata,
+            this_deployment: current_metadata,
+        },
+        resource_changes: ResourceChanges {
+            counts: description
+                .changes
+                .iter()
+                .map(|change| change.action.clone())
+                .counts()
+                .into_iter()
+                .collect(),
+            replacements: description
+                .replacements()
+                .iter()
+                .map(|change| change.logical_id.clone())
+                .collect(),
+            changes: description.changes,
+        },
+        parameter_changes,
+        changelog,
+        changelog_error,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mock::{self, MockDispatcher},
+        parameter::Parameter,
+    };
+
+    fn parameter(key: &str, value: &str) -> String {
+        format!(
+            "<member><ParameterKey>{}</ParameterKey><ParameterValue>{}</ParameterValue></member>",
+            key, value
+        )
+    }
+
+    #[tokio::test]
+    async fn deployment_report_omits_deployment_metadata_from_parameter_changes() {
+        let dispatcher = MockDispatcher::new(vec![
+            format!(
+                "<DescribeChangeSetResponse><DescribeChangeSetResult>\
+                 <Status>CREATE_COMPLETE</Status><Parameters>{}{}</Parameters><Changes>\
+                 <member><Type>Resource</Type><ResourceChange><Action>Modify</Action>\
+                 <LogicalResourceId>Instance</LogicalResourceId>\
+                 <ResourceType>AWS::EC2::Instance</ResourceType>\
+                 <Replacement>True</Replacement></ResourceChange></member>\
+                 </Changes></DescribeChangeSetResult>\
+                 <ResponseMetadata><RequestId>request-id</RequestId></ResponseMetadata>\
+                 </DescribeChangeSetResponse>",
+                parameter("Size", "2"),
+                parameter("DeploymentMetadata", "{}"),
+            ),
+            format!(
+                "<DescribeStacksResponse><DescribeStacksResult><Stacks><member>\
+                 <StackName>app</StackName>\
+                 <CreationTime>2025-01-01T12:00:00Z</CreationTime>\
+                 <StackStatus>UPDATE_COMPLETE</StackStatus>\
+                 <Parameters>{}</Parameters>\
+                 </member></Stacks></DescribeStacksResult>\
+                 <ResponseMetadata><RequestId>request-id</RequestId></ResponseMetadata>\
+                 </DescribeStacksResponse>",
+                parameter("Size", "1"),
+            ),
+        ]);
+        let cfn = mock::cloudformation(&dispatcher);
+
+        let report = deployment_report(&cfn, "app", "release", "DeploymentMetadata", ".")
+            .await
+            .unwrap();
+
+        let parameter_changes = report.parameter_changes.unwrap();
+        assert!(parameter_changes.left.is_empty());
+        assert!(parameter_changes.right.is_empty());
+        assert_eq!(1, parameter_changes.unequal.len());
+        assert_eq!(
+            Parameter::with_value("Size", "2"),
+            parameter_changes.unequal[0].right
+        );
+        assert_eq!(Some(&1), report.resource_changes.counts.get("Modify"));
+        assert_eq!(vec!["Instance"], report.resource_changes.replacements);
+        // Without a deployed commit there is no changelog, which is not an error.
+        assert!(report.changelog.is_none());
+        assert!(report.changelog_error.is_none());
+    }
+}
@@ -0,0 +1,29 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Commands operating on CloudFormation stacks, their parameters, change sets and templates.
+//!
+//! Local files, e.g. templates and parameter files, are loaded by the caller and passed to these
+//! commands, so that they don't depend on the file system or on how the caller expands
+//! placeholders.
+
+pub mod change_set;
+pub mod deploy;
+pub mod deployment;
+pub mod move_resource;
+pub mod parameters;
+pub mod stack;
+pub mod template;
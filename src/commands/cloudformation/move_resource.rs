@@ -0,0 +1,755 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Commands moving a resource from one stack to another, without replacing the resource.
+//!
+//! The move is planned through [`plan_resource_move`], which performs all safety checks upfront.
+//! Every [`MoveStep`] of the plan is then carried out through a change set, which is created
+//! through [`create_move_change_set`] and verified through [`verify_move_change_set`] before it
+//! is executed.
+
+use itertools::Itertools;
+use rusoto_cloudformation::{CloudFormation, GetTemplateSummaryInput, ResourceToImport};
+use serde::Serialize;
+use serde_yaml::Value;
+use std::{collections::HashMap, str::FromStr};
+use uuid::Uuid;
+
+use crate::{
+    changeset::{is_no_changes_reason, ChangeSet, ChangeSetDescription, ResourceChange},
+    error::Error,
+    parameter::Parameters,
+    s3::S3Uploader,
+    stack::Stack,
+    template::{referenced_logical_ids, Template},
+};
+
+/// A single identifier property of a resource, e.g. `BucketName=my-bucket`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdentifierProperty {
+    /// Name of the property.
+    pub property: String,
+    /// Value of the property.
+    pub value: String,
+}
+
+impl FromStr for IdentifierProperty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (property, value) = s
+            .split_once('=')
+            .ok_or_else(|| format!("`{}` is not of the form `Property=Value`", s))?;
+        Ok(IdentifierProperty {
+            property: property.to_owned(),
+            value: value.to_owned(),
+        })
+    }
+}
+
+/// The input of [`plan_resource_move`].
+pub struct ResourceMove<'a> {
+    /// Name of the stack currently managing the resource.
+    pub source_stack_name: &'a str,
+    /// Name of the stack to move the resource to.
+    pub destination_stack_name: &'a str,
+    /// Logical ID of the resource in the source stack.
+    pub logical_resource_id: &'a str,
+    /// Logical ID of the resource in the destination stack, or `None` to keep the logical ID.
+    pub destination_logical_resource_id: Option<&'a str>,
+    /// The template of the destination stack declaring the resource, or `None` to copy the
+    /// declaration from the source stack into the deployed template of the destination stack.
+    pub destination_template: Option<Template>,
+    /// Identifier properties of the resource, which are only required for resource types
+    /// identified by more than one property.
+    pub resource_identifier: &'a [IdentifierProperty],
+}
+
+/// The resource to move, and how it is moved.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MovedResource {
+    /// Name of the stack currently managing the resource.
+    pub source_stack_name: String,
+    /// Name of the stack the resource is moved to.
+    pub destination_stack_name: String,
+    /// Logical ID of the resource in the source stack.
+    pub logical_resource_id: String,
+    /// Logical ID of the resource in the destination stack.
+    pub destination_logical_resource_id: String,
+    /// Type of the resource.
+    pub resource_type: String,
+    /// Physical ID of the resource.
+    pub physical_resource_id: String,
+    /// The identifier properties used to import the resource.
+    pub resource_identifier: HashMap<String, String>,
+    /// Description of every step of the move.
+    pub plan: Vec<String>,
+}
+
+/// The outcome of moving a resource.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ResourceMoveOutcome {
+    /// The moved resource, whose fields are serialized next to the others.
+    #[serde(flatten)]
+    pub resource: MovedResource,
+    /// Whether only the safety checks were performed, without moving the resource.
+    pub dry_run: bool,
+    /// Names of the executed change sets, in the order they were executed.
+    pub change_sets: Vec<String>,
+}
+
+/// A step of moving a resource, each of which is carried out through a change set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveStep {
+    /// Set the `DeletionPolicy` of the resource in the source stack to `Retain`.
+    Retain,
+    /// Remove the resource from the source stack, retaining it.
+    Remove,
+    /// Import the resource into the destination stack.
+    Import,
+}
+
+/// The plan of moving a resource, which passed all safety checks.
+pub struct ResourceMovePlan {
+    /// The resource to move.
+    pub resource: MovedResource,
+    /// The template of the source stack retaining the resource.
+    pub retained_template: Template,
+    /// The template of the source stack without the resource.
+    pub removed_template: Template,
+    /// The template of the destination stack declaring the resource.
+    pub destination_template: Template,
+    /// The parameters of the source stack, keeping their previous values.
+    pub source_parameters: Parameters,
+    /// The parameters of the destination stack, keeping their previous values.
+    pub destination_parameters: Parameters,
+    needs_retain: bool,
+}
+
+impl ResourceMovePlan {
+    /// The steps of the move, in the order they have to be carried out.
+    ///
+    /// The resource is only retained first if its `DeletionPolicy` is not `Retain` already.
+    pub fn steps(&self) -> Vec<MoveStep> {
+        let mut steps = Vec::new();
+        if self.needs_retain {
+            steps.push(MoveStep::Retain);
+        }
+        steps.push(MoveStep::Remove);
+        steps.push(MoveStep::Import);
+        steps
+    }
+
+    /// Describe what the step does.
+    pub fn describe(&self, step: MoveStep) -> String {
+        let resource = &self.resource;
+        match step {
+            MoveStep::Retain => format!(
+                "Set the DeletionPolicy of {} in stack {} to Retain",
+                resource.logical_resource_id, resource.source_stack_name
+            ),
+            MoveStep::Remove => format!(
+                "Remove {} from stack {}, retaining {}",
+                resource.logical_resource_id,
+                resource.source_stack_name,
+                resource.physical_resource_id
+            ),
+            MoveStep::Import => format!(
+                "Import {} into stack {} as {}",
+                resource.physical_resource_id,
+                resource.destination_stack_name,
+                resource.destination_logical_resource_id
+            ),
+        }
+    }
+
+    /// The stack the step changes.
+    pub fn stack_name(&self, step: MoveStep) -> &str {
+        match step {
+            MoveStep::Retain | MoveStep::Remove => &self.resource.source_stack_name,
+            MoveStep::Import => &self.resource.destination_stack_name,
+        }
+    }
+
+    /// The template the step applies.
+    pub fn template(&self, step: MoveStep) -> &Template {
+        match step {
+            MoveStep::Retain => &self.retained_template,
+            MoveStep::Remove => &self.removed_template,
+            MoveStep::Import => &self.destination_template,
+        }
+    }
+
+    /// The parameters the step applies.
+    pub fn parameters(&self, step: MoveStep) -> &Parameters {
+        match step {
+            MoveStep::Retain | MoveStep::Remove => &self.source_parameters,
+            MoveStep::Import => &self.destination_parameters,
+        }
+    }
+
+    /// Create a new change set for the step, with a unique name.
+    pub fn change_set(&self, step: MoveStep) -> ChangeSet {
+        let name = match step {
+            MoveStep::Retain => "retain",
+            MoveStep::Remove => "remove",
+            MoveStep::Import => "import",
+        };
+        ChangeSet::new(
+            self.stack_name(step),
+            format!("awsx-move-resource-{}-{}", name, Uuid::new_v4()),
+        )
+    }
+
+    /// Check whether the change is the one expected from the step.
+    fn is_expected(&self, step: MoveStep, change: &ResourceChange) -> bool {
+        let resource = &self.resource;
+        match step {
+            MoveStep::Retain => {
+                change.logical_id == resource.logical_resource_id && change.action == "Modify"
+            }
+            MoveStep::Remove => {
+                change.logical_id == resource.logical_resource_id && change.action == "Remove"
+            }
+            MoveStep::Import => {
+                change.logical_id == resource.destination_logical_resource_id
+                    && change.action == "Import"
+            }
+        }
+    }
+}
+
+/// Plan moving the resource from the source stack to the destination stack, performing all safety
+/// checks.
+///
+/// Both stacks have to be in a stable state, the resource must not be referenced anywhere else in
+/// the source stack and must not exist in the destination stack yet, and neither template may use
+/// transforms. The destination template has to declare the resource with the same type and a
+/// `DeletionPolicy`.
+pub async fn plan_resource_move(
+    cfn: &dyn CloudFormation,
+    input: ResourceMove<'_>,
+    s3_upload: Option<(&S3Uploader, &str)>,
+) -> Result<ResourceMovePlan, Error> {
+    let logical_id = input.logical_resource_id;
+    let destination_logical_id = input.destination_logical_resource_id.unwrap_or(logical_id);
+    let source = Stack::new(input.source_stack_name);
+    let destination = Stack::new(input.destination_stack_name);
+    ensure_stable(cfn, &source).await?;
+    ensure_stable(cfn, &destination).await?;
+
+    let resource = source
+        .get_resources(cfn)
+        .await?
+        .into_iter()
+        .find(|resource| resource.logical_id == logical_id)
+        .ok_or_else(|| {
+            Error::ResourceNotFound(format!(
+                "resource {} in stack {}",
+                logical_id, input.source_stack_name
+            ))
+        })?;
+    let physical_id = resource.physical_id.clone().ok_or_else(|| {
+        Error::UnsafeResourceMove(format!("{} has not been created yet", logical_id))
+    })?;
+    if destination
+        .get_resources(cfn)
+        .await?
+        .iter()
+        .any(|resource| resource.logical_id == destination_logical_id)
+    {
+        return Err(Error::UnsafeResourceMove(format!(
+            "stack {} already contains a resource {}",
+            input.destination_stack_name, destination_logical_id
+        )));
+    }
+
+    // Prepare the templates of the source stack, first retaining the resource and then removing
+    // it. The resource must not be referenced anywhere else, otherwise removing it would fail or
+    // change the behaviour of the remaining resources.
+    let source_template = source.get_template(cfn).await?;
+    let source_value = source_template.to_value()?;
+    ensure_without_transform(&source_value, input.source_stack_name)?;
+    let declaration = source_value["Resources"][logical_id].clone();
+    if declaration.is_null() {
+        return Err(Error::InvalidTemplate(format!(
+            "the template of stack {} does not declare the resource {}",
+            input.source_stack_name, logical_id
+        )));
+    }
+    let referencing = referencing_entries(&source_value, logical_id);
+    if !referencing.is_empty() {
+        return Err(Error::UnsafeResourceMove(format!(
+            "{} is still referenced by {}",
+            logical_id,
+            referencing.join(", ")
+        )));
+    }
+    let needs_retain = declaration["DeletionPolicy"].as_str() != Some("Retain");
+    let mut retained_value = source_value.clone();
+    retained_value["Resources"][logical_id]["DeletionPolicy"] = "Retain".into();
+    let retained_template = Template::from_value(&retained_value, source_template.is_json())?;
+    let mut removed_value = retained_value;
+    if let Some(resources) = removed_value
+        .get_mut("Resources")
+        .and_then(Value::as_mapping_mut)
+    {
+        resources.remove(logical_id);
+        if resources.is_empty() {
+            return Err(Error::UnsafeResourceMove(format!(
+                "{} is the only resource of stack {}, which can not be left without resources",
+                logical_id, input.source_stack_name
+            )));
+        }
+    }
+    let removed_template = Template::from_value(&removed_value, source_template.is_json())?;
+
+    // Prepare the template of the destination stack declaring the resource.
+    let destination_template = match input.destination_template {
+        Some(destination_template) => destination_template,
+        None => {
+            let references = referenced_logical_ids(&declaration);
+            if !references.is_empty() {
+                return Err(Error::UnsafeResourceMove(format!(
+                    "{} references {}, which can not be copied to stack {}, specify \
+                     --destination-template-path instead",
+                    logical_id,
+                    references.iter().join(", "),
+                    input.destination_stack_name
+                )));
+            }
+            let template = destination.get_template(cfn).await?;
+            let mut value = template.to_value()?;
+            let mut declaration = declaration.clone();
+            declaration["DeletionPolicy"] = "Retain".into();
+            value["Resources"][destination_logical_id] = declaration;
+            Template::from_value(&value, template.is_json())?
+        }
+    };
+    let destination_value = destination_template.to_value()?;
+    ensure_without_transform(&destination_value, input.destination_stack_name)?;
+    let imported = &destination_value["Resources"][destination_logical_id];
+    if imported["Type"].as_str() != Some(resource.resource_type.as_str()) {
+        return Err(Error::UnsafeResourceMove(format!(
+            "the destination template has to declare {} with the type {}",
+            destination_logical_id, resource.resource_type
+        )));
+    }
+    if imported["DeletionPolicy"].is_null() {
+        return Err(Error::UnsafeResourceMove(format!(
+            "the destination template has to declare a DeletionPolicy for {}",
+            destination_logical_id
+        )));
+    }
+
+    let resource_identifier = resource_identifier(
+        cfn,
+        &destination_template,
+        s3_upload,
+        &resource.resource_type,
+        &physical_id,
+        input.resource_identifier,
+    )
+    .await?;
+
+    let mut plan = ResourceMovePlan {
+        resource: MovedResource {
+            source_stack_name: input.source_stack_name.to_owned(),
+            destination_stack_name: input.destination_stack_name.to_owned(),
+            logical_resource_id: logical_id.to_owned(),
+            destination_logical_resource_id: destination_logical_id.to_owned(),
+            resource_type: resource.resource_type,
+            physical_resource_id: physical_id,
+            resource_identifier,
+            plan: Vec::new(),
+        },
+        retained_template,
+        removed_template,
+        destination_template,
+        source_parameters: source.get_parameters_as_previous_value(cfn).await?,
+        destination_parameters: destination.get_parameters_as_previous_value(cfn).await?,
+        needs_retain,
+    };
+    plan.resource.plan = plan
+        .steps()
+        .into_iter()
+        .map(|step| plan.describe(step))
+        .collect();
+    Ok(plan)
+}
+
+/// Create the change set carrying out the step of the planned move.
+///
+/// This does not wait for the creation to complete, see [`verify_move_change_set`].
+pub async fn create_move_change_set(
+    cfn: &dyn CloudFormation,
+    plan: &ResourceMovePlan,
+    step: MoveStep,
+    change_set: &ChangeSet,
+    role_arn: Option<&str>,
+    s3_upload: Option<(&S3Uploader, &str)>,
+) -> Result<(), Error> {
+    let resource = &plan.resource;
+    match step {
+        MoveStep::Retain | MoveStep::Remove => {
+            let description = if step == MoveStep::Retain {
+                format!(
+                    "Retain {} before moving it to stack {}",
+                    resource.logical_resource_id, resource.destination_stack_name
+                )
+            } else {
+                format!(
+                    "Remove {} to move it to stack {}",
+                    resource.logical_resource_id, resource.destination_stack_name
+                )
+            };
+            plan.template(step)
+                .create_change_set(
+                    cfn,
+                    change_set.name(),
+                    change_set.stack_name(),
+                    plan.parameters(step),
+                    role_arn,
+                    None,
+                    Some(&description),
+                    s3_upload,
+                    false,
+                )
+                .await?;
+        }
+        MoveStep::Import => {
+            plan.destination_template
+                .create_import_change_set(
+                    cfn,
+                    change_set.name(),
+                    change_set.stack_name(),
+                    &plan.destination_parameters,
+                    role_arn,
+                    vec![ResourceToImport {
+                        logical_resource_id: resource.destination_logical_resource_id.clone(),
+                        resource_identifier: resource.resource_identifier.clone(),
+                        resource_type: resource.resource_type.clone(),
+                    }],
+                    Some(&format!(
+                        "Import {} moved from stack {}",
+                        resource.destination_logical_resource_id, resource.source_stack_name
+                    )),
+                    s3_upload,
+                )
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Wait for the change set of the step to be created, and verify it only contains the change
+/// expected from the step.
+///
+/// Returns `None` if the change set did not contain any changes, in which case it is deleted. A
+/// change set containing unexpected changes is deleted as well, and fails the move.
+pub async fn verify_move_change_set(
+    cfn: &dyn CloudFormation,
+    plan: &ResourceMovePlan,
+    step: MoveStep,
+    change_set: &ChangeSet,
+) -> Result<Option<ChangeSetDescription>, Error> {
+    let description = match change_set.wait_until_created(cfn).await {
+        Ok(description) => description,
+        Err(Error::ChangeSetCreationFailed(reason)) if is_no_changes_reason(&reason) => {
+            change_set.delete(cfn).await?;
+            return Ok(None);
+        }
+        Err(error) => return Err(error),
+    };
+    let unexpected = description
+        .changes
+        .iter()
+        .filter(|change| !plan.is_expected(step, change))
+        .map(|change| format!("{} {}", change.action, change.logical_id))
+        .collect::<Vec<_>>();
+    if !unexpected.is_empty() {
+        change_set.delete(cfn).await?;
+        return Err(Error::UnsafeResourceMove(format!(
+            "change set {} of stack {} contains unexpected changes: {}",
+            change_set.name(),
+            change_set.stack_name(),
+            unexpected.join(", ")
+        )));
+    }
+    Ok(Some(description))
+}
+
+/// Ensure the stack exists and no operation is in progress or has left it in a state that does
+/// not allow updates.
+async fn ensure_stable(cfn: &dyn CloudFormation, stack: &Stack) -> Result<(), Error> {
+    match stack.status(cfn).await? {
+        None => Err(Error::InvalidStack(stack.name().to_owned())),
+        Some(status)
+            if status.ends_with("_COMPLETE")
+                && status != "ROLLBACK_COMPLETE"
+                && status != "DELETE_COMPLETE" =>
+        {
+            Ok(())
+        }
+        Some(status) => Err(Error::UnsafeResourceMove(format!(
+            "stack {} is in status {}",
+            stack.name(),
+            status
+        ))),
+    }
+}
+
+/// Ensure the template does not use transforms, since the resources of the processed template can
+/// differ from the declarations in the original template.
+fn ensure_without_transform(template: &Value, stack_name: &str) -> Result<(), Error> {
+    if template.get("Transform").is_some() {
+        Err(Error::UnsafeResourceMove(format!(
+            "the template of stack {} uses transforms",
+            stack_name
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+/// Return all entries of the template referencing the logical ID, e.g. `Outputs.BucketName`.
+fn referencing_entries(template: &Value, logical_id: &str) -> Vec<String> {
+    ["Conditions", "Resources", "Outputs"]
+        .iter()
+        .filter_map(|section| {
+            template
+                .get(*section)
+                .and_then(Value::as_mapping)
+                .map(|entries| (section, entries))
+        })
+        .flat_map(|(section, entries)| {
+            entries
+                .iter()
+                .filter(move |(name, value)| {
+                    name.as_str() != Some(logical_id)
+                        && referenced_logical_ids(value).contains(logical_id)
+                })
+                .map(move |(name, _)| format!("{}.{}", section, name.as_str().unwrap_or_default()))
+        })
+        .collect()
+}
+
+/// Determine the identifier properties used to import the resource.
+///
+/// Resource types identified by a single property, e.g. the `BucketName` of an S3 bucket, are
+/// identified by the physical ID, all others have to be specified explicitly.
+async fn resource_identifier(
+    cfn: &dyn CloudFormation,
+    template: &Template,
+    s3_upload: Option<(&S3Uploader, &str)>,
+    resource_type: &str,
+    physical_id: &str,
+    specified: &[IdentifierProperty],
+) -> Result<HashMap<String, String>, Error> {
+    let mut input = GetTemplateSummaryInput::default();
+    if let Some((s3, bucket_name)) = s3_upload {
+        input.template_url = Some(template.upload_to_s3(s3, bucket_name).await?);
+    } else {
+        input.template_body = Some(template.template_body()?);
+    }
+    let properties = cfn
+        .get_template_summary(input)
+        .await?
+        .resource_identifier_summaries
+        .unwrap_or_default()
+        .into_iter()
+        .find(|summary| summary.resource_type.as_deref() == Some(resource_type))
+        .and_then(|summary| summary.resource_identifiers)
+        .unwrap_or_default();
+
+    choose_resource_identifier(&properties, resource_type, physical_id, specified)
+}
+
+/// Map the identifier properties of the resource type to their values, using the physical ID for
+/// types identified by a single property unless the properties were specified explicitly.
+fn choose_resource_identifier(
+    properties: &[String],
+    resource_type: &str,
+    physical_id: &str,
+    specified: &[IdentifierProperty],
+) -> Result<HashMap<String, String>, Error> {
+    match properties {
+        [] => Err(Error::UnsafeResourceMove(format!(
+            "resources of type {} can not be imported",
+            resource_type
+        ))),
+        [property] if specified.is_empty() => Ok(vec![(property.clone(), physical_id.to_owned())]
+            .into_iter()
+            .collect()),
+        _ => properties
+            .iter()
+            .map(|property| {
+                specified
+                    .iter()
+                    .find(|specified| &specified.property == property)
+                    .map(|specified| (property.clone(), specified.value.clone()))
+                    .ok_or_else(|| {
+                        Error::InvalidParameters(format!(
+                            "resources of type {} are identified by {}, specify them through \
+                             --resource-identifier",
+                            resource_type,
+                            properties.join(", ")
+                        ))
+                    })
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template(yaml: &str) -> Value {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn referencing_entries_covers_all_sections() {
+        let template = template(
+            r#"
+Conditions:
+  HasBucket: !Not [!Equals [!Ref Bucket, ""]]
+Resources:
+  Bucket:
+    Type: AWS::S3::Bucket
+    Properties:
+      BucketName: !Sub "${AWS::StackName}-bucket"
+  Policy:
+    Type: AWS::S3::BucketPolicy
+    DependsOn: Bucket
+  Topic:
+    Type: AWS::SNS::Topic
+    Properties:
+      TopicName: !GetAtt Bucket.Arn
+  Queue:
+    Type: AWS::SQS::Queue
+Outputs:
+  BucketName:
+    Value: !Ref Bucket
+  QueueName:
+    Value: !GetAtt Queue.QueueName
+"#,
+        );
+
+        assert_eq!(
+            vec![
+                "Conditions.HasBucket",
+                "Resources.Policy",
+                "Resources.Topic",
+                "Outputs.BucketName",
+            ],
+            referencing_entries(&template, "Bucket")
+        );
+        assert_eq!(
+            vec!["Outputs.QueueName"],
+            referencing_entries(&template, "Queue")
+        );
+        assert!(referencing_entries(&template, "Topic").is_empty());
+    }
+
+    #[test]
+    fn refuses_templates_with_transform() {
+        let with_transform = template(
+            r#"
+Transform: AWS::Serverless-2016-10-31
+Resources:
+  Function:
+    Type: AWS::Serverless::Function
+"#,
+        );
+        assert!(matches!(
+            ensure_without_transform(&with_transform, "my-stack"),
+            Err(Error::UnsafeResourceMove(_))
+        ));
+
+        let without_transform = template(
+            r#"
+Resources:
+  Bucket:
+    Type: AWS::S3::Bucket
+"#,
+        );
+        assert!(ensure_without_transform(&without_transform, "my-stack").is_ok());
+    }
+
+    fn identifier(property: &str, value: &str) -> IdentifierProperty {
+        IdentifierProperty {
+            property: property.to_owned(),
+            value: value.to_owned(),
+        }
+    }
+
+    #[test]
+    fn resource_identifier_uses_physical_id_for_single_property() {
+        let properties = vec!["BucketName".to_owned()];
+        let identifier =
+            choose_resource_identifier(&properties, "AWS::S3::Bucket", "my-bucket", &[]).unwrap();
+        assert_eq!(Some(&"my-bucket".to_owned()), identifier.get("BucketName"));
+        assert_eq!(1, identifier.len());
+    }
+
+    #[test]
+    fn resource_identifier_requires_all_of_multiple_properties() {
+        let properties = vec!["TableName".to_owned(), "Id".to_owned()];
+        let specified = vec![identifier("TableName", "my-table"), identifier("Id", "42")];
+        let chosen =
+            choose_resource_identifier(&properties, "AWS::Some::Thing", "physical", &specified)
+                .unwrap();
+        assert_eq!(Some(&"my-table".to_owned()), chosen.get("TableName"));
+        assert_eq!(Some(&"42".to_owned()), chosen.get("Id"));
+
+        assert!(matches!(
+            choose_resource_identifier(
+                &properties,
+                "AWS::Some::Thing",
+                "physical",
+                &specified[..1]
+            ),
+            Err(Error::InvalidParameters(_))
+        ));
+        assert!(matches!(
+            choose_resource_identifier(&properties, "AWS::Some::Thing", "physical", &[]),
+            Err(Error::InvalidParameters(_))
+        ));
+    }
+
+    #[test]
+    fn resource_identifier_prefers_specified_single_property() {
+        let properties = vec!["BucketName".to_owned()];
+        let chosen = choose_resource_identifier(
+            &properties,
+            "AWS::S3::Bucket",
+            "physical",
+            &[identifier("BucketName", "explicit")],
+        )
+        .unwrap();
+        assert_eq!(Some(&"explicit".to_owned()), chosen.get("BucketName"));
+    }
+
+    #[test]
+    fn resource_identifier_refuses_types_without_identifiers() {
+        assert!(matches!(
+            choose_resource_identifier(&[], "AWS::Some::Thing", "physical", &[]),
+            Err(Error::UnsafeResourceMove(_))
+        ));
+    }
+}
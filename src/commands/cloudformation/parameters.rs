@@ -0,0 +1,520 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Commands inspecting the parameters of stacks, and verifying parameter files against them.
+
+use rusoto_cloudformation::CloudFormation;
+use serde::Serialize;
+use std::str::FromStr;
+
+use crate::{
+    changeset::ChangeSet,
+    error::Error,
+    parameter::{
+        DifferenceFormat, OwnedParametersDifference, Parameter, Parameters, UnequalParameter,
+        NO_ECHO_PARAMETER_VALUE,
+    },
+    stack::Stack,
+    template::Template,
+};
+
+/// A parameter of a stack, as shown by `show-stack-parameters`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ShownParameter {
+    /// Key of the parameter.
+    pub key: String,
+    /// Value of the parameter, unless it is declared as `NoEcho` or uses its previous value.
+    pub value: Option<String>,
+    /// Value CloudFormation resolved the parameter to, e.g. for parameters of the AWS Systems
+    /// Manager parameter types.
+    pub resolved_value: Option<String>,
+    /// Whether the parameter is declared as `NoEcho`, i.e. its value is masked by CloudFormation.
+    pub no_echo: bool,
+}
+
+/// The parameters of a stack.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StackParameters {
+    /// Name of the stack.
+    pub stack_name: String,
+    /// The parameters of the stack.
+    pub parameters: Vec<ShownParameter>,
+}
+
+impl StackParameters {
+    /// Convert the parameters into a parameter file.
+    ///
+    /// Parameters whose value is not known, i.e. `NoEcho` parameters, use their previous value.
+    pub fn to_parameter_file(&self) -> Parameters {
+        self.parameters
+            .iter()
+            .map(|parameter| match &parameter.value {
+                Some(value) => Parameter::with_value(parameter.key.as_str(), value.as_str()),
+                None => Parameter::previous_value(parameter.key.clone()),
+            })
+            .collect::<Vec<_>>()
+            .into()
+    }
+}
+
+/// Show the parameters of the stack together with their resolved values, either in the order
+/// CloudFormation returns them or ordered by their key.
+pub async fn show_stack_parameters(
+    cfn: &dyn CloudFormation,
+    stack_name: &str,
+    sort_parameters: bool,
+) -> Result<StackParameters, Error> {
+    let stack = Stack::new(stack_name);
+    let mut parameters = stack.get_parameters(cfn).await?;
+    if sort_parameters {
+        parameters = parameters.sorted();
+    }
+    let resolved_values = stack.get_resolved_values(cfn).await?;
+
+    Ok(StackParameters {
+        stack_name: stack_name.to_owned(),
+        parameters: parameters
+            .values()
+            .map(|parameter| {
+                let value = match parameter {
+                    Parameter::WithValue { value, .. } => Some(value.as_str()),
+                    Parameter::PreviousValue { .. } => None,
+                };
+                let no_echo = value == Some(NO_ECHO_PARAMETER_VALUE);
+                ShownParameter {
+                    key: parameter.key().to_owned(),
+                    value: value.filter(|_| !no_echo).map(ToOwned::to_owned),
+                    resolved_value: resolved_values.get(parameter.key()).cloned(),
+                    no_echo,
+                }
+            })
+            .collect(),
+    })
+}
+
+/// The declaration of a parameter in a template.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ParameterDeclaration {
+    /// Type of the parameter, e.g. `String` or `CommaDelimitedList`.
+    #[serde(rename = "type")]
+    pub parameter_type: String,
+    /// Default value of the parameter.
+    pub default: Option<String>,
+    /// Description of the parameter.
+    pub description: Option<String>,
+    /// Whether the parameter is declared as `NoEcho`.
+    pub no_echo: bool,
+}
+
+/// A parameter defined in a template, but not set on the stack.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NewParameter {
+    /// Key of the parameter.
+    pub key: String,
+    /// The declaration of the parameter, whose fields are serialized next to the key.
+    #[serde(flatten)]
+    pub declaration: Option<ParameterDeclaration>,
+}
+
+/// The parameters added to and removed from a template, compared to a stack.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NewParameters {
+    /// Keys of the parameters defined in the template, but not set on the stack.
+    pub parameters: Vec<String>,
+    /// The parameters defined in the template, but not set on the stack, with their declaration.
+    pub parameter_details: Vec<NewParameter>,
+    /// Keys of the parameters set on the stack, but removed from the template.
+    pub removed_parameters: Vec<String>,
+}
+
+/// Identify the parameters defined in the template that are not set on the stack yet.
+///
+/// Parameters set on the stack that were removed from the template are identified too. They do not
+/// need to be set and will simply be removed once the template is deployed, but they can indicate
+/// an accidental template regression.
+pub async fn identify_new_parameters(
+    cfn: &dyn CloudFormation,
+    stack_name: &str,
+    template: &Template,
+) -> Result<NewParameters, Error> {
+    let template_parameters = template.get_parameters_as_previous_value();
+    let stack_parameters = Stack::new(stack_name)
+        .get_parameters_as_previous_value(cfn)
+        .await?;
+
+    let new_parameters = template_parameters.clone() - &stack_parameters;
+    let removed_parameters = stack_parameters - &template_parameters;
+
+    // Enrich the new parameters with their declaration in the template, so the operator can fill in
+    // a parameter file without having to look up the template.
+    let declarations = template.get_parameter_declarations();
+    Ok(NewParameters {
+        parameters: new_parameters.keys().cloned().collect(),
+        parameter_details: new_parameters
+            .keys()
+            .map(|key| NewParameter {
+                key: key.clone(),
+                declaration: declarations
+                    .get(key)
+                    .map(|declaration| ParameterDeclaration {
+                        parameter_type: declaration.parameter_type.clone(),
+                        default: declaration.default.clone(),
+                        description: declaration.description.clone(),
+                        no_echo: declaration.no_echo,
+                    }),
+            })
+            .collect(),
+        removed_parameters: removed_parameters.keys().cloned().collect(),
+    })
+}
+
+/// A category of differences between a parameter file and the parameters it is verified against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DifferenceCategory {
+    /// Parameters only on the stack, change set or template.
+    Missing,
+    /// Parameters with different values.
+    Unequal,
+    /// Parameters only in the parameter file.
+    Extra,
+}
+
+impl FromStr for DifferenceCategory {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "missing" => Ok(DifferenceCategory::Missing),
+            "unequal" => Ok(DifferenceCategory::Unequal),
+            "extra" => Ok(DifferenceCategory::Extra),
+            _ => Err(format!("unknown difference category `{}`", s)),
+        }
+    }
+}
+
+/// How severe differences of a category are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The differences cause the verification to fail.
+    Error,
+    /// The differences are reported, but don't cause the verification to fail.
+    Warning,
+}
+
+/// What a parameter file is verified against.
+#[derive(Debug)]
+pub enum VerifiedAgainst {
+    /// The parameters currently deployed on the stack.
+    Stack {
+        /// Name of the stack.
+        stack_name: String,
+        /// Compare against the resolved values of the parameters, rather than the values they
+        /// were specified with.
+        compare_resolved_values: bool,
+    },
+    /// The parameters a change set of the stack will apply.
+    ChangeSet {
+        /// Name of the stack.
+        stack_name: String,
+        /// Name of the change set.
+        change_set_name: String,
+        /// Compare against the resolved values of the parameters, rather than the values they
+        /// were specified with.
+        compare_resolved_values: bool,
+    },
+    /// The parameters defined in a template.
+    Template(Template),
+}
+
+impl VerifiedAgainst {
+    /// Describe what the parameter file is verified against, e.g. `CloudFormation stack`.
+    pub fn description(&self) -> &'static str {
+        match self {
+            VerifiedAgainst::Stack { .. } => "CloudFormation stack",
+            VerifiedAgainst::ChangeSet { .. } => "CloudFormation change set",
+            VerifiedAgainst::Template(_) => "CloudFormation template",
+        }
+    }
+}
+
+/// The parameters of a verification, grouped by how they differ.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ComparedParameters {
+    /// Parameters only on the stack, change set or template.
+    pub only_on_stack_or_template: Vec<Parameter>,
+    /// Parameters with equal values.
+    pub equal_between_both: Vec<Parameter>,
+    /// Parameters with different values, except those masked by CloudFormation, which cannot be
+    /// compared.
+    pub unequal_between_both: Vec<UnequalParameter>,
+    /// Parameters only in the parameter file.
+    pub only_in_parameter_file: Vec<Parameter>,
+}
+
+/// The severity of the differences of each category.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DifferenceSeverities {
+    /// Severity of parameters only on the stack, change set or template.
+    pub only_on_stack_or_template: Severity,
+    /// Severity of parameters with different values.
+    pub unequal_between_both: Severity,
+    /// Severity of parameters only in the parameter file.
+    pub only_in_parameter_file: Severity,
+}
+
+/// The result of verifying a parameter file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ParameterFileVerification {
+    /// The compared parameters, grouped by how they differ.
+    pub parameters: ComparedParameters,
+    /// The severity of the differences of each category.
+    pub severities: DifferenceSeverities,
+    /// The differences, if there are any, including parameters masked by CloudFormation.
+    #[serde(skip)]
+    pub difference: Option<OwnedParametersDifference>,
+    /// Keys of the parameters declared as `NoEcho`, whose values must not be shown.
+    #[serde(skip)]
+    pub no_echo_parameters: Vec<String>,
+    /// The most severe category of differences that is present and causes the verification to
+    /// fail, if any.
+    #[serde(skip)]
+    pub failed_category: Option<DifferenceCategory>,
+}
+
+impl ParameterFileVerification {
+    /// Render the differences for humans in the given format, masking the values of `NoEcho`
+    /// parameters, see [`ParametersDifference::render`].
+    ///
+    /// [`ParametersDifference::render`]: ../../../parameter/struct.ParametersDifference.html#method.render
+    pub fn render_difference(&self, format: DifferenceFormat, colorize: bool) -> Option<String> {
+        self.difference.as_ref().map(|difference| {
+            difference.as_difference().render(
+                format,
+                |key| self.no_echo_parameters.iter().any(|no_echo| no_echo == key),
+                colorize,
+            )
+        })
+    }
+}
+
+/// Verify the parameters of a parameter file against those of a stack, change set or template.
+///
+/// Differences of the categories in `fail_on`, or of all categories if it is empty, cause the
+/// verification to fail. The most severe failing category is reported as the
+/// [`failed_category`], where unequal parameters are more severe than missing ones, which are more
+/// severe than extra ones.
+///
+/// The client is only used if the parameter file is verified against a stack or change set.
+///
+/// [`failed_category`]: struct.ParameterFileVerification.html#structfield.failed_category
+pub async fn verify_parameter_file(
+    cfn: &dyn CloudFormation,
+    against: &VerifiedAgainst,
+    file_parameters: &Parameters,
+    fail_on: &[DifferenceCategory],
+) -> Result<ParameterFileVerification, Error> {
+    // Parameters declared as `NoEcho`, whose values must not be shown. The stack and change set
+    // mask these values themselves, but the values from the parameter file have to be masked too.
+    let no_echo_parameters = |template: &Template| -> Vec<String> {
+        template
+            .get_parameter_declarations()
+            .iter()
+            .filter(|(_, declaration)| declaration.no_echo)
+            .map(|(key, _)| key.to_owned())
+            .collect()
+    };
+    let (defined_parameters, no_echo_parameters) = match against {
+        VerifiedAgainst::Stack {
+            stack_name,
+            compare_resolved_values,
+        } => {
+            let stack = Stack::new(stack_name);
+            let parameters = stack.get_parameters(cfn).await?;
+            let parameters = if *compare_resolved_values {
+                parameters.with_resolved_values(&stack.get_resolved_values(cfn).await?)
+            } else {
+                parameters
+            };
+            (
+                parameters,
+                no_echo_parameters(&stack.get_template(cfn).await?),
+            )
+        }
+        VerifiedAgainst::ChangeSet {
+            stack_name,
+            change_set_name,
+            compare_resolved_values,
+        } => {
+            let change_set = ChangeSet::new(stack_name, change_set_name);
+            let description = change_set.describe(cfn).await?;
+            let parameters = if *compare_resolved_values {
+                description
+                    .parameters
+                    .with_resolved_values(&description.resolved_values)
+            } else {
+                description.parameters
+            };
+            (
+                parameters,
+                no_echo_parameters(&change_set.get_template(cfn).await?),
+            )
+        }
+        VerifiedAgainst::Template(template) => (
+            template.get_parameters().to_owned(),
+            no_echo_parameters(template),
+        ),
+    };
+
+    let fails_on = |category: DifferenceCategory| fail_on.is_empty() || fail_on.contains(&category);
+    let severity = |category: DifferenceCategory| {
+        if fails_on(category) {
+            Severity::Error
+        } else {
+            Severity::Warning
+        }
+    };
+    let severities = DifferenceSeverities {
+        only_on_stack_or_template: severity(DifferenceCategory::Missing),
+        unequal_between_both: severity(DifferenceCategory::Unequal),
+        only_in_parameter_file: severity(DifferenceCategory::Extra),
+    };
+
+    let difference = match defined_parameters.loose_difference(file_parameters) {
+        Some(difference) => difference.into_owned(),
+        None => {
+            return Ok(ParameterFileVerification {
+                parameters: ComparedParameters {
+                    equal_between_both: defined_parameters.values().cloned().collect(),
+                    ..Default::default()
+                },
+                severities,
+                difference: None,
+                no_echo_parameters,
+                failed_category: None,
+            })
+        }
+    };
+
+    let failed_category = [
+        (DifferenceCategory::Unequal, !difference.unequal.is_empty()),
+        (DifferenceCategory::Missing, !difference.left.is_empty()),
+        (DifferenceCategory::Extra, !difference.right.is_empty()),
+    ]
+    .iter()
+    .find(|(category, present)| *present && fails_on(*category))
+    .map(|(category, _)| *category);
+
+    // Parameters masked by CloudFormation cannot be compared, which is why they are omitted from
+    // the unequal parameters.
+    let unequal_between_both = difference
+        .unequal
+        .iter()
+        .filter(|unequal| {
+            !matches!(&unequal.left, Parameter::WithValue { value, .. } if value == NO_ECHO_PARAMETER_VALUE)
+        })
+        .cloned()
+        .collect();
+
+    Ok(ParameterFileVerification {
+        parameters: ComparedParameters {
+            only_on_stack_or_template: difference.left.clone(),
+            equal_between_both: difference.equal.clone(),
+            unequal_between_both,
+            only_in_parameter_file: difference.right.clone(),
+        },
+        severities,
+        difference: Some(difference),
+        no_echo_parameters,
+        failed_category,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{self, MockDispatcher};
+    use serde_json::json;
+
+    #[test]
+    fn new_parameter_declaration_is_flattened() {
+        assert_eq!(
+            json!([
+                {
+                    "key": "Environment",
+                    "type": "String",
+                    "default": "staging",
+                    "description": null,
+                    "no_echo": false,
+                },
+                { "key": "Unknown" },
+            ]),
+            serde_json::to_value(vec![
+                NewParameter {
+                    key: "Environment".to_owned(),
+                    declaration: Some(ParameterDeclaration {
+                        parameter_type: "String".to_owned(),
+                        default: Some("staging".to_owned()),
+                        description: None,
+                        no_echo: false,
+                    }),
+                },
+                NewParameter {
+                    key: "Unknown".to_owned(),
+                    declaration: None,
+                },
+            ])
+            .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn verify_parameter_file_against_template() {
+        let template = Template::from_bytes(
+            "Parameters:\n  Environment:\n    Type: String\n  Password:\n    Type: String\n    \
+             NoEcho: true\nResources: {}\n",
+        )
+        .unwrap();
+        let file_parameters = Parameters::new(vec![
+            Parameter::with_value("Environment", "staging"),
+            Parameter::with_value("Password", "secret"),
+            Parameter::with_value("Extra", "value"),
+        ]);
+        let dispatcher = MockDispatcher::new(Vec::<String>::new());
+        let cfn = mock::cloudformation(&dispatcher);
+
+        let verification = verify_parameter_file(
+            &cfn,
+            &VerifiedAgainst::Template(template),
+            &file_parameters,
+            &[DifferenceCategory::Missing],
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(None, verification.failed_category);
+        assert_eq!(
+            vec![Parameter::with_value("Extra", "value")],
+            verification.parameters.only_in_parameter_file
+        );
+        assert_eq!(
+            Severity::Warning,
+            verification.severities.only_in_parameter_file
+        );
+        assert_eq!(vec!["Password".to_owned()], verification.no_echo_parameters);
+        assert!(dispatcher.actions().is_empty());
+    }
+}
@@ -0,0 +1,271 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Commands inspecting whole stacks.
+
+use indexmap::IndexMap;
+use owo_colors::OwoColorize;
+use rusoto_cloudformation::CloudFormation;
+use serde::Serialize;
+use similar::TextDiff;
+
+use crate::{
+    error::Error,
+    parameter::{DifferenceFormat, Parameter, ParametersDifference, UnequalParameter},
+    stack::Stack,
+};
+
+/// The difference between two maps of plain values, e.g. stack tags or outputs.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ValuesDifference {
+    /// Values only in the *left* map.
+    pub only_left: IndexMap<String, String>,
+    /// Values in both maps, which are unequal, as pairs of the *left* and *right* value.
+    pub unequal: IndexMap<String, (String, String)>,
+    /// Values only in the *right* map.
+    pub only_right: IndexMap<String, String>,
+}
+
+impl ValuesDifference {
+    /// Compare the *left* with the *right* values.
+    pub fn new(left: &IndexMap<String, String>, right: &IndexMap<String, String>) -> Self {
+        let mut difference = ValuesDifference::default();
+        for (key, value) in left {
+            match right.get(key) {
+                None => {
+                    difference.only_left.insert(key.clone(), value.clone());
+                }
+                Some(other) if other != value => {
+                    difference
+                        .unequal
+                        .insert(key.clone(), (value.clone(), other.clone()));
+                }
+                Some(_) => {}
+            }
+        }
+        for (key, value) in right {
+            if !left.contains_key(key) {
+                difference.only_right.insert(key.clone(), value.clone());
+            }
+        }
+        difference
+    }
+
+    /// Check whether the values are equal.
+    pub fn is_empty(&self) -> bool {
+        self.only_left.is_empty() && self.unequal.is_empty() && self.only_right.is_empty()
+    }
+
+    /// Render the difference the same way as [`ParametersDifference::render`] renders the
+    /// [`DifferenceFormat::Text`] format.
+    ///
+    /// [`ParametersDifference::render`]: ../../../parameter/struct.ParametersDifference.html#method.render
+    /// [`DifferenceFormat::Text`]: ../../../parameter/enum.DifferenceFormat.html#variant.Text
+    pub fn render(&self, colorize: bool) -> String {
+        let mut lines = Vec::new();
+        for (key, value) in &self.only_left {
+            let line = format!("- {}: {}", key, value);
+            lines.push(if colorize {
+                line.red().to_string()
+            } else {
+                line
+            });
+        }
+        for (key, (left, right)) in &self.unequal {
+            let line = format!("~ {}: {} -> {}", key, left, right);
+            lines.push(if colorize {
+                line.yellow().to_string()
+            } else {
+                line
+            });
+        }
+        for (key, value) in &self.only_right {
+            let line = format!("+ {}: {}", key, value);
+            lines.push(if colorize {
+                line.green().to_string()
+            } else {
+                line
+            });
+        }
+
+        lines.join("\n")
+    }
+}
+
+/// The comparison of the templates of two stacks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TemplateComparison {
+    /// Whether the templates are equal.
+    pub equal: bool,
+    /// Unified diff of the templates, empty if they are equal.
+    pub diff: String,
+}
+
+/// The differences between the parameters of two stacks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ParametersComparison {
+    /// Parameters only on the first stack.
+    pub only_on_stack: Vec<Parameter>,
+    /// Parameters on both stacks, with unequal values.
+    pub unequal_between_both: Vec<UnequalParameter>,
+    /// Parameters only on the other stack.
+    pub only_on_other_stack: Vec<Parameter>,
+}
+
+impl ParametersComparison {
+    /// Render the differences in the [`DifferenceFormat::Text`] format, see
+    /// [`ParametersDifference::render`].
+    ///
+    /// [`DifferenceFormat::Text`]: ../../../parameter/enum.DifferenceFormat.html#variant.Text
+    /// [`ParametersDifference::render`]: ../../../parameter/struct.ParametersDifference.html#method.render
+    pub fn render(&self, colorize: bool) -> String {
+        ParametersDifference {
+            left: self.only_on_stack.iter().collect(),
+            equal: Vec::new(),
+            unequal: self
+                .unequal_between_both
+                .iter()
+                .map(|unequal| (&unequal.left, &unequal.right))
+                .collect(),
+            right: self.only_on_other_stack.iter().collect(),
+        }
+        .render(DifferenceFormat::Text, |_| false, colorize)
+    }
+}
+
+/// The comparison of two stacks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StackComparison {
+    /// Name of the first stack.
+    pub stack_name: String,
+    /// Name of the stack compared against.
+    pub other_stack_name: String,
+    /// The comparison of the templates.
+    pub template: TemplateComparison,
+    /// The differences between the parameters, if there are any.
+    pub parameters: Option<ParametersComparison>,
+    /// The differences between the tags.
+    pub tags: ValuesDifference,
+    /// The differences between the values of the outputs.
+    pub outputs: ValuesDifference,
+}
+
+impl StackComparison {
+    /// Check whether the stacks are equal in their template, parameters, tags and outputs.
+    pub fn is_equal(&self) -> bool {
+        self.template.equal
+            && self.parameters.is_none()
+            && self.tags.is_empty()
+            && self.outputs.is_empty()
+    }
+}
+
+/// Compare two stacks in their template, parameters, tags and outputs.
+///
+/// The stacks are accessed through their own clients, so the other stack can reside in another
+/// account or region.
+pub async fn compare_stacks(
+    cfn: &dyn CloudFormation,
+    stack_name: &str,
+    other_cfn: &dyn CloudFormation,
+    other_stack_name: &str,
+) -> Result<StackComparison, Error> {
+    let stack = Stack::new(stack_name);
+    let other_stack = Stack::new(other_stack_name);
+
+    // Compare the templates line by line.
+    let template = stack.get_template(cfn).await?;
+    let other_template = other_stack.get_template(other_cfn).await?;
+    let template = String::from_utf8_lossy(template.contents());
+    let other_template = String::from_utf8_lossy(other_template.contents());
+    let template_diff = TextDiff::from_lines(template.as_ref(), other_template.as_ref())
+        .unified_diff()
+        .header(stack_name, other_stack_name)
+        .to_string();
+
+    let parameters = stack.get_parameters(cfn).await?;
+    let other_parameters = other_stack.get_parameters(other_cfn).await?;
+    let parameters = parameters
+        .loose_difference(&other_parameters)
+        .map(ParametersDifference::into_owned)
+        .map(|difference| ParametersComparison {
+            only_on_stack: difference.left,
+            unequal_between_both: difference.unequal,
+            only_on_other_stack: difference.right,
+        });
+
+    Ok(StackComparison {
+        stack_name: stack_name.to_owned(),
+        other_stack_name: other_stack_name.to_owned(),
+        template: TemplateComparison {
+            equal: template_diff.is_empty(),
+            diff: template_diff,
+        },
+        parameters,
+        tags: ValuesDifference::new(
+            &stack.get_tags(cfn).await?,
+            &other_stack.get_tags(other_cfn).await?,
+        ),
+        outputs: ValuesDifference::new(
+            &output_values(&stack, cfn).await?,
+            &output_values(&other_stack, other_cfn).await?,
+        ),
+    })
+}
+
+/// Get the values of the outputs of the stack, keyed by their output key.
+async fn output_values(
+    stack: &Stack,
+    cfn: &dyn CloudFormation,
+) -> Result<IndexMap<String, String>, Error> {
+    Ok(stack
+        .get_outputs(cfn)
+        .await?
+        .into_iter()
+        .map(|(key, output)| (key, output.value.unwrap_or_default()))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_difference_between_maps() {
+        let left: IndexMap<String, String> = vec![
+            ("Environment".to_owned(), "staging".to_owned()),
+            ("Owner".to_owned(), "team".to_owned()),
+            ("Removed".to_owned(), "value".to_owned()),
+        ]
+        .into_iter()
+        .collect();
+        let right: IndexMap<String, String> = vec![
+            ("Environment".to_owned(), "production".to_owned()),
+            ("Owner".to_owned(), "team".to_owned()),
+            ("Added".to_owned(), "value".to_owned()),
+        ]
+        .into_iter()
+        .collect();
+
+        let difference = ValuesDifference::new(&left, &right);
+        assert!(!difference.is_empty());
+        assert_eq!(
+            "- Removed: value\n~ Environment: staging -> production\n+ Added: value",
+            difference.render(false)
+        );
+        assert!(ValuesDifference::new(&left, &left).is_empty());
+    }
+}
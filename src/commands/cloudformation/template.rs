@@ -0,0 +1,587 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Commands operating on templates, either local ones or those uploaded to S3.
+
+use chrono::{DateTime, Duration, Utc};
+use futures::{future, TryStreamExt};
+use rusoto_cloudformation::{CloudFormation, GetTemplateInput};
+use rusoto_core::Region;
+use rusoto_s3::{Delete, DeleteObjectsRequest, ListObjectsV2Request, Object, ObjectIdentifier, S3};
+use serde::Serialize;
+use std::{collections::HashSet, convert::TryFrom, path::Path};
+
+use crate::{
+    changeset::ChangeSet,
+    deployment::DeploymentMetadata,
+    error::Error,
+    package,
+    pagination::{paginate, Page},
+    parameter::Parameter,
+    s3::{ChecksumAlgorithm, S3Uploader},
+    stack::Stack,
+    template::{ResourceDependency, Template, TemplateLintReport},
+};
+
+/// Maximum number of objects a single `DeleteObjects` request can delete.
+const DELETE_OBJECTS_BATCH_SIZE: usize = 1000;
+
+/// Lint the template, see [`Template::lint`].
+///
+/// [`Template::lint`]: ../../../template/struct.Template.html#method.lint
+pub fn lint_template(template: &Template) -> Result<TemplateLintReport, Error> {
+    template.lint()
+}
+
+/// A resource of the graph output by `template-graph`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GraphResource {
+    /// Logical ID of the resource.
+    pub logical_id: String,
+    /// Type of the resource, e.g. `AWS::S3::Bucket`.
+    #[serde(rename = "type")]
+    pub resource_type: String,
+}
+
+/// The resources of a template and the dependencies between them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TemplateGraph {
+    /// The resources of the graph.
+    pub resources: Vec<GraphResource>,
+    /// Dependencies between the resources.
+    pub dependencies: Vec<ResourceDependency>,
+    /// The graph in the DOT language of Graphviz.
+    #[serde(skip)]
+    pub dot: String,
+}
+
+/// Build the graph of the resources of the template and the dependencies between them.
+///
+/// If a logical resource ID is given, the graph only includes that resource and all resources
+/// that directly or transitively depend on it. Fails if the template has no such resource.
+pub fn template_graph(
+    template: &Template,
+    logical_resource_id: Option<&str>,
+) -> Result<TemplateGraph, Error> {
+    let mut graph = template.resource_graph()?;
+
+    if let Some(logical_resource_id) = logical_resource_id {
+        if !graph.resources.contains_key(logical_resource_id) {
+            return Err(Error::ResourceNotFound(format!(
+                "resource {} in the template",
+                logical_resource_id
+            )));
+        }
+        let mut logical_ids = graph.dependents(logical_resource_id);
+        logical_ids.insert(logical_resource_id.to_owned());
+        graph = graph.restricted_to(&logical_ids);
+    }
+
+    Ok(TemplateGraph {
+        dot: graph.to_dot(),
+        resources: graph
+            .resources
+            .into_iter()
+            .map(|(logical_id, resource_type)| GraphResource {
+                logical_id,
+                resource_type,
+            })
+            .collect(),
+        dependencies: graph.dependencies,
+    })
+}
+
+/// A template whose local artifacts were uploaded to S3.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PackagedTemplate {
+    /// Path the caller wrote the packaged template to, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_template_path: Option<String>,
+    /// Contents of the packaged template, unless the caller wrote it to a file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+}
+
+/// Package the template, uploading all local artifacts it references to the S3 bucket.
+///
+/// The artifacts are resolved relative to `base_dir`. The packaged template is returned in
+/// `template`; a caller writing it to a file takes it from there and records the path instead.
+pub async fn package_template(
+    s3: &S3Uploader,
+    template: &Template,
+    base_dir: &Path,
+    bucket_name: &str,
+) -> Result<PackagedTemplate, Error> {
+    let packaged = package::package_template(template, base_dir, s3, bucket_name).await?;
+
+    Ok(PackagedTemplate {
+        output_template_path: None,
+        template: Some(String::from_utf8_lossy(packaged.contents()).into_owned()),
+    })
+}
+
+/// A template in the bucket that no stack or change set references.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UnreferencedTemplate {
+    /// Key of the template.
+    pub key: String,
+    /// Time the template was last modified.
+    pub last_modified: DateTime<Utc>,
+    /// Size of the template in bytes.
+    pub size: i64,
+}
+
+fn is_template_key(key: &str) -> bool {
+    key.strip_suffix(".template").is_some_and(|checksum| {
+        (checksum.len() == 32 || checksum.len() == 64)
+            && checksum.chars().all(|c| c.is_ascii_hexdigit())
+    })
+}
+
+fn template_object(object: Object) -> Option<UnreferencedTemplate> {
+    let key = object.key?;
+    let last_modified = DateTime::parse_from_rfc3339(&object.last_modified?)
+        .ok()?
+        .with_timezone(&Utc);
+    Some(UnreferencedTemplate {
+        key,
+        last_modified,
+        size: object.size.unwrap_or_default(),
+    })
+}
+
+fn template_keys(template: &Template) -> Result<Vec<String>, Error> {
+    [ChecksumAlgorithm::Md5, ChecksumAlgorithm::Sha256]
+        .iter()
+        .map(|algorithm| template.s3_key(*algorithm))
+        .collect()
+}
+
+fn object_key(url: &str, bucket_name: &str) -> Option<String> {
+    let (host, path) = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))?
+        .split_once('/')?;
+    let key = if host.starts_with(&format!("{}.s3.", bucket_name)) {
+        path
+    } else if host.starts_with("s3.") || host.starts_with("s3-") {
+        path.strip_prefix(bucket_name)?.strip_prefix('/')?
+    } else {
+        return None;
+    };
+    Some(key.split('?').next().unwrap_or(key).to_owned())
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum RecordedTemplate {
+    /// The template was uploaded to the bucket with the key.
+    InBucket(String),
+    /// The template was submitted inline or uploaded to another bucket.
+    Elsewhere,
+    /// The deployment metadata does not record the template.
+    Unknown,
+}
+
+impl RecordedTemplate {
+    fn from_metadata(metadata_parameter: Option<Parameter>, bucket_name: &str) -> Self {
+        let applied = metadata_parameter
+            .and_then(|parameter| DeploymentMetadata::try_from(parameter).ok())
+            .and_then(|metadata| metadata.applied);
+        match applied {
+            None => RecordedTemplate::Unknown,
+            Some(applied) => match applied
+                .template_url
+                .and_then(|template_url| object_key(&template_url, bucket_name))
+            {
+                Some(key) => RecordedTemplate::InBucket(key),
+                None => RecordedTemplate::Elsewhere,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct References {
+    /// Keys recorded in the deployment metadata.
+    recorded: HashSet<String>,
+    /// Keys derived from the checksums of templates, whose key is not recorded.
+    by_checksum: HashSet<String>,
+}
+
+impl References {
+    fn contains(&self, key: &str) -> bool {
+        self.recorded.contains(key) || self.by_checksum.contains(key)
+    }
+
+    fn len(&self) -> usize {
+        self.recorded.union(&self.by_checksum).count()
+    }
+
+    /// Add the template of a stack or change set, deriving its keys from the template only if the
+    /// deployment metadata does not record it.
+    async fn add<F>(&mut self, recorded: RecordedTemplate, template: F) -> Result<(), Error>
+    where
+        F: std::future::Future<Output = Result<Option<Template>, Error>>,
+    {
+        match recorded {
+            RecordedTemplate::InBucket(key) => {
+                self.recorded.insert(key);
+            }
+            RecordedTemplate::Elsewhere => {}
+            RecordedTemplate::Unknown => {
+                if let Some(template) = template.await? {
+                    self.by_checksum.extend(template_keys(&template)?);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn extend(&mut self, other: References) {
+        self.recorded.extend(other.recorded);
+        self.by_checksum.extend(other.by_checksum);
+    }
+}
+
+async fn referenced_templates(
+    cfn: &dyn CloudFormation,
+    bucket_name: &str,
+    metadata_key: &str,
+) -> Result<References, Error> {
+    let mut references = References::default();
+    for summary in Stack::list(cfn).await? {
+        let stack = Stack::new(&summary.stack_name);
+        // A stack created through a change set that was never executed has no template yet.
+        if summary.stack_status != "REVIEW_IN_PROGRESS" {
+            let recorded = RecordedTemplate::from_metadata(
+                stack.get_parameter(cfn, metadata_key).await?,
+                bucket_name,
+            );
+            references
+                .add(recorded, async { stack.get_template(cfn).await.map(Some) })
+                .await?;
+        }
+
+        for change_set in stack.list_change_sets(cfn).await? {
+            let change_set_name = change_set.change_set_name.as_deref().unwrap_or_default();
+            let description = ChangeSet::new(&summary.stack_name, change_set_name)
+                .describe(cfn)
+                .await?;
+            let recorded = RecordedTemplate::from_metadata(
+                description.parameters.get(metadata_key).cloned(),
+                bucket_name,
+            );
+            references
+                .add(recorded, async {
+                    let output = cfn
+                        .get_template(GetTemplateInput {
+                            stack_name: Some(summary.stack_name.clone()),
+                            change_set_name: change_set.change_set_id.clone(),
+                            template_stage: Some("Original".to_owned()),
+                        })
+                        .await?;
+                    output.template_body.map(Template::from_bytes).transpose()
+                })
+                .await?;
+        }
+    }
+    Ok(references)
+}
+
+/// A template that could not be deleted by `prune-templates`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FailedDeletion {
+    /// Key of the template.
+    pub key: String,
+    /// Why the template could not be deleted.
+    pub message: String,
+}
+
+/// A CloudFormation client together with the region it was created for.
+pub struct RegionalCloudFormation<'a> {
+    /// The CloudFormation client.
+    pub cfn: &'a dyn CloudFormation,
+    /// The region of the client.
+    pub region: Region,
+}
+
+/// Describes which templates `prune-templates` deletes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplatePruning {
+    /// Name of the bucket the templates were uploaded to.
+    pub bucket_name: String,
+    /// Name of the parameter holding the deployment metadata of the stacks and change sets.
+    pub deployment_metadata_parameter: String,
+    /// Minimum age in days of the templates to delete.
+    pub retention_days: i64,
+    /// Delete the unreferenced templates instead of only finding them.
+    pub delete: bool,
+}
+
+/// The templates found, and possibly deleted, by `prune-templates`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PrunedTemplates {
+    /// Name of the bucket the templates were uploaded to.
+    pub bucket_name: String,
+    /// Regions whose stacks and change sets were checked for references to the templates.
+    pub regions: Vec<String>,
+    /// Whether the unreferenced templates were only found, but not deleted.
+    pub dry_run: bool,
+    /// Number of templates referenced by any stack or change set.
+    pub referenced_templates: usize,
+    /// Templates no stack or change set references, which are older than the retention.
+    pub unreferenced_templates: Vec<UnreferencedTemplate>,
+    /// The unreferenced templates that failed to be deleted.
+    pub failed: Vec<FailedDeletion>,
+}
+
+/// Find the templates in the bucket that no stack or change set in any of the regions references,
+/// and which are older than the retention, deleting them if requested.
+///
+/// The referenced templates are determined from the template URL recorded in the deployment
+/// metadata of the stacks and change sets, falling back to the checksum of the template if no URL
+/// is recorded. Every unreferenced template is passed to `on_unreferenced` as soon as the page it
+/// appears on was retrieved.
+pub async fn prune_templates<F: FnMut(&UnreferencedTemplate)>(
+    s3: &dyn S3,
+    cloudformations: &[RegionalCloudFormation<'_>],
+    pruning: &TemplatePruning,
+    mut on_unreferenced: F,
+) -> Result<PrunedTemplates, Error> {
+    let bucket_name = &pruning.bucket_name;
+    let mut referenced = References::default();
+    for cloudformation in cloudformations {
+        referenced.extend(
+            referenced_templates(
+                cloudformation.cfn,
+                bucket_name,
+                &pruning.deployment_metadata_parameter,
+            )
+            .await?,
+        );
+    }
+
+    let cutoff = Utc::now() - Duration::days(pruning.retention_days);
+    let unreferenced: Vec<UnreferencedTemplate> = paginate(|continuation_token| {
+        let request = ListObjectsV2Request {
+            bucket: bucket_name.clone(),
+            continuation_token,
+            ..Default::default()
+        };
+        async move {
+            s3.list_objects_v2(request)
+                .await
+                .map(|output| Page::new(output.contents, output.next_continuation_token))
+        }
+    })
+    .try_filter_map(|object| future::ready(Ok(template_object(object))))
+    .try_filter(|template| {
+        future::ready(
+            is_template_key(&template.key)
+                && !referenced.contains(&template.key)
+                && template.last_modified < cutoff,
+        )
+    })
+    .inspect_ok(|template| on_unreferenced(template))
+    .try_collect()
+    .await?;
+
+    let mut failed = Vec::new();
+    if pruning.delete {
+        for batch in unreferenced.chunks(DELETE_OBJECTS_BATCH_SIZE) {
+            let output = s3
+                .delete_objects(DeleteObjectsRequest {
+                    bucket: bucket_name.clone(),
+                    delete: Delete {
+                        objects: batch
+                            .iter()
+                            .map(|template| ObjectIdentifier {
+                                key: template.key.clone(),
+                                version_id: None,
+                            })
+                            .collect(),
+                        quiet: Some(true),
+                    },
+                    ..Default::default()
+                })
+                .await?;
+            failed.extend(output.errors.unwrap_or_default().into_iter().map(|error| {
+                FailedDeletion {
+                    key: error.key.unwrap_or_default(),
+                    message: error.message.unwrap_or_default(),
+                }
+            }));
+        }
+    }
+
+    Ok(PrunedTemplates {
+        bucket_name: bucket_name.clone(),
+        regions: cloudformations
+            .iter()
+            .map(|cloudformation| cloudformation.region.name().to_owned())
+            .collect(),
+        dry_run: !pruning.delete,
+        referenced_templates: referenced.len(),
+        unreferenced_templates: unreferenced,
+        failed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn template_key_naming_scheme() {
+        assert!(is_template_key("5d41402abc4b2a76b9719d911017c592.template"));
+        assert!(is_template_key(
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824.template"
+        ));
+        assert!(!is_template_key("5d41402abc4b2a76b9719d911017c592.zip"));
+        assert!(!is_template_key("templates/stack.template"));
+        assert!(!is_template_key(".template"));
+    }
+
+    #[test]
+    fn object_key_from_template_urls() {
+        assert_eq!(
+            Some("abc.template".to_owned()),
+            object_key(
+                "https://my-bucket.s3.eu-central-1.amazonaws.com/abc.template",
+                "my-bucket"
+            )
+        );
+        assert_eq!(
+            Some("abc.template".to_owned()),
+            object_key(
+                "https://s3.eu-central-1.amazonaws.com/my.bucket/abc.template",
+                "my.bucket"
+            )
+        );
+        assert_eq!(
+            Some("abc.template".to_owned()),
+            object_key(
+                "https://s3-eu-west-1.amazonaws.com/my-bucket/abc.template?versionId=1",
+                "my-bucket"
+            )
+        );
+        assert_eq!(
+            None,
+            object_key(
+                "https://other-bucket.s3.eu-central-1.amazonaws.com/abc.template",
+                "my-bucket"
+            )
+        );
+        assert_eq!(
+            None,
+            object_key(
+                "https://s3.eu-central-1.amazonaws.com/my-bucket-2/abc.template",
+                "my-bucket"
+            )
+        );
+        assert_eq!(
+            None,
+            object_key("https://my-bucket.example.com/abc.template", "my-bucket")
+        );
+        assert_eq!(None, object_key("abc.template", "my-bucket"));
+    }
+
+    fn metadata_parameter(value: &str) -> Option<Parameter> {
+        Some(Parameter::WithValue {
+            key: "DeploymentMetadata".to_owned(),
+            value: value.to_owned(),
+        })
+    }
+
+    #[test]
+    fn recorded_template_from_deployment_metadata() {
+        let base = r#""user":"me","when":"now","git":{"commit":"","ref":"","dirty":false}"#;
+        assert_eq!(
+            RecordedTemplate::InBucket("abc.template".to_owned()),
+            RecordedTemplate::from_metadata(
+                metadata_parameter(&format!(
+                    r#"{{{},"applied":{{"template_url":"https://my-bucket.s3.eu-central-1.amazonaws.com/abc.template","parameters":{{}}}}}}"#,
+                    base
+                )),
+                "my-bucket"
+            )
+        );
+        assert_eq!(
+            RecordedTemplate::Elsewhere,
+            RecordedTemplate::from_metadata(
+                metadata_parameter(&format!(
+                    r#"{{{},"applied":{{"template_url":"https://other.s3.eu-central-1.amazonaws.com/abc.template","parameters":{{}}}}}}"#,
+                    base
+                )),
+                "my-bucket"
+            )
+        );
+        assert_eq!(
+            RecordedTemplate::Elsewhere,
+            RecordedTemplate::from_metadata(
+                metadata_parameter(&format!(r#"{{{},"applied":{{"parameters":{{}}}}}}"#, base)),
+                "my-bucket"
+            )
+        );
+        assert_eq!(
+            RecordedTemplate::Unknown,
+            RecordedTemplate::from_metadata(
+                metadata_parameter(&format!("{{{}}}", base)),
+                "my-bucket"
+            )
+        );
+        assert_eq!(
+            RecordedTemplate::Unknown,
+            RecordedTemplate::from_metadata(None, "my-bucket")
+        );
+    }
+
+    #[tokio::test]
+    async fn checksums_only_match_unrecorded_templates() {
+        let template = || Template::from_bytes("Resources: {}\n");
+        let checksum_key = template().unwrap().s3_key(ChecksumAlgorithm::Md5).unwrap();
+        let mut references = References::default();
+
+        references
+            .add(
+                RecordedTemplate::InBucket("recorded.template".to_owned()),
+                async { panic!("the template of a recorded key must not be retrieved") },
+            )
+            .await
+            .unwrap();
+        references
+            .add(RecordedTemplate::Elsewhere, async {
+                panic!("the template of a template elsewhere must not be retrieved")
+            })
+            .await
+            .unwrap();
+        assert!(references.contains("recorded.template"));
+        assert!(!references.contains(&checksum_key));
+
+        references
+            .add(RecordedTemplate::Unknown, async { template().map(Some) })
+            .await
+            .unwrap();
+        assert!(references.contains(&checksum_key));
+        assert!(references.contains(
+            &template()
+                .unwrap()
+                .s3_key(ChecksumAlgorithm::Sha256)
+                .unwrap()
+        ));
+        assert_eq!(3, references.len());
+    }
+}
@@ -0,0 +1,323 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Commands operating on CloudFront distributions.
+//!
+//! CloudFront is a global service whose API is only available in `us-east-1`, so the client passed
+//! to these commands has to be created for that region.
+
+use futures::stream::{FuturesOrdered, TryStreamExt};
+use itertools::Itertools;
+use rusoto_cloudfront::{
+    CloudFront, CreateInvalidationRequest, DistributionSummary, GetDistributionRequest,
+    GetInvalidationRequest, InvalidationBatch, ListDistributionsRequest,
+    ListTagsForResourceRequest, Paths,
+};
+use serde::Serialize;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::{
+    error::Error,
+    pagination::{paginate, Page},
+    tags::TagFilter,
+};
+
+/// Interval in which the status of an invalidation is polled while waiting.
+const INVALIDATION_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Interval in which the status of a distribution is polled while waiting.
+const DISTRIBUTION_POLL_INTERVAL: Duration = Duration::from_secs(20);
+
+/// Selects a single CloudFront distribution.
+#[derive(Debug, Clone)]
+pub enum DistributionSelector {
+    /// Select the distribution by its ID.
+    Id(String),
+    /// Select the only distribution matching the tag filter.
+    Tags(TagFilter),
+}
+
+impl DistributionSelector {
+    /// Find the ID of the selected CloudFront distribution.
+    ///
+    /// Fails if no distribution matches the tags, or if the tags match more than one distribution.
+    pub async fn find_id(&self, cloudfront: &dyn CloudFront) -> Result<String, Error> {
+        let tag_filter = match self {
+            DistributionSelector::Id(distribution_id) => return Ok(distribution_id.clone()),
+            DistributionSelector::Tags(tag_filter) => tag_filter,
+        };
+
+        let distributions = find_distributions(cloudfront, tag_filter).await?;
+        match distributions.len() {
+            0 => Err(Error::ResourceNotFound(
+                "a CloudFront distribution matching given filters".to_owned(),
+            )),
+            1 => Ok(distributions
+                .into_iter()
+                .next()
+                .expect("CloudFront distribution should exist")
+                .id),
+            _ => Err(Error::AmbiguousResource(format!(
+                "the tags match multiple CloudFront distributions: {}",
+                distributions
+                    .iter()
+                    .map(|distribution| &distribution.id)
+                    .join(", ")
+            ))),
+        }
+    }
+}
+
+/// Find all CloudFront distributions whose tags match the filter.
+pub async fn find_distributions(
+    cloudfront: &dyn CloudFront,
+    tag_filter: &TagFilter,
+) -> Result<Vec<DistributionSummary>, Error> {
+    let distributions: Vec<DistributionSummary> = paginate(|marker| async move {
+        let output = cloudfront
+            .list_distributions(ListDistributionsRequest {
+                marker,
+                ..Default::default()
+            })
+            .await?;
+        Ok::<_, Error>(match output.distribution_list {
+            Some(distribution_list) => {
+                Page::new(distribution_list.items, distribution_list.next_marker)
+            }
+            None => Page::new(None, None),
+        })
+    })
+    .try_collect()
+    .await?;
+
+    Ok(distributions
+        .into_iter()
+        .map(|distribution| async {
+            cloudfront
+                .list_tags_for_resource(ListTagsForResourceRequest {
+                    resource: distribution.arn.clone(),
+                })
+                .await
+                .map(|tags| (distribution, tags.tags.items))
+        })
+        .collect::<FuturesOrdered<_>>()
+        .try_collect::<Vec<_>>()
+        .await?
+        .into_iter()
+        .filter(|(_, resource_tags)| {
+            tag_filter.matches(resource_tags.as_deref().unwrap_or_default())
+        })
+        .map(|(distribution, _)| distribution)
+        .collect())
+}
+
+/// A CloudFront distribution found by `find-cloudfront-distribution`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FoundDistribution {
+    /// ARN of the distribution.
+    pub cloudfront_distribution_arn: String,
+    /// ID of the distribution.
+    pub cloudfront_distribution_id: String,
+}
+
+/// Find the first CloudFront distribution whose tags match the filter.
+pub async fn find_cloudfront_distribution(
+    cloudfront: &dyn CloudFront,
+    tag_filter: &TagFilter,
+) -> Result<Option<FoundDistribution>, Error> {
+    Ok(find_distributions(cloudfront, tag_filter)
+        .await?
+        .into_iter()
+        .next()
+        .map(|distribution| FoundDistribution {
+            cloudfront_distribution_arn: distribution.arn,
+            cloudfront_distribution_id: distribution.id,
+        }))
+}
+
+/// The status of an invalidation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InvalidationStatus {
+    /// ID of the invalidated distribution.
+    pub cloudfront_distribution_id: String,
+    /// ID of the invalidation.
+    pub invalidation_id: String,
+    /// Status of the invalidation, `InProgress` or `Completed`.
+    pub status: String,
+}
+
+/// Invalidate the given paths of the selected CloudFront distribution.
+///
+/// This does not wait for the invalidation to complete, use [`wait_for_invalidation`] for that.
+///
+/// [`wait_for_invalidation`]: fn.wait_for_invalidation.html
+pub async fn create_cloudfront_invalidation(
+    cloudfront: &dyn CloudFront,
+    selector: &DistributionSelector,
+    paths: &[String],
+) -> Result<InvalidationStatus, Error> {
+    let distribution_id = selector.find_id(cloudfront).await?;
+    let invalidation = cloudfront
+        .create_invalidation(CreateInvalidationRequest {
+            distribution_id: distribution_id.clone(),
+            invalidation_batch: InvalidationBatch {
+                // The caller reference only has to be unique per invalidation.
+                caller_reference: format!("awsx-{}", Uuid::new_v4()),
+                paths: Paths {
+                    items: Some(paths.to_vec()),
+                    quantity: paths.len() as i64,
+                },
+            },
+        })
+        .await?
+        .invalidation
+        .ok_or_else(|| {
+            Error::ResourceNotFound(format!("invalidation of distribution {}", distribution_id))
+        })?;
+
+    Ok(InvalidationStatus {
+        cloudfront_distribution_id: distribution_id,
+        invalidation_id: invalidation.id,
+        status: invalidation.status,
+    })
+}
+
+/// Wait until the invalidation has completed, returning its final status.
+pub async fn wait_for_invalidation(
+    cloudfront: &dyn CloudFront,
+    distribution_id: &str,
+    invalidation_id: &str,
+) -> Result<String, Error> {
+    loop {
+        let status = cloudfront
+            .get_invalidation(GetInvalidationRequest {
+                distribution_id: distribution_id.to_owned(),
+                id: invalidation_id.to_owned(),
+            })
+            .await?
+            .invalidation
+            .map(|invalidation| invalidation.status)
+            .unwrap_or_default();
+        if status == "Completed" {
+            return Ok(status);
+        }
+        tokio::time::sleep(INVALIDATION_POLL_INTERVAL).await;
+    }
+}
+
+/// The progress of a distribution that is being deployed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DistributionProgress {
+    /// Status of the distribution, e.g. `InProgress`.
+    pub status: String,
+    /// Number of invalidations that are in progress.
+    pub in_progress_invalidation_batches: i64,
+}
+
+/// The status of a deployed distribution.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DeployedDistribution {
+    /// ID of the distribution.
+    pub cloudfront_distribution_id: String,
+    /// Status of the distribution, always `Deployed`.
+    pub status: String,
+}
+
+/// Wait until the distribution has the status `Deployed`.
+///
+/// The progress is passed to `on_progress` every time the distribution is polled while it is not
+/// deployed yet.
+pub async fn wait_for_cloudfront_distribution<F: FnMut(&DistributionProgress)>(
+    cloudfront: &dyn CloudFront,
+    distribution_id: &str,
+    mut on_progress: F,
+) -> Result<DeployedDistribution, Error> {
+    loop {
+        let distribution = cloudfront
+            .get_distribution(GetDistributionRequest {
+                id: distribution_id.to_owned(),
+            })
+            .await?
+            .distribution
+            .ok_or_else(|| {
+                Error::ResourceNotFound(format!("CloudFront distribution {}", distribution_id))
+            })?;
+        if distribution.status == "Deployed" {
+            return Ok(DeployedDistribution {
+                cloudfront_distribution_id: distribution_id.to_owned(),
+                status: distribution.status,
+            });
+        }
+
+        on_progress(&DistributionProgress {
+            status: distribution.status,
+            in_progress_invalidation_batches: distribution.in_progress_invalidation_batches,
+        });
+        tokio::time::sleep(DISTRIBUTION_POLL_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{self, MockDispatcher};
+
+    fn tags_response(environment: &str) -> String {
+        format!(
+            "<Tags><Items><Tag><Key>Environment</Key><Value>{}</Value></Tag></Items></Tags>",
+            environment
+        )
+    }
+
+    fn mock_distributions(environments: [&str; 2]) -> MockDispatcher {
+        MockDispatcher::new(vec![
+            "<DistributionList><IsTruncated>false</IsTruncated><Quantity>2</Quantity><Items>\
+             <DistributionSummary><ARN>arn:aws:cloudfront::123456789012:distribution/E1</ARN>\
+             <Id>E1</Id></DistributionSummary>\
+             <DistributionSummary><ARN>arn:aws:cloudfront::123456789012:distribution/E2</ARN>\
+             <Id>E2</Id></DistributionSummary>\
+             </Items></DistributionList>"
+                .to_owned(),
+            tags_response(environments[0]),
+            tags_response(environments[1]),
+        ])
+    }
+
+    #[tokio::test]
+    async fn selector_requires_exactly_one_matching_distribution() {
+        let tag_filter = TagFilter::new(vec!["Environment=prod".parse().unwrap()]);
+        let selector = DistributionSelector::Tags(tag_filter);
+
+        let dispatcher = mock_distributions(["staging", "prod"]);
+        let cloudfront = mock::cloudfront(&dispatcher);
+        assert_eq!("E2", selector.find_id(&cloudfront).await.unwrap());
+
+        let dispatcher = mock_distributions(["prod", "prod"]);
+        let cloudfront = mock::cloudfront(&dispatcher);
+        assert!(matches!(
+            selector.find_id(&cloudfront).await,
+            Err(Error::AmbiguousResource(_))
+        ));
+
+        let dispatcher = mock_distributions(["staging", "staging"]);
+        let cloudfront = mock::cloudfront(&dispatcher);
+        assert!(matches!(
+            selector.find_id(&cloudfront).await,
+            Err(Error::ResourceNotFound(_))
+        ));
+    }
+}
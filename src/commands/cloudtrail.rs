@@ -0,0 +1,119 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+//! Commands looking up who changed resources through CloudTrail.
+
+use chrono::{Duration, SecondsFormat, Utc};
+use serde::Serialize;
+
+use crate::{
+    cloudtrail::{CloudTrailClient, TrailEvent},
+    error::Error,
+};
+
+/// The CloudFormation API actions that change a stack.
+const MUTATING_EVENT_NAMES: &[&str] = &["UpdateStack", "ExecuteChangeSet", "SetStackPolicy"];
+
+/// Check whether the event concerns the stack, which is referenced by either its name or its ARN.
+fn concerns_stack(event: &TrailEvent, stack_name: &str) -> bool {
+    let stack_arn_part = format!(":stack/{}/", stack_name);
+    let matches = |value: &str| value == stack_name || value.contains(&stack_arn_part);
+
+    event.resources.iter().any(|resource| matches(resource))
+        || ["stackName", "changeSetName"]
+            .iter()
+            .filter_map(|key| event.request_parameters.get(key))
+            .filter_map(|value| value.as_str())
+            .any(matches)
+}
+
+/// The changes to a stack found by `who-changed-stack`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StackChanges {
+    /// Name of the stack.
+    pub stack_name: String,
+    /// Start of the period the changes were looked up in, in RFC 3339 format.
+    pub start_time: String,
+    /// The events that changed the stack, latest first.
+    pub events: Vec<TrailEvent>,
+}
+
+/// Look up the events that changed the stack within the last `days` days.
+pub async fn who_changed_stack(
+    cloudtrail: &CloudTrailClient,
+    stack_name: &str,
+    days: i64,
+) -> Result<StackChanges, Error> {
+    let end_time = Utc::now();
+    let start_time = end_time - Duration::days(days);
+    let mut events = Vec::new();
+    for event_name in MUTATING_EVENT_NAMES {
+        events.extend(
+            cloudtrail
+                .lookup_events_by_name(event_name, start_time, end_time)
+                .await?
+                .into_iter()
+                .filter(|event| concerns_stack(event, stack_name)),
+        );
+    }
+    events.sort_by_key(|event| std::cmp::Reverse(event.event_time));
+
+    Ok(StackChanges {
+        stack_name: stack_name.to_owned(),
+        start_time: start_time.to_rfc3339_opts(SecondsFormat::Secs, true),
+        events,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn event(resources: &[&str], request_parameters: serde_json::Value) -> TrailEvent {
+        TrailEvent {
+            event_id: "0f6a".to_owned(),
+            event_name: "ExecuteChangeSet".to_owned(),
+            event_time: None,
+            username: None,
+            caller_arn: None,
+            source_ip_address: None,
+            user_agent: None,
+            resources: resources
+                .iter()
+                .map(|&resource| resource.to_owned())
+                .collect(),
+            request_parameters,
+            error_code: None,
+            error_message: None,
+        }
+    }
+
+    #[test]
+    fn event_concerns_stack_by_name_or_arn() {
+        let stack_arn = "arn:aws:cloudformation:eu-central-1:123456789012:stack/shop/0f6a";
+        assert!(concerns_stack(&event(&["shop"], json!({})), "shop"));
+        assert!(concerns_stack(&event(&[stack_arn], json!({})), "shop"));
+        assert!(concerns_stack(
+            &event(&[], json!({ "stackName": stack_arn })),
+            "shop"
+        ));
+        assert!(!concerns_stack(&event(&[stack_arn], json!({})), "sho"));
+        assert!(!concerns_stack(
+            &event(&[], json!({ "stackName": "shop-staging" })),
+            "shop"
+        ));
+    }
+}
@@ -0,0 +1,201 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Commands monitoring CloudWatch alarms, e.g. as the canary of a deployment.
+
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    cloudwatch::{AlarmState, CloudWatchClient},
+    error::Error,
+};
+
+/// Interval in which the alarms are polled while monitoring them.
+const POLL_INTERVAL: Duration = Duration::from_secs(15);
+
+/// A change of the state of an alarm observed while monitoring it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AlarmTransition {
+    /// Name of the alarm.
+    pub alarm_name: String,
+    /// State before the transition.
+    pub from: String,
+    /// State after the transition.
+    pub to: String,
+    /// Explanation of the new state.
+    pub reason: Option<String>,
+    /// When the state was updated.
+    pub updated_at: Option<String>,
+}
+
+/// The outcome of monitoring the alarms for the bake period of a canary.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CanaryReport {
+    /// Names of the alarms that were in the `ALARM` state.
+    pub fired: Vec<String>,
+    /// State transitions observed while monitoring the alarms.
+    pub transitions: Vec<AlarmTransition>,
+    /// The state of the alarms when the bake period ended.
+    pub alarms: Vec<AlarmState>,
+}
+
+impl CanaryReport {
+    /// Check whether no alarm fired.
+    pub fn is_success(&self) -> bool {
+        self.fired.is_empty()
+    }
+}
+
+/// Verify that all alarms exist, returning the states of the alarms that are firing already.
+///
+/// This is meant to be called before the change that is monitored, so that a misspelled alarm name
+/// does not go unnoticed until the bake period.
+pub async fn verify_alarms(
+    cloudwatch: &CloudWatchClient,
+    alarm_names: &[String],
+) -> Result<Vec<AlarmState>, Error> {
+    let alarm_states = cloudwatch.describe_alarms(alarm_names).await?;
+    let missing = alarm_names
+        .iter()
+        .filter(|alarm_name| {
+            !alarm_states
+                .iter()
+                .any(|alarm_state| &alarm_state.alarm_name == *alarm_name)
+        })
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+    if !missing.is_empty() {
+        return Err(Error::ResourceNotFound(format!(
+            "CloudWatch alarms {}",
+            missing.join(", ")
+        )));
+    }
+
+    Ok(alarm_states
+        .into_iter()
+        .filter(AlarmState::is_alarm)
+        .collect())
+}
+
+/// Poll the alarms for the bake period, stopping early as soon as any alarm fires.
+///
+/// Every state transition is passed to `on_transition` as it is observed.
+pub async fn bake_alarms<F: FnMut(&AlarmTransition)>(
+    cloudwatch: &CloudWatchClient,
+    alarm_names: &[String],
+    bake_time: Duration,
+    mut on_transition: F,
+) -> Result<CanaryReport, Error> {
+    let deadline = Instant::now() + bake_time;
+    let mut states: HashMap<String, AlarmState> = HashMap::new();
+    let mut transitions = Vec::new();
+    loop {
+        let alarm_states = cloudwatch.describe_alarms(alarm_names).await?;
+        for transition in record_transitions(&mut states, &alarm_states) {
+            on_transition(&transition);
+            transitions.push(transition);
+        }
+
+        let fired = fired_alarms(&alarm_states);
+        let now = Instant::now();
+        if !fired.is_empty() || now >= deadline {
+            return Ok(CanaryReport {
+                fired,
+                transitions,
+                alarms: alarm_states,
+            });
+        }
+        tokio::time::sleep(POLL_INTERVAL.min(deadline - now)).await;
+    }
+}
+
+/// Record the polled states of the alarms, returning the transitions from their previously
+/// recorded states.
+fn record_transitions(
+    states: &mut HashMap<String, AlarmState>,
+    alarm_states: &[AlarmState],
+) -> Vec<AlarmTransition> {
+    alarm_states
+        .iter()
+        .filter_map(|alarm_state| {
+            match states.insert(alarm_state.alarm_name.clone(), alarm_state.clone()) {
+                Some(previous) if previous.state != alarm_state.state => Some(AlarmTransition {
+                    alarm_name: alarm_state.alarm_name.clone(),
+                    from: previous.state,
+                    to: alarm_state.state.clone(),
+                    reason: alarm_state.reason.clone(),
+                    updated_at: alarm_state.updated_at.clone(),
+                }),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Return the names of the alarms in the `ALARM` state.
+fn fired_alarms(alarm_states: &[AlarmState]) -> Vec<String> {
+    alarm_states
+        .iter()
+        .filter(|alarm_state| alarm_state.is_alarm())
+        .map(|alarm_state| alarm_state.alarm_name.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alarm_state(alarm_name: &str, state: &str) -> AlarmState {
+        AlarmState {
+            alarm_name: alarm_name.to_owned(),
+            state: state.to_owned(),
+            reason: Some(format!("{} is {}", alarm_name, state)),
+            updated_at: None,
+        }
+    }
+
+    #[test]
+    fn bake_reports_transitions_between_polls() {
+        let mut states = HashMap::new();
+        let first = vec![
+            alarm_state("Errors", "OK"),
+            alarm_state("Latency", "INSUFFICIENT_DATA"),
+        ];
+        assert!(record_transitions(&mut states, &first).is_empty());
+        assert!(fired_alarms(&first).is_empty());
+
+        let second = vec![alarm_state("Errors", "OK"), alarm_state("Latency", "OK")];
+        let transitions = record_transitions(&mut states, &second);
+        assert_eq!(1, transitions.len());
+        assert_eq!("Latency", transitions[0].alarm_name);
+        assert_eq!("INSUFFICIENT_DATA", transitions[0].from);
+        assert_eq!("OK", transitions[0].to);
+        assert!(fired_alarms(&second).is_empty());
+
+        let third = vec![alarm_state("Errors", "ALARM"), alarm_state("Latency", "OK")];
+        let transitions = record_transitions(&mut states, &third);
+        assert_eq!(1, transitions.len());
+        assert_eq!("Errors", transitions[0].alarm_name);
+        assert_eq!("OK", transitions[0].from);
+        assert_eq!("ALARM", transitions[0].to);
+        assert_eq!(Some("Errors is ALARM"), transitions[0].reason.as_deref());
+        assert_eq!(vec!["Errors"], fired_alarms(&third));
+    }
+}
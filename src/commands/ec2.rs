@@ -0,0 +1,168 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Commands operating on EC2 instances and the AMIs they are launched from.
+
+use futures::{future, TryStreamExt};
+use rusoto_autoscaling::{Autoscaling, LaunchConfiguration, LaunchConfigurationNamesType};
+use rusoto_ec2::{
+    DescribeInstancesRequest, DescribeLaunchTemplateVersionsRequest,
+    DescribeLaunchTemplatesRequest, Ec2, Instance, LaunchTemplate, LaunchTemplateVersion,
+};
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+use crate::{
+    error::Error,
+    pagination::{paginate, Page},
+};
+
+/// Pass the AMI-IDs of all EC2 instances to `record`, page by page.
+async fn amis_inuse_by_ec2(ec2: &dyn Ec2, record: &mut impl FnMut(String)) -> Result<(), Error> {
+    paginate(|next_token| async move {
+        let output = ec2
+            .describe_instances(DescribeInstancesRequest {
+                next_token,
+                ..Default::default()
+            })
+            .await?;
+        let instances = output
+            .reservations
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|reservation| reservation.instances)
+            .flatten()
+            .collect();
+        Ok::<_, Error>(Page::new(Some(instances), output.next_token))
+    })
+    .try_for_each(|instance: Instance| {
+        if let Some(image_id) = instance.image_id {
+            record(image_id);
+        }
+        future::ready(Ok(()))
+    })
+    .await
+}
+
+/// Pass the AMI-IDs of all launch configurations to `record`, page by page.
+async fn amis_inuse_by_launchconfiguration(
+    autoscaling: &dyn Autoscaling,
+    record: &mut impl FnMut(String),
+) -> Result<(), Error> {
+    paginate(|next_token| async move {
+        let output = autoscaling
+            .describe_launch_configurations(LaunchConfigurationNamesType {
+                next_token,
+                ..Default::default()
+            })
+            .await?;
+        Ok::<_, Error>(Page::new(
+            Some(output.launch_configurations),
+            output.next_token,
+        ))
+    })
+    .try_for_each(|launch_configuration: LaunchConfiguration| {
+        record(launch_configuration.image_id);
+        future::ready(Ok(()))
+    })
+    .await
+}
+
+/// Pass the AMI-IDs of all versions of all launch templates to `record`, page by page.
+async fn amis_inuse_by_launchtemplate(
+    ec2: &dyn Ec2,
+    record: &mut impl FnMut(String),
+) -> Result<(), Error> {
+    let launch_templates: Vec<LaunchTemplate> = paginate(|next_token| async move {
+        let output = ec2
+            .describe_launch_templates(DescribeLaunchTemplatesRequest {
+                next_token,
+                ..Default::default()
+            })
+            .await?;
+        Ok::<_, Error>(Page::new(output.launch_templates, output.next_token))
+    })
+    .try_filter(|launch_template| future::ready(launch_template.launch_template_id.is_some()))
+    .try_collect()
+    .await?;
+
+    for launch_template in launch_templates {
+        let launch_template_id = &launch_template.launch_template_id;
+        paginate(|next_token| async move {
+            let output = ec2
+                .describe_launch_template_versions(DescribeLaunchTemplateVersionsRequest {
+                    launch_template_id: launch_template_id.clone(),
+                    next_token,
+                    ..Default::default()
+                })
+                .await?;
+            Ok::<_, Error>(Page::new(
+                output.launch_template_versions,
+                output.next_token,
+            ))
+        })
+        .try_for_each(|launch_template_version: LaunchTemplateVersion| {
+            if let Some(image_id) = launch_template_version
+                .launch_template_data
+                .and_then(|launch_template_data| launch_template_data.image_id)
+            {
+                record(image_id);
+            }
+            future::ready(Ok(()))
+        })
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// The AMIs in use by EC2 instances, launch configurations or launch templates.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct AmisInUse {
+    /// IDs of the AMIs in use.
+    pub amis: BTreeSet<String>,
+}
+
+/// A single AMI in use, as streamed while searching for the AMIs in use.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AmiInUse {
+    /// ID of the AMI.
+    pub ami: String,
+}
+
+/// Find the AMIs in use by EC2 instances, launch configurations or any version of a launch
+/// template.
+///
+/// Every AMI is passed to `on_ami` as soon as the page it appears on was retrieved, but only the
+/// first time it is found.
+pub async fn find_amis_inuse<F: FnMut(&str)>(
+    ec2: &dyn Ec2,
+    autoscaling: &dyn Autoscaling,
+    mut on_ami: F,
+) -> Result<AmisInUse, Error> {
+    let mut amis_inuse = AmisInUse::default();
+    let mut record = |ami_id: String| {
+        if !amis_inuse.amis.contains(&ami_id) {
+            on_ami(&ami_id);
+            amis_inuse.amis.insert(ami_id);
+        }
+    };
+    amis_inuse_by_ec2(ec2, &mut record).await?;
+    amis_inuse_by_launchconfiguration(autoscaling, &mut record).await?;
+    amis_inuse_by_launchtemplate(ec2, &mut record).await?;
+
+    Ok(amis_inuse)
+}
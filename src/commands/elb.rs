@@ -0,0 +1,436 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Commands operating on the target groups of application and network load balancers.
+
+use futures::stream::{FuturesOrdered, TryStreamExt};
+use itertools::Itertools;
+use rusoto_elbv2::{
+    DeregisterTargetsInput, DescribeTagsInput, DescribeTargetGroupsInput,
+    DescribeTargetHealthInput, Elb, RegisterTargetsInput, TagDescription, TargetDescription,
+    TargetGroup, TargetHealthDescription,
+};
+use serde::Serialize;
+use std::time::Duration;
+
+use crate::{
+    error::Error,
+    pagination::{paginate, Page},
+    tags::TagFilter,
+};
+
+/// Interval in which the health of the targets is polled while waiting.
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Selects a single target group.
+#[derive(Debug, Clone)]
+pub enum TargetGroupSelector {
+    /// Select the target group by its ARN.
+    Arn(String),
+    /// Select the only target group matching the tag filter.
+    Tags(TagFilter),
+}
+
+impl TargetGroupSelector {
+    /// Find the ARN of the selected target group.
+    ///
+    /// Fails if no target group matches the tags, or if the tags match more than one target group.
+    pub async fn find_arn(&self, elb: &dyn Elb) -> Result<String, Error> {
+        let tag_filter = match self {
+            TargetGroupSelector::Arn(target_group_arn) => return Ok(target_group_arn.clone()),
+            TargetGroupSelector::Tags(tag_filter) => tag_filter,
+        };
+
+        let target_group_arns = find_target_group_arns(elb, None, tag_filter).await?;
+        match target_group_arns.len() {
+            0 => Err(Error::ResourceNotFound(
+                "a target group matching given filters".to_owned(),
+            )),
+            1 => Ok(target_group_arns
+                .into_iter()
+                .next()
+                .expect("target group should exist")),
+            _ => Err(Error::AmbiguousResource(format!(
+                "the tags match multiple target groups: {}",
+                target_group_arns.iter().join(", ")
+            ))),
+        }
+    }
+}
+
+/// Find the ARNs of all target groups whose tags match the filter, optionally only those assigned
+/// to the given load balancer.
+pub async fn find_target_group_arns(
+    elb: &dyn Elb,
+    load_balancer_arn: Option<&str>,
+    tag_filter: &TagFilter,
+) -> Result<Vec<String>, Error> {
+    let target_groups: Vec<TargetGroup> = paginate(|marker| async move {
+        let output = elb
+            .describe_target_groups(DescribeTargetGroupsInput {
+                load_balancer_arn: load_balancer_arn.map(ToOwned::to_owned),
+                marker,
+                ..Default::default()
+            })
+            .await?;
+        Ok::<_, Error>(Page::new(output.target_groups, output.next_marker))
+    })
+    .try_collect()
+    .await?;
+
+    let tag_descriptions: Vec<TagDescription> = target_groups
+        .into_iter()
+        .filter_map(|target_group| target_group.target_group_arn)
+        .collect::<Vec<_>>()
+        .chunks(20)
+        .map(|arns| arns.to_vec())
+        .map(|arns| async {
+            elb.describe_tags(DescribeTagsInput {
+                resource_arns: arns,
+            })
+            .await
+        })
+        .collect::<FuturesOrdered<_>>()
+        .try_collect::<Vec<_>>()
+        .await?
+        .into_iter()
+        .fold(Vec::new(), |mut acc, mut tag_descriptions| {
+            if let Some(tag_descriptions) = tag_descriptions.tag_descriptions.as_mut() {
+                acc.append(tag_descriptions)
+            }
+            acc
+        });
+    Ok(tag_descriptions
+        .into_iter()
+        .filter(|tag_description| {
+            tag_filter.matches(tag_description.tags.as_deref().unwrap_or_default())
+        })
+        .filter_map(|tag_description| tag_description.resource_arn)
+        .collect())
+}
+
+/// A target group found by `find-target-group`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FoundTargetGroup {
+    /// ARN of the target group.
+    pub target_group_arn: String,
+}
+
+/// Find the first target group whose tags match the filter, optionally only considering those
+/// assigned to the given load balancer.
+pub async fn find_target_group(
+    elb: &dyn Elb,
+    load_balancer_arn: Option<&str>,
+    tag_filter: &TagFilter,
+) -> Result<Option<FoundTargetGroup>, Error> {
+    Ok(find_target_group_arns(elb, load_balancer_arn, tag_filter)
+        .await?
+        .into_iter()
+        .next()
+        .map(|target_group_arn| FoundTargetGroup { target_group_arn }))
+}
+
+/// Parse a target given as `id` or `id:port`.
+pub fn parse_target(target: &str) -> Result<TargetDescription, String> {
+    let (id, port) = match target.rsplit_once(':') {
+        // Lambda function ARNs contain colons as well, but never end in a port.
+        Some((id, port)) if !target.starts_with("arn:") => (
+            id,
+            Some(
+                port.parse::<u16>()
+                    .map_err(|_| format!("`{}` is not a valid port of target `{}`", port, id))?
+                    .into(),
+            ),
+        ),
+        _ => (target, None),
+    };
+
+    Ok(TargetDescription {
+        id: id.to_owned(),
+        port,
+        ..Default::default()
+    })
+}
+
+/// Format the target as `id:port`, or only its ID if it has no port.
+pub fn format_target(target: &TargetDescription) -> String {
+    match target.port {
+        Some(port) => format!("{}:{}", target.id, port),
+        None => target.id.clone(),
+    }
+}
+
+/// The health of a single target.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TargetHealth {
+    /// ID of the target, i.e. an instance ID, IP address or Lambda function ARN.
+    pub id: Option<String>,
+    /// Port of the target, if it overrides the port of the target group.
+    pub port: Option<i64>,
+    /// State of the target, e.g. `healthy` or `draining`.
+    pub state: String,
+    /// Reason code for the state, if the target is not healthy.
+    pub reason: Option<String>,
+    /// Description of the state, if the target is not healthy.
+    pub description: Option<String>,
+}
+
+impl TargetHealth {
+    /// Format the target as `id:port`, or only its ID if it has no port.
+    pub fn target(&self) -> String {
+        match (&self.id, self.port) {
+            (Some(id), Some(port)) => format!("{}:{}", id, port),
+            (Some(id), None) => id.clone(),
+            (None, _) => String::new(),
+        }
+    }
+}
+
+impl From<TargetHealthDescription> for TargetHealth {
+    fn from(target_health_description: TargetHealthDescription) -> Self {
+        let (reason, description, state) = match target_health_description.target_health {
+            Some(target_health) => (
+                target_health.reason,
+                target_health.description,
+                target_health.state,
+            ),
+            None => (None, None, None),
+        };
+        let (id, port) = match target_health_description.target {
+            Some(target) => (Some(target.id), target.port),
+            None => (None, None),
+        };
+        TargetHealth {
+            id,
+            port,
+            state: state.unwrap_or_else(|| "unknown".to_owned()),
+            reason,
+            description,
+        }
+    }
+}
+
+/// Describe the health of the given targets registered with the target group, or of all
+/// registered targets.
+pub async fn describe_target_health(
+    elb: &dyn Elb,
+    target_group_arn: &str,
+    targets: Option<&[TargetDescription]>,
+) -> Result<Vec<TargetHealth>, Error> {
+    Ok(elb
+        .describe_target_health(DescribeTargetHealthInput {
+            target_group_arn: target_group_arn.to_owned(),
+            targets: targets.map(<[_]>::to_vec),
+        })
+        .await?
+        .target_health_descriptions
+        .unwrap_or_default()
+        .into_iter()
+        .map(Into::into)
+        .collect())
+}
+
+/// The health of all targets registered with a target group.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TargetGroupHealth {
+    /// ARN of the target group.
+    pub target_group_arn: String,
+    /// Number of healthy targets.
+    pub healthy_count: usize,
+    /// Health of each registered target.
+    pub targets: Vec<TargetHealth>,
+}
+
+impl TargetGroupHealth {
+    /// Check whether enough targets are healthy: at least `min_healthy`, or all if not specified.
+    pub fn is_healthy_enough(&self, min_healthy: Option<usize>) -> bool {
+        match min_healthy {
+            Some(min_healthy) => self.healthy_count >= min_healthy,
+            None => self.healthy_count > 0 && self.healthy_count == self.targets.len(),
+        }
+    }
+}
+
+/// Describe the health of all targets registered with the target group.
+pub async fn get_target_health(
+    elb: &dyn Elb,
+    target_group_arn: &str,
+) -> Result<TargetGroupHealth, Error> {
+    let targets = describe_target_health(elb, target_group_arn, None).await?;
+    Ok(TargetGroupHealth {
+        target_group_arn: target_group_arn.to_owned(),
+        healthy_count: targets
+            .iter()
+            .filter(|target| target.state == "healthy")
+            .count(),
+        targets,
+    })
+}
+
+/// Wait until enough targets of the target group are healthy, see
+/// [`TargetGroupHealth::is_healthy_enough`].
+///
+/// The health of the targets is passed to `on_progress` every time they are polled while not
+/// enough of them are healthy yet.
+///
+/// [`TargetGroupHealth::is_healthy_enough`]: struct.TargetGroupHealth.html#method.is_healthy_enough
+pub async fn wait_for_healthy_targets<F: FnMut(&TargetGroupHealth)>(
+    elb: &dyn Elb,
+    target_group_arn: &str,
+    min_healthy: Option<usize>,
+    mut on_progress: F,
+) -> Result<TargetGroupHealth, Error> {
+    loop {
+        let target_group_health = get_target_health(elb, target_group_arn).await?;
+        if target_group_health.is_healthy_enough(min_healthy) {
+            return Ok(target_group_health);
+        }
+
+        on_progress(&target_group_health);
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// The targets that were registered with or deregistered from a target group.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TargetGroupTargets {
+    /// ARN of the target group.
+    pub target_group_arn: String,
+    /// The targets, formatted as `id:port`, or only their ID if they have no port.
+    pub targets: Vec<String>,
+}
+
+/// Register the targets with the selected target group.
+///
+/// This does not wait for the targets to become healthy, use [`wait_for_target_state`] for that.
+///
+/// [`wait_for_target_state`]: fn.wait_for_target_state.html
+pub async fn register_targets(
+    elb: &dyn Elb,
+    selector: &TargetGroupSelector,
+    targets: &[TargetDescription],
+) -> Result<TargetGroupTargets, Error> {
+    let target_group_arn = selector.find_arn(elb).await?;
+    elb.register_targets(RegisterTargetsInput {
+        target_group_arn: target_group_arn.clone(),
+        targets: targets.to_vec(),
+    })
+    .await?;
+
+    Ok(TargetGroupTargets {
+        target_group_arn,
+        targets: targets.iter().map(format_target).collect(),
+    })
+}
+
+/// Deregister the targets from the selected target group.
+///
+/// This does not wait for the connections to the targets to be drained, use
+/// [`wait_for_target_state`] with the state `unused` for that.
+///
+/// [`wait_for_target_state`]: fn.wait_for_target_state.html
+pub async fn deregister_targets(
+    elb: &dyn Elb,
+    selector: &TargetGroupSelector,
+    targets: &[TargetDescription],
+) -> Result<TargetGroupTargets, Error> {
+    let target_group_arn = selector.find_arn(elb).await?;
+    elb.deregister_targets(DeregisterTargetsInput {
+        target_group_arn: target_group_arn.clone(),
+        targets: targets.to_vec(),
+    })
+    .await?;
+
+    Ok(TargetGroupTargets {
+        target_group_arn,
+        targets: targets.iter().map(format_target).collect(),
+    })
+}
+
+/// Wait until all given targets are in the given state, e.g. `healthy`.
+///
+/// The health of the targets is passed to `on_progress` every time they are polled while not all
+/// of them are in the state yet.
+pub async fn wait_for_target_state<F: FnMut(&[TargetHealth])>(
+    elb: &dyn Elb,
+    target_group_arn: &str,
+    targets: &[TargetDescription],
+    state: &str,
+    mut on_progress: F,
+) -> Result<(), Error> {
+    loop {
+        let target_health = describe_target_health(elb, target_group_arn, Some(targets)).await?;
+        if target_health.iter().all(|target| target.state == state) {
+            return Ok(());
+        }
+
+        on_progress(&target_health);
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_target_with_and_without_port() {
+        assert_eq!(
+            TargetDescription {
+                id: "10.0.0.1".to_owned(),
+                port: Some(8080),
+                ..Default::default()
+            },
+            parse_target("10.0.0.1:8080").unwrap()
+        );
+        assert_eq!(
+            TargetDescription {
+                id: "i-0123456789abcdef0".to_owned(),
+                ..Default::default()
+            },
+            parse_target("i-0123456789abcdef0").unwrap()
+        );
+        let lambda = "arn:aws:lambda:eu-west-1:123456789012:function:example";
+        assert_eq!(lambda, parse_target(lambda).unwrap().id);
+        assert!(parse_target("10.0.0.1:http").is_err());
+    }
+
+    #[test]
+    fn target_group_is_healthy_enough() {
+        let target = |state: &str| TargetHealth {
+            id: Some("i-0123456789abcdef0".to_owned()),
+            port: None,
+            state: state.to_owned(),
+            reason: None,
+            description: None,
+        };
+        let health = TargetGroupHealth {
+            target_group_arn: "arn".to_owned(),
+            healthy_count: 1,
+            targets: vec![target("healthy"), target("unhealthy")],
+        };
+
+        assert!(!health.is_healthy_enough(None));
+        assert!(health.is_healthy_enough(Some(1)));
+        assert!(!health.is_healthy_enough(Some(2)));
+        assert!(!TargetGroupHealth {
+            target_group_arn: "arn".to_owned(),
+            healthy_count: 0,
+            targets: Vec::new(),
+        }
+        .is_healthy_enough(None));
+    }
+}
@@ -0,0 +1,167 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Commands generating IAM policies granting the permissions required by `awsx`.
+
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use crate::endpoint::Partition;
+
+/// CloudFormation actions that can be scoped to specific stacks.
+const STACK_SCOPED_ACTIONS: &[&str] = &[
+    "cloudformation:CreateChangeSet",
+    "cloudformation:DeleteChangeSet",
+    "cloudformation:DescribeChangeSet",
+    "cloudformation:DescribeStackEvents",
+    "cloudformation:DescribeStacks",
+    "cloudformation:ExecuteChangeSet",
+    "cloudformation:GetTemplate",
+    "cloudformation:ListChangeSets",
+];
+
+/// The resources the actions of a policy are scoped to.
+#[derive(Debug, Clone)]
+pub struct PolicyScope {
+    /// ARNs of the stacks to scope CloudFormation actions to, which can contain wildcards, or all
+    /// stacks if empty.
+    pub stack_arns: Vec<String>,
+    /// Names of the buckets to scope S3 actions to, or all buckets if empty.
+    pub bucket_names: Vec<String>,
+    /// The partition of the bucket ARNs.
+    pub partition: Partition,
+}
+
+/// An IAM policy document.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PolicyDocument {
+    /// Version of the policy language.
+    pub version: String,
+    /// The statements of the policy.
+    pub statement: Vec<PolicyStatement>,
+}
+
+/// A statement of an IAM policy document.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PolicyStatement {
+    /// Whether the statement allows or denies the actions.
+    pub effect: String,
+    /// The actions, e.g. `cloudformation:DescribeStacks`.
+    pub action: Vec<String>,
+    /// ARNs of the resources the actions apply to.
+    pub resource: Vec<String>,
+}
+
+/// Create the policy allowing the actions, scoped to the given resources where possible.
+///
+/// The actions are grouped into one statement per set of resources they are scoped to.
+pub fn iam_policy<I, S>(actions: I, scope: &PolicyScope) -> PolicyDocument
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let all_resources = vec!["*".to_owned()];
+    let stack_resources = if scope.stack_arns.is_empty() {
+        all_resources.clone()
+    } else {
+        scope.stack_arns.clone()
+    };
+    let object_resources = if scope.bucket_names.is_empty() {
+        all_resources.clone()
+    } else {
+        scope
+            .bucket_names
+            .iter()
+            .map(|bucket_name| format!("arn:{}:s3:::{}/*", scope.partition.name(), bucket_name))
+            .collect()
+    };
+
+    let mut statements: IndexMap<&Vec<String>, Vec<String>> = IndexMap::new();
+    for action in actions {
+        let action = action.as_ref();
+        let resources = if STACK_SCOPED_ACTIONS.contains(&action) {
+            &stack_resources
+        } else if action.starts_with("s3:") {
+            &object_resources
+        } else {
+            &all_resources
+        };
+        statements
+            .entry(resources)
+            .or_default()
+            .push(action.to_owned());
+    }
+
+    PolicyDocument {
+        version: "2012-10-17".to_owned(),
+        statement: statements
+            .into_iter()
+            .map(|(resources, actions)| PolicyStatement {
+                effect: "Allow".to_owned(),
+                action: actions,
+                resource: resources.clone(),
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn actions_are_grouped_by_scoped_resources() {
+        let policy = iam_policy(
+            [
+                "cloudformation:CreateChangeSet",
+                "cloudformation:ValidateTemplate",
+                "s3:PutObject",
+            ],
+            &PolicyScope {
+                stack_arns: vec!["arn:aws-cn:cloudformation:cn-north-1:1:stack/app-*".to_owned()],
+                bucket_names: vec!["templates".to_owned()],
+                partition: Partition::AwsCn,
+            },
+        );
+
+        assert_eq!(
+            json!({
+                "Version": "2012-10-17",
+                "Statement": [
+                    {
+                        "Effect": "Allow",
+                        "Action": ["cloudformation:CreateChangeSet"],
+                        "Resource": ["arn:aws-cn:cloudformation:cn-north-1:1:stack/app-*"],
+                    },
+                    {
+                        "Effect": "Allow",
+                        "Action": ["cloudformation:ValidateTemplate"],
+                        "Resource": ["*"],
+                    },
+                    {
+                        "Effect": "Allow",
+                        "Action": ["s3:PutObject"],
+                        "Resource": ["arn:aws-cn:s3:::templates/*"],
+                    },
+                ],
+            }),
+            serde_json::to_value(policy).unwrap()
+        );
+    }
+}
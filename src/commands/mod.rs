@@ -0,0 +1,117 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Implementations of the `awsx` subcommands, usable without the binary.
+//!
+//! Every command is a public async function taking the AWS client it operates on and its typed
+//! inputs, and returning a typed, serializable result. The `awsx` binary parses the command line
+//! into these inputs and renders the results, so other programs can run the same operations
+//! without shelling out and parsing JSON.
+//!
+//! Progress of long-running operations is reported through callbacks rather than printed, and
+//! waiting is not bounded by a timeout; wrap the future in [`tokio::time::timeout`] if needed.
+//!
+//! The structured output of a command is a [`CommandOutput`], which wraps the result of the command
+//! with its outcome, so that all commands report `success` and `message` alike.
+//!
+//! The commands are grouped by the service they operate on: [`auto_scaling`], [`cloudformation`],
+//! [`cloudfront`], [`cloudtrail`], [`cloudwatch`], [`ec2`], [`elb`], [`iam`], [`rds`] and
+//! [`route53`]. The phases of blue/green deployments, which span several services, are provided by
+//! [`blue_green`].
+//!
+//! **Note:** what surrounds the operations when the binary runs them is not part of this module,
+//! i.e. interactive confirmations, policy checks, hooks, canaries, notifications and the audit log.
+//! In particular, the binary itself verifies and executes change sets for `execute-change-set` and
+//! the other subcommands creating change sets, and persists and resumes the state of blue/green
+//! deployments. The subcommands concerning the binary itself, i.e. `self-update` and `ui`, are not
+//! part of this module either.
+
+pub mod auto_scaling;
+pub mod blue_green;
+pub mod cloudformation;
+pub mod cloudfront;
+pub mod cloudtrail;
+pub mod cloudwatch;
+pub mod ec2;
+pub mod elb;
+pub mod iam;
+pub mod rds;
+pub mod route53;
+
+use serde::Serialize;
+
+/// The outcome of a command together with its result, as rendered by the `awsx` binary.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CommandOutput<T> {
+    /// Whether the command was successful.
+    pub success: bool,
+    /// Description of the outcome.
+    pub message: String,
+    /// The result of the command, whose fields are serialized next to `success` and `message`.
+    #[serde(flatten)]
+    pub result: T,
+}
+
+impl<T> CommandOutput<T> {
+    /// Create the output of a successful command.
+    pub fn success(message: impl Into<String>, result: T) -> CommandOutput<T> {
+        CommandOutput {
+            success: true,
+            message: message.into(),
+            result,
+        }
+    }
+
+    /// Create the output of a failed command.
+    pub fn failure(message: impl Into<String>, result: T) -> CommandOutput<T> {
+        CommandOutput {
+            success: false,
+            message: message.into(),
+            result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn command_output_flattens_result() {
+        #[derive(Serialize)]
+        struct Result {
+            name: &'static str,
+        }
+
+        assert_eq!(
+            json!({
+                "success": true,
+                "message": "Done",
+                "name": "example",
+            }),
+            serde_json::to_value(CommandOutput::success("Done", Result { name: "example" }))
+                .unwrap()
+        );
+        assert_eq!(
+            json!({
+                "success": false,
+                "message": "Failed",
+            }),
+            serde_json::to_value(CommandOutput::failure("Failed", ())).unwrap()
+        );
+    }
+}
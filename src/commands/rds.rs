@@ -0,0 +1,639 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Commands operating on RDS DB snapshots and DB cluster snapshots.
+//!
+//! The snapshots themselves are handled by [`DbSnapshot`], these commands combine its operations
+//! the way the `awsx` subcommands use them.
+//!
+//! [`DbSnapshot`]: ../../rds/enum.DbSnapshot.html
+
+use chrono::{DateTime, Utc};
+use futures::{
+    future,
+    stream::{self, StreamExt, TryStreamExt},
+};
+use rusoto_core::Region;
+use rusoto_credential::ProvideAwsCredentials;
+use rusoto_rds::{
+    DBClusterSnapshot, DBSnapshot, DescribeDBClusterSnapshotsMessage, DescribeDBSnapshotsMessage,
+    Filter, ListTagsForResourceMessage, Rds,
+};
+use serde::Serialize;
+use std::{num::NonZeroUsize, time::Duration};
+
+use crate::{
+    error::Error,
+    pagination::{paginate, Page},
+    rds::{CopyOptions, DbSnapshot, DbSnapshotDescription},
+    tags::{TagFilter, TagSpecification},
+};
+
+/// Filters shared by the commands finding DB snapshots and DB cluster snapshots.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SnapshotFilter {
+    /// Only consider snapshots of databases with this engine, e.g. `postgres`.
+    pub engine: Option<String>,
+    /// Only consider snapshots with this status, e.g. `available`.
+    pub status: Option<String>,
+    /// Only consider snapshots created at or after this time.
+    pub min_create_time: Option<DateTime<Utc>>,
+    /// Only consider snapshots created at or before this time.
+    pub max_create_time: Option<DateTime<Utc>>,
+    /// Include snapshots shared with this account by other accounts.
+    pub include_shared: bool,
+    /// Include public snapshots.
+    pub include_public: bool,
+}
+
+impl SnapshotFilter {
+    /// The filters that are applied by the API when describing the snapshots.
+    fn api_filters(&self) -> Option<Vec<Filter>> {
+        self.engine.as_ref().map(|engine| {
+            vec![Filter {
+                name: "engine".to_owned(),
+                values: vec![engine.clone()],
+            }]
+        })
+    }
+
+    /// Check whether a snapshot with the given status and creation time matches the filters that
+    /// can't be applied by the API.
+    pub fn matches(&self, status: Option<&str>, snapshot_create_time: Option<&str>) -> bool {
+        if let Some(expected_status) = &self.status {
+            if status != Some(expected_status.as_str()) {
+                return false;
+            }
+        }
+        if self.min_create_time.is_none() && self.max_create_time.is_none() {
+            return true;
+        }
+
+        match snapshot_create_time.and_then(|time| DateTime::parse_from_rfc3339(time).ok()) {
+            Some(create_time) => {
+                self.min_create_time
+                    .map(|min_create_time| create_time >= min_create_time)
+                    .unwrap_or(true)
+                    && self
+                        .max_create_time
+                        .map(|max_create_time| create_time <= max_create_time)
+                        .unwrap_or(true)
+            }
+            None => false,
+        }
+    }
+}
+
+/// Describes which snapshots `find-db-snapshot` and `find-db-cluster-snapshot` consider.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SnapshotQuery {
+    /// Only consider snapshots of the DB instance or DB cluster with this identifier.
+    pub source_identifier: Option<String>,
+    /// Only consider snapshots of this type, e.g. `manual`.
+    pub snapshot_type: Option<String>,
+    /// Filters on the properties of the snapshots.
+    pub filter: SnapshotFilter,
+    /// Tags the snapshots have to match.
+    pub tag_filter: TagFilter,
+}
+
+mod serde_remote {
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    #[serde(remote = "rusoto_rds::DBSnapshot")]
+    pub(super) struct DBSnapshotProxy {
+        pub allocated_storage: Option<i64>,
+        pub availability_zone: Option<String>,
+        pub db_instance_identifier: Option<String>,
+        pub db_snapshot_arn: Option<String>,
+        pub db_snapshot_identifier: Option<String>,
+        pub dbi_resource_id: Option<String>,
+        pub encrypted: Option<bool>,
+        pub engine: Option<String>,
+        pub engine_version: Option<String>,
+        pub iam_database_authentication_enabled: Option<bool>,
+        pub instance_create_time: Option<String>,
+        pub iops: Option<i64>,
+        pub kms_key_id: Option<String>,
+        pub license_model: Option<String>,
+        pub master_username: Option<String>,
+        pub option_group_name: Option<String>,
+        pub percent_progress: Option<i64>,
+        pub port: Option<i64>,
+        pub snapshot_create_time: Option<String>,
+        pub snapshot_type: Option<String>,
+        pub source_db_snapshot_identifier: Option<String>,
+        pub source_region: Option<String>,
+        pub status: Option<String>,
+        pub storage_type: Option<String>,
+        pub tde_credential_arn: Option<String>,
+        pub timezone: Option<String>,
+        pub vpc_id: Option<String>,
+    }
+
+    #[derive(Serialize)]
+    #[serde(remote = "rusoto_rds::DBClusterSnapshot")]
+    pub(super) struct DBClusterSnapshotProxy {
+        pub allocated_storage: Option<i64>,
+        pub availability_zones: Option<Vec<String>>,
+        pub cluster_create_time: Option<String>,
+        pub db_cluster_identifier: Option<String>,
+        pub db_cluster_snapshot_arn: Option<String>,
+        pub db_cluster_snapshot_identifier: Option<String>,
+        pub engine: Option<String>,
+        pub engine_mode: Option<String>,
+        pub engine_version: Option<String>,
+        pub iam_database_authentication_enabled: Option<bool>,
+        pub kms_key_id: Option<String>,
+        pub license_model: Option<String>,
+        pub master_username: Option<String>,
+        pub percent_progress: Option<i64>,
+        pub port: Option<i64>,
+        pub snapshot_create_time: Option<String>,
+        pub snapshot_type: Option<String>,
+        pub source_db_cluster_snapshot_arn: Option<String>,
+        pub status: Option<String>,
+        pub storage_encrypted: Option<bool>,
+        pub vpc_id: Option<String>,
+    }
+}
+
+/// The details of a DB snapshot, serialized with the fields AWS describes it with.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DbSnapshotDetails(#[serde(with = "serde_remote::DBSnapshotProxy")] pub DBSnapshot);
+
+/// The details of a DB cluster snapshot, serialized with the fields AWS describes it with.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DbClusterSnapshotDetails(
+    #[serde(with = "serde_remote::DBClusterSnapshotProxy")] pub DBClusterSnapshot,
+);
+
+/// A DB snapshot found by `find-db-snapshot`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FoundDbSnapshot {
+    /// ARN of the DB snapshot.
+    pub db_snapshot_arn: String,
+    /// Details of the DB snapshot.
+    pub db_snapshot: DbSnapshotDetails,
+}
+
+/// A DB cluster snapshot found by `find-db-cluster-snapshot`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FoundDbClusterSnapshot {
+    /// ARN of the DB cluster snapshot.
+    pub db_cluster_snapshot_arn: String,
+    /// Details of the DB cluster snapshot.
+    pub db_cluster_snapshot: DbClusterSnapshotDetails,
+}
+
+/// A DB snapshot or DB cluster snapshot, whose tags can be retrieved through its ARN.
+trait Snapshot {
+    /// ARN of the snapshot.
+    fn arn(&self) -> Option<&String>;
+    /// Type of the snapshot, e.g. `manual` or `shared`.
+    fn snapshot_type(&self) -> Option<&str>;
+}
+
+impl Snapshot for DBSnapshot {
+    fn arn(&self) -> Option<&String> {
+        self.db_snapshot_arn.as_ref()
+    }
+
+    fn snapshot_type(&self) -> Option<&str> {
+        self.snapshot_type.as_deref()
+    }
+}
+
+impl Snapshot for DBClusterSnapshot {
+    fn arn(&self) -> Option<&String> {
+        self.db_cluster_snapshot_arn.as_ref()
+    }
+
+    fn snapshot_type(&self) -> Option<&str> {
+        self.snapshot_type.as_deref()
+    }
+}
+
+/// Retain the snapshots matching the tag filter, dropping the snapshots without an ARN.
+///
+/// The tags are only retrieved if there is a filter on them, for up to `concurrency` snapshots at
+/// the same time. The tags of snapshots shared by other accounts or public snapshots can't be
+/// retrieved, so these snapshots never match a tag filter. Neither do snapshots that have been
+/// deleted since they were described.
+///
+/// Fails if the tags of any other snapshot could not be retrieved, e.g. since the requests were
+/// throttled, as the snapshot might otherwise be missed silently.
+async fn filter_by_tags<T: Snapshot>(
+    rds: &dyn Rds,
+    snapshots: Vec<T>,
+    tag_filter: &TagFilter,
+    concurrency: NonZeroUsize,
+) -> Result<Vec<T>, Error> {
+    if tag_filter.is_empty() {
+        return Ok(snapshots
+            .into_iter()
+            .filter(|snapshot| snapshot.arn().is_some())
+            .collect());
+    }
+
+    stream::iter(snapshots)
+        .map(|snapshot| async move {
+            let resource_name = match snapshot.arn() {
+                Some(resource_name) => resource_name.clone(),
+                None => return Ok(None),
+            };
+            if matches!(snapshot.snapshot_type(), Some("shared") | Some("public")) {
+                return Ok(None);
+            }
+            let tags = match rds
+                .list_tags_for_resource(ListTagsForResourceMessage {
+                    resource_name,
+                    ..Default::default()
+                })
+                .await
+                .map_err(Error::from)
+            {
+                Ok(tags) => tags,
+                Err(error) if error.is_not_found() => return Ok(None),
+                Err(error) => return Err(error),
+            };
+            Ok(tag_filter
+                .matches(&tags.tag_list.unwrap_or_default())
+                .then_some(snapshot))
+        })
+        .buffered(concurrency.get())
+        .try_filter_map(|snapshot| future::ready(Ok(snapshot)))
+        .try_collect()
+        .await
+}
+
+/// Find the latest DB snapshot matching the query.
+///
+/// If the query filters on tags, the tags of up to `concurrency` snapshots are retrieved at the
+/// same time. Snapshots shared by other accounts and public snapshots never match a tag filter.
+pub async fn find_db_snapshot(
+    rds: &dyn Rds,
+    query: &SnapshotQuery,
+    concurrency: NonZeroUsize,
+) -> Result<Option<FoundDbSnapshot>, Error> {
+    let db_snapshots: Vec<DBSnapshot> = paginate(|marker| async move {
+        let output = rds
+            .describe_db_snapshots(DescribeDBSnapshotsMessage {
+                db_instance_identifier: query.source_identifier.clone(),
+                snapshot_type: query.snapshot_type.clone(),
+                filters: query.filter.api_filters(),
+                include_shared: query.filter.include_shared.then_some(true),
+                include_public: query.filter.include_public.then_some(true),
+                marker,
+                ..Default::default()
+            })
+            .await?;
+        Ok::<_, Error>(Page::new(output.db_snapshots, output.marker))
+    })
+    .try_filter(|db_snapshot| {
+        future::ready(query.filter.matches(
+            db_snapshot.status.as_deref(),
+            db_snapshot.snapshot_create_time.as_deref(),
+        ))
+    })
+    .try_collect()
+    .await?;
+
+    let db_snapshot = filter_by_tags(rds, db_snapshots, &query.tag_filter, concurrency)
+        .await?
+        .into_iter()
+        .max_by_key(|db_snapshot| db_snapshot.snapshot_create_time.clone());
+
+    Ok(db_snapshot.and_then(|db_snapshot| {
+        Some(FoundDbSnapshot {
+            db_snapshot_arn: db_snapshot.db_snapshot_arn.clone()?,
+            db_snapshot: DbSnapshotDetails(db_snapshot),
+        })
+    }))
+}
+
+/// Find the latest DB cluster snapshot matching the query.
+///
+/// If the query filters on tags, the tags of up to `concurrency` snapshots are retrieved at the
+/// same time. Snapshots shared by other accounts and public snapshots never match a tag filter.
+pub async fn find_db_cluster_snapshot(
+    rds: &dyn Rds,
+    query: &SnapshotQuery,
+    concurrency: NonZeroUsize,
+) -> Result<Option<FoundDbClusterSnapshot>, Error> {
+    let db_cluster_snapshots: Vec<DBClusterSnapshot> = paginate(|marker| async move {
+        let output = rds
+            .describe_db_cluster_snapshots(DescribeDBClusterSnapshotsMessage {
+                db_cluster_identifier: query.source_identifier.clone(),
+                snapshot_type: query.snapshot_type.clone(),
+                filters: query.filter.api_filters(),
+                include_shared: query.filter.include_shared.then_some(true),
+                include_public: query.filter.include_public.then_some(true),
+                marker,
+                ..Default::default()
+            })
+            .await?;
+        Ok::<_, Error>(Page::new(output.db_cluster_snapshots, output.marker))
+    })
+    .try_filter(|db_cluster_snapshot| {
+        future::ready(query.filter.matches(
+            db_cluster_snapshot.status.as_deref(),
+            db_cluster_snapshot.snapshot_create_time.as_deref(),
+        ))
+    })
+    .try_collect()
+    .await?;
+
+    let db_cluster_snapshot =
+        filter_by_tags(rds, db_cluster_snapshots, &query.tag_filter, concurrency)
+            .await?
+            .into_iter()
+            .max_by_key(|db_cluster_snapshot| db_cluster_snapshot.snapshot_create_time.clone());
+
+    Ok(db_cluster_snapshot.and_then(|db_cluster_snapshot| {
+        Some(FoundDbClusterSnapshot {
+            db_cluster_snapshot_arn: db_cluster_snapshot.db_cluster_snapshot_arn.clone()?,
+            db_cluster_snapshot: DbClusterSnapshotDetails(db_cluster_snapshot),
+        })
+    }))
+}
+
+/// The status of a snapshot that was created, copied or waited for.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DbSnapshotStatus {
+    /// ARN of the snapshot, unknown until AWS reports it.
+    pub db_snapshot_arn: Option<String>,
+    /// Status of the snapshot, e.g. `creating` or `available`.
+    pub status: String,
+}
+
+impl From<DbSnapshotDescription> for DbSnapshotStatus {
+    fn from(description: DbSnapshotDescription) -> Self {
+        DbSnapshotStatus {
+            db_snapshot_arn: description.arn,
+            status: description.status,
+        }
+    }
+}
+
+/// Create the snapshot of the given DB instance or DB cluster, returning its initial status.
+///
+/// This does not wait for the creation to complete, use [`wait_for_db_snapshot`] for that.
+///
+/// [`wait_for_db_snapshot`]: fn.wait_for_db_snapshot.html
+pub async fn create_db_snapshot(
+    rds: &dyn Rds,
+    db_snapshot: &DbSnapshot,
+    source_identifier: &str,
+    tags: &[TagSpecification],
+) -> Result<DbSnapshotStatus, Error> {
+    Ok(db_snapshot
+        .create(rds, source_identifier, tags)
+        .await?
+        .into())
+}
+
+/// Wait until the snapshot is available, returning its final status.
+///
+/// Fails if the snapshot ends up in any other status, or does not become available within the
+/// timeout.
+pub async fn wait_for_db_snapshot(
+    rds: &dyn Rds,
+    db_snapshot: &DbSnapshot,
+    timeout: Duration,
+) -> Result<DbSnapshotStatus, Error> {
+    Ok(db_snapshot.wait_until_available(rds, timeout).await?.into())
+}
+
+/// An RDS client together with the region it was created for.
+pub struct RegionalRds<'a> {
+    /// The RDS client.
+    pub rds: &'a dyn Rds,
+    /// The region of the client.
+    pub region: Region,
+}
+
+/// Copy the snapshot, which resides in the source region, to a new snapshot in the destination
+/// region, returning the new snapshot and its initial status.
+///
+/// Snapshots copied to another region are referenced by their ARN. Copying an encrypted snapshot
+/// to another region requires a KMS key of the destination region in `options`, and the pre-signed
+/// URL authorizing the copy is created with the credentials of the provider. A pre-signed URL
+/// given in `options` is ignored.
+///
+/// This does not wait for the copy to complete, use [`wait_for_db_snapshot`] on the returned
+/// snapshot for that.
+///
+/// [`wait_for_db_snapshot`]: fn.wait_for_db_snapshot.html
+pub async fn copy_db_snapshot<P: ProvideAwsCredentials>(
+    source: &RegionalRds<'_>,
+    destination: &RegionalRds<'_>,
+    db_snapshot: &DbSnapshot,
+    target_identifier: &str,
+    options: &CopyOptions,
+    provider: &P,
+) -> Result<(DbSnapshot, DbSnapshotStatus), Error> {
+    let mut db_snapshot = db_snapshot.clone();
+    let description = db_snapshot.describe(source.rds).await?;
+
+    let mut options = CopyOptions {
+        pre_signed_url: None,
+        ..options.clone()
+    };
+    if source.region != destination.region {
+        // Snapshots in other regions have to be referenced by their ARN.
+        if let Some(arn) = &description.arn {
+            db_snapshot = match db_snapshot {
+                DbSnapshot::Instance(_) => DbSnapshot::Instance(arn.clone()),
+                DbSnapshot::Cluster(_) => DbSnapshot::Cluster(arn.clone()),
+            };
+        }
+        if description.encrypted {
+            let kms_key_id = options.kms_key_id.as_deref().ok_or_else(|| {
+                Error::InvalidParameters(format!(
+                    "the snapshot {} is encrypted, --kms-key-id is required to copy it to \
+                     another region",
+                    db_snapshot.identifier()
+                ))
+            })?;
+            options.pre_signed_url = Some(db_snapshot.pre_signed_copy_url(
+                target_identifier,
+                kms_key_id,
+                &source.region,
+                &destination.region,
+                &provider.credentials().await?,
+            ));
+        }
+    }
+
+    let (target, status) = db_snapshot
+        .copy(destination.rds, target_identifier, &options)
+        .await?;
+    Ok((target, status.into()))
+}
+
+/// The accounts a snapshot is shared with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SharedDbSnapshot {
+    /// IDs of all accounts the snapshot is shared with.
+    pub shared_with_account_ids: Vec<String>,
+}
+
+/// Share the snapshot with the given accounts, or revoke the access of the given accounts,
+/// returning all accounts the snapshot is shared with afterwards.
+///
+/// See [`DbSnapshot::share`] for the restrictions on sharing encrypted snapshots.
+///
+/// [`DbSnapshot::share`]: ../../rds/enum.DbSnapshot.html#method.share
+pub async fn share_db_snapshot(
+    rds: &dyn Rds,
+    db_snapshot: &DbSnapshot,
+    accounts_to_add: &[String],
+    accounts_to_remove: &[String],
+) -> Result<SharedDbSnapshot, Error> {
+    Ok(SharedDbSnapshot {
+        shared_with_account_ids: db_snapshot
+            .share(rds, accounts_to_add, accounts_to_remove)
+            .await?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{self, MockDispatcher};
+
+    #[test]
+    fn snapshot_filter_matches_status_and_create_time() {
+        let filter = SnapshotFilter {
+            status: Some("available".to_owned()),
+            min_create_time: Some("2025-01-01T00:00:00Z".parse().unwrap()),
+            max_create_time: Some("2025-01-31T23:59:59Z".parse().unwrap()),
+            ..Default::default()
+        };
+
+        assert!(filter.matches(Some("available"), Some("2025-01-15T12:00:00.000Z")));
+        assert!(!filter.matches(Some("creating"), Some("2025-01-15T12:00:00.000Z")));
+        assert!(!filter.matches(Some("available"), Some("2025-02-01T00:00:00.000Z")));
+        // Snapshots without a creation time, e.g. while they are created, can't be in the range.
+        assert!(!filter.matches(Some("available"), None));
+        assert!(SnapshotFilter::default().matches(None, None));
+    }
+
+    fn db_snapshot(identifier: &str, snapshot_type: &str) -> DBSnapshot {
+        DBSnapshot {
+            db_snapshot_arn: Some(format!(
+                "arn:aws:rds:eu-central-1:123456789012:snapshot:{}",
+                identifier
+            )),
+            db_snapshot_identifier: Some(identifier.to_owned()),
+            snapshot_type: Some(snapshot_type.to_owned()),
+            ..Default::default()
+        }
+    }
+
+    fn identifiers(db_snapshots: Vec<DBSnapshot>) -> Vec<String> {
+        db_snapshots
+            .into_iter()
+            .filter_map(|db_snapshot| db_snapshot.db_snapshot_identifier)
+            .collect()
+    }
+
+    fn tag_list(key: &str, value: &str) -> String {
+        format!(
+            "<ListTagsForResourceResponse><ListTagsForResourceResult><TagList>\
+             <Tag><Key>{}</Key><Value>{}</Value></Tag>\
+             </TagList></ListTagsForResourceResult>\
+             <ResponseMetadata><RequestId>request-id</RequestId></ResponseMetadata>\
+             </ListTagsForResourceResponse>",
+            key, value
+        )
+    }
+
+    #[tokio::test]
+    async fn tags_are_only_retrieved_when_filtering_by_tags() {
+        let dispatcher = MockDispatcher::new(Vec::<String>::new());
+        let rds = mock::rds(&dispatcher);
+        let mut without_arn = db_snapshot("without-arn", "manual");
+        without_arn.db_snapshot_arn = None;
+
+        let db_snapshots = filter_by_tags(
+            &rds,
+            vec![db_snapshot("manual", "manual"), without_arn],
+            &TagFilter::default(),
+            NonZeroUsize::new(1).unwrap(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(vec!["manual"], identifiers(db_snapshots));
+        assert!(dispatcher.actions().is_empty());
+    }
+
+    #[tokio::test]
+    async fn tag_filter_skips_shared_and_deleted_snapshots() {
+        let dispatcher = MockDispatcher::with_status(vec![
+            (200, tag_list("env", "prod")),
+            (
+                404,
+                "<ErrorResponse><Error><Type>Sender</Type><Code>DBSnapshotNotFound</Code>\
+                 <Message>DBSnapshot deleted not found.</Message></Error>\
+                 <RequestId>request-id</RequestId></ErrorResponse>"
+                    .to_owned(),
+            ),
+            (200, tag_list("env", "test")),
+        ]);
+        let rds = mock::rds(&dispatcher);
+
+        let db_snapshots = filter_by_tags(
+            &rds,
+            vec![
+                db_snapshot("prod", "manual"),
+                db_snapshot("shared", "shared"),
+                db_snapshot("deleted", "automated"),
+                db_snapshot("public", "public"),
+                db_snapshot("test", "manual"),
+            ],
+            &TagFilter::new(vec!["env=prod".parse().unwrap()]),
+            NonZeroUsize::new(1).unwrap(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(vec!["prod"], identifiers(db_snapshots));
+        assert_eq!(vec!["ListTagsForResource"; 3], dispatcher.actions());
+    }
+
+    #[tokio::test]
+    async fn tag_filter_fails_if_tags_cannot_be_retrieved() {
+        let dispatcher = MockDispatcher::with_status(vec![(
+            400,
+            "<ErrorResponse><Error><Type>Sender</Type><Code>Throttling</Code>\
+             <Message>Rate exceeded</Message></Error>\
+             <RequestId>request-id</RequestId></ErrorResponse>",
+        )]);
+        let rds = mock::rds(&dispatcher);
+
+        let error = filter_by_tags(
+            &rds,
+            vec![db_snapshot("prod", "manual")],
+            &TagFilter::new(vec!["env=prod".parse().unwrap()]),
+            NonZeroUsize::new(1).unwrap(),
+        )
+        .await
+        .unwrap_err();
+        assert!(error.is_retryable());
+    }
+}
@@ -0,0 +1,230 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Commands operating on Route 53 record sets.
+
+use indexmap::IndexMap;
+use serde::Serialize;
+use std::{num::NonZeroU32, str::FromStr, time::Duration};
+
+use crate::{
+    error::Error,
+    route53::{ResourceRecordSet, Route53Client},
+};
+
+/// Selects a hosted zone.
+#[derive(Debug, Clone)]
+pub enum HostedZoneSelector {
+    /// Select the hosted zone by its ID.
+    Id(String),
+    /// Select the hosted zone by its name, e.g. `example.com`.
+    Name(String),
+}
+
+impl HostedZoneSelector {
+    /// Find the ID of the selected hosted zone.
+    pub async fn find_id(&self, route53: &Route53Client) -> Result<String, Error> {
+        match self {
+            HostedZoneSelector::Id(hosted_zone_id) => Ok(hosted_zone_id.clone()),
+            HostedZoneSelector::Name(hosted_zone_name) => {
+                route53.find_hosted_zone_id(hosted_zone_name).await
+            }
+        }
+    }
+}
+
+/// The weight of the record set with the given set identifier.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Weight {
+    /// Set identifier of the weighted record set.
+    pub set_identifier: String,
+    /// Weight of the record set, between 0 and 255.
+    pub weight: i64,
+}
+
+impl FromStr for Weight {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (set_identifier, weight) = s
+            .split_once('=')
+            .ok_or_else(|| format!("`{}` is not of the form `SetIdentifier=Weight`", s))?;
+        match weight.parse::<i64>() {
+            Ok(weight) if (0..=255).contains(&weight) => Ok(Weight {
+                set_identifier: set_identifier.to_owned(),
+                weight,
+            }),
+            _ => Err(format!(
+                "`{}` is not a valid weight, expected a number between 0 and 255",
+                weight
+            )),
+        }
+    }
+}
+
+/// Interpolate the weight of the given step between the current and the target weight.
+pub fn step_weight(current: i64, target: i64, step: u32, steps: u32) -> i64 {
+    current + (target - current) * i64::from(step) / i64::from(steps)
+}
+
+/// Describes how the weights of weighted record sets are shifted.
+#[derive(Debug, Clone)]
+pub struct WeightShift {
+    /// The hosted zone of the record sets.
+    pub hosted_zone: HostedZoneSelector,
+    /// Name of the weighted record sets, e.g. `app.example.com`.
+    pub record_name: String,
+    /// Type of the weighted record sets, e.g. `A`.
+    pub record_type: String,
+    /// Target weights of the record sets. Record sets that are not given keep their weight.
+    pub weights: Vec<Weight>,
+    /// Number of steps to change the weights in, gradually from their current to their target
+    /// values.
+    pub steps: NonZeroU32,
+    /// Time to wait between the steps.
+    pub step_interval: Duration,
+    /// Maximum time to wait for each change to be propagated.
+    pub change_timeout: Duration,
+}
+
+/// The weights of the record sets after a step of shifting them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ShiftedWeights {
+    /// ID of the hosted zone of the record sets.
+    pub hosted_zone_id: String,
+    /// Weights of the shifted record sets, by their set identifier.
+    pub weights: IndexMap<String, i64>,
+}
+
+impl ShiftedWeights {
+    fn new(hosted_zone_id: &str, resource_record_sets: &[ResourceRecordSet]) -> ShiftedWeights {
+        ShiftedWeights {
+            hosted_zone_id: hosted_zone_id.to_owned(),
+            weights: resource_record_sets
+                .iter()
+                .map(|resource_record_set| {
+                    (
+                        resource_record_set
+                            .set_identifier()
+                            .unwrap_or_default()
+                            .to_owned(),
+                        resource_record_set.weight().unwrap_or_default(),
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Shift the weights of the weighted record sets, returning the weights after the last step.
+///
+/// Every step waits for the change to be propagated. The step number and the weights after it are
+/// passed to `on_step` once the change of the step was propagated.
+pub async fn shift_route53_weights<F: FnMut(u32, &ShiftedWeights)>(
+    route53: &Route53Client,
+    shift: &WeightShift,
+    mut on_step: F,
+) -> Result<ShiftedWeights, Error> {
+    let hosted_zone_id = shift.hosted_zone.find_id(route53).await?;
+    let resource_record_sets = route53
+        .list_resource_record_sets(&hosted_zone_id, &shift.record_name, &shift.record_type)
+        .await?;
+
+    // Pair every record set to change with its current and target weight.
+    let shifts = shift
+        .weights
+        .iter()
+        .map(|weight| {
+            resource_record_sets
+                .iter()
+                .find(|resource_record_set| {
+                    resource_record_set.set_identifier() == Some(&weight.set_identifier)
+                        && resource_record_set.weight().is_some()
+                })
+                .map(|resource_record_set| {
+                    (
+                        resource_record_set,
+                        resource_record_set.weight().unwrap_or_default(),
+                        weight.weight,
+                    )
+                })
+                .ok_or_else(|| {
+                    Error::ResourceNotFound(format!(
+                        "weighted {} record set {} with set identifier {}",
+                        shift.record_type, shift.record_name, weight.set_identifier
+                    ))
+                })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    let steps = shift.steps.get();
+    let mut shifted = ShiftedWeights::new(&hosted_zone_id, &[]);
+    for step in 1..=steps {
+        let resource_record_sets: Vec<_> = shifts
+            .iter()
+            .map(|(resource_record_set, current, target)| {
+                resource_record_set.with_weight(step_weight(*current, *target, step, steps))
+            })
+            .collect();
+        let change_id = route53
+            .upsert_resource_record_sets(
+                &hosted_zone_id,
+                &format!("awsx: shift weights, step {} of {}", step, steps),
+                &resource_record_sets,
+            )
+            .await?;
+        route53
+            .wait_for_change(&change_id, shift.change_timeout)
+            .await?;
+        shifted = ShiftedWeights::new(&hosted_zone_id, &resource_record_sets);
+        on_step(step, &shifted);
+
+        if step < steps {
+            tokio::time::sleep(shift.step_interval).await;
+        }
+    }
+
+    Ok(shifted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weights_are_interpolated_per_step() {
+        assert_eq!(
+            vec![25, 50, 75, 100],
+            (1..=4)
+                .map(|step| step_weight(0, 100, step, 4))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(0, step_weight(100, 0, 4, 4));
+    }
+
+    #[test]
+    fn weight_is_parsed_and_range_checked() {
+        assert_eq!(
+            Ok(Weight {
+                set_identifier: "green".to_owned(),
+                weight: 100,
+            }),
+            "green=100".parse()
+        );
+        assert!("green=256".parse::<Weight>().is_err());
+        assert!("green".parse::<Weight>().is_err());
+    }
+}
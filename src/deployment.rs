@@ -0,0 +1,551 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Metadata recording who deployed what to a stack, and the git history between deployments.
+//!
+//! The metadata is stored as JSON in a parameter of the stack, by default `DeploymentMetadata`, so
+//! that later deployments can verify that they build upon the deployed changes.
+
+use chrono::{Local, SecondsFormat};
+use failure::format_err;
+use git2::{Config, Oid, Repository};
+use indexmap::IndexMap;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::{convert::TryFrom, fmt};
+
+use crate::{
+    error::Error,
+    parameter::{Parameter, Parameters, NO_ECHO_PARAMETER_VALUE},
+    template::Template,
+};
+
+/// The maximum length of a parameter value accepted by CloudFormation.
+const MAX_PARAMETER_VALUE_LENGTH: usize = 4096;
+
+/// Metadata about a deployment, recorded in the deployment-metadata parameter of the stack.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct DeploymentMetadata {
+    /// The git user.email of the user who deployed.
+    pub user: String,
+    /// When the deployment happened, as an RFC 3339 timestamp.
+    pub when: String,
+    /// The git commit that was deployed.
+    pub git: DeploymentMetadataGit,
+    /// The stack the deployment was promoted from, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub promoted_from: Option<DeploymentMetadataPromotion>,
+    /// What the deployment applied to the stack, if it could be recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub applied: Option<DeploymentMetadataApplied>,
+}
+
+impl DeploymentMetadata {
+    /// Read the deployment metadata from the parameter with the given key, if it is among the
+    /// parameters and holds valid metadata.
+    pub fn from_parameters(parameters: &Parameters, key: &str) -> Option<DeploymentMetadata> {
+        parameters
+            .get(key)
+            .cloned()
+            .and_then(|parameter| DeploymentMetadata::try_from(parameter).ok())
+    }
+
+    /// Summarize who deployed what, e.g. as the description of a change set.
+    pub fn summary(&self) -> String {
+        let mut summary = format!("Deployed by {}", self.user);
+        if !self.git.commit.is_empty() {
+            summary.push_str(&format!(
+                " from {} ({}{})",
+                self.git.r#ref,
+                &self.git.commit[..self.git.commit.len().min(12)],
+                if self.git.dirty { ", dirty" } else { "" }
+            ));
+        }
+        if let Some(promoted_from) = &self.promoted_from {
+            summary.push_str(&format!(
+                ", promoted from {} in {}",
+                promoted_from.stack_name, promoted_from.region
+            ));
+        }
+        summary.push_str(&format!(" at {}", self.when));
+        summary
+    }
+
+    /// Record the template and parameters the deployment applies, so that it can be re-applied
+    /// later, e.g. to roll back after a failed canary.
+    ///
+    /// The template is given if the deployment applies a new one, together with the URL it is
+    /// uploaded to, if any. Otherwise the previously recorded template is kept. Parameters keeping
+    /// their previous value are looked up in the deployed parameters, if given, or in the previous
+    /// record. If the value of any parameter is unknown, nothing is recorded. The values of `NoEcho`
+    /// parameters, and of parameters whose key `is_sensitive` matches, are never recorded.
+    pub fn record_applied<F: Fn(&str) -> bool>(
+        &mut self,
+        template: Option<(&Template, Option<String>)>,
+        parameters: &Parameters,
+        deployed_parameters: Option<&Parameters>,
+        metadata_key: &str,
+        is_sensitive: F,
+    ) {
+        let previous = self.applied.take().unwrap_or_default();
+        let deployed_value = |key: &str| match deployed_parameters.and_then(|p| p.get(key)) {
+            Some(Parameter::WithValue { value, .. }) => Some(value.as_str()),
+            _ => None,
+        };
+        let (template_url, no_echo) = match template {
+            Some((template, template_url)) => (
+                template_url,
+                template
+                    .get_parameter_declarations()
+                    .iter()
+                    .filter(|(_, declaration)| declaration.no_echo)
+                    .map(|(key, _)| key.clone())
+                    .collect::<Vec<_>>(),
+            ),
+            None => (
+                previous.template_url.clone(),
+                parameters
+                    .keys()
+                    .filter(|key| {
+                        matches!(previous.parameters.get(*key), Some(None))
+                            || deployed_value(key) == Some(NO_ECHO_PARAMETER_VALUE)
+                    })
+                    .cloned()
+                    .collect(),
+            ),
+        };
+
+        let mut applied = DeploymentMetadataApplied {
+            template_url,
+            ..Default::default()
+        };
+        for (key, parameter) in parameters.iter() {
+            if key == metadata_key {
+                continue;
+            }
+            let value = match parameter {
+                _ if no_echo.contains(key) || is_sensitive(key) => {
+                    if !parameter.is_previous_value() {
+                        applied.changed_no_echo_parameters.push(key.clone());
+                    }
+                    None
+                }
+                Parameter::WithValue { value, .. } => Some(value.clone()),
+                Parameter::PreviousValue { .. } => {
+                    match (deployed_value(key), previous.parameters.get(key)) {
+                        (Some(value), _) => Some(value.to_owned()),
+                        (None, Some(Some(value))) => Some(value.clone()),
+                        (None, _) => return,
+                    }
+                }
+            };
+            applied.parameters.insert(key.clone(), value);
+        }
+        self.applied = Some(applied);
+    }
+
+    /// Check whether the metadata fits into the value of a parameter, whose length CloudFormation
+    /// limits to 4096 characters.
+    pub fn fits_parameter_value(&self) -> bool {
+        self.to_string().len() <= MAX_PARAMETER_VALUE_LENGTH
+    }
+
+    /// Turn the metadata into the value of the deployment-metadata parameter.
+    ///
+    /// If the record of what was applied would exceed the maximum length of a parameter value, it
+    /// is left out, see [`fits_parameter_value`].
+    ///
+    /// [`fits_parameter_value`]: #method.fits_parameter_value
+    pub fn into_parameter(mut self, key: &str) -> Parameter {
+        if self.applied.is_some() && !self.fits_parameter_value() {
+            self.applied = None;
+        }
+        Parameter::WithValue {
+            key: key.to_owned(),
+            value: self.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for DeploymentMetadata {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", serde_json::to_string(self).unwrap_or_default())
+    }
+}
+
+/// The git commit a deployment was made from.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct DeploymentMetadataGit {
+    /// ID of the commit, empty if the deployment was not made from a git repository.
+    pub commit: String,
+    /// The ref that was checked out, e.g. the branch name.
+    pub r#ref: String,
+    /// Whether the working tree had uncommitted changes.
+    pub dirty: bool,
+}
+
+/// What a deployment applied to the stack.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct DeploymentMetadataApplied {
+    /// URL of the template in S3, `None` if the template was submitted inline.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template_url: Option<String>,
+    /// The values of all parameters, except for the deployment metadata itself. The values of
+    /// `NoEcho` and sensitive parameters are `None`.
+    #[serde(default)]
+    pub parameters: IndexMap<String, Option<String>>,
+    /// Keys of the `NoEcho` and sensitive parameters that were given a value, instead of keeping
+    /// their previous value.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub changed_no_echo_parameters: Vec<String>,
+}
+
+/// Record of the stack a deployment was promoted from, e.g. from staging to production.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct DeploymentMetadataPromotion {
+    /// Name of the stack the deployment was promoted from.
+    pub stack_name: String,
+    /// Region of the stack the deployment was promoted from.
+    pub region: String,
+    /// When the promoted deployment was deployed on the source stack.
+    pub when: String,
+}
+
+impl TryFrom<Parameter> for DeploymentMetadata {
+    type Error = Error;
+
+    fn try_from(parameter: Parameter) -> Result<Self, Self::Error> {
+        match parameter {
+            Parameter::WithValue { value, .. } => serde_json::from_str(&value).map_err(Into::into),
+            Parameter::PreviousValue { key } => Err(Error::InvalidParameters(key)),
+        }
+    }
+}
+
+/// Generate the metadata of a new deployment, based on the metadata of the previous deployment, if
+/// any.
+///
+/// The user is taken from the git configuration. If a path is given, the commit checked out in
+/// the repository containing it is recorded too.
+pub fn generate_deployment_metadata(
+    previous_metadata_parameter: Option<Parameter>,
+    git_discover_path: Option<&str>,
+) -> Result<DeploymentMetadata, Error> {
+    let mut metadata = previous_metadata_parameter
+        .and_then(|parameter| DeploymentMetadata::try_from(parameter).ok())
+        .unwrap_or_default();
+
+    metadata.user = Config::open_default()?
+        .get_string("user.email")
+        .unwrap_or_else(|_| "unknown".to_owned());
+    metadata.when = Local::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+
+    if let Some(git_discover_path) = git_discover_path {
+        let repo = Repository::discover(git_discover_path)?;
+        let head = repo.head()?;
+        let r#ref = head
+            .shorthand()
+            .ok_or_else(|| Error::GitError(format_err!("Failed to retrieve ref for git HEAD")))?
+            .to_owned();
+        let commit = format!(
+            "{}",
+            head.target().ok_or_else(|| Error::GitError(format_err!(
+                "Failed to retrieve commit for git HEAD"
+            )))?
+        );
+        let statuses = repo.statuses(Some(git2::StatusOptions::new().include_untracked(false)))?;
+        let dirty = !statuses.is_empty();
+
+        metadata.user = repo
+            .config()?
+            .get_string("user.email")
+            .unwrap_or_else(|_| "unknown".to_owned());
+        metadata.git = DeploymentMetadataGit {
+            commit,
+            r#ref,
+            dirty,
+        }
+    }
+
+    Ok(metadata)
+}
+
+/// A commit in the changelog between two deployments.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangelogEntry {
+    /// ID of the commit.
+    pub commit: String,
+    /// First line of the commit message.
+    pub subject: String,
+    /// Name of the author, or their email if the name is unknown.
+    pub author: String,
+    /// Number of the pull request the commit was merged with, if its subject mentions one.
+    pub pull_request: Option<u64>,
+}
+
+/// Extract the number of the pull request from a commit subject, as written by GitHub for merge
+/// commits ("Merge pull request #12 from ...") and squash merges ("Subject (#12)").
+pub fn pull_request_number(subject: &str) -> Option<u64> {
+    let pattern = Regex::new(r"^Merge pull request #(\d+)|\(#(\d+)\)$").expect("valid regex");
+    pattern
+        .captures(subject.trim_end())
+        .and_then(|captures| captures.get(1).or_else(|| captures.get(2)))
+        .and_then(|number| number.as_str().parse().ok())
+}
+
+/// Collect the commits reachable from `until` (or HEAD) but not from `since`, newest first.
+pub fn git_changelog(
+    git_discover_path: &str,
+    since: &str,
+    until: Option<&str>,
+) -> Result<Vec<ChangelogEntry>, Error> {
+    let repo = Repository::discover(git_discover_path)?;
+    let until = match until {
+        Some(until) => Oid::from_str(until)?,
+        None => repo.head()?.target().ok_or_else(|| {
+            Error::GitError(format_err!("Failed to retrieve commit for git HEAD"))
+        })?,
+    };
+
+    let mut revwalk = repo.revwalk()?;
+    revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
+    revwalk.push(until)?;
+    revwalk.hide(Oid::from_str(since)?)?;
+
+    revwalk
+        .map(|oid| {
+            let commit = repo.find_commit(oid?)?;
+            let subject = commit.summary().unwrap_or_default().to_owned();
+            let author = commit.author();
+            Ok(ChangelogEntry {
+                commit: commit.id().to_string(),
+                pull_request: pull_request_number(&subject),
+                subject,
+                author: author
+                    .name()
+                    .or_else(|| author.email())
+                    .unwrap_or("unknown")
+                    .to_owned(),
+            })
+        })
+        .collect()
+}
+
+/// Verify that the changes of the current deployment are compatible with those of the previous one,
+/// i.e. that the previously deployed commit is an ancestor of the current one.
+///
+/// If the previous deployment was made from a dirty working tree, the changes are only compatible
+/// if the same user deploys again on top of the same commit.
+pub fn verify_changes_compatible(
+    previous_metadata: &DeploymentMetadata,
+    current_metadata: &DeploymentMetadata,
+    git_discover_path: &str,
+) -> Result<bool, Error> {
+    // Find the common ancestor
+    let previous_commit_is_common_ancestor =
+        if previous_metadata.git.commit == current_metadata.git.commit {
+            true
+        } else {
+            // Retrieve previous and current commit
+            let repo = Repository::discover(git_discover_path)?;
+            let previous_commit = Oid::from_str(&previous_metadata.git.commit)?;
+            let current_commit = repo.head()?.target().ok_or_else(|| {
+                Error::GitError(format_err!("Failed to retrieve commit for git HEAD"))
+            })?;
+
+            match repo.merge_base(previous_commit, current_commit) {
+                Ok(common_ancestor) => previous_commit == common_ancestor,
+                Err(ref e)
+                    if e.code() == git2::ErrorCode::GenericError
+                        && e.class() == git2::ErrorClass::Odb =>
+                {
+                    // If either of the commits we are comparing is unknown to the repository, the
+                    // error returned will be of code `GenericError` and class `Odb` (bad object).
+                    // Rather than showing that error, which can commonly occur if either the
+                    // deployed changes are based on a commit another developer only has locally, or
+                    // if the user has rebased their own changes since the last time they deployed,
+                    // we simply return `false` here indicating that the changes are not compatible.
+                    false
+                }
+                Err(e) => return Err(e.into()),
+            }
+        };
+
+    // In general it is true that if the previous changes were dirty, we cannot guarantee any
+    // compatibility. We make one exception: if the user stays unchanged, and the previous commit is
+    // the common ancestor, we assume that the change is just the person developing and testing.
+    if previous_metadata.git.dirty {
+        return Ok(
+            previous_metadata.user == current_metadata.user && previous_commit_is_common_ancestor
+        );
+    }
+
+    // If the previous changes were not dirty, we can now verify if the current commit is a direct
+    // descendant from the previous commit. If it isn't, the two commits are out of two separate
+    // trees and we thus cannot assume them to be compatible.
+    Ok(previous_commit_is_common_ancestor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pull_request_number_from_merge_and_squash_commits() {
+        assert_eq!(
+            pull_request_number("Merge pull request #42 from takkt-ag/feature"),
+            Some(42)
+        );
+        assert_eq!(pull_request_number("Add deployment reports (#7)"), Some(7));
+        assert_eq!(pull_request_number("Fix #3 in the middle"), None);
+    }
+
+    fn with_value(key: &str, value: &str) -> Parameter {
+        Parameter::WithValue {
+            key: key.to_owned(),
+            value: value.to_owned(),
+        }
+    }
+
+    fn previous_value(key: &str) -> Parameter {
+        Parameter::PreviousValue {
+            key: key.to_owned(),
+        }
+    }
+
+    #[test]
+    fn record_applied_withholds_no_echo_and_sensitive_values() {
+        let template = Template::from_bytes(
+            "Parameters:\n  Name:\n    Type: String\n  Secret:\n    Type: String\n    NoEcho: true\n\
+             Resources: {}\n",
+        )
+        .unwrap();
+        let mut metadata = DeploymentMetadata::default();
+        metadata.record_applied(
+            Some((&template, Some("https://bucket/key.template".to_owned()))),
+            &vec![
+                with_value("Name", "app"),
+                with_value("Secret", "hunter2"),
+                with_value("ApiToken", "abc123"),
+                with_value("DeploymentMetadata", "{}"),
+            ]
+            .into(),
+            None,
+            "DeploymentMetadata",
+            |key| key == "ApiToken",
+        );
+
+        let applied = metadata.applied.unwrap();
+        assert_eq!(
+            Some("https://bucket/key.template"),
+            applied.template_url.as_deref()
+        );
+        assert_eq!(
+            vec![
+                ("Name".to_owned(), Some("app".to_owned())),
+                ("Secret".to_owned(), None),
+                ("ApiToken".to_owned(), None),
+            ],
+            applied.parameters.into_iter().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec!["Secret", "ApiToken"],
+            applied.changed_no_echo_parameters
+        );
+    }
+
+    #[test]
+    fn record_applied_resolves_previous_values() {
+        let mut metadata = DeploymentMetadata {
+            applied: Some(DeploymentMetadataApplied {
+                template_url: Some("https://bucket/old.template".to_owned()),
+                parameters: vec![
+                    ("Name".to_owned(), Some("app".to_owned())),
+                    ("Size".to_owned(), Some("1".to_owned())),
+                    ("Secret".to_owned(), None),
+                ]
+                .into_iter()
+                .collect(),
+                changed_no_echo_parameters: vec!["Secret".to_owned()],
+            }),
+            ..Default::default()
+        };
+        metadata.record_applied(
+            None,
+            &vec![
+                previous_value("Name"),
+                with_value("Size", "2"),
+                previous_value("Secret"),
+                previous_value("Other"),
+            ]
+            .into(),
+            Some(
+                &vec![
+                    with_value("Name", "app"),
+                    with_value("Size", "1"),
+                    with_value("Secret", NO_ECHO_PARAMETER_VALUE),
+                    with_value("Other", "deployed"),
+                ]
+                .into(),
+            ),
+            "DeploymentMetadata",
+            |_| false,
+        );
+
+        let applied = metadata.applied.clone().unwrap();
+        assert_eq!(
+            Some("https://bucket/old.template"),
+            applied.template_url.as_deref()
+        );
+        assert_eq!(Some(&Some("2".to_owned())), applied.parameters.get("Size"));
+        assert_eq!(
+            Some(&Some("deployed".to_owned())),
+            applied.parameters.get("Other")
+        );
+        assert_eq!(Some(&None), applied.parameters.get("Secret"));
+        assert!(applied.changed_no_echo_parameters.is_empty());
+
+        // The value of a parameter neither deployed nor recorded before is unknown.
+        metadata.record_applied(
+            None,
+            &vec![previous_value("Other"), previous_value("Unknown")].into(),
+            None,
+            "DeploymentMetadata",
+            |_| false,
+        );
+        assert_eq!(None, metadata.applied);
+    }
+
+    #[test]
+    fn into_parameter_drops_oversized_record() {
+        let metadata = DeploymentMetadata {
+            applied: Some(DeploymentMetadataApplied {
+                parameters: vec![("Large".to_owned(), Some("x".repeat(5000)))]
+                    .into_iter()
+                    .collect(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        match metadata.into_parameter("DeploymentMetadata") {
+            Parameter::WithValue { value, .. } => {
+                assert!(value.len() <= MAX_PARAMETER_VALUE_LENGTH);
+                assert!(!value.contains("applied"));
+            }
+            Parameter::PreviousValue { .. } => panic!("expected a value"),
+        }
+    }
+}
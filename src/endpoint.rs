@@ -0,0 +1,209 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+//! Resolution of FIPS and dual-stack endpoints.
+//!
+//! Rusoto derives the endpoint of a client from its region, always using the default endpoint of
+//! the service. To use the FIPS or dual-stack endpoints instead, every client is created with a
+//! region resolved through [`region_for`], which carries the alternative endpoint if one of them
+//! was [`configure`]d.
+//!
+//! [`region_for`]: fn.region_for.html
+//! [`configure`]: fn.configure.html
+
+use rusoto_core::Region;
+use std::sync::OnceLock;
+
+use crate::error::Error;
+
+static ENDPOINT_OPTIONS: OnceLock<EndpointOptions> = OnceLock::new();
+
+/// Global services, which are not available through FIPS or dual-stack endpoints of the form used
+/// by regional services.
+const GLOBAL_SERVICES: &[&str] = &["cloudfront", "iam", "route53"];
+
+/// The partition a region belongs to, which determines the domain of its endpoints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Partition {
+    /// The standard partition of the commercial regions.
+    Aws,
+    /// The partition of the regions in China.
+    AwsCn,
+    /// The partition of the AWS GovCloud (US) regions.
+    AwsUsGov,
+}
+
+impl Partition {
+    /// Determine the partition of a region from its name.
+    ///
+    /// ```
+    /// # use awsx::endpoint::Partition;
+    /// # use rusoto_core::Region;
+    /// assert_eq!(Partition::Aws, Partition::of(&Region::EuCentral1));
+    /// assert_eq!(Partition::AwsCn, Partition::of(&Region::CnNorthwest1));
+    /// assert_eq!(Partition::AwsUsGov, Partition::of(&Region::UsGovWest1));
+    /// ```
+    pub fn of(region: &Region) -> Partition {
+        let name = region.name();
+        if name.starts_with("cn-") {
+            Partition::AwsCn
+        } else if name.starts_with("us-gov-") {
+            Partition::AwsUsGov
+        } else {
+            Partition::Aws
+        }
+    }
+
+    /// The name of the partition as used in ARNs, e.g. `aws`.
+    ///
+    /// ```
+    /// # use awsx::endpoint::Partition;
+    /// assert_eq!("aws-cn", Partition::AwsCn.name());
+    /// ```
+    pub fn name(self) -> &'static str {
+        match self {
+            Partition::Aws => "aws",
+            Partition::AwsCn => "aws-cn",
+            Partition::AwsUsGov => "aws-us-gov",
+        }
+    }
+
+    /// The domain of the endpoints in the partition, e.g. `amazonaws.com`.
+    pub fn dns_suffix(self) -> &'static str {
+        match self {
+            Partition::Aws | Partition::AwsUsGov => "amazonaws.com",
+            Partition::AwsCn => "amazonaws.com.cn",
+        }
+    }
+
+    /// The domain of the dual-stack endpoints in the partition, e.g. `api.aws`.
+    pub fn dualstack_dns_suffix(self) -> &'static str {
+        match self {
+            Partition::Aws | Partition::AwsUsGov => "api.aws",
+            Partition::AwsCn => "api.amazonwebservices.com.cn",
+        }
+    }
+}
+
+/// Options selecting the kind of endpoint all clients use.
+#[derive(Debug, Clone, Default)]
+pub struct EndpointOptions {
+    use_fips: bool,
+    use_dualstack: bool,
+}
+
+impl EndpointOptions {
+    /// Use the FIPS endpoints, which only use FIPS 140-2 validated cryptography.
+    pub fn with_fips(mut self, use_fips: bool) -> Self {
+        self.use_fips = use_fips;
+        self
+    }
+
+    /// Use the dual-stack endpoints, which can be reached through IPv4 and IPv6.
+    pub fn with_dualstack(mut self, use_dualstack: bool) -> Self {
+        self.use_dualstack = use_dualstack;
+        self
+    }
+
+    /// Get the endpoint of the service in the region, or `None` if the default endpoint should be
+    /// used.
+    ///
+    /// ```
+    /// # use awsx::endpoint::EndpointOptions;
+    /// # use rusoto_core::Region;
+    /// let fips = EndpointOptions::default().with_fips(true);
+    /// assert_eq!(
+    ///     Some("https://cloudformation-fips.us-gov-west-1.amazonaws.com".to_owned()),
+    ///     fips.endpoint("cloudformation", &Region::UsGovWest1),
+    /// );
+    ///
+    /// let dualstack = EndpointOptions::default().with_dualstack(true);
+    /// assert_eq!(
+    ///     Some("https://s3.dualstack.eu-central-1.amazonaws.com".to_owned()),
+    ///     dualstack.endpoint("s3", &Region::EuCentral1),
+    /// );
+    /// assert_eq!(
+    ///     Some("https://ec2.eu-central-1.api.aws".to_owned()),
+    ///     dualstack.endpoint("ec2", &Region::EuCentral1),
+    /// );
+    ///
+    /// assert_eq!(None, EndpointOptions::default().endpoint("ec2", &Region::EuCentral1));
+    /// assert_eq!(None, fips.endpoint("route53", &Region::UsEast1));
+    /// ```
+    pub fn endpoint(&self, service: &str, region: &Region) -> Option<String> {
+        if !self.use_fips && !self.use_dualstack {
+            return None;
+        }
+        if GLOBAL_SERVICES.contains(&service) {
+            return None;
+        }
+        if let Region::Custom { .. } = region {
+            return None;
+        }
+
+        let partition = Partition::of(region);
+        let prefix = if self.use_fips {
+            format!("{}-fips", service)
+        } else {
+            service.to_owned()
+        };
+        let hostname = if !self.use_dualstack {
+            format!("{}.{}.{}", prefix, region.name(), partition.dns_suffix())
+        } else if service == "s3" {
+            // S3 predates the `api.aws` domain of the dual-stack endpoints of other services.
+            format!(
+                "{}.dualstack.{}.{}",
+                prefix,
+                region.name(),
+                partition.dns_suffix()
+            )
+        } else {
+            format!(
+                "{}.{}.{}",
+                prefix,
+                region.name(),
+                partition.dualstack_dns_suffix()
+            )
+        };
+        Some(format!("https://{}", hostname))
+    }
+}
+
+/// Configure the kind of endpoint all clients created from now on use.
+///
+/// The options can only be configured once.
+pub fn configure(options: EndpointOptions) -> Result<(), Error> {
+    ENDPOINT_OPTIONS.set(options).map_err(|_| {
+        Error::InvalidConfiguration("the endpoint options were already configured".to_owned())
+    })
+}
+
+/// Resolve the region to create a client for the service with, e.g. `cloudformation`, carrying
+/// the FIPS or dual-stack endpoint if configured.
+///
+/// The service is the prefix of the default endpoint of the service, e.g. `monitoring` for
+/// CloudWatch.
+pub fn region_for(service: &str, region: Region) -> Region {
+    match ENDPOINT_OPTIONS
+        .get()
+        .and_then(|options| options.endpoint(service, &region))
+    {
+        Some(endpoint) => Region::Custom {
+            name: region.name().to_owned(),
+            endpoint,
+        },
+        None => region,
+    }
+}
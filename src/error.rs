@@ -19,16 +19,56 @@
 #![allow(non_local_definitions)]
 
 use failure::Fail;
+use serde::Serialize;
+use std::fmt;
+
+use crate::parameter::ParameterValidationReport;
 
 /// Representation of an error within awsx.
 #[derive(Debug, Fail)]
 pub enum Error {
+    /// More than one resource matches the filters, where exactly one resource was expected.
+    #[fail(display = "ambiguous filters: {}", 0)]
+    AmbiguousResource(String),
     /// Error caused in Rusoto, in proxy from AWS.
     #[fail(display = "failed to perform AWS action: {}", 0)]
     AwsError(#[fail(cause)] failure::Error),
+    /// An error of the service Rusoto mapped to a specific error, in proxy from AWS, along with its
+    /// error code, e.g. `ChangeSetNotFound`.
+    #[fail(display = "failed to perform AWS action: {}", 1)]
+    AwsServiceError(String, #[fail(cause)] failure::Error),
+    /// The creation of a change set failed.
+    ///
+    /// This notably also happens if the change set would not contain any changes.
+    #[fail(display = "change set creation failed: {}", 0)]
+    ChangeSetCreationFailed(String),
+    /// A DB snapshot or DB cluster snapshot ended up in a status other than `available`.
+    #[fail(display = "DB snapshot {} failed with status {}", 0, 1)]
+    DbSnapshotFailed(String, String),
+    /// A DB snapshot or DB cluster snapshot could not be shared with other accounts.
+    #[fail(display = "DB snapshot {} cannot be shared: {}", 0, 1)]
+    DbSnapshotNotShareable(String, String),
+    /// A phase of a deployment was started before the phases it depends on have completed.
+    #[fail(display = "phase {} requires phase {} to be completed first", 0, 1)]
+    DeploymentPhaseOutOfOrder(String, String),
     /// Error caused in git2
     #[fail(display = "failed to perform git action: {}", 0)]
     GitError(#[fail(cause)] failure::Error),
+    /// A hook command failed, aborting the command.
+    #[fail(display = "hook failed: {}", 0)]
+    HookFailed(String),
+    /// Dispatching a request to AWS failed, e.g. due to a network issue.
+    #[fail(display = "failed to dispatch request to AWS: {}", 0)]
+    HttpDispatchError(#[fail(cause)] failure::Error),
+    /// A connection to AWS could not be established or the response did not arrive in time.
+    #[fail(display = "request to AWS timed out: {}", 0)]
+    HttpTimeout(#[fail(cause)] crate::http::TimeoutError),
+    /// An instance refresh of an auto-scaling group failed or was cancelled.
+    #[fail(display = "instance refresh of {} failed with status {}", 0, 1)]
+    InstanceRefreshFailed(String, String),
+    /// The configuration provided is not valid.
+    #[fail(display = "invalid configuration: {}", 0)]
+    InvalidConfiguration(String),
     /// The parameters provided were invalid.
     ///
     /// This can happen if either the template or stack the parameters should be applied to do not
@@ -44,15 +84,49 @@ pub enum Error {
     /// A general IO error.
     #[fail(display = "general IO error")]
     IoError(#[fail(cause)] std::io::Error),
+    /// A parameter file could not be parsed or serialized.
+    #[fail(display = "invalid parameter file {}: {}", 0, 1)]
+    ParameterFileInvalid(String, #[fail(cause)] failure::Error),
+    /// The parameters provided did not pass the validation against the template.
+    #[fail(display = "invalid parameters provided: {}", 0)]
+    ParameterValidationFailed(ParameterValidationReport),
+    /// The subcommand is unknown, and there is no plugin providing it either.
+    #[fail(
+        display = "unknown subcommand, no plugin executable '{}' found on the PATH",
+        0
+    )]
+    PluginNotFound(String),
+    /// The template or parameters violate the policy rules.
+    #[fail(display = "policy validation failed:\n{}", 0)]
+    PolicyValidationFailed(String),
+    /// A change set removes or replaces resources that are protected.
+    #[fail(display = "refusing to remove or replace protected resources: {}", 0)]
+    ProtectedResourceChange(String),
     /// Error caused while parsing a regex
     #[fail(display = "failed to parse regex: {}", 0)]
     RegexParseError(String),
     /// General regex error cause while working with a regex
     #[fail(display = "general regex error")]
     RegexError(#[fail(cause)] failure::Error),
+    /// No resource matches the filters, where exactly one resource was expected.
+    #[fail(display = "unable to find {}", 0)]
+    ResourceNotFound(String),
+    /// Executing a change set was refused, since it replaces resources and the replacement was not
+    /// allowed explicitly.
+    #[fail(display = "refusing to execute change set replacing resources: {}", 0)]
+    ResourceReplacementRefused(String),
+    /// An S3 bucket is required for the requested action, but none was provided.
+    #[fail(display = "an S3 bucket is required to {}", 0)]
+    S3BucketRequired(String),
+    /// Updating the awsx binary itself failed.
+    #[fail(display = "self-update failed: {}", 0)]
+    SelfUpdateFailed(String),
     /// Error caused in serde_json
     #[fail(display = "failed to perform serde_json action: {}", 0)]
     SerdeJsonError(#[fail(cause)] serde_json::Error),
+    /// A stack operation, e.g. executing a change set, failed or was rolled back.
+    #[fail(display = "stack operation failed with status {}", 0)]
+    StackOperationFailed(String),
     /// Deserializing the template failed.
     #[fail(display = "failed to deserialize the template")]
     TemplateDeserializationFailed(#[fail(cause)] failure::Error),
@@ -60,11 +134,271 @@ pub enum Error {
     #[fail(display = "failed to perform Rusoto action")]
     TlsError(#[fail(cause)] failure::Error),
     /// An unknown error caused in Rusoto, in proxy from AWS.
-    #[fail(display = "encountered AWS error:\n{}", 0)]
-    UnknownAwsError(String),
+    #[fail(display = "encountered AWS error {}", 0)]
+    UnknownAwsError(AwsErrorDetails),
     /// The output format specified was unknown
     #[fail(display = "specified output format is unknown: {}", 0)]
     UnknownOutputFormat(String),
+    /// Moving a resource between stacks was refused, since it could not be done safely.
+    #[fail(display = "refusing to move resource: {}", 0)]
+    UnsafeResourceMove(String),
+    /// Waiting for a resource to reach the expected state timed out.
+    #[fail(display = "timed out waiting for {}", 0)]
+    WaitTimeout(String),
+}
+
+impl Error {
+    /// Return a short, stable identifier of the kind of error, e.g. `invalid_parameters`.
+    ///
+    /// In contrast to the display output, the kind is meant to be processed by machines.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::AmbiguousResource(_) => "ambiguous_resource",
+            Error::AwsError(_) => "aws_error",
+            Error::AwsServiceError(..) => "aws_service_error",
+            Error::ChangeSetCreationFailed(_) => "change_set_creation_failed",
+            Error::DbSnapshotFailed(..) => "db_snapshot_failed",
+            Error::DbSnapshotNotShareable(..) => "db_snapshot_not_shareable",
+            Error::DeploymentPhaseOutOfOrder(..) => "deployment_phase_out_of_order",
+            Error::GitError(_) => "git_error",
+            Error::HookFailed(_) => "hook_failed",
+            Error::HttpDispatchError(_) => "http_dispatch_error",
+            Error::HttpTimeout(_) => "http_timeout",
+            Error::InstanceRefreshFailed(..) => "instance_refresh_failed",
+            Error::InvalidConfiguration(_) => "invalid_configuration",
+            Error::InvalidParameters(_) => "invalid_parameters",
+            Error::InvalidStack(_) => "invalid_stack",
+            Error::InvalidTemplate(_) => "invalid_template",
+            Error::IoError(_) => "io_error",
+            Error::ParameterFileInvalid(..) => "parameter_file_invalid",
+            Error::ParameterValidationFailed(_) => "parameter_validation_failed",
+            Error::PluginNotFound(_) => "plugin_not_found",
+            Error::PolicyValidationFailed(_) => "policy_validation_failed",
+            Error::ProtectedResourceChange(_) => "protected_resource_change",
+            Error::RegexParseError(_) => "regex_parse_error",
+            Error::RegexError(_) => "regex_error",
+            Error::ResourceNotFound(_) => "resource_not_found",
+            Error::ResourceReplacementRefused(_) => "resource_replacement_refused",
+            Error::S3BucketRequired(_) => "s3_bucket_required",
+            Error::SelfUpdateFailed(_) => "self_update_failed",
+            Error::SerdeJsonError(_) => "serde_json_error",
+            Error::StackOperationFailed(_) => "stack_operation_failed",
+            Error::TemplateDeserializationFailed(_) => "template_deserialization_failed",
+            Error::TlsError(_) => "tls_error",
+            Error::UnknownAwsError(_) => "unknown_aws_error",
+            Error::UnknownOutputFormat(_) => "unknown_output_format",
+            Error::UnsafeResourceMove(_) => "unsafe_resource_move",
+            Error::WaitTimeout(_) => "wait_timeout",
+        }
+    }
+
+    /// Check if the error is transient, i.e. retrying the action might succeed.
+    ///
+    /// This is the case for throttling, server-side errors of AWS and failures to dispatch the
+    /// request.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::HttpDispatchError(_) | Error::HttpTimeout(_) => true,
+            Error::AwsServiceError(code, _) => is_throttling_code(code),
+            Error::UnknownAwsError(details) => details.is_retryable(),
+            _ => false,
+        }
+    }
+
+    /// Check if the error was caused by the user, e.g. by providing invalid input or by lacking
+    /// permissions, in which case repeating the same action will fail again.
+    pub fn is_user_error(&self) -> bool {
+        match self {
+            Error::AmbiguousResource(_)
+            | Error::DbSnapshotNotShareable(..)
+            | Error::DeploymentPhaseOutOfOrder(..)
+            | Error::InvalidConfiguration(_)
+            | Error::InvalidParameters(_)
+            | Error::InvalidStack(_)
+            | Error::InvalidTemplate(_)
+            | Error::ParameterFileInvalid(..)
+            | Error::ParameterValidationFailed(_)
+            | Error::PluginNotFound(_)
+            | Error::PolicyValidationFailed(_)
+            | Error::ProtectedResourceChange(_)
+            | Error::RegexParseError(_)
+            | Error::ResourceReplacementRefused(_)
+            | Error::S3BucketRequired(_)
+            | Error::TemplateDeserializationFailed(_)
+            | Error::UnknownOutputFormat(_)
+            | Error::UnsafeResourceMove(_) => true,
+            // Rusoto only maps errors the service declares for the action, which are caused by the
+            // request, except for throttling.
+            Error::AwsServiceError(code, _) => !is_throttling_code(code),
+            Error::UnknownAwsError(details) => {
+                (400..500).contains(&details.http_status) && !details.is_retryable()
+            }
+            _ => false,
+        }
+    }
+
+    /// Check if the error was caused by a resource that does not exist, e.g. a stack.
+    pub fn is_not_found(&self) -> bool {
+        match self {
+            Error::InvalidStack(_) | Error::ResourceNotFound(_) => true,
+            Error::IoError(error) => error.kind() == std::io::ErrorKind::NotFound,
+            Error::AwsServiceError(code, _) => is_not_found_code(code),
+            Error::UnknownAwsError(details) => details.is_not_found(),
+            _ => false,
+        }
+    }
+
+    /// Return the keys of the parameters that caused the error, if they are known.
+    pub fn parameter_keys(&self) -> Vec<&str> {
+        match self {
+            Error::ParameterValidationFailed(report) => report.keys(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Return the error code returned by AWS, e.g. `ValidationError`, if it is known.
+    pub fn aws_error_code(&self) -> Option<&str> {
+        match self {
+            Error::AwsServiceError(code, _) => Some(code),
+            Error::UnknownAwsError(details) => details.code.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Return the details of the response returned by AWS, if they are known.
+    ///
+    /// **Note:** the details are only retained for errors Rusoto could not map to a specific error
+    /// of the service, since Rusoto discards the raw response otherwise.
+    pub fn aws_error_details(&self) -> Option<&AwsErrorDetails> {
+        match self {
+            Error::UnknownAwsError(details) => Some(details),
+            _ => None,
+        }
+    }
+}
+
+/// Details of an erroneous response returned by AWS.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AwsErrorDetails {
+    /// HTTP status code of the response.
+    pub http_status: u16,
+    /// Error code returned by AWS, e.g. `Throttling` or `AccessDenied`.
+    pub code: Option<String>,
+    /// ID of the request, which AWS support requires to investigate issues.
+    pub request_id: Option<String>,
+    /// Body of the response.
+    pub body: String,
+}
+
+impl AwsErrorDetails {
+    fn is_retryable(&self) -> bool {
+        self.http_status == 429
+            || self.http_status >= 500
+            || self
+                .code
+                .as_deref()
+                .map(is_throttling_code)
+                .unwrap_or(false)
+    }
+
+    fn is_not_found(&self) -> bool {
+        match self.code.as_deref() {
+            // CloudFormation reports missing stacks as validation errors.
+            Some("ValidationError") => self.body.contains("does not exist"),
+            Some(code) => is_not_found_code(code),
+            None => self.http_status == 404,
+        }
+    }
+
+    fn from_response(response: &rusoto_core::request::BufferedHttpResponse) -> Self {
+        let body = String::from_utf8_lossy(&response.body).into_owned();
+        let header = |name: &str| response.headers.get(name).map(ToOwned::to_owned);
+        AwsErrorDetails {
+            http_status: response.status.as_u16(),
+            code: header("x-amzn-ErrorType")
+                .or_else(|| xml_element(&body, "Code"))
+                .or_else(|| json_field(&body, "__type"))
+                .or_else(|| json_field(&body, "code"))
+                // JSON protocols can prefix the code with a namespace, e.g. `aws.foo#Throttling`,
+                // and the header can suffix it with further details, e.g. `Throttling:http://...`.
+                .map(|code| {
+                    let code = code.rsplit('#').next().unwrap_or_default();
+                    code.split(':').next().unwrap_or_default().to_owned()
+                }),
+            request_id: header("x-amzn-RequestId")
+                .or_else(|| header("x-amz-request-id"))
+                .or_else(|| xml_element(&body, "RequestId")),
+            body,
+        }
+    }
+}
+
+impl fmt::Display for AwsErrorDetails {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(HTTP status {}", self.http_status)?;
+        if let Some(code) = &self.code {
+            write!(f, ", error code {}", code)?;
+        }
+        if let Some(request_id) = &self.request_id {
+            write!(f, ", request ID {}", request_id)?;
+        }
+        write!(f, "):\n{}", self.body)
+    }
+}
+
+/// Check if the AWS error code signals that the request was throttled.
+fn is_throttling_code(code: &str) -> bool {
+    const THROTTLING_CODES: &[&str] = &[
+        "PriorRequestNotComplete",
+        "ProvisionedThroughputExceededException",
+        "RequestLimitExceeded",
+        "RequestThrottled",
+        "RequestThrottledException",
+        "SlowDown",
+        "Throttling",
+        "ThrottlingException",
+        "TooManyRequestsException",
+    ];
+
+    THROTTLING_CODES.contains(&code)
+}
+
+/// Check if the AWS error code signals that a resource does not exist.
+fn is_not_found_code(code: &str) -> bool {
+    code.starts_with("NoSuch")
+        || code.ends_with("NotFound")
+        || code.ends_with("NotFoundException")
+        || code.ends_with("NotFoundFault")
+}
+
+/// Return the error code of a service error mapped by Rusoto, which is the name of the variant.
+///
+/// The service errors are generated as enums with one tuple variant per error code, e.g.
+/// `DescribeChangeSetError::ChangeSetNotFound(String)`.
+fn service_error_code<E: fmt::Debug>(error: &E) -> String {
+    let debug = format!("{:?}", error);
+    debug
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .next()
+        .unwrap_or_default()
+        .to_owned()
+}
+
+/// Return the text of the first XML element with the given name.
+fn xml_element(body: &str, name: &str) -> Option<String> {
+    let start_tag = format!("<{}>", name);
+    let start = body.find(&start_tag)? + start_tag.len();
+    let end = start + body[start..].find(&format!("</{}>", name))?;
+    Some(body[start..end].trim().to_owned())
+}
+
+/// Return the string value of a top-level field of a JSON object.
+fn json_field(body: &str, name: &str) -> Option<String> {
+    serde_json::from_str::<serde_json::Value>(body)
+        .ok()?
+        .get(name)?
+        .as_str()
+        .map(ToOwned::to_owned)
 }
 
 impl From<std::io::Error> for Error {
@@ -79,15 +413,29 @@ where
 {
     fn from(cause: rusoto_core::RusotoError<E>) -> Self {
         match cause {
-            rusoto_core::RusotoError::Unknown(rusoto_core::request::BufferedHttpResponse {
-                body,
-                ..
-            }) => Error::UnknownAwsError(String::from_utf8_lossy(&body).into_owned()),
+            rusoto_core::RusotoError::HttpDispatch(error) => {
+                match crate::http::TimeoutError::from_dispatch_error(&error) {
+                    Some(timeout) => Error::HttpTimeout(timeout),
+                    None => Error::HttpDispatchError(error.into()),
+                }
+            }
+            rusoto_core::RusotoError::Unknown(response) => {
+                Error::UnknownAwsError(AwsErrorDetails::from_response(&response))
+            }
+            rusoto_core::RusotoError::Service(error) => {
+                Error::AwsServiceError(service_error_code(&error), error.into())
+            }
             _ => Error::AwsError(cause.into()),
         }
     }
 }
 
+impl From<rusoto_credential::CredentialsError> for Error {
+    fn from(cause: rusoto_credential::CredentialsError) -> Self {
+        Error::AwsError(cause.into())
+    }
+}
+
 impl From<rusoto_core::request::TlsError> for Error {
     fn from(cause: rusoto_core::request::TlsError) -> Self {
         Error::TlsError(cause.into())
@@ -114,3 +462,139 @@ impl From<serde_json::Error> for Error {
         Error::SerdeJsonError(cause)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusoto_core::request::BufferedHttpResponse;
+    use std::convert::TryInto;
+
+    #[test]
+    fn aws_error_details_from_xml_response() {
+        let response = BufferedHttpResponse {
+            status: 400u16.try_into().unwrap(),
+            body: "<ErrorResponse><Error><Type>Sender</Type><Code>Throttling</Code>\
+                   <Message>Rate exceeded</Message></Error>\
+                   <RequestId>3f1c3b5e-1a2b-4c3d-8e9f-0a1b2c3d4e5f</RequestId></ErrorResponse>"
+                .into(),
+            headers: Default::default(),
+        };
+
+        let details = AwsErrorDetails::from_response(&response);
+        assert_eq!(400, details.http_status);
+        assert_eq!(Some("Throttling"), details.code.as_deref());
+        assert_eq!(
+            Some("3f1c3b5e-1a2b-4c3d-8e9f-0a1b2c3d4e5f"),
+            details.request_id.as_deref()
+        );
+    }
+
+    #[test]
+    fn aws_error_details_from_json_response() {
+        let mut response = BufferedHttpResponse {
+            status: 403u16.try_into().unwrap(),
+            body: r#"{"__type":"com.amazonaws.kms#AccessDeniedException","message":"denied"}"#
+                .into(),
+            headers: Default::default(),
+        };
+        response
+            .headers
+            .insert("x-amzn-RequestId", "request-id".to_owned());
+
+        let details = AwsErrorDetails::from_response(&response);
+        assert_eq!(403, details.http_status);
+        assert_eq!(Some("AccessDeniedException"), details.code.as_deref());
+        assert_eq!(Some("request-id"), details.request_id.as_deref());
+    }
+
+    #[test]
+    fn error_classification() {
+        let aws_error = |http_status, code: &str, body: &str| {
+            Error::UnknownAwsError(AwsErrorDetails {
+                http_status,
+                code: Some(code.to_owned()),
+                request_id: None,
+                body: body.to_owned(),
+            })
+        };
+
+        let throttling = aws_error(400, "Throttling", "Rate exceeded");
+        assert!(throttling.is_retryable());
+        assert!(!throttling.is_user_error());
+        assert!(!throttling.is_not_found());
+
+        let missing_stack = aws_error(400, "ValidationError", "Stack with id foo does not exist");
+        assert!(!missing_stack.is_retryable());
+        assert!(missing_stack.is_user_error());
+        assert!(missing_stack.is_not_found());
+
+        let internal_error = aws_error(500, "InternalFailure", "");
+        assert!(internal_error.is_retryable());
+        assert!(!internal_error.is_user_error());
+
+        assert!(aws_error(404, "NoSuchKey", "").is_not_found());
+        assert!(Error::InvalidStack("foo".to_owned()).is_not_found());
+        assert!(Error::InvalidParameters("foo".to_owned()).is_user_error());
+    }
+
+    #[test]
+    fn service_error_classification() {
+        let missing_change_set = Error::from(rusoto_core::RusotoError::Service(
+            rusoto_cloudformation::DescribeChangeSetError::ChangeSetNotFound(
+                "ChangeSet [foo] does not exist".to_owned(),
+            ),
+        ));
+        assert_eq!("aws_service_error", missing_change_set.kind());
+        assert_eq!(
+            Some("ChangeSetNotFound"),
+            missing_change_set.aws_error_code()
+        );
+        assert!(missing_change_set.is_not_found());
+        assert!(missing_change_set.is_user_error());
+        assert!(!missing_change_set.is_retryable());
+        assert_eq!(
+            "failed to perform AWS action: ChangeSet [foo] does not exist",
+            missing_change_set.to_string()
+        );
+
+        let missing_snapshot = Error::from(rusoto_core::RusotoError::Service(
+            rusoto_rds::DescribeDBSnapshotsError::DBSnapshotNotFoundFault("foo".to_owned()),
+        ));
+        assert_eq!(
+            Some("DBSnapshotNotFoundFault"),
+            missing_snapshot.aws_error_code()
+        );
+        assert!(missing_snapshot.is_not_found());
+
+        let invalid_state = Error::from(rusoto_core::RusotoError::Service(
+            rusoto_rds::ModifyDBSnapshotAttributeError::InvalidDBSnapshotStateFault(
+                "foo".to_owned(),
+            ),
+        ));
+        assert!(!invalid_state.is_not_found());
+        assert!(invalid_state.is_user_error());
+    }
+
+    #[test]
+    fn http_timeout_classification() {
+        let dispatch_error = |message: &str| {
+            Error::from(
+                rusoto_core::RusotoError::<std::convert::Infallible>::HttpDispatch(
+                    rusoto_core::request::HttpDispatchError::new(message.to_owned()),
+                ),
+            )
+        };
+
+        let timeout = dispatch_error("waiting for the response timed out after 120s");
+        assert_eq!("http_timeout", timeout.kind());
+        assert!(timeout.is_retryable());
+        assert!(matches!(
+            timeout,
+            Error::HttpTimeout(crate::http::TimeoutError::Response { after })
+                if after == std::time::Duration::from_secs(120)
+        ));
+
+        let refused = dispatch_error("Error during dispatch: connection refused");
+        assert_eq!("http_dispatch_error", refused.kind());
+    }
+}
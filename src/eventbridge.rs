@@ -0,0 +1,212 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+//! This module enables putting custom events on EventBridge event buses.
+//!
+//! There is no Rusoto crate for EventBridge available to awsx, which is why this module provides a
+//! minimal client for putting events, dispatching signed requests through Rusoto.
+
+use rusoto_core::{
+    credential::ProvideAwsCredentials, signature::SignedRequest, Client, DispatchSignedRequest,
+    Region, RusotoError,
+};
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+
+use crate::error::Error;
+
+/// A custom event to put on an event bus.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Event {
+    /// Source of the event, e.g. `awsx`.
+    pub source: String,
+    /// Type of the event, e.g. `awsx.deployment`.
+    pub detail_type: String,
+    /// The event itself, as a JSON object.
+    pub detail: String,
+    /// Name or ARN of the event bus, the default event bus if unspecified.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_bus_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct PutEventsResponse {
+    #[serde(default)]
+    failed_entry_count: usize,
+    #[serde(default)]
+    entries: Vec<PutEventsResultEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct PutEventsResultEntry {
+    event_id: Option<String>,
+    error_code: Option<String>,
+    error_message: Option<String>,
+}
+
+/// A minimal client for the EventBridge API.
+pub struct EventBridgeClient {
+    client: Client,
+    region: Region,
+}
+
+impl EventBridgeClient {
+    /// Create a client dispatching its requests with the given dispatcher and credentials.
+    pub fn new_with<P, D>(request_dispatcher: D, credentials_provider: P, region: Region) -> Self
+    where
+        P: ProvideAwsCredentials + Send + Sync + 'static,
+        D: DispatchSignedRequest + Send + Sync + 'static,
+    {
+        EventBridgeClient {
+            client: Client::new_with(credentials_provider, request_dispatcher),
+            region,
+        }
+    }
+
+    /// Put the events on their event buses, returning the IDs EventBridge assigned to them.
+    ///
+    /// If any of the events was not accepted, an error describing the first rejected event is
+    /// returned.
+    pub async fn put_events(&self, events: &[Event]) -> Result<Vec<String>, Error> {
+        #[derive(Serialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct PutEventsRequest<'a> {
+            entries: &'a [Event],
+        }
+
+        let mut request = SignedRequest::new("POST", "events", &self.region, "/");
+        request.add_header("x-amz-target", "AWSEvents.PutEvents");
+        request.set_content_type("application/x-amz-json-1.1".to_owned());
+        request.set_payload(Some(serde_json::to_vec(&PutEventsRequest {
+            entries: events,
+        })?));
+
+        let mut response = self
+            .client
+            .sign_and_dispatch(request)
+            .await
+            .map_err(RusotoError::<Infallible>::from)?;
+        let response = response
+            .buffer()
+            .await
+            .map_err(RusotoError::<Infallible>::from)?;
+        if !response.status.is_success() {
+            return Err(RusotoError::<Infallible>::Unknown(response).into());
+        }
+
+        let response: PutEventsResponse = serde_json::from_slice(&response.body)?;
+        if response.failed_entry_count > 0 {
+            let failed = response
+                .entries
+                .iter()
+                .find(|entry| entry.error_code.is_some());
+            return Err(Error::AwsError(failure::format_err!(
+                "{} of {} events were not accepted: {}",
+                response.failed_entry_count,
+                events.len(),
+                failed
+                    .map(|entry| format!(
+                        "{} ({})",
+                        entry.error_message.as_deref().unwrap_or_default(),
+                        entry.error_code.as_deref().unwrap_or_default()
+                    ))
+                    .unwrap_or_default(),
+            )));
+        }
+        Ok(response
+            .entries
+            .into_iter()
+            .filter_map(|entry| entry.event_id)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockDispatcher;
+    use rusoto_core::credential::StaticProvider;
+    use serde_json::json;
+
+    fn client(dispatcher: &MockDispatcher) -> EventBridgeClient {
+        EventBridgeClient::new_with(
+            dispatcher.clone(),
+            StaticProvider::new_minimal("access-key-id".to_owned(), "secret-access-key".to_owned()),
+            Region::EuCentral1,
+        )
+    }
+
+    fn event(event_bus_name: Option<&str>) -> Event {
+        Event {
+            source: "awsx".to_owned(),
+            detail_type: "awsx.deployment".to_owned(),
+            detail: r#"{"stack_name":"app"}"#.to_owned(),
+            event_bus_name: event_bus_name.map(ToOwned::to_owned),
+        }
+    }
+
+    #[tokio::test]
+    async fn put_events_returns_event_ids() {
+        let dispatcher = MockDispatcher::new(vec![
+            r#"{"FailedEntryCount":0,"Entries":[{"EventId":"first"},{"EventId":"second"}]}"#,
+        ]);
+
+        let event_ids = client(&dispatcher)
+            .put_events(&[event(None), event(Some("deployments"))])
+            .await
+            .unwrap();
+        assert_eq!(vec!["first", "second"], event_ids);
+        assert_eq!(vec!["AWSEvents.PutEvents"], dispatcher.actions());
+        assert_eq!(
+            json!({
+                "Entries": [
+                    {
+                        "Source": "awsx",
+                        "DetailType": "awsx.deployment",
+                        "Detail": r#"{"stack_name":"app"}"#,
+                    },
+                    {
+                        "Source": "awsx",
+                        "DetailType": "awsx.deployment",
+                        "Detail": r#"{"stack_name":"app"}"#,
+                        "EventBusName": "deployments",
+                    },
+                ],
+            }),
+            serde_json::from_str::<serde_json::Value>(&dispatcher.payloads()[0]).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn put_events_fails_if_any_event_was_rejected() {
+        let dispatcher = MockDispatcher::new(vec![
+            r#"{"FailedEntryCount":1,"Entries":[{"EventId":"first"},
+                {"ErrorCode":"InternalFailure","ErrorMessage":"try again"}]}"#,
+        ]);
+
+        let error = client(&dispatcher)
+            .put_events(&[event(None), event(None)])
+            .await
+            .unwrap_err();
+        assert_eq!(
+            "failed to perform AWS action: 1 of 2 events were not accepted: try again \
+             (InternalFailure)",
+            error.to_string()
+        );
+    }
+}
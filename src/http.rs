@@ -0,0 +1,537 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! The HTTP client shared by all AWS clients.
+//!
+//! Every AWS client needs an HTTP client to dispatch its requests. Sharing a single client between
+//! all of them reuses its connection pool, which avoids establishing a new connection, including
+//! the TLS handshake, for every client a command creates.
+//!
+//! The certificates of the operating system are trusted, as well as those of a custom CA bundle,
+//! e.g. of a proxy intercepting TLS connections.
+//!
+//! Establishing a connection, waiting for a response and every read of its body are limited by a
+//! timeout, so that awsx never hangs on an unresponsive connection, while large responses can still
+//! take as long as they need. Connections that could not be established are retried a few times
+//! before giving up.
+
+use futures::{future::BoxFuture, Stream};
+use hyper::{client::HttpConnector, service::Service, Uri};
+use hyper_rustls::{HttpsConnector, HttpsConnectorBuilder};
+use rusoto_core::{
+    request::{DispatchSignedRequest, HttpDispatchError, HttpResponse},
+    signature::SignedRequest,
+    ByteStream, HttpClient,
+};
+use rustls::{Certificate, ClientConfig, RootCertStore};
+use std::{
+    fmt,
+    future::Future,
+    path::PathBuf,
+    pin::Pin,
+    sync::{Arc, OnceLock},
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::time::{Instant, Sleep};
+
+use crate::error::Error;
+
+static HTTP_CLIENT: OnceLock<Arc<AwsxHttpClient>> = OnceLock::new();
+
+static HTTP_CLIENT_OPTIONS: OnceLock<HttpClientOptions> = OnceLock::new();
+
+/// Options for the HTTP clients, which have to be [`configure`]d before the first client is
+/// created.
+///
+/// [`configure`]: fn.configure.html
+#[derive(Debug, Clone)]
+pub struct HttpClientOptions {
+    ca_bundle: Option<PathBuf>,
+    connect_timeout: Duration,
+    read_timeout: Duration,
+    connect_retries: u32,
+}
+
+impl Default for HttpClientOptions {
+    fn default() -> Self {
+        HttpClientOptions {
+            ca_bundle: None,
+            connect_timeout: Duration::from_secs(10),
+            read_timeout: Duration::from_secs(120),
+            connect_retries: 2,
+        }
+    }
+}
+
+impl HttpClientOptions {
+    /// Trust the certificates of the given PEM file in addition to those of the operating system.
+    pub fn with_ca_bundle<P: Into<PathBuf>>(mut self, ca_bundle: Option<P>) -> Self {
+        self.ca_bundle = ca_bundle.map(Into::into);
+        self
+    }
+
+    /// Limit the time to establish a connection, unless `None` is given, which keeps the default
+    /// of 10 seconds.
+    pub fn with_connect_timeout(mut self, connect_timeout: Option<Duration>) -> Self {
+        if let Some(connect_timeout) = connect_timeout {
+            self.connect_timeout = connect_timeout;
+        }
+        self
+    }
+
+    /// Limit the time to wait for the response to a request, and for every read of its body, unless
+    /// `None` is given, which keeps the default of 120 seconds.
+    pub fn with_read_timeout(mut self, read_timeout: Option<Duration>) -> Self {
+        if let Some(read_timeout) = read_timeout {
+            self.read_timeout = read_timeout;
+        }
+        self
+    }
+
+    /// Retry establishing a connection the given number of times, unless `None` is given, which
+    /// keeps the default of 2 retries.
+    pub fn with_connect_retries(mut self, connect_retries: Option<u32>) -> Self {
+        if let Some(connect_retries) = connect_retries {
+            self.connect_retries = connect_retries;
+        }
+        self
+    }
+}
+
+/// A request to AWS that timed out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimeoutError {
+    /// A connection to the host could not be established in time.
+    Connect {
+        /// The host the connection was established to.
+        host: String,
+        /// The time after which establishing the connection was given up.
+        after: Duration,
+    },
+    /// The response did not start to arrive in time.
+    Response {
+        /// The time after which waiting for the response was given up.
+        after: Duration,
+    },
+    /// The body of the response stopped arriving.
+    Read {
+        /// The time after which waiting for the next part of the body was given up.
+        after: Duration,
+    },
+}
+
+impl TimeoutError {
+    const MARKER: &'static str = " timed out after ";
+
+    /// Return the timeout that caused dispatching a request to fail, if it failed due to one.
+    ///
+    /// Rusoto only retains the message of the errors occurring while dispatching a request, and
+    /// appends it to the context it occurred in. The timeout is thus recovered from the end of the
+    /// message, which has to be exactly what the timeout displays as.
+    pub fn from_dispatch_error(error: &HttpDispatchError) -> Option<TimeoutError> {
+        let message = error.to_string();
+        let (operation, after) = message.strip_suffix('s')?.rsplit_once(Self::MARKER)?;
+        let after = Duration::try_from_secs_f64(after.parse().ok()?).ok()?;
+        if operation.ends_with("waiting for the response") {
+            Some(TimeoutError::Response { after })
+        } else if operation.ends_with("reading the response") {
+            Some(TimeoutError::Read { after })
+        } else {
+            let (_, host) = operation.rsplit_once("connecting to ")?;
+            Some(TimeoutError::Connect {
+                host: host.to_owned(),
+                after,
+            })
+        }
+    }
+}
+
+impl fmt::Display for TimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (operation, after) = match self {
+            TimeoutError::Connect { host, after } => (format!("connecting to {}", host), after),
+            TimeoutError::Response { after } => ("waiting for the response".to_owned(), after),
+            TimeoutError::Read { after } => ("reading the response".to_owned(), after),
+        };
+        write!(f, "{}{}{}s", operation, Self::MARKER, after.as_secs_f64())
+    }
+}
+
+impl std::error::Error for TimeoutError {}
+
+/// Configure the options of all HTTP clients created from now on.
+///
+/// The options can only be configured once, and only before the first client was created.
+pub fn configure(options: HttpClientOptions) -> Result<(), Error> {
+    configure_in(&HTTP_CLIENT, &HTTP_CLIENT_OPTIONS, options)
+}
+
+fn configure_in(
+    http_client: &OnceLock<Arc<AwsxHttpClient>>,
+    http_client_options: &OnceLock<HttpClientOptions>,
+    options: HttpClientOptions,
+) -> Result<(), Error> {
+    if http_client.get().is_some() {
+        return Err(Error::InvalidConfiguration(
+            "the HTTP client options must be configured before the first client is created"
+                .to_owned(),
+        ));
+    }
+    http_client_options.set(options).map_err(|_| {
+        Error::InvalidConfiguration("the HTTP client options were already configured".to_owned())
+    })
+}
+
+fn root_certificates(options: &HttpClientOptions) -> Result<RootCertStore, Error> {
+    let mut roots = RootCertStore::empty();
+    // Like Rusoto, certificates of the operating system that cannot be parsed are skipped.
+    for certificate in rustls_native_certs::load_native_certs()? {
+        let _ = roots.add(&Certificate(certificate.0));
+    }
+
+    if let Some(ca_bundle) = &options.ca_bundle {
+        let invalid_ca_bundle = |reason: &str| {
+            Error::InvalidConfiguration(format!("CA bundle {}: {}", ca_bundle.display(), reason))
+        };
+        let pem = std::fs::read(ca_bundle)?;
+        let certificates = rustls_pemfile::certs(&mut pem.as_slice())
+            .map_err(|error| invalid_ca_bundle(&error.to_string()))?;
+        let (added, _) = roots.add_parsable_certificates(&certificates);
+        if added == 0 {
+            return Err(invalid_ca_bundle("contains no valid certificates"));
+        }
+    }
+    Ok(roots)
+}
+
+/// A connector that limits the time to establish a TCP connection and retries failed attempts.
+#[derive(Debug, Clone)]
+pub struct RetryingConnector {
+    inner: HttpConnector,
+    timeout: Duration,
+    retries: u32,
+}
+
+impl Service<Uri> for RetryingConnector {
+    type Response = <HttpConnector as Service<Uri>>::Response;
+    type Error = std::io::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(std::io::Error::other)
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        let mut inner = self.inner.clone();
+        let timeout = self.timeout;
+        let retries = self.retries;
+        Box::pin(async move {
+            let mut attempt = 0;
+            loop {
+                let error = match tokio::time::timeout(timeout, inner.call(uri.clone())).await {
+                    Ok(Ok(stream)) => return Ok(stream),
+                    Ok(Err(error)) => std::io::Error::other(error),
+                    Err(_) => std::io::Error::new(
+                        std::io::ErrorKind::TimedOut,
+                        TimeoutError::Connect {
+                            host: uri.host().unwrap_or_default().to_owned(),
+                            after: timeout,
+                        },
+                    ),
+                };
+                if attempt >= retries {
+                    return Err(error);
+                }
+                attempt += 1;
+                tokio::time::sleep(Duration::from_millis(200) * attempt).await;
+            }
+        })
+    }
+}
+
+/// The body of a response, limiting the time to wait for each of its chunks.
+struct TimedBody {
+    inner: ByteStream,
+    timeout: Duration,
+    deadline: Pin<Box<Sleep>>,
+    timed_out: bool,
+}
+
+impl TimedBody {
+    fn new(inner: ByteStream, timeout: Duration) -> TimedBody {
+        TimedBody {
+            inner,
+            timeout,
+            deadline: Box::pin(tokio::time::sleep(timeout)),
+            timed_out: false,
+        }
+    }
+}
+
+impl Stream for TimedBody {
+    type Item = <ByteStream as Stream>::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.timed_out {
+            return Poll::Ready(None);
+        }
+        if let Poll::Ready(chunk) = Pin::new(&mut self.inner).poll_next(cx) {
+            let deadline = Instant::now() + self.timeout;
+            self.deadline.as_mut().reset(deadline);
+            return Poll::Ready(chunk);
+        }
+        match self.deadline.as_mut().poll(cx) {
+            Poll::Ready(()) => {
+                self.timed_out = true;
+                Poll::Ready(Some(Err(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    TimeoutError::Read {
+                        after: self.timeout,
+                    },
+                ))))
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// The HTTP client dispatching the requests to AWS, limiting the time to wait for the response and
+/// for every read of its body.
+pub struct AwsxHttpClient {
+    inner: HttpClient<HttpsConnector<RetryingConnector>>,
+    read_timeout: Duration,
+}
+
+impl DispatchSignedRequest for AwsxHttpClient {
+    fn dispatch(
+        &self,
+        request: SignedRequest,
+        timeout: Option<Duration>,
+    ) -> BoxFuture<'static, Result<HttpResponse, HttpDispatchError>> {
+        let timeout = timeout.unwrap_or(self.read_timeout);
+        let response = self.inner.dispatch(request, None);
+        Box::pin(async move {
+            // The response resolves as soon as its headers arrived, while its body is streamed.
+            let mut response = tokio::time::timeout(timeout, response)
+                .await
+                .unwrap_or_else(|_| {
+                    Err(HttpDispatchError::new(
+                        TimeoutError::Response { after: timeout }.to_string(),
+                    ))
+                })?;
+            response.body = ByteStream::new(TimedBody::new(response.body, timeout));
+            Ok(response)
+        })
+    }
+}
+
+/// Create a connector for HTTP and HTTPS, trusting the configured certificates and applying the
+/// configured connect timeout and retries.
+///
+/// This can be used for HTTP clients other than the one dispatching requests to AWS, e.g. to
+/// notify webhooks.
+pub fn https_connector() -> Result<HttpsConnector<RetryingConnector>, Error> {
+    https_connector_with(&HTTP_CLIENT_OPTIONS.get().cloned().unwrap_or_default())
+}
+
+fn https_connector_with(
+    options: &HttpClientOptions,
+) -> Result<HttpsConnector<RetryingConnector>, Error> {
+    let tls_config = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(root_certificates(options)?)
+        .with_no_client_auth();
+
+    let mut http_connector = HttpConnector::new();
+    // The scheme is checked by the HTTPS connector wrapping this connector.
+    http_connector.enforce_http(false);
+    let connector = RetryingConnector {
+        inner: http_connector,
+        timeout: options.connect_timeout,
+        retries: options.connect_retries,
+    };
+
+    Ok(HttpsConnectorBuilder::new()
+        .with_tls_config(tls_config)
+        .https_or_http()
+        .enable_http1()
+        .enable_http2()
+        .wrap_connector(connector))
+}
+
+/// Get the configured time to wait for the response to a request, and for every read of its body.
+pub fn read_timeout() -> Duration {
+    HTTP_CLIENT_OPTIONS
+        .get()
+        .cloned()
+        .unwrap_or_default()
+        .read_timeout
+}
+
+/// Get the shared HTTP client, creating it on first use.
+///
+/// The returned client can be passed to the `new_with` constructor of any Rusoto client.
+pub fn client() -> Result<Arc<AwsxHttpClient>, Error> {
+    client_in(&HTTP_CLIENT, &HTTP_CLIENT_OPTIONS)
+}
+
+fn client_in(
+    http_client: &OnceLock<Arc<AwsxHttpClient>>,
+    http_client_options: &OnceLock<HttpClientOptions>,
+) -> Result<Arc<AwsxHttpClient>, Error> {
+    if let Some(client) = http_client.get() {
+        return Ok(client.clone());
+    }
+
+    let options = http_client_options.get().cloned().unwrap_or_default();
+    let client = Arc::new(AwsxHttpClient {
+        inner: HttpClient::from_connector(https_connector_with(&options)?),
+        read_timeout: options.read_timeout,
+    });
+    Ok(http_client.get_or_init(|| client).clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    /// A self-signed certificate, valid until 2126.
+    const CERTIFICATE: &str = "-----BEGIN CERTIFICATE-----
+MIIBhDCCASugAwIBAgIUUwMLlFJtjKimOoufvlGuTGnSYTgwCgYIKoZIzj0EAwIw
+FzEVMBMGA1UEAwwMYXdzeCB0ZXN0IENBMCAXDTI2MTAxNzAwNTYwMFoYDzIxMjYw
+OTIzMDA1NjAwWjAXMRUwEwYDVQQDDAxhd3N4IHRlc3QgQ0EwWTATBgcqhkjOPQIB
+BggqhkjOPQMBBwNCAATKCsGmVnZqQVI/oalIjjSwAJFWgNMet9oo4jgGvUklLmlL
+N6GX9VIekhi0lhiyT2WHkGUKdxb0NH6BY5Q3VR/yo1MwUTAdBgNVHQ4EFgQUGsPi
+fTKXWzoZPuBaEVJY9lbauqwwHwYDVR0jBBgwFoAUGsPifTKXWzoZPuBaEVJY9lba
+uqwwDwYDVR0TAQH/BAUwAwEB/zAKBggqhkjOPQQDAgNHADBEAiBK3fk/9O4Xtlpi
+MLJprD+cU28dMHdqHy+yfeG5xrFIBgIgX8ax+RwpSSbGKcJoGvlKY8YPyL9J3LKm
+E2L9mxcn06M=
+-----END CERTIFICATE-----
+";
+
+    #[test]
+    fn options_keep_defaults_unless_given() {
+        let options = HttpClientOptions::default()
+            .with_connect_timeout(None)
+            .with_read_timeout(Some(Duration::from_secs(30)))
+            .with_connect_retries(Some(0))
+            .with_ca_bundle(None::<PathBuf>);
+
+        assert_eq!(Duration::from_secs(10), options.connect_timeout);
+        assert_eq!(Duration::from_secs(30), options.read_timeout);
+        assert_eq!(0, options.connect_retries);
+        assert_eq!(None, options.ca_bundle);
+    }
+
+    #[tokio::test]
+    async fn client_is_created_once_with_the_configured_options() {
+        let http_client = OnceLock::new();
+        let http_client_options = OnceLock::new();
+        let options = HttpClientOptions::default().with_read_timeout(Some(Duration::from_secs(5)));
+
+        configure_in(&http_client, &http_client_options, options.clone()).unwrap();
+        assert!(matches!(
+            configure_in(&http_client, &http_client_options, options.clone()),
+            Err(Error::InvalidConfiguration(message)) if message.contains("already configured")
+        ));
+
+        let client = client_in(&http_client, &http_client_options).unwrap();
+        assert_eq!(Duration::from_secs(5), client.read_timeout);
+        assert!(Arc::ptr_eq(
+            &client,
+            &client_in(&http_client, &http_client_options).unwrap()
+        ));
+
+        let http_client_options = OnceLock::new();
+        assert!(matches!(
+            configure_in(&http_client, &http_client_options, options),
+            Err(Error::InvalidConfiguration(message)) if message.contains("before the first client")
+        ));
+    }
+
+    #[test]
+    fn ca_bundle_is_trusted() {
+        let directory = std::env::temp_dir().join(format!("awsx-http-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let ca_bundle = directory.join("ca-bundle.pem");
+        let options = HttpClientOptions::default().with_ca_bundle(Some(&ca_bundle));
+
+        assert!(matches!(
+            root_certificates(&options),
+            Err(Error::IoError(_))
+        ));
+
+        std::fs::write(&ca_bundle, "not a certificate").unwrap();
+        assert!(matches!(
+            root_certificates(&options),
+            Err(Error::InvalidConfiguration(message)) if message.ends_with("contains no valid certificates")
+        ));
+
+        std::fs::write(&ca_bundle, CERTIFICATE).unwrap();
+        let native = root_certificates(&HttpClientOptions::default()).unwrap();
+        assert_eq!(native.len() + 1, root_certificates(&options).unwrap().len());
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn timeout_is_recovered_from_dispatch_error() {
+        let timeouts = vec![
+            TimeoutError::Connect {
+                host: "cloudformation.eu-central-1.amazonaws.com".to_owned(),
+                after: Duration::from_secs(10),
+            },
+            TimeoutError::Response {
+                after: Duration::from_secs(120),
+            },
+            TimeoutError::Read {
+                after: Duration::from_millis(1500),
+            },
+        ];
+        for timeout in timeouts {
+            let error = HttpDispatchError::new(format!("Error obtaining body: {}", timeout));
+            assert_eq!(Some(timeout), TimeoutError::from_dispatch_error(&error));
+        }
+
+        for message in &[
+            "Error during dispatch: connection refused",
+            "the handshake timed out after all",
+            "reading the response timed out after 2s, retrying",
+        ] {
+            let error = HttpDispatchError::new((*message).to_owned());
+            assert_eq!(None, TimeoutError::from_dispatch_error(&error));
+        }
+    }
+
+    #[tokio::test]
+    async fn timed_body_limits_every_read() {
+        let body = ByteStream::new(
+            ByteStream::from(b"first chunk".to_vec()).chain(futures::stream::pending()),
+        );
+        let mut body = TimedBody::new(body, Duration::from_millis(50));
+
+        assert_eq!(b"first chunk", &body.next().await.unwrap().unwrap()[..]);
+        let error = body.next().await.unwrap().unwrap_err();
+        assert_eq!(std::io::ErrorKind::TimedOut, error.kind());
+        assert_eq!(
+            "reading the response timed out after 0.05s",
+            error.to_string()
+        );
+        assert!(body.next().await.is_none());
+    }
+}
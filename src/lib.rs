@@ -25,9 +25,27 @@
 //! This specifically is the library used internally in the `awsx` binary. For further documentation
 //! on how to use the binary, please check the respective documentation.
 
+pub mod changeset;
+pub mod cloudtrail;
+pub mod cloudwatch;
+pub mod commands;
+pub mod deployment;
+pub mod endpoint;
 pub mod error;
+pub mod eventbridge;
+pub mod http;
+#[cfg(test)]
+mod mock;
+pub mod package;
+pub mod pagination;
 pub mod parameter;
 pub mod provider;
+pub mod rds;
+pub mod region;
+pub mod route53;
 pub mod s3;
+pub mod sns;
 pub mod stack;
+pub mod tags;
 pub mod template;
+pub mod xml;
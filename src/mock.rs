@@ -0,0 +1,146 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Clients returning canned responses, for testing code calling AWS without network access.
+
+use rusoto_cloudformation::CloudFormationClient;
+use rusoto_cloudfront::CloudFrontClient;
+use rusoto_core::{
+    credential::StaticProvider,
+    request::{DispatchSignedRequestFuture, HttpResponse},
+    signature::{SignedRequest, SignedRequestPayload},
+    ByteStream, DispatchSignedRequest, Region,
+};
+use rusoto_rds::RdsClient;
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// A request dispatcher returning the given response bodies in order, each with status 200 unless
+/// created through [`MockDispatcher::with_status`].
+///
+/// The actions and payloads of the dispatched requests are recorded, so that tests can assert which
+/// calls were made.
+#[derive(Clone, Default)]
+pub(crate) struct MockDispatcher {
+    responses: Arc<Mutex<VecDeque<(u16, String)>>>,
+    actions: Arc<Mutex<Vec<String>>>,
+    payloads: Arc<Mutex<Vec<String>>>,
+}
+
+impl MockDispatcher {
+    pub(crate) fn new<S: Into<String>>(responses: Vec<S>) -> MockDispatcher {
+        MockDispatcher::with_status(
+            responses
+                .into_iter()
+                .map(|response| (200, response))
+                .collect(),
+        )
+    }
+
+    /// Create a dispatcher returning the given response bodies with the given status codes, e.g. to
+    /// return errors of AWS.
+    pub(crate) fn with_status<S: Into<String>>(responses: Vec<(u16, S)>) -> MockDispatcher {
+        MockDispatcher {
+            responses: Arc::new(Mutex::new(
+                responses
+                    .into_iter()
+                    .map(|(status, body)| (status, body.into()))
+                    .collect(),
+            )),
+            actions: Arc::default(),
+            payloads: Arc::default(),
+        }
+    }
+
+    /// The actions of the requests dispatched so far, i.e. their `Action` parameter or, for JSON
+    /// APIs, their `X-Amz-Target` header.
+    pub(crate) fn actions(&self) -> Vec<String> {
+        self.actions.lock().unwrap().clone()
+    }
+
+    /// The payloads of the requests dispatched so far.
+    pub(crate) fn payloads(&self) -> Vec<String> {
+        self.payloads.lock().unwrap().clone()
+    }
+}
+
+impl DispatchSignedRequest for MockDispatcher {
+    fn dispatch(
+        &self,
+        request: SignedRequest,
+        _timeout: Option<Duration>,
+    ) -> DispatchSignedRequestFuture {
+        let payload = match &request.payload {
+            Some(SignedRequestPayload::Buffer(body)) => String::from_utf8_lossy(body).into_owned(),
+            _ => String::new(),
+        };
+        let action = serde_urlencoded::from_str::<Vec<(String, String)>>(&payload)
+            .unwrap_or_default()
+            .into_iter()
+            .find(|(key, _)| key == "Action")
+            .map(|(_, action)| action)
+            .or_else(|| {
+                let target = request.headers.get("x-amz-target")?.first()?;
+                Some(String::from_utf8_lossy(target).into_owned())
+            })
+            .unwrap_or_default();
+        self.actions.lock().unwrap().push(action.clone());
+        self.payloads.lock().unwrap().push(payload);
+        let (status, body) = self
+            .responses
+            .lock()
+            .unwrap()
+            .pop_front()
+            .unwrap_or_else(|| panic!("unexpected request {}", action));
+        Box::pin(async move {
+            Ok(HttpResponse {
+                status: hyper::StatusCode::from_u16(status).expect("invalid status code"),
+                body: ByteStream::from(body.into_bytes()),
+                headers: Default::default(),
+            })
+        })
+    }
+}
+
+/// Create a CloudFormation client dispatching its requests to the mock.
+pub(crate) fn cloudformation(dispatcher: &MockDispatcher) -> CloudFormationClient {
+    CloudFormationClient::new_with(
+        dispatcher.clone(),
+        StaticProvider::new_minimal("access-key-id".to_owned(), "secret-access-key".to_owned()),
+        Region::EuCentral1,
+    )
+}
+
+/// Create a CloudFront client dispatching its requests to the mock.
+pub(crate) fn cloudfront(dispatcher: &MockDispatcher) -> CloudFrontClient {
+    CloudFrontClient::new_with(
+        dispatcher.clone(),
+        StaticProvider::new_minimal("access-key-id".to_owned(), "secret-access-key".to_owned()),
+        Region::UsEast1,
+    )
+}
+
+/// Create an RDS client dispatching its requests to the mock.
+pub(crate) fn rds(dispatcher: &MockDispatcher) -> RdsClient {
+    RdsClient::new_with(
+        dispatcher.clone(),
+        StaticProvider::new_minimal("access-key-id".to_owned(), "secret-access-key".to_owned()),
+        Region::EuCentral1,
+    )
+}
@@ -0,0 +1,267 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Packaging of templates referencing local artifacts, equivalent to `aws cloudformation package`.
+//!
+//! Resources in a template can reference local files, for example the code of a Lambda function or
+//! the template of a nested stack. Packaging uploads these files to S3 and rewrites the template to
+//! reference the uploaded objects instead.
+
+use futures::future::{BoxFuture, FutureExt};
+use serde_yaml::{Mapping, Value};
+use std::path::{Path, PathBuf};
+
+use crate::{error::Error, s3::S3Uploader, template::Template};
+
+/// How a property referencing a local artifact is rewritten once the artifact was uploaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArtifactReference {
+    /// The property is replaced by an object with `S3Bucket` and `S3Key` fields.
+    BucketAndKey,
+    /// The property is replaced by an `s3://bucket/key` URI.
+    S3Uri,
+    /// The artifact is a template that is packaged itself, the property is replaced by its URL.
+    NestedTemplate,
+}
+
+/// The resource types and properties that can reference local artifacts.
+const PACKAGEABLE_PROPERTIES: &[(&str, &str, ArtifactReference)] = &[
+    (
+        "AWS::CloudFormation::Stack",
+        "TemplateURL",
+        ArtifactReference::NestedTemplate,
+    ),
+    (
+        "AWS::Lambda::Function",
+        "Code",
+        ArtifactReference::BucketAndKey,
+    ),
+    (
+        "AWS::Lambda::LayerVersion",
+        "Content",
+        ArtifactReference::BucketAndKey,
+    ),
+    (
+        "AWS::Serverless::Application",
+        "Location",
+        ArtifactReference::NestedTemplate,
+    ),
+    (
+        "AWS::Serverless::Api",
+        "DefinitionUri",
+        ArtifactReference::S3Uri,
+    ),
+    (
+        "AWS::Serverless::Function",
+        "CodeUri",
+        ArtifactReference::S3Uri,
+    ),
+    (
+        "AWS::Serverless::HttpApi",
+        "DefinitionUri",
+        ArtifactReference::S3Uri,
+    ),
+    (
+        "AWS::Serverless::LayerVersion",
+        "ContentUri",
+        ArtifactReference::S3Uri,
+    ),
+    (
+        "AWS::Serverless::StateMachine",
+        "DefinitionUri",
+        ArtifactReference::S3Uri,
+    ),
+];
+
+/// Package a template, uploading all local artifacts it references to S3.
+///
+/// Relative paths in the template are resolved against `base_dir`, which usually is the directory
+/// the template was loaded from. Nested templates are packaged recursively, and directories
+/// containing code are zipped before being uploaded. The returned template
+/// references the uploaded artifacts instead of the local paths. Properties that do not reference
+/// an existing local path, e.g. because they already reference S3, are left unchanged.
+///
+/// **Note:** the rewritten template is serialized anew, which means comments and formatting of the
+/// original template are not retained. JSON templates stay JSON, all others are emitted as YAML.
+pub async fn package_template(
+    template: &Template,
+    base_dir: &Path,
+    s3: &S3Uploader,
+    bucket_name: &str,
+) -> Result<Template, Error> {
+    package_contents(
+        template.contents().to_owned(),
+        base_dir.to_owned(),
+        s3,
+        bucket_name,
+    )
+    .await
+    .and_then(Template::from_bytes)
+}
+
+fn package_contents<'a>(
+    contents: Vec<u8>,
+    base_dir: PathBuf,
+    s3: &'a S3Uploader,
+    bucket_name: &'a str,
+) -> BoxFuture<'a, Result<Vec<u8>, Error>> {
+    async move {
+        let is_json = contents
+            .iter()
+            .find(|byte| !byte.is_ascii_whitespace())
+            .map(|byte| *byte == b'{')
+            .unwrap_or(false);
+        let mut template: Value = serde_yaml::from_slice(&contents)
+            .map_err(|error| Error::TemplateDeserializationFailed(error.into()))?;
+
+        if let Some(resources) = template
+            .get_mut("Resources")
+            .and_then(Value::as_mapping_mut)
+        {
+            for (_, resource) in resources.iter_mut() {
+                package_resource(resource, &base_dir, s3, bucket_name).await?;
+            }
+        }
+
+        if is_json {
+            Ok(serde_json::to_vec_pretty(&template)?)
+        } else {
+            serde_yaml::to_string(&template)
+                .map(String::into_bytes)
+                .map_err(|error| Error::InvalidTemplate(error.to_string()))
+        }
+    }
+    .boxed()
+}
+
+async fn package_resource(
+    resource: &mut Value,
+    base_dir: &Path,
+    s3: &S3Uploader,
+    bucket_name: &str,
+) -> Result<(), Error> {
+    let resource_type = match resource.get("Type").and_then(Value::as_str) {
+        Some(resource_type) => resource_type.to_owned(),
+        None => return Ok(()),
+    };
+    let properties = match resource
+        .get_mut("Properties")
+        .and_then(Value::as_mapping_mut)
+    {
+        Some(properties) => properties,
+        None => return Ok(()),
+    };
+
+    for (_, property_name, reference) in PACKAGEABLE_PROPERTIES
+        .iter()
+        .filter(|(packageable_type, ..)| *packageable_type == resource_type)
+    {
+        let path = match local_artifact_path(properties.get(*property_name), base_dir) {
+            Some(path) => path,
+            None => continue,
+        };
+
+        let value = match reference {
+            ArtifactReference::NestedTemplate => {
+                let nested_base_dir = path
+                    .parent()
+                    .map(Path::to_path_buf)
+                    .unwrap_or_else(|| base_dir.to_path_buf());
+                let contents = std::fs::read(&path)?;
+                let packaged = Template::from_bytes(
+                    package_contents(contents, nested_base_dir, s3, bucket_name).await?,
+                )?;
+                Value::String(packaged.upload_to_s3(s3, bucket_name).await?)
+            }
+            ArtifactReference::BucketAndKey => {
+                let object = s3.upload_lambda_artifact(bucket_name, &path).await?;
+                let mut mapping = Mapping::new();
+                mapping.insert("S3Bucket".into(), object.bucket.into());
+                mapping.insert("S3Key".into(), object.key.into());
+                if let Some(version) = object.version {
+                    mapping.insert("S3ObjectVersion".into(), version.into());
+                }
+                Value::Mapping(mapping)
+            }
+            ArtifactReference::S3Uri => {
+                let key = if path.is_dir() {
+                    s3.upload_lambda_artifact(bucket_name, &path).await?.key
+                } else {
+                    upload_artifact(&path, s3, bucket_name).await?
+                };
+                Value::String(format!("s3://{}/{}", bucket_name, key))
+            }
+        };
+        properties.insert((*property_name).into(), value);
+    }
+
+    Ok(())
+}
+
+/// Return the path of the local artifact the property references, if it does.
+fn local_artifact_path(property: Option<&Value>, base_dir: &Path) -> Option<PathBuf> {
+    let value = property?.as_str()?;
+    if value.starts_with("s3://") || value.starts_with("http://") || value.starts_with("https://") {
+        return None;
+    }
+
+    let path = base_dir.join(value);
+    if path.exists() {
+        Some(path)
+    } else {
+        None
+    }
+}
+
+/// Upload a local file as is, returning the key it was uploaded to.
+///
+/// The key is derived from the checksum of the artifact, retaining its extension, which
+/// deduplicates uploads the same way templates are deduplicated.
+async fn upload_artifact(path: &Path, s3: &S3Uploader, bucket_name: &str) -> Result<String, Error> {
+    let contents = std::fs::read(path)?;
+    let checksum = s3.checksum_algorithm().hex_digest(&contents);
+    let key = match path.extension().and_then(|extension| extension.to_str()) {
+        Some(extension) => format!("{}.{}", checksum, extension),
+        None => checksum,
+    };
+    s3.upload(bucket_name, &key, contents.into()).await?;
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn local_artifact_path_only_matches_existing_local_paths() {
+        let base_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+        assert_eq!(
+            Some(base_dir.join("Cargo.toml")),
+            local_artifact_path(Some(&Value::from("Cargo.toml")), base_dir),
+        );
+        assert_eq!(
+            None,
+            local_artifact_path(Some(&Value::from("does-not-exist.zip")), base_dir),
+        );
+        assert_eq!(
+            None,
+            local_artifact_path(Some(&Value::from("s3://bucket/key")), base_dir),
+        );
+        assert_eq!(None, local_artifact_path(Some(&Value::from(1)), base_dir));
+        assert_eq!(None, local_artifact_path(None, base_dir));
+    }
+}
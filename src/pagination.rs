@@ -0,0 +1,100 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Retrieval of all pages of paginated AWS API calls.
+
+use futures::stream::{self, Stream, TryStreamExt};
+use std::future::Future;
+
+use crate::error::Error;
+
+/// A single page of items returned by a paginated AWS API call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Page<T> {
+    items: Vec<T>,
+    next_token: Option<String>,
+}
+
+impl<T> Page<T> {
+    /// Create a page from the items and the token of the next page, as returned by the API.
+    ///
+    /// Missing items are treated as an empty page. An empty token is treated as no token, since
+    /// some APIs return an empty string on the last page instead of omitting the token.
+    pub fn new(items: Option<Vec<T>>, next_token: Option<String>) -> Page<T> {
+        Page {
+            items: items.unwrap_or_default(),
+            next_token: next_token.filter(|next_token| !next_token.is_empty()),
+        }
+    }
+}
+
+/// Retrieve all pages of a paginated AWS API call, returning a stream of their items.
+///
+/// `fetch_page` is called with the token of the page to retrieve, which is `None` for the first
+/// page, and has to return the page with the token of the next page. Pages are retrieved lazily as
+/// the stream is consumed, and the stream ends after the first error. Retries or rate limiting can
+/// be added by wrapping the call in `fetch_page`.
+pub fn paginate<'a, T, E, F, Fut>(fetch_page: F) -> impl Stream<Item = Result<T, Error>> + 'a
+where
+    T: 'a,
+    E: Into<Error>,
+    F: FnMut(Option<String>) -> Fut + 'a,
+    Fut: Future<Output = Result<Page<T>, E>> + 'a,
+{
+    // The state holds the token of the next page to retrieve, or `None` once all pages have been.
+    stream::try_unfold(
+        (fetch_page, Some(None)),
+        |(mut fetch_page, next_token)| async move {
+            let next_token = match next_token {
+                Some(next_token) => next_token,
+                None => return Ok::<_, Error>(None),
+            };
+            let page = fetch_page(next_token).await.map_err(Into::into)?;
+            let items = stream::iter(page.items.into_iter().map(Ok));
+            Ok(Some((items, (fetch_page, page.next_token.map(Some)))))
+        },
+    )
+    .try_flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{executor::block_on, future};
+
+    #[test]
+    fn paginate_retrieves_all_pages() {
+        let mut requested_tokens = Vec::new();
+        let items: Vec<u32> = block_on(
+            paginate(|next_token: Option<String>| {
+                requested_tokens.push(next_token.clone());
+                future::ready(Ok::<_, Error>(match next_token.as_deref() {
+                    None => Page::new(Some(vec![1, 2]), Some("a".to_owned())),
+                    Some("a") => Page::new(None, Some("b".to_owned())),
+                    _ => Page::new(Some(vec![3]), Some(String::new())),
+                }))
+            })
+            .try_collect(),
+        )
+        .unwrap();
+
+        assert_eq!(vec![1, 2, 3], items);
+        assert_eq!(
+            vec![None, Some("a".to_owned()), Some("b".to_owned())],
+            requested_tokens
+        );
+    }
+}
@@ -18,10 +18,25 @@
 
 use indexmap::IndexMap;
 use itertools::Itertools;
+use owo_colors::{AnsiColors, OwoColorize};
+use prettytable::row;
+use regex::Regex;
 use serde::{de, ser, Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
 use std::ops;
+use std::path::Path;
 use std::str::FromStr;
 
+use crate::{
+    error::Error,
+    template::{Template, TemplateParameter},
+};
+
+/// The value CloudFormation reports for parameters declared with `NoEcho`.
+pub const NO_ECHO_PARAMETER_VALUE: &str = "****";
+
 /// Represents a CloudFormation stack or template parameter.
 ///
 /// A parameter can either have a value ([`WithValue`]), or it can use the previous value
@@ -38,7 +53,16 @@ pub enum Parameter {
         #[serde(rename = "ParameterKey")]
         key: String,
         /// Value of the CloudFormation stack or template parameter.
-        #[serde(rename = "ParameterValue")]
+        ///
+        /// The value is held in the string form CloudFormation expects. Use
+        /// [`Parameter::with_value`] to create a parameter from a typed [`ParameterValue`].
+        ///
+        /// [`Parameter::with_value`]: enum.Parameter.html#method.with_value
+        /// [`ParameterValue`]: enum.ParameterValue.html
+        #[serde(
+            rename = "ParameterValue",
+            deserialize_with = "deserialize_parameter_value"
+        )]
         value: String,
     },
     /// A parameter where the previous, pre-existing value on the stack should be retained.
@@ -51,6 +75,36 @@ pub enum Parameter {
 }
 
 impl Parameter {
+    /// Create a parameter of the [`WithValue`] variant from a typed value.
+    ///
+    /// The value is converted into the string form CloudFormation expects:
+    ///
+    /// ```
+    /// # use awsx::parameter::Parameter;
+    /// assert_eq!(
+    ///     Parameter::WithValue {
+    ///         key: "Subnets".to_owned(),
+    ///         value: "subnet-1,subnet-2".to_owned(),
+    ///     },
+    ///     Parameter::with_value("Subnets", vec!["subnet-1", "subnet-2"]),
+    /// );
+    /// assert_eq!(
+    ///     Parameter::WithValue {
+    ///         key: "DesiredCapacity".to_owned(),
+    ///         value: "3".to_owned(),
+    ///     },
+    ///     Parameter::with_value("DesiredCapacity", 3),
+    /// );
+    /// ```
+    ///
+    /// [`WithValue`]: #variant.WithValue
+    pub fn with_value<K: Into<String>, V: Into<ParameterValue>>(key: K, value: V) -> Self {
+        Parameter::WithValue {
+            key: key.into(),
+            value: value.into().to_string(),
+        }
+    }
+
     /// Create a parameter of the [`PreviousValue`] variant.
     ///
     /// This is a simple helper and is equal to instantiating the variant yourself:
@@ -81,11 +135,14 @@ impl Parameter {
     ///
     /// * Only a `resolved_value` is present, whereas `parameter_value` isn't. The `resolved_value`
     ///   field is used in the AWS Systems Manager context, and this exact scenario probably can't
-    ///   happen, but we also don't deal with it should it happen.
+    ///   happen, but we also don't deal with it should it happen. (Use [`resolved_from`] if you
+    ///   are interested in the resolved value.)
     ///
     /// * No `parameter_value` is given, and `use_previous_value` is not `true`.
     ///
     /// Hence we return an `Option<Parameter>`.
+    ///
+    /// [`resolved_from`]: #method.resolved_from
     pub fn from(cfn_parameter: &rusoto_cloudformation::Parameter) -> Option<Self> {
         match cfn_parameter {
             rusoto_cloudformation::Parameter {
@@ -107,6 +164,43 @@ impl Parameter {
         }
     }
 
+    /// Convert the parameter type as returned by Rusoto CloudFormation into our Parameter type,
+    /// using the value CloudFormation resolved the parameter to, if any.
+    ///
+    /// CloudFormation resolves the values of parameters of the AWS Systems Manager parameter
+    /// types, e.g. `AWS::SSM::Parameter::Value<String>`, where the `parameter_value` only holds the
+    /// name of the SSM parameter. All other parameters are converted the same way as [`from`]
+    /// does.
+    ///
+    /// ```
+    /// # use awsx::parameter::Parameter;
+    /// let cfn_parameter = rusoto_cloudformation::Parameter {
+    ///     parameter_key: Some("ImageId".to_owned()),
+    ///     parameter_value: Some("/aws/service/ami-amazon-linux-latest".to_owned()),
+    ///     resolved_value: Some("ami-0123456789abcdef0".to_owned()),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(
+    ///     Some(Parameter::with_value("ImageId", "ami-0123456789abcdef0")),
+    ///     Parameter::resolved_from(&cfn_parameter),
+    /// );
+    /// ```
+    ///
+    /// [`from`]: #method.from
+    pub fn resolved_from(cfn_parameter: &rusoto_cloudformation::Parameter) -> Option<Self> {
+        match cfn_parameter {
+            rusoto_cloudformation::Parameter {
+                parameter_key: Some(ref key),
+                resolved_value: Some(ref resolved_value),
+                ..
+            } => Some(Parameter::WithValue {
+                key: key.to_owned(),
+                value: resolved_value.to_owned(),
+            }),
+            _ => Parameter::from(cfn_parameter),
+        }
+    }
+
     /// Convert the parameter type as returned by Rusoto CloudFormation into our Parameter type,
     /// specifically into the [`PreviousValue`] variant.
     ///
@@ -248,6 +342,107 @@ impl FromStr for Parameter {
     }
 }
 
+/// A typed value of a CloudFormation stack or template parameter.
+///
+/// CloudFormation itself only knows string values, where lists are represented as comma-delimited
+/// strings. This type allows creating and parsing parameter values of other types, converting them
+/// into the form CloudFormation expects through its `Display` implementation:
+///
+/// ```
+/// # use awsx::parameter::ParameterValue;
+/// assert_eq!("true", ParameterValue::from(true).to_string());
+/// assert_eq!("1.5", ParameterValue::from(1.5).to_string());
+/// assert_eq!("a,b", ParameterValue::from(vec!["a", "b"]).to_string());
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum ParameterValue {
+    /// A string value.
+    String(String),
+    /// A numeric value.
+    Number(serde_json::Number),
+    /// A boolean value.
+    Bool(bool),
+    /// A list value, which CloudFormation expects to be comma-delimited.
+    List(Vec<ParameterValue>),
+}
+
+impl fmt::Display for ParameterValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParameterValue::String(value) => write!(f, "{}", value),
+            ParameterValue::Number(value) => write!(f, "{}", value),
+            ParameterValue::Bool(value) => write!(f, "{}", value),
+            ParameterValue::List(values) => write!(f, "{}", values.iter().join(",")),
+        }
+    }
+}
+
+impl From<String> for ParameterValue {
+    fn from(value: String) -> Self {
+        ParameterValue::String(value)
+    }
+}
+
+impl From<&str> for ParameterValue {
+    fn from(value: &str) -> Self {
+        ParameterValue::String(value.to_owned())
+    }
+}
+
+impl From<bool> for ParameterValue {
+    fn from(value: bool) -> Self {
+        ParameterValue::Bool(value)
+    }
+}
+
+impl From<i64> for ParameterValue {
+    fn from(value: i64) -> Self {
+        ParameterValue::Number(value.into())
+    }
+}
+
+impl From<u64> for ParameterValue {
+    fn from(value: u64) -> Self {
+        ParameterValue::Number(value.into())
+    }
+}
+
+impl From<i32> for ParameterValue {
+    fn from(value: i32) -> Self {
+        ParameterValue::Number(value.into())
+    }
+}
+
+impl From<f64> for ParameterValue {
+    /// Convert a floating point number into a parameter value.
+    ///
+    /// Since CloudFormation has no representation for them, non-finite numbers are converted into
+    /// their string representation (e.g. `NaN`).
+    fn from(value: f64) -> Self {
+        serde_json::Number::from_f64(value)
+            .map(ParameterValue::Number)
+            .unwrap_or_else(|| ParameterValue::String(value.to_string()))
+    }
+}
+
+impl<T: Into<ParameterValue>> From<Vec<T>> for ParameterValue {
+    fn from(values: Vec<T>) -> Self {
+        ParameterValue::List(values.into_iter().map(Into::into).collect())
+    }
+}
+
+/// Deserialize the value of a `Parameter::WithValue` variant.
+///
+/// Parameter files written by hand often contain numbers, booleans or lists as values, which are
+/// accepted in addition to strings and converted into the string form CloudFormation expects.
+fn deserialize_parameter_value<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: de::Deserializer<'de>,
+{
+    ParameterValue::deserialize(deserializer).map(|value| value.to_string())
+}
+
 /// This specialized serializer is used for the `Parameter::PreviousValue` variant internally.
 /// Within the `PreviousValue` variant we do not track the `UsePreviousValue` variable since we
 /// define it to be `true` when we instantiate this variant. During serialization we need to
@@ -274,6 +469,71 @@ where
     .serialize(serializer)
 }
 
+/// The format of a parameter file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParameterFileFormat {
+    /// A JSON array of parameters, as used by the AWS CLI.
+    Json,
+    /// A YAML sequence of parameters, with the same structure as the JSON format.
+    Yaml,
+}
+
+impl ParameterFileFormat {
+    /// Determine the format from the extension of the path, if it is `.json`, `.yaml` or `.yml`.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => Some(ParameterFileFormat::Json),
+            Some("yaml") | Some("yml") => Some(ParameterFileFormat::Yaml),
+            _ => None,
+        }
+    }
+
+    /// Determine the format from the contents of a file, which is JSON if it starts with an array
+    /// or object and YAML otherwise.
+    pub fn from_contents(contents: &[u8]) -> Self {
+        match contents.iter().find(|byte| !byte.is_ascii_whitespace()) {
+            Some(b'[') | Some(b'{') => ParameterFileFormat::Json,
+            _ => ParameterFileFormat::Yaml,
+        }
+    }
+}
+
+/// How conflicting values are resolved when combining two parameter collections, i.e. when both
+/// collections specify a different value for the same parameter.
+///
+/// Parameters using their previous value never conflict, they are always replaced by a value of
+/// the other collection.
+#[derive(Clone, Copy)]
+pub enum MergeStrategy<'a> {
+    /// Keep the value of the current collection.
+    KeepLeft,
+    /// Take the value of the other collection, which is what [`Parameters::update`] and
+    /// [`Parameters::merge`] do.
+    ///
+    /// [`Parameters::update`]: struct.Parameters.html#method.update
+    /// [`Parameters::merge`]: struct.Parameters.html#method.merge
+    KeepRight,
+    /// Fail with an [`Error::InvalidParameters`] listing all conflicting parameters.
+    ///
+    /// [`Error::InvalidParameters`]: ../error/enum.Error.html#variant.InvalidParameters
+    Error,
+    /// Resolve every conflict with the given function, which receives the parameter of the current
+    /// and of the other collection, and returns the parameter to use, e.g. after asking the user.
+    /// An error returned by the function fails the merge.
+    Custom(&'a dyn Fn(&Parameter, &Parameter) -> Result<Parameter, Error>),
+}
+
+impl fmt::Debug for MergeStrategy<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MergeStrategy::KeepLeft => write!(f, "KeepLeft"),
+            MergeStrategy::KeepRight => write!(f, "KeepRight"),
+            MergeStrategy::Error => write!(f, "Error"),
+            MergeStrategy::Custom(_) => write!(f, "Custom"),
+        }
+    }
+}
+
 /// A collection holding one or more stack or template parameters.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Parameters(pub(crate) IndexMap<String, Parameter>);
@@ -289,6 +549,109 @@ impl Parameters {
         )
     }
 
+    /// Load the parameters from a parameter file.
+    ///
+    /// The format is determined by the extension of the file, falling back to detecting it from
+    /// the contents if the extension is unknown. See [`ParameterFileFormat`].
+    ///
+    /// [`ParameterFileFormat`]: enum.ParameterFileFormat.html
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let contents = fs::read(path)?;
+        let format = ParameterFileFormat::from_path(path)
+            .unwrap_or_else(|| ParameterFileFormat::from_contents(&contents));
+        let invalid = |error: failure::Error| {
+            Error::ParameterFileInvalid(path.to_string_lossy().into_owned(), error)
+        };
+        match format {
+            ParameterFileFormat::Json => {
+                serde_json::from_slice(&contents).map_err(|error| invalid(error.into()))
+            }
+            ParameterFileFormat::Yaml => {
+                serde_yaml::from_slice(&contents).map_err(|error| invalid(error.into()))
+            }
+        }
+    }
+
+    /// Save the parameters to a parameter file, overwriting it if it exists.
+    ///
+    /// The format is determined by the extension of the file, defaulting to JSON.
+    pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+        let contents = match ParameterFileFormat::from_path(path) {
+            Some(ParameterFileFormat::Yaml) => serde_yaml::to_string(self).map_err(|error| {
+                Error::ParameterFileInvalid(path.to_string_lossy().into_owned(), error.into())
+            })?,
+            _ => serde_json::to_string_pretty(self)? + "\n",
+        };
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    /// Return the collection with the values of the parameters replaced by the values
+    /// CloudFormation resolved them to, keyed by the parameter key.
+    ///
+    /// Parameters without a resolved value, as well as parameters using their previous value, are
+    /// kept as they are. This allows comparing the parameters of a stack with parameters holding
+    /// the actual values, rather than the names of the AWS Systems Manager parameters.
+    ///
+    /// ```
+    /// # use awsx::parameter::{Parameter, Parameters};
+    /// # use indexmap::IndexMap;
+    /// let parameters = Parameters::new(vec![
+    ///     Parameter::with_value("ImageId", "/aws/service/ami-amazon-linux-latest"),
+    ///     Parameter::with_value("InstanceType", "t3.micro"),
+    /// ]);
+    /// let mut resolved_values = IndexMap::new();
+    /// resolved_values.insert("ImageId".to_owned(), "ami-0123456789abcdef0".to_owned());
+    /// assert_eq!(
+    ///     Parameters::new(vec![
+    ///         Parameter::with_value("ImageId", "ami-0123456789abcdef0"),
+    ///         Parameter::with_value("InstanceType", "t3.micro"),
+    ///     ]),
+    ///     parameters.with_resolved_values(&resolved_values),
+    /// );
+    /// ```
+    pub fn with_resolved_values(
+        mut self,
+        resolved_values: &IndexMap<String, String>,
+    ) -> Parameters {
+        for (key, parameter) in self.0.iter_mut() {
+            if let (Parameter::WithValue { value, .. }, Some(resolved_value)) =
+                (parameter, resolved_values.get(key))
+            {
+                *value = resolved_value.to_owned();
+            }
+        }
+        self
+    }
+
+    /// Return the collection with the parameters ordered alphabetically by their key.
+    ///
+    /// The order of parameters retrieved from a stack or a parameter file depends on its source,
+    /// normalizing it guarantees a stable output, e.g. when writing parameter files.
+    ///
+    /// ```
+    /// # use awsx::parameter::{Parameter, Parameters};
+    /// let parameters = Parameters::new(vec![
+    ///     Parameter::PreviousValue {
+    ///         key: "SecondParameter".to_owned(),
+    ///     },
+    ///     Parameter::PreviousValue {
+    ///         key: "FirstParameter".to_owned(),
+    ///     },
+    /// ])
+    /// .sorted();
+    /// assert_eq!(
+    ///     vec!["FirstParameter", "SecondParameter"],
+    ///     parameters.keys().collect::<Vec<_>>(),
+    /// );
+    /// ```
+    pub fn sorted(mut self) -> Parameters {
+        self.0.sort_keys();
+        self
+    }
+
     /// Update all parameters in the current collection with the ones also available in the other
     /// collection.
     ///
@@ -438,6 +801,146 @@ impl Parameters {
         Parameters(this)
     }
 
+    /// Return the keys of all parameters the other collection specifies a different value for.
+    ///
+    /// ```
+    /// # use awsx::parameter::{Parameter, Parameters};
+    /// let parameters = Parameters::new(vec![
+    ///     Parameter::WithValue {
+    ///         key: "FirstParameter".to_owned(),
+    ///         value: "Initial value".to_owned(),
+    ///     },
+    ///     Parameter::PreviousValue {
+    ///         key: "SecondParameter".to_owned(),
+    ///     },
+    /// ]);
+    /// let others = Parameters::new(vec![
+    ///     Parameter::WithValue {
+    ///         key: "FirstParameter".to_owned(),
+    ///         value: "New value".to_owned(),
+    ///     },
+    ///     Parameter::WithValue {
+    ///         key: "SecondParameter".to_owned(),
+    ///         value: "New value".to_owned(),
+    ///     },
+    /// ]);
+    ///
+    /// assert_eq!(vec!["FirstParameter"], parameters.conflicts_with(&others));
+    /// ```
+    pub fn conflicts_with(&self, other: &Parameters) -> Vec<&str> {
+        self.0
+            .iter()
+            .filter(|(key, parameter)| match (parameter, other.0.get(*key)) {
+                (
+                    Parameter::WithValue { value: left, .. },
+                    Some(Parameter::WithValue { value: right, .. }),
+                ) => left != right,
+                _ => false,
+            })
+            .map(|(key, _)| key.as_str())
+            .collect()
+    }
+
+    /// Update all parameters in the current collection with the ones also available in the other
+    /// collection, like [`update`], resolving conflicting values with the given strategy.
+    ///
+    /// [`update`]: #method.update
+    pub fn update_with<P: IntoParameters>(
+        &mut self,
+        other: P,
+        strategy: MergeStrategy<'_>,
+    ) -> Result<(), Error> {
+        self.combine(other.into_parameters(), false, strategy)
+    }
+
+    /// Add or update all parameters in the other collection to the current collection, like
+    /// [`merge`], resolving conflicting values with the given strategy.
+    ///
+    /// ```
+    /// # use awsx::parameter::{MergeStrategy, Parameter, Parameters};
+    /// let mut parameters = Parameters::new(vec![Parameter::WithValue {
+    ///     key: "FirstParameter".to_owned(),
+    ///     value: "Initial value".to_owned(),
+    /// }]);
+    /// let others = Parameters::new(vec![
+    ///     Parameter::WithValue {
+    ///         key: "FirstParameter".to_owned(),
+    ///         value: "New value".to_owned(),
+    ///     },
+    ///     Parameter::WithValue {
+    ///         key: "SecondParameter".to_owned(),
+    ///         value: "New value".to_owned(),
+    ///     },
+    /// ]);
+    /// assert!(parameters
+    ///     .clone()
+    ///     .merge_with(&others, MergeStrategy::Error)
+    ///     .is_err());
+    ///
+    /// parameters.merge_with(others, MergeStrategy::KeepLeft).unwrap();
+    /// assert_eq!(
+    ///     parameters,
+    ///     vec![
+    ///         Parameter::WithValue {
+    ///             key: "FirstParameter".to_owned(),
+    ///             value: "Initial value".to_owned(),
+    ///         },
+    ///         Parameter::WithValue {
+    ///             key: "SecondParameter".to_owned(),
+    ///             value: "New value".to_owned(),
+    ///         },
+    ///     ].into()
+    /// );
+    /// ```
+    ///
+    /// [`merge`]: #method.merge
+    pub fn merge_with<P: IntoParameters>(
+        &mut self,
+        other: P,
+        strategy: MergeStrategy<'_>,
+    ) -> Result<(), Error> {
+        self.combine(other.into_parameters(), true, strategy)
+    }
+
+    fn combine(
+        &mut self,
+        other: Parameters,
+        add: bool,
+        strategy: MergeStrategy<'_>,
+    ) -> Result<(), Error> {
+        let conflicts = self
+            .conflicts_with(&other)
+            .into_iter()
+            .map(ToOwned::to_owned)
+            .collect::<Vec<_>>();
+        if let (MergeStrategy::Error, false) = (strategy, conflicts.is_empty()) {
+            return Err(Error::InvalidParameters(format!(
+                "conflicting values for the parameters {}",
+                conflicts.join(", ")
+            )));
+        }
+
+        for (key, parameter) in other.0 {
+            let parameter = match (strategy, self.0.get(&key)) {
+                (MergeStrategy::KeepLeft, _) if conflicts.contains(&key) => continue,
+                (MergeStrategy::Custom(resolve), Some(current)) if conflicts.contains(&key) => {
+                    resolve(current, &parameter)?
+                }
+                _ => parameter,
+            };
+            match self.0.entry(key) {
+                indexmap::map::Entry::Occupied(mut entry) => {
+                    entry.insert(parameter);
+                }
+                indexmap::map::Entry::Vacant(entry) if add => {
+                    entry.insert(parameter);
+                }
+                indexmap::map::Entry::Vacant(_) => {}
+            }
+        }
+        Ok(())
+    }
+
     /// Set values for all parameters in the current collection based on the provided defaults,
     /// without modifying any pre-existing values or adding parameters that did not exist.
     ///
@@ -639,23 +1142,655 @@ impl Parameters {
             })
         }
     }
+
+    /// Expand placeholders in the values of all parameters.
+    ///
+    /// A placeholder of the form `{{name}}` is replaced by the value of the variable `name`, and
+    /// a placeholder of the form `{{parameters.Key}}` is replaced by the (expanded) value of the
+    /// parameter `Key` within this collection. Unknown parameters, as well as parameters
+    /// referencing each other cyclically, result in an error. Placeholders that name no variable
+    /// are kept as they are, e.g. CloudFormation dynamic references like
+    /// `{{resolve:ssm:/name}}`, and `{{{{` is expanded to a literal `{{`.
+    ///
+    /// ```
+    /// # use awsx::parameter::{Parameter, Parameters};
+    /// # use std::collections::HashMap;
+    /// let mut parameters = Parameters::new(vec![
+    ///     Parameter::with_value("Environment", "{{stack_name}}"),
+    ///     Parameter::with_value("BucketName", "assets-{{parameters.Environment}}-{{region}}"),
+    /// ]);
+    /// let variables = vec![
+    ///     ("stack_name".to_owned(), "staging".to_owned()),
+    ///     ("region".to_owned(), "eu-central-1".to_owned()),
+    /// ]
+    /// .into_iter()
+    /// .collect::<HashMap<_, _>>();
+    /// parameters.expand_placeholders(&variables).unwrap();
+    ///
+    /// assert_eq!(
+    ///     Some(&Parameter::with_value("BucketName", "assets-staging-eu-central-1")),
+    ///     parameters.get("BucketName"),
+    /// );
+    /// ```
+    pub fn expand_placeholders(
+        &mut self,
+        variables: &HashMap<String, String>,
+    ) -> Result<(), Error> {
+        let mut expanded = HashMap::new();
+        for key in self.keys() {
+            expand_parameter(key, self, variables, &mut expanded, &mut Vec::new())?;
+        }
+
+        for (key, value) in expanded {
+            if let Some(Parameter::WithValue {
+                value: existing, ..
+            }) = self.0.get_mut(&key)
+            {
+                *existing = value;
+            }
+        }
+        Ok(())
+    }
+
+    /// Check whether a placeholder in the value of any parameter references the variable `name`.
+    ///
+    /// This allows to only retrieve variables that are expensive to determine when they are used.
+    ///
+    /// ```
+    /// # use awsx::parameter::{Parameter, Parameters};
+    /// let parameters = Parameters::new(vec![
+    ///     Parameter::with_value("Role", "arn:aws:iam::{{ account_id }}:role/deploy"),
+    ///     Parameter::with_value("Name", "account_id-{{{{account_id}}"),
+    /// ]);
+    /// assert!(parameters.references_variable("account_id"));
+    /// assert!(!parameters.references_variable("region"));
+    /// ```
+    pub fn references_variable(&self, name: &str) -> bool {
+        self.values().any(|parameter| match parameter {
+            Parameter::WithValue { value, .. } => {
+                placeholder_segments(value)
+                    .iter()
+                    .any(|segment| match segment {
+                        PlaceholderSegment::Placeholder {
+                            name: referenced, ..
+                        } => *referenced == name,
+                        PlaceholderSegment::Literal(_) => false,
+                    })
+            }
+            Parameter::PreviousValue { .. } => false,
+        })
+    }
+
+    /// Validate the parameters against the parameter declarations of the template.
+    ///
+    /// The returned report lists parameters the template requires but that were not provided,
+    /// parameters that were provided but are not declared by the template, as well as provided
+    /// values that violate the type or constraints declared by the template. Parameters without
+    /// a value, i.e. the [`PreviousValue`] variant, are considered provided, but their values can
+    /// not be validated.
+    ///
+    /// ```
+    /// # use awsx::{parameter::{Parameter, Parameters}, template::Template};
+    /// let template = Template::from_bytes(
+    ///     "Parameters:\n  \
+    ///        Environment:\n    \
+    ///          Type: String\n    \
+    ///          AllowedValues: [staging, production]\n  \
+    ///        Instances:\n    \
+    ///          Type: Number\n",
+    /// )
+    /// .unwrap();
+    /// let parameters = Parameters::new(vec![
+    ///     Parameter::with_value("Environment", "testing"),
+    ///     Parameter::with_value("Unknown", "value"),
+    /// ]);
+    ///
+    /// let report = parameters.validate_against(&template);
+    /// assert!(!report.is_valid());
+    /// assert_eq!(vec!["Instances".to_owned()], report.missing);
+    /// assert_eq!(vec!["Unknown".to_owned()], report.unknown);
+    /// assert_eq!("Environment", report.violations[0].key);
+    /// ```
+    ///
+    /// [`PreviousValue`]: enum.Parameter.html#variant.PreviousValue
+    pub fn validate_against(&self, template: &Template) -> ParameterValidationReport {
+        let declarations = template.get_parameter_declarations();
+        let mut report = ParameterValidationReport::default();
+
+        report.missing.extend(
+            declarations
+                .iter()
+                .filter(|(key, declaration)| {
+                    declaration.default.is_none() && !self.contains_key(key.as_str())
+                })
+                .map(|(key, _)| key.to_owned()),
+        );
+        report.unknown.extend(
+            self.keys()
+                .filter(|key| !declarations.contains_key(key.as_str()))
+                .sorted()
+                .cloned(),
+        );
+        report
+            .violations
+            .extend(self.values().flat_map(|parameter| {
+                match (parameter, declarations.get(parameter.key())) {
+                    (Parameter::WithValue { key, value }, Some(declaration)) => {
+                        validate_value(value, declaration)
+                            .into_iter()
+                            .map(|message| ParameterViolation {
+                                key: key.to_owned(),
+                                message,
+                            })
+                            .collect()
+                    }
+                    _ => Vec::new(),
+                }
+            }));
+
+        report
+    }
+}
+
+/// Expand the placeholders of a single parameter, recursively expanding the parameters it
+/// references first. `resolving` tracks the parameters currently being expanded to detect cycles.
+fn expand_parameter(
+    key: &str,
+    parameters: &Parameters,
+    variables: &HashMap<String, String>,
+    expanded: &mut HashMap<String, String>,
+    resolving: &mut Vec<String>,
+) -> Result<Option<String>, Error> {
+    if let Some(value) = expanded.get(key) {
+        return Ok(Some(value.to_owned()));
+    }
+    let value = match parameters.get(key) {
+        Some(Parameter::WithValue { value, .. }) => value,
+        Some(Parameter::PreviousValue { .. }) => return Ok(None),
+        None => {
+            return Err(Error::InvalidParameters(format!(
+                "placeholder references unknown parameter {}",
+                key
+            )))
+        }
+    };
+    if resolving.iter().any(|resolving| resolving == key) {
+        return Err(Error::InvalidParameters(format!(
+            "placeholders of parameters {} reference each other",
+            resolving.join(", ")
+        )));
+    }
+    resolving.push(key.to_owned());
+
+    let mut result = String::with_capacity(value.len());
+    for segment in placeholder_segments(value) {
+        match segment {
+            PlaceholderSegment::Literal(literal) => result.push_str(literal),
+            PlaceholderSegment::Placeholder { name, raw } => {
+                if let Some(referenced_key) = name.strip_prefix("parameters.") {
+                    let replacement = expand_parameter(
+                        referenced_key,
+                        parameters,
+                        variables,
+                        expanded,
+                        resolving,
+                    )?
+                    .ok_or_else(|| {
+                        Error::InvalidParameters(format!(
+                            "placeholder in parameter {} references parameter {} which \
+                                     has no value",
+                            key, referenced_key
+                        ))
+                    })?;
+                    result.push_str(&replacement);
+                } else if let Some(replacement) = variables.get(name) {
+                    result.push_str(replacement);
+                } else {
+                    // Anything else, e.g. a dynamic reference like `{{resolve:ssm:/name}}`, is
+                    // meant for CloudFormation.
+                    result.push_str(raw);
+                }
+            }
+        }
+    }
+
+    resolving.pop();
+    expanded.insert(key.to_owned(), result.clone());
+    Ok(Some(result))
+}
+
+/// A part of a parameter value, as split by [`placeholder_segments`].
+///
+/// [`placeholder_segments`]: fn.placeholder_segments.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PlaceholderSegment<'a> {
+    /// Text to keep as it is.
+    Literal(&'a str),
+    /// A placeholder with its trimmed name, and its raw form including the braces.
+    Placeholder { name: &'a str, raw: &'a str },
+}
+
+/// Split a parameter value into literal text and `{{name}}` placeholders.
+///
+/// `{{{{` is an escaped, literal `{{`, and a `{{` without a closing `}}` is literal text as well.
+fn placeholder_segments(value: &str) -> Vec<PlaceholderSegment<'_>> {
+    let mut segments = Vec::new();
+    let mut remaining = value;
+    while let Some(start) = remaining.find("{{") {
+        segments.push(PlaceholderSegment::Literal(&remaining[..start]));
+        if remaining[start..].starts_with("{{{{") {
+            segments.push(PlaceholderSegment::Literal("{{"));
+            remaining = &remaining[start + 4..];
+            continue;
+        }
+        match remaining[start..].find("}}") {
+            Some(end) => {
+                let end = start + end;
+                segments.push(PlaceholderSegment::Placeholder {
+                    name: remaining[start + 2..end].trim(),
+                    raw: &remaining[start..end + 2],
+                });
+                remaining = &remaining[end + 2..];
+            }
+            None => {
+                segments.push(PlaceholderSegment::Literal(&remaining[start..]));
+                remaining = "";
+            }
+        }
+    }
+    segments.push(PlaceholderSegment::Literal(remaining));
+    segments
+}
+
+/// Validate a value against the type and constraints of the parameter declaration, returning a
+/// message for every violation.
+fn validate_value(value: &str, declaration: &TemplateParameter) -> Vec<String> {
+    let mut violations = Vec::new();
+
+    let values = if declaration.is_list() {
+        value.split(',').map(str::trim).collect::<Vec<_>>()
+    } else {
+        vec![value]
+    };
+    for value in values {
+        if declaration.is_numeric() {
+            match value.parse::<f64>() {
+                Ok(number) => {
+                    if let Some(min_value) = declaration.min_value {
+                        if number < min_value {
+                            violations.push(format!("{} is smaller than {}", value, min_value));
+                        }
+                    }
+                    if let Some(max_value) = declaration.max_value {
+                        if number > max_value {
+                            violations.push(format!("{} is larger than {}", value, max_value));
+                        }
+                    }
+                }
+                Err(_) => violations.push(format!("'{}' is not a number", value)),
+            }
+        }
+        if !declaration.allowed_values.is_empty()
+            && !declaration
+                .allowed_values
+                .iter()
+                .any(|allowed| allowed == value)
+        {
+            violations.push(format!(
+                "'{}' is not one of the allowed values ({})",
+                value,
+                declaration.allowed_values.iter().join(", "),
+            ));
+        }
+    }
+
+    let length = value.chars().count() as f64;
+    if let Some(min_length) = declaration.min_length {
+        if length < min_length {
+            violations.push(format!("value is shorter than {} characters", min_length));
+        }
+    }
+    if let Some(max_length) = declaration.max_length {
+        if length > max_length {
+            violations.push(format!("value is longer than {} characters", max_length));
+        }
+    }
+    if let Some(allowed_pattern) = &declaration.allowed_pattern {
+        // CloudFormation requires the pattern to match the entire value.
+        match Regex::new(&format!("^(?:{})$", allowed_pattern)) {
+            Ok(regex) if !regex.is_match(value) => violations.push(format!(
+                "value does not match the pattern {}",
+                allowed_pattern
+            )),
+            Ok(_) => {}
+            Err(_) => violations.push(format!(
+                "the allowed pattern {} of the template is invalid",
+                allowed_pattern
+            )),
+        }
+    }
+
+    violations
+}
+
+/// Report of validating [`Parameters`] against a [`Template`], as returned by
+/// [`Parameters::validate_against`].
+///
+/// [`Parameters`]: struct.Parameters.html
+/// [`Template`]: ../template/struct.Template.html
+/// [`Parameters::validate_against`]: struct.Parameters.html#method.validate_against
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ParameterValidationReport {
+    /// Parameters required by the template, i.e. without a default, that were not provided.
+    pub missing: Vec<String>,
+    /// Parameters that were provided, but are not declared by the template.
+    pub unknown: Vec<String>,
+    /// Provided values violating the type or constraints declared by the template.
+    pub violations: Vec<ParameterViolation>,
+}
+
+impl ParameterValidationReport {
+    /// Check if the parameters were valid, i.e. the report does not contain any findings.
+    pub fn is_valid(&self) -> bool {
+        self.missing.is_empty() && self.unknown.is_empty() && self.violations.is_empty()
+    }
+
+    /// Return the keys of all parameters with findings, without duplicates.
+    pub fn keys(&self) -> Vec<&str> {
+        self.missing
+            .iter()
+            .chain(&self.unknown)
+            .chain(self.violations.iter().map(|violation| &violation.key))
+            .map(String::as_str)
+            .unique()
+            .collect()
+    }
+}
+
+impl fmt::Display for ParameterValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut findings = Vec::new();
+        if !self.missing.is_empty() {
+            findings.push(format!(
+                "missing required parameters: {}",
+                self.missing.iter().join(", ")
+            ));
+        }
+        if !self.unknown.is_empty() {
+            findings.push(format!(
+                "parameters not declared by the template: {}",
+                self.unknown.iter().join(", ")
+            ));
+        }
+        findings.extend(
+            self.violations
+                .iter()
+                .map(|violation| format!("{}: {}", violation.key, violation.message)),
+        );
+        write!(f, "{}", findings.join("; "))
+    }
+}
+
+/// A provided parameter value violating the type or constraints declared by the template.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ParameterViolation {
+    /// Key of the parameter.
+    pub key: String,
+    /// Description of the violation.
+    pub message: String,
 }
 
 /// Struct to track the differences between two sets of [`Parameters`].
 ///
+/// It serializes the same way as its owned form, [`OwnedParametersDifference`].
+///
 /// [`Parameters`]: struct.Parameters.html
-#[derive(Debug)]
+/// [`OwnedParametersDifference`]: struct.OwnedParametersDifference.html
+#[derive(Debug, Serialize)]
 pub struct ParametersDifference<'a> {
     /// Parameters that are only available in the *left* parameter set
     pub left: Vec<&'a Parameter>,
     /// Parameters that are equal across the two parameter sets
     pub equal: Vec<&'a Parameter>,
     /// Parameters that are unequal across the two parameter sets
+    #[serde(serialize_with = "serialize_unequal")]
     pub unequal: Vec<(&'a Parameter, &'a Parameter)>,
     /// Parameters that are only available in the *right* parameter set
     pub right: Vec<&'a Parameter>,
 }
 
+fn serialize_unequal<S>(
+    unequal: &[(&Parameter, &Parameter)],
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: ser::Serializer,
+{
+    #[derive(Serialize)]
+    struct Unequal<'a> {
+        left: &'a Parameter,
+        right: &'a Parameter,
+    }
+
+    serializer.collect_seq(unequal.iter().map(|(left, right)| Unequal { left, right }))
+}
+
+impl<'a> ParametersDifference<'a> {
+    /// Convert this difference into its owned form, e.g. to keep it beyond the lifetime of the
+    /// compared parameter sets or to deserialize it again.
+    pub fn into_owned(self) -> OwnedParametersDifference {
+        OwnedParametersDifference {
+            left: self.left.into_iter().cloned().collect(),
+            equal: self.equal.into_iter().cloned().collect(),
+            unequal: self
+                .unequal
+                .into_iter()
+                .map(|(left, right)| UnequalParameter {
+                    left: left.clone(),
+                    right: right.clone(),
+                })
+                .collect(),
+            right: self.right.into_iter().cloned().collect(),
+        }
+    }
+
+    /// Render this difference for humans in the given format.
+    ///
+    /// By convention, the *left* parameters are the existing ones, e.g. of the stack or template,
+    /// and the *right* parameters are the new ones, e.g. of a parameter file. Values of parameters
+    /// for which `is_no_echo` returns true are masked, as are both values of parameters already
+    /// masked by CloudFormation on either side. If `colorize` is set, the lines of the [`Text`]
+    /// format are colored red, yellow and green for removed, changed and added parameters
+    /// respectively; the [`Table`] format is never colored.
+    ///
+    /// ```
+    /// # use awsx::parameter::{DifferenceFormat, Parameter, Parameters};
+    /// let stack = Parameters::new(vec![
+    ///     Parameter::with_value("Environment", "staging"),
+    ///     Parameter::with_value("Password", "secret"),
+    ///     Parameter::with_value("Removed", "value"),
+    /// ]);
+    /// let file = Parameters::new(vec![
+    ///     Parameter::with_value("Environment", "production"),
+    ///     Parameter::with_value("Password", "other secret"),
+    /// ]);
+    /// let difference = stack.loose_difference(&file).unwrap();
+    /// assert_eq!(
+    ///     difference.render(DifferenceFormat::Text, |key| key == "Password", false),
+    ///     "- Removed: value\n\
+    ///      ~ Environment: staging -> production\n\
+    ///      ~ Password: **** -> ****",
+    /// );
+    /// ```
+    ///
+    /// [`Text`]: enum.DifferenceFormat.html#variant.Text
+    /// [`Table`]: enum.DifferenceFormat.html#variant.Table
+    pub fn render(
+        &self,
+        format: DifferenceFormat,
+        is_no_echo: impl Fn(&str) -> bool,
+        colorize: bool,
+    ) -> String {
+        // A value masked by CloudFormation shows that the parameter is declared as `NoEcho`, even
+        // if `is_no_echo` does not know about it, so its value on the other side is masked too.
+        let masked_keys: HashSet<&str> = self
+            .left
+            .iter()
+            .chain(&self.right)
+            .copied()
+            .chain(
+                self.unequal
+                    .iter()
+                    .flat_map(|(left, right)| [*left, *right]),
+            )
+            .filter(|parameter| match parameter {
+                Parameter::WithValue { value, .. } => value == NO_ECHO_PARAMETER_VALUE,
+                Parameter::PreviousValue { .. } => false,
+            })
+            .map(Parameter::key)
+            .collect();
+        let display_value = |parameter: &Parameter| match parameter {
+            Parameter::WithValue { key, .. }
+                if is_no_echo(key) || masked_keys.contains(key.as_str()) =>
+            {
+                NO_ECHO_PARAMETER_VALUE.to_owned()
+            }
+            Parameter::WithValue { value, .. } => value.to_owned(),
+            Parameter::PreviousValue { .. } => "(previous value)".to_owned(),
+        };
+
+        match format {
+            DifferenceFormat::Text => {
+                let colored = |line: String, color: AnsiColors| {
+                    if colorize {
+                        line.color(color).to_string()
+                    } else {
+                        line
+                    }
+                };
+
+                let mut lines = Vec::new();
+                for parameter in &self.left {
+                    lines.push(colored(
+                        format!("- {}: {}", parameter.key(), display_value(parameter)),
+                        AnsiColors::Red,
+                    ));
+                }
+                for (left, right) in &self.unequal {
+                    lines.push(colored(
+                        format!(
+                            "~ {}: {} -> {}",
+                            left.key(),
+                            display_value(left),
+                            display_value(right)
+                        ),
+                        AnsiColors::Yellow,
+                    ));
+                }
+                for parameter in &self.right {
+                    lines.push(colored(
+                        format!("+ {}: {}", parameter.key(), display_value(parameter)),
+                        AnsiColors::Green,
+                    ));
+                }
+                lines.join("\n")
+            }
+            DifferenceFormat::Table => {
+                let mut table = prettytable::Table::new();
+                table.set_format(*prettytable::format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+                table.set_titles(row!["", "Key", "Left value", "Right value"]);
+                for parameter in &self.left {
+                    table.add_row(row!["-", parameter.key(), display_value(parameter), ""]);
+                }
+                for (left, right) in &self.unequal {
+                    table.add_row(row![
+                        "~",
+                        left.key(),
+                        display_value(left),
+                        display_value(right)
+                    ]);
+                }
+                for parameter in &self.right {
+                    table.add_row(row!["+", parameter.key(), "", display_value(parameter)]);
+                }
+                table.to_string().trim_end().to_owned()
+            }
+        }
+    }
+}
+
+/// The owned and deserializable form of a [`ParametersDifference`].
+///
+/// [`ParametersDifference`]: struct.ParametersDifference.html
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OwnedParametersDifference {
+    /// Parameters that are only available in the *left* parameter set
+    pub left: Vec<Parameter>,
+    /// Parameters that are equal across the two parameter sets
+    pub equal: Vec<Parameter>,
+    /// Parameters that are unequal across the two parameter sets
+    pub unequal: Vec<UnequalParameter>,
+    /// Parameters that are only available in the *right* parameter set
+    pub right: Vec<Parameter>,
+}
+
+impl OwnedParametersDifference {
+    /// Borrow this difference, e.g. to [`render`] it.
+    ///
+    /// [`render`]: struct.ParametersDifference.html#method.render
+    pub fn as_difference(&self) -> ParametersDifference<'_> {
+        ParametersDifference {
+            left: self.left.iter().collect(),
+            equal: self.equal.iter().collect(),
+            unequal: self
+                .unequal
+                .iter()
+                .map(|unequal| (&unequal.left, &unequal.right))
+                .collect(),
+            right: self.right.iter().collect(),
+        }
+    }
+}
+
+/// A parameter available in both parameter sets of an [`OwnedParametersDifference`], with unequal
+/// values.
+///
+/// [`OwnedParametersDifference`]: struct.OwnedParametersDifference.html
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnequalParameter {
+    /// The parameter of the *left* parameter set
+    pub left: Parameter,
+    /// The parameter of the *right* parameter set
+    pub right: Parameter,
+}
+
+/// The formats a [`ParametersDifference`] can be rendered in.
+///
+/// [`ParametersDifference`]: struct.ParametersDifference.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DifferenceFormat {
+    /// One line per differing parameter, prefixed with `-` (only left), `~` (unequal) or `+` (only
+    /// right).
+    Text,
+    /// A table with the key and both values of every differing parameter.
+    Table,
+}
+
+impl FromStr for DifferenceFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(DifferenceFormat::Text),
+            "table" => Ok(DifferenceFormat::Table),
+            _ => Err(Error::InvalidParameters(format!(
+                "unknown difference format `{}`",
+                s
+            ))),
+        }
+    }
+}
+
 impl From<Vec<Parameter>> for Parameters {
     fn from(parameters: Vec<Parameter>) -> Self {
         Parameters::new(parameters)
@@ -771,6 +1906,101 @@ impl IntoParameters for &Vec<Parameter> {
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    #[test]
+    fn parameters_difference_serialization() {
+        let left = Parameters::new(vec![
+            Parameter::with_value("Equal", "value"),
+            Parameter::with_value("Unequal", "left"),
+            Parameter::with_value("OnlyLeft", "value"),
+        ]);
+        let right = Parameters::new(vec![
+            Parameter::with_value("Equal", "value"),
+            Parameter::with_value("Unequal", "right"),
+        ]);
+        let difference = left.loose_difference(&right).unwrap();
+        let serialized = serde_json::to_value(&difference).unwrap();
+        assert_eq!(
+            serialized["unequal"],
+            serde_json::json!([{
+                "left": {"ParameterKey": "Unequal", "ParameterValue": "left"},
+                "right": {"ParameterKey": "Unequal", "ParameterValue": "right"},
+            }])
+        );
+
+        let owned = difference.into_owned();
+        assert_eq!(serialized, serde_json::to_value(&owned).unwrap());
+        assert_eq!(
+            owned,
+            serde_json::from_value::<OwnedParametersDifference>(serialized).unwrap()
+        );
+    }
+
+    #[test]
+    fn difference_to_stack_masks_no_echo_parameters() {
+        // CloudFormation masks the values of `NoEcho` parameters of a stack, even if the caller
+        // does not know which parameters are declared as such.
+        let stack = Parameters::new(vec![
+            Parameter::with_value("Environment", "staging"),
+            Parameter::with_value("Password", NO_ECHO_PARAMETER_VALUE),
+        ]);
+        let file = Parameters::new(vec![
+            Parameter::with_value("Environment", "staging"),
+            Parameter::with_value("Password", "secret"),
+        ]);
+        let difference = stack.loose_difference(&file).unwrap();
+
+        assert_eq!(
+            "~ Password: **** -> ****",
+            difference.render(DifferenceFormat::Text, |_| false, false)
+        );
+        let table = difference.render(DifferenceFormat::Table, |_| false, false);
+        assert!(table.contains("Password"));
+        assert!(!table.contains("secret"));
+    }
+
+    #[test]
+    fn parameter_file_round_trip() {
+        let parameters = Parameters::new(vec![
+            Parameter::WithValue {
+                key: "First".to_owned(),
+                value: "first value".to_owned(),
+            },
+            Parameter::PreviousValue {
+                key: "Second".to_owned(),
+            },
+        ]);
+        let directory =
+            std::env::temp_dir().join(format!("awsx-parameters-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+
+        for filename in &["parameters.json", "parameters.yaml", "parameters"] {
+            let path = directory.join(filename);
+            parameters.to_file(&path).unwrap();
+            assert_eq!(parameters, Parameters::from_file(&path).unwrap());
+        }
+
+        let path = directory.join("parameters.txt");
+        fs::write(
+            &path,
+            "- ParameterKey: First\n  ParameterValue: first value\n",
+        )
+        .unwrap();
+        assert_eq!(
+            Parameter::WithValue {
+                key: "First".to_owned(),
+                value: "first value".to_owned(),
+            },
+            Parameters::from_file(&path).unwrap()["First"],
+        );
+        fs::write(&path, "{\"First\": \"first value\"}").unwrap();
+        assert!(matches!(
+            Parameters::from_file(&path),
+            Err(Error::ParameterFileInvalid(..))
+        ));
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
     use serde_json::json;
 
     #[test]
@@ -933,6 +2163,70 @@ mod tests {
         assert_eq!(previous_value_expected, previous_value_actual.key());
     }
 
+    #[test]
+    fn merge_with_custom_resolves_only_conflicts() {
+        let mut parameters = Parameters::new(vec![
+            Parameter::WithValue {
+                key: "Conflicting".to_owned(),
+                value: "left".to_owned(),
+            },
+            Parameter::WithValue {
+                key: "Equal".to_owned(),
+                value: "same".to_owned(),
+            },
+        ]);
+        let others = Parameters::new(vec![
+            Parameter::WithValue {
+                key: "Conflicting".to_owned(),
+                value: "right".to_owned(),
+            },
+            Parameter::WithValue {
+                key: "Equal".to_owned(),
+                value: "same".to_owned(),
+            },
+            Parameter::WithValue {
+                key: "New".to_owned(),
+                value: "new".to_owned(),
+            },
+        ]);
+        let resolved = std::cell::RefCell::new(Vec::new());
+        let resolve = |left: &Parameter, right: &Parameter| {
+            resolved.borrow_mut().push(left.key().to_owned());
+            Ok(Parameter::WithValue {
+                key: right.key().to_owned(),
+                value: "resolved".to_owned(),
+            })
+        };
+
+        parameters
+            .merge_with(others.clone(), MergeStrategy::Custom(&resolve))
+            .unwrap();
+        assert_eq!(vec!["Conflicting".to_owned()], resolved.into_inner());
+        assert_eq!(
+            Parameters::new(vec![
+                Parameter::WithValue {
+                    key: "Conflicting".to_owned(),
+                    value: "resolved".to_owned(),
+                },
+                Parameter::WithValue {
+                    key: "Equal".to_owned(),
+                    value: "same".to_owned(),
+                },
+                Parameter::WithValue {
+                    key: "New".to_owned(),
+                    value: "new".to_owned(),
+                },
+            ]),
+            parameters
+        );
+
+        let fail =
+            |left: &Parameter, _: &Parameter| Err(Error::InvalidParameters(left.key().to_owned()));
+        assert!(parameters
+            .merge_with(others, MergeStrategy::Custom(&fail))
+            .is_err());
+    }
+
     #[test]
     fn parameter_fromstr() {
         let with_space_actual = "MyKey=my value";
@@ -1027,6 +2321,130 @@ mod tests {
         assert!(serde_json::from_value::<Parameter>(no_key).is_err());
     }
 
+    #[test]
+    fn parameter_deserialize_typed_value() {
+        let values = vec![
+            (json!(3), "3"),
+            (json!(1.5), "1.5"),
+            (json!(true), "true"),
+            (json!(["a", "b"]), "a,b"),
+            (json!([1, 2]), "1,2"),
+        ];
+
+        for (value, expected) in values {
+            let actual: Parameter = serde_json::from_value(json!({
+                "ParameterKey": "MyKey",
+                "ParameterValue": value,
+            }))
+            .unwrap();
+            assert_eq!(
+                Parameter::WithValue {
+                    key: "MyKey".to_owned(),
+                    value: expected.to_owned(),
+                },
+                actual,
+            );
+        }
+    }
+
+    #[test]
+    fn parameters_validate_against_constraints() {
+        let template = Template::from_bytes(
+            "Parameters:
+  Count:
+    Type: Number
+    MinValue: 1
+    MaxValue: 5
+  Ports:
+    Type: List<Number>
+  Name:
+    Type: String
+    AllowedPattern: '[a-z]+'
+    MaxLength: 8
+  Optional:
+    Type: String
+    Default: value
+",
+        )
+        .unwrap();
+
+        let valid = Parameters::new(vec![
+            Parameter::with_value("Count", 5),
+            Parameter::with_value("Ports", vec![80, 443]),
+            Parameter::with_value("Name", "awsx"),
+        ]);
+        assert_eq!(
+            ParameterValidationReport::default(),
+            valid.validate_against(&template)
+        );
+
+        let invalid = Parameters::new(vec![
+            Parameter::with_value("Count", 6),
+            Parameter::with_value("Ports", "80,https"),
+            Parameter::with_value("Name", "awsx-cli-tool"),
+        ]);
+        let report = invalid.validate_against(&template);
+        assert!(report.missing.is_empty());
+        assert!(report.unknown.is_empty());
+        assert_eq!(
+            vec!["Count", "Ports", "Name", "Name"],
+            report
+                .violations
+                .iter()
+                .map(|violation| violation.key.as_str())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn parameters_expand_placeholders_errors() {
+        let variables = HashMap::new();
+
+        let mut cyclic = Parameters::new(vec![
+            Parameter::with_value("First", "{{parameters.Second}}"),
+            Parameter::with_value("Second", "{{parameters.First}}"),
+        ]);
+        assert!(cyclic.expand_placeholders(&variables).is_err());
+
+        let mut unknown_parameter = Parameters::new(vec![Parameter::with_value(
+            "First",
+            "{{parameters.Second}}",
+        )]);
+        assert!(unknown_parameter.expand_placeholders(&variables).is_err());
+
+        let mut without_placeholders = Parameters::new(vec![
+            Parameter::with_value("First", "{value}"),
+            Parameter::previous_value("Second".to_owned()),
+            Parameter::with_value("Third", "{{x}}"),
+            Parameter::with_value("Fourth", "{{x"),
+            Parameter::with_value("Fifth", "{{resolve:ssm:/path/to/parameter}}"),
+        ]);
+        let expected = without_placeholders.clone();
+        without_placeholders
+            .expand_placeholders(&variables)
+            .unwrap();
+        assert_eq!(expected, without_placeholders);
+    }
+
+    #[test]
+    fn parameters_expand_placeholders_escaped() {
+        let variables = vec![("region".to_owned(), "eu-central-1".to_owned())]
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+        let mut parameters = Parameters::new(vec![Parameter::with_value(
+            "First",
+            "{{{{region}} is {{region}}",
+        )]);
+        parameters.expand_placeholders(&variables).unwrap();
+        assert_eq!(
+            Some(&Parameter::with_value(
+                "First",
+                "{{region}} is eu-central-1"
+            )),
+            parameters.get("First")
+        );
+    }
+
     #[test]
     fn parameters_new_empty() {
         let empty = Parameters::new(vec![]);
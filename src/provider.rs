@@ -17,13 +17,15 @@
 //! A Rusoto/AWS credential provider, with integrated support for role assumption.
 
 use async_trait::async_trait;
-use rusoto_core::{HttpClient, Region};
+use rusoto_core::Region;
 use rusoto_credential::{
     AwsCredentials, CredentialsError, DefaultCredentialsProvider, ProvideAwsCredentials,
 };
-use rusoto_sts::{StsAssumeRoleSessionCredentialsProvider, StsClient};
+use rusoto_sts::{AssumeRoleRequest, NewAwsCredsForStsCreds, Sts, StsClient};
 use uuid::Uuid;
 
+use crate::{endpoint, http, tags::TagSpecification};
+
 /// A Rusoto/AWS credential provider, with integrated support for role assumption.
 ///
 /// The provider can be supplied with a AWS Access Key ID and AWS Secret Access Key pair to use for
@@ -31,10 +33,14 @@ use uuid::Uuid;
 /// instance metadata, container metadata or AWS profiles).
 ///
 /// In addition, if a role was supplied, the provider will assume the role with the initially
-/// discovered credentials, returning the new STS credentials instead.
+/// discovered credentials, returning the new STS credentials instead. The role can be assumed with
+/// a source identity, which CloudTrail records for all actions performed with the role, and with
+/// session tags, which IAM policies can use for attribute-based access control.
 #[derive(Clone)]
 pub struct AwsxProvider {
     assume_role_arn: Option<String>,
+    source_identity: Option<String>,
+    session_tags: Vec<TagSpecification>,
     aws_region: Region,
     inner: AwsxInnerProvider,
 }
@@ -49,10 +55,30 @@ impl AwsxProvider {
     ) -> Result<AwsxProvider, CredentialsError> {
         Ok(AwsxProvider {
             assume_role_arn,
+            source_identity: None,
+            session_tags: Vec::new(),
             aws_region,
             inner: AwsxInnerProvider::new(aws_access_key_id, aws_secret_access_key)?,
         })
     }
+
+    /// Set the source identity the role is assumed with, if any.
+    ///
+    /// The trust policy of the role has to allow `sts:SetSourceIdentity` for a source identity to
+    /// be set. Without a role to assume, the source identity has no effect.
+    pub fn with_source_identity(mut self, source_identity: Option<String>) -> Self {
+        self.source_identity = source_identity;
+        self
+    }
+
+    /// Set the tags of the session the role is assumed with.
+    ///
+    /// The trust policy of the role has to allow `sts:TagSession` for session tags to be set.
+    /// Without a role to assume, the session tags have no effect.
+    pub fn with_session_tags(mut self, session_tags: Vec<TagSpecification>) -> Self {
+        self.session_tags = session_tags;
+        self
+    }
 }
 
 #[async_trait]
@@ -60,26 +86,36 @@ impl ProvideAwsCredentials for AwsxProvider {
     async fn credentials(&self) -> Result<AwsCredentials, CredentialsError> {
         return if let Some(assume_role_arn) = &self.assume_role_arn {
             let sts_client = StsClient::new_with(
-                HttpClient::new().expect("Failed to create HTTP client"),
+                http::client().expect("Failed to create HTTP client"),
                 self.inner.clone(),
-                self.aws_region.clone(),
+                endpoint::region_for("sts", self.aws_region.clone()),
             );
-            StsAssumeRoleSessionCredentialsProvider::new(
-                sts_client,
-                assume_role_arn.to_owned(),
-                format!(
-                    "{name}=={version}@{request_id}",
-                    name = env!("CARGO_PKG_NAME"),
-                    version = env!("CARGO_PKG_VERSION"),
-                    request_id = Uuid::new_v4(),
-                ),
-                None,
-                None,
-                None,
-                None,
+            let response = sts_client
+                .assume_role(AssumeRoleRequest {
+                    role_arn: assume_role_arn.to_owned(),
+                    role_session_name: format!(
+                        "{name}=={version}@{request_id}",
+                        name = env!("CARGO_PKG_NAME"),
+                        version = env!("CARGO_PKG_VERSION"),
+                        request_id = Uuid::new_v4(),
+                    ),
+                    source_identity: self.source_identity.clone(),
+                    tags: if self.session_tags.is_empty() {
+                        None
+                    } else {
+                        Some(self.session_tags.iter().map(Into::into).collect())
+                    },
+                    ..Default::default()
+                })
+                .await
+                .map_err(|err| {
+                    CredentialsError::new(format!("StsProvider assume_role error: {:?}", err))
+                })?;
+            AwsCredentials::new_for_credentials(
+                response
+                    .credentials
+                    .ok_or_else(|| CredentialsError::new("no credentials in response"))?,
             )
-            .credentials()
-            .await
         } else {
             self.inner.credentials().await
         };
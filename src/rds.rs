@@ -0,0 +1,517 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module enables handling of RDS DB snapshots and DB cluster snapshots.
+
+use rusoto_core::{signature::SignedRequest, Region};
+use rusoto_credential::AwsCredentials;
+use rusoto_rds::{
+    CopyDBClusterSnapshotMessage, CopyDBSnapshotMessage, CreateDBClusterSnapshotMessage,
+    CreateDBSnapshotMessage, DBClusterSnapshot, DBSnapshot, DescribeDBClusterSnapshotsMessage,
+    DescribeDBSnapshotsMessage, ModifyDBClusterSnapshotAttributeMessage,
+    ModifyDBSnapshotAttributeMessage, Rds,
+};
+use serde::Serialize;
+use std::time::Duration;
+
+use crate::{error::Error, tags::TagSpecification};
+
+/// Interval in which the status of a snapshot is polled while waiting.
+const POLL_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Duration for which the pre-signed URL of a cross-region copy is valid.
+const PRE_SIGNED_URL_EXPIRY: Duration = Duration::from_secs(3600);
+
+/// Name of the snapshot attribute holding the accounts that are allowed to restore the snapshot.
+const RESTORE_ATTRIBUTE: &str = "restore";
+
+/// Alias of the AWS managed KMS key RDS encrypts snapshots with by default.
+const DEFAULT_KMS_KEY_ALIAS: &str = "alias/aws/rds";
+
+/// Whether the KMS key, given by its alias or ARN, is the AWS managed `aws/rds` key.
+///
+/// Snapshots encrypted with this key cannot be shared. Only the alias identifies the key, the key
+/// ID of the AWS managed key can't be told apart from customer managed keys without asking KMS.
+fn is_default_kms_key(kms_key_id: &str) -> bool {
+    kms_key_id == DEFAULT_KMS_KEY_ALIAS
+        || (kms_key_id.starts_with("arn:")
+            && kms_key_id.ends_with(&format!(":{}", DEFAULT_KMS_KEY_ALIAS)))
+}
+
+/// Represents an RDS snapshot, either of a DB instance or of a DB cluster.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DbSnapshot {
+    /// A DB snapshot, i.e. a snapshot of a DB instance, identified by its identifier or ARN.
+    Instance(String),
+    /// A DB cluster snapshot, identified by its identifier or ARN.
+    Cluster(String),
+}
+
+/// Options for copying a snapshot, see [`DbSnapshot::copy`].
+///
+/// [`DbSnapshot::copy`]: enum.DbSnapshot.html#method.copy
+#[derive(Debug, Clone, Default)]
+pub struct CopyOptions {
+    /// KMS key to encrypt the copy with. Required when copying an encrypted snapshot to another
+    /// region, otherwise the copy is encrypted with the same key as the source snapshot.
+    pub kms_key_id: Option<String>,
+    /// Whether to copy all tags of the source snapshot to the copy.
+    pub copy_tags: bool,
+    /// Additional tags to assign to the copy.
+    pub tags: Vec<TagSpecification>,
+    /// Pre-signed URL authorizing the copy in the source region, required when copying an
+    /// encrypted snapshot to another region. See [`DbSnapshot::pre_signed_copy_url`].
+    ///
+    /// [`DbSnapshot::pre_signed_copy_url`]: enum.DbSnapshot.html#method.pre_signed_copy_url
+    pub pre_signed_url: Option<String>,
+}
+
+/// The description of a snapshot, including its current status.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DbSnapshotDescription {
+    /// ARN of the snapshot.
+    pub arn: Option<String>,
+    /// Status of the snapshot, e.g. `creating` or `available`.
+    pub status: String,
+    /// Progress of the creation or copy of the snapshot, in percent.
+    pub percent_progress: Option<i64>,
+    /// Whether the snapshot is encrypted.
+    pub encrypted: bool,
+    /// ARN of the KMS key the snapshot is encrypted with, if it is encrypted.
+    pub kms_key_id: Option<String>,
+}
+
+impl From<DBSnapshot> for DbSnapshotDescription {
+    fn from(db_snapshot: DBSnapshot) -> Self {
+        DbSnapshotDescription {
+            arn: db_snapshot.db_snapshot_arn,
+            status: db_snapshot.status.unwrap_or_default(),
+            percent_progress: db_snapshot.percent_progress,
+            encrypted: db_snapshot.encrypted.unwrap_or(false),
+            kms_key_id: db_snapshot.kms_key_id,
+        }
+    }
+}
+
+impl From<DBClusterSnapshot> for DbSnapshotDescription {
+    fn from(db_cluster_snapshot: DBClusterSnapshot) -> Self {
+        DbSnapshotDescription {
+            arn: db_cluster_snapshot.db_cluster_snapshot_arn,
+            status: db_cluster_snapshot.status.unwrap_or_default(),
+            percent_progress: db_cluster_snapshot.percent_progress,
+            encrypted: db_cluster_snapshot.storage_encrypted.unwrap_or(false),
+            kms_key_id: db_cluster_snapshot.kms_key_id,
+        }
+    }
+}
+
+impl DbSnapshot {
+    /// Return the identifier or ARN of the snapshot.
+    pub fn identifier(&self) -> &str {
+        match self {
+            DbSnapshot::Instance(identifier) | DbSnapshot::Cluster(identifier) => identifier,
+        }
+    }
+
+    /// Create the snapshot of the given DB instance or DB cluster, returning its initial status.
+    ///
+    /// The identifier of the snapshot is used as identifier of the new snapshot. This method will
+    /// not wait for the creation to complete, use [`wait_until_available`] for that.
+    ///
+    /// [`wait_until_available`]: #method.wait_until_available
+    pub async fn create(
+        &self,
+        rds: &dyn Rds,
+        source_identifier: &str,
+        tags: &[TagSpecification],
+    ) -> Result<DbSnapshotDescription, Error> {
+        let tags = Some(tags.iter().map(Into::into).collect());
+        let status = match self {
+            DbSnapshot::Instance(identifier) => rds
+                .create_db_snapshot(CreateDBSnapshotMessage {
+                    db_instance_identifier: source_identifier.to_owned(),
+                    db_snapshot_identifier: identifier.clone(),
+                    tags,
+                })
+                .await?
+                .db_snapshot
+                .map(DbSnapshotDescription::from),
+            DbSnapshot::Cluster(identifier) => rds
+                .create_db_cluster_snapshot(CreateDBClusterSnapshotMessage {
+                    db_cluster_identifier: source_identifier.to_owned(),
+                    db_cluster_snapshot_identifier: identifier.clone(),
+                    tags,
+                })
+                .await?
+                .db_cluster_snapshot
+                .map(DbSnapshotDescription::from),
+        };
+
+        Ok(status.unwrap_or_else(|| DbSnapshotDescription {
+            arn: None,
+            status: "creating".to_owned(),
+            percent_progress: None,
+            encrypted: false,
+            kms_key_id: None,
+        }))
+    }
+
+    /// Copy the snapshot to a new snapshot of the same kind, returning the new snapshot and its
+    /// initial status.
+    ///
+    /// The copy is created in the region of the given client. To copy the snapshot from another
+    /// region, the identifier of this snapshot has to be its ARN. This method will not wait for
+    /// the copy to complete, use [`wait_until_available`] on the returned snapshot for that.
+    ///
+    /// [`wait_until_available`]: #method.wait_until_available
+    pub async fn copy(
+        &self,
+        rds: &dyn Rds,
+        target_identifier: &str,
+        options: &CopyOptions,
+    ) -> Result<(DbSnapshot, DbSnapshotDescription), Error> {
+        let tags = Some(options.tags.iter().map(Into::into).collect());
+        let copy_tags = options.copy_tags.then_some(true);
+        let (target, status) = match self {
+            DbSnapshot::Instance(identifier) => (
+                DbSnapshot::Instance(target_identifier.to_owned()),
+                rds.copy_db_snapshot(CopyDBSnapshotMessage {
+                    copy_tags,
+                    kms_key_id: options.kms_key_id.clone(),
+                    pre_signed_url: options.pre_signed_url.clone(),
+                    source_db_snapshot_identifier: identifier.clone(),
+                    tags,
+                    target_db_snapshot_identifier: target_identifier.to_owned(),
+                    ..Default::default()
+                })
+                .await?
+                .db_snapshot
+                .map(DbSnapshotDescription::from),
+            ),
+            DbSnapshot::Cluster(identifier) => (
+                DbSnapshot::Cluster(target_identifier.to_owned()),
+                rds.copy_db_cluster_snapshot(CopyDBClusterSnapshotMessage {
+                    copy_tags,
+                    kms_key_id: options.kms_key_id.clone(),
+                    pre_signed_url: options.pre_signed_url.clone(),
+                    source_db_cluster_snapshot_identifier: identifier.clone(),
+                    tags,
+                    target_db_cluster_snapshot_identifier: target_identifier.to_owned(),
+                })
+                .await?
+                .db_cluster_snapshot
+                .map(DbSnapshotDescription::from),
+            ),
+        };
+
+        let status = status.unwrap_or_else(|| DbSnapshotDescription {
+            arn: None,
+            status: "copying".to_owned(),
+            percent_progress: None,
+            encrypted: options.kms_key_id.is_some(),
+            kms_key_id: options.kms_key_id.clone(),
+        });
+        Ok((target, status))
+    }
+
+    /// Create the pre-signed URL required to copy this encrypted snapshot from the source region
+    /// to the destination region, see [`CopyOptions::pre_signed_url`].
+    ///
+    /// The identifier of this snapshot has to be its ARN, and the KMS key has to reside in the
+    /// destination region.
+    ///
+    /// [`CopyOptions::pre_signed_url`]: struct.CopyOptions.html#structfield.pre_signed_url
+    pub fn pre_signed_copy_url(
+        &self,
+        target_identifier: &str,
+        kms_key_id: &str,
+        source_region: &Region,
+        destination_region: &Region,
+        credentials: &AwsCredentials,
+    ) -> String {
+        let mut request = SignedRequest::new("GET", "rds", source_region, "/");
+        let (action, source_key, target_key) = match self {
+            DbSnapshot::Instance(_) => (
+                "CopyDBSnapshot",
+                "SourceDBSnapshotIdentifier",
+                "TargetDBSnapshotIdentifier",
+            ),
+            DbSnapshot::Cluster(_) => (
+                "CopyDBClusterSnapshot",
+                "SourceDBClusterSnapshotIdentifier",
+                "TargetDBClusterSnapshotIdentifier",
+            ),
+        };
+        request.add_param("Action", action);
+        request.add_param("Version", "2014-10-31");
+        request.add_param(source_key, self.identifier());
+        request.add_param(target_key, target_identifier);
+        request.add_param("KmsKeyId", kms_key_id);
+        request.add_param("DestinationRegion", destination_region.name());
+        request.generate_presigned_url(credentials, &PRE_SIGNED_URL_EXPIRY, false)
+    }
+
+    /// Describe the snapshot, including its current status.
+    pub async fn describe(&self, rds: &dyn Rds) -> Result<DbSnapshotDescription, Error> {
+        let status = match self {
+            DbSnapshot::Instance(identifier) => rds
+                .describe_db_snapshots(DescribeDBSnapshotsMessage {
+                    db_snapshot_identifier: Some(identifier.clone()),
+                    ..Default::default()
+                })
+                .await?
+                .db_snapshots
+                .and_then(|db_snapshots| db_snapshots.into_iter().next())
+                .map(DbSnapshotDescription::from),
+            DbSnapshot::Cluster(identifier) => rds
+                .describe_db_cluster_snapshots(DescribeDBClusterSnapshotsMessage {
+                    db_cluster_snapshot_identifier: Some(identifier.clone()),
+                    ..Default::default()
+                })
+                .await?
+                .db_cluster_snapshots
+                .and_then(|db_cluster_snapshots| db_cluster_snapshots.into_iter().next())
+                .map(DbSnapshotDescription::from),
+        };
+
+        status.ok_or_else(|| {
+            Error::DbSnapshotFailed(self.identifier().to_owned(), "not-found".to_owned())
+        })
+    }
+
+    /// Share the snapshot with the given accounts, or revoke the access of the given accounts.
+    ///
+    /// Returns the IDs of all accounts the snapshot is shared with after the modification.
+    ///
+    /// Only snapshots that are `available` can be shared. Encrypted snapshots can only be shared
+    /// if they are encrypted with a customer managed KMS key, in which case the target accounts
+    /// additionally need access to that key. AWS rejecting the modification of a snapshot encrypted
+    /// with the default `aws/rds` key is reported as [`Error::DbSnapshotNotShareable`], explaining
+    /// this caveat.
+    ///
+    /// [`Error::DbSnapshotNotShareable`]: ../error/enum.Error.html#variant.DbSnapshotNotShareable
+    pub async fn share(
+        &self,
+        rds: &dyn Rds,
+        accounts_to_add: &[String],
+        accounts_to_remove: &[String],
+    ) -> Result<Vec<String>, Error> {
+        let description = self.describe(rds).await?;
+        if description.status != "available" {
+            return Err(Error::DbSnapshotNotShareable(
+                self.identifier().to_owned(),
+                format!(
+                    "the snapshot is {}, only available snapshots can be shared",
+                    description.status
+                ),
+            ));
+        }
+
+        let values_to_add = Some(accounts_to_add.to_vec()).filter(|values| !values.is_empty());
+        let values_to_remove =
+            Some(accounts_to_remove.to_vec()).filter(|values| !values.is_empty());
+        let result = match self {
+            DbSnapshot::Instance(identifier) => rds
+                .modify_db_snapshot_attribute(ModifyDBSnapshotAttributeMessage {
+                    attribute_name: RESTORE_ATTRIBUTE.to_owned(),
+                    db_snapshot_identifier: identifier.clone(),
+                    values_to_add,
+                    values_to_remove,
+                })
+                .await
+                .map(|output| {
+                    output
+                        .db_snapshot_attributes_result
+                        .and_then(|result| result.db_snapshot_attributes)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .filter(|attribute| {
+                            attribute.attribute_name.as_deref() == Some(RESTORE_ATTRIBUTE)
+                        })
+                        .flat_map(|attribute| attribute.attribute_values.unwrap_or_default())
+                        .collect()
+                })
+                .map_err(Error::from),
+            DbSnapshot::Cluster(identifier) => rds
+                .modify_db_cluster_snapshot_attribute(ModifyDBClusterSnapshotAttributeMessage {
+                    attribute_name: RESTORE_ATTRIBUTE.to_owned(),
+                    db_cluster_snapshot_identifier: identifier.clone(),
+                    values_to_add,
+                    values_to_remove,
+                })
+                .await
+                .map(|output| {
+                    output
+                        .db_cluster_snapshot_attributes_result
+                        .and_then(|result| result.db_cluster_snapshot_attributes)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .filter(|attribute| {
+                            attribute.attribute_name.as_deref() == Some(RESTORE_ATTRIBUTE)
+                        })
+                        .flat_map(|attribute| attribute.attribute_values.unwrap_or_default())
+                        .collect()
+                })
+                .map_err(Error::from),
+        };
+
+        result.map_err(|error| match description.kms_key_id.as_deref() {
+            Some(kms_key_id)
+                if description.encrypted
+                    && is_default_kms_key(kms_key_id)
+                    && error.is_user_error() =>
+            {
+                Error::DbSnapshotNotShareable(
+                    self.identifier().to_owned(),
+                    format!(
+                        "the snapshot is encrypted with the KMS key {}, but snapshots encrypted \
+                         with the default `aws/rds` key cannot be shared. Copy the snapshot using \
+                         a customer managed KMS key and share the copy instead ({})",
+                        kms_key_id, error
+                    ),
+                )
+            }
+            _ => error,
+        })
+    }
+
+    /// Wait until the snapshot is available, returning its final status.
+    ///
+    /// Returns an [`Error::DbSnapshotFailed`] if the snapshot ends up in any other status, e.g.
+    /// `failed`, and an [`Error::WaitTimeout`] if it is not available within the timeout.
+    ///
+    /// [`Error::DbSnapshotFailed`]: ../error/enum.Error.html#variant.DbSnapshotFailed
+    /// [`Error::WaitTimeout`]: ../error/enum.Error.html#variant.WaitTimeout
+    pub async fn wait_until_available(
+        &self,
+        rds: &dyn Rds,
+        timeout: Duration,
+    ) -> Result<DbSnapshotDescription, Error> {
+        let wait = async {
+            loop {
+                let status = self.describe(rds).await?;
+                match status.status.as_str() {
+                    "creating" | "copying" | "pending" => tokio::time::sleep(POLL_INTERVAL).await,
+                    "available" => return Ok(status),
+                    _ => {
+                        return Err(Error::DbSnapshotFailed(
+                            self.identifier().to_owned(),
+                            status.status,
+                        ))
+                    }
+                }
+            }
+        };
+
+        tokio::time::timeout(timeout, wait)
+            .await
+            .unwrap_or_else(|_| {
+                Err(Error::WaitTimeout(format!(
+                    "DB snapshot {} to become available",
+                    self.identifier()
+                )))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{self, MockDispatcher};
+
+    #[test]
+    fn default_kms_key_is_recognized_by_its_alias() {
+        assert!(is_default_kms_key("alias/aws/rds"));
+        assert!(is_default_kms_key(
+            "arn:aws:kms:eu-central-1:123456789012:alias/aws/rds"
+        ));
+        assert!(!is_default_kms_key("alias/aws/rds-backup"));
+        assert!(!is_default_kms_key("alias/my-key"));
+        assert!(!is_default_kms_key(
+            "arn:aws:kms:eu-central-1:123456789012:key/1234abcd-12ab-34cd-56ef-1234567890ab"
+        ));
+    }
+
+    async fn share_encrypted(kms_key_id: &str) -> Error {
+        let dispatcher = MockDispatcher::with_status(vec![
+            (
+                200,
+                format!(
+                    "<DescribeDBSnapshotsResponse><DescribeDBSnapshotsResult><DBSnapshots>\
+                     <DBSnapshot><DBSnapshotIdentifier>my-snapshot</DBSnapshotIdentifier>\
+                     <Status>available</Status><Encrypted>true</Encrypted>\
+                     <KmsKeyId>{}</KmsKeyId></DBSnapshot>\
+                     </DBSnapshots></DescribeDBSnapshotsResult>\
+                     <ResponseMetadata><RequestId>request-id</RequestId></ResponseMetadata>\
+                     </DescribeDBSnapshotsResponse>",
+                    kms_key_id
+                ),
+            ),
+            (
+                400,
+                "<ErrorResponse><Error><Type>Sender</Type><Code>InvalidParameterValue</Code>\
+                 <Message>The snapshot cannot be shared.</Message></Error>\
+                 <RequestId>request-id</RequestId></ErrorResponse>"
+                    .to_owned(),
+            ),
+        ]);
+
+        DbSnapshot::Instance("my-snapshot".to_owned())
+            .share(&mock::rds(&dispatcher), &["210987654321".to_owned()], &[])
+            .await
+            .unwrap_err()
+    }
+
+    #[tokio::test]
+    async fn sharing_snapshot_with_default_kms_key_explains_caveat() {
+        let error = share_encrypted("arn:aws:kms:eu-central-1:123456789012:alias/aws/rds").await;
+        assert!(matches!(error, Error::DbSnapshotNotShareable(..)));
+        assert!(error.to_string().contains("customer managed KMS key"));
+    }
+
+    #[tokio::test]
+    async fn sharing_snapshot_with_customer_managed_kms_key_passes_error_through() {
+        let error = share_encrypted(
+            "arn:aws:kms:eu-central-1:123456789012:key/1234abcd-12ab-34cd-56ef-1234567890ab",
+        )
+        .await;
+        assert_eq!(Some("InvalidParameterValue"), error.aws_error_code());
+    }
+
+    #[test]
+    fn pre_signed_copy_url_targets_source_region() {
+        let db_snapshot = DbSnapshot::Cluster(
+            "arn:aws:rds:eu-west-1:123456789012:cluster-snapshot:my-snapshot".to_owned(),
+        );
+        let credentials =
+            AwsCredentials::new("AKIDEXAMPLE", "secret", Some("token".to_owned()), None);
+
+        let url = db_snapshot.pre_signed_copy_url(
+            "my-copy",
+            "alias/my-key",
+            &Region::EuWest1,
+            &Region::EuCentral1,
+            &credentials,
+        );
+
+        assert!(url.starts_with("https://rds.eu-west-1.amazonaws.com/?"));
+        assert!(url.contains("Action=CopyDBClusterSnapshot"));
+        assert!(url.contains("TargetDBClusterSnapshotIdentifier=my-copy"));
+        assert!(url.contains("DestinationRegion=eu-central-1"));
+        assert!(url.contains("X-Amz-Signature="));
+        assert!(url.contains("X-Amz-Security-Token=token"));
+    }
+}
@@ -0,0 +1,235 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Resolution of the region the AWS API calls are performed in.
+//!
+//! Rusoto falls back to us-east-1 silently if it cannot determine the region, and ignores invalid
+//! region names. Resolving the region explicitly instead allows telling where the region came
+//! from, rejecting invalid names and asking the instance metadata service of EC2 as well.
+
+use hyper::{body, client::HttpConnector, Body, Client, Method, Request};
+use rusoto_core::Region;
+use rusoto_credential::ProfileProvider;
+use std::{fmt, str::FromStr, time::Duration};
+
+use crate::error::Error;
+
+/// The base URL of the instance metadata service of EC2.
+const INSTANCE_METADATA_URL: &str = "http://169.254.169.254/latest";
+
+/// The time to wait for the instance metadata service, which is unreachable outside of EC2.
+const INSTANCE_METADATA_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Where the region was determined from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegionSource {
+    /// The region was provided explicitly.
+    Explicit,
+    /// The region was read from the given environment variable.
+    Environment(&'static str),
+    /// The region was read from the profile in the AWS config file.
+    Profile,
+    /// The region was retrieved from the instance metadata service of EC2.
+    InstanceMetadata,
+    /// The region could not be determined, which is why the default region, us-east-1, is used.
+    Fallback,
+}
+
+impl fmt::Display for RegionSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegionSource::Explicit => write!(f, "explicitly provided"),
+            RegionSource::Environment(variable) => write!(f, "environment variable {}", variable),
+            RegionSource::Profile => write!(f, "profile in the AWS config file"),
+            RegionSource::InstanceMetadata => write!(f, "EC2 instance metadata"),
+            RegionSource::Fallback => write!(f, "fallback"),
+        }
+    }
+}
+
+/// Resolve the region, unless it was provided explicitly.
+///
+/// The region is taken from the first of these sources providing one:
+///
+/// * the environment variables `AWS_REGION` and `AWS_DEFAULT_REGION`,
+/// * the profile selected by `AWS_PROFILE` in the AWS config file,
+/// * the instance metadata service of EC2 (IMDSv2), unless `AWS_EC2_METADATA_DISABLED` is `true`.
+///
+/// Empty values, e.g. an empty `AWS_REGION`, are treated as if they were not set. If none of the
+/// sources provides a region, us-east-1 is returned with the source [`RegionSource::Fallback`],
+/// which callers should warn about. An invalid region name fails the resolution instead of falling
+/// back.
+///
+/// [`RegionSource::Fallback`]: enum.RegionSource.html#variant.Fallback
+pub async fn resolve(explicit: Option<Region>) -> Result<(Region, RegionSource), Error> {
+    if let Some(region) = explicit {
+        return Ok((region, RegionSource::Explicit));
+    }
+
+    if let Some(resolved) = environment_region(|variable| std::env::var(variable).ok())? {
+        return Ok(resolved);
+    }
+
+    if let Some(region) = parse(ProfileProvider::region().ok().flatten(), "AWS config file")? {
+        return Ok((region, RegionSource::Profile));
+    }
+
+    if let Some(region) = instance_metadata_region().await {
+        return Ok((region, RegionSource::InstanceMetadata));
+    }
+
+    Ok((Region::UsEast1, RegionSource::Fallback))
+}
+
+/// Resolve the region from the first environment variable providing one, looking up the variables
+/// with `var`.
+fn environment_region<F: Fn(&str) -> Option<String>>(
+    var: F,
+) -> Result<Option<(Region, RegionSource)>, Error> {
+    for variable in &["AWS_REGION", "AWS_DEFAULT_REGION"] {
+        let source = format!("environment variable {}", variable);
+        if let Some(region) = parse(var(variable), &source)? {
+            return Ok(Some((region, RegionSource::Environment(variable))));
+        }
+    }
+    Ok(None)
+}
+
+/// Parse the name of the region, if there is a name that is not empty.
+fn parse(name: Option<String>, source: &str) -> Result<Option<Region>, Error> {
+    let name = match name.as_deref().map(str::trim) {
+        Some(name) if !name.is_empty() => name,
+        _ => return Ok(None),
+    };
+    Region::from_str(name).map(Some).map_err(|_| {
+        Error::InvalidConfiguration(format!("invalid region {} in the {}", name, source))
+    })
+}
+
+/// Retrieve the region from the instance metadata service, using a session token (IMDSv2).
+///
+/// Any failure, most notably not running on EC2, results in `None`.
+async fn instance_metadata_region() -> Option<Region> {
+    if std::env::var("AWS_EC2_METADATA_DISABLED")
+        .map(|disabled| disabled.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+    {
+        return None;
+    }
+
+    let client = Client::builder().build::<_, Body>(HttpConnector::new());
+    let get = |request: Request<Body>| {
+        let response = client.request(request);
+        async move {
+            let response = response.await.ok()?;
+            if !response.status().is_success() {
+                return None;
+            }
+            let bytes = body::to_bytes(response.into_body()).await.ok()?;
+            String::from_utf8(bytes.to_vec()).ok()
+        }
+    };
+
+    let lookup = async {
+        let token = get(Request::builder()
+            .method(Method::PUT)
+            .uri(format!("{}/api/token", INSTANCE_METADATA_URL))
+            .header("x-aws-ec2-metadata-token-ttl-seconds", "60")
+            .body(Body::empty())
+            .ok()?)
+        .await?;
+        let name = get(Request::builder()
+            .method(Method::GET)
+            .uri(format!(
+                "{}/meta-data/placement/region",
+                INSTANCE_METADATA_URL
+            ))
+            .header("x-aws-ec2-metadata-token", token)
+            .body(Body::empty())
+            .ok()?)
+        .await?;
+        Region::from_str(name.trim()).ok()
+    };
+
+    tokio::time::timeout(INSTANCE_METADATA_TIMEOUT, lookup)
+        .await
+        .ok()
+        .flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn environment<'a>(variables: &'a [(&str, &str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |name| {
+            variables
+                .iter()
+                .find(|(variable, _)| *variable == name)
+                .map(|(_, value)| (*value).to_owned())
+        }
+    }
+
+    #[test]
+    fn empty_environment_variables_are_ignored() {
+        assert_eq!(
+            Some((
+                Region::EuWest1,
+                RegionSource::Environment("AWS_DEFAULT_REGION")
+            )),
+            environment_region(environment(&[
+                ("AWS_REGION", ""),
+                ("AWS_DEFAULT_REGION", "eu-west-1"),
+            ]))
+            .unwrap()
+        );
+        assert_eq!(
+            None,
+            environment_region(environment(&[
+                ("AWS_REGION", ""),
+                ("AWS_DEFAULT_REGION", "  "),
+            ]))
+            .unwrap()
+        );
+        assert_eq!(None, environment_region(environment(&[])).unwrap());
+    }
+
+    #[test]
+    fn first_environment_variable_takes_precedence() {
+        assert_eq!(
+            Some((Region::EuCentral1, RegionSource::Environment("AWS_REGION"))),
+            environment_region(environment(&[
+                ("AWS_REGION", "eu-central-1"),
+                ("AWS_DEFAULT_REGION", "eu-west-1"),
+            ]))
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn invalid_region_names_are_rejected() {
+        assert!(matches!(
+            environment_region(environment(&[("AWS_REGION", "mars-north-1")])),
+            Err(Error::InvalidConfiguration(message))
+                if message == "invalid region mars-north-1 in the environment variable AWS_REGION"
+        ));
+        assert_eq!(None, parse(Some(String::new()), "AWS config file").unwrap());
+        assert_eq!(
+            Some(Region::UsWest2),
+            parse(Some(" us-west-2\n".to_owned()), "AWS config file").unwrap()
+        );
+    }
+}
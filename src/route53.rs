@@ -0,0 +1,354 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module enables handling of Route 53 record sets.
+//!
+//! There is no Rusoto crate for Route 53 available to awsx, which is why this module provides a
+//! minimal client for the few actions awsx needs, dispatching signed requests through Rusoto.
+
+use rusoto_core::{
+    credential::ProvideAwsCredentials, param::Params, signature::SignedRequest, Client,
+    DispatchSignedRequest, Region, RusotoError,
+};
+use std::{convert::Infallible, time::Duration};
+
+use crate::{
+    error::Error,
+    xml::{escape, XmlElement},
+};
+
+/// Version of the Route 53 API this client implements.
+const API_VERSION: &str = "2013-04-01";
+
+/// Interval in which the status of a change is polled while waiting.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Normalize a DNS name to the form Route 53 returns, i.e. lowercase with a trailing dot.
+pub fn normalize_name(name: &str) -> String {
+    let name = name.to_lowercase();
+    if name.ends_with('.') {
+        name
+    } else {
+        format!("{}.", name)
+    }
+}
+
+/// A record set of a hosted zone.
+///
+/// The record set keeps the complete XML representation returned by Route 53, so that it can be
+/// written back unchanged apart from the modified weight, including alias targets and health
+/// checks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceRecordSet {
+    element: XmlElement,
+}
+
+impl ResourceRecordSet {
+    /// Name of the record set, with a trailing dot.
+    pub fn name(&self) -> &str {
+        self.element.child_text("Name").unwrap_or_default()
+    }
+
+    /// Type of the record set, e.g. `A` or `CNAME`.
+    pub fn record_type(&self) -> &str {
+        self.element.child_text("Type").unwrap_or_default()
+    }
+
+    /// Identifier distinguishing record sets with the same name and type, e.g. of weighted records.
+    pub fn set_identifier(&self) -> Option<&str> {
+        self.element.child_text("SetIdentifier")
+    }
+
+    /// Weight of a weighted record set.
+    pub fn weight(&self) -> Option<i64> {
+        self.element
+            .child_text("Weight")
+            .and_then(|weight| weight.parse().ok())
+    }
+
+    /// Return a copy of the record set with the given weight.
+    pub fn with_weight(&self, weight: i64) -> Self {
+        let mut element = self.element.clone();
+        match element
+            .children
+            .iter_mut()
+            .find(|child| child.name == "Weight")
+        {
+            Some(child) => child.text = weight.to_string(),
+            None => element
+                .children
+                .push(XmlElement::new("Weight", &weight.to_string())),
+        }
+        ResourceRecordSet { element }
+    }
+}
+
+/// A minimal client for the Route 53 API.
+pub struct Route53Client {
+    client: Client,
+}
+
+impl Route53Client {
+    /// Create a client dispatching its requests with the given dispatcher and credentials.
+    ///
+    /// Route 53 is a global service, so no region has to be given.
+    pub fn new_with<P, D>(request_dispatcher: D, credentials_provider: P) -> Self
+    where
+        P: ProvideAwsCredentials + Send + Sync + 'static,
+        D: DispatchSignedRequest + Send + Sync + 'static,
+    {
+        Route53Client {
+            client: Client::new_with(credentials_provider, request_dispatcher),
+        }
+    }
+
+    async fn request(
+        &self,
+        method: &str,
+        path: &str,
+        params: Params,
+        body: Option<String>,
+    ) -> Result<XmlElement, Error> {
+        // The region only determines the signature, Route 53 itself is always signed for us-east-1.
+        let mut request = SignedRequest::new(
+            method,
+            "route53",
+            &Region::UsEast1,
+            &format!("/{}{}", API_VERSION, path),
+        );
+        request.set_params(params);
+        if let Some(body) = body {
+            request.set_content_type("application/xml".to_owned());
+            request.set_payload(Some(body));
+        }
+
+        let mut response = self
+            .client
+            .sign_and_dispatch(request)
+            .await
+            .map_err(RusotoError::<Infallible>::from)?;
+        let response = response
+            .buffer()
+            .await
+            .map_err(RusotoError::<Infallible>::from)?;
+        if !response.status.is_success() {
+            return Err(RusotoError::<Infallible>::Unknown(response).into());
+        }
+        XmlElement::parse(&response.body)
+    }
+
+    /// Find the ID of the hosted zone with the given name, e.g. `example.com`.
+    ///
+    /// Fails if there is no such hosted zone, or if both a public and a private hosted zone have
+    /// the name.
+    pub async fn find_hosted_zone_id(&self, hosted_zone_name: &str) -> Result<String, Error> {
+        let hosted_zone_name = normalize_name(hosted_zone_name);
+        let mut params = Params::new();
+        params.insert("dnsname".to_owned(), Some(hosted_zone_name.clone()));
+        let response = self
+            .request("GET", "/hostedzonesbyname", params, None)
+            .await?;
+
+        let hosted_zone_ids: Vec<&str> = response
+            .child("HostedZones")
+            .map(|hosted_zones| hosted_zones.children.iter())
+            .into_iter()
+            .flatten()
+            .filter(|hosted_zone| hosted_zone.child_text("Name") == Some(&hosted_zone_name))
+            .filter_map(|hosted_zone| hosted_zone.child_text("Id"))
+            .map(|id| id.trim_start_matches("/hostedzone/"))
+            .collect();
+        match hosted_zone_ids.as_slice() {
+            [] => Err(Error::ResourceNotFound(format!(
+                "hosted zone {}",
+                hosted_zone_name
+            ))),
+            [hosted_zone_id] => Ok((*hosted_zone_id).to_owned()),
+            _ => Err(Error::AmbiguousResource(format!(
+                "multiple hosted zones are named {}: {}, specify the hosted zone ID instead",
+                hosted_zone_name,
+                hosted_zone_ids.join(", ")
+            ))),
+        }
+    }
+
+    /// List all record sets of the hosted zone with the given name and type.
+    pub async fn list_resource_record_sets(
+        &self,
+        hosted_zone_id: &str,
+        name: &str,
+        record_type: &str,
+    ) -> Result<Vec<ResourceRecordSet>, Error> {
+        let name = normalize_name(name);
+        let mut resource_record_sets = Vec::new();
+        let mut next_identifier = None;
+        loop {
+            let mut params = Params::new();
+            params.insert("name".to_owned(), Some(name.clone()));
+            params.insert("type".to_owned(), Some(record_type.to_owned()));
+            if let Some(identifier) = next_identifier.take() {
+                params.insert("identifier".to_owned(), Some(identifier));
+            }
+            let response = self
+                .request(
+                    "GET",
+                    &format!("/hostedzone/{}/rrset", hosted_zone_id),
+                    params,
+                    None,
+                )
+                .await?;
+
+            // Record sets are listed starting at the given name and type, so the listing can be
+            // stopped as soon as another name or type shows up.
+            let mut exhausted = false;
+            for element in response
+                .child("ResourceRecordSets")
+                .map(|resource_record_sets| resource_record_sets.children.clone())
+                .unwrap_or_default()
+            {
+                let resource_record_set = ResourceRecordSet { element };
+                if normalize_name(resource_record_set.name()) != name
+                    || resource_record_set.record_type() != record_type
+                {
+                    exhausted = true;
+                    break;
+                }
+                resource_record_sets.push(resource_record_set);
+            }
+
+            let next_is_same = response.child_text("NextRecordName").map(normalize_name)
+                == Some(name.clone())
+                && response.child_text("NextRecordType") == Some(record_type);
+            next_identifier = response
+                .child_text("NextRecordIdentifier")
+                .map(ToOwned::to_owned);
+            if exhausted || !next_is_same || response.child_text("IsTruncated") != Some("true") {
+                return Ok(resource_record_sets);
+            }
+        }
+    }
+
+    /// Create or update the given record sets in a single change batch, returning the change ID.
+    pub async fn upsert_resource_record_sets(
+        &self,
+        hosted_zone_id: &str,
+        comment: &str,
+        resource_record_sets: &[ResourceRecordSet],
+    ) -> Result<String, Error> {
+        let changes: String = resource_record_sets
+            .iter()
+            .map(|resource_record_set| {
+                format!(
+                    "<Change><Action>UPSERT</Action>{}</Change>",
+                    resource_record_set.element.to_xml()
+                )
+            })
+            .collect();
+        let body = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+             <ChangeResourceRecordSetsRequest xmlns=\"https://route53.amazonaws.com/doc/{}/\">\
+             <ChangeBatch><Comment>{}</Comment><Changes>{}</Changes></ChangeBatch>\
+             </ChangeResourceRecordSetsRequest>",
+            API_VERSION,
+            escape(comment),
+            changes
+        );
+
+        let response = self
+            .request(
+                "POST",
+                &format!("/hostedzone/{}/rrset/", hosted_zone_id),
+                Params::new(),
+                Some(body),
+            )
+            .await?;
+        Ok(response
+            .child("ChangeInfo")
+            .and_then(|change_info| change_info.child_text("Id"))
+            .unwrap_or_default()
+            .trim_start_matches("/change/")
+            .to_owned())
+    }
+
+    /// Wait until the change was propagated to all Route 53 DNS servers.
+    pub async fn wait_for_change(&self, change_id: &str, timeout: Duration) -> Result<(), Error> {
+        let wait = async {
+            loop {
+                let response = self
+                    .request(
+                        "GET",
+                        &format!("/change/{}", change_id),
+                        Params::new(),
+                        None,
+                    )
+                    .await?;
+                let status = response
+                    .child("ChangeInfo")
+                    .and_then(|change_info| change_info.child_text("Status"));
+                if status == Some("INSYNC") {
+                    return Ok(());
+                }
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+        };
+
+        tokio::time::timeout(timeout, wait)
+            .await
+            .unwrap_or_else(|_| {
+                Err(Error::WaitTimeout(format!(
+                    "Route 53 change {} to be propagated",
+                    change_id
+                )))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resource_record_set_with_weight() {
+        let element = XmlElement::parse(
+            br#"<ResourceRecordSet xmlns="https://route53.amazonaws.com/doc/2013-04-01/">
+                <Name>app.example.com.</Name>
+                <Type>A</Type>
+                <SetIdentifier>blue</SetIdentifier>
+                <Weight>100</Weight>
+                <AliasTarget>
+                    <HostedZoneId>Z32O12XQLNTSW2</HostedZoneId>
+                    <DNSName>blue-123.eu-central-1.elb.amazonaws.com.</DNSName>
+                    <EvaluateTargetHealth>true</EvaluateTargetHealth>
+                </AliasTarget>
+            </ResourceRecordSet>"#,
+        )
+        .unwrap();
+        let resource_record_set = ResourceRecordSet { element };
+
+        assert_eq!("app.example.com.", resource_record_set.name());
+        assert_eq!("A", resource_record_set.record_type());
+        assert_eq!(Some("blue"), resource_record_set.set_identifier());
+        assert_eq!(Some(100), resource_record_set.weight());
+        assert_eq!(
+            "<ResourceRecordSet><Name>app.example.com.</Name><Type>A</Type>\
+             <SetIdentifier>blue</SetIdentifier><Weight>25</Weight><AliasTarget>\
+             <HostedZoneId>Z32O12XQLNTSW2</HostedZoneId>\
+             <DNSName>blue-123.eu-central-1.elb.amazonaws.com.</DNSName>\
+             <EvaluateTargetHealth>true</EvaluateTargetHealth></AliasTarget></ResourceRecordSet>",
+            resource_record_set.with_weight(25).element.to_xml()
+        );
+    }
+}
@@ -16,32 +16,191 @@
 
 //! A helper for uploading content to S3.
 
-use rusoto_core::HttpClient;
+use md5::{Digest, Md5};
 use rusoto_core::Region;
-use rusoto_s3::{PutObjectRequest, S3Client, StreamingBody, S3};
+use rusoto_s3::{HeadObjectRequest, PutObjectRequest, S3Client, StreamingBody, S3};
+use sha2::Sha256;
+use std::{
+    collections::HashMap,
+    fs,
+    io::{Cursor, Write},
+    path::Path,
+    str::FromStr,
+};
+use zip::{write::SimpleFileOptions, ZipWriter};
 
-use crate::{error::Error, provider::AwsxProvider};
+use crate::{
+    endpoint::{self, Partition},
+    error::Error,
+    http,
+    provider::AwsxProvider,
+    tags::TagSpecification,
+};
+
+/// The style of the URLs of uploaded objects.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UrlStyle {
+    /// `https://<bucket>.s3.<region>.amazonaws.com/<key>`, which AWS recommends, since path-style
+    /// URLs are deprecated.
+    #[default]
+    VirtualHosted,
+    /// `https://s3.<region>.amazonaws.com/<bucket>/<key>`.
+    Path,
+}
+
+/// Return the URL of an S3 object, as expected for the `TemplateURL` of a stack.
+///
+/// The URL uses the regional endpoint in the domain of the partition of the region, which is valid
+/// for all regions, unlike the legacy `s3-<region>` and global endpoints.
+///
+/// Bucket names containing dots do not match the wildcard certificate of the virtual-hosted
+/// endpoint, which is why path-style URLs are returned for them regardless of the style.
+pub fn object_url(region: &Region, bucket_name: &str, key: &str, style: UrlStyle) -> String {
+    let endpoint = format!(
+        "s3.{}.{}",
+        region.name(),
+        Partition::of(region).dns_suffix()
+    );
+    if style == UrlStyle::VirtualHosted && !bucket_name.contains('.') {
+        format!("https://{}.{}/{}", bucket_name, endpoint, key)
+    } else {
+        format!("https://{}/{}/{}", endpoint, bucket_name, key)
+    }
+}
+
+/// The hash algorithm the keys of uploaded templates and artifacts are derived from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ChecksumAlgorithm {
+    /// MD5, which is what the AWS CLI uses, allowing uploads to be deduplicated across both tools.
+    #[default]
+    Md5,
+    /// SHA-256, for environments where MD5 must not be used at all.
+    Sha256,
+}
+
+impl ChecksumAlgorithm {
+    /// Return the hex-encoded checksum of the contents.
+    ///
+    /// ```
+    /// # use awsx::s3::ChecksumAlgorithm;
+    /// assert_eq!(
+    ///     "5d41402abc4b2a76b9719d911017c592",
+    ///     ChecksumAlgorithm::Md5.hex_digest(b"hello"),
+    /// );
+    /// assert_eq!(
+    ///     "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824",
+    ///     ChecksumAlgorithm::Sha256.hex_digest(b"hello"),
+    /// );
+    /// ```
+    pub fn hex_digest(self, contents: &[u8]) -> String {
+        match self {
+            ChecksumAlgorithm::Md5 => format!("{:x}", Md5::digest(contents)),
+            ChecksumAlgorithm::Sha256 => format!("{:x}", Sha256::digest(contents)),
+        }
+    }
+}
+
+impl FromStr for ChecksumAlgorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "md5" => Ok(ChecksumAlgorithm::Md5),
+            "sha256" => Ok(ChecksumAlgorithm::Sha256),
+            _ => Err(format!("unknown checksum algorithm `{}`", s)),
+        }
+    }
+}
 
 /// A helper for uploading content to S3.
+#[derive(Clone)]
 pub struct S3Uploader {
     region: Region,
     s3_client: S3Client,
+    checksum_algorithm: ChecksumAlgorithm,
+    url_style: UrlStyle,
+    object_tags: Vec<TagSpecification>,
+    object_metadata: HashMap<String, String>,
 }
 
 impl S3Uploader {
     /// Create a new S3 uploader
     pub fn new(region: Region, provider: AwsxProvider) -> S3Uploader {
         let s3_client = S3Client::new_with(
-            HttpClient::new().expect("Failed to create HTTP client"),
+            http::client().expect("Failed to create HTTP client"),
             provider,
-            region.clone(),
+            endpoint::region_for("s3", region.clone()),
         );
-        S3Uploader { region, s3_client }
+        S3Uploader {
+            region,
+            s3_client,
+            checksum_algorithm: ChecksumAlgorithm::default(),
+            url_style: UrlStyle::default(),
+            object_tags: Vec::new(),
+            object_metadata: HashMap::new(),
+        }
+    }
+
+    /// Use the given hash algorithm to derive the keys of uploaded templates and artifacts,
+    /// instead of MD5.
+    pub fn with_checksum_algorithm(mut self, checksum_algorithm: ChecksumAlgorithm) -> Self {
+        self.checksum_algorithm = checksum_algorithm;
+        self
+    }
+
+    /// Return URLs of the given style for uploaded objects, instead of virtual-hosted-style URLs.
+    pub fn with_url_style(mut self, url_style: UrlStyle) -> Self {
+        self.url_style = url_style;
+        self
+    }
+
+    /// Tag all uploaded objects with the given tags, e.g. to select them in lifecycle rules.
+    ///
+    /// Tagging objects requires `s3:PutObjectTagging` in addition to `s3:PutObject`.
+    pub fn with_object_tags(mut self, object_tags: Vec<TagSpecification>) -> Self {
+        self.object_tags = object_tags;
+        self
+    }
+
+    /// Attach the given user metadata to all uploaded objects, in addition to the metadata
+    /// attached already.
+    ///
+    /// Since identical contents are deduplicated, the metadata of an object is the one of its
+    /// last upload.
+    pub fn with_object_metadata(mut self, object_metadata: HashMap<String, String>) -> Self {
+        self.object_metadata.extend(object_metadata);
+        self
+    }
+
+    /// Return the hash algorithm the keys of uploaded templates and artifacts are derived from.
+    pub fn checksum_algorithm(&self) -> ChecksumAlgorithm {
+        self.checksum_algorithm
+    }
+
+    /// Return the URL of an object in the bucket, in the configured style.
+    pub fn object_url(&self, bucket_name: &str, key: &str) -> String {
+        object_url(&self.region, bucket_name, key, self.url_style)
+    }
+
+    /// Check if the bucket contains an object with the key and the size.
+    ///
+    /// Any failure to check, e.g. due to lacking the permission `s3:GetObject`, is reported as the
+    /// object not existing, so that callers fall back to uploading the object.
+    pub async fn object_exists(&self, bucket_name: &str, key: &str, size: usize) -> bool {
+        self.s3_client
+            .head_object(HeadObjectRequest {
+                bucket: bucket_name.to_owned(),
+                key: key.to_owned(),
+                ..Default::default()
+            })
+            .await
+            .map(|output| output.content_length == Some(size as i64))
+            .unwrap_or(false)
     }
 
     /// Upload a given body to S3.
     ///
-    /// The return value is the path-like URL to the S3 object.
+    /// The return value is the URL of the S3 object, in the configured style.
     pub async fn upload(
         &self,
         bucket_name: &str,
@@ -49,23 +208,196 @@ impl S3Uploader {
         body: StreamingBody,
     ) -> Result<String, Error> {
         self.s3_client
-            .put_object(PutObjectRequest {
-                bucket: bucket_name.to_owned(),
-                key: key.to_owned(),
-                body: Some(body),
-                server_side_encryption: Some("AES256".to_owned()),
-                ..Default::default()
-            })
+            .put_object(self.put_object_request(bucket_name, key, body))
             .await?;
-        Ok(format!(
-            "https://s3{region}.amazonaws.com/{bucket_name}/{key}",
-            region = if self.region != Region::UsEast1 {
-                format!("-{}", self.region.name())
+        Ok(self.object_url(bucket_name, key))
+    }
+
+    /// Upload a Lambda deployment package.
+    ///
+    /// If `path` is a directory, its contents are zipped, otherwise the file is expected to be a
+    /// pre-built zip file. The key the package is uploaded to is the checksum of the zip file,
+    /// which deduplicates uploads the same way templates are deduplicated.
+    ///
+    /// The returned object can be used as is for the `Code` property of a Lambda function.
+    pub async fn upload_lambda_artifact(
+        &self,
+        bucket_name: &str,
+        path: &Path,
+    ) -> Result<S3Object, Error> {
+        let contents = if path.is_dir() {
+            zip_directory(path)?
+        } else {
+            fs::read(path)?
+        };
+        let key = format!("{}.zip", self.checksum_algorithm.hex_digest(&contents));
+
+        let output = self
+            .s3_client
+            .put_object(self.put_object_request(bucket_name, &key, contents.into()))
+            .await?;
+        Ok(S3Object {
+            bucket: bucket_name.to_owned(),
+            key,
+            version: output.version_id,
+        })
+    }
+
+    fn put_object_request(
+        &self,
+        bucket_name: &str,
+        key: &str,
+        body: StreamingBody,
+    ) -> PutObjectRequest {
+        PutObjectRequest {
+            bucket: bucket_name.to_owned(),
+            key: key.to_owned(),
+            body: Some(body),
+            server_side_encryption: Some("AES256".to_owned()),
+            tagging: tagging(&self.object_tags),
+            metadata: if self.object_metadata.is_empty() {
+                None
             } else {
-                String::new()
+                Some(self.object_metadata.clone())
             },
-            bucket_name = bucket_name,
-            key = key,
-        ))
+            ..Default::default()
+        }
+    }
+}
+
+/// Encode the tags as the URL query parameters expected by the `x-amz-tagging` header.
+fn tagging(tags: &[TagSpecification]) -> Option<String> {
+    if tags.is_empty() {
+        return None;
+    }
+    let pairs = tags
+        .iter()
+        .map(|tag| (tag.key.as_str(), tag.value.as_str()))
+        .collect::<Vec<_>>();
+    serde_urlencoded::to_string(pairs).ok()
+}
+
+/// The location of an object uploaded to S3.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct S3Object {
+    /// Name of the bucket the object was uploaded to.
+    pub bucket: String,
+    /// Key of the object.
+    pub key: String,
+    /// Version of the object, if versioning is enabled on the bucket.
+    pub version: Option<String>,
+}
+
+/// Zip the contents of the directory in memory.
+///
+/// Files are added in a stable order and without modification times, so that zipping the same
+/// contents always yields the same checksum.
+fn zip_directory(directory: &Path) -> Result<Vec<u8>, Error> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    add_directory_to_zip(&mut zip, directory, "")?;
+    Ok(zip.finish().map_err(std::io::Error::from)?.into_inner())
+}
+
+fn add_directory_to_zip(
+    zip: &mut ZipWriter<Cursor<Vec<u8>>>,
+    directory: &Path,
+    prefix: &str,
+) -> Result<(), Error> {
+    let mut entries = fs::read_dir(directory)?.collect::<Result<Vec<_>, _>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let name = format!("{}{}", prefix, entry.file_name().to_string_lossy());
+        let path = entry.path();
+        let options = SimpleFileOptions::default().last_modified_time(zip::DateTime::default());
+        // Retain permissions, since e.g. the `bootstrap` of custom runtimes has to be executable.
+        #[cfg(unix)]
+        let options = {
+            use std::os::unix::fs::PermissionsExt;
+            options.unix_permissions(entry.metadata()?.permissions().mode())
+        };
+
+        if path.is_dir() {
+            zip.add_directory(name.as_str(), options)
+                .map_err(std::io::Error::from)?;
+            add_directory_to_zip(zip, &path, &format!("{}/", name))?;
+        } else {
+            zip.start_file(name.as_str(), options)
+                .map_err(std::io::Error::from)?;
+            zip.write_all(&fs::read(&path)?)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn virtual_hosted_object_url() {
+        assert_eq!(
+            "https://templates.s3.eu-central-1.amazonaws.com/stack.yml",
+            object_url(
+                &Region::EuCentral1,
+                "templates",
+                "stack.yml",
+                UrlStyle::VirtualHosted
+            ),
+        );
+        assert_eq!(
+            "https://templates.s3.cn-north-1.amazonaws.com.cn/stack.yml",
+            object_url(
+                &Region::CnNorth1,
+                "templates",
+                "stack.yml",
+                UrlStyle::VirtualHosted
+            ),
+        );
+        // Bucket names with dots do not match the wildcard certificate.
+        assert_eq!(
+            "https://s3.eu-central-1.amazonaws.com/templates.example.com/stack.yml",
+            object_url(
+                &Region::EuCentral1,
+                "templates.example.com",
+                "stack.yml",
+                UrlStyle::VirtualHosted
+            ),
+        );
+    }
+
+    #[test]
+    fn object_tagging() {
+        assert_eq!(None, tagging(&[]));
+        assert_eq!(
+            Some("team=platform&purpose=cloudformation+templates".to_owned()),
+            tagging(&[
+                "team=platform".parse().unwrap(),
+                "purpose=cloudformation templates".parse().unwrap(),
+            ]),
+        );
+    }
+
+    #[test]
+    fn path_style_object_url() {
+        assert_eq!(
+            "https://s3.eu-central-1.amazonaws.com/templates/stack.yml",
+            object_url(
+                &Region::EuCentral1,
+                "templates",
+                "stack.yml",
+                UrlStyle::Path
+            ),
+        );
+        assert_eq!(
+            "https://s3.us-gov-west-1.amazonaws.com/templates/stack.yml",
+            object_url(
+                &Region::UsGovWest1,
+                "templates",
+                "stack.yml",
+                UrlStyle::Path
+            ),
+        );
     }
 }
@@ -0,0 +1,208 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+//! This module enables publishing messages to SNS topics.
+//!
+//! There is no Rusoto crate for SNS available to awsx, which is why this module provides a minimal
+//! client for publishing, dispatching signed requests through Rusoto.
+
+use rusoto_core::{
+    credential::ProvideAwsCredentials, param::Params, signature::SignedRequest, Client,
+    DispatchSignedRequest, Region, RusotoError,
+};
+use std::{convert::Infallible, str::FromStr};
+
+use crate::{error::Error, xml::XmlElement};
+
+/// Version of the SNS API this client implements.
+const API_VERSION: &str = "2010-03-31";
+
+/// Extract the region of an SNS topic from its ARN, e.g. `eu-central-1` from
+/// `arn:aws:sns:eu-central-1:123456789012:deployments`.
+///
+/// ```
+/// # use awsx::sns::topic_region;
+/// # use rusoto_core::Region;
+/// assert_eq!(
+///     Some(Region::EuCentral1),
+///     topic_region("arn:aws:sns:eu-central-1:123456789012:deployments"),
+/// );
+/// assert_eq!(None, topic_region("deployments"));
+/// ```
+pub fn topic_region(topic_arn: &str) -> Option<Region> {
+    topic_arn
+        .split(':')
+        .nth(3)
+        .and_then(|region| Region::from_str(region).ok())
+}
+
+/// A minimal client for the SNS API.
+pub struct SnsClient {
+    client: Client,
+    region: Region,
+}
+
+impl SnsClient {
+    /// Create a client dispatching its requests with the given dispatcher and credentials.
+    pub fn new_with<P, D>(request_dispatcher: D, credentials_provider: P, region: Region) -> Self
+    where
+        P: ProvideAwsCredentials + Send + Sync + 'static,
+        D: DispatchSignedRequest + Send + Sync + 'static,
+    {
+        SnsClient {
+            client: Client::new_with(credentials_provider, request_dispatcher),
+            region,
+        }
+    }
+
+    async fn request(&self, action: &str, mut params: Params) -> Result<XmlElement, Error> {
+        params.insert("Action".to_owned(), Some(action.to_owned()));
+        params.insert("Version".to_owned(), Some(API_VERSION.to_owned()));
+        // Messages can be larger than a URL may be long, which is why the parameters are sent as
+        // the body of the request.
+        let mut request = SignedRequest::new("POST", "sns", &self.region, "/");
+        request
+            .set_payload(Some(serde_urlencoded::to_string(&params).map_err(
+                |error| Error::AwsError(failure::err_msg(error.to_string())),
+            )?));
+        request.set_content_type("application/x-www-form-urlencoded".to_owned());
+
+        let mut response = self
+            .client
+            .sign_and_dispatch(request)
+            .await
+            .map_err(RusotoError::<Infallible>::from)?;
+        let response = response
+            .buffer()
+            .await
+            .map_err(RusotoError::<Infallible>::from)?;
+        if !response.status.is_success() {
+            return Err(RusotoError::<Infallible>::Unknown(response).into());
+        }
+        XmlElement::parse(&response.body)
+    }
+
+    /// Publish a message to the topic, returning the ID SNS assigned to the message.
+    ///
+    /// The attributes are attached to the message as string attributes, which subscribers can
+    /// filter messages by.
+    pub async fn publish(
+        &self,
+        topic_arn: &str,
+        message: &str,
+        attributes: &[(&str, &str)],
+    ) -> Result<String, Error> {
+        let mut params = Params::new();
+        params.insert("TopicArn".to_owned(), Some(topic_arn.to_owned()));
+        params.insert("Message".to_owned(), Some(message.to_owned()));
+        for (index, (name, value)) in attributes.iter().enumerate() {
+            let prefix = format!("MessageAttributes.entry.{}", index + 1);
+            params.insert(format!("{}.Name", prefix), Some((*name).to_owned()));
+            params.insert(
+                format!("{}.Value.DataType", prefix),
+                Some("String".to_owned()),
+            );
+            params.insert(
+                format!("{}.Value.StringValue", prefix),
+                Some((*value).to_owned()),
+            );
+        }
+
+        let response = self.request("Publish", params).await?;
+        Ok(response
+            .child("PublishResult")
+            .and_then(|result| result.child_text("MessageId"))
+            .unwrap_or_default()
+            .to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockDispatcher;
+    use rusoto_core::credential::StaticProvider;
+
+    fn client(dispatcher: &MockDispatcher) -> SnsClient {
+        SnsClient::new_with(
+            dispatcher.clone(),
+            StaticProvider::new_minimal("access-key-id".to_owned(), "secret-access-key".to_owned()),
+            Region::EuCentral1,
+        )
+    }
+
+    #[tokio::test]
+    async fn publish_sends_message_with_attributes() {
+        let dispatcher = MockDispatcher::new(vec![
+            "<PublishResponse xmlns=\"https://sns.amazonaws.com/doc/2010-03-31/\">\
+             <PublishResult><MessageId>message-id</MessageId></PublishResult>\
+             <ResponseMetadata><RequestId>request-id</RequestId></ResponseMetadata>\
+             </PublishResponse>",
+        ]);
+
+        let message_id = client(&dispatcher)
+            .publish(
+                "arn:aws:sns:eu-central-1:123456789012:deployments",
+                "Stack app & co updated",
+                &[("event", "deployment_finished")],
+            )
+            .await
+            .unwrap();
+        assert_eq!("message-id", message_id);
+        assert_eq!(vec!["Publish"], dispatcher.actions());
+
+        let mut params =
+            serde_urlencoded::from_str::<Vec<(String, String)>>(&dispatcher.payloads()[0]).unwrap();
+        params.sort();
+        assert_eq!(
+            vec![
+                ("Action", "Publish"),
+                ("Message", "Stack app & co updated"),
+                ("MessageAttributes.entry.1.Name", "event"),
+                ("MessageAttributes.entry.1.Value.DataType", "String"),
+                (
+                    "MessageAttributes.entry.1.Value.StringValue",
+                    "deployment_finished"
+                ),
+                (
+                    "TopicArn",
+                    "arn:aws:sns:eu-central-1:123456789012:deployments"
+                ),
+                ("Version", "2010-03-31"),
+            ],
+            params
+                .iter()
+                .map(|(key, value)| (key.as_str(), value.as_str()))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn publish_fails_with_error_of_sns() {
+        let dispatcher = MockDispatcher::with_status(vec![(
+            404,
+            "<ErrorResponse><Error><Type>Sender</Type><Code>NotFound</Code>\
+             <Message>Topic does not exist</Message></Error>\
+             <RequestId>request-id</RequestId></ErrorResponse>",
+        )]);
+
+        let error = client(&dispatcher)
+            .publish("arn:aws:sns:eu-central-1:123456789012:missing", "", &[])
+            .await
+            .unwrap_err();
+        assert_eq!(Some("NotFound"), error.aws_error_code());
+        assert!(error.is_not_found());
+    }
+}
@@ -16,30 +16,151 @@
 
 //! This module enables handling of CloudFormation stacks.
 
-use rusoto_cloudformation::{CloudFormation, CreateChangeSetInput, CreateChangeSetOutput};
+use chrono::{DateTime, Utc};
+use futures::stream::{self, Stream, TryStreamExt};
+use indexmap::IndexMap;
+use rusoto_cloudformation::{
+    ChangeSetSummary, CloudFormation, CreateChangeSetInput, CreateChangeSetOutput,
+    DeleteStackInput, DescribeStackEventsInput, GetTemplateInput, ListChangeSetsInput,
+    ListStackResourcesInput, ListStacksInput, StackEvent, StackSummary,
+};
+use rusoto_core::RusotoError;
+use std::{sync::RwLock, time::Duration};
+
+use serde::Serialize;
 
 use crate::{
     error::Error,
     parameter::{Parameter, Parameters},
+    template::Template,
 };
 
-/// Represents a CloudFormation stack.
+/// Interval in which the status of a stack is polled while waiting.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Identifies the point up to which stack events should be retrieved.
+///
+/// Stack events are returned by AWS newest first, so retrieving events "since" a point means that
+/// pagination stops as soon as the given point is reached. The point itself is not included.
 #[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StackEventsSince {
+    /// Only return events that happened after the given timestamp.
+    Timestamp(DateTime<Utc>),
+    /// Only return events that happened after the event with the given ID.
+    EventId(String),
+}
+
+impl StackEventsSince {
+    /// Check whether the event is at or before the point identified.
+    fn is_reached_by(&self, event: &StackEvent) -> bool {
+        match self {
+            StackEventsSince::Timestamp(timestamp) => {
+                DateTime::parse_from_rfc3339(&event.timestamp)
+                    .map(|event_timestamp| event_timestamp <= *timestamp)
+                    .unwrap_or(false)
+            }
+            StackEventsSince::EventId(event_id) => &event.event_id == event_id,
+        }
+    }
+}
+
+/// An output of a CloudFormation stack.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StackOutput {
+    /// Key of the output.
+    pub key: String,
+    /// Value of the output.
+    pub value: Option<String>,
+    /// Description of the output, as defined in the template.
+    pub description: Option<String>,
+    /// Name of the export, if the output is exported.
+    pub export_name: Option<String>,
+}
+
+/// A resource of a CloudFormation stack.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StackResource {
+    /// Logical ID of the resource, as defined in the template.
+    pub logical_id: String,
+    /// Physical ID of the resource, if it has been created.
+    pub physical_id: Option<String>,
+    /// Type of the resource, e.g. `AWS::EC2::Instance`.
+    pub resource_type: String,
+    /// Current status of the resource, e.g. `CREATE_COMPLETE`.
+    pub status: String,
+}
+
+/// Represents a CloudFormation stack.
+///
+/// The description of the stack is remembered and reused by all methods that depend on it, e.g.
+/// [`get_parameters`] and [`get_outputs`], so that a command describes a stack only once. Only
+/// [`status`] always retrieves the current description, since it is used to poll for changes, and
+/// the description it retrieves replaces the remembered one. After waiting for a stack operation
+/// through the same `Stack`, these methods therefore return the state after the operation.
+///
+/// [`get_parameters`]: #method.get_parameters
+/// [`get_outputs`]: #method.get_outputs
+/// [`status`]: #method.status
+#[derive(Debug)]
 pub struct Stack {
     name: String,
+    description: RwLock<Option<rusoto_cloudformation::Stack>>,
 }
 
+impl Clone for Stack {
+    fn clone(&self) -> Self {
+        Stack {
+            name: self.name.clone(),
+            description: RwLock::new(self.cached_description()),
+        }
+    }
+}
+
+impl PartialEq for Stack {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl Eq for Stack {}
+
 impl Stack {
     /// Create a new representation of a CloudFormation stack.
     pub fn new<S: AsRef<str>>(name: S) -> Stack {
         Stack {
             name: name.as_ref().to_owned(),
+            description: RwLock::new(None),
+        }
+    }
+
+    /// Return the name of the stack.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Check whether the stack exists.
+    ///
+    /// In contrast to the other methods, a stack that does not exist is not considered an error,
+    /// whereas any other error retrieving the stack is.
+    pub async fn exists(&self, cfn: &dyn CloudFormation) -> Result<bool, Error> {
+        Ok(self.status(cfn).await?.is_some())
+    }
+
+    /// Get the current status of the stack, e.g. `UPDATE_COMPLETE`.
+    ///
+    /// Returns `None` if the stack does not exist.
+    pub async fn status(&self, cfn: &dyn CloudFormation) -> Result<Option<String>, Error> {
+        match self.describe_uncached(cfn).await {
+            Ok(stack) => Ok(Some(stack.stack_status)),
+            Err(Error::InvalidStack(_)) => Ok(None),
+            Err(error) => Err(error),
         }
     }
 
     /// Get the value of a single parameter of the stack.
     ///
-    /// *Note:* internally this retrieves all parameters defined on the stack.
+    /// *Note:* internally this retrieves all parameters defined on the stack, unless they have been
+    /// retrieved before.
     pub async fn get_parameter(
         &self,
         cfn: &dyn CloudFormation,
@@ -53,16 +174,7 @@ impl Stack {
     /// This retrieves all parameters defined on the AWS CloudFormation stack, including their
     /// current values.
     pub async fn get_parameters(&self, cfn: &dyn CloudFormation) -> Result<Parameters, Error> {
-        let response = cfn
-            .describe_stacks(rusoto_cloudformation::DescribeStacksInput {
-                stack_name: Some(self.name.clone()),
-                ..Default::default()
-            })
-            .await?;
-        let stack = response
-            .stacks
-            .and_then(|stacks| stacks.first().cloned())
-            .ok_or_else(|| Error::InvalidStack(self.name.clone()))?;
+        let stack = self.describe(cfn).await?;
 
         Ok(stack
             .parameters
@@ -77,6 +189,120 @@ impl Stack {
             .into())
     }
 
+    /// Get the resolved values of the parameters of the stack, keyed by the parameter key.
+    ///
+    /// Only parameters whose value is resolved by CloudFormation have a resolved value, e.g.
+    /// parameters of the type `AWS::SSM::Parameter::Value<String>`.
+    pub async fn get_resolved_values(
+        &self,
+        cfn: &dyn CloudFormation,
+    ) -> Result<IndexMap<String, String>, Error> {
+        let stack = self.describe(cfn).await?;
+
+        Ok(stack
+            .parameters
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|parameter| Some((parameter.parameter_key?, parameter.resolved_value?)))
+            .collect())
+    }
+
+    /// Get the current outputs of the stack.
+    ///
+    /// The outputs are keyed by their output key, retaining the order in which AWS returned them.
+    pub async fn get_outputs(
+        &self,
+        cfn: &dyn CloudFormation,
+    ) -> Result<IndexMap<String, StackOutput>, Error> {
+        let stack = self.describe(cfn).await?;
+
+        Ok(stack
+            .outputs
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|output| {
+                let key = output.output_key?;
+                Some((
+                    key.clone(),
+                    StackOutput {
+                        key,
+                        value: output.output_value,
+                        description: output.description,
+                        export_name: output.export_name,
+                    },
+                ))
+            })
+            .collect())
+    }
+
+    /// Get the tags of the stack.
+    pub async fn get_tags(
+        &self,
+        cfn: &dyn CloudFormation,
+    ) -> Result<IndexMap<String, String>, Error> {
+        let stack = self.describe(cfn).await?;
+
+        Ok(stack
+            .tags
+            .unwrap_or_default()
+            .into_iter()
+            .map(|tag| (tag.key, tag.value))
+            .collect())
+    }
+
+    /// Get all resources of the stack.
+    ///
+    /// This retrieves the resources through `ListStackResources`, handling pagination internally.
+    pub async fn get_resources(
+        &self,
+        cfn: &dyn CloudFormation,
+    ) -> Result<Vec<StackResource>, Error> {
+        let mut resources = Vec::new();
+        let mut continuation_token: Option<String> = None;
+        while {
+            let output = cfn
+                .list_stack_resources(ListStackResourcesInput {
+                    stack_name: self.name.clone(),
+                    next_token: continuation_token.clone(),
+                })
+                .await?;
+            continuation_token = output.next_token;
+            resources.extend(
+                output
+                    .stack_resource_summaries
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|summary| StackResource {
+                        logical_id: summary.logical_resource_id,
+                        physical_id: summary.physical_resource_id,
+                        resource_type: summary.resource_type,
+                        status: summary.resource_status,
+                    }),
+            );
+
+            continuation_token.is_some()
+        } {}
+
+        Ok(resources)
+    }
+
+    /// Get the template currently deployed on the stack.
+    ///
+    /// This retrieves the original template, i.e. before any transforms have been processed.
+    pub async fn get_template(&self, cfn: &dyn CloudFormation) -> Result<Template, Error> {
+        let output = cfn
+            .get_template(GetTemplateInput {
+                stack_name: Some(self.name.clone()),
+                template_stage: Some("Original".to_owned()),
+                ..Default::default()
+            })
+            .await?;
+        output
+            .template_body
+            .ok_or_else(|| Error::InvalidStack(self.name.clone()))
+            .and_then(Template::from_bytes)
+    }
+
     /// Get the current parameters for the stack, as previous values.
     ///
     /// This retrieves all parameters defined on the AWS CloudFormation stack and turns them into
@@ -95,6 +321,196 @@ impl Stack {
             .into())
     }
 
+    /// Delete the stack.
+    ///
+    /// This method will not wait for the deletion to complete, use [`wait_until_deleted`] for that.
+    ///
+    /// [`wait_until_deleted`]: #method.wait_until_deleted
+    pub async fn delete(
+        &self,
+        cfn: &dyn CloudFormation,
+        role_arn: Option<&str>,
+    ) -> Result<(), Error> {
+        cfn.delete_stack(DeleteStackInput {
+            stack_name: self.name.clone(),
+            role_arn: role_arn.map(ToOwned::to_owned),
+            ..Default::default()
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// Wait until the deletion of the stack has completed.
+    ///
+    /// Returns an [`Error::StackOperationFailed`] if the deletion failed.
+    ///
+    /// [`Error::StackOperationFailed`]: ../error/enum.Error.html#variant.StackOperationFailed
+    pub async fn wait_until_deleted(&self, cfn: &dyn CloudFormation) -> Result<(), Error> {
+        loop {
+            match self.status(cfn).await? {
+                None => return Ok(()),
+                Some(status) if status == "DELETE_COMPLETE" => return Ok(()),
+                Some(status) if status.ends_with("_IN_PROGRESS") => {
+                    tokio::time::sleep(POLL_INTERVAL).await
+                }
+                Some(status) => return Err(Error::StackOperationFailed(status)),
+            }
+        }
+    }
+
+    /// Return the description of the stack retrieved last, if any.
+    fn cached_description(&self) -> Option<rusoto_cloudformation::Stack> {
+        self.description
+            .read()
+            .expect("stack description lock poisoned")
+            .clone()
+    }
+
+    /// Get the description of the stack, retrieving it only if it has not been retrieved before.
+    async fn describe(
+        &self,
+        cfn: &dyn CloudFormation,
+    ) -> Result<rusoto_cloudformation::Stack, Error> {
+        match self.cached_description() {
+            Some(description) => Ok(description),
+            None => self.describe_uncached(cfn).await,
+        }
+    }
+
+    /// Retrieve the current description of the stack, replacing the one remembered before.
+    async fn describe_uncached(
+        &self,
+        cfn: &dyn CloudFormation,
+    ) -> Result<rusoto_cloudformation::Stack, Error> {
+        let response = cfn
+            .describe_stacks(rusoto_cloudformation::DescribeStacksInput {
+                stack_name: Some(self.name.clone()),
+                ..Default::default()
+            })
+            .await
+            .map_err(|error| match error {
+                // CloudFormation does not model a "stack not found" error, but instead returns a
+                // generic validation error, which Rusoto can only provide as an unknown error.
+                RusotoError::Unknown(ref response)
+                    if response.status.as_u16() == 400
+                        && String::from_utf8_lossy(&response.body).contains("does not exist") =>
+                {
+                    Error::InvalidStack(self.name.clone())
+                }
+                error => error.into(),
+            })?;
+        let description = response
+            .stacks
+            .and_then(|stacks| stacks.into_iter().next())
+            .ok_or_else(|| Error::InvalidStack(self.name.clone()))?;
+        *self
+            .description
+            .write()
+            .expect("stack description lock poisoned") = Some(description.clone());
+        Ok(description)
+    }
+
+    /// List all stacks that have not been deleted.
+    pub async fn list(cfn: &dyn CloudFormation) -> Result<Vec<StackSummary>, Error> {
+        let mut stacks = Vec::new();
+        let mut continuation_token: Option<String> = None;
+        while {
+            let output = cfn
+                .list_stacks(ListStacksInput {
+                    next_token: continuation_token.clone(),
+                    ..Default::default()
+                })
+                .await?;
+            continuation_token = output.next_token;
+            stacks.extend(
+                output
+                    .stack_summaries
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|stack| stack.stack_status != "DELETE_COMPLETE"),
+            );
+
+            continuation_token.is_some()
+        } {}
+
+        Ok(stacks)
+    }
+
+    /// List all change sets of the stack that have not been executed or deleted yet.
+    pub async fn list_change_sets(
+        &self,
+        cfn: &dyn CloudFormation,
+    ) -> Result<Vec<ChangeSetSummary>, Error> {
+        let mut change_sets = Vec::new();
+        let mut continuation_token: Option<String> = None;
+        while {
+            let output = cfn
+                .list_change_sets(ListChangeSetsInput {
+                    stack_name: self.name.clone(),
+                    next_token: continuation_token.clone(),
+                })
+                .await?;
+            continuation_token = output.next_token;
+            change_sets.extend(output.summaries.unwrap_or_default());
+
+            continuation_token.is_some()
+        } {}
+
+        Ok(change_sets)
+    }
+
+    /// Retrieve the events of the stack, newest first.
+    ///
+    /// The events are returned as a stream, where pagination is handled internally: further pages
+    /// are only requested once the events of the previous page have been consumed. If `since` is
+    /// provided, the stream ends once the identified point has been reached.
+    pub fn events<'a>(
+        &'a self,
+        cfn: &'a dyn CloudFormation,
+        since: Option<StackEventsSince>,
+    ) -> impl Stream<Item = Result<StackEvent, Error>> + 'a {
+        // The state tracks the token for the next page. An outer `None` signals that there are no
+        // further pages to retrieve.
+        stream::try_unfold(Some(None), move |state: Option<Option<String>>| {
+            let since = since.clone();
+            async move {
+                let next_token = match state {
+                    Some(next_token) => next_token,
+                    None => return Ok::<_, Error>(None),
+                };
+                let output = cfn
+                    .describe_stack_events(DescribeStackEventsInput {
+                        stack_name: Some(self.name.clone()),
+                        next_token,
+                    })
+                    .await?;
+
+                let mut since_reached = false;
+                let events = output
+                    .stack_events
+                    .unwrap_or_default()
+                    .into_iter()
+                    .take_while(|event| match &since {
+                        Some(since) if since.is_reached_by(event) => {
+                            since_reached = true;
+                            false
+                        }
+                        _ => true,
+                    })
+                    .map(Ok)
+                    .collect::<Vec<Result<StackEvent, Error>>>();
+
+                let next_state = if since_reached {
+                    None
+                } else {
+                    output.next_token.map(Some)
+                };
+                Ok(Some((stream::iter(events), next_state)))
+            }
+        })
+        .try_flatten()
+    }
+
     /// Create a change set for the current stack with the provided parameters.
     ///
     /// # Notes
@@ -107,17 +523,25 @@ impl Stack {
     ///
     ///   Waiting for the change set should be performed externally through the AWS CLI, using the
     ///   `aws cloudformation wait change-set-create-complete` command.
+    ///
+    /// * If `resource_types` is provided, the change set can only contain resources of types
+    ///   matched by the list, e.g. `AWS::EC2::*`, otherwise its creation fails.
+    ///
+    /// * The `description` is shown alongside the change set, e.g. in the console.
     pub async fn create_change_set(
         &self,
         cfn: &dyn CloudFormation,
         name: &str,
         role_arn: Option<&str>,
+        resource_types: Option<&[String]>,
+        description: Option<&str>,
         parameters: &Parameters,
     ) -> Result<CreateChangeSetOutput, Error> {
         cfn.create_change_set(CreateChangeSetInput {
             stack_name: self.name.clone(),
             use_previous_template: Some(true),
             change_set_name: name.to_owned(),
+            description: description.map(ToOwned::to_owned),
             capabilities: Some(vec![
                 "CAPABILITY_IAM".to_owned(),
                 "CAPABILITY_NAMED_IAM".to_owned(),
@@ -125,6 +549,7 @@ impl Stack {
             ]),
             change_set_type: Some("UPDATE".to_owned()),
             role_arn: role_arn.map(ToOwned::to_owned),
+            resource_types: resource_types.map(ToOwned::to_owned),
             parameters: Some(parameters.into()),
             ..Default::default()
         })
@@ -132,3 +557,71 @@ impl Stack {
         .map_err(Into::into)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{self, MockDispatcher};
+
+    #[test]
+    fn stack_events_since_is_reached_by() {
+        let event = StackEvent {
+            event_id: "event-2".to_owned(),
+            timestamp: "2025-01-01T12:00:00.000Z".to_owned(),
+            ..Default::default()
+        };
+
+        let before = "2025-01-01T11:59:59Z".parse::<DateTime<Utc>>().unwrap();
+        let exact = "2025-01-01T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        assert!(!StackEventsSince::Timestamp(before).is_reached_by(&event));
+        assert!(StackEventsSince::Timestamp(exact).is_reached_by(&event));
+
+        assert!(StackEventsSince::EventId("event-2".to_owned()).is_reached_by(&event));
+        assert!(!StackEventsSince::EventId("event-1".to_owned()).is_reached_by(&event));
+    }
+
+    fn describe_stacks_response(status: &str, url: &str) -> String {
+        format!(
+            "<DescribeStacksResponse xmlns=\"http://cloudformation.amazonaws.com/doc/2010-05-15/\">\
+             <DescribeStacksResult><Stacks><member>\
+             <StackName>app</StackName>\
+             <CreationTime>2025-01-01T12:00:00Z</CreationTime>\
+             <StackStatus>{}</StackStatus>\
+             <Outputs><member><OutputKey>Url</OutputKey><OutputValue>{}</OutputValue></member>\
+             </Outputs>\
+             </member></Stacks></DescribeStacksResult>\
+             <ResponseMetadata><RequestId>request-id</RequestId></ResponseMetadata>\
+             </DescribeStacksResponse>",
+            status, url
+        )
+    }
+
+    #[tokio::test]
+    async fn outputs_reflect_latest_status_poll() {
+        let dispatcher = MockDispatcher::new(vec![
+            describe_stacks_response("UPDATE_IN_PROGRESS", "https://blue.example.com"),
+            describe_stacks_response("UPDATE_COMPLETE", "https://green.example.com"),
+        ]);
+        let cfn = mock::cloudformation(&dispatcher);
+        let stack = Stack::new("app");
+
+        assert_eq!(
+            Some("UPDATE_IN_PROGRESS".to_owned()),
+            stack.status(&cfn).await.unwrap()
+        );
+        assert_eq!(
+            Some("UPDATE_COMPLETE".to_owned()),
+            stack.status(&cfn).await.unwrap()
+        );
+        let outputs = stack.get_outputs(&cfn).await.unwrap();
+        assert_eq!(
+            Some("https://green.example.com"),
+            outputs[0].value.as_deref()
+        );
+        // The outputs were taken from the last poll instead of describing the stack again.
+        assert_eq!(
+            vec!["DescribeStacks", "DescribeStacks"],
+            dispatcher.actions()
+        );
+    }
+}
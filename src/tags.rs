@@ -0,0 +1,322 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Filtering of AWS resources by the tags assigned to them.
+
+use itertools::Itertools;
+use regex::Regex;
+use std::str::FromStr;
+
+/// A tag, i.e. a key-value pair assigned to an AWS resource, that resources are filtered by.
+///
+/// The value can be given as exact value (`Key=Value`), as wildcard pattern where `*` matches any
+/// characters (`Key=Prefix*`), or as regex (`Key~=Regex`). If only the key is given (`Key`), any
+/// value matches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tag {
+    /// Key of the tag.
+    pub key: String,
+    /// Value of the tag.
+    pub value: TagValue,
+}
+
+impl FromStr for Tag {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (key, value) = match s.split_once('=') {
+            Some((key, value)) => (key, value),
+            None => {
+                return Ok(Tag {
+                    key: s.to_owned(),
+                    value: TagValue::Any,
+                })
+            }
+        };
+
+        let (key, value) = match key.strip_suffix('~') {
+            Some(key) => (
+                key,
+                Regex::new(value)
+                    .map(TagValue::Regex)
+                    .map_err(|error| format!("Invalid regex for tag {}: {}", key, error))?,
+            ),
+            None if value.contains('*') => {
+                let pattern = value.split('*').map(regex::escape).join(".*");
+                (
+                    key,
+                    TagValue::Wildcard(
+                        Regex::new(&format!("^{}$", pattern)).expect("escaped pattern is valid"),
+                    ),
+                )
+            }
+            None => (key, TagValue::Exact(value.to_owned())),
+        };
+        Ok(Tag {
+            key: key.to_owned(),
+            value,
+        })
+    }
+}
+
+/// The value a tag has to have to match.
+#[derive(Debug, Clone)]
+pub enum TagValue {
+    /// Any value matches, i.e. the tag only has to be assigned.
+    Any,
+    /// The value has to equal the given value.
+    Exact(String),
+    /// The value has to match the wildcard pattern, which is compiled into an anchored regex.
+    Wildcard(Regex),
+    /// The value has to match the regex, anywhere unless anchored explicitly.
+    Regex(Regex),
+}
+
+impl TagValue {
+    /// Check whether the value matches.
+    pub fn matches(&self, value: &str) -> bool {
+        match self {
+            TagValue::Any => true,
+            TagValue::Exact(expected) => expected == value,
+            TagValue::Wildcard(regex) | TagValue::Regex(regex) => regex.is_match(value),
+        }
+    }
+}
+
+impl PartialEq for TagValue {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (TagValue::Any, TagValue::Any) => true,
+            (TagValue::Exact(left), TagValue::Exact(right)) => left == right,
+            (TagValue::Wildcard(left), TagValue::Wildcard(right))
+            | (TagValue::Regex(left), TagValue::Regex(right)) => left.as_str() == right.as_str(),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for TagValue {}
+
+/// A tag to assign to a resource, specified as `Key=Value`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagSpecification {
+    /// Key of the tag.
+    pub key: String,
+    /// Value of the tag.
+    pub value: String,
+}
+
+impl FromStr for TagSpecification {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (key, value) = s
+            .split_once('=')
+            .ok_or_else(|| "Tag needs to be provided in the form `Key=Value`".to_owned())?;
+        Ok(TagSpecification {
+            key: key.to_owned(),
+            value: value.to_owned(),
+        })
+    }
+}
+
+impl From<&TagSpecification> for rusoto_rds::Tag {
+    fn from(tag: &TagSpecification) -> Self {
+        rusoto_rds::Tag {
+            key: Some(tag.key.clone()),
+            value: Some(tag.value.clone()),
+        }
+    }
+}
+
+impl From<&TagSpecification> for rusoto_sts::Tag {
+    fn from(tag: &TagSpecification) -> Self {
+        rusoto_sts::Tag {
+            key: tag.key.clone(),
+            value: tag.value.clone(),
+        }
+    }
+}
+
+/// A tag as it is returned by the API of an AWS service.
+///
+/// Every service models tags slightly differently, this trait provides uniform access to them.
+pub trait ResourceTag {
+    /// Key of the tag, if any.
+    fn key(&self) -> Option<&str>;
+    /// Value of the tag, if any.
+    fn value(&self) -> Option<&str>;
+}
+
+impl ResourceTag for rusoto_autoscaling::TagDescription {
+    fn key(&self) -> Option<&str> {
+        self.key.as_deref()
+    }
+
+    fn value(&self) -> Option<&str> {
+        self.value.as_deref()
+    }
+}
+
+impl ResourceTag for rusoto_cloudfront::Tag {
+    fn key(&self) -> Option<&str> {
+        Some(&self.key)
+    }
+
+    fn value(&self) -> Option<&str> {
+        self.value.as_deref()
+    }
+}
+
+impl ResourceTag for rusoto_elbv2::Tag {
+    fn key(&self) -> Option<&str> {
+        Some(&self.key)
+    }
+
+    fn value(&self) -> Option<&str> {
+        self.value.as_deref()
+    }
+}
+
+impl ResourceTag for rusoto_rds::Tag {
+    fn key(&self) -> Option<&str> {
+        self.key.as_deref()
+    }
+
+    fn value(&self) -> Option<&str> {
+        self.value.as_deref()
+    }
+}
+
+/// A filter matching resources that have all of the given tags assigned, but none of the
+/// excluded tags.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TagFilter {
+    tags: Vec<Tag>,
+    excluded_tags: Vec<Tag>,
+}
+
+impl TagFilter {
+    /// Create a filter requiring all of the given tags.
+    pub fn new(tags: Vec<Tag>) -> TagFilter {
+        TagFilter {
+            tags,
+            excluded_tags: Vec::new(),
+        }
+    }
+
+    /// Exclude all resources that have any of the given tags assigned.
+    pub fn excluding(mut self, excluded_tags: Vec<Tag>) -> TagFilter {
+        self.excluded_tags = excluded_tags;
+        self
+    }
+
+    /// Check whether the filter matches any resource, regardless of its tags.
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty() && self.excluded_tags.is_empty()
+    }
+
+    /// Check whether the resource with the given tags matches the filter.
+    pub fn matches<T: ResourceTag>(&self, resource_tags: &[T]) -> bool {
+        let is_assigned = |needle: &Tag| {
+            resource_tags.iter().any(|haystack| {
+                haystack.key() == Some(needle.key.as_str())
+                    && needle.value.matches(haystack.value().unwrap_or_default())
+            })
+        };
+        self.tags.iter().all(is_assigned) && !self.excluded_tags.iter().any(is_assigned)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resource_tag(key: &str, value: &str) -> rusoto_rds::Tag {
+        rusoto_rds::Tag {
+            key: Some(key.to_owned()),
+            value: Some(value.to_owned()),
+        }
+    }
+
+    #[test]
+    fn tag_from_str() {
+        assert_eq!(
+            Ok(Tag {
+                key: "Environment".to_owned(),
+                value: TagValue::Exact("a=b".to_owned()),
+            }),
+            "Environment=a=b".parse(),
+        );
+        assert_eq!(
+            Ok(Tag {
+                key: "Environment".to_owned(),
+                value: TagValue::Any,
+            }),
+            "Environment".parse(),
+        );
+        assert!("Version~=(".parse::<Tag>().is_err());
+    }
+
+    #[test]
+    fn tag_value_patterns() {
+        let wildcard = "Version=1.4.*".parse::<Tag>().unwrap().value;
+        assert!(wildcard.matches("1.4.0"));
+        assert!(wildcard.matches("1.4."));
+        assert!(!wildcard.matches("1.40"));
+        assert!(!wildcard.matches("v1.4.0"));
+
+        let regex = "Version~=^1\\.[45]\\.".parse::<Tag>().unwrap();
+        assert_eq!("Version", regex.key);
+        assert!(regex.value.matches("1.5.2"));
+        assert!(!regex.value.matches("1.6.0"));
+    }
+
+    #[test]
+    fn tag_filter_requires_all_tags() {
+        let filter = TagFilter::new(vec![
+            "Environment=production".parse().unwrap(),
+            "Team=platform".parse().unwrap(),
+        ]);
+
+        assert!(filter.matches(&[
+            resource_tag("Team", "platform"),
+            resource_tag("Environment", "production"),
+            resource_tag("Name", "web"),
+        ]));
+        assert!(!filter.matches(&[resource_tag("Environment", "production")]));
+        assert!(!filter.matches(&[
+            resource_tag("Environment", "staging"),
+            resource_tag("Team", "platform"),
+        ]));
+        assert!(TagFilter::default().matches::<rusoto_rds::Tag>(&[]));
+    }
+
+    #[test]
+    fn tag_filter_excludes_tags() {
+        let filter = TagFilter::new(vec!["Environment=production".parse().unwrap()])
+            .excluding(vec!["DeletionMarker".parse().unwrap()]);
+
+        assert!(filter.matches(&[resource_tag("Environment", "production")]));
+        assert!(!filter.matches(&[
+            resource_tag("Environment", "production"),
+            resource_tag("DeletionMarker", ""),
+        ]));
+        assert!(TagFilter::default()
+            .excluding(vec!["Status=deleting".parse().unwrap()])
+            .matches(&[resource_tag("Status", "available")]));
+    }
+}
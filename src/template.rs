@@ -16,22 +16,39 @@
 
 //! This module enables type-safe handling of CloudFormation templates.
 
+use indexmap::IndexMap;
 use md5::{Digest, Md5};
-use rusoto_cloudformation::{CloudFormation, CreateChangeSetInput, CreateChangeSetOutput};
-use serde::Deserialize;
-use std::collections::HashMap;
+use rusoto_cloudformation::{
+    CloudFormation, CreateChangeSetInput, CreateChangeSetOutput, ResourceToImport,
+};
+use serde::{de, Deserialize, Serialize};
+use serde_yaml::Value;
+use sha2::Sha256;
+use std::collections::{BTreeSet, HashMap};
 use std::fs::File;
 use std::io::{self, prelude::*, BufReader};
+use std::str::FromStr;
 
-use crate::{error::Error, parameter::*, s3::S3Uploader};
+use crate::{
+    error::Error,
+    parameter::*,
+    s3::{ChecksumAlgorithm, S3Uploader},
+};
 
-/// Represents a CloudFormation template, based on some source file.
+/// The maximum size in bytes of a template submitted inline, i.e. not uploaded to S3, as enforced
+/// by the AWS API.
+pub const MAX_TEMPLATE_BODY_SIZE: usize = 51_200;
+
+/// Represents a CloudFormation template.
 ///
-/// It holds the loaded file contents as well as the parsed template parameters.
+/// It holds the template contents as well as the parsed template parameters. If the template was
+/// loaded from a file, the filename is retained as metadata.
 pub struct Template {
-    filename: String,
+    filename: Option<String>,
     contents: Vec<u8>,
     parameters: Parameters,
+    parameter_declarations: IndexMap<String, TemplateParameter>,
+    minify: bool,
 }
 
 impl Template {
@@ -40,33 +57,69 @@ impl Template {
     /// **Note:** this will load the template into memory.
     pub fn new<S: AsRef<str>>(filename: S) -> Result<Template, Error> {
         let contents = load_file(filename.as_ref())?;
-        let parameters = serde_yaml::from_slice::<CloudFormationTemplate>(&contents)
-            .map_err(|error| Error::TemplateDeserializationFailed(error.into()))?
-            .parameters
+        let mut template = Template::from_bytes(contents)?;
+        template.filename = Some(filename.as_ref().to_owned());
+        Ok(template)
+    }
+
+    /// Creates a template from its contents held in memory.
+    ///
+    /// This performs the same parameter extraction as [`new`], but does not require the template
+    /// to be available as a file.
+    ///
+    /// ```
+    /// # use awsx::template::Template;
+    /// let template = Template::from_bytes(
+    ///     "Parameters:\n  MyParameter:\n    Type: String\nResources: {}\n",
+    /// )
+    /// .unwrap();
+    /// assert!(template.filename().is_none());
+    /// assert!(template.get_parameters().contains_key("MyParameter"));
+    /// ```
+    ///
+    /// [`new`]: #method.new
+    pub fn from_bytes<B: Into<Vec<u8>>>(contents: B) -> Result<Template, Error> {
+        let contents = contents.into();
+        let mut parameter_declarations =
+            serde_yaml::from_slice::<CloudFormationTemplate>(&contents)
+                .map_err(|error| Error::TemplateDeserializationFailed(error.into()))?
+                .parameters
+                .into_iter()
+                .collect::<Vec<_>>();
+        parameter_declarations.sort_by(|(left, _), (right, _)| left.cmp(right));
+        let parameter_declarations = parameter_declarations
             .into_iter()
+            .collect::<IndexMap<_, _>>();
+
+        let parameters = parameter_declarations
+            .iter()
             .map(|(name, parameter)| {
-                if let Some(default) = parameter.default {
+                if let Some(default) = &parameter.default {
                     Parameter::WithValue {
-                        key: name,
-                        value: default,
+                        key: name.to_owned(),
+                        value: default.to_owned(),
                     }
                 } else {
-                    Parameter::PreviousValue { key: name }
+                    Parameter::PreviousValue {
+                        key: name.to_owned(),
+                    }
                 }
             })
             .collect::<Vec<_>>()
             .into();
 
         Ok(Template {
-            filename: filename.as_ref().to_owned(),
+            filename: None,
+            minify: false,
             contents,
             parameters,
+            parameter_declarations,
         })
     }
 
-    /// Return the path to the file loaded.
-    pub fn filename(&self) -> &str {
-        &self.filename
+    /// Return the path to the file loaded, if the template was loaded from a file.
+    pub fn filename(&self) -> Option<&str> {
+        self.filename.as_deref()
     }
 
     /// Return the contents of the loaded template.
@@ -82,11 +135,35 @@ impl Template {
         Ok(format!("{:x}", contents_md5.finalize()))
     }
 
+    /// Generate and return a SHA-256 checksum of the file contents.
+    pub fn checksum_sha256hex(&self) -> Result<String, Error> {
+        let mut contents_sha256 = Sha256::new();
+        let mut bufreader = BufReader::new(self.contents.as_slice());
+        io::copy(&mut bufreader, &mut contents_sha256)?;
+        Ok(format!("{:x}", contents_sha256.finalize()))
+    }
+
+    /// Generate and return a checksum of the file contents using the given hash algorithm.
+    pub fn checksum_hex(&self, algorithm: ChecksumAlgorithm) -> Result<String, Error> {
+        match algorithm {
+            ChecksumAlgorithm::Md5 => self.checksum_md5hex(),
+            ChecksumAlgorithm::Sha256 => self.checksum_sha256hex(),
+        }
+    }
+
     /// Create the change set input for the loaded template and a given list of parameters.
     ///
-    /// This function will validate that the parameter list matches what the template expects, and
-    /// will return an error if this isn't the case. If the stack doesn't exist but should be
+    /// This function will validate that the parameter list matches what the template expects, i.e.
+    /// that all required parameters are provided and no unknown parameters are provided, and will
+    /// return an error if this isn't the case. Constraints on the values are not validated here,
+    /// use [`Parameters::validate_against`] for that. If the stack doesn't exist but should be
     /// created, set `create_stack` to `true`.
+    ///
+    /// If `resource_types` is provided, CloudFormation will refuse to create the change set if the
+    /// template contains resources of types not matched by the list, e.g. `AWS::EC2::*`. The
+    /// `description` is shown alongside the change set, e.g. in the console.
+    ///
+    /// [`Parameters::validate_against`]: ../parameter/struct.Parameters.html#method.validate_against
     #[allow(clippy::too_many_arguments)]
     pub async fn create_change_set(
         &self,
@@ -95,46 +172,100 @@ impl Template {
         stack_name: &str,
         parameters: &Parameters,
         role_arn: Option<&str>,
+        resource_types: Option<&[String]>,
+        description: Option<&str>,
         s3_upload: Option<(&S3Uploader, &str)>,
         create_stack: bool,
     ) -> Result<CreateChangeSetOutput, Error> {
-        if self.validate_parameters(parameters) {
-            let mut create_change_set_input = CreateChangeSetInput {
-                stack_name: stack_name.to_owned(),
-                change_set_name: name.to_owned(),
-                capabilities: Some(vec![
-                    "CAPABILITY_IAM".to_owned(),
-                    "CAPABILITY_NAMED_IAM".to_owned(),
-                    "CAPABILITY_AUTO_EXPAND".to_owned(),
-                ]),
-                change_set_type: if create_stack {
-                    Some("CREATE".to_owned())
-                } else {
-                    Some("UPDATE".to_owned())
-                },
-                role_arn: role_arn.map(ToOwned::to_owned),
-                parameters: Some(parameters.into()),
-                ..Default::default()
-            };
+        let create_change_set_input = CreateChangeSetInput {
+            stack_name: stack_name.to_owned(),
+            change_set_name: name.to_owned(),
+            description: description.map(ToOwned::to_owned),
+            change_set_type: if create_stack {
+                Some("CREATE".to_owned())
+            } else {
+                Some("UPDATE".to_owned())
+            },
+            role_arn: role_arn.map(ToOwned::to_owned),
+            resource_types: resource_types.map(ToOwned::to_owned),
+            ..Default::default()
+        };
+        self.dispatch_change_set(cfn, create_change_set_input, parameters, s3_upload)
+            .await
+    }
+
+    /// Create a change set importing existing resources into a stack.
+    ///
+    /// The template has to declare the resources to import, including a `DeletionPolicy`, and must
+    /// not contain any other changes compared to the template deployed on the stack. The
+    /// parameters are validated the same way as for [`create_change_set`].
+    ///
+    /// [`create_change_set`]: #method.create_change_set
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create_import_change_set(
+        &self,
+        cfn: &dyn CloudFormation,
+        name: &str,
+        stack_name: &str,
+        parameters: &Parameters,
+        role_arn: Option<&str>,
+        resources_to_import: Vec<ResourceToImport>,
+        description: Option<&str>,
+        s3_upload: Option<(&S3Uploader, &str)>,
+    ) -> Result<CreateChangeSetOutput, Error> {
+        let create_change_set_input = CreateChangeSetInput {
+            stack_name: stack_name.to_owned(),
+            change_set_name: name.to_owned(),
+            description: description.map(ToOwned::to_owned),
+            change_set_type: Some("IMPORT".to_owned()),
+            role_arn: role_arn.map(ToOwned::to_owned),
+            resources_to_import: Some(resources_to_import),
+            ..Default::default()
+        };
+        self.dispatch_change_set(cfn, create_change_set_input, parameters, s3_upload)
+            .await
+    }
+
+    /// Validate the parameters and create the change set, completing the input with the
+    /// parameters, the capabilities and the template itself.
+    async fn dispatch_change_set(
+        &self,
+        cfn: &dyn CloudFormation,
+        mut create_change_set_input: CreateChangeSetInput,
+        parameters: &Parameters,
+        s3_upload: Option<(&S3Uploader, &str)>,
+    ) -> Result<CreateChangeSetOutput, Error> {
+        let report = parameters.validate_against(self);
+        if report.missing.is_empty() && report.unknown.is_empty() {
+            create_change_set_input.capabilities = Some(vec![
+                "CAPABILITY_IAM".to_owned(),
+                "CAPABILITY_NAMED_IAM".to_owned(),
+                "CAPABILITY_AUTO_EXPAND".to_owned(),
+            ]);
+            create_change_set_input.parameters = Some(parameters.into());
 
             // Upload the template if the S3 configuration was provided, use the template as-is
-            // otherwise.
+            // otherwise. The uploaded template records the stack it was uploaded for.
             if let Some((s3_uploader, bucket_name)) = s3_upload {
-                let url = self.upload_to_s3(s3_uploader, bucket_name).await?;
+                let s3_uploader = s3_uploader.clone().with_object_metadata(HashMap::from([(
+                    "stack-name".to_owned(),
+                    create_change_set_input.stack_name.clone(),
+                )]));
+                let url = self.upload_to_s3(&s3_uploader, bucket_name).await?;
                 create_change_set_input.template_url = Some(url);
             } else {
-                create_change_set_input.template_body = Some(
-                    String::from_utf8(self.contents.clone())
-                        .expect("Template is not well formatted UTF8"),
-                );
+                create_change_set_input.template_body = Some(self.template_body()?);
             }
 
             cfn.create_change_set(create_change_set_input)
                 .await
                 .map_err(Into::into)
         } else {
-            Err(Error::InvalidParameters(
-                "the template expected other parameters than were provided".to_owned(),
+            Err(Error::ParameterValidationFailed(
+                ParameterValidationReport {
+                    violations: Vec::new(),
+                    ..report
+                },
             ))
         }
     }
@@ -144,6 +275,13 @@ impl Template {
         &self.parameters
     }
 
+    /// Get the declarations of all parameters of the template, including their constraints.
+    ///
+    /// The declarations are ordered by the parameter name.
+    pub fn get_parameter_declarations(&self) -> &IndexMap<String, TemplateParameter> {
+        &self.parameter_declarations
+    }
+
     /// Get the parameters of type `Parameter::PreviousValue`.
     ///
     /// **Note:** this will recreate the parameter collection.
@@ -159,28 +297,500 @@ impl Template {
     ///
     /// # Deduplication
     ///
-    /// This will deduplicate the uploaded template by hashing the template contents using MD5, and
-    /// using the result as the filename. This means that if a template that duplicates one already
-    /// on S3 is to be uploaded, the filenames will match and the file will be deduplicated
-    /// automatically.
+    /// This will deduplicate the uploaded template by hashing the template contents using the
+    /// [`checksum_algorithm`] of the uploader, MD5 by default, and using the result as the
+    /// filename. This means that if a template that duplicates one already on S3 is to be uploaded,
+    /// the filenames will match and the file will be deduplicated automatically.
     ///
-    /// This behaviour is identical to the AWS CLI, which means the deduplication works across both
-    /// tools.
+    /// With MD5 this behaviour is identical to the AWS CLI, which means the deduplication works
+    /// across both tools.
+    ///
+    /// If an object with the same key and size exists already, the upload is skipped altogether,
+    /// which also keeps the tags and metadata of the existing object.
+    ///
+    /// [`checksum_algorithm`]: ../s3/struct.S3Uploader.html#method.checksum_algorithm
     pub async fn upload_to_s3(&self, s3: &S3Uploader, bucket_name: &str) -> Result<String, Error> {
-        let key = format!("{}.template", self.checksum_md5hex()?);
+        let key = self.s3_key(s3.checksum_algorithm())?;
+        if s3
+            .object_exists(bucket_name, &key, self.contents.len())
+            .await
+        {
+            return Ok(s3.object_url(bucket_name, &key));
+        }
         let url = s3
             .upload(bucket_name, &key, self.contents.clone().into())
             .await?;
         Ok(url)
     }
 
-    fn validate_parameters(&self, parameters: &Parameters) -> bool {
-        let mut keys = self.parameters.keys().collect::<Vec<_>>();
-        keys.sort();
-        let mut other_keys = parameters.keys().collect::<Vec<_>>();
-        other_keys.sort();
+    /// Return the key the template is uploaded to by [`upload_to_s3`] with the checksum algorithm.
+    ///
+    /// ```
+    /// # use awsx::{s3::ChecksumAlgorithm, template::Template};
+    /// let template = Template::from_bytes("Resources: {}\n").unwrap();
+    /// assert_eq!(
+    ///     format!("{}.template", template.checksum_md5hex().unwrap()),
+    ///     template.s3_key(ChecksumAlgorithm::Md5).unwrap(),
+    /// );
+    /// ```
+    ///
+    /// [`upload_to_s3`]: #method.upload_to_s3
+    pub fn s3_key(&self, algorithm: ChecksumAlgorithm) -> Result<String, Error> {
+        Ok(format!("{}.template", self.checksum_hex(algorithm)?))
+    }
+
+    /// Minify the template when submitting it inline, should it otherwise exceed
+    /// [`MAX_TEMPLATE_BODY_SIZE`].
+    ///
+    /// Templates within the limit are submitted as they are, and the checksums are always computed
+    /// on the original contents.
+    ///
+    /// [`MAX_TEMPLATE_BODY_SIZE`]: constant.MAX_TEMPLATE_BODY_SIZE.html
+    pub fn with_minification(mut self, minify: bool) -> Self {
+        self.minify = minify;
+        self
+    }
+
+    /// Return the template contents minified, i.e. without comments and insignificant whitespace.
+    ///
+    /// JSON templates are serialized compactly, YAML templates are normalized, which retains
+    /// short-form intrinsic functions, e.g. `!Ref`.
+    ///
+    /// ```
+    /// # use awsx::template::Template;
+    /// let template = Template::from_bytes(
+    ///     "{\n    \"Resources\": {\n        \"Topic\": {\"Type\": \"AWS::SNS::Topic\"}\n    }\n}\n",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(
+    ///     br#"{"Resources":{"Topic":{"Type":"AWS::SNS::Topic"}}}"#,
+    ///     template.minified().unwrap().as_slice(),
+    /// );
+    ///
+    /// let template = Template::from_bytes(
+    ///     "# The topic\nResources:\n    Topic:\n        Type: AWS::SNS::Topic\n",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(
+    ///     b"Resources:\n  Topic:\n    Type: AWS::SNS::Topic\n",
+    ///     template.minified().unwrap().as_slice(),
+    /// );
+    /// ```
+    pub fn minified(&self) -> Result<Vec<u8>, Error> {
+        let value = self.to_value()?;
+        if self.is_json() {
+            Ok(serde_json::to_vec(&value)?)
+        } else {
+            serde_yaml::to_string(&value)
+                .map(String::into_bytes)
+                .map_err(|error| Error::InvalidTemplate(error.to_string()))
+        }
+    }
+
+    /// Return the template contents to submit inline, i.e. without uploading it to S3.
+    ///
+    /// Templates larger than [`MAX_TEMPLATE_BODY_SIZE`] cannot be submitted inline, which results
+    /// in an [`Error::S3BucketRequired`] rather than a rejection by the AWS API, unless
+    /// [minification] was enabled and the minified template fits.
+    ///
+    /// [`MAX_TEMPLATE_BODY_SIZE`]: constant.MAX_TEMPLATE_BODY_SIZE.html
+    /// [`Error::S3BucketRequired`]: ../error/enum.Error.html#variant.S3BucketRequired
+    /// [minification]: #method.with_minification
+    pub fn template_body(&self) -> Result<String, Error> {
+        let mut contents = self.contents.clone();
+        if self.minify && contents.len() > MAX_TEMPLATE_BODY_SIZE {
+            contents = self.minified()?;
+        }
+        if contents.len() > MAX_TEMPLATE_BODY_SIZE {
+            return Err(Error::S3BucketRequired(format!(
+                "submit the template of {} bytes{}, since templates submitted inline are limited \
+                 to {} bytes",
+                contents.len(),
+                if self.minify { " (minified)" } else { "" },
+                MAX_TEMPLATE_BODY_SIZE,
+            )));
+        }
+        Ok(String::from_utf8(contents).expect("Template is not well formatted UTF8"))
+    }
+
+    /// Check if the template is written in JSON, in contrast to YAML.
+    pub fn is_json(&self) -> bool {
+        self.contents
+            .iter()
+            .find(|byte| !byte.is_ascii_whitespace())
+            .map(|byte| *byte == b'{')
+            .unwrap_or(false)
+    }
+
+    /// Parse the template into a generic value, e.g. to inspect or modify its resources.
+    ///
+    /// Short-form intrinsic functions of YAML templates, e.g. `!Ref`, are retained as tagged
+    /// values.
+    pub fn to_value(&self) -> Result<Value, Error> {
+        serde_yaml::from_slice(&self.contents)
+            .map_err(|error| Error::TemplateDeserializationFailed(error.into()))
+    }
+
+    /// Create a template from a generic value, serializing it as JSON if `as_json` is `true` and
+    /// as YAML otherwise.
+    ///
+    /// **Note:** comments and formatting of a template the value was parsed from are not retained.
+    pub fn from_value(value: &Value, as_json: bool) -> Result<Template, Error> {
+        if as_json {
+            Template::from_bytes(serde_json::to_vec_pretty(value)?)
+        } else {
+            serde_yaml::to_string(value)
+                .map_err(|error| Error::InvalidTemplate(error.to_string()))
+                .and_then(Template::from_bytes)
+        }
+    }
+
+    /// Lint the template for parameters that are declared but never used, as well as for
+    /// references to logical IDs that are not declared.
+    ///
+    /// Parameters count as used if they are referenced within the resources, outputs or
+    /// conditions, e.g. through `Ref` or `Fn::Sub`.
+    ///
+    /// ```
+    /// # use awsx::template::Template;
+    /// let template = Template::from_bytes(
+    ///     r#"
+    /// Parameters:
+    ///   Environment:
+    ///     Type: String
+    ///   Unused:
+    ///     Type: String
+    /// Resources:
+    ///   Topic:
+    ///     Type: AWS::SNS::Topic
+    ///     Properties:
+    ///       TopicName: !Sub "${Environment}-${Stage}-${AWS::Region}"
+    /// "#,
+    /// )
+    /// .unwrap();
+    /// let report = template.lint().unwrap();
+    /// assert_eq!(vec!["Unused"], report.unused_parameters);
+    /// assert_eq!(vec!["Stage"], report.undeclared_references);
+    /// ```
+    pub fn lint(&self) -> Result<TemplateLintReport, Error> {
+        let value = self.to_value()?;
+        let section = |name: &str| {
+            value
+                .get(name)
+                .and_then(Value::as_mapping)
+                .cloned()
+                .unwrap_or_default()
+        };
+        let resources = section("Resources");
+        let conditions = section("Conditions");
+
+        let mut referenced = BTreeSet::new();
+        for name in &["Resources", "Outputs", "Conditions"] {
+            if let Some(section) = value.get(name) {
+                referenced.extend(referenced_logical_ids(section));
+            }
+        }
+
+        let unused_parameters = self
+            .parameter_declarations
+            .keys()
+            .filter(|key| !referenced.contains(*key))
+            .cloned()
+            .collect();
+        let undeclared_references = referenced
+            .into_iter()
+            .filter(|logical_id| {
+                !self.parameter_declarations.contains_key(logical_id)
+                    && !resources.contains_key(logical_id.as_str())
+                    && !conditions.contains_key(logical_id.as_str())
+            })
+            .collect();
+
+        Ok(TemplateLintReport {
+            unused_parameters,
+            undeclared_references,
+        })
+    }
+
+    /// Build the graph of the dependencies between the resources of the template.
+    ///
+    /// A resource depends on another resource if it references it through `Ref`, `Fn::GetAtt`,
+    /// `Fn::Sub` or `DependsOn`. References to parameters and conditions are not part of the
+    /// graph.
+    ///
+    /// ```
+    /// # use awsx::template::Template;
+    /// let template = Template::from_bytes(
+    ///     r#"
+    /// Resources:
+    ///   Queue:
+    ///     Type: AWS::SQS::Queue
+    ///   Topic:
+    ///     Type: AWS::SNS::Topic
+    ///   Subscription:
+    ///     Type: AWS::SNS::Subscription
+    ///     Properties:
+    ///       TopicArn: !Ref Topic
+    ///       Endpoint: !GetAtt Queue.Arn
+    /// "#,
+    /// )
+    /// .unwrap();
+    /// let graph = template.resource_graph().unwrap();
+    /// assert_eq!(3, graph.resources.len());
+    /// assert_eq!(
+    ///     vec!["Subscription"],
+    ///     graph.dependents("Topic").into_iter().collect::<Vec<_>>(),
+    /// );
+    /// ```
+    pub fn resource_graph(&self) -> Result<ResourceGraph, Error> {
+        let value = self.to_value()?;
+        let resources = value
+            .get("Resources")
+            .and_then(Value::as_mapping)
+            .cloned()
+            .unwrap_or_default();
+
+        let mut graph = ResourceGraph::default();
+        for (logical_id, resource) in &resources {
+            let logical_id = match logical_id.as_str() {
+                Some(logical_id) => logical_id.to_owned(),
+                None => continue,
+            };
+            graph.resources.insert(
+                logical_id.clone(),
+                resource
+                    .get("Type")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_owned(),
+            );
+            graph.dependencies.extend(
+                referenced_logical_ids(resource)
+                    .into_iter()
+                    .filter(|dependency| resources.contains_key(dependency.as_str()))
+                    .map(|dependency| ResourceDependency {
+                        resource: logical_id.clone(),
+                        depends_on: dependency,
+                    }),
+            );
+        }
+        Ok(graph)
+    }
+}
+
+/// The resources of a template and the dependencies between them, see
+/// [`Template::resource_graph`].
+///
+/// [`Template::resource_graph`]: struct.Template.html#method.resource_graph
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ResourceGraph {
+    /// Types of all resources, keyed by their logical ID.
+    pub resources: IndexMap<String, String>,
+    /// Dependencies between the resources.
+    pub dependencies: Vec<ResourceDependency>,
+}
+
+/// A resource depending on another resource of the same template.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ResourceDependency {
+    /// Logical ID of the dependent resource.
+    pub resource: String,
+    /// Logical ID of the resource depended on.
+    pub depends_on: String,
+}
+
+impl ResourceGraph {
+    /// Return the logical IDs of all resources that directly or transitively depend on the given
+    /// resource, i.e. which may be affected by a change of it.
+    pub fn dependents(&self, logical_id: &str) -> BTreeSet<String> {
+        let mut dependents = BTreeSet::new();
+        let mut pending = vec![logical_id.to_owned()];
+        while let Some(current) = pending.pop() {
+            for dependency in &self.dependencies {
+                if dependency.depends_on == current
+                    && dependents.insert(dependency.resource.clone())
+                {
+                    pending.push(dependency.resource.clone());
+                }
+            }
+        }
+        dependents.remove(logical_id);
+        dependents
+    }
+
+    /// Restrict the graph to the given resources and the dependencies between them.
+    pub fn restricted_to(mut self, logical_ids: &BTreeSet<String>) -> ResourceGraph {
+        self.resources
+            .retain(|logical_id, _| logical_ids.contains(logical_id));
+        self.dependencies.retain(|dependency| {
+            logical_ids.contains(&dependency.resource)
+                && logical_ids.contains(&dependency.depends_on)
+        });
+        self
+    }
+
+    /// Render the graph in the DOT language of Graphviz, with edges pointing from a resource to
+    /// the resources it depends on.
+    pub fn to_dot(&self) -> String {
+        let escape = |string: &str| string.replace('\\', "\\\\").replace('"', "\\\"");
+        let mut lines = vec!["digraph resources {".to_owned()];
+        for (logical_id, resource_type) in &self.resources {
+            lines.push(format!(
+                "    \"{id}\" [label=\"{id}\\n{resource_type}\"];",
+                id = escape(logical_id),
+                resource_type = escape(resource_type),
+            ));
+        }
+        for dependency in &self.dependencies {
+            lines.push(format!(
+                "    \"{}\" -> \"{}\";",
+                escape(&dependency.resource),
+                escape(&dependency.depends_on),
+            ));
+        }
+        lines.push("}".to_owned());
+        lines.join("\n")
+    }
+}
+
+/// The findings of linting a template, see [`Template::lint`].
+///
+/// [`Template::lint`]: struct.Template.html#method.lint
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct TemplateLintReport {
+    /// Parameters that are declared, but never referenced within the resources, outputs or
+    /// conditions.
+    pub unused_parameters: Vec<String>,
+    /// Logical IDs that are referenced, but declared neither as parameter, resource nor condition.
+    pub undeclared_references: Vec<String>,
+}
+
+impl TemplateLintReport {
+    /// Check if the template passed the lint, i.e. the report does not contain any findings.
+    pub fn is_clean(&self) -> bool {
+        self.unused_parameters.is_empty() && self.undeclared_references.is_empty()
+    }
+}
+
+/// Return the logical IDs of the resources, parameters and conditions referenced within a part of
+/// a template.
+///
+/// References are made through `Ref`, `Fn::GetAtt`, the variables of `Fn::Sub`, `Condition` and
+/// `DependsOn`, in both their long and short form. Pseudo parameters, e.g. `AWS::Region`, are not
+/// included.
+pub fn referenced_logical_ids(value: &Value) -> BTreeSet<String> {
+    let mut logical_ids = BTreeSet::new();
+    collect_references(value, &mut logical_ids);
+    logical_ids.retain(|logical_id| !logical_id.starts_with("AWS::"));
+    logical_ids
+}
+
+fn collect_references(value: &Value, logical_ids: &mut BTreeSet<String>) {
+    match value {
+        Value::Mapping(mapping) => {
+            for (key, value) in mapping {
+                match key.as_str() {
+                    Some("Ref") | Some("Condition") => collect_ref(value, logical_ids),
+                    Some("DependsOn") => collect_depends_on(value, logical_ids),
+                    Some("Fn::GetAtt") => collect_get_att(value, logical_ids),
+                    Some("Fn::Sub") => collect_sub(value, logical_ids),
+                    _ => collect_references(value, logical_ids),
+                }
+            }
+        }
+        Value::Sequence(sequence) => sequence
+            .iter()
+            .for_each(|value| collect_references(value, logical_ids)),
+        Value::Tagged(tagged) => match tagged.tag.to_string().as_str() {
+            "!Ref" | "!Condition" => collect_ref(&tagged.value, logical_ids),
+            "!GetAtt" => collect_get_att(&tagged.value, logical_ids),
+            "!Sub" => collect_sub(&tagged.value, logical_ids),
+            _ => collect_references(&tagged.value, logical_ids),
+        },
+        _ => {}
+    }
+}
+
+fn collect_ref(value: &Value, logical_ids: &mut BTreeSet<String>) {
+    match value.as_str() {
+        Some(logical_id) => {
+            logical_ids.insert(logical_id.to_owned());
+        }
+        None => collect_references(value, logical_ids),
+    }
+}
+
+fn collect_depends_on(value: &Value, logical_ids: &mut BTreeSet<String>) {
+    match value {
+        Value::Sequence(sequence) => sequence
+            .iter()
+            .for_each(|value| collect_depends_on(value, logical_ids)),
+        _ => collect_ref(value, logical_ids),
+    }
+}
 
-        keys == other_keys
+fn collect_get_att(value: &Value, logical_ids: &mut BTreeSet<String>) {
+    match value {
+        Value::String(attribute) => {
+            if let Some((logical_id, _)) = attribute.split_once('.') {
+                logical_ids.insert(logical_id.to_owned());
+            }
+        }
+        Value::Sequence(sequence) => {
+            if let Some(first) = sequence.first() {
+                collect_ref(first, logical_ids);
+            }
+            sequence
+                .iter()
+                .skip(1)
+                .for_each(|value| collect_references(value, logical_ids));
+        }
+        _ => collect_references(value, logical_ids),
+    }
+}
+
+fn collect_sub(value: &Value, logical_ids: &mut BTreeSet<String>) {
+    let (string, variables) = match value {
+        Value::String(string) => (Some(string.as_str()), None),
+        Value::Sequence(sequence) => (
+            sequence.first().and_then(Value::as_str),
+            sequence.get(1).and_then(Value::as_mapping),
+        ),
+        _ => (None, None),
+    };
+    if let Some(string) = string {
+        let mut rest = string;
+        while let Some(start) = rest.find("${") {
+            rest = &rest[start + 2..];
+            let end = match rest.find('}') {
+                Some(end) => end,
+                None => break,
+            };
+            let variable = &rest[..end];
+            rest = &rest[end + 1..];
+            // `${!Literal}` is written out literally instead of being substituted.
+            if variable.starts_with('!') {
+                continue;
+            }
+            let logical_id = variable.split('.').next().unwrap_or(variable).trim();
+            let is_local = variables.is_some_and(|variables| variables.contains_key(logical_id));
+            if !is_local {
+                logical_ids.insert(logical_id.to_owned());
+            }
+        }
+    }
+    if let Some(variables) = variables {
+        variables
+            .values()
+            .for_each(|value| collect_references(value, logical_ids));
+    }
+}
+
+impl FromStr for Template {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Template::from_bytes(s)
     }
 }
 
@@ -195,14 +805,93 @@ impl std::fmt::Debug for Template {
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 struct CloudFormationTemplate {
+    #[serde(default)]
     parameters: HashMap<String, TemplateParameter>,
 }
-#[derive(Debug, Deserialize)]
+/// The declaration of a parameter within a template, including the constraints of its value.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 #[serde(rename_all = "PascalCase")]
-struct TemplateParameter {
+pub struct TemplateParameter {
+    /// Type of the parameter, e.g. `String`, `Number` or `CommaDelimitedList`.
     #[serde(rename = "Type")]
-    _type: String,
-    default: Option<String>,
+    pub parameter_type: String,
+    /// Description of the parameter.
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Default value of the parameter.
+    #[serde(default, deserialize_with = "deserialize_optional_value")]
+    pub default: Option<String>,
+    /// Whether the parameter value is sensitive and should be masked.
+    #[serde(default, deserialize_with = "deserialize_bool")]
+    pub no_echo: bool,
+    /// List of values allowed for the parameter.
+    #[serde(default, deserialize_with = "deserialize_values")]
+    pub allowed_values: Vec<String>,
+    /// Regular expression the parameter value has to match.
+    #[serde(default)]
+    pub allowed_pattern: Option<String>,
+    /// Minimum number of characters of a `String` parameter.
+    #[serde(default, deserialize_with = "deserialize_optional_number")]
+    pub min_length: Option<f64>,
+    /// Maximum number of characters of a `String` parameter.
+    #[serde(default, deserialize_with = "deserialize_optional_number")]
+    pub max_length: Option<f64>,
+    /// Smallest value allowed for a `Number` parameter.
+    #[serde(default, deserialize_with = "deserialize_optional_number")]
+    pub min_value: Option<f64>,
+    /// Largest value allowed for a `Number` parameter.
+    #[serde(default, deserialize_with = "deserialize_optional_number")]
+    pub max_value: Option<f64>,
+}
+
+impl TemplateParameter {
+    /// Check if the parameter is of a list type, e.g. `CommaDelimitedList` or `List<Number>`.
+    pub fn is_list(&self) -> bool {
+        self.parameter_type == "CommaDelimitedList" || self.parameter_type.starts_with("List<")
+    }
+
+    /// Check if the values of the parameter are numeric, i.e. `Number` or `List<Number>`.
+    pub fn is_numeric(&self) -> bool {
+        self.parameter_type == "Number" || self.parameter_type == "List<Number>"
+    }
+}
+
+// Templates commonly specify values of parameter properties as numbers or booleans instead of
+// strings, e.g. `Default: 5` or `NoEcho: true`, so the deserializers accept all of them.
+
+fn deserialize_optional_value<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: de::Deserializer<'de>,
+{
+    Ok(Option::<ParameterValue>::deserialize(deserializer)?.map(|value| value.to_string()))
+}
+
+fn deserialize_bool<'de, D>(deserializer: D) -> Result<bool, D::Error>
+where
+    D: de::Deserializer<'de>,
+{
+    Ok(deserialize_optional_value(deserializer)?
+        .map(|value| value.eq_ignore_ascii_case("true"))
+        .unwrap_or(false))
+}
+
+fn deserialize_values<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: de::Deserializer<'de>,
+{
+    Ok(Vec::<ParameterValue>::deserialize(deserializer)?
+        .into_iter()
+        .map(|value| value.to_string())
+        .collect())
+}
+
+fn deserialize_optional_number<'de, D>(deserializer: D) -> Result<Option<f64>, D::Error>
+where
+    D: de::Deserializer<'de>,
+{
+    deserialize_optional_value(deserializer)?
+        .map(|value| value.parse().map_err(de::Error::custom))
+        .transpose()
 }
 
 fn load_file(filename: &str) -> Result<Vec<u8>, Error> {
@@ -213,3 +902,68 @@ fn load_file(filename: &str) -> Result<Vec<u8>, Error> {
     reader.read_to_end(&mut contents)?;
     Ok(contents)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn template_body_is_limited_in_size() {
+        let resources = "Resources:\n  Topic:\n    Type: AWS::SNS::Topic\n";
+        let template = Template::from_bytes(resources).unwrap();
+        assert_eq!(resources, template.template_body().unwrap());
+
+        let padding = format!("# {}\n", "-".repeat(MAX_TEMPLATE_BODY_SIZE));
+        let template = Template::from_bytes(format!("{}{}", resources, padding)).unwrap();
+        assert!(matches!(
+            template.template_body(),
+            Err(Error::S3BucketRequired(_))
+        ));
+
+        let template = template.with_minification(true);
+        assert_eq!(
+            "Resources:\n  Topic:\n    Type: AWS::SNS::Topic\n",
+            template.template_body().unwrap()
+        );
+    }
+
+    #[test]
+    fn referenced_logical_ids_covers_long_and_short_form() {
+        let template = Template::from_bytes(
+            r#"
+Resources:
+  Bucket:
+    Type: AWS::S3::Bucket
+  Policy:
+    Type: AWS::S3::BucketPolicy
+    DependsOn: [Queue]
+    Condition: IsProduction
+    Properties:
+      Bucket: !Ref Bucket
+      Arn: {"Fn::GetAtt": ["Role", "Arn"]}
+      Domain: !GetAtt Distribution.DomainName
+      Name: !Sub "${Prefix}-${AWS::Region}-${!Literal}-${Local}"
+      Other: !Sub ["${Local}", {Local: !Ref Topic}]
+"#,
+        )
+        .unwrap();
+        let value = template.to_value().unwrap();
+
+        assert_eq!(
+            vec![
+                "Bucket",
+                "Distribution",
+                "IsProduction",
+                "Local",
+                "Prefix",
+                "Queue",
+                "Role",
+                "Topic",
+            ],
+            referenced_logical_ids(&value["Resources"]["Policy"])
+                .into_iter()
+                .collect::<Vec<_>>(),
+        );
+        assert!(referenced_logical_ids(&value["Resources"]["Bucket"]).is_empty());
+    }
+}
@@ -0,0 +1,185 @@
+// Copyright 2025 TAKKT Industrial & Packaging GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! A minimal representation of XML documents.
+//!
+//! Some AWS APIs awsx uses are called through signed requests dispatched by Rusoto rather than a
+//! Rusoto client, e.g. since there is no Rusoto crate for the service available to awsx. Their XML
+//! responses are parsed into a generic tree of elements, and request bodies are serialized from it.
+
+use xml::reader::{EventReader, XmlEvent};
+
+use crate::error::Error;
+
+/// An element of an XML document, with its text and child elements.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct XmlElement {
+    /// Name of the element, without its namespace.
+    pub name: String,
+    /// Text content of the element.
+    pub text: String,
+    /// Child elements, in document order.
+    pub children: Vec<XmlElement>,
+}
+
+impl XmlElement {
+    /// Create an element with the given name and text, without any children.
+    pub fn new(name: &str, text: &str) -> Self {
+        XmlElement {
+            name: name.to_owned(),
+            text: text.to_owned(),
+            children: Vec::new(),
+        }
+    }
+
+    /// Parse an XML document, returning its root element.
+    ///
+    /// The whole document is parsed, so that anything after the root element is rejected as well.
+    pub fn parse(document: &[u8]) -> Result<Self, Error> {
+        let mut stack: Vec<XmlElement> = Vec::new();
+        let mut root = None;
+        for event in EventReader::new(document) {
+            match event.map_err(|error| Error::AwsError(failure::err_msg(error.to_string())))? {
+                XmlEvent::StartElement { name, .. } => {
+                    if stack.is_empty() && root.is_some() {
+                        return Err(Error::AwsError(failure::err_msg(
+                            "XML document with multiple root elements",
+                        )));
+                    }
+                    stack.push(XmlElement {
+                        name: name.local_name,
+                        ..Default::default()
+                    })
+                }
+                XmlEvent::Characters(text) | XmlEvent::CData(text) => {
+                    if let Some(element) = stack.last_mut() {
+                        element.text.push_str(&text);
+                    }
+                }
+                XmlEvent::EndElement { .. } => {
+                    let element = stack.pop().expect("end element without start element");
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(element),
+                        None => root = Some(element),
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        root.ok_or_else(|| Error::AwsError(failure::err_msg("XML document without root element")))
+    }
+
+    /// Return the first child element with the given name.
+    pub fn child(&self, name: &str) -> Option<&XmlElement> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    /// Return the text of the first child element with the given name.
+    pub fn child_text(&self, name: &str) -> Option<&str> {
+        self.child(name).map(|child| child.text.as_str())
+    }
+
+    /// Serialize the element and its children as XML.
+    pub fn to_xml(&self) -> String {
+        let mut xml = format!("<{}>", self.name);
+        if self.children.is_empty() {
+            xml.push_str(&escape(&self.text));
+        }
+        for child in &self.children {
+            xml.push_str(&child.to_xml());
+        }
+        xml.push_str(&format!("</{}>", self.name));
+        xml
+    }
+}
+
+/// Escape the text for use as the content of an XML element.
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_and_repeated_children_are_parsed_in_order() {
+        let root = XmlElement::parse(
+            b"<?xml version=\"1.0\"?>\n\
+              <ListResponse>\n  <Items>\n    <Item><Id>1</Id></Item>\n    <Item><Id>2</Id></Item>\n  </Items>\n</ListResponse>",
+        )
+        .unwrap();
+
+        assert_eq!("ListResponse", root.name);
+        let items = root.child("Items").unwrap();
+        assert_eq!(
+            vec!["1", "2"],
+            items
+                .children
+                .iter()
+                .map(|item| item.child_text("Id").unwrap())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(None, root.child("Missing"));
+    }
+
+    #[test]
+    fn namespaces_are_stripped_from_names() {
+        let root = XmlElement::parse(
+            b"<Response xmlns=\"https://sns.amazonaws.com/doc/2010-03-31/\">\
+              <ns:Result xmlns:ns=\"urn:example\">ok</ns:Result></Response>",
+        )
+        .unwrap();
+
+        assert_eq!("Response", root.name);
+        assert_eq!(Some("ok"), root.child_text("Result"));
+    }
+
+    #[test]
+    fn entities_and_cdata_are_resolved() {
+        let root = XmlElement::parse(
+            b"<Message>a &amp; b &lt;c&gt; &#x41;&#66;<![CDATA[ <raw> ]]></Message>",
+        )
+        .unwrap();
+
+        assert_eq!("a & b <c> AB <raw> ", root.text);
+        assert_eq!(
+            "<Message>a &amp; b &lt;c&gt; AB &lt;raw&gt; </Message>",
+            root.to_xml()
+        );
+    }
+
+    #[test]
+    fn malformed_documents_are_rejected() {
+        for document in &[
+            "",
+            "just text",
+            "<Unclosed>",
+            "<A><B></A></B>",
+            "<A>&unknown;</A>",
+            "<A></A><B></B>",
+        ] {
+            assert!(
+                XmlElement::parse(document.as_bytes()).is_err(),
+                "{:?} was parsed",
+                document
+            );
+        }
+    }
+}